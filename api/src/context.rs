@@ -41,7 +41,7 @@ use aptos_types::{
         state_key_prefix::StateKeyPrefix,
         state_value::StateValue,
     },
-    transaction::{SignedTransaction, TransactionWithProof, Version},
+    transaction::{ExecutionFingerprint, SignedTransaction, TransactionWithProof, Version},
 };
 use aptos_vm::{
     data_cache::{AsMoveResolver, StorageAdapter},
@@ -619,6 +619,17 @@ impl Context {
         Ok(txns)
     }
 
+    /// The execution fingerprints of the transactions from `start_version`, recorded by this
+    /// node when its execution diverged.
+    pub fn get_execution_fingerprints(
+        &self,
+        start_version: u64,
+        limit: u16,
+    ) -> Result<Vec<(u64, ExecutionFingerprint)>> {
+        self.db
+            .get_execution_fingerprints(start_version, limit as u64)
+    }
+
     pub fn get_transactions(
         &self,
         start_version: u64,
@@ -9,14 +9,15 @@ use aptos_config::config::NodeConfig;
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
-    PrivateKey, SigningKey, Uniform,
+    HashValue, PrivateKey, SigningKey, Uniform,
 };
 use aptos_sdk::types::LocalAccount;
+use aptos_storage_interface::DbWriter;
 use aptos_types::{
     account_address::AccountAddress,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
-        EntryFunction, Script, SignedTransaction,
+        EntryFunction, ExecutionFingerprint, ExecutionStatus, Script, SignedTransaction,
     },
     utility_coin::APTOS_COIN_TYPE,
 };
@@ -385,6 +386,60 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_fingerprints() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    // The transactions executed as committed have no recorded fingerprints.
+    let fingerprints = context
+        .get("/transactions/fingerprints?start=0&limit=10")
+        .await;
+    assert!(fingerprints.as_array().unwrap().is_empty());
+
+    // Only the fingerprints recorded for the diverged executions are returned, even after the
+    // ledger version.
+    let ledger_version = context.get_latest_ledger_info().version();
+    let execution_fingerprints = vec![
+        ExecutionFingerprint::new(
+            HashValue::random(),
+            HashValue::random(),
+            1,
+            ExecutionStatus::Success,
+        ),
+        ExecutionFingerprint::new(
+            HashValue::random(),
+            HashValue::random(),
+            2,
+            ExecutionStatus::Success,
+        ),
+    ];
+    context
+        .db
+        .save_execution_fingerprints(ledger_version, &execution_fingerprints)
+        .unwrap();
+
+    let fingerprints = context
+        .get(&format!(
+            "/transactions/fingerprints?start={}&limit=10",
+            ledger_version - 2
+        ))
+        .await;
+    let fingerprints = fingerprints.as_array().unwrap();
+    assert_eq!(2, fingerprints.len());
+    for (version, (fingerprint, execution_fingerprint)) in
+        (ledger_version..).zip(fingerprints.iter().zip(execution_fingerprints))
+    {
+        assert_eq!(fingerprint["version"], version.to_string());
+        assert_eq!(
+            fingerprint["fingerprint"],
+            execution_fingerprint.fingerprint().to_hex_literal()
+        );
+    }
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -23,9 +23,9 @@ use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
     HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
-    MAX_RECURSIVE_TYPES_ALLOWED, U64,
+    Transaction, TransactionData, TransactionFingerprint, TransactionOnChainData,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult, UserTransaction,
+    VerifyInput, VerifyInputWithRecursion, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
 use aptos_types::{
@@ -228,6 +228,44 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get transaction fingerprints
+    ///
+    /// Retrieve the fingerprints the node recorded when its own execution of transactions didn't
+    /// match the committed ones, to compare them with a replay of the transactions. The page
+    /// size and start ledger version can be provided to get a specific sequence of fingerprints.
+    ///
+    /// Only the recorded fingerprints are returned, so the versions the node executed as
+    /// committed are skipped, and a page can be short or empty. The recorded fingerprints can be
+    /// after the latest ledger version.
+    #[oai(
+        path = "/transactions/fingerprints",
+        method = "get",
+        operation_id = "get_transaction_fingerprints",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transaction_fingerprints(
+        &self,
+        accept_type: AcceptType,
+        /// Ledger version to start list of fingerprints
+        ///
+        /// If not provided, defaults to showing the latest fingerprints
+        start: Query<Option<U64>>,
+        /// Max number of transactions to retrieve the fingerprints of.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<TransactionFingerprint>> {
+        fail_point_poem("endpoint_get_transaction_fingerprints")?;
+        self.context
+            .check_api_output_enabled("Get transaction fingerprints", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+        self.list_fingerprints(&accept_type, page)
+    }
+
     /// Get account transactions
     ///
     /// Retrieves on-chain committed transactions from an account. If the start
@@ -661,6 +699,48 @@ impl TransactionsApi {
         }
     }
 
+    fn list_fingerprints(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+    ) -> BasicResultWith404<Vec<TransactionFingerprint>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_version = latest_ledger_info.version();
+
+        let limit = page.limit(&latest_ledger_info)?;
+        // The fingerprints recorded for diverged executions can be after the ledger version.
+        let start_version = match page.start_option() {
+            Some(start_version) => start_version,
+            None => page.compute_start(limit, ledger_version, &latest_ledger_info)?,
+        };
+        let data = self
+            .context
+            .get_execution_fingerprints(start_version, limit)
+            .context("Failed to read execution fingerprints from storage")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                data.iter()
+                    .map(|(version, execution_fingerprint)| {
+                        TransactionFingerprint::from((*version, execution_fingerprint))
+                    })
+                    .collect(),
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     async fn get_transaction_by_hash_inner(
         &self,
         accept_type: &AcceptType,
@@ -18,7 +18,7 @@ use aptos_types::{
     contract_event::{ContractEvent, EventWithVersion},
     transaction::{
         authenticator::{AccountAuthenticator, TransactionAuthenticator, MAX_NUM_OF_SIGS},
-        Script, SignedTransaction, TransactionOutput, TransactionWithProof,
+        ExecutionFingerprint, Script, SignedTransaction, TransactionOutput, TransactionWithProof,
     },
 };
 use poem_openapi::{Object, Union};
@@ -334,6 +334,33 @@ pub struct TransactionInfo {
     pub epoch: Option<U64>,
 }
 
+/// The fingerprint of the execution of a transaction
+///
+/// It is the hash of its write set, events, gas used and VM status.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionFingerprint {
+    pub version: U64,
+    pub fingerprint: HashValue,
+    pub write_set_hash: HashValue,
+    pub event_root_hash: HashValue,
+    pub gas_used: U64,
+    /// Whether the transaction was successful
+    pub success: bool,
+}
+
+impl From<(u64, &ExecutionFingerprint)> for TransactionFingerprint {
+    fn from((version, execution_fingerprint): (u64, &ExecutionFingerprint)) -> Self {
+        Self {
+            version: version.into(),
+            fingerprint: execution_fingerprint.fingerprint().into(),
+            write_set_hash: execution_fingerprint.write_set_hash().into(),
+            event_root_hash: execution_fingerprint.event_root_hash().into(),
+            gas_used: execution_fingerprint.gas_used().into(),
+            success: execution_fingerprint.status().is_success(),
+        }
+    }
+}
+
 /// A transaction waiting in mempool
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct PendingTransaction {
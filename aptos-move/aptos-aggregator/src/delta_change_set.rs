@@ -13,6 +13,7 @@ use aptos_types::{
     write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use move_binary_format::errors::{Location, PartialVMError, PartialVMResult};
+use serde::{Deserialize, Serialize};
 use std::collections::{btree_map::Entry, BTreeMap};
 
 /// When `Addition` operation overflows the `limit`.
@@ -22,7 +23,7 @@ const EADD_OVERFLOW: u64 = 0x02_0001;
 const ESUB_UNDERFLOW: u64 = 0x02_0002;
 
 /// Represents an update from aggregator's operation.
-#[derive(Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeltaOp {
     /// Maximum positive delta seen during execution.
     max_positive: u128,
@@ -36,7 +37,7 @@ pub struct DeltaOp {
 }
 
 /// Different delta functions.
-#[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DeltaUpdate {
     Plus(u128),
     Minus(u128),
@@ -289,7 +290,7 @@ pub fn delta_add(v: u128, limit: u128) -> DeltaOp {
 }
 
 /// `DeltaChangeSet` contains all access paths that one transaction wants to update with deltas.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct DeltaChangeSet {
     delta_change_set: BTreeMap<StateKey, DeltaOp>,
 }
@@ -0,0 +1,85 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::transaction::{ExecutionFingerprint, Version};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// The first transaction whose execution fingerprints differ, and the parts they differ on.
+#[derive(Clone, Debug)]
+pub struct FingerprintDivergence {
+    pub version: Version,
+    pub replayed: ExecutionFingerprint,
+    pub recorded: ExecutionFingerprint,
+}
+
+impl FingerprintDivergence {
+    pub fn diff(&self) -> Vec<&'static str> {
+        self.replayed.diff(&self.recorded)
+    }
+}
+
+/// The comparison of the execution fingerprints a node recorded when its execution diverged, with
+/// those of a replay of the same transactions.
+#[derive(Clone, Debug, Default)]
+pub struct FingerprintComparison {
+    pub num_compared: usize,
+    /// The recorded versions the replay has no fingerprint for, e.g. after its ledger version.
+    pub missing_versions: Vec<Version>,
+    pub first_divergence: Option<FingerprintDivergence>,
+}
+
+impl FingerprintComparison {
+    /// Compares the `recorded` fingerprints with the `replayed` ones of the same versions, the
+    /// other replayed versions are ignored.
+    pub fn compare(
+        recorded: Vec<(Version, ExecutionFingerprint)>,
+        replayed: Vec<(Version, ExecutionFingerprint)>,
+    ) -> Self {
+        let mut replayed: BTreeMap<_, _> = replayed.into_iter().collect();
+        let mut comparison = Self::default();
+        for (version, recorded) in recorded {
+            match replayed.remove(&version) {
+                Some(replayed) => {
+                    comparison.num_compared += 1;
+                    if comparison.first_divergence.is_none() && recorded != replayed {
+                        comparison.first_divergence = Some(FingerprintDivergence {
+                            version,
+                            replayed,
+                            recorded,
+                        });
+                    }
+                },
+                None => comparison.missing_versions.push(version),
+            }
+        }
+        comparison
+    }
+}
+
+impl Display for FingerprintComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Compared fingerprints: {}", self.num_compared)?;
+        writeln!(
+            f,
+            "Recorded versions missing from the replay: {}",
+            self.missing_versions.len()
+        )?;
+        match &self.first_divergence {
+            Some(divergence) => {
+                writeln!(
+                    f,
+                    "First divergence at version {} ({}):",
+                    divergence.version,
+                    divergence.diff().join(", ")
+                )?;
+                writeln!(f, "    replayed: {:?}", divergence.replayed)?;
+                writeln!(f, "    recorded: {:?}", divergence.recorded)?;
+            },
+            None => writeln!(f, "No divergence")?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_state_view::{StateViewId, TStateView};
+use aptos_types::{
+    on_chain_config::{GasScheduleV2, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{ExecutionStatus, TransactionPayload, TransactionStatus, Version},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// A state view serving the given gas schedule in place of the on-chain one, to execute
+/// transactions under a proposed gas schedule.
+pub struct GasScheduleOverrideStateView<'a, S> {
+    base_view: &'a S,
+    gas_schedule_key: StateKey,
+    gas_schedule_bytes: Vec<u8>,
+}
+
+impl<'a, S: TStateView<Key = StateKey>> GasScheduleOverrideStateView<'a, S> {
+    pub fn new(base_view: &'a S, gas_schedule: &GasScheduleV2) -> Result<Self> {
+        Ok(Self {
+            base_view,
+            gas_schedule_key: StateKey::access_path(GasScheduleV2::access_path()?),
+            gas_schedule_bytes: bcs::to_bytes(gas_schedule)?,
+        })
+    }
+}
+
+impl<'a, S: TStateView<Key = StateKey>> TStateView for GasScheduleOverrideStateView<'a, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if state_key == &self.gas_schedule_key {
+            return Ok(Some(StateValue::new_legacy(
+                self.gas_schedule_bytes.clone(),
+            )));
+        }
+        self.base_view.get_state_value(state_key)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base_view.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base_view.get_usage()
+    }
+}
+
+/// The gas used by a transaction under the current and the proposed gas schedules.
+#[derive(Clone, Debug)]
+pub struct TransactionGasDelta {
+    pub version: Version,
+    pub entry_function: String,
+    pub current_gas_used: u64,
+    pub current_status: TransactionStatus,
+    pub proposed_gas_used: u64,
+    pub proposed_status: TransactionStatus,
+}
+
+impl TransactionGasDelta {
+    pub fn gas_delta(&self) -> i128 {
+        self.proposed_gas_used as i128 - self.current_gas_used as i128
+    }
+
+    /// Whether the transaction runs out of gas under the proposed gas schedule only.
+    pub fn newly_out_of_gas(&self) -> bool {
+        let out_of_gas = TransactionStatus::Keep(ExecutionStatus::OutOfGas);
+        self.proposed_status == out_of_gas && self.current_status != out_of_gas
+    }
+}
+
+/// The gas used by the transactions calling an entry function, under the current and the
+/// proposed gas schedules.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EntryFunctionGasDelta {
+    pub num_txns: u64,
+    pub current_gas_used: u64,
+    pub proposed_gas_used: u64,
+}
+
+impl EntryFunctionGasDelta {
+    /// The relative change of the gas used, in percent.
+    pub fn change_percentage(&self) -> f64 {
+        if self.current_gas_used == 0 {
+            return 0.0;
+        }
+        (self.proposed_gas_used as f64 - self.current_gas_used as f64) * 100.0
+            / self.current_gas_used as f64
+    }
+}
+
+/// The impact of a proposed gas schedule on the gas used by a sample of historical transactions.
+#[derive(Clone, Debug, Default)]
+pub struct GasScheduleImpactReport {
+    pub txns: Vec<TransactionGasDelta>,
+}
+
+pub(crate) fn entry_function_name(payload: &TransactionPayload) -> String {
+    match payload {
+        TransactionPayload::EntryFunction(entry_func) => format!(
+            "0x{}::{}::{}",
+            entry_func.module().address().short_str_lossless(),
+            entry_func.module().name(),
+            entry_func.function()
+        ),
+        TransactionPayload::Script(_) => "<script>".to_string(),
+        TransactionPayload::ModuleBundle(_) => "<module bundle>".to_string(),
+        TransactionPayload::Multisig(_) => "<multisig>".to_string(),
+    }
+}
+
+impl GasScheduleImpactReport {
+    pub fn per_entry_function(&self) -> BTreeMap<String, EntryFunctionGasDelta> {
+        let mut deltas: BTreeMap<String, EntryFunctionGasDelta> = BTreeMap::new();
+        for txn in &self.txns {
+            let delta = deltas.entry(txn.entry_function.clone()).or_default();
+            delta.num_txns += 1;
+            delta.current_gas_used += txn.current_gas_used;
+            delta.proposed_gas_used += txn.proposed_gas_used;
+        }
+        deltas
+    }
+
+    pub fn newly_out_of_gas(&self) -> impl Iterator<Item = &TransactionGasDelta> {
+        self.txns.iter().filter(|txn| txn.newly_out_of_gas())
+    }
+}
+
+impl Display for GasScheduleImpactReport {
+    /// Renders the gas deltas of the transactions and of the entry functions, most changed first,
+    /// and the transactions that would newly run out of gas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transactions (current gas, proposed gas, delta):")?;
+        let mut txns: Vec<_> = self.txns.iter().collect();
+        txns.sort_by_key(|txn| std::cmp::Reverse(txn.gas_delta().abs()));
+        for txn in txns {
+            writeln!(
+                f,
+                "    {} {}: {}, {}, {:+}",
+                txn.version,
+                txn.entry_function,
+                txn.current_gas_used,
+                txn.proposed_gas_used,
+                txn.gas_delta()
+            )?;
+        }
+
+        writeln!(
+            f,
+            "Entry functions (transactions, current gas, proposed gas, change):"
+        )?;
+        let mut entry_functions: Vec<_> = self.per_entry_function().into_iter().collect();
+        entry_functions.sort_by(|(_, delta1), (_, delta2)| {
+            delta2
+                .change_percentage()
+                .abs()
+                .total_cmp(&delta1.change_percentage().abs())
+        });
+        for (name, delta) in entry_functions {
+            writeln!(
+                f,
+                "    {}: {}, {}, {}, {:+.2}%",
+                name,
+                delta.num_txns,
+                delta.current_gas_used,
+                delta.proposed_gas_used,
+                delta.change_percentage()
+            )?;
+        }
+
+        writeln!(f, "Newly out of gas:")?;
+        for txn in self.newly_out_of_gas() {
+            writeln!(f, "    {} {}", txn.version, txn.entry_function)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_language_e2e_tests::{account::AccountData, executor::FakeExecutor};
+    use aptos_types::transaction::{SignedTransaction, Transaction, TransactionOutput};
+    use aptos_vm::{AptosVM, VMExecutor};
+
+    fn execute(
+        txn: &SignedTransaction,
+        state_view: &(impl TStateView<Key = StateKey> + Sync),
+    ) -> TransactionOutput {
+        AptosVM::execute_block(
+            vec![Transaction::UserTransaction(txn.clone())],
+            state_view,
+            None,
+        )
+        .unwrap()
+        .pop()
+        .unwrap()
+    }
+
+    fn transfer(
+        sender: &AccountData,
+        receiver: &AccountData,
+        max_gas_amount: u64,
+    ) -> SignedTransaction {
+        sender
+            .account()
+            .transaction()
+            .payload(aptos_stdlib::aptos_coin_transfer(
+                *receiver.address(),
+                1_000,
+            ))
+            .sequence_number(0)
+            .max_gas_amount(max_gas_amount)
+            .gas_unit_price(100)
+            .sign()
+    }
+
+    fn gas_delta(
+        txn: &SignedTransaction,
+        state_view: &(impl TStateView<Key = StateKey> + Sync),
+        proposed_gas_schedule: &GasScheduleV2,
+    ) -> TransactionGasDelta {
+        let current_output = execute(txn, state_view);
+        let proposed_state_view =
+            GasScheduleOverrideStateView::new(state_view, proposed_gas_schedule).unwrap();
+        let proposed_output = execute(txn, &proposed_state_view);
+        TransactionGasDelta {
+            version: 0,
+            entry_function: entry_function_name(txn.payload()),
+            current_gas_used: current_output.gas_used(),
+            current_status: current_output.status().clone(),
+            proposed_gas_used: proposed_output.gas_used(),
+            proposed_status: proposed_output.status().clone(),
+        }
+    }
+
+    #[test]
+    fn test_inflated_gas_schedule() {
+        let mut executor = FakeExecutor::from_head_genesis();
+        let sender = executor.create_raw_account_data(1_000_000_000, 0);
+        let receiver = executor.create_raw_account_data(1_000_000_000, 0);
+        executor.add_account_data(&sender);
+        executor.add_account_data(&receiver);
+        let state_view = executor.get_state_view();
+
+        // Propose the on-chain gas schedule with every instruction 100 times more expensive
+        let gas_schedule_key = StateKey::access_path(GasScheduleV2::access_path().unwrap());
+        let mut proposed_gas_schedule: GasScheduleV2 = bcs::from_bytes(
+            state_view
+                .get_state_value(&gas_schedule_key)
+                .unwrap()
+                .unwrap()
+                .bytes(),
+        )
+        .unwrap();
+        for (name, value) in &mut proposed_gas_schedule.entries {
+            if name.starts_with("instr.") {
+                *value *= 100;
+            }
+        }
+
+        // The transfer costs more under the proposed gas schedule
+        let success = TransactionStatus::Keep(ExecutionStatus::Success);
+        let txn_delta = gas_delta(
+            &transfer(&sender, &receiver, 100_000),
+            state_view,
+            &proposed_gas_schedule,
+        );
+        assert_eq!(txn_delta.current_status, success);
+        assert_eq!(txn_delta.proposed_status, success);
+        assert!(txn_delta.gas_delta() > 0);
+        assert!(!txn_delta.newly_out_of_gas());
+
+        // The same transfer with just enough gas under the current gas schedule runs out of gas
+        // under the proposed one
+        let max_gas_amount = txn_delta.current_gas_used;
+        let oog_delta = gas_delta(
+            &transfer(&sender, &receiver, max_gas_amount),
+            state_view,
+            &proposed_gas_schedule,
+        );
+        assert_eq!(oog_delta.current_status, success);
+        assert!(oog_delta.newly_out_of_gas());
+
+        let report = GasScheduleImpactReport {
+            txns: vec![txn_delta.clone(), oog_delta.clone()],
+        };
+        let newly_out_of_gas: Vec<_> = report.newly_out_of_gas().collect();
+        assert_eq!(newly_out_of_gas.len(), 1);
+        assert_eq!(newly_out_of_gas[0].current_gas_used, max_gas_amount);
+
+        // Both transfers are aggregated under the entry function
+        let per_entry_function = report.per_entry_function();
+        assert_eq!(per_entry_function.len(), 1);
+        let transfer_delta = per_entry_function["0x1::coin::transfer"];
+        assert_eq!(transfer_delta.num_txns, 2);
+        assert_eq!(
+            transfer_delta.current_gas_used,
+            txn_delta.current_gas_used + oog_delta.current_gas_used
+        );
+        assert_eq!(
+            transfer_delta.proposed_gas_used,
+            txn_delta.proposed_gas_used + oog_delta.proposed_gas_used
+        );
+        assert!(transfer_delta.change_percentage() > 0.0);
+    }
+}
@@ -1,45 +1,62 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod fingerprints;
+mod gas_schedule_impact;
+
 use anyhow::{format_err, Result};
+use aptos_block_executor::conflict_report::BlockConflictReport;
 use aptos_gas::{
-    AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters, StandardGasMeter,
-    LATEST_GAS_FEATURE_VERSION,
+    AbstractValueSizeGasParameters, AptosGasParameters, ChangeSetConfigs, FromOnChainGasSchedule,
+    NativeGasParameters, StandardGasMeter, LATEST_GAS_FEATURE_VERSION,
 };
-use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_gas_profiling::{AggregatedGasLog, GasProfiler, TransactionGasLog};
 use aptos_resource_viewer::{AnnotatedAccountStateBlob, AptosValueAnnotator};
 use aptos_rest_client::Client;
 use aptos_state_view::TStateView;
 use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
-    on_chain_config::{Features, OnChainConfig, TimedFeatures},
+    on_chain_config::{Features, GasScheduleV2, OnChainConfig, TimedFeatures},
+    state_store::state_key::StateKey,
     transaction::{
-        SignedTransaction, Transaction, TransactionInfo, TransactionOutput, TransactionPayload,
-        Version,
+        ExecutionFingerprint, SignedTransaction, Transaction, TransactionInfo, TransactionOutput,
+        TransactionPayload, Version,
     },
     vm_status::VMStatus,
 };
 use aptos_validator_interface::{
-    AptosValidatorInterface, DBDebuggerInterface, DebuggerStateView, RestDebuggerInterface,
+    AptosValidatorInterface, CacheStats, CachedDebuggerInterface, DBDebuggerInterface,
+    DebuggerStateView, RestDebuggerInterface,
 };
 use aptos_vm::{
+    aptos_vm::RAYON_EXEC_POOL,
+    block_executor::BlockAptosVM,
     data_cache::StorageAdapter,
     move_vm_ext::{MoveVmExt, SessionExt, SessionId},
     AptosVM, VMExecutor,
 };
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::{change_set::VMChangeSet, output::VMOutput};
+pub use fingerprints::{FingerprintComparison, FingerprintDivergence};
+pub use gas_schedule_impact::{
+    EntryFunctionGasDelta, GasScheduleImpactReport, GasScheduleOverrideStateView,
+    TransactionGasDelta,
+};
 use move_binary_format::errors::VMResult;
 use std::{path::Path, sync::Arc};
 
 pub struct AptosDebugger {
     debugger: Arc<dyn AptosValidatorInterface + Send>,
+    cache: Option<Arc<CachedDebuggerInterface>>,
 }
 
 impl AptosDebugger {
     pub fn new(debugger: Arc<dyn AptosValidatorInterface + Send>) -> Self {
-        Self { debugger }
+        Self {
+            debugger,
+            cache: None,
+        }
     }
 
     pub fn rest_client(rest_client: Client) -> Result<Self> {
@@ -52,6 +69,32 @@ impl AptosDebugger {
         )?)))
     }
 
+    /// Keeps everything the debugger reads from `target` (e.g. the URL of a REST endpoint) in a
+    /// local DB at `cache_path`, and reads it from there afterwards. In offline mode, reading
+    /// anything that isn't cached yet fails.
+    pub fn with_cache<P: AsRef<Path>>(
+        self,
+        cache_path: P,
+        target: &str,
+        offline: bool,
+    ) -> Result<Self> {
+        let cache = Arc::new(CachedDebuggerInterface::open(
+            self.debugger,
+            cache_path,
+            target,
+            offline,
+        )?);
+        Ok(Self {
+            debugger: cache.clone(),
+            cache: Some(cache),
+        })
+    }
+
+    /// The hits and misses of the cache so far, if the debugger has one.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
     pub fn execute_transactions_at_version(
         &self,
         version: Version,
@@ -103,6 +146,104 @@ impl AptosDebugger {
         Ok((status, output, gas_profiler.finish()))
     }
 
+    /// Profiles the gas of the user transactions in the given range of versions, and aggregates
+    /// the gas logs of those calling a module published at one of the given addresses (or of all
+    /// of them if no address is given).
+    pub async fn profile_past_transactions(
+        &self,
+        begin: Version,
+        limit: u64,
+        module_addresses: &[AccountAddress],
+    ) -> Result<AggregatedGasLog> {
+        let (txns, _) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let mut aggregated_gas_log = AggregatedGasLog::new();
+        for (version, txn) in (begin..).zip(txns) {
+            let txn = match txn {
+                Transaction::UserTransaction(txn) => txn,
+                _ => continue,
+            };
+            // The gas profiler doesn't support these payloads
+            if matches!(
+                txn.payload(),
+                TransactionPayload::ModuleBundle(_) | TransactionPayload::Multisig(_)
+            ) {
+                continue;
+            }
+
+            match self.execute_transaction_at_version_with_gas_profiler(version, txn) {
+                Ok((_, _, gas_log)) => {
+                    if module_addresses.is_empty()
+                        || gas_log.call_graph.calls_module_at(module_addresses)
+                    {
+                        aggregated_gas_log.add(&gas_log);
+                    }
+                },
+                Err(err) => {
+                    println!("Failed to profile transaction {}: {:?}", version, err);
+                    aggregated_gas_log.add_failure(version);
+                },
+            }
+        }
+        Ok(aggregated_gas_log)
+    }
+
+    /// Replays every `sample_interval`-th user transaction in the given range of versions under
+    /// both the on-chain gas schedule and the proposed one, and reports the gas deltas.
+    pub async fn simulate_gas_schedule_change(
+        &self,
+        begin: Version,
+        limit: u64,
+        sample_interval: u64,
+        proposed_gas_schedule: &GasScheduleV2,
+    ) -> Result<GasScheduleImpactReport> {
+        AptosGasParameters::from_on_chain_gas_schedule(
+            &proposed_gas_schedule.clone().to_btree_map(),
+            proposed_gas_schedule.feature_version,
+        )
+        .ok_or_else(|| format_err!("The proposed gas schedule is missing some entries"))?;
+
+        let (txns, _) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let mut report = GasScheduleImpactReport::default();
+        let user_txns = (begin..).zip(txns).filter_map(|(version, txn)| match txn {
+            Transaction::UserTransaction(txn) => Some((version, txn)),
+            _ => None,
+        });
+        for (version, txn) in user_txns.step_by(sample_interval.max(1) as usize) {
+            let entry_function = gas_schedule_impact::entry_function_name(txn.payload());
+            let txns = vec![Transaction::UserTransaction(txn)];
+
+            let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+            let current_output = AptosVM::execute_block(txns.clone(), &state_view, None)
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+                .pop()
+                .expect("There should be one output");
+            let proposed_state_view =
+                GasScheduleOverrideStateView::new(&state_view, proposed_gas_schedule)?;
+            let proposed_output = AptosVM::execute_block(txns, &proposed_state_view, None)
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+                .pop()
+                .expect("There should be one output");
+
+            report.txns.push(TransactionGasDelta {
+                version,
+                entry_function,
+                current_gas_used: current_output.gas_used(),
+                current_status: current_output.status().clone(),
+                proposed_gas_used: proposed_output.gas_used(),
+                proposed_status: proposed_output.status().clone(),
+            });
+        }
+        Ok(report)
+    }
+
     pub async fn execute_past_transactions(
         &self,
         mut begin: Version,
@@ -133,6 +274,65 @@ impl AptosDebugger {
         Ok(ret)
     }
 
+    /// Returns the execution fingerprints the target recorded for the transactions in the range.
+    pub async fn get_execution_fingerprints(
+        &self,
+        begin: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        self.debugger.get_execution_fingerprints(begin, limit).await
+    }
+
+    /// Replays the transactions in the range, up to the latest version of the target, and returns
+    /// the execution fingerprints of the outputs which are kept.
+    pub async fn replay_execution_fingerprints(
+        &self,
+        begin: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        let limit = limit.min((self.get_latest_version().await? + 1).saturating_sub(begin));
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        let outputs = self.execute_past_transactions(begin, limit).await?;
+        Ok((begin..)
+            .zip(outputs)
+            .filter_map(|(version, output)| Some((version, output.execution_fingerprint()?)))
+            .collect())
+    }
+
+    /// Executes the given transactions as a single block at version `begin`, in parallel, and
+    /// returns the outputs with the report of the conflicts between the transactions. The
+    /// execution stops at the first reconfiguration, so the block shouldn't span epochs.
+    pub async fn execute_past_transactions_with_conflict_report(
+        &self,
+        begin: Version,
+        limit: u64,
+        concurrency_level: usize,
+    ) -> Result<(Vec<TransactionOutput>, BlockConflictReport<StateKey>)> {
+        let (txns, txn_infos) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+        let state_view = DebuggerStateView::new(self.debugger.clone(), begin);
+        let (mut outputs, conflict_report) = BlockAptosVM::execute_block_with_conflict_report(
+            Arc::clone(&RAYON_EXEC_POOL),
+            txns,
+            &state_view,
+            concurrency_level,
+            None,
+        )
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+        let conflict_report = conflict_report.ok_or_else(|| {
+            format_err!("No conflict report, the block wasn't executed in parallel")
+        })?;
+
+        // The transactions after a reconfiguration are skipped
+        outputs.truncate(outputs.partition_point(|output| !output.status().is_retry()));
+        Self::print_mismatches(&outputs, &txn_infos[..outputs.len()], begin);
+        Ok((outputs, conflict_report))
+    }
+
     fn print_mismatches(
         txn_outputs: &[TransactionOutput],
         expected_txn_infos: &[TransactionInfo],
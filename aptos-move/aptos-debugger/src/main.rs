@@ -1,12 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
-use aptos_debugger::AptosDebugger;
+use anyhow::{ensure, Result};
+use aptos_debugger::{AptosDebugger, FingerprintComparison};
+use aptos_gas::gen::gas_schedule_from_proposal_script;
 use aptos_rest_client::Client;
+use aptos_types::{account_address::AccountAddress, on_chain_config::GasScheduleV2};
 use aptos_vm::AptosVM;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 #[derive(Subcommand)]
@@ -24,11 +26,80 @@ pub struct Argument {
     #[clap(long)]
     begin_version: u64,
 
+    /// Number of transactions to replay, from `begin_version`
     #[clap(long)]
     limit: u64,
 
     #[clap(long, default_value = "1")]
     concurrency_level: usize,
+
+    /// Execute the transactions as a single block in parallel, and print the report of the
+    /// conflicts between them instead of the outputs
+    #[clap(
+        long,
+        conflicts_with_all = &["gas-profile", "proposed-gas-schedule", "compare-fingerprints"]
+    )]
+    conflict_report: bool,
+
+    /// Profile the gas of the user transactions, and print the aggregated profile instead of the
+    /// outputs. The merged flamegraphs are saved to `gas_profile_dir`.
+    #[clap(
+        long,
+        conflicts_with_all = &["proposed-gas-schedule", "compare-fingerprints"]
+    )]
+    gas_profile: bool,
+
+    /// Only aggregate the gas profiles of the transactions calling the modules published at these
+    /// addresses (all of them by default)
+    #[clap(long, multiple_values = true)]
+    module_addresses: Vec<AccountAddress>,
+
+    #[clap(long, default_value = "gas-profiling")]
+    gas_profile_dir: PathBuf,
+
+    /// Replay the user transactions under both the on-chain gas schedule and this one, and print
+    /// the gas deltas instead of the outputs. Either a JSON file or a gas schedule update proposal
+    /// script (which must bind the gas schedule blob to literals, as the generated ones do).
+    #[clap(long, conflicts_with = "compare-fingerprints")]
+    proposed_gas_schedule: Option<PathBuf>,
+
+    /// Only replay one in every `sample_interval` user transactions under the proposed gas
+    /// schedule
+    #[clap(long, default_value = "1")]
+    sample_interval: u64,
+
+    /// Compare the execution fingerprints the target recorded when its execution diverged with
+    /// those of a replay of the same transactions, and print the first transaction they diverge
+    /// on instead of the outputs
+    #[clap(long)]
+    compare_fingerprints: bool,
+
+    /// Replay the transactions to compare the fingerprints with from the full node at this rest
+    /// endpoint instead of the target, e.g. when the target halted before committing them. The
+    /// cache is only kept for the target, so they can't be used together.
+    #[clap(long, requires = "compare-fingerprints", conflicts_with = "cache-dir")]
+    other_endpoint: Option<String>,
+
+    /// Keep everything read from the target in a local DB at this path, and read it from there
+    /// when replaying the same history again
+    #[clap(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Never query the target, and fail on anything that isn't in the cache yet
+    #[clap(long, requires = "cache-dir")]
+    offline: bool,
+}
+
+fn load_gas_schedule(path: &Path) -> Result<GasScheduleV2> {
+    let contents = std::fs::read_to_string(path)?;
+    if path
+        .extension()
+        .map_or(false, |extension| extension == "json")
+    {
+        Ok(serde_json::from_str(&contents)?)
+    } else {
+        gas_schedule_from_proposal_script(&contents)
+    }
 }
 
 #[tokio::main]
@@ -37,19 +108,109 @@ async fn main() -> Result<()> {
     let args = Argument::parse();
     AptosVM::set_concurrency_level_once(args.concurrency_level);
 
-    let debugger = match args.target {
-        Target::Rest { endpoint } => {
-            AptosDebugger::rest_client(Client::new(Url::parse(&endpoint)?))?
+    let (debugger, target) = match args.target {
+        Target::Rest { endpoint } => (
+            AptosDebugger::rest_client(Client::new(Url::parse(&endpoint)?))?,
+            endpoint,
+        ),
+        Target::DB { path } => {
+            let target = path.display().to_string();
+            (AptosDebugger::db(path)?, target)
         },
-        Target::DB { path } => AptosDebugger::db(path)?,
     };
+    let debugger = match &args.cache_dir {
+        Some(cache_dir) => debugger.with_cache(cache_dir, &target, args.offline)?,
+        None => debugger,
+    };
+
+    if let Some(proposed_gas_schedule) = &args.proposed_gas_schedule {
+        let report = debugger
+            .simulate_gas_schedule_change(
+                args.begin_version,
+                args.limit,
+                args.sample_interval,
+                &load_gas_schedule(proposed_gas_schedule)?,
+            )
+            .await?;
+        println!("{}", report);
+    } else if args.compare_fingerprints {
+        let recorded = debugger
+            .get_execution_fingerprints(args.begin_version, args.limit)
+            .await?;
+        // Only the recorded versions are replayed.
+        let replayed = match (recorded.first(), recorded.last()) {
+            (Some((first, _)), Some((last, _))) => {
+                let other_debugger;
+                let replayer = match &args.other_endpoint {
+                    Some(endpoint) => {
+                        other_debugger =
+                            AptosDebugger::rest_client(Client::new(Url::parse(endpoint)?))?;
+                        &other_debugger
+                    },
+                    None => &debugger,
+                };
+                replayer
+                    .replay_execution_fingerprints(*first, last - first + 1)
+                    .await?
+            },
+            _ => vec![],
+        };
+        println!("{}", FingerprintComparison::compare(recorded, replayed));
+    } else if args.gas_profile {
+        // The flamegraphs are titled with the last version of the range
+        ensure!(args.limit > 0, "--limit must be greater than 0");
+        let aggregated_gas_log = debugger
+            .profile_past_transactions(args.begin_version, args.limit, &args.module_addresses)
+            .await?;
+        println!("{}", aggregated_gas_log);
+
+        let title = format!(
+            "Transactions {} to {}",
+            args.begin_version,
+            args.begin_version + args.limit - 1
+        );
+        std::fs::create_dir_all(&args.gas_profile_dir)?;
+        if let Some(graph_bytes) =
+            aggregated_gas_log.to_flamegraph(format!("{} -- Execution & IO", title))?
+        {
+            let graph_file_path = args.gas_profile_dir.join("aggregated.exec_io.svg");
+            std::fs::write(&graph_file_path, graph_bytes)?;
+            println!(
+                "Execution & IO Gas flamegraph saved to {}",
+                graph_file_path.display()
+            );
+        }
+        if let Some(graph_bytes) =
+            aggregated_gas_log.to_storage_flamegraph(format!("{} -- Storage Fee", title))?
+        {
+            let graph_file_path = args.gas_profile_dir.join("aggregated.storage.svg");
+            std::fs::write(&graph_file_path, graph_bytes)?;
+            println!(
+                "Storage fee flamegraph saved to {}",
+                graph_file_path.display()
+            );
+        }
+    } else if args.conflict_report {
+        let (_, conflict_report) = debugger
+            .execute_past_transactions_with_conflict_report(
+                args.begin_version,
+                args.limit,
+                args.concurrency_level,
+            )
+            .await?;
+        println!("{:#?}", conflict_report);
+    } else {
+        println!(
+            "{:#?}",
+            debugger
+                .execute_past_transactions(args.begin_version, args.limit)
+                .await?
+        );
+    }
 
-    println!(
-        "{:#?}",
-        debugger
-            .execute_past_transactions(args.begin_version, args.limit)
-            .await?
-    );
+    if let Some(cache_stats) = debugger.cache_stats() {
+        println!("Cache stats:\n{}", cache_stats);
+    }
 
     Ok(())
 }
@@ -0,0 +1,502 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    flamegraph::{render_flamegraph, render_gas_units},
+    log::{CallFrame, ExecutionGasEvent, FrameName, TransactionGasLog, WriteOpType},
+};
+use aptos_gas::{Fee, GasScalingFactor};
+use aptos_types::transaction::Version;
+use move_core_types::{gas_algebra::InternalGas, identifier::IdentStr, language_storage::ModuleId};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// The gas used by the calls to a function, summed over the aggregated transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct FunctionGas {
+    pub num_calls: u64,
+    /// The gas of the instructions and resource loads of the function itself.
+    pub self_cost: InternalGas,
+    /// The gas of the function and of all the functions it calls. Counted once per call, so
+    /// the cost of the recursive calls is counted several times.
+    pub total_cost: InternalGas,
+}
+
+/// The gas used by the calls to a native function, summed over the aggregated transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct NativeGas {
+    pub num_calls: u64,
+    pub cost: InternalGas,
+}
+
+/// The storage fees of the aggregated transactions, by kind.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageFeeTotals {
+    pub txn_storage: Fee,
+    pub creations: Fee,
+    pub modifications: Fee,
+    pub deletions: Fee,
+    pub events: Fee,
+    pub event_discount: Fee,
+}
+
+/// The gas logs of many transactions (e.g., all the transactions calling some modules over a
+/// range of versions) aggregated by function and native function, with the distributions of the
+/// gas used and storage fees of the transactions and the merged flamegraphs.
+#[derive(Debug)]
+pub struct AggregatedGasLog {
+    gas_scaling_factor: Option<GasScalingFactor>,
+    pub functions: BTreeMap<String, FunctionGas>,
+    pub natives: BTreeMap<String, NativeGas>,
+    pub storage: StorageFeeTotals,
+    // The execution & IO gas and the storage fee of each transaction
+    execution_and_io_costs: Vec<InternalGas>,
+    storage_fees: Vec<Fee>,
+    // The costs of the folded stack lines of all the transactions
+    execution_and_io_lines: BTreeMap<String, u64>,
+    storage_lines: BTreeMap<String, u64>,
+    // The versions of the transactions that couldn't be profiled
+    failed_versions: Vec<Version>,
+}
+
+fn function_name(module_id: &ModuleId, name: &IdentStr) -> String {
+    format!(
+        "0x{}::{}::{}",
+        module_id.address().short_str_lossless(),
+        module_id.name(),
+        name
+    )
+}
+
+fn frame_name(name: &FrameName) -> String {
+    match name {
+        FrameName::Script => "<script>".to_string(),
+        FrameName::Function {
+            module_id, name, ..
+        } => function_name(module_id, name),
+    }
+}
+
+fn merge_folded_stack_lines(merged_lines: &mut BTreeMap<String, u64>, lines: Vec<String>) {
+    for line in lines {
+        let (stack, count) = line
+            .rsplit_once(' ')
+            .expect("folded stack lines should end with a count");
+        let count: u64 = count.parse().expect("should be able parse count as u64");
+        *merged_lines.entry(stack.to_string()).or_insert(0) += count;
+    }
+}
+
+/// Returns the value at the given percentile (between 0 and 100) of the sorted values, using the
+/// nearest rank.
+fn percentile<T: Copy>(sorted_values: &[T], percentile: f64) -> Option<T> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let rank = (percentile / 100.0 * sorted_values.len() as f64).ceil() as usize;
+    Some(sorted_values[rank.clamp(1, sorted_values.len()) - 1])
+}
+
+impl AggregatedGasLog {
+    pub fn new() -> Self {
+        Self {
+            gas_scaling_factor: None,
+            functions: BTreeMap::new(),
+            natives: BTreeMap::new(),
+            storage: StorageFeeTotals {
+                txn_storage: 0.into(),
+                creations: 0.into(),
+                modifications: 0.into(),
+                deletions: 0.into(),
+                events: 0.into(),
+                event_discount: 0.into(),
+            },
+            execution_and_io_costs: vec![],
+            storage_fees: vec![],
+            execution_and_io_lines: BTreeMap::new(),
+            storage_lines: BTreeMap::new(),
+            failed_versions: vec![],
+        }
+    }
+
+    pub fn num_txns(&self) -> usize {
+        self.execution_and_io_costs.len()
+    }
+
+    /// The versions of the transactions that couldn't be profiled, which are missing from the
+    /// aggregate.
+    pub fn failed_versions(&self) -> &[Version] {
+        &self.failed_versions
+    }
+
+    /// Records a transaction that couldn't be profiled.
+    pub fn add_failure(&mut self, version: Version) {
+        self.failed_versions.push(version);
+    }
+
+    /// Adds the gas log of a transaction to the aggregate.
+    pub fn add(&mut self, log: &TransactionGasLog) {
+        self.gas_scaling_factor
+            .get_or_insert(log.gas_scaling_factor);
+
+        let mut execution_and_io_cost = log.intrinsic_cost + self.add_frame(&log.call_graph);
+        for item in &log.write_set_transient {
+            execution_and_io_cost += item.cost;
+        }
+        self.execution_and_io_costs.push(execution_and_io_cost);
+
+        let storage = &log.storage;
+        let mut storage_fee = storage.txn_storage;
+        self.storage.txn_storage += storage.txn_storage;
+        for item in &storage.write_set_storage {
+            storage_fee += item.cost;
+            match item.op_type {
+                WriteOpType::Creation => self.storage.creations += item.cost,
+                WriteOpType::Modification => self.storage.modifications += item.cost,
+                WriteOpType::Deletion => self.storage.deletions += item.cost,
+            }
+        }
+        for event in &storage.events {
+            storage_fee += event.cost;
+            self.storage.events += event.cost;
+        }
+        self.storage.event_discount += storage.event_discount;
+        self.storage_fees.push(
+            storage_fee
+                .checked_sub(storage.event_discount)
+                .unwrap_or_else(|| 0.into()),
+        );
+
+        merge_folded_stack_lines(
+            &mut self.execution_and_io_lines,
+            log.to_folded_stack_lines(),
+        );
+        merge_folded_stack_lines(&mut self.storage_lines, storage.to_folded_stack_lines());
+    }
+
+    /// Adds the calls of the frame to the aggregate, and returns the total cost of the frame.
+    fn add_frame(&mut self, frame: &CallFrame) -> InternalGas {
+        let mut self_cost = InternalGas::new(0);
+        let mut total_cost = InternalGas::new(0);
+
+        for event in &frame.events {
+            use ExecutionGasEvent::*;
+
+            match event {
+                Loc(_) => (),
+                Bytecode { cost, .. } | LoadResource { cost, .. } => self_cost += *cost,
+                Call(inner_frame) => total_cost += self.add_frame(inner_frame),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    cost,
+                    ..
+                } => {
+                    let native = self
+                        .natives
+                        .entry(function_name(module_id, fn_name))
+                        .or_insert(NativeGas {
+                            num_calls: 0,
+                            cost: 0.into(),
+                        });
+                    native.num_calls += 1;
+                    native.cost += *cost;
+                    total_cost += *cost;
+                },
+            }
+        }
+        total_cost += self_cost;
+
+        let function = self
+            .functions
+            .entry(frame_name(&frame.name))
+            .or_insert(FunctionGas {
+                num_calls: 0,
+                self_cost: 0.into(),
+                total_cost: 0.into(),
+            });
+        function.num_calls += 1;
+        function.self_cost += self_cost;
+        function.total_cost += total_cost;
+        total_cost
+    }
+
+    /// Returns the execution & IO gas at the given percentile (between 0 and 100) of the
+    /// aggregated transactions.
+    pub fn execution_and_io_cost_percentile(&self, p: f64) -> Option<InternalGas> {
+        let mut costs = self.execution_and_io_costs.clone();
+        costs.sort();
+        percentile(&costs, p)
+    }
+
+    /// Returns the storage fee at the given percentile (between 0 and 100) of the aggregated
+    /// transactions.
+    pub fn storage_fee_percentile(&self, p: f64) -> Option<Fee> {
+        let mut fees = self.storage_fees.clone();
+        fees.sort();
+        percentile(&fees, p)
+    }
+
+    pub fn total_execution_and_io_cost(&self) -> InternalGas {
+        self.execution_and_io_costs
+            .iter()
+            .fold(0.into(), |total, cost| total + *cost)
+    }
+
+    pub fn total_storage_fee(&self) -> Fee {
+        self.storage_fees
+            .iter()
+            .fold(0.into(), |total, fee| total + *fee)
+    }
+
+    /// Tries to generate a flamegraph merging the execution logs of all the transactions.
+    /// None will be returned if no transaction has been aggregated.
+    pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
+        let gas_scaling_factor = match self.gas_scaling_factor {
+            Some(gas_scaling_factor) => gas_scaling_factor,
+            None => return Ok(None),
+        };
+        let lines: Vec<_> = self
+            .execution_and_io_lines
+            .iter()
+            .map(|(stack, count)| format!("{} {}", stack, count))
+            .collect();
+        render_flamegraph(&lines, title, false, |count| {
+            render_gas_units(count, gas_scaling_factor)
+        })
+    }
+
+    /// Tries to generate a flamegraph merging the storage fee logs of all the transactions.
+    /// None will be returned if no transaction has been aggregated.
+    pub fn to_storage_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
+        let lines: Vec<_> = self
+            .storage_lines
+            .iter()
+            .map(|(stack, count)| format!("{} {}", stack, count))
+            .collect();
+        render_flamegraph(&lines, title, false, |count| format!("{} Octa", count))
+    }
+
+    fn to_gas_units(&self, cost: InternalGas) -> f64 {
+        match self.gas_scaling_factor {
+            Some(gas_scaling_factor) => {
+                u64::from(cost) as f64 / u64::from(gas_scaling_factor) as f64
+            },
+            None => 0.0,
+        }
+    }
+}
+
+impl Default for AggregatedGasLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The number of functions and native functions listed in the summary
+const NUM_TOP_FUNCTIONS: usize = 20;
+
+const PERCENTILES: [f64; 4] = [50.0, 90.0, 99.0, 100.0];
+
+impl Display for AggregatedGasLog {
+    /// Renders a summary of the aggregate: the distributions of the gas used and of the storage
+    /// fees, and the functions and native functions using the most gas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transactions: {}", self.num_txns())?;
+        if !self.failed_versions.is_empty() {
+            writeln!(
+                f,
+                "Failed to profile {} transactions: {:?}",
+                self.failed_versions.len(),
+                self.failed_versions
+            )?;
+        }
+
+        writeln!(
+            f,
+            "Execution & IO (gas units): total {:.2}",
+            self.to_gas_units(self.total_execution_and_io_cost())
+        )?;
+        for p in PERCENTILES {
+            if let Some(cost) = self.execution_and_io_cost_percentile(p) {
+                writeln!(f, "    p{}: {:.2}", p, self.to_gas_units(cost))?;
+            }
+        }
+
+        writeln!(f, "Storage fee (Octa): total {}", self.total_storage_fee())?;
+        for p in PERCENTILES {
+            if let Some(fee) = self.storage_fee_percentile(p) {
+                writeln!(f, "    p{}: {}", p, fee)?;
+            }
+        }
+        writeln!(f, "    transactions: {}", self.storage.txn_storage)?;
+        writeln!(f, "    creations: {}", self.storage.creations)?;
+        writeln!(f, "    modifications: {}", self.storage.modifications)?;
+        writeln!(f, "    deletions: {}", self.storage.deletions)?;
+        writeln!(f, "    events: {}", self.storage.events)?;
+        writeln!(f, "    event discount: -{}", self.storage.event_discount)?;
+
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by(|(_, gas1), (_, gas2)| gas2.total_cost.cmp(&gas1.total_cost));
+        writeln!(f, "Functions (calls, self gas units, total gas units):")?;
+        for (name, gas) in functions.into_iter().take(NUM_TOP_FUNCTIONS) {
+            writeln!(
+                f,
+                "    {}: {}, {:.2}, {:.2}",
+                name,
+                gas.num_calls,
+                self.to_gas_units(gas.self_cost),
+                self.to_gas_units(gas.total_cost)
+            )?;
+        }
+
+        let mut natives: Vec<_> = self.natives.iter().collect();
+        natives.sort_by(|(_, gas1), (_, gas2)| gas2.cost.cmp(&gas1.cost));
+        writeln!(f, "Native functions (calls, gas units):")?;
+        for (name, gas) in natives.into_iter().take(NUM_TOP_FUNCTIONS) {
+            writeln!(
+                f,
+                "    {}: {}, {:.2}",
+                name,
+                gas.num_calls,
+                self.to_gas_units(gas.cost)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format_common::Opcodes;
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+    };
+
+    fn module_id() -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap())
+    }
+
+    fn frame(name: &str, events: Vec<ExecutionGasEvent>) -> CallFrame {
+        CallFrame {
+            name: FrameName::Function {
+                module_id: module_id(),
+                name: Identifier::new(name).unwrap(),
+                ty_args: vec![],
+            },
+            events,
+        }
+    }
+
+    fn bytecode(cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op: Opcodes::ADD,
+            cost: cost.into(),
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile::<u64>(&[], 50.0), None);
+        assert_eq!(percentile(&[7], 0.0), Some(7));
+        assert_eq!(percentile(&[7], 100.0), Some(7));
+
+        let values: Vec<u64> = (1..=10).collect();
+        assert_eq!(percentile(&values, 0.0), Some(1));
+        assert_eq!(percentile(&values, 50.0), Some(5));
+        assert_eq!(percentile(&values, 51.0), Some(6));
+        assert_eq!(percentile(&values, 90.0), Some(9));
+        assert_eq!(percentile(&values, 99.0), Some(10));
+        assert_eq!(percentile(&values, 100.0), Some(10));
+    }
+
+    #[test]
+    fn test_merge_folded_stack_lines() {
+        let mut merged_lines = BTreeMap::new();
+        merge_folded_stack_lines(&mut merged_lines, vec![
+            "a;b 3".to_string(),
+            "a 2".to_string(),
+        ]);
+        merge_folded_stack_lines(&mut merged_lines, vec![
+            "a;b 4".to_string(),
+            "a;<storage> fee 1".to_string(),
+        ]);
+
+        assert_eq!(
+            merged_lines,
+            BTreeMap::from([
+                ("a".to_string(), 2),
+                ("a;<storage> fee".to_string(), 1),
+                ("a;b".to_string(), 7),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_add_frame() {
+        let mut aggregate = AggregatedGasLog::new();
+        let inner = frame("g", vec![bytecode(20), ExecutionGasEvent::LoadResource {
+            addr: AccountAddress::ONE,
+            ty: TypeTag::U64,
+            cost: 3.into(),
+        }]);
+        let outer = frame("f", vec![
+            bytecode(10),
+            ExecutionGasEvent::Loc(0),
+            ExecutionGasEvent::CallNative {
+                module_id: module_id(),
+                fn_name: Identifier::new("n").unwrap(),
+                ty_args: vec![],
+                cost: 5.into(),
+            },
+            ExecutionGasEvent::Call(inner),
+        ]);
+
+        // The total cost includes the callees, the self cost only the function's own events
+        assert_eq!(aggregate.add_frame(&outer), 38.into());
+        let f = aggregate.functions["0x1::m::f"];
+        assert_eq!(f.num_calls, 1);
+        assert_eq!(f.self_cost, 10.into());
+        assert_eq!(f.total_cost, 38.into());
+        let g = aggregate.functions["0x1::m::g"];
+        assert_eq!(g.num_calls, 1);
+        assert_eq!(g.self_cost, 23.into());
+        assert_eq!(g.total_cost, 23.into());
+        let n = aggregate.natives["0x1::m::n"];
+        assert_eq!(n.num_calls, 1);
+        assert_eq!(n.cost, 5.into());
+    }
+
+    #[test]
+    fn test_add_failure() {
+        let mut aggregate = AggregatedGasLog::new();
+        aggregate.add_failure(7);
+        aggregate.add_failure(9);
+
+        // The failed transactions aren't aggregated, but they are reported
+        assert_eq!(aggregate.num_txns(), 0);
+        assert_eq!(aggregate.failed_versions(), &[7, 9]);
+        assert!(aggregate
+            .to_string()
+            .contains("Failed to profile 2 transactions: [7, 9]"));
+    }
+
+    #[test]
+    fn test_add_frame_recursion() {
+        let mut aggregate = AggregatedGasLog::new();
+        let innermost = frame("f", vec![bytecode(1)]);
+        let middle = frame("f", vec![bytecode(1), ExecutionGasEvent::Call(innermost)]);
+        let outer = frame("f", vec![bytecode(1), ExecutionGasEvent::Call(middle)]);
+
+        // The self costs add up to the total cost of the transaction, while the total costs
+        // count the recursive calls once per enclosing call (1 + 2 + 3).
+        assert_eq!(aggregate.add_frame(&outer), 3.into());
+        let f = aggregate.functions["0x1::m::f"];
+        assert_eq!(f.num_calls, 3);
+        assert_eq!(f.self_cost, 3.into());
+        assert_eq!(f.total_cost, 6.into());
+    }
+}
@@ -4,6 +4,7 @@
 use crate::log::{
     CallFrame, ExecutionGasEvent, FrameName, StorageFees, TransactionGasLog, WriteOpType,
 };
+use aptos_gas::GasScalingFactor;
 use aptos_types::{
     access_path::Path,
     state_store::{
@@ -148,6 +149,60 @@ fn strip_trailing_zeros_and_decimal_point(mut s: &str) -> &str {
     }
 }
 
+pub(crate) fn render_gas_units(count: u64, gas_scaling_factor: GasScalingFactor) -> String {
+    let count_scaled = count as f64 / u64::from(gas_scaling_factor) as f64;
+
+    format!(
+        "{} gas units",
+        strip_trailing_zeros_and_decimal_point(&format!("{:.8}", count_scaled))
+    )
+}
+
+/// Generates a flamegraph from folded stack lines, with the counts rendered by `render_count`.
+/// None will be returned if there are no lines.
+///
+/// With `flame_chart`, the stacks are kept in the order of the lines (i.e., the order of
+/// execution) instead of being merged and sorted alphabetically.
+pub(crate) fn render_flamegraph(
+    lines: &[String],
+    title: String,
+    flame_chart: bool,
+    render_count: impl Fn(u64) -> String,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    if lines.is_empty() {
+        return Ok(None);
+    }
+
+    let mut options = inferno::flamegraph::Options::default();
+    options.flame_chart = flame_chart;
+    options.text_truncate_direction = TextTruncateDirection::Right;
+    options.color_diffusion = true;
+    options.title = title;
+
+    let mut graph_content = vec![];
+    inferno::flamegraph::from_lines(
+        &mut options,
+        lines.iter().rev().map(|s| s.as_str()),
+        &mut graph_content,
+    )?;
+    let graph_content = String::from_utf8_lossy(&graph_content);
+
+    // Inferno does not allow us to customize some of the text in the resulting graph,
+    // so we have to do it through regex replacement.
+    let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
+        .expect("should be able to build regex successfully");
+    let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
+        let count: u64 = caps[1]
+            .replace(',', "")
+            .parse()
+            .expect("should be able parse count as u64");
+
+        render_count(count)
+    });
+
+    Ok(Some(graph_content.as_bytes().to_vec()))
+}
+
 struct LineBuffer(Vec<String>);
 
 impl LineBuffer {
@@ -171,7 +226,7 @@ impl LineBuffer {
 impl StorageFees {
     /// Convert the storage fee log into folded stack lines, which can
     /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    pub(crate) fn to_folded_stack_lines(&self) -> Vec<String> {
         let mut lines = LineBuffer::new();
 
         lines.push("transaction", self.txn_storage);
@@ -193,47 +248,16 @@ impl StorageFees {
     /// Tries to generate a flamegraph from the execution log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
-
-        if lines.is_empty() {
-            return Ok(None);
-        }
-
-        let mut options = inferno::flamegraph::Options::default();
-        options.flame_chart = true;
-        options.text_truncate_direction = TextTruncateDirection::Right;
-        options.color_diffusion = true;
-        options.title = title;
-
-        let mut graph_content = vec![];
-        inferno::flamegraph::from_lines(
-            &mut options,
-            lines.iter().rev().map(|s| s.as_str()),
-            &mut graph_content,
-        )?;
-        let graph_content = String::from_utf8_lossy(&graph_content);
-
-        // Inferno does not allow us to customize some of the text in the resulting graph,
-        // so we have to do it through regex replacement.
-        let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
-            .expect("should be able to build regex successfully");
-        let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
-            let count: u64 = caps[1]
-                .replace(',', "")
-                .parse()
-                .expect("should be able parse count as u64");
-
+        render_flamegraph(&self.to_folded_stack_lines(), title, true, |count| {
             format!("{} Octa", count)
-        });
-
-        Ok(Some(graph_content.as_bytes().to_vec()))
+        })
     }
 }
 
 impl TransactionGasLog {
     /// Convert the execution gas log into folded stack lines, which can
     /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    pub(crate) fn to_folded_stack_lines(&self) -> Vec<String> {
         let mut lines = LineBuffer::new();
 
         lines.push("intrinsic", self.intrinsic_cost);
@@ -306,44 +330,8 @@ impl TransactionGasLog {
     /// Tries to generate a flamegraph from the execution log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
-
-        if lines.is_empty() {
-            return Ok(None);
-        }
-
-        let mut options = inferno::flamegraph::Options::default();
-        options.flame_chart = true;
-        options.text_truncate_direction = TextTruncateDirection::Right;
-        options.color_diffusion = true;
-        options.title = title;
-
-        let mut graph_content = vec![];
-        inferno::flamegraph::from_lines(
-            &mut options,
-            lines.iter().rev().map(|s| s.as_str()),
-            &mut graph_content,
-        )?;
-        let graph_content = String::from_utf8_lossy(&graph_content);
-
-        // Inferno does not allow us to customize some of the text in the resulting graph,
-        // so we have to do it through regex replacement.
-        let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
-            .expect("should be able to build regex successfully");
-        let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
-            let count: u64 = caps[1]
-                .replace(',', "")
-                .parse()
-                .expect("should be able parse count as u64");
-
-            let count_scaled = count as f64 / u64::from(self.gas_scaling_factor) as f64;
-
-            format!(
-                "{} gas units",
-                strip_trailing_zeros_and_decimal_point(&format!("{:.8}", count_scaled))
-            )
-        });
-
-        Ok(Some(graph_content.as_bytes().to_vec()))
+        render_flamegraph(&self.to_folded_stack_lines(), title, true, |count| {
+            render_gas_units(count, self.gas_scaling_factor)
+        })
     }
 }
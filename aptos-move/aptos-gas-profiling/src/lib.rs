@@ -1,9 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod aggregate;
 mod flamegraph;
 mod log;
 mod profiler;
 
+pub use aggregate::{AggregatedGasLog, FunctionGas, NativeGas, StorageFeeTotals};
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
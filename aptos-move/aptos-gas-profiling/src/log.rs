@@ -130,6 +130,23 @@ impl CallFrame {
             events: vec![],
         }
     }
+
+    /// Returns whether the frame, or any of the frames it calls, is a function (or calls a
+    /// native function) of a module published at one of the given addresses.
+    pub fn calls_module_at(&self, addresses: &[AccountAddress]) -> bool {
+        if let FrameName::Function { module_id, .. } = &self.name {
+            if addresses.contains(module_id.address()) {
+                return true;
+            }
+        }
+        self.events.iter().any(|event| match event {
+            ExecutionGasEvent::Call(inner_frame) => inner_frame.calls_module_at(addresses),
+            ExecutionGasEvent::CallNative { module_id, .. } => {
+                addresses.contains(module_id.address())
+            },
+            _ => false,
+        })
+    }
 }
 
 impl TransactionGasLog {
@@ -4,7 +4,7 @@
 use crate::gas_meter::{
     AptosGasParameters, InitialGasSchedule, ToOnChainGasSchedule, LATEST_GAS_FEATURE_VERSION,
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use aptos_package_builder::PackageBuilder;
 use aptos_types::on_chain_config::GasScheduleV2;
 use clap::Parser;
@@ -96,6 +96,119 @@ fn generate_script(gas_schedule: &GasScheduleV2) -> Result<String> {
     Ok(writer.process_result(|s| s.to_string()))
 }
 
+/// The functions of the gas schedule module that a proposal script can set the gas schedule with
+const GAS_SCHEDULE_SETTERS: &[&str] = &[
+    "gas_schedule::set_gas_schedule(",
+    "gas_schedule::set_for_next_epoch(",
+];
+
+/// Extracts the gas schedule set by an update proposal script, as generated by
+/// [generate_update_proposal] or the release builder.
+///
+/// The script must pass a local `vector<u8>` to one of the gas schedule setters. Its value is
+/// assembled from its `let` binding and the `vector::append(&mut <blob>, ...)` calls that follow
+/// (for scripts that split the blob into chunks), each being a `vector[...]` or an `x"..."`
+/// literal. Any other way of computing the blob is rejected.
+pub fn gas_schedule_from_proposal_script(script: &str) -> Result<GasScheduleV2> {
+    // Drop the comments, as the generated scripts list the gas schedule entries in them
+    let script = script
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let (setter_start, setter_args_start) = GAS_SCHEDULE_SETTERS
+        .iter()
+        .filter_map(|setter| {
+            script
+                .find(setter)
+                .map(|start| (start, start + setter.len()))
+        })
+        .min()
+        .ok_or_else(|| anyhow!("No gas schedule setter call in the proposal script"))?;
+    let setter_args_len = script[setter_args_start..]
+        .find(')')
+        .ok_or_else(|| anyhow!("Unterminated gas schedule setter call in the proposal script"))?;
+    let blob_name = script[setter_args_start..setter_args_start + setter_args_len]
+        .rsplit(',')
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if blob_name.is_empty() || !blob_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        bail!(
+            "The gas schedule blob must be a local variable, not: {}",
+            blob_name
+        );
+    }
+
+    let append_prefix = format!("vector::append(&mut{},", blob_name);
+    let mut gas_schedule_blob: Option<Vec<u8>> = None;
+    for statement in script[..setter_start].split(';') {
+        // Only keep the statement itself (and not the block it opens)
+        let statement = statement
+            .rsplit(|c: char| c == '{' || c == '}')
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let compact_statement: String = statement.split_whitespace().collect();
+        if let Some(binding) = statement.strip_prefix("let ") {
+            let (name, value) = binding
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Unsupported let statement: {}", statement))?;
+            let name = name.split(':').next().unwrap_or_default().trim();
+            if name == blob_name {
+                gas_schedule_blob = Some(parse_byte_vector(value)?);
+            }
+        } else if let Some(chunk) = compact_statement
+            .strip_prefix(&append_prefix)
+            .and_then(|arguments| arguments.strip_suffix(')'))
+        {
+            gas_schedule_blob
+                .as_mut()
+                .ok_or_else(|| anyhow!("The gas schedule blob is appended to before it is bound"))?
+                .extend(parse_byte_vector(chunk)?);
+        } else if compact_statement.starts_with(&format!("{}=", blob_name)) {
+            bail!("The gas schedule blob must not be reassigned");
+        }
+    }
+
+    let gas_schedule_blob = gas_schedule_blob.ok_or_else(|| {
+        anyhow!(
+            "No binding of the gas schedule blob {} in the proposal script",
+            blob_name
+        )
+    })?;
+    Ok(bcs::from_bytes(&gas_schedule_blob)?)
+}
+
+/// Parses a `vector[...]` (optionally typed) or an `x"..."` byte vector literal
+fn parse_byte_vector(literal: &str) -> Result<Vec<u8>> {
+    let literal: String = literal.split_whitespace().collect();
+    if let Some(hex) = literal
+        .strip_prefix("x\"")
+        .and_then(|hex| hex.strip_suffix('"'))
+    {
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            bail!("Invalid hex literal: x\"{}\"", hex);
+        }
+        return (0..hex.len())
+            .step_by(2)
+            .map(|i| Ok(u8::from_str_radix(&hex[i..i + 2], 16)?))
+            .collect();
+    }
+
+    let bytes = literal
+        .strip_prefix("vector<u8>[")
+        .or_else(|| literal.strip_prefix("vector["))
+        .and_then(|bytes| bytes.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("Unsupported byte vector: {}", literal))?;
+    Ok(bytes
+        .split(',')
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| byte.trim_end_matches("u8").parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
 fn aptos_framework_path() -> PathBuf {
     Path::join(
         Path::new(env!("CARGO_MANIFEST_DIR")),
@@ -123,3 +236,66 @@ pub fn generate_update_proposal(args: &GenArgs) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_schedule_round_trips_through_proposal_script() {
+        let gas_schedule = current_gas_schedule();
+        let script = generate_script(&gas_schedule).unwrap();
+        assert_eq!(
+            gas_schedule_from_proposal_script(&script).unwrap(),
+            gas_schedule
+        );
+    }
+
+    #[test]
+    fn gas_schedule_round_trips_through_chunked_proposal_script() {
+        let gas_schedule = current_gas_schedule();
+        let blob = bcs::to_bytes(&gas_schedule).unwrap();
+        let (first_chunk, second_chunk) = blob.split_at(blob.len() / 2);
+        let hex_chunk: String = second_chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let script = format!(
+            "// The gas_schedule_blob below is split in chunks: vector[1, 2]\n\
+             script {{\n\
+                 use std::vector;\n\
+                 use aptos_framework::aptos_governance;\n\
+                 use aptos_framework::gas_schedule;\n\
+                 fun main(proposal_id: u64) {{\n\
+                     let framework_signer = aptos_governance::resolve_multi_step_proposal(\n\
+                         proposal_id,\n\
+                         @0x1,\n\
+                         vector[1u8, 2u8,],\n\
+                     );\n\
+                     let gas_schedule_blob: vector<u8> = vector[{}];\n\
+                     vector::append(&mut gas_schedule_blob, x\"{}\");\n\
+                     gas_schedule::set_for_next_epoch(&framework_signer, gas_schedule_blob);\n\
+                 }}\n\
+             }}\n",
+            first_chunk
+                .iter()
+                .map(|b| format!("{}u8", b))
+                .collect::<Vec<_>>()
+                .join(", "),
+            hex_chunk,
+        );
+        assert_eq!(
+            gas_schedule_from_proposal_script(&script).unwrap(),
+            gas_schedule
+        );
+    }
+
+    #[test]
+    fn gas_schedule_from_proposal_script_rejects_computed_blobs() {
+        let script = "script {\n\
+            use aptos_framework::gas_schedule;\n\
+            fun main(framework_signer: &signer) {\n\
+                let gas_schedule_blob = make_blob();\n\
+                gas_schedule::set_gas_schedule(framework_signer, gas_schedule_blob);\n\
+            }\n\
+        }\n";
+        assert!(gas_schedule_from_proposal_script(script).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_bitvec::BitVec;
+use aptos_block_partitioner::sharded_block_partitioner::ShardedBlockPartitioner;
 use aptos_crypto::HashValue;
 use aptos_language_e2e_tests::{
     account_universe::{AUTransactionGen, AccountPickStyle, AccountUniverse, AccountUniverseGen},
@@ -24,6 +25,8 @@ use proptest::{
 };
 use std::{sync::Arc, time::Instant};
 
+const NUM_PARTITIONING_ROUNDS: usize = 4;
+
 /// Benchmarking support for transactions.
 #[derive(Clone)]
 pub struct TransactionBencher<S> {
@@ -171,6 +174,9 @@ struct TransactionBenchState<S> {
     account_universe: AccountUniverse,
     parallel_block_executor: Arc<ShardedBlockExecutor<FakeDataStore>>,
     sequential_block_executor: Arc<ShardedBlockExecutor<FakeDataStore>>,
+    // The partitioners of the blocks, with as many shards as the corresponding executors
+    parallel_block_partitioner: ShardedBlockPartitioner,
+    sequential_block_partitioner: ShardedBlockPartitioner,
     validator_set: ValidatorSet,
     state_view: Arc<FakeDataStore>,
 }
@@ -220,6 +226,8 @@ where
         let parallel_block_executor =
             Arc::new(ShardedBlockExecutor::new(num_executor_shards, None));
         let sequential_block_executor = Arc::new(ShardedBlockExecutor::new(1, Some(1)));
+        let parallel_block_partitioner = ShardedBlockPartitioner::new(num_executor_shards);
+        let sequential_block_partitioner = ShardedBlockPartitioner::new(1);
 
         let validator_set = ValidatorSet::fetch_config(
             &FakeExecutor::from_head_genesis()
@@ -234,6 +242,8 @@ where
             account_universe: universe,
             parallel_block_executor,
             sequential_block_executor,
+            parallel_block_partitioner,
+            sequential_block_partitioner,
             validator_set,
             state_view,
         }
@@ -282,9 +292,12 @@ where
         // The output is ignored here since we're just testing transaction performance, not trying
         // to assert correctness.
         let txns = self.gen_transaction(false);
+        let sub_blocks = self
+            .sequential_block_partitioner
+            .partition_transactions(txns, NUM_PARTITIONING_ROUNDS);
         let executor = self.sequential_block_executor;
         executor
-            .execute_block(self.state_view.clone(), txns, 1, None)
+            .execute_block(self.state_view.clone(), sub_blocks, 1, None)
             .expect("VM should not fail to start");
     }
 
@@ -293,25 +306,32 @@ where
         // The output is ignored here since we're just testing transaction performance, not trying
         // to assert correctness.
         let txns = self.gen_transaction(false);
+        let sub_blocks = self
+            .parallel_block_partitioner
+            .partition_transactions(txns, NUM_PARTITIONING_ROUNDS);
         let executor = self.parallel_block_executor.clone();
         executor
-            .execute_block(self.state_view.clone(), txns, num_cpus::get(), None)
+            .execute_block(self.state_view.clone(), sub_blocks, num_cpus::get(), None)
             .expect("VM should not fail to start");
     }
 
     fn execute_benchmark(
         &self,
         transactions: Vec<Transaction>,
+        block_partitioner: &ShardedBlockPartitioner,
         block_executor: Arc<ShardedBlockExecutor<FakeDataStore>>,
         concurrency_level_per_shard: usize,
         maybe_block_gas_limit: Option<u64>,
     ) -> usize {
         let block_size = transactions.len();
+        // Only the execution of the partitioned block is measured
+        let sub_blocks =
+            block_partitioner.partition_transactions(transactions, NUM_PARTITIONING_ROUNDS);
         let timer = Instant::now();
         block_executor
             .execute_block(
                 self.state_view.clone(),
-                transactions,
+                sub_blocks,
                 concurrency_level_per_shard,
                 maybe_block_gas_limit,
             )
@@ -334,6 +354,7 @@ where
             println!("Parallel execution starts...");
             let tps = self.execute_benchmark(
                 transactions.clone(),
+                &self.parallel_block_partitioner,
                 self.parallel_block_executor.clone(),
                 conurrency_level_per_shard,
                 maybe_block_gas_limit,
@@ -347,6 +368,7 @@ where
             println!("Sequential execution starts...");
             let tps = self.execute_benchmark(
                 transactions,
+                &self.sequential_block_partitioner,
                 self.sequential_block_executor.clone(),
                 1,
                 maybe_block_gas_limit,
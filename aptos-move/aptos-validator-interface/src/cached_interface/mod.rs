@@ -0,0 +1,363 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod schema;
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    cached_interface::schema::{
+        AccountSequenceSchema, AccountStateSchema, MetadataSchema, StateValueSchema,
+        TransactionSchema, ACCOUNT_SEQUENCE_CF_NAME, ACCOUNT_STATE_CF_NAME, METADATA_CF_NAME,
+        STATE_VALUE_CF_NAME, TARGET_METADATA_KEY, TRANSACTION_CF_NAME,
+    },
+    AptosValidatorInterface,
+};
+use anyhow::{bail, ensure, Result};
+use aptos_schemadb::{schema::Schema, Options, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{ExecutionFingerprint, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use itertools::izip;
+use std::{
+    fmt,
+    future::Future,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+const CACHE_DB_NAME: &str = "debugger_cache";
+
+/// An `AptosValidatorInterface` keeping everything it reads from another one in a local DB, so
+/// replaying the same history again doesn't fetch it again.
+///
+/// All the reads are of committed data at fixed versions, so the cache never has to be
+/// invalidated. The only exception is the latest version, which is always read from the inner
+/// interface. A cache only serves the target it was filled from (e.g. the URL of a REST endpoint
+/// or the path of a DB), as the history of another network differs.
+///
+/// In offline mode, the inner interface is never queried, and reading anything that isn't in the
+/// cache fails.
+pub struct CachedDebuggerInterface {
+    inner: Arc<dyn AptosValidatorInterface + Send>,
+    db: DB,
+    offline: bool,
+    state_values: CacheCounter,
+    account_states: CacheCounter,
+    transactions: CacheCounter,
+    account_sequences: CacheCounter,
+}
+
+impl CachedDebuggerInterface {
+    /// Opens the cache at `cache_path` for the `target` the inner interface reads from, and fails
+    /// if the cache was filled from another target.
+    pub fn open(
+        inner: Arc<dyn AptosValidatorInterface + Send>,
+        cache_path: impl AsRef<Path>,
+        target: &str,
+        offline: bool,
+    ) -> Result<Self> {
+        let column_families = vec![
+            /* UNUSED CF = */ DEFAULT_COLUMN_FAMILY_NAME,
+            STATE_VALUE_CF_NAME,
+            ACCOUNT_STATE_CF_NAME,
+            TRANSACTION_CF_NAME,
+            ACCOUNT_SEQUENCE_CF_NAME,
+            METADATA_CF_NAME,
+        ];
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open(cache_path.as_ref(), CACHE_DB_NAME, column_families, &opts)?;
+
+        let target_key = TARGET_METADATA_KEY.to_string();
+        match db.get::<MetadataSchema>(&target_key)? {
+            Some(cached_target) => ensure!(
+                cached_target == target,
+                "The debugger cache at {} was filled from {}, and can't be used for {}",
+                cache_path.as_ref().display(),
+                cached_target,
+                target
+            ),
+            None => db.put::<MetadataSchema>(&target_key, &target.to_string())?,
+        }
+
+        Ok(Self {
+            inner,
+            db,
+            offline,
+            state_values: CacheCounter::default(),
+            account_states: CacheCounter::default(),
+            transactions: CacheCounter::default(),
+            account_sequences: CacheCounter::default(),
+        })
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            state_values: self.state_values.stats(),
+            account_states: self.account_states.stats(),
+            transactions: self.transactions.stats(),
+            account_sequences: self.account_sequences.stats(),
+        }
+    }
+
+    /// Reads the value from the cache, or from the inner interface if it isn't cached yet.
+    async fn get_or_fetch<S, F>(
+        &self,
+        counter: &CacheCounter,
+        key: &S::Key,
+        fetch: F,
+    ) -> Result<S::Value>
+    where
+        S: Schema,
+        F: Future<Output = Result<S::Value>>,
+    {
+        if let Some(value) = self.db.get::<S>(key)? {
+            counter.hit(1);
+            return Ok(value);
+        }
+        counter.miss(1);
+        if self.offline {
+            bail!(
+                "{:?} isn't in the debugger cache, and can't be fetched in offline mode",
+                key
+            );
+        }
+        let value = fetch.await?;
+        self.db.put::<S>(key, &value)?;
+        Ok(value)
+    }
+}
+
+#[async_trait::async_trait]
+impl AptosValidatorInterface for CachedDebuggerInterface {
+    async fn get_account_state_by_version(
+        &self,
+        account: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountState>> {
+        self.get_or_fetch::<AccountStateSchema, _>(
+            &self.account_states,
+            &(account, version),
+            self.inner.get_account_state_by_version(account, version),
+        )
+        .await
+    }
+
+    async fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        self.get_or_fetch::<StateValueSchema, _>(
+            &self.state_values,
+            &(state_key.clone(), version),
+            self.inner.get_state_value_by_version(state_key, version),
+        )
+        .await
+    }
+
+    async fn get_committed_transactions(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+        let (txns, txn_infos, _) = self
+            .get_committed_transactions_with_write_sets(start, limit)
+            .await?;
+        Ok((txns, txn_infos))
+    }
+
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)> {
+        let mut txns = Vec::with_capacity(limit as usize);
+        let mut txn_infos = Vec::with_capacity(limit as usize);
+        let mut write_sets = Vec::with_capacity(limit as usize);
+
+        // Serves the longest cached prefix of the range, and fetches the rest at once.
+        while (txns.len() as u64) < limit {
+            match self
+                .db
+                .get::<TransactionSchema>(&(start + txns.len() as u64))?
+            {
+                Some((txn, txn_info, write_set)) => {
+                    txns.push(txn);
+                    txn_infos.push(txn_info);
+                    write_sets.push(write_set);
+                },
+                None => break,
+            }
+        }
+        let num_cached = txns.len() as u64;
+        self.transactions.hit(num_cached);
+        if num_cached == limit {
+            return Ok((txns, txn_infos, write_sets));
+        }
+
+        let fetch_start = start + num_cached;
+        let fetch_limit = limit - num_cached;
+        self.transactions.miss(fetch_limit);
+        if self.offline {
+            bail!(
+                "Transactions {} to {} aren't in the debugger cache, and can't be fetched in offline mode",
+                fetch_start,
+                fetch_start + fetch_limit - 1
+            );
+        }
+        let (fetched_txns, fetched_txn_infos, fetched_write_sets) = self
+            .inner
+            .get_committed_transactions_with_write_sets(fetch_start, fetch_limit)
+            .await?;
+        ensure!(
+            fetched_txns.len() == fetched_txn_infos.len()
+                && fetched_txns.len() == fetched_write_sets.len(),
+            "Mismatched number of transactions, infos and write sets"
+        );
+
+        let batch = SchemaBatch::new();
+        for (version, value) in
+            (fetch_start..).zip(izip!(fetched_txns, fetched_txn_infos, fetched_write_sets))
+        {
+            batch.put::<TransactionSchema>(&version, &value)?;
+            let (txn, txn_info, write_set) = value;
+            txns.push(txn);
+            txn_infos.push(txn_info);
+            write_sets.push(write_set);
+        }
+        self.db.write_schemas(batch)?;
+
+        Ok((txns, txn_infos, write_sets))
+    }
+
+    async fn get_execution_fingerprints(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        // The fingerprints aren't cached, they are only read to be compared with a replay.
+        if self.offline {
+            bail!("The execution fingerprints can't be fetched in offline mode");
+        }
+        self.inner.get_execution_fingerprints(start, limit).await
+    }
+
+    async fn get_latest_version(&self) -> Result<Version> {
+        if self.offline {
+            bail!("The latest version can't be fetched in offline mode");
+        }
+        self.inner.get_latest_version().await
+    }
+
+    async fn get_version_by_account_sequence(
+        &self,
+        account: AccountAddress,
+        seq: u64,
+    ) -> Result<Option<Version>> {
+        // Only the committed transactions are cached, as the others may be committed later.
+        let key = (account, seq);
+        if let Some(version) = self.db.get::<AccountSequenceSchema>(&key)? {
+            self.account_sequences.hit(1);
+            return Ok(Some(version));
+        }
+        self.account_sequences.miss(1);
+        if self.offline {
+            bail!(
+                "{:?} isn't in the debugger cache, and can't be fetched in offline mode",
+                key
+            );
+        }
+        let version = self
+            .inner
+            .get_version_by_account_sequence(account, seq)
+            .await?;
+        if let Some(version) = version {
+            self.db.put::<AccountSequenceSchema>(&key, &version)?;
+        }
+        Ok(version)
+    }
+}
+
+#[derive(Default)]
+struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    fn hit(&self, count: u64) {
+        self.hits.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn miss(&self, count: u64) {
+        self.misses.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> HitRate {
+        HitRate {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HitRate {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl HitRate {
+    pub fn ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl fmt::Display for HitRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            self.ratio() * 100.0
+        )
+    }
+}
+
+/// The hits and misses of the cache since it was opened, by kind of read.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub state_values: HitRate,
+    pub account_states: HitRate,
+    pub transactions: HitRate,
+    pub account_sequences: HitRate,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "State values: {}", self.state_values)?;
+        writeln!(f, "Account states: {}", self.account_states)?;
+        writeln!(f, "Transactions: {}", self.transactions)?;
+        write!(f, "Account sequences: {}", self.account_sequences)
+    }
+}
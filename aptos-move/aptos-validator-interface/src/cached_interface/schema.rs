@@ -0,0 +1,183 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The schemas of the cache of `CachedDebuggerInterface`. The keys and values are all BCS
+//! encoded, except for the versions which are encoded big endian so they are iterated in order.
+
+use anyhow::Result;
+use aptos_schemadb::{
+    schema::{KeyCodec, Schema, ValueCodec},
+    ColumnFamilyName,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use std::convert::TryInto;
+
+pub(crate) const STATE_VALUE_CF_NAME: ColumnFamilyName = "state_value";
+pub(crate) const ACCOUNT_STATE_CF_NAME: ColumnFamilyName = "account_state";
+pub(crate) const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
+pub(crate) const ACCOUNT_SEQUENCE_CF_NAME: ColumnFamilyName = "account_sequence";
+pub(crate) const METADATA_CF_NAME: ColumnFamilyName = "metadata";
+
+/// The key of the metadata holding the target the cache is filled from.
+pub(crate) const TARGET_METADATA_KEY: &str = "target";
+
+/// The state values by key and version, `None` if the key doesn't exist at the version.
+#[derive(Debug)]
+pub(crate) struct StateValueSchema;
+
+impl Schema for StateValueSchema {
+    type Key = (StateKey, Version);
+    type Value = Option<StateValue>;
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = STATE_VALUE_CF_NAME;
+}
+
+impl KeyCodec<StateValueSchema> for (StateKey, Version) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<StateValueSchema> for Option<StateValue> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+/// The account states by address and version.
+#[derive(Debug)]
+pub(crate) struct AccountStateSchema;
+
+impl Schema for AccountStateSchema {
+    type Key = (AccountAddress, Version);
+    type Value = Option<AccountState>;
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = ACCOUNT_STATE_CF_NAME;
+}
+
+impl KeyCodec<AccountStateSchema> for (AccountAddress, Version) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<AccountStateSchema> for Option<AccountState> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+/// The committed transactions by version, along with their infos and write sets.
+#[derive(Debug)]
+pub(crate) struct TransactionSchema;
+
+impl Schema for TransactionSchema {
+    type Key = Version;
+    type Value = (Transaction, TransactionInfo, WriteSet);
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = TRANSACTION_CF_NAME;
+}
+
+impl KeyCodec<TransactionSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(Version::from_be_bytes(data.try_into()?))
+    }
+}
+
+impl ValueCodec<TransactionSchema> for (Transaction, TransactionInfo, WriteSet) {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+/// The versions of the committed transactions by sender and sequence number.
+#[derive(Debug)]
+pub(crate) struct AccountSequenceSchema;
+
+impl Schema for AccountSequenceSchema {
+    type Key = (AccountAddress, u64);
+    type Value = Version;
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = ACCOUNT_SEQUENCE_CF_NAME;
+}
+
+impl KeyCodec<AccountSequenceSchema> for (AccountAddress, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<AccountSequenceSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(Version::from_be_bytes(data.try_into()?))
+    }
+}
+
+/// The properties of the cache itself, by name.
+#[derive(Debug)]
+pub(crate) struct MetadataSchema;
+
+impl Schema for MetadataSchema {
+    type Key = String;
+    type Value = String;
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = METADATA_CF_NAME;
+}
+
+impl KeyCodec<MetadataSchema> for String {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<MetadataSchema> for String {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
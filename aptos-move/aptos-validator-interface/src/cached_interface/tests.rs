@@ -0,0 +1,180 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{AptosValidatorInterface, CachedDebuggerInterface, HitRate};
+use anyhow::{bail, Result};
+use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{ExecutionFingerprint, ExecutionStatus, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use std::sync::{Arc, Mutex};
+
+const TARGET: &str = "http://localhost:8080";
+
+/// An interface serving any range of transactions, recording the ranges it is asked for.
+#[derive(Default)]
+struct MockInterface {
+    requests: Mutex<Vec<(Version, u64)>>,
+}
+
+fn transaction_info(version: Version) -> TransactionInfo {
+    // The gas used tells the transactions apart
+    TransactionInfo::new(
+        HashValue::zero(),
+        HashValue::zero(),
+        HashValue::zero(),
+        None,
+        version,
+        ExecutionStatus::Success,
+    )
+}
+
+#[async_trait::async_trait]
+impl AptosValidatorInterface for MockInterface {
+    async fn get_account_state_by_version(
+        &self,
+        _account: AccountAddress,
+        _version: Version,
+    ) -> Result<Option<AccountState>> {
+        bail!("Not served by the mock interface")
+    }
+
+    async fn get_state_value_by_version(
+        &self,
+        _state_key: &StateKey,
+        _version: Version,
+    ) -> Result<Option<StateValue>> {
+        bail!("Not served by the mock interface")
+    }
+
+    async fn get_committed_transactions(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+        let (txns, txn_infos, _) = self
+            .get_committed_transactions_with_write_sets(start, limit)
+            .await?;
+        Ok((txns, txn_infos))
+    }
+
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)> {
+        self.requests.lock().unwrap().push((start, limit));
+        let versions = start..start + limit;
+        Ok((
+            versions
+                .clone()
+                .map(|_| Transaction::StateCheckpoint(HashValue::zero()))
+                .collect(),
+            versions.clone().map(transaction_info).collect(),
+            versions.map(|_| WriteSet::default()).collect(),
+        ))
+    }
+
+    async fn get_execution_fingerprints(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        bail!("Not served by the mock interface")
+    }
+
+    async fn get_latest_version(&self) -> Result<Version> {
+        bail!("Not served by the mock interface")
+    }
+
+    async fn get_version_by_account_sequence(
+        &self,
+        _account: AccountAddress,
+        _seq: u64,
+    ) -> Result<Option<Version>> {
+        bail!("Not served by the mock interface")
+    }
+}
+
+#[tokio::test]
+async fn test_offline_miss_fails_without_querying_the_target() {
+    let cache_path = TempPath::new();
+    let inner = Arc::new(MockInterface::default());
+    let cache =
+        CachedDebuggerInterface::open(inner.clone(), cache_path.path(), TARGET, true).unwrap();
+    assert!(cache.is_offline());
+
+    let err = cache
+        .get_committed_transactions_with_write_sets(10, 2)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("offline mode"), "{}", err);
+    assert!(cache
+        .get_state_value_by_version(&StateKey::raw(b"key".to_vec()), 10)
+        .await
+        .is_err());
+    assert!(inner.requests.lock().unwrap().is_empty());
+    assert_eq!(cache.stats().transactions, HitRate { hits: 0, misses: 2 });
+}
+
+#[tokio::test]
+async fn test_partially_cached_range() {
+    let cache_path = TempPath::new();
+    let inner = Arc::new(MockInterface::default());
+    {
+        let cache =
+            CachedDebuggerInterface::open(inner.clone(), cache_path.path(), TARGET, false).unwrap();
+        cache
+            .get_committed_transactions_with_write_sets(10, 2)
+            .await
+            .unwrap();
+
+        // Only the versions after the cached prefix are fetched
+        let (txns, txn_infos, write_sets) = cache
+            .get_committed_transactions_with_write_sets(10, 5)
+            .await
+            .unwrap();
+        assert_eq!(txns.len(), 5);
+        assert_eq!(write_sets.len(), 5);
+        let gas_used: Vec<_> = txn_infos.iter().map(|info| info.gas_used()).collect();
+        assert_eq!(gas_used, vec![10, 11, 12, 13, 14]);
+        assert_eq!(*inner.requests.lock().unwrap(), vec![(10, 2), (12, 3)]);
+        assert_eq!(cache.stats().transactions, HitRate { hits: 2, misses: 5 });
+    }
+
+    // The whole range is then served offline, but not beyond it
+    let cache =
+        CachedDebuggerInterface::open(inner.clone(), cache_path.path(), TARGET, true).unwrap();
+    let (_, txn_infos, _) = cache
+        .get_committed_transactions_with_write_sets(11, 4)
+        .await
+        .unwrap();
+    let gas_used: Vec<_> = txn_infos.iter().map(|info| info.gas_used()).collect();
+    assert_eq!(gas_used, vec![11, 12, 13, 14]);
+    assert!(cache
+        .get_committed_transactions_with_write_sets(11, 5)
+        .await
+        .is_err());
+    assert_eq!(inner.requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_cache_of_another_target_is_refused() {
+    let cache_path = TempPath::new();
+    let inner = Arc::new(MockInterface::default());
+    drop(CachedDebuggerInterface::open(inner.clone(), cache_path.path(), TARGET, false).unwrap());
+
+    assert!(CachedDebuggerInterface::open(
+        inner.clone(),
+        cache_path.path(),
+        "/opt/aptos/db",
+        false
+    )
+    .is_err());
+    CachedDebuggerInterface::open(inner, cache_path.path(), TARGET, true).unwrap();
+}
@@ -2,10 +2,15 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod cached_interface;
 mod rest_interface;
 mod storage_interface;
 
-pub use crate::{rest_interface::RestDebuggerInterface, storage_interface::DBDebuggerInterface};
+pub use crate::{
+    cached_interface::{CacheStats, CachedDebuggerInterface, HitRate},
+    rest_interface::RestDebuggerInterface,
+    storage_interface::DBDebuggerInterface,
+};
 use anyhow::{anyhow, Result};
 use aptos_state_view::TStateView;
 use aptos_types::{
@@ -17,7 +22,8 @@ use aptos_types::{
     state_store::{
         state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
     },
-    transaction::{Transaction, TransactionInfo, Version},
+    transaction::{ExecutionFingerprint, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use lru::LruCache;
 use move_binary_format::file_format::CompiledModule;
@@ -46,6 +52,20 @@ pub trait AptosValidatorInterface: Sync {
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)>;
 
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)>;
+
+    /// Returns the execution fingerprints recorded for the transactions in the range, when the
+    /// execution of the node diverged. The versions without a recorded fingerprint are skipped.
+    async fn get_execution_fingerprints(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>>;
+
     async fn get_latest_version(&self) -> Result<Version>;
 
     async fn get_version_by_account_sequence(
@@ -114,8 +134,13 @@ pub trait AptosValidatorInterface: Sync {
 }
 
 pub struct DebuggerStateView {
-    query_sender:
-        Mutex<UnboundedSender<(StateKey, Version, std::sync::mpsc::Sender<Option<Vec<u8>>>)>>,
+    query_sender: Mutex<
+        UnboundedSender<(
+            StateKey,
+            Version,
+            std::sync::mpsc::Sender<Result<Option<Vec<u8>>>>,
+        )>,
+    >,
     version: Version,
 }
 
@@ -124,7 +149,7 @@ async fn handler_thread<'a>(
     mut thread_receiver: UnboundedReceiver<(
         StateKey,
         Version,
-        std::sync::mpsc::Sender<Option<Vec<u8>>>,
+        std::sync::mpsc::Sender<Result<Option<Vec<u8>>>>,
     )>,
 ) {
     const M: usize = 1024 * 1024;
@@ -141,7 +166,7 @@ async fn handler_thread<'a>(
             };
 
         if let Some(val) = cache.lock().unwrap().get(&(key.clone(), version)) {
-            sender.send(val.clone()).unwrap();
+            sender.send(Ok(val.clone())).unwrap();
         } else {
             assert!(version > 0, "Expecting a non-genesis version");
             let db = db.clone();
@@ -150,9 +175,10 @@ async fn handler_thread<'a>(
                 let val = db
                     .get_state_value_by_version(&key, version - 1)
                     .await
-                    .ok()
-                    .and_then(|v| v.map(|s| s.into_bytes()));
-                cache.lock().unwrap().put((key, version), val.clone());
+                    .map(|v| v.map(|s| s.into_bytes()));
+                if let Ok(val) = &val {
+                    cache.lock().unwrap().put((key, version), val.clone());
+                }
                 sender.send(val)
             });
         }
@@ -180,7 +206,7 @@ impl DebuggerStateView {
         query_handler_locked
             .send((state_key.clone(), version, tx))
             .unwrap();
-        let bytes_opt = rx.recv()?;
+        let bytes_opt = rx.recv()??;
         Ok(bytes_opt.map(StateValue::new_legacy))
     }
 }
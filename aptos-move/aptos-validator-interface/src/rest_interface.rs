@@ -12,7 +12,8 @@ use aptos_types::{
     account_address::AccountAddress,
     account_state::AccountState,
     state_store::{state_key::StateKey, state_value::StateValue},
-    transaction::{Transaction, TransactionInfo, Version},
+    transaction::{ExecutionFingerprint, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use std::collections::BTreeMap;
 
@@ -70,8 +71,20 @@ impl AptosValidatorInterface for RestDebuggerInterface {
         start: Version,
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+        let (txns, txn_infos, _) = self
+            .get_committed_transactions_with_write_sets(start, limit)
+            .await?;
+        Ok((txns, txn_infos))
+    }
+
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)> {
         let mut txns = Vec::with_capacity(limit as usize);
         let mut txn_infos = Vec::with_capacity(limit as usize);
+        let mut write_sets = Vec::with_capacity(limit as usize);
 
         while txns.len() < limit as usize {
             self.0
@@ -85,11 +98,37 @@ impl AptosValidatorInterface for RestDebuggerInterface {
                 .for_each(|txn| {
                     txns.push(txn.transaction);
                     txn_infos.push(txn.info);
+                    write_sets.push(txn.changes);
                 });
             println!("Got {}/{} txns from RestApi.", txns.len(), limit);
         }
 
-        Ok((txns, txn_infos))
+        Ok((txns, txn_infos, write_sets))
+    }
+
+    async fn get_execution_fingerprints(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        let end = start + limit;
+        let mut fingerprints = vec![];
+        let mut next = start;
+
+        // The versions without a fingerprint are skipped, so a page can be short or empty
+        // without being the last one.
+        while next < end {
+            let page_size = (end - next).min(100);
+            let page = self
+                .0
+                .get_transaction_fingerprints_bcs(Some(next), Some(page_size as u16))
+                .await?
+                .into_inner();
+            fingerprints.extend(page);
+            next += page_size;
+        }
+
+        Ok(fingerprints)
     }
 
     async fn get_latest_version(&self) -> Result<Version> {
@@ -14,7 +14,8 @@ use aptos_types::{
     account_address::AccountAddress,
     account_state::AccountState,
     state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
-    transaction::{Transaction, TransactionInfo, Version},
+    transaction::{ExecutionFingerprint, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use std::{path::Path, sync::Arc};
 
@@ -79,6 +80,28 @@ impl AptosValidatorInterface for DBDebuggerInterface {
         Ok((txns, txn_infos))
     }
 
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)> {
+        let (txns, txn_infos) = self.get_committed_transactions(start, limit).await?;
+        let write_sets = self
+            .0
+            .get_write_set_iterator(start, limit)?
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(txns.len() == write_sets.len());
+        Ok((txns, txn_infos, write_sets))
+    }
+
+    async fn get_execution_fingerprints(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        self.0.get_execution_fingerprints(start, limit)
+    }
+
     async fn get_latest_version(&self) -> Result<Version> {
         let (version, _) = self
             .0
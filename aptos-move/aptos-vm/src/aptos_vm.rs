@@ -19,6 +19,7 @@ use crate::{
 };
 use anyhow::{anyhow, Result};
 use aptos_aggregator::delta_change_set::DeltaChangeSet;
+use aptos_block_partitioner::types::SubBlock;
 use aptos_crypto::HashValue;
 use aptos_framework::natives::code::PublishRequest;
 use aptos_gas::{
@@ -80,6 +81,8 @@ static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static CONFLICT_REPORT: OnceCell<bool> = OnceCell::new();
+static CROSS_BLOCK_CODE_CACHE: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
 
 pub static RAYON_EXEC_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
@@ -209,6 +212,36 @@ impl AptosVM {
         }
     }
 
+    /// Enables the conflict reports of the parallel execution of the blocks when invoked the
+    /// first time.
+    pub fn set_conflict_report_enabled_once() {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CONFLICT_REPORT.set(true).ok();
+    }
+
+    /// Get whether the conflicts of the parallel execution of the blocks should be reported
+    pub fn get_conflict_report_enabled() -> bool {
+        match CONFLICT_REPORT.get() {
+            Some(value) => *value,
+            None => false,
+        }
+    }
+
+    /// Enables the cache of the verified modules and scripts shared by the VMs of all the blocks
+    /// when invoked the first time.
+    pub fn set_cross_block_code_cache_enabled_once() {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CROSS_BLOCK_CODE_CACHE.set(true).ok();
+    }
+
+    /// Get whether the verified modules and scripts are cached across blocks
+    pub fn get_cross_block_code_cache_enabled() -> bool {
+        match CROSS_BLOCK_CODE_CACHE.get() {
+            Some(value) => *value,
+            None => false,
+        }
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.0)
     }
@@ -1519,21 +1552,22 @@ impl VMExecutor for AptosVM {
 
     fn execute_block_sharded<S: StateView + Sync + Send + 'static>(
         sharded_block_executor: &ShardedBlockExecutor<S>,
-        transactions: Vec<Transaction>,
+        sub_blocks: Vec<SubBlock>,
         state_view: Arc<S>,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let count = sub_blocks.iter().map(SubBlock::len).sum::<usize>();
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
         info!(
             log_context,
-            "Executing block, transaction count: {}",
-            transactions.len()
+            "Executing block, transaction count: {}, sub-block count: {}",
+            count,
+            sub_blocks.len()
         );
 
-        let count = transactions.len();
         let ret = sharded_block_executor.execute_block(
             state_view,
-            transactions,
+            sub_blocks,
             AptosVM::get_concurrency_level(),
             maybe_block_gas_limit,
         );
@@ -8,13 +8,16 @@ use crate::{
     adapter_common::{preprocess_transaction, PreprocessedTransaction},
     block_executor::vm_wrapper::AptosExecutorTask,
     counters::{
-        BLOCK_EXECUTOR_CONCURRENCY, BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS,
+        BLOCK_EXECUTOR_ABORTED_TXNS, BLOCK_EXECUTOR_CONCURRENCY,
+        BLOCK_EXECUTOR_DEPENDENCY_WAIT_SECONDS, BLOCK_EXECUTOR_EFFECTIVE_PARALLELISM,
+        BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS, BLOCK_EXECUTOR_INVALIDATED_READS,
         BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS,
     },
     AptosVM,
 };
-use aptos_aggregator::delta_change_set::DeltaOp;
+use aptos_aggregator::delta_change_set::{DeltaChangeSet, DeltaOp};
 use aptos_block_executor::{
+    conflict_report::BlockConflictReport,
     errors::Error,
     executor::BlockExecutor,
     task::{
@@ -23,10 +26,12 @@ use aptos_block_executor::{
     },
 };
 use aptos_infallible::Mutex;
+use aptos_logger::debug;
 use aptos_state_view::{StateView, StateViewId};
 use aptos_types::{
+    access_path::Path,
     executable::ExecutableTestType,
-    state_store::state_key::StateKey,
+    state_store::state_key::{StateKey, StateKeyInner},
     transaction::{Transaction, TransactionOutput, TransactionStatus},
     write_set::WriteOp,
 };
@@ -47,18 +52,21 @@ impl BlockExecutorTransaction for PreprocessedTransaction {
 pub(crate) struct AptosTransactionOutput {
     vm_output: Mutex<Option<VMOutput>>,
     committed_output: OnceCell<TransactionOutput>,
+    // The deltas of the transaction before their materialization
+    delta_change_set: DeltaChangeSet,
 }
 
 impl AptosTransactionOutput {
-    pub(crate) fn new(output: VMOutput) -> Self {
+    pub(crate) fn new(output: VMOutput, delta_change_set: DeltaChangeSet) -> Self {
         Self {
             vm_output: Mutex::new(Some(output)),
             committed_output: OnceCell::new(),
+            delta_change_set,
         }
     }
 
-    fn take_output(mut self) -> TransactionOutput {
-        match self.committed_output.take() {
+    fn take_output_with_deltas(mut self) -> (TransactionOutput, DeltaChangeSet) {
+        let output = match self.committed_output.take() {
             Some(output) => output,
             None => self
                 .vm_output
@@ -66,7 +74,8 @@ impl AptosTransactionOutput {
                 .take()
                 .expect("Output must be set")
                 .output_with_delta_writes(vec![]),
-        }
+        };
+        (output, self.delta_change_set)
     }
 }
 
@@ -75,7 +84,10 @@ impl BlockExecutorTransactionOutput for AptosTransactionOutput {
 
     /// Execution output for transactions that comes after SkipRest signal.
     fn skip_output() -> Self {
-        Self::new(VMOutput::empty_with_status(TransactionStatus::Retry))
+        Self::new(
+            VMOutput::empty_with_status(TransactionStatus::Retry),
+            DeltaChangeSet::empty(),
+        )
     }
 
     /// Should never be called after incorporate_delta_writes, as it
@@ -139,6 +151,84 @@ impl BlockAptosVM {
         concurrency_level: usize,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        Self::execute_block_with_deltas(
+            executor_thread_pool,
+            transactions,
+            state_view,
+            concurrency_level,
+            maybe_block_gas_limit,
+        )
+        .map(|outputs| outputs.into_iter().map(|(output, _)| output).collect())
+    }
+
+    /// Executes the block like [BlockAptosVM::execute_block], and also returns the aggregator
+    /// deltas of each transaction. The outputs include the writes of the deltas materialized
+    /// against the given state view, which the sharded block executor materializes again on
+    /// top of the outputs of the other shards.
+    pub fn execute_block_with_deltas<S: StateView + Sync>(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<Vec<(TransactionOutput, DeltaChangeSet)>, VMStatus> {
+        Self::execute_block_impl(
+            executor_thread_pool,
+            transactions,
+            state_view,
+            concurrency_level,
+            maybe_block_gas_limit,
+            AptosVM::get_conflict_report_enabled(),
+        )
+        .map(|(outputs, _)| outputs)
+    }
+
+    /// Executes the block like [BlockAptosVM::execute_block], and also returns the report of the
+    /// conflicts between its transactions. There is no report if the block wasn't executed in
+    /// parallel, i.e., with a concurrency level of 1 or when it publishes modules.
+    pub fn execute_block_with_conflict_report<S: StateView + Sync>(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<
+        (
+            Vec<TransactionOutput>,
+            Option<BlockConflictReport<StateKey>>,
+        ),
+        VMStatus,
+    > {
+        Self::execute_block_impl(
+            executor_thread_pool,
+            transactions,
+            state_view,
+            concurrency_level,
+            maybe_block_gas_limit,
+            true,
+        )
+        .map(|(outputs, conflict_report)| {
+            (
+                outputs.into_iter().map(|(output, _)| output).collect(),
+                conflict_report,
+            )
+        })
+    }
+
+    fn execute_block_impl<S: StateView + Sync>(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+        maybe_block_gas_limit: Option<u64>,
+        collect_conflict_report: bool,
+    ) -> Result<
+        (
+            Vec<(TransactionOutput, DeltaChangeSet)>,
+            Option<BlockConflictReport<StateKey>>,
+        ),
+        VMStatus,
+    > {
         let _timer = BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS.start_timer();
         // Verify the signatures of all the transactions in parallel.
         // This is time consuming so don't wait and do the checking
@@ -164,7 +254,7 @@ impl BlockAptosVM {
         }
 
         BLOCK_EXECUTOR_CONCURRENCY.set(concurrency_level as i64);
-        let executor = BlockExecutor::<
+        let mut executor = BlockExecutor::<
             PreprocessedTransaction,
             AptosExecutorTask<S>,
             S,
@@ -174,18 +264,25 @@ impl BlockAptosVM {
             executor_thread_pool,
             maybe_block_gas_limit,
         );
+        if collect_conflict_report {
+            executor = executor.with_conflict_report();
+        }
 
         let ret = executor.execute_block(state_view, signature_verified_block, state_view);
+        let conflict_report = executor.take_conflict_report();
+        if let Some(conflict_report) = &conflict_report {
+            report_conflicts(conflict_report);
+        }
 
         match ret {
             Ok(outputs) => {
-                let output_vec: Vec<TransactionOutput> = outputs
+                let output_vec: Vec<(TransactionOutput, DeltaChangeSet)> = outputs
                     .into_iter()
-                    .map(|output| output.take_output())
+                    .map(|output| output.take_output_with_deltas())
                     .collect();
 
                 // Flush the speculative logs of the committed transactions.
-                let pos = output_vec.partition_point(|o| !o.status().is_retry());
+                let pos = output_vec.partition_point(|(o, _)| !o.status().is_retry());
 
                 if state_view.id() != StateViewId::Miscellaneous {
                     // Speculation is disabled in Miscellaneous context, which is used by testing and
@@ -193,7 +290,7 @@ impl BlockAptosVM {
                     flush_speculative_logs(pos);
                 }
 
-                Ok(output_vec)
+                Ok((output_vec, conflict_report))
             },
             Err(Error::ModulePathReadWrite) => {
                 unreachable!("[Execution]: Must be handled by sequential fallback")
@@ -202,3 +299,49 @@ impl BlockAptosVM {
         }
     }
 }
+
+/// The number of most invalidated keys logged with the conflict report of a block
+const NUM_LOGGED_HOT_KEYS: usize = 10;
+
+/// Exports the conflict report of a block through the metrics and a debug log.
+fn report_conflicts(conflict_report: &BlockConflictReport<StateKey>) {
+    BLOCK_EXECUTOR_ABORTED_TXNS.observe(conflict_report.num_aborted_txns() as f64);
+    BLOCK_EXECUTOR_DEPENDENCY_WAIT_SECONDS
+        .observe(conflict_report.total_dependency_wait().as_secs_f64());
+    BLOCK_EXECUTOR_EFFECTIVE_PARALLELISM.observe(conflict_report.effective_parallelism());
+    for (state_key, count) in &conflict_report.invalidated_keys {
+        BLOCK_EXECUTOR_INVALIDATED_READS
+            .with_label_values(&[&resource_label(state_key)])
+            .inc_by(*count as u64);
+    }
+
+    debug!(
+        num_txns = conflict_report.num_txns(),
+        num_aborts = conflict_report.num_aborts(),
+        num_aborted_txns = conflict_report.num_aborted_txns(),
+        dependency_wait_ms = conflict_report.total_dependency_wait().as_millis() as u64,
+        wasted_execution_ms = conflict_report.wasted_execution_time().as_millis() as u64,
+        effective_parallelism = conflict_report.effective_parallelism(),
+        concurrency_level = conflict_report.concurrency_level,
+        "[Execution]: Block conflicts, hot keys: {:?}",
+        conflict_report.hot_keys(NUM_LOGGED_HOT_KEYS)
+    );
+}
+
+/// The label of the metrics of a state key: the type of the resource without its type arguments
+/// (to bound the cardinality of the metrics), or the kind of the state key for the others
+fn resource_label(state_key: &StateKey) -> String {
+    match state_key.inner() {
+        StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+            Path::Code(_) => "module".to_string(),
+            Path::Resource(struct_tag) | Path::ResourceGroup(struct_tag) => format!(
+                "{}::{}::{}",
+                struct_tag.address.short_str_lossless(),
+                struct_tag.module,
+                struct_tag.name
+            ),
+        },
+        StateKeyInner::TableItem { .. } => "table_item".to_string(),
+        StateKeyInner::Raw(_) => "raw".to_string(),
+    }
+}
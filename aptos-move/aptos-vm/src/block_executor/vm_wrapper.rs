@@ -68,6 +68,7 @@ impl<'a, S: 'a + StateView + Sync> ExecutorTask for AptosExecutorTask<'a, S> {
             .execute_single_transaction(txn, &self.vm.as_move_resolver(view), &log_context)
         {
             Ok((vm_status, mut vm_output, sender)) => {
+                let delta_change_set = vm_output.delta_change_set().clone();
                 if materialize_deltas {
                     // TODO: Integrate delta application failure.
                     vm_output = vm_output
@@ -97,9 +98,15 @@ impl<'a, S: 'a + StateView + Sync> ExecutorTask for AptosExecutorTask<'a, S> {
                         &log_context,
                         "Reconfiguration occurred: restart required".into()
                     );
-                    ExecutionStatus::SkipRest(AptosTransactionOutput::new(vm_output))
+                    ExecutionStatus::SkipRest(AptosTransactionOutput::new(
+                        vm_output,
+                        delta_change_set,
+                    ))
                 } else {
-                    ExecutionStatus::Success(AptosTransactionOutput::new(vm_output))
+                    ExecutionStatus::Success(AptosTransactionOutput::new(
+                        vm_output,
+                        delta_change_set,
+                    ))
                 }
             },
             Err(err) => ExecutionStatus::Abort(err),
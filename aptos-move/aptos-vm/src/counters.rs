@@ -42,6 +42,51 @@ pub static BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS: Lazy<Histogram> = Lazy
     .unwrap()
 });
 
+pub static BLOCK_EXECUTOR_ABORTED_TXNS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "block_executor_aborted_txns",
+        // metric description
+        "The number of transactions of a block aborted at least once by the parallel execution",
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 2.0, /*count=*/ 15).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static BLOCK_EXECUTOR_DEPENDENCY_WAIT_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "block_executor_dependency_wait_seconds",
+        // metric description
+        "The total time in seconds the transactions of a block waited on their dependencies in \
+        the parallel execution",
+        exponential_buckets(/*start=*/ 1e-4, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static BLOCK_EXECUTOR_EFFECTIVE_PARALLELISM: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "block_executor_effective_parallelism",
+        // metric description
+        "The average number of transactions of a block usefully executed at the same time by \
+        the parallel execution",
+        vec![0.5, 1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0, 48.0, 64.0],
+    )
+    .unwrap()
+});
+
+/// Count the reads invalidated by the parallel execution, by type of resource
+pub static BLOCK_EXECUTOR_INVALIDATED_READS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "block_executor_invalidated_reads",
+        "Number of reads invalidated by the parallel execution, by type of resource",
+        &["resource"]
+    )
+    .unwrap()
+});
+
 /// Count the number of transactions that brake invariants of VM.
 pub static TRANSACTIONS_INVARIANT_VIOLATION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -137,3 +182,23 @@ pub static TXN_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Count the lookups in the cross-block cache of verified code, by kind of code and result
+pub static CROSS_BLOCK_CODE_CACHE_LOOKUPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_vm_cross_block_code_cache_lookups",
+        "Number of lookups in the cross-block cache of verified code, by kind and result",
+        &["kind", "result"]
+    )
+    .unwrap()
+});
+
+/// Count the flushes of the cross-block cache of verified code, by reason
+pub static CROSS_BLOCK_CODE_CACHE_FLUSHES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_vm_cross_block_code_cache_flushes",
+        "Number of flushes of the cross-block cache of verified code, by reason",
+        &["reason"]
+    )
+    .unwrap()
+});
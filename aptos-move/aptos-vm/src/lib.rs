@@ -123,6 +123,7 @@ mod verifier;
 
 pub use crate::aptos_vm::AptosVM;
 use crate::sharded_block_executor::ShardedBlockExecutor;
+use aptos_block_partitioner::types::SubBlock;
 use aptos_state_view::StateView;
 use aptos_types::{
     transaction::{SignedTransaction, Transaction, TransactionOutput, VMValidatorResult},
@@ -155,10 +156,11 @@ pub trait VMExecutor: Send + Sync {
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus>;
 
-    /// Executes a block of transactions using a sharded block executor and returns the results.
+    /// Executes a block partitioned into sub-blocks using a sharded block executor and returns
+    /// the results, in the order of the sub-blocks.
     fn execute_block_sharded<S: StateView + Sync + Send + 'static>(
         sharded_block_executor: &ShardedBlockExecutor<S>,
-        transactions: Vec<Transaction>,
+        sub_blocks: Vec<SubBlock>,
         state_view: Arc<S>,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus>;
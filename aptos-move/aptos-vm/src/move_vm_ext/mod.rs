@@ -6,6 +6,7 @@
 mod resolver;
 mod respawned_session;
 mod session;
+mod verified_code_cache;
 mod vm;
 
 pub use crate::move_vm_ext::{
@@ -0,0 +1,118 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters::{CROSS_BLOCK_CODE_CACHE_FLUSHES, CROSS_BLOCK_CODE_CACHE_LOOKUPS};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_types::on_chain_config::Features;
+use move_binary_format::file_format::{CompiledModule, CompiledScript};
+use move_bytecode_verifier::VerifierConfig;
+use move_vm_runtime::verified_code_cache::VerifiedCodeCache;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Arc};
+
+/// The maximum number of modules, and of scripts, kept by the cache before it is flushed.
+const MAX_CACHED_ENTRIES: usize = 10_000;
+
+/// The maximum number of environments with a cache before all the caches are flushed.
+const MAX_CACHED_ENVIRONMENTS: usize = 4;
+
+static CROSS_BLOCK_CODE_CACHES: Lazy<
+    Mutex<HashMap<CodeCacheEnvironment, Arc<CrossBlockCodeCache>>>,
+> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Everything the outcome of the deserialization and verification of the code depends on.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CodeCacheEnvironment {
+    pub features: Features,
+    pub gas_feature_version: u64,
+    pub verifier_config: VerifierConfig,
+    pub max_binary_format_version: u32,
+}
+
+/// The modules and scripts verified by the VMs of the previous blocks, so the code called in
+/// every block (e.g. the framework) isn't deserialized and verified again for each block.
+///
+/// The code is keyed by the hash of its bytes, so publishing a module, including speculatively
+/// in the versioned modules of the parallel execution, never invalidates anything: the new bytes
+/// are looked up under their own hash, and the old ones can only be hit by a transaction which
+/// still reads them. The verification also depends on the on-chain features and configs though,
+/// so there is one cache per environment. The VMs created concurrently for different state
+/// views (e.g. around a feature flip, or by the API simulating at an older version) don't
+/// evict each other's caches.
+pub struct CrossBlockCodeCache {
+    modules: RwLock<HashMap<[u8; 32], Arc<CompiledModule>>>,
+    scripts: RwLock<HashMap<[u8; 32], Arc<CompiledScript>>>,
+}
+
+impl CrossBlockCodeCache {
+    fn new() -> Self {
+        Self {
+            modules: RwLock::new(HashMap::new()),
+            scripts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cache for the environment, creating it if there is none yet. All the caches
+    /// are flushed once there are too many environments.
+    pub(crate) fn for_environment(environment: CodeCacheEnvironment) -> Arc<Self> {
+        let mut caches = CROSS_BLOCK_CODE_CACHES.lock();
+        if let Some(cache) = caches.get(&environment) {
+            return cache.clone();
+        }
+        if caches.len() >= MAX_CACHED_ENVIRONMENTS {
+            CROSS_BLOCK_CODE_CACHE_FLUSHES
+                .with_label_values(&["too_many_environments"])
+                .inc();
+            caches.clear();
+        }
+        let cache = Arc::new(Self::new());
+        caches.insert(environment, cache.clone());
+        cache
+    }
+
+    fn get<T>(
+        kind: &str,
+        entries: &RwLock<HashMap<[u8; 32], Arc<T>>>,
+        hash: &[u8; 32],
+    ) -> Option<Arc<T>> {
+        let entry = entries.read().get(hash).cloned();
+        CROSS_BLOCK_CODE_CACHE_LOOKUPS
+            .with_label_values(&[kind, if entry.is_some() { "hit" } else { "miss" }])
+            .inc();
+        entry
+    }
+
+    fn insert<T>(
+        kind: &str,
+        entries: &RwLock<HashMap<[u8; 32], Arc<T>>>,
+        hash: [u8; 32],
+        entry: Arc<T>,
+    ) {
+        let mut entries = entries.write();
+        if entries.len() >= MAX_CACHED_ENTRIES {
+            CROSS_BLOCK_CODE_CACHE_FLUSHES
+                .with_label_values(&[&format!("{}_cache_full", kind)])
+                .inc();
+            entries.clear();
+        }
+        entries.insert(hash, entry);
+    }
+}
+
+impl VerifiedCodeCache for CrossBlockCodeCache {
+    fn get_module(&self, hash: &[u8; 32]) -> Option<Arc<CompiledModule>> {
+        Self::get("module", &self.modules, hash)
+    }
+
+    fn insert_module(&self, hash: [u8; 32], module: Arc<CompiledModule>) {
+        Self::insert("module", &self.modules, hash, module)
+    }
+
+    fn get_script(&self, hash: &[u8; 32]) -> Option<Arc<CompiledScript>> {
+        Self::get("script", &self.scripts, hash)
+    }
+
+    fn insert_script(&self, hash: [u8; 32], script: Arc<CompiledScript>) {
+        Self::insert("script", &self.scripts, hash, script)
+    }
+}
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    move_vm_ext::{MoveResolverExt, SessionExt, SessionId},
+    move_vm_ext::{
+        verified_code_cache::{CodeCacheEnvironment, CrossBlockCodeCache},
+        MoveResolverExt, SessionExt, SessionId,
+    },
     natives::aptos_natives,
 };
 use aptos_framework::natives::{
@@ -56,26 +59,44 @@ impl MoveVmExt {
         let type_size_limit = true;
 
         let verifier_config = verifier_config(&features, &timed_features);
+        let verified_code_cache = if crate::AptosVM::get_cross_block_code_cache_enabled() {
+            Some(CrossBlockCodeCache::for_environment(CodeCacheEnvironment {
+                features: features.clone(),
+                gas_feature_version,
+                verifier_config: verifier_config.clone(),
+                max_binary_format_version,
+            }))
+        } else {
+            None
+        };
         let features = Arc::new(features);
 
-        Ok(Self {
-            inner: MoveVM::new_with_config(
-                aptos_natives(
-                    native_gas_params,
-                    abs_val_size_gas_params,
-                    gas_feature_version,
-                    timed_features,
-                    features.clone(),
-                ),
-                VMConfig {
-                    verifier: verifier_config,
-                    max_binary_format_version,
-                    paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
-                    enable_invariant_violation_check_in_swap_loc,
-                    type_size_limit,
-                    max_value_nest_depth: Some(128),
-                },
+        let natives = aptos_natives(
+            native_gas_params,
+            abs_val_size_gas_params,
+            gas_feature_version,
+            timed_features,
+            features.clone(),
+        );
+        let vm_config = VMConfig {
+            verifier: verifier_config,
+            max_binary_format_version,
+            paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
+            enable_invariant_violation_check_in_swap_loc,
+            type_size_limit,
+            max_value_nest_depth: Some(128),
+        };
+        let inner = match verified_code_cache {
+            Some(verified_code_cache) => MoveVM::new_with_config_and_verified_code_cache(
+                natives,
+                vm_config,
+                verified_code_cache,
             )?,
+            None => MoveVM::new_with_config(natives, vm_config)?,
+        };
+
+        Ok(Self {
+            inner,
             chain_id,
             features,
         })
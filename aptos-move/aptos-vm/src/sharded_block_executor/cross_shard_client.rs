@@ -0,0 +1,56 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_block_partitioner::types::{ShardId, TxnIndex};
+use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// The writes of a transaction, sent by its executor shard to the shards depending on them
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrossShardMsg {
+    pub txn_index: TxnIndex,
+    pub writes: Vec<(StateKey, Option<StateValue>)>,
+}
+
+/// Sends and receives the cross-shard messages of an executor shard, during the execution of a
+/// block
+pub trait CrossShardClient: Send {
+    fn send_cross_shard_msg(&self, shard_id: ShardId, msg: CrossShardMsg) -> Result<()>;
+
+    /// Blocks until a message is received from any of the other shards
+    fn receive_cross_shard_msg(&self) -> Result<CrossShardMsg>;
+}
+
+/// A cross-shard client of a shard running in the local process, connected to the other shards
+/// by channels
+pub struct LocalCrossShardClient {
+    message_txs: Vec<Sender<CrossShardMsg>>,
+    message_rx: Receiver<CrossShardMsg>,
+}
+
+impl LocalCrossShardClient {
+    /// Creates the clients of all the shards (in order by shard id), connected to each other
+    pub fn create_clients(num_shards: usize) -> Vec<Self> {
+        let (message_txs, message_rxs): (Vec<_>, Vec<_>) =
+            (0..num_shards).map(|_| channel()).unzip();
+        message_rxs
+            .into_iter()
+            .map(|message_rx| Self {
+                message_txs: message_txs.clone(),
+                message_rx,
+            })
+            .collect()
+    }
+}
+
+impl CrossShardClient for LocalCrossShardClient {
+    fn send_cross_shard_msg(&self, shard_id: ShardId, msg: CrossShardMsg) -> Result<()> {
+        Ok(self.message_txs[shard_id].send(msg)?)
+    }
+
+    fn receive_cross_shard_msg(&self) -> Result<CrossShardMsg> {
+        Ok(self.message_rx.recv()?)
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_block_partitioner::types::TxnIndex;
+use aptos_state_view::{StateView, StateViewId, TStateView};
+use aptos_types::{
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    write_set::{TransactionWrite, WriteSet},
+};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// A view of the state of the block, overlaying the writes of some transactions of the block
+/// (e.g., the writes an executor shard executed or received from the other shards) on the base
+/// state view. The latest write of each state key (by transaction index) is visible.
+pub struct CrossShardStateView<'a, S> {
+    base_view: &'a S,
+    // The latest write of each state key, and the index of the transaction that wrote it
+    writes: HashMap<StateKey, (TxnIndex, Option<StateValue>)>,
+}
+
+impl<'a, S: StateView> CrossShardStateView<'a, S> {
+    pub fn new(base_view: &'a S) -> Self {
+        Self {
+            base_view,
+            writes: HashMap::new(),
+        }
+    }
+
+    /// Records the write of the state key by the transaction at the given index, unless a later
+    /// transaction already wrote it
+    pub fn apply_write(
+        &mut self,
+        txn_index: TxnIndex,
+        state_key: StateKey,
+        state_value: Option<StateValue>,
+    ) {
+        match self.writes.entry(state_key) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().0 <= txn_index {
+                    entry.insert((txn_index, state_value));
+                }
+            },
+            Entry::Vacant(entry) => {
+                entry.insert((txn_index, state_value));
+            },
+        }
+    }
+
+    pub fn apply_write_set(&mut self, txn_index: TxnIndex, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set.iter() {
+            self.apply_write(txn_index, state_key.clone(), write_op.as_state_value());
+        }
+    }
+}
+
+impl<'a, S: StateView> TStateView for CrossShardStateView<'a, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        match self.writes.get(state_key) {
+            Some((_, state_value)) => Ok(state_value.clone()),
+            None => self.base_view.get_state_value(state_key),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base_view.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base_view.get_usage()
+    }
+}
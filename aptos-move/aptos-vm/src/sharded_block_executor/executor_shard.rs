@@ -2,10 +2,11 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{block_executor::BlockAptosVM, sharded_block_executor::ExecutorShardCommand};
+use crate::sharded_block_executor::{
+    execute_sub_blocks, ExecutorShardCommand, TransactionOutputWithDeltas,
+};
 use aptos_logger::trace;
 use aptos_state_view::StateView;
-use aptos_types::transaction::TransactionOutput;
 use aptos_vm_logging::disable_speculative_logging;
 use move_core_types::vm_status::VMStatus;
 use std::sync::{
@@ -13,13 +14,13 @@ use std::sync::{
     Arc,
 };
 
-/// A remote block executor that receives transactions from a channel and executes them in parallel.
-/// Currently it runs in the local machine and it will be further extended to run in a remote machine.
+/// An executor shard running in the local process, which receives its sub-blocks from a channel
+/// and executes them in order (see [execute_sub_blocks]).
 pub struct ExecutorShard<S: StateView + Sync + Send + 'static> {
     shard_id: usize,
     executor_thread_pool: Arc<rayon::ThreadPool>,
     command_rx: Receiver<ExecutorShardCommand<S>>,
-    result_tx: Sender<Result<Vec<TransactionOutput>, VMStatus>>,
+    result_tx: Sender<Result<Vec<TransactionOutputWithDeltas>, VMStatus>>,
 }
 
 impl<S: StateView + Sync + Send + 'static> ExecutorShard<S> {
@@ -28,7 +29,7 @@ impl<S: StateView + Sync + Send + 'static> ExecutorShard<S> {
         shard_id: usize,
         num_executor_threads: usize,
         command_rx: Receiver<ExecutorShardCommand<S>>,
-        result_tx: Sender<Result<Vec<TransactionOutput>, VMStatus>>,
+        result_tx: Sender<Result<Vec<TransactionOutputWithDeltas>, VMStatus>>,
     ) -> Self {
         let executor_thread_pool = Arc::new(
             rayon::ThreadPoolBuilder::new()
@@ -54,21 +55,24 @@ impl<S: StateView + Sync + Send + 'static> ExecutorShard<S> {
         loop {
             let command = self.command_rx.recv().unwrap();
             match command {
-                ExecutorShardCommand::ExecuteBlock(
+                ExecutorShardCommand::ExecuteSubBlocks(
                     state_view,
-                    transactions,
+                    sub_blocks,
+                    cross_shard_client,
                     concurrency_level_per_shard,
                     maybe_block_gas_limit,
                 ) => {
                     trace!(
-                        "Shard {} received ExecuteBlock command of block size {} ",
+                        "Shard {} received ExecuteSubBlocks command of {} sub-blocks",
                         self.shard_id,
-                        transactions.len()
+                        sub_blocks.len()
                     );
-                    let ret = BlockAptosVM::execute_block(
-                        self.executor_thread_pool.clone(),
-                        transactions,
+                    let ret = execute_sub_blocks(
+                        self.shard_id,
+                        sub_blocks,
                         state_view.as_ref(),
+                        cross_shard_client.as_ref(),
+                        self.executor_thread_pool.clone(),
                         concurrency_level_per_shard,
                         maybe_block_gas_limit,
                     );
@@ -0,0 +1,142 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sharded_block_executor::{
+    cross_shard_client::LocalCrossShardClient, executor_shard::ExecutorShard, ExecutorClient,
+    ExecutorShardCommand, ShardSubBlock, TransactionOutputWithDeltas,
+};
+use aptos_logger::{error, info, trace};
+use aptos_state_view::StateView;
+use move_core_types::vm_status::VMStatus;
+use std::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+/// An executor client that runs the executor shards as threads of the local process, and
+/// dispatches the sub-blocks to them through channels. The shards exchange their cross-shard
+/// messages through channels as well.
+pub struct LocalExecutorClient<S: StateView + Sync + Send + 'static> {
+    num_executor_shards: usize,
+    command_txs: Vec<Sender<ExecutorShardCommand<S>>>,
+    shard_threads: Vec<thread::JoinHandle<()>>,
+    result_rxs: Vec<Receiver<Result<Vec<TransactionOutputWithDeltas>, VMStatus>>>,
+}
+
+impl<S: StateView + Sync + Send + 'static> LocalExecutorClient<S> {
+    pub fn new(num_executor_shards: usize, executor_threads_per_shard: Option<usize>) -> Self {
+        assert!(num_executor_shards > 0, "num_executor_shards must be > 0");
+        let executor_threads_per_shard = executor_threads_per_shard.unwrap_or_else(|| {
+            (num_cpus::get() as f64 / num_executor_shards as f64).ceil() as usize
+        });
+        let mut command_txs = vec![];
+        let mut result_rxs = vec![];
+        let mut shard_join_handles = vec![];
+        for i in 0..num_executor_shards {
+            let (transactions_tx, transactions_rx) = std::sync::mpsc::channel();
+            let (result_tx, result_rx) = std::sync::mpsc::channel();
+            command_txs.push(transactions_tx);
+            result_rxs.push(result_rx);
+            shard_join_handles.push(spawn_executor_shard(
+                num_executor_shards,
+                i,
+                executor_threads_per_shard,
+                transactions_rx,
+                result_tx,
+            ));
+        }
+        info!(
+            "Creating a new LocalExecutorClient with {} shards and concurrency per shard {}",
+            num_executor_shards, executor_threads_per_shard
+        );
+        Self {
+            num_executor_shards,
+            command_txs,
+            shard_threads: shard_join_handles,
+            result_rxs,
+        }
+    }
+}
+
+impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for LocalExecutorClient<S> {
+    fn num_shards(&self) -> usize {
+        self.num_executor_shards
+    }
+
+    fn execute_block(
+        &self,
+        state_view: Arc<S>,
+        sub_blocks: Vec<Vec<ShardSubBlock>>,
+        concurrency_level_per_shard: usize,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<Vec<Vec<TransactionOutputWithDeltas>>, VMStatus> {
+        // The cross-shard channels are created for each block, so that the messages left over
+        // by a failed block aren't received during the next one
+        let cross_shard_clients = LocalCrossShardClient::create_clients(self.num_executor_shards);
+        for (i, (sub_blocks, cross_shard_client)) in
+            sub_blocks.into_iter().zip(cross_shard_clients).enumerate()
+        {
+            self.command_txs[i]
+                .send(ExecutorShardCommand::ExecuteSubBlocks(
+                    state_view.clone(),
+                    sub_blocks,
+                    Box::new(cross_shard_client),
+                    concurrency_level_per_shard,
+                    maybe_block_gas_limit,
+                ))
+                .unwrap();
+        }
+        // wait for all executor shards to send the result back, in order by shard id
+        trace!("LocalExecutorClient Waiting for results");
+        let mut results = vec![];
+        for i in 0..self.num_executor_shards {
+            results.push(self.result_rxs[i].recv().unwrap());
+        }
+        results.into_iter().collect()
+    }
+}
+
+impl<S: StateView + Sync + Send + 'static> Drop for LocalExecutorClient<S> {
+    /// Best effort stops all the executor shards and waits for the thread to finish.
+    fn drop(&mut self) {
+        // send stop command to all executor shards
+        for command_tx in self.command_txs.iter() {
+            if let Err(e) = command_tx.send(ExecutorShardCommand::Stop) {
+                error!("Failed to send stop command to executor shard: {:?}", e);
+            }
+        }
+
+        // wait for all executor shards to stop
+        for shard_thread in self.shard_threads.drain(..) {
+            shard_thread.join().unwrap_or_else(|e| {
+                error!("Failed to join executor shard thread: {:?}", e);
+            });
+        }
+    }
+}
+
+fn spawn_executor_shard<S: StateView + Sync + Send + 'static>(
+    num_executor_shards: usize,
+    shard_id: usize,
+    concurrency_level: usize,
+    command_rx: Receiver<ExecutorShardCommand<S>>,
+    result_tx: Sender<Result<Vec<TransactionOutputWithDeltas>, VMStatus>>,
+) -> thread::JoinHandle<()> {
+    // create and start a new executor shard in a separate thread
+    thread::Builder::new()
+        .name(format!("executor-shard-{}", shard_id))
+        .spawn(move || {
+            let executor_shard = ExecutorShard::new(
+                num_executor_shards,
+                shard_id,
+                concurrency_level,
+                command_rx,
+                result_tx,
+            );
+            executor_shard.start();
+        })
+        .unwrap()
+}
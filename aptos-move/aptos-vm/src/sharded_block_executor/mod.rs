@@ -2,146 +2,313 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sharded_block_executor::executor_shard::ExecutorShard;
-use aptos_block_partitioner::{BlockPartitioner, UniformPartitioner};
-use aptos_logger::{error, info, trace};
+use crate::sharded_block_executor::cross_shard_state_view::CrossShardStateView;
+use aptos_aggregator::delta_change_set::DeltaChangeSet;
+use aptos_block_partitioner::types::{ShardId, SubBlock, TxnIndex};
 use aptos_state_view::StateView;
-use aptos_types::transaction::{Transaction, TransactionOutput};
+use aptos_types::{
+    state_store::state_key::StateKey,
+    transaction::{analyzed_transaction::StorageLocation, Transaction, TransactionOutput},
+    vm_status::StatusCode,
+};
 use move_core_types::vm_status::VMStatus;
+use serde::{Deserialize, Serialize};
 use std::{
-    marker::PhantomData,
-    sync::{
-        mpsc::{Receiver, Sender},
-        Arc,
-    },
-    thread,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
 };
 
+mod cross_shard_client;
+mod cross_shard_state_view;
 mod executor_shard;
+mod local_executor_client;
+mod sub_block_executor;
+
+pub use cross_shard_client::{CrossShardClient, CrossShardMsg, LocalCrossShardClient};
+pub use local_executor_client::LocalExecutorClient;
+pub use sub_block_executor::execute_sub_blocks;
 
 /// A wrapper around sharded block executors that manages multiple shards and aggregates the results.
 pub struct ShardedBlockExecutor<S: StateView + Sync + Send + 'static> {
-    num_executor_shards: usize,
-    partitioner: Arc<dyn BlockPartitioner>,
-    command_txs: Vec<Sender<ExecutorShardCommand<S>>>,
-    shard_threads: Vec<thread::JoinHandle<()>>,
-    result_rxs: Vec<Receiver<Result<Vec<TransactionOutput>, VMStatus>>>,
-    phantom: PhantomData<S>,
+    executor_client: Box<dyn ExecutorClient<S>>,
 }
 
 pub enum ExecutorShardCommand<S: StateView + Sync + Send + 'static> {
-    ExecuteBlock(Arc<S>, Vec<Transaction>, usize, Option<u64>),
+    ExecuteSubBlocks(
+        Arc<S>,
+        Vec<ShardSubBlock>,
+        Box<dyn CrossShardClient>,
+        usize,
+        Option<u64>,
+    ),
     Stop,
 }
 
+/// Dispatches the sub-blocks of a block to the executor shards, and returns the outputs of
+/// each shard. The shards may run in the local process (see [LocalExecutorClient]) or in
+/// remote processes.
+pub trait ExecutorClient<S: StateView + Sync + Send + 'static>: Send {
+    /// The number of executor shards
+    fn num_shards(&self) -> usize;
+
+    /// Executes the sub-blocks of each shard (in order by shard id) against the given state
+    /// view, exchanging the cross-shard messages between the shards (see [execute_sub_blocks]),
+    /// and returns the outputs of each shard in the order of its sub-blocks.
+    fn execute_block(
+        &self,
+        state_view: Arc<S>,
+        sub_blocks: Vec<Vec<ShardSubBlock>>,
+        concurrency_level_per_shard: usize,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<Vec<Vec<TransactionOutputWithDeltas>>, VMStatus>;
+}
+
+/// A sub-block of a partitioned block, as executed by its executor shard
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShardSubBlock {
+    /// The index of the first transaction of the sub-block in the block
+    pub start_index: TxnIndex,
+    pub transactions: Vec<Transaction>,
+    /// The transactions of the other shards whose writes must be received before the sub-block
+    /// is executed
+    pub dependencies: BTreeSet<TxnIndex>,
+    /// The transactions of the sub-block whose writes the other shards depend on
+    pub dependents: BTreeMap<TxnIndex, CrossShardDependents>,
+    /// The state keys the transactions of the sub-block are expected to read or write (leaving
+    /// out the hints with wildcards), so that a remote shard can fetch their values ahead of the
+    /// execution
+    pub read_hints: Vec<StateKey>,
+}
+
+impl ShardSubBlock {
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+}
+
+/// The shards depending on the writes of a transaction
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CrossShardDependents {
+    pub shards: BTreeSet<ShardId>,
+    /// The state keys the transaction is expected to write, whose values are sent even if the
+    /// transaction doesn't write them (e.g., if it fails)
+    pub write_hints: Vec<StateKey>,
+}
+
+/// The output of a transaction executed by a shard, with the aggregator deltas it contains.
+/// The shard materializes the deltas against its own view of the state, which misses the deltas
+/// of the other shards, so they are materialized again once the outputs of all the shards are
+/// known.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionOutputWithDeltas {
+    pub output: TransactionOutput,
+    pub delta_change_set: DeltaChangeSet,
+}
+
 impl<S: StateView + Sync + Send + 'static> ShardedBlockExecutor<S> {
+    /// Creates a sharded block executor whose shards are threads of the local process
     pub fn new(num_executor_shards: usize, executor_threads_per_shard: Option<usize>) -> Self {
-        assert!(num_executor_shards > 0, "num_executor_shards must be > 0");
-        let executor_threads_per_shard = executor_threads_per_shard.unwrap_or_else(|| {
-            (num_cpus::get() as f64 / num_executor_shards as f64).ceil() as usize
-        });
-        let mut command_txs = vec![];
-        let mut result_rxs = vec![];
-        let mut shard_join_handles = vec![];
-        for i in 0..num_executor_shards {
-            let (transactions_tx, transactions_rx) = std::sync::mpsc::channel();
-            let (result_tx, result_rx) = std::sync::mpsc::channel();
-            command_txs.push(transactions_tx);
-            result_rxs.push(result_rx);
-            shard_join_handles.push(spawn_executor_shard(
-                num_executor_shards,
-                i,
-                executor_threads_per_shard,
-                transactions_rx,
-                result_tx,
-            ));
-        }
-        info!(
-            "Creating a new ShardedBlockExecutor with {} shards and concurrency per shard {}",
-            num_executor_shards, executor_threads_per_shard
-        );
-        Self {
+        Self::with_executor_client(Box::new(LocalExecutorClient::new(
             num_executor_shards,
-            partitioner: Arc::new(UniformPartitioner {}),
-            command_txs,
-            shard_threads: shard_join_handles,
-            result_rxs,
-            phantom: PhantomData,
-        }
+            executor_threads_per_shard,
+        )))
+    }
+
+    /// Creates a sharded block executor that dispatches the sub-blocks through the given client
+    pub fn with_executor_client(executor_client: Box<dyn ExecutorClient<S>>) -> Self {
+        Self { executor_client }
+    }
+
+    pub fn num_shards(&self) -> usize {
+        self.executor_client.num_shards()
     }
 
-    /// Execute a block of transactions in parallel by splitting the block into num_remote_executors partitions and
-    /// dispatching each partition to a remote executor shard.
+    /// Executes a block partitioned by the sharded block partitioner, and returns the outputs in
+    /// the order of the sub-blocks. The i-th sub-block is executed by the shard i % num_shards,
+    /// and waits for the writes of the transactions of the other shards it depends on. The result
+    /// matches the sequential execution of the transactions of the sub-blocks as long as their
+    /// read and write hints are accurate, whatever the number of shards.
+    ///
+    /// The block gas limit can't be enforced across the shards, which don't know the gas used by
+    /// each other, so the blocks with a block gas limit are rejected.
     pub fn execute_block(
         &self,
         state_view: Arc<S>,
-        block: Vec<Transaction>,
+        sub_blocks: Vec<SubBlock>,
         concurrency_level_per_shard: usize,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
-        let block_partitions = self.partitioner.partition(block, self.num_executor_shards);
-        // Number of partitions might be smaller than the number of executor shards in case of
-        // block size is smaller than number of executor shards.
-        let num_partitions = block_partitions.len();
-        for (i, transactions) in block_partitions.into_iter().enumerate() {
-            self.command_txs[i]
-                .send(ExecutorShardCommand::ExecuteBlock(
-                    state_view.clone(),
-                    transactions,
-                    concurrency_level_per_shard,
-                    maybe_block_gas_limit,
-                ))
-                .unwrap();
+        if maybe_block_gas_limit.is_some() {
+            return Err(invariant_violation(
+                "Sharded execution doesn't support the block gas limit".to_string(),
+            ));
         }
-        // wait for all remote executors to send the result back and append them in order by shard id
-        let mut aggregated_results = vec![];
-        trace!("ShardedBlockExecutor Waiting for results");
-        for i in 0..num_partitions {
-            let result = self.result_rxs[i].recv().unwrap();
-            aggregated_results.extend(result?);
+        let num_shards = self.executor_client.num_shards();
+        let num_txns = sub_blocks.iter().map(SubBlock::len).sum();
+        let shard_sub_blocks = assign_sub_blocks_to_shards(sub_blocks, num_shards, num_txns)?;
+        // The indices of the transactions executed by each shard, in execution order
+        let shard_txn_indices: Vec<Vec<TxnIndex>> = shard_sub_blocks
+            .iter()
+            .map(|sub_blocks| {
+                sub_blocks
+                    .iter()
+                    .flat_map(|sub_block| {
+                        sub_block.start_index..sub_block.start_index + sub_block.len()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let shard_outputs = self.executor_client.execute_block(
+            state_view.clone(),
+            shard_sub_blocks,
+            concurrency_level_per_shard,
+            maybe_block_gas_limit,
+        )?;
+
+        let mut outputs: Vec<Option<TransactionOutputWithDeltas>> =
+            (0..num_txns).map(|_| None).collect();
+        for (txn_indices, shard_outputs) in shard_txn_indices.into_iter().zip(shard_outputs) {
+            if txn_indices.len() != shard_outputs.len() {
+                return Err(invariant_violation(format!(
+                    "A shard returned {} outputs for {} transactions",
+                    shard_outputs.len(),
+                    txn_indices.len()
+                )));
+            }
+            for (txn_index, output) in txn_indices.into_iter().zip(shard_outputs) {
+                outputs[txn_index] = Some(output);
+            }
         }
-        Ok(aggregated_results)
+        let outputs = outputs
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invariant_violation("Missing outputs of the shards".to_string()))?;
+        materialize_deltas(state_view.as_ref(), outputs)
     }
 }
 
-impl<S: StateView + Sync + Send + 'static> Drop for ShardedBlockExecutor<S> {
-    /// Best effort stops all the executor shards and waits for the thread to finish.
-    fn drop(&mut self) {
-        // send stop command to all executor shards
-        for command_tx in self.command_txs.iter() {
-            if let Err(e) = command_tx.send(ExecutorShardCommand::Stop) {
-                error!("Failed to send stop command to executor shard: {:?}", e);
+/// Assigns the i-th sub-block to the shard i % num_shards, and resolves the transactions of the
+/// other shards each sub-block depends on, and the shards depending on each transaction.
+fn assign_sub_blocks_to_shards(
+    sub_blocks: Vec<SubBlock>,
+    num_shards: usize,
+    num_txns: usize,
+) -> Result<Vec<Vec<ShardSubBlock>>, VMStatus> {
+    let mut txn_shards = vec![0; num_txns];
+    for (i, sub_block) in sub_blocks.iter().enumerate() {
+        if sub_block.start_index + sub_block.len() > num_txns {
+            return Err(invariant_violation(format!(
+                "Sub-block {} is out of the bounds of the block",
+                i
+            )));
+        }
+        txn_shards[sub_block.start_index..sub_block.start_index + sub_block.len()]
+            .fill(i % num_shards);
+    }
+
+    let mut dependent_shards: HashMap<TxnIndex, BTreeSet<ShardId>> = HashMap::new();
+    let mut shard_dependencies = vec![];
+    for (i, sub_block) in sub_blocks.iter().enumerate() {
+        let shard_id = i % num_shards;
+        let mut dependencies = BTreeSet::new();
+        for txn in sub_block.transactions_with_deps() {
+            for txn_index in txn.cross_shard_dependencies.depends_on() {
+                // The dependencies must precede the sub-block, or the shards would wait forever
+                if *txn_index >= sub_block.start_index {
+                    return Err(invariant_violation(format!(
+                        "Sub-block {} depends on the later transaction {}",
+                        i, txn_index
+                    )));
+                }
+                if txn_shards[*txn_index] != shard_id {
+                    dependencies.insert(*txn_index);
+                    dependent_shards
+                        .entry(*txn_index)
+                        .or_default()
+                        .insert(shard_id);
+                }
             }
         }
+        shard_dependencies.push(dependencies);
+    }
 
-        // wait for all executor shards to stop
-        for shard_thread in self.shard_threads.drain(..) {
-            shard_thread.join().unwrap_or_else(|e| {
-                error!("Failed to join executor shard thread: {:?}", e);
-            });
+    let mut shard_sub_blocks: Vec<Vec<ShardSubBlock>> = (0..num_shards).map(|_| vec![]).collect();
+    for (i, (sub_block, dependencies)) in sub_blocks.into_iter().zip(shard_dependencies).enumerate()
+    {
+        let mut dependents = BTreeMap::new();
+        let mut transactions = Vec::with_capacity(sub_block.len());
+        let mut read_hints = BTreeSet::new();
+        for (txn_index, txn) in (sub_block.start_index..).zip(sub_block.transactions) {
+            for location in txn.txn.read_hints().iter().chain(txn.txn.write_hints()) {
+                if let StorageLocation::Specific(state_key) = location {
+                    read_hints.insert(state_key.clone());
+                }
+            }
+            if let Some(shards) = dependent_shards.remove(&txn_index) {
+                let write_hints = txn
+                    .txn
+                    .write_hints()
+                    .iter()
+                    .filter_map(|location| match location {
+                        StorageLocation::Specific(state_key) => Some(state_key.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                dependents.insert(txn_index, CrossShardDependents {
+                    shards,
+                    write_hints,
+                });
+            }
+            transactions.push(txn.txn.into_inner());
         }
+        shard_sub_blocks[i % num_shards].push(ShardSubBlock {
+            start_index: sub_block.start_index,
+            transactions,
+            dependencies,
+            dependents,
+            read_hints: read_hints.into_iter().collect(),
+        });
+    }
+    Ok(shard_sub_blocks)
+}
+
+/// Materializes the deltas of the outputs again, in order, on top of the writes of the preceding
+/// transactions of all the shards.
+fn materialize_deltas<S: StateView>(
+    state_view: &S,
+    outputs: Vec<TransactionOutputWithDeltas>,
+) -> Result<Vec<TransactionOutput>, VMStatus> {
+    let mut materialized_view = CrossShardStateView::new(state_view);
+    let mut materialized_outputs = Vec::with_capacity(outputs.len());
+    for (txn_index, output) in outputs.into_iter().enumerate() {
+        let TransactionOutputWithDeltas {
+            output,
+            delta_change_set,
+        } = output;
+        let output = if delta_change_set.is_empty() {
+            output
+        } else {
+            let (write_set, events, gas_used, status) = output.unpack();
+            let mut write_set_mut = write_set.into_mut();
+            for (state_key, write_op) in delta_change_set.try_materialize(&materialized_view)? {
+                write_set_mut.insert((state_key, write_op));
+            }
+            let write_set = write_set_mut.freeze().map_err(|_| {
+                invariant_violation("Error when freezing materialized deltas".to_string())
+            })?;
+            TransactionOutput::new(write_set, events, gas_used, status)
+        };
+        materialized_view.apply_write_set(txn_index, output.write_set());
+        materialized_outputs.push(output);
     }
+    Ok(materialized_outputs)
 }
 
-fn spawn_executor_shard<S: StateView + Sync + Send + 'static>(
-    num_executor_shards: usize,
-    shard_id: usize,
-    concurrency_level: usize,
-    command_rx: Receiver<ExecutorShardCommand<S>>,
-    result_tx: Sender<Result<Vec<TransactionOutput>, VMStatus>>,
-) -> thread::JoinHandle<()> {
-    // create and start a new executor shard in a separate thread
-    thread::Builder::new()
-        .name(format!("executor-shard-{}", shard_id))
-        .spawn(move || {
-            let executor_shard = ExecutorShard::new(
-                num_executor_shards,
-                shard_id,
-                concurrency_level,
-                command_rx,
-                result_tx,
-            );
-            executor_shard.start();
-        })
-        .unwrap()
+fn invariant_violation(message: String) -> VMStatus {
+    VMStatus::Error(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR, Some(message))
 }
@@ -0,0 +1,207 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block_executor::BlockAptosVM,
+    sharded_block_executor::{
+        cross_shard_client::{CrossShardClient, CrossShardMsg},
+        cross_shard_state_view::CrossShardStateView,
+        ShardSubBlock, TransactionOutputWithDeltas,
+    },
+};
+use aptos_aggregator::delta_change_set::DeltaChangeSet;
+use aptos_block_partitioner::types::{ShardId, TxnIndex};
+use aptos_logger::trace;
+use aptos_state_view::{StateView, TStateView};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::TransactionOutput,
+    vm_status::StatusCode,
+    write_set::TransactionWrite,
+};
+use move_core_types::vm_status::VMStatus;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+/// Executes the sub-blocks of an executor shard in order. Before a sub-block is executed, the
+/// writes of the transactions of the other shards it depends on are received, and after it's
+/// executed, the writes of its transactions the other shards depend on are sent to them.
+///
+/// The dependencies are derived from the read and write hints of the transactions, so the
+/// outputs only match the sequential execution of the block if the hints are accurate.
+pub fn execute_sub_blocks<S: StateView + Sync>(
+    shard_id: ShardId,
+    sub_blocks: Vec<ShardSubBlock>,
+    state_view: &S,
+    cross_shard_client: &dyn CrossShardClient,
+    executor_thread_pool: Arc<rayon::ThreadPool>,
+    concurrency_level: usize,
+    maybe_block_gas_limit: Option<u64>,
+) -> Result<Vec<TransactionOutputWithDeltas>, VMStatus> {
+    let mut executor = SubBlockExecutor {
+        shard_id,
+        cross_shard_view: CrossShardStateView::new(state_view),
+        pending_msgs: BTreeMap::new(),
+        received_txns: HashSet::new(),
+        cross_shard_client,
+        executor_thread_pool,
+        concurrency_level,
+        maybe_block_gas_limit,
+    };
+
+    let mut outputs = vec![];
+    for (i, sub_block) in sub_blocks.iter().enumerate() {
+        match executor.execute_sub_block(sub_block) {
+            Ok(sub_block_outputs) => outputs.extend(sub_block_outputs),
+            Err(error) => {
+                // Unblock the shards waiting for the writes of this shard. Their outputs are
+                // discarded with the block anyway.
+                for sub_block in &sub_blocks[i..] {
+                    for (txn_index, dependents) in &sub_block.dependents {
+                        for dependent_shard in &dependents.shards {
+                            let _ = cross_shard_client.send_cross_shard_msg(
+                                *dependent_shard,
+                                CrossShardMsg {
+                                    txn_index: *txn_index,
+                                    writes: vec![],
+                                },
+                            );
+                        }
+                    }
+                }
+                return Err(error);
+            },
+        }
+    }
+    Ok(outputs)
+}
+
+struct SubBlockExecutor<'a, S> {
+    shard_id: ShardId,
+    // The writes of the transactions executed by the shard, and of the ones received from the
+    // other shards that precede the next sub-block
+    cross_shard_view: CrossShardStateView<'a, S>,
+    // The writes received from the other shards that follow the next sub-block
+    pending_msgs: BTreeMap<TxnIndex, Vec<(StateKey, Option<StateValue>)>>,
+    received_txns: HashSet<TxnIndex>,
+    cross_shard_client: &'a dyn CrossShardClient,
+    executor_thread_pool: Arc<rayon::ThreadPool>,
+    concurrency_level: usize,
+    maybe_block_gas_limit: Option<u64>,
+}
+
+impl<'a, S: StateView + Sync> SubBlockExecutor<'a, S> {
+    fn execute_sub_block(
+        &mut self,
+        sub_block: &ShardSubBlock,
+    ) -> Result<Vec<TransactionOutputWithDeltas>, VMStatus> {
+        if sub_block.is_empty() {
+            return Ok(vec![]);
+        }
+
+        for txn_index in &sub_block.dependencies {
+            while !self.received_txns.contains(txn_index) {
+                let msg = self
+                    .cross_shard_client
+                    .receive_cross_shard_msg()
+                    .map_err(cross_shard_error)?;
+                self.received_txns.insert(msg.txn_index);
+                self.pending_msgs.insert(msg.txn_index, msg.writes);
+            }
+        }
+        // Only the writes of the transactions preceding the sub-block are visible to it, the
+        // later ones are kept for the next sub-blocks
+        let later_msgs = self.pending_msgs.split_off(&sub_block.start_index);
+        for (txn_index, writes) in std::mem::replace(&mut self.pending_msgs, later_msgs) {
+            for (state_key, state_value) in writes {
+                self.cross_shard_view
+                    .apply_write(txn_index, state_key, state_value);
+            }
+        }
+        trace!(
+            "Shard {} executing the sub-block at {} with {} transactions",
+            self.shard_id,
+            sub_block.start_index,
+            sub_block.len()
+        );
+
+        let outputs = BlockAptosVM::execute_block_with_deltas(
+            self.executor_thread_pool.clone(),
+            sub_block.transactions.clone(),
+            &self.cross_shard_view,
+            self.concurrency_level,
+            self.maybe_block_gas_limit,
+        )?;
+
+        for (txn_index, dependents) in &sub_block.dependents {
+            let writes = self.writes_of_txn(
+                &outputs[..=*txn_index - sub_block.start_index],
+                &dependents.write_hints,
+            )?;
+            for dependent_shard in &dependents.shards {
+                self.cross_shard_client
+                    .send_cross_shard_msg(*dependent_shard, CrossShardMsg {
+                        txn_index: *txn_index,
+                        writes: writes.clone(),
+                    })
+                    .map_err(cross_shard_error)?;
+            }
+        }
+
+        for (txn_index, (output, _)) in (sub_block.start_index..).zip(outputs.iter()) {
+            self.cross_shard_view
+                .apply_write_set(txn_index, output.write_set());
+        }
+        Ok(outputs
+            .into_iter()
+            .map(|(output, delta_change_set)| TransactionOutputWithDeltas {
+                output,
+                delta_change_set,
+            })
+            .collect())
+    }
+
+    /// Returns the writes of the last transaction of the outputs, and the values of the keys it
+    /// was expected to write but didn't as of its execution
+    fn writes_of_txn(
+        &self,
+        outputs: &[(TransactionOutput, DeltaChangeSet)],
+        write_hints: &[StateKey],
+    ) -> Result<Vec<(StateKey, Option<StateValue>)>, VMStatus> {
+        let (preceding_outputs, txn_output) = outputs.split_at(outputs.len() - 1);
+        let write_set = txn_output[0].0.write_set();
+        let mut writes: Vec<_> = write_set
+            .iter()
+            .map(|(state_key, write_op)| (state_key.clone(), write_op.as_state_value()))
+            .collect();
+        for state_key in write_hints {
+            if write_set.get(state_key).is_some() {
+                continue;
+            }
+            let state_value = match preceding_outputs
+                .iter()
+                .rev()
+                .find_map(|(output, _)| output.write_set().get(state_key))
+            {
+                Some(write_op) => write_op.as_state_value(),
+                None => self
+                    .cross_shard_view
+                    .get_state_value(state_key)
+                    .map_err(|error| {
+                        VMStatus::Error(StatusCode::STORAGE_ERROR, Some(error.to_string()))
+                    })?,
+            };
+            writes.push((state_key.clone(), state_value));
+        }
+        Ok(writes)
+    }
+}
+
+fn cross_shard_error(error: anyhow::Error) -> VMStatus {
+    VMStatus::Error(
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+        Some(format!("Cross-shard messaging failed: {}", error)),
+    )
+}
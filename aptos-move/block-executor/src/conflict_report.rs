@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_mvhashmap::types::TxnIndex;
+use dashmap::DashMap;
+use std::{
+    hash::Hash,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// The conflicts encountered by a transaction during the parallel execution of its block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxnConflictStats {
+    /// The number of times the transaction was aborted (and re-executed) after failing
+    /// validation.
+    pub num_aborts: u32,
+    /// The time spent waiting for the re-execution of lower transactions it read from.
+    pub dependency_wait: Duration,
+    /// The time spent executing all the incarnations of the transaction.
+    pub execution_time: Duration,
+    /// The time spent executing the last (committed) incarnation of the transaction.
+    pub committed_execution_time: Duration,
+}
+
+/// A report of the conflicts encountered during the parallel execution of a block (Block-STM):
+/// which transactions were aborted, which keys made them abort, and how much of the
+/// parallelism was lost to them.
+#[derive(Clone, Debug)]
+pub struct BlockConflictReport<K> {
+    /// The conflict statistics of each transaction of the block, in order.
+    pub txn_stats: Vec<TxnConflictStats>,
+    /// The keys whose reads were invalidated, and how many times, most invalidated first.
+    pub invalidated_keys: Vec<(K, usize)>,
+    /// The number of threads executing the block.
+    pub concurrency_level: usize,
+    /// The wall-clock time of the parallel execution of the block.
+    pub wall_clock_time: Duration,
+}
+
+impl<K> BlockConflictReport<K> {
+    pub fn num_txns(&self) -> usize {
+        self.txn_stats.len()
+    }
+
+    /// The total number of aborts of the transactions of the block.
+    pub fn num_aborts(&self) -> u64 {
+        self.txn_stats
+            .iter()
+            .map(|stats| stats.num_aborts as u64)
+            .sum()
+    }
+
+    /// The number of transactions aborted at least once.
+    pub fn num_aborted_txns(&self) -> usize {
+        self.txn_stats
+            .iter()
+            .filter(|stats| stats.num_aborts > 0)
+            .count()
+    }
+
+    pub fn total_dependency_wait(&self) -> Duration {
+        self.txn_stats
+            .iter()
+            .map(|stats| stats.dependency_wait)
+            .sum()
+    }
+
+    /// The time spent executing the incarnations that were aborted.
+    pub fn wasted_execution_time(&self) -> Duration {
+        self.txn_stats
+            .iter()
+            .map(|stats| {
+                stats
+                    .execution_time
+                    .saturating_sub(stats.committed_execution_time)
+            })
+            .sum()
+    }
+
+    /// The average number of transactions usefully executed at the same time, i.e., the time
+    /// it would take to execute the committed incarnations one after the other, divided by the
+    /// wall-clock time of the execution. It's at most the concurrency level, and decreases with
+    /// the re-executions and the dependency waits (as well as the overhead of Block-STM).
+    pub fn effective_parallelism(&self) -> f64 {
+        if self.wall_clock_time.is_zero() {
+            return 0.0;
+        }
+        let committed_execution_time: Duration = self
+            .txn_stats
+            .iter()
+            .map(|stats| stats.committed_execution_time)
+            .sum();
+        committed_execution_time.as_secs_f64() / self.wall_clock_time.as_secs_f64()
+    }
+
+    /// Returns the (at most) `n` most invalidated keys.
+    pub fn hot_keys(&self, n: usize) -> &[(K, usize)] {
+        &self.invalidated_keys[..n.min(self.invalidated_keys.len())]
+    }
+}
+
+/// Records the conflicts of the transactions during a parallel execution, concurrently.
+pub(crate) struct ConflictTracker<K> {
+    num_aborts: Vec<AtomicU32>,
+    dependency_wait_nanos: Vec<AtomicU64>,
+    execution_nanos: Vec<AtomicU64>,
+    last_execution_nanos: Vec<AtomicU64>,
+    invalidated_keys: DashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone> ConflictTracker<K> {
+    pub(crate) fn new(num_txns: usize) -> Self {
+        Self {
+            num_aborts: (0..num_txns).map(|_| AtomicU32::new(0)).collect(),
+            dependency_wait_nanos: (0..num_txns).map(|_| AtomicU64::new(0)).collect(),
+            execution_nanos: (0..num_txns).map(|_| AtomicU64::new(0)).collect(),
+            last_execution_nanos: (0..num_txns).map(|_| AtomicU64::new(0)).collect(),
+            invalidated_keys: DashMap::new(),
+        }
+    }
+
+    pub(crate) fn record_execution(&self, txn_idx: TxnIndex, duration: Duration) {
+        let nanos = duration.as_nanos() as u64;
+        self.execution_nanos[txn_idx as usize].fetch_add(nanos, Ordering::Relaxed);
+        self.last_execution_nanos[txn_idx as usize].store(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_abort<'a>(
+        &self,
+        txn_idx: TxnIndex,
+        invalidated_keys: impl Iterator<Item = &'a K>,
+    ) where
+        K: 'a,
+    {
+        self.num_aborts[txn_idx as usize].fetch_add(1, Ordering::Relaxed);
+        for key in invalidated_keys {
+            *self.invalidated_keys.entry(key.clone()).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn record_dependency_wait(&self, txn_idx: TxnIndex, duration: Duration) {
+        self.dependency_wait_nanos[txn_idx as usize]
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn into_report(
+        self,
+        concurrency_level: usize,
+        wall_clock_time: Duration,
+    ) -> BlockConflictReport<K> {
+        let txn_stats = (0..self.num_aborts.len())
+            .map(|idx| TxnConflictStats {
+                num_aborts: self.num_aborts[idx].load(Ordering::Relaxed),
+                dependency_wait: Duration::from_nanos(
+                    self.dependency_wait_nanos[idx].load(Ordering::Relaxed),
+                ),
+                execution_time: Duration::from_nanos(
+                    self.execution_nanos[idx].load(Ordering::Relaxed),
+                ),
+                committed_execution_time: Duration::from_nanos(
+                    self.last_execution_nanos[idx].load(Ordering::Relaxed),
+                ),
+            })
+            .collect();
+        let mut invalidated_keys: Vec<_> = self.invalidated_keys.into_iter().collect();
+        invalidated_keys.sort_by(|(_, count1), (_, count2)| count2.cmp(count1));
+        BlockConflictReport {
+            txn_stats,
+            invalidated_keys,
+            concurrency_level,
+            wall_clock_time,
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::{BlockConflictReport, ConflictTracker},
     counters,
     counters::{
         PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
@@ -11,10 +12,11 @@ use crate::{
     errors::*,
     scheduler::{DependencyStatus, Scheduler, SchedulerTask, Wave},
     task::{ExecutionStatus, ExecutorTask, Transaction, TransactionOutput},
-    txn_last_input_output::TxnLastInputOutput,
+    txn_last_input_output::{ReadDescriptor, TxnLastInputOutput},
     view::{LatestView, MVHashMapView},
 };
 use aptos_aggregator::delta_change_set::{deserialize, serialize};
+use aptos_infallible::Mutex;
 use aptos_logger::{debug, info};
 use aptos_mvhashmap::{
     types::{MVDataError, MVDataOutput, TxnIndex, Version},
@@ -33,6 +35,7 @@ use std::{
         mpsc::{Receiver, Sender},
         Arc,
     },
+    time::Instant,
 };
 
 #[derive(Debug)]
@@ -41,12 +44,16 @@ enum CommitRole {
     Worker(Receiver<TxnIndex>),
 }
 
-pub struct BlockExecutor<T, E, S, X> {
+pub struct BlockExecutor<T: Transaction, E, S, X> {
     // number of active concurrent tasks, corresponding to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
     concurrency_level: usize,
     executor_thread_pool: Arc<ThreadPool>,
     maybe_block_gas_limit: Option<u64>,
+    // Whether to record the conflicts of the parallel execution, and the report of the last
+    // parallel execution if so.
+    collect_conflict_report: bool,
+    conflict_report: Mutex<Option<BlockConflictReport<T::Key>>>,
     phantom: PhantomData<(T, E, S, X)>,
 }
 
@@ -73,10 +80,27 @@ where
             concurrency_level,
             executor_thread_pool,
             maybe_block_gas_limit,
+            collect_conflict_report: false,
+            conflict_report: Mutex::new(None),
             phantom: PhantomData,
         }
     }
 
+    /// Records the conflicts encountered during the parallel executions of blocks, which adds
+    /// some overhead. The report of the last one is available through
+    /// [BlockExecutor::take_conflict_report].
+    pub fn with_conflict_report(mut self) -> Self {
+        self.collect_conflict_report = true;
+        self
+    }
+
+    /// Returns the conflict report of the last parallel execution of a block, if the conflicts
+    /// are recorded. There is no report if the block was executed sequentially (including when
+    /// the parallel execution fell back to the sequential execution).
+    pub fn take_conflict_report(&self) -> Option<BlockConflictReport<T::Key>> {
+        self.conflict_report.lock().take()
+    }
+
     fn execute(
         &self,
         version: Version,
@@ -86,20 +110,25 @@ where
         scheduler: &Scheduler,
         executor: &E,
         base_view: &S,
+        conflict_tracker: Option<&ConflictTracker<T::Key>>,
     ) -> SchedulerTask {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
         let (idx_to_execute, incarnation) = version;
         let txn = &signature_verified_block[idx_to_execute as usize];
 
-        let speculative_view = MVHashMapView::new(versioned_cache, scheduler);
+        let speculative_view = MVHashMapView::new(versioned_cache, scheduler, conflict_tracker);
 
         // VM execution.
+        let execution_start = Instant::now();
         let execute_result = executor.execute_transaction(
             &LatestView::<T, S, X>::new_mv_view(base_view, &speculative_view, idx_to_execute),
             txn,
             idx_to_execute,
             false,
         );
+        if let Some(conflict_tracker) = conflict_tracker {
+            conflict_tracker.record_execution(idx_to_execute, execution_start.elapsed());
+        }
         let mut prev_modified_keys = last_input_output.modified_keys(idx_to_execute);
 
         // For tracking whether the recent execution wrote outside of the previous write/delta set.
@@ -168,6 +197,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Value, X>,
         scheduler: &Scheduler,
+        conflict_tracker: Option<&ConflictTracker<T::Key>>,
     ) -> SchedulerTask {
         use MVDataError::*;
         use MVDataOutput::*;
@@ -178,7 +208,7 @@ where
             .read_set(idx_to_validate)
             .expect("[BlockSTM]: Prior read-set must be recorded");
 
-        let valid = read_set.iter().all(|r| {
+        let is_read_valid = |r: &ReadDescriptor<T::Key>| {
             match versioned_cache.fetch_data(r.path(), idx_to_validate) {
                 Ok(Versioned(version, _)) => r.validate_version(version),
                 Ok(Resolved(value)) => r.validate_resolved(value),
@@ -195,12 +225,24 @@ where
                 // is also preferable as it allows testing for this scenario.
                 Err(DeltaApplicationFailure) => r.validate_delta_application_failure(),
             }
-        });
+        };
+        let valid = read_set.iter().all(is_read_valid);
 
         let aborted = !valid && scheduler.try_abort(idx_to_validate, incarnation);
 
         if aborted {
             counters::SPECULATIVE_ABORT_COUNT.inc();
+            if let Some(conflict_tracker) = conflict_tracker {
+                // The reads are validated again to find all the invalidated keys, which may
+                // include keys written after the validation failed.
+                conflict_tracker.record_abort(
+                    idx_to_validate,
+                    read_set
+                        .iter()
+                        .filter(|r| !is_read_valid(r))
+                        .map(|r| r.path()),
+                );
+            }
 
             // Any logs from the aborted execution should be cleared and not reported.
             clear_speculative_txn_logs(idx_to_validate as usize);
@@ -341,6 +383,7 @@ where
         scheduler: &Scheduler,
         base_view: &S,
         role: CommitRole,
+        conflict_tracker: Option<&ConflictTracker<T::Key>>,
     ) {
         // Make executor for each task. TODO: fast concurrent executor.
         let init_timer = VM_INIT_SECONDS.start_timer();
@@ -386,6 +429,7 @@ where
                     last_input_output,
                     versioned_cache,
                     scheduler,
+                    conflict_tracker,
                 ),
                 SchedulerTask::ExecutionTask(version_to_execute, None) => self.execute(
                     version_to_execute,
@@ -395,6 +439,7 @@ where
                     scheduler,
                     &executor,
                     base_view,
+                    conflict_tracker,
                 ),
                 SchedulerTask::ExecutionTask(_, Some(condvar)) => {
                     let (lock, cvar) = &*condvar;
@@ -447,6 +492,9 @@ where
         let num_txns = signature_verified_block.len() as u32;
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
+        let conflict_tracker = self
+            .collect_conflict_report
+            .then(|| ConflictTracker::new(num_txns as usize));
 
         let mut roles: Vec<CommitRole> = vec![];
         let mut senders: Vec<Sender<u32>> = Vec::with_capacity(self.concurrency_level - 1);
@@ -463,6 +511,7 @@ where
         roles.push(CommitRole::Coordinator(senders));
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
+        let execution_start = Instant::now();
         self.executor_thread_pool.scope(|s| {
             for _ in 0..self.concurrency_level {
                 let role = roles.pop().expect("Role must be set for all threads");
@@ -475,10 +524,12 @@ where
                         &scheduler,
                         base_view,
                         role,
+                        conflict_tracker.as_ref(),
                     );
                 });
             }
         });
+        let wall_clock_time = execution_start.elapsed();
         drop(timer);
 
         let num_txns = num_txns as usize;
@@ -489,6 +540,10 @@ where
             counters::MODULE_PUBLISHING_FALLBACK_COUNT.inc();
             Some(Error::ModulePathReadWrite)
         } else {
+            if let Some(conflict_tracker) = conflict_tracker {
+                *self.conflict_report.lock() =
+                    Some(conflict_tracker.into_report(self.concurrency_level, wall_clock_time));
+            }
             let mut ret = None;
             for idx in 0..num_txns {
                 match last_input_output.take_output(idx as TxnIndex) {
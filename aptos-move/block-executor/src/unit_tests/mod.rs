@@ -163,6 +163,55 @@ fn cycle_transactions() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn conflict_report() {
+    // Every transaction reads and writes the same key, so that the parallel execution aborts.
+    let key = KeyType(random::<[u8; 32]>(), false);
+    let transactions: Vec<_> = (0..WRITES_PER_KEY)
+        .map(|_| Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![key]],
+            writes_and_deltas: vec![(vec![(key, random_value(false))], vec![])],
+        })
+        .collect();
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>, ValueType<Vec<u8>>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+
+    let executor = BlockExecutor::<
+        Transaction<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        Task<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        DeltaDataView<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        ExecutableTestType,
+    >::new(num_cpus::get(), executor_thread_pool, None)
+    .with_conflict_report();
+    let output = executor.execute_transactions_parallel((), &transactions, &data_view);
+    ExpectedOutput::generate_baseline(&transactions, None, None).assert_output(&output);
+
+    let report = executor.take_conflict_report().unwrap();
+    assert_eq!(report.num_txns(), transactions.len());
+    assert_eq!(report.concurrency_level, num_cpus::get());
+    // The only key read by the transactions is the only one that can be invalidated
+    assert!(report.invalidated_keys.len() <= 1);
+    for (invalidated_key, count) in &report.invalidated_keys {
+        assert_eq!(invalidated_key, &key);
+        assert!(*count as u64 <= report.num_aborts());
+    }
+    for stats in &report.txn_stats {
+        assert!(stats.execution_time >= stats.committed_execution_time);
+    }
+    assert!(report.effective_parallelism() <= report.concurrency_level as f64);
+
+    // The report is only returned once
+    assert!(executor.take_conflict_report().is_none());
+}
+
 const NUM_BLOCKS: u64 = 10;
 const TXN_PER_BLOCK: u64 = 100;
 
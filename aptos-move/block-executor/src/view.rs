@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::ConflictTracker,
     counters,
     scheduler::{DependencyResult, DependencyStatus, Scheduler},
     task::Transaction,
@@ -23,7 +24,7 @@ use aptos_types::{
     write_set::TransactionWrite,
 };
 use aptos_vm_logging::{log_schema::AdapterLogSchema, prelude::*};
-use std::{cell::RefCell, fmt::Debug, hash::Hash, sync::Arc};
+use std::{cell::RefCell, fmt::Debug, hash::Hash, sync::Arc, time::Instant};
 
 /// A struct that is always used by a single thread performing an execution task. The struct is
 /// passed to the VM and acts as a proxy to resolve reads first in the shared multi-version
@@ -36,6 +37,7 @@ pub(crate) struct MVHashMapView<'a, K, V: TransactionWrite, X: Executable> {
     versioned_map: &'a MVHashMap<K, V, X>,
     scheduler: &'a Scheduler,
     captured_reads: RefCell<Vec<ReadDescriptor<K>>>,
+    conflict_tracker: Option<&'a ConflictTracker<K>>,
 }
 
 /// A struct which describes the result of the read from the proxy. The client
@@ -61,11 +63,16 @@ impl<
         X: Executable,
     > MVHashMapView<'a, K, V, X>
 {
-    pub(crate) fn new(versioned_map: &'a MVHashMap<K, V, X>, scheduler: &'a Scheduler) -> Self {
+    pub(crate) fn new(
+        versioned_map: &'a MVHashMap<K, V, X>,
+        scheduler: &'a Scheduler,
+        conflict_tracker: Option<&'a ConflictTracker<K>>,
+    ) -> Self {
         Self {
             versioned_map,
             scheduler,
             captured_reads: RefCell::new(Vec::new()),
+            conflict_tracker,
         }
     }
 
@@ -140,11 +147,16 @@ impl<
                             // thread that aborted dep_idx was alive, and again, since lower txns
                             // than txn_idx are not blocked, so the execution of dep_idx will
                             // eventually finish and lead to unblocking txn_idx, contradiction.
+                            let wait_start = Instant::now();
                             let (lock, cvar) = &*dep_condition;
                             let mut dep_resolved = lock.lock();
                             while let DependencyStatus::Unresolved = *dep_resolved {
                                 dep_resolved = cvar.wait(dep_resolved).unwrap();
                             }
+                            if let Some(conflict_tracker) = self.conflict_tracker {
+                                conflict_tracker
+                                    .record_dependency_wait(txn_idx, wait_start.elapsed());
+                            }
                             if let DependencyStatus::ExecutionHalted = *dep_resolved {
                                 return ReadResult::ExecutionHalted;
                             }
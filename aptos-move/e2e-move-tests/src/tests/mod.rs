@@ -38,3 +38,4 @@ mod token_objects;
 mod transaction_fee;
 mod type_too_large;
 mod vector_numeric_address;
+mod verified_code_cache;
@@ -0,0 +1,89 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_language_e2e_tests::account::Account;
+use aptos_package_builder::PackageBuilder;
+use aptos_types::{account_address::AccountAddress, on_chain_config::FeatureFlag};
+use aptos_vm::AptosVM;
+use move_core_types::parser::parse_struct_tag;
+use serde::{Deserialize, Serialize};
+
+/// Mimics `0xcafe::test::State`
+#[derive(Serialize, Deserialize)]
+struct State {
+    value: u64,
+}
+
+/// Publishes the version of the test package which stores the given value
+fn publish_version(h: &mut MoveHarness, acc: &Account, value: u64) {
+    let mut builder = PackageBuilder::new("test");
+    builder.add_source(
+        "test",
+        &format!(
+            "
+module 0xcafe::test {{
+    struct State has key {{ value: u64 }}
+
+    public entry fun store(s: &signer) acquires State {{
+        if (exists<State>(@0xcafe)) {{
+            borrow_global_mut<State>(@0xcafe).value = {}
+        }} else {{
+            move_to(s, State {{ value: {} }})
+        }}
+    }}
+}}
+    ",
+            value, value
+        ),
+    );
+    let dir = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(acc, dir.path()));
+}
+
+/// Runs the stored version of the test package, and returns the value it stored
+fn store_value(h: &mut MoveHarness, acc: &Account) -> u64 {
+    assert_success!(h.run_entry_function(
+        acc,
+        str::parse("0xcafe::test::store").unwrap(),
+        vec![],
+        vec![],
+    ));
+    h.read_resource::<State>(
+        acc.address(),
+        parse_struct_tag("0xcafe::test::State").unwrap(),
+    )
+    .unwrap()
+    .value
+}
+
+#[test]
+fn cross_block_code_cache_upgrade_and_feature_flip() {
+    AptosVM::set_cross_block_code_cache_enabled_once();
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+
+    // Every transaction runs in its own block, so the code is served by the cache of the
+    // previous blocks once it was loaded.
+    publish_version(&mut h, &acc, 1);
+    assert_eq!(store_value(&mut h, &acc), 1);
+    assert_eq!(store_value(&mut h, &acc), 1);
+
+    // The upgraded module is loaded in the next blocks, the old one stays cached
+    publish_version(&mut h, &acc, 2);
+    assert_eq!(store_value(&mut h, &acc), 2);
+
+    // Flipping a feature changes the verifier config, so the code is verified again for
+    // the new environment
+    h.enable_features(vec![FeatureFlag::SIGNATURE_CHECKER_V2], vec![]);
+    assert_eq!(store_value(&mut h, &acc), 2);
+    publish_version(&mut h, &acc, 3);
+    assert_eq!(store_value(&mut h, &acc), 3);
+
+    // Flipping the feature back reuses the cache of the first environment, which never
+    // loaded the last upgrade
+    h.enable_features(vec![], vec![FeatureFlag::SIGNATURE_CHECKER_V2]);
+    assert_eq!(store_value(&mut h, &acc), 3);
+    publish_version(&mut h, &acc, 4);
+    assert_eq!(store_value(&mut h, &acc), 4);
+}
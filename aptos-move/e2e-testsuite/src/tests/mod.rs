@@ -25,5 +25,6 @@ mod module_publishing;
 mod on_chain_configs;
 mod peer_to_peer;
 mod scripts;
+mod sharded_block_executor;
 mod transaction_fuzzer;
 mod verify_txn;
@@ -0,0 +1,239 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that the sharded block executor produces the same outputs as the sequential execution
+//! of the partitioned block, for workloads with conflicts across the shards.
+
+use aptos_bitvec::BitVec;
+use aptos_block_partitioner::{
+    sharded_block_partitioner::ShardedBlockPartitioner, types::SubBlock,
+};
+use aptos_crypto::HashValue;
+use aptos_language_e2e_tests::{
+    account::Account, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+};
+use aptos_types::{
+    block_metadata::BlockMetadata,
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    transaction::{ExecutionStatus, Transaction, TransactionOutput, TransactionStatus},
+};
+use aptos_vm::{data_cache::AsMoveResolver, sharded_block_executor::ShardedBlockExecutor};
+use std::sync::Arc;
+
+const NUM_PARTITIONING_ROUNDS: usize = 2;
+
+fn block_metadata(executor: &FakeExecutor) -> Transaction {
+    let validator_set = ValidatorSet::fetch_config(&executor.get_state_view().as_move_resolver())
+        .expect("Unable to retrieve the validator set from storage");
+    Transaction::BlockMetadata(BlockMetadata::new(
+        HashValue::zero(),
+        0,
+        0,
+        *validator_set.payload().next().unwrap().account_address(),
+        BitVec::with_num_bits(validator_set.num_validators() as u16).into(),
+        vec![],
+        1,
+    ))
+}
+
+/// Generates transfers between a few accounts, each account sending to and receiving from
+/// several others, so that most of the transactions conflict
+fn conflicting_transfers(
+    accounts: &[Account],
+    sequence_numbers: &mut [u64],
+    num_transfers: usize,
+) -> Vec<Transaction> {
+    (0..num_transfers)
+        .map(|i| {
+            let sender = i % accounts.len();
+            let receiver = (sender + 1 + i / accounts.len()) % accounts.len();
+            let receiver = if receiver == sender {
+                (receiver + 1) % accounts.len()
+            } else {
+                receiver
+            };
+            sequence_numbers[sender] += 1;
+            Transaction::UserTransaction(peer_to_peer_txn(
+                &accounts[sender],
+                &accounts[receiver],
+                sequence_numbers[sender] - 1,
+                1_000,
+                100,
+            ))
+        })
+        .collect()
+}
+
+/// Executes the block with the sharded block executor, and checks that the outputs match the
+/// sequential execution of the transactions in the order of the sub-blocks
+fn assert_sharded_execution_matches_sequential(
+    executor: &FakeExecutor,
+    num_shards: usize,
+    sub_blocks: Vec<SubBlock>,
+) -> Vec<TransactionOutput> {
+    let transactions: Vec<Transaction> = sub_blocks
+        .iter()
+        .flat_map(|sub_block| {
+            sub_block
+                .transactions_with_deps()
+                .iter()
+                .map(|txn| txn.txn.transaction().clone())
+        })
+        .collect();
+    let expected_outputs = executor
+        .execute_transaction_block(transactions.clone())
+        .unwrap();
+
+    let sharded_executor = ShardedBlockExecutor::new(num_shards, Some(2));
+    let outputs = sharded_executor
+        .execute_block(
+            Arc::new(executor.get_state_view().clone()),
+            sub_blocks,
+            2,
+            None,
+        )
+        .unwrap();
+    assert_eq!(outputs.len(), transactions.len());
+    for (i, (output, expected_output)) in outputs.iter().zip(&expected_outputs).enumerate() {
+        assert_eq!(
+            output, expected_output,
+            "The output of transaction {} differs",
+            i
+        );
+    }
+    outputs
+}
+
+fn assert_user_txns_succeeded(transactions: &[Transaction], outputs: &[TransactionOutput]) {
+    for (txn, output) in transactions.iter().zip(outputs) {
+        if matches!(txn, Transaction::UserTransaction(_)) {
+            assert_eq!(
+                output.status(),
+                &TransactionStatus::Keep(ExecutionStatus::Success)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_sharded_execution_with_conflicting_transfers() {
+    for num_shards in [1, 2, 4] {
+        let mut executor = FakeExecutor::from_head_genesis();
+        let accounts = executor.create_accounts(8, 1_000_000_000, 0);
+        let mut sequence_numbers = vec![0; accounts.len()];
+        let user_txns = conflicting_transfers(&accounts, &mut sequence_numbers, 100);
+        let transactions: Vec<Transaction> = std::iter::once(block_metadata(&executor))
+            .chain(user_txns)
+            .chain(std::iter::once(Transaction::StateCheckpoint(
+                HashValue::zero(),
+            )))
+            .collect();
+
+        let sub_blocks = ShardedBlockPartitioner::new(num_shards)
+            .partition_transactions(transactions, NUM_PARTITIONING_ROUNDS);
+        // The block metadata, the user transactions in each partitioning round and the state
+        // checkpoint are in separate rounds
+        assert!(sub_blocks.len() > 2 * num_shards);
+        let partitioned_txns: Vec<Transaction> = sub_blocks
+            .iter()
+            .flat_map(|sub_block| {
+                sub_block
+                    .transactions_with_deps()
+                    .iter()
+                    .map(|txn| txn.txn.transaction().clone())
+            })
+            .collect();
+
+        let outputs =
+            assert_sharded_execution_matches_sequential(&executor, num_shards, sub_blocks);
+        assert_user_txns_succeeded(&partitioned_txns, &outputs);
+    }
+}
+
+#[test]
+fn test_sharded_execution_of_consecutive_blocks() {
+    let num_shards = 4;
+    let partitioner = ShardedBlockPartitioner::new(num_shards);
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(4, 1_000_000_000, 0);
+    let mut sequence_numbers = vec![0; accounts.len()];
+
+    // The writes of each block are applied before the next one, so that the blocks conflict with
+    // each other through the sequence numbers and balances of the accounts
+    for _ in 0..3 {
+        let transactions = conflicting_transfers(&accounts, &mut sequence_numbers, 40);
+        let sub_blocks = partitioner.partition_transactions(transactions, NUM_PARTITIONING_ROUNDS);
+        for output in assert_sharded_execution_matches_sequential(&executor, num_shards, sub_blocks)
+        {
+            assert_eq!(
+                output.status(),
+                &TransactionStatus::Keep(ExecutionStatus::Success)
+            );
+            executor.apply_write_set(output.write_set());
+        }
+    }
+}
+
+#[test]
+fn test_sharded_execution_falls_back_to_first_shard() {
+    let num_shards = 2;
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(4, 1_000_000_000, 0);
+    let mut sequence_numbers = vec![0; accounts.len()];
+    // A state checkpoint between the user transactions can't be partitioned
+    let transactions: Vec<Transaction> = std::iter::once(block_metadata(&executor))
+        .chain(conflicting_transfers(&accounts, &mut sequence_numbers, 10))
+        .chain(std::iter::once(Transaction::StateCheckpoint(
+            HashValue::zero(),
+        )))
+        .chain(conflicting_transfers(&accounts, &mut sequence_numbers, 10))
+        .collect();
+
+    let sub_blocks = ShardedBlockPartitioner::new(num_shards)
+        .partition_transactions(transactions.clone(), NUM_PARTITIONING_ROUNDS);
+    assert_eq!(sub_blocks.len(), num_shards);
+    assert_eq!(sub_blocks[0].len(), transactions.len());
+    assert!(sub_blocks[1].is_empty());
+
+    let outputs = assert_sharded_execution_matches_sequential(&executor, num_shards, sub_blocks);
+    assert_user_txns_succeeded(&transactions, &outputs);
+}
+
+#[test]
+fn test_sharded_execution_with_other_number_of_shards() {
+    // The partitioning is part of the on-chain config, while the number of executor shards is
+    // local to each node, so they don't need to match
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(8, 1_000_000_000, 0);
+    let mut sequence_numbers = vec![0; accounts.len()];
+    let transactions = conflicting_transfers(&accounts, &mut sequence_numbers, 50);
+    let sub_blocks = ShardedBlockPartitioner::new(4)
+        .partition_transactions(transactions, NUM_PARTITIONING_ROUNDS);
+
+    for output in assert_sharded_execution_matches_sequential(&executor, 3, sub_blocks) {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+}
+
+#[test]
+fn test_sharded_execution_rejects_block_gas_limit() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(4, 1_000_000_000, 0);
+    let mut sequence_numbers = vec![0; accounts.len()];
+    let transactions = conflicting_transfers(&accounts, &mut sequence_numbers, 10);
+    let sub_blocks = ShardedBlockPartitioner::new(2)
+        .partition_transactions(transactions, NUM_PARTITIONING_ROUNDS);
+
+    let sharded_executor = ShardedBlockExecutor::new(2, Some(2));
+    assert!(sharded_executor
+        .execute_block(
+            Arc::new(executor.get_state_view().clone()),
+            sub_blocks,
+            2,
+            Some(1_000),
+        )
+        .is_err());
+}
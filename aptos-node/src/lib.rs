@@ -505,6 +505,7 @@ pub fn setup_environment_and_start_node(
     let (
         network_runtimes,
         consensus_network_interfaces,
+        consensus_observer_network_interfaces,
         mempool_network_interfaces,
         peer_monitoring_service_network_interfaces,
         storage_service_network_interfaces,
@@ -548,22 +549,43 @@ pub fn setup_environment_and_start_node(
         );
 
     // Create the consensus runtime (this blocks on state sync first)
-    let consensus_runtime = consensus_network_interfaces.map(|consensus_network_interfaces| {
+    let consensus_runtime = if let Some(consensus_network_interfaces) = consensus_network_interfaces
+    {
         // Wait until state sync has been initialized
         debug!("Waiting until state sync is initialized!");
         state_sync_runtimes.block_until_initialized();
         debug!("State sync initialization complete.");
 
-        // Initialize and start consensus
-        services::start_consensus_runtime(
+        // Initialize and start consensus (publishing to observers, if enabled)
+        Some(services::start_consensus_runtime(
             &mut node_config,
             db_rw,
             consensus_reconfig_subscription,
             consensus_network_interfaces,
             consensus_notifier,
             consensus_to_mempool_sender,
-        )
-    });
+            consensus_observer_network_interfaces,
+        ))
+    } else if node_config.consensus_observer.observer_enabled {
+        // Wait until state sync has been initialized
+        debug!("Waiting until state sync is initialized!");
+        state_sync_runtimes.block_until_initialized();
+        debug!("State sync initialization complete.");
+
+        // Initialize and start the consensus observer
+        consensus_observer_network_interfaces.map(|network_interfaces| {
+            services::start_consensus_observer_runtime(
+                &node_config,
+                db_rw,
+                consensus_reconfig_subscription,
+                network_interfaces,
+                consensus_notifier,
+                consensus_to_mempool_sender,
+            )
+        })
+    } else {
+        None
+    };
 
     Ok(AptosHandle {
         _api_runtime: api_runtime,
@@ -4,7 +4,9 @@
 use crate::{bootstrap_api, indexer, mpsc::Receiver, network::ApplicationNetworkInterfaces};
 use aptos_build_info::build_information;
 use aptos_config::config::NodeConfig;
-use aptos_consensus::network_interface::ConsensusMsg;
+use aptos_consensus::{
+    consensus_observer::ConsensusObserverMessage, network_interface::ConsensusMsg,
+};
 use aptos_consensus_notifications::ConsensusNotifier;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_indexer_grpc_fullnode::runtime::bootstrap as bootstrap_indexer_grpc;
@@ -83,7 +85,18 @@ pub fn start_consensus_runtime(
     consensus_network_interfaces: ApplicationNetworkInterfaces<ConsensusMsg>,
     consensus_notifier: ConsensusNotifier,
     consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
+    consensus_observer_network_interfaces: Option<
+        ApplicationNetworkInterfaces<ConsensusObserverMessage>,
+    >,
 ) -> Runtime {
+    let (consensus_publisher_network_client, consensus_publisher_network_events) =
+        match consensus_observer_network_interfaces {
+            Some(network_interfaces) => (
+                Some(network_interfaces.network_client),
+                Some(network_interfaces.network_service_events),
+            ),
+            None => (None, None),
+        };
     let instant = Instant::now();
     let consensus_runtime = aptos_consensus::consensus_provider::start_consensus(
         node_config,
@@ -94,11 +107,40 @@ pub fn start_consensus_runtime(
         db_rw,
         consensus_reconfig_subscription
             .expect("Consensus requires a reconfiguration subscription!"),
+        consensus_publisher_network_client,
+        consensus_publisher_network_events,
     );
     debug!("Consensus started in {} ms", instant.elapsed().as_millis());
     consensus_runtime
 }
 
+/// Starts the consensus observer and returns the runtime
+pub fn start_consensus_observer_runtime(
+    node_config: &NodeConfig,
+    db_rw: DbReaderWriter,
+    consensus_reconfig_subscription: Option<ReconfigNotificationListener>,
+    consensus_observer_network_interfaces: ApplicationNetworkInterfaces<ConsensusObserverMessage>,
+    consensus_notifier: ConsensusNotifier,
+    consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
+) -> Runtime {
+    let instant = Instant::now();
+    let consensus_observer_runtime = aptos_consensus::consensus_provider::start_consensus_observer(
+        node_config,
+        consensus_observer_network_interfaces.network_client,
+        consensus_observer_network_interfaces.network_service_events,
+        Arc::new(consensus_notifier),
+        consensus_to_mempool_sender,
+        db_rw,
+        consensus_reconfig_subscription
+            .expect("The consensus observer requires a reconfiguration subscription!"),
+    );
+    debug!(
+        "Consensus observer started in {} ms",
+        instant.elapsed().as_millis()
+    );
+    consensus_observer_runtime
+}
+
 /// Create the mempool runtime and start mempool
 pub fn start_mempool_runtime_and_get_consensus_sender(
     node_config: &mut NodeConfig,
@@ -54,8 +54,11 @@ pub fn create_event_subscription_service(
         .subscribe_to_reconfigurations()
         .expect("Mempool must subscribe to reconfigurations");
 
-    // Create a reconfiguration subscription for consensus (if this is a validator)
-    let consensus_reconfig_subscription = if node_config.base.role.is_validator() {
+    // Create a reconfiguration subscription for consensus (if this is a validator
+    // or a fullnode running the consensus observer)
+    let consensus_reconfig_subscription = if node_config.base.role.is_validator()
+        || node_config.consensus_observer.observer_enabled
+    {
         Some(
             event_subscription_service
                 .subscribe_to_reconfigurations()
@@ -2,14 +2,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::anyhow;
-use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
-use aptos_db::AptosDB;
+use aptos_backup_cli::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
+use aptos_config::{
+    config::{BootstrappingMode, NodeConfig, NO_OP_STORAGE_PRUNER_CONFIG},
+    utils::get_genesis_txn,
+};
+use aptos_db::{AptosDB, GetRestoreHandler};
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_logger::{debug, info};
+use aptos_state_sync_driver::{
+    metadata_storage::PersistentMetadataStorage, restore_from_backup, BackupRestorer,
+};
 use aptos_storage_interface::{DbReader, DbReaderWriter};
-use aptos_types::waypoint::Waypoint;
+use aptos_types::{transaction::Version, waypoint::Waypoint};
 use aptos_vm::AptosVM;
-use std::{fs, net::SocketAddr, path::Path, sync::Arc, time::Instant};
+use std::{collections::HashMap, fs, net::SocketAddr, path::Path, sync::Arc, time::Instant};
 use tokio::runtime::Runtime;
 
 #[cfg(not(feature = "consensus-only-perf-test"))]
@@ -74,13 +90,122 @@ fn create_rocksdb_checkpoint_and_change_working_dir(
         .expect("ConsensusDB checkpoint creation failed.");
 
     // Create a state sync db checkpoint
-    let state_sync_db =
-        aptos_state_sync_driver::metadata_storage::PersistentMetadataStorage::new(&source_dir);
+    let state_sync_db = PersistentMetadataStorage::new(&source_dir);
     state_sync_db
         .create_checkpoint(&checkpoint_dir)
         .expect("StateSyncDB checkpoint creation failed.");
 }
 
+/// Restores the database from the trusted backup specified in the node config
+struct NodeBackupRestorer<'a> {
+    node_config: &'a NodeConfig,
+    run_mode: Arc<RestoreRunMode>,
+}
+
+impl BackupRestorer for NodeBackupRestorer<'_> {
+    fn get_next_version(&self) -> anyhow::Result<Version> {
+        self.run_mode.get_next_expected_transaction_version()
+    }
+
+    fn restore(&self) -> anyhow::Result<()> {
+        // Trust the configured waypoints (the epoch endings are verified against them)
+        let mut trusted_waypoints = HashMap::new();
+        for waypoint in [
+            self.node_config.base.waypoint.genesis_waypoint(),
+            self.node_config.base.waypoint.waypoint(),
+        ] {
+            trusted_waypoints.insert(waypoint.version(), waypoint);
+        }
+
+        // Create the restore options
+        let backup_restore_config = &self.node_config.storage.backup_restore;
+        let global_restore_options = GlobalRestoreOptions {
+            target_version: backup_restore_config.target_version.unwrap_or(Version::MAX),
+            trusted_waypoints: Arc::new(trusted_waypoints),
+            run_mode: self.run_mode.clone(),
+            concurrent_downloads: backup_restore_config
+                .concurrent_downloads
+                .unwrap_or_else(num_cpus::get),
+            replay_concurrency_level: backup_restore_config
+                .replay_concurrency_level
+                .unwrap_or_else(num_cpus::get),
+        };
+        let restore_coordinator_opt = RestoreCoordinatorOpt {
+            metadata_cache_opt: MetadataCacheOpt::new(
+                backup_restore_config.metadata_cache_dir.as_ref(),
+            ),
+            replay_all: false,
+            ledger_history_start_version: None,
+            skip_epoch_endings: false,
+        };
+
+        // Restore the database from the backup storage
+        let instant = Instant::now();
+        let runtime = aptos_runtimes::spawn_named_runtime("restore".into(), None);
+        runtime.block_on(async {
+            let backup_storage: Arc<dyn BackupStorage> =
+                if let Some(local_fs_dir) = &backup_restore_config.local_fs_dir {
+                    Arc::new(LocalFs::new(local_fs_dir.clone()))
+                } else if let Some(config_path) = &backup_restore_config.command_adapter_config {
+                    Arc::new(CommandAdapter::new(
+                        CommandAdapterConfig::load_from_file(config_path).await?,
+                    ))
+                } else {
+                    return Err(anyhow!("No backup location was specified to restore from!"));
+                };
+            RestoreCoordinator::new(
+                restore_coordinator_opt,
+                global_restore_options,
+                backup_storage,
+            )
+            .run()
+            .await
+        })?;
+        info!(
+            "Restored the database from a backup in {} seconds",
+            instant.elapsed().as_secs()
+        );
+
+        Ok(())
+    }
+}
+
+/// Restores the storage database from the trusted backup specified in the
+/// node config, if the node bootstraps by restoring from a backup. The state
+/// sync bootstrapper drives the restore (and persists its progress, so that an
+/// interrupted restore is resumed). Everything newer than the backup will be
+/// synced by state sync (over P2P).
+fn maybe_restore_from_backup(node_config: &NodeConfig) -> anyhow::Result<()> {
+    if node_config.state_sync.state_sync_driver.bootstrapping_mode
+        != BootstrappingMode::RestoreFromBackup
+    {
+        return Ok(());
+    }
+
+    // Open the database for restoring (we can always start with an empty buffered state)
+    let db_dir = node_config.storage.dir();
+    let restore_handler = Arc::new(AptosDB::open_kv_only(
+        &db_dir,
+        false, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        node_config.storage.rocksdb_configs,
+        false, /* enable_indexer */
+        node_config.storage.buffered_state_target_items,
+        node_config.storage.max_num_nodes_per_lru_cache_shard,
+    )?)
+    .get_restore_handler();
+    let backup_restorer = NodeBackupRestorer {
+        node_config,
+        run_mode: Arc::new(RestoreRunMode::Restore { restore_handler }),
+    };
+
+    // Restore the database (the state sync db is closed again before state sync starts)
+    let metadata_storage = PersistentMetadataStorage::new(&db_dir);
+    restore_from_backup(&metadata_storage, &backup_restorer)?;
+
+    Ok(())
+}
+
 /// Creates any rocksdb checkpoints, opens the storage database,
 /// starts the backup service, handles genesis initialization and returns
 /// the various handles.
@@ -93,6 +218,10 @@ pub fn initialize_database_and_checkpoints(
         create_rocksdb_checkpoint_and_change_working_dir(node_config, working_dir);
     }
 
+    // If required, restore the database from a trusted backup
+    maybe_restore_from_backup(node_config)
+        .map_err(|err| anyhow!("DB failed to restore from backup {}", err))?;
+
     // Open the database
     let instant = Instant::now();
     let aptos_db = AptosDB::open(
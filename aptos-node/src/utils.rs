@@ -53,6 +53,7 @@ pub fn set_aptos_vm_configurations(node_config: &NodeConfig) {
     AptosVM::set_num_proof_reading_threads_once(
         node_config.execution.num_proof_reading_threads as usize,
     );
+    AptosVM::set_num_shards_once(node_config.execution.num_executor_shards as usize);
 
     if node_config
         .execution
@@ -60,4 +61,10 @@ pub fn set_aptos_vm_configurations(node_config: &NodeConfig) {
     {
         AptosVM::set_processed_transactions_detailed_counters();
     }
+    if node_config.execution.enable_conflict_report {
+        AptosVM::set_conflict_report_enabled_once();
+    }
+    if node_config.execution.enable_cross_block_code_cache {
+        AptosVM::set_cross_block_code_cache_enabled_once();
+    }
 }
@@ -3,8 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::{
-    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
-    QuorumStoreConfig, SafetyRulesConfig,
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, DagConsensusConfig, Error,
+    NodeConfig, QuorumStoreConfig, SafetyRulesConfig,
 };
 use aptos_types::chain_id::ChainId;
 use cfg_if::cfg_if;
@@ -65,6 +65,8 @@ pub struct ConsensusConfig {
     // must match one of the CHAIN_HEALTH_WINDOW_SIZES values.
     pub window_for_chain_health: usize,
     pub chain_health_backoff: Vec<ChainHealthBackoffValues>,
+    // Only used when DAG consensus is enabled through the on-chain consensus config
+    pub dag_consensus: DagConsensusConfig,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -205,6 +207,7 @@ impl Default for ConsensusConfig {
                     backoff_proposal_delay_ms: 300,
                 },
             ],
+            dag_consensus: DagConsensusConfig::default(),
         }
     }
 }
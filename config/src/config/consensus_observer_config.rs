@@ -0,0 +1,161 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsensusObserverConfig {
+    pub observer_enabled: bool, // Whether the node follows ordered blocks published by its peers
+    pub publisher_enabled: bool, // Whether the node publishes ordered blocks to its downstream peers
+    pub max_network_channel_size: u64, // Max num of pending network messages
+    pub max_num_pending_blocks: u64, // Max num of ordered blocks waiting for a commit decision
+    pub observer_fallback_duration_ms: u64, // Time (ms) without commits before state sync takes over
+    pub max_batch_requests_per_second: u64, // Max num of batch requests served to each peer per second
+    pub batch_request_timeout_ms: u64, // Timeout (ms) of the batch requests sent to upstream peers
+}
+
+impl Default for ConsensusObserverConfig {
+    fn default() -> Self {
+        Self {
+            observer_enabled: false,
+            publisher_enabled: false,
+            max_network_channel_size: 1000,
+            max_num_pending_blocks: 100,
+            observer_fallback_duration_ms: 10_000, // 10 seconds
+            max_batch_requests_per_second: 100,
+            batch_request_timeout_ms: 1_000, // 1 second
+        }
+    }
+}
+
+impl ConsensusObserverConfig {
+    /// Returns true iff the observer or the publisher is enabled
+    pub fn is_observer_or_publisher_enabled(&self) -> bool {
+        self.observer_enabled || self.publisher_enabled
+    }
+}
+
+impl ConfigSanitizer for ConsensusObserverConfig {
+    fn sanitize(
+        node_config: &mut NodeConfig,
+        node_type: NodeType,
+        _chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let consensus_observer_config = &node_config.consensus_observer;
+
+        // Verify that validators don't run the observer (they already run consensus)
+        if node_type.is_validator() && consensus_observer_config.observer_enabled {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The consensus observer cannot be enabled on validators!".into(),
+            ));
+        }
+
+        // Verify that the observer can buffer at least one ordered block
+        if consensus_observer_config.observer_enabled
+            && consensus_observer_config.max_num_pending_blocks == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The max number of pending blocks must be non-zero for the observer!".into(),
+            ));
+        }
+
+        // Verify that the publisher can serve batch requests
+        if consensus_observer_config.publisher_enabled
+            && consensus_observer_config.max_batch_requests_per_second == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The max number of batch requests per second must be non-zero for the publisher!"
+                    .into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_observer_on_validator() {
+        // Create a node config with the observer enabled
+        let mut node_config = NodeConfig {
+            consensus_observer: ConsensusObserverConfig {
+                observer_enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization for validators
+        let error = ConsensusObserverConfig::sanitize(
+            &mut node_config,
+            NodeType::Validator,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that the config passes sanitization for fullnodes
+        ConsensusObserverConfig::sanitize(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_no_pending_blocks() {
+        // Create a node config with the observer enabled and no pending blocks
+        let mut node_config = NodeConfig {
+            consensus_observer: ConsensusObserverConfig {
+                observer_enabled: true,
+                max_num_pending_blocks: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error = ConsensusObserverConfig::sanitize(
+            &mut node_config,
+            NodeType::ValidatorFullnode,
+            ChainId::mainnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_no_batch_requests() {
+        // Create a node config with the publisher enabled and no batch requests
+        let mut node_config = NodeConfig {
+            consensus_observer: ConsensusObserverConfig {
+                publisher_enabled: true,
+                max_batch_requests_per_second: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error = ConsensusObserverConfig::sanitize(
+            &mut node_config,
+            NodeType::Validator,
+            ChainId::mainnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DagPayloadConfig {
+    pub max_sending_txns_per_round: u64,
+    pub max_sending_size_per_round_bytes: u64,
+    pub max_receiving_txns_per_round: u64,
+    pub max_receiving_size_per_round_bytes: u64,
+}
+
+impl Default for DagPayloadConfig {
+    fn default() -> Self {
+        Self {
+            max_sending_txns_per_round: 1000,
+            max_sending_size_per_round_bytes: 2 * 1024 * 1024, // 2MB
+            max_receiving_txns_per_round: 2000,
+            max_receiving_size_per_round_bytes: 4 * 1024 * 1024, // 4MB
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DagFetcherConfig {
+    // Number of attempts to fetch missing nodes, each attempt asks the next responder
+    pub max_retries: u32,
+    pub retry_interval_ms: u64,
+    pub rpc_timeout_ms: u64,
+}
+
+impl Default for DagFetcherConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            retry_interval_ms: 500,
+            rpc_timeout_ms: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReliableBroadcastConfig {
+    pub backoff_policy_base_ms: u64,
+    pub backoff_policy_factor: u64,
+    pub backoff_policy_max_delay_ms: u64,
+    pub rpc_timeout_ms: u64,
+}
+
+impl Default for ReliableBroadcastConfig {
+    fn default() -> Self {
+        Self {
+            // A backoff policy that starts at 100ms and doubles each iteration up to 3secs.
+            backoff_policy_base_ms: 2,
+            backoff_policy_factor: 50,
+            backoff_policy_max_delay_ms: 3000,
+            rpc_timeout_ms: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DagConsensusConfig {
+    pub node_payload_config: DagPayloadConfig,
+    pub rb_config: ReliableBroadcastConfig,
+    pub fetcher_config: DagFetcherConfig,
+    // Number of rounds the dag keeps below the highest committed anchor, lagging peers can
+    // only fetch the nodes within this window
+    pub dag_window_rounds: u64,
+}
+
+impl Default for DagConsensusConfig {
+    fn default() -> Self {
+        Self {
+            node_payload_config: DagPayloadConfig::default(),
+            rb_config: ReliableBroadcastConfig::default(),
+            fetcher_config: DagFetcherConfig::default(),
+            dag_window_rounds: 10,
+        }
+    }
+}
@@ -34,6 +34,15 @@ pub struct ExecutionConfig {
     pub paranoid_hot_potato_verification: bool,
     /// Enables enhanced metrics around processed transactions
     pub processed_transactions_detailed_counters: bool,
+    /// Enables the conflict reports of the parallel execution of the blocks (aborts,
+    /// invalidated keys and dependency waits), exported through the metrics and debug logs
+    pub enable_conflict_report: bool,
+    /// Enables the cache of the verified modules and scripts shared by the VMs of all the
+    /// blocks, so the code called in every block isn't verified again for each of them
+    pub enable_cross_block_code_cache: bool,
+    /// Number of local executor shards to execute the blocks partitioned by consensus on. The
+    /// partitioning, and so the committed order, only depends on the on-chain execution config.
+    pub num_executor_shards: u16,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -63,6 +72,10 @@ impl Default for ExecutionConfig {
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             processed_transactions_detailed_counters: false,
+            enable_conflict_report: false,
+            enable_cross_block_code_cache: false,
+            // Sharded execution is disabled by default.
+            num_executor_shards: 1,
         }
     }
 }
@@ -148,6 +161,12 @@ impl ConfigSanitizer for ExecutionConfig {
                     "paranoid_type_verification must be enabled for mainnet nodes!".into(),
                 ));
             }
+            if execution_config.num_executor_shards > 1 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "num_executor_shards must be 1 for mainnet nodes!".into(),
+                ));
+            }
         }
 
         Ok(())
@@ -218,6 +237,27 @@ mod test {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_sharded_execution_mainnet() {
+        // Create a node config with sharded execution enabled on mainnet
+        let mut node_config = NodeConfig {
+            execution: ExecutionConfig {
+                num_executor_shards: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            ExecutionConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Sanitize the config on a test network and verify that it succeeds
+        ExecutionConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::test()).unwrap();
+    }
+
     #[test]
     fn test_no_genesis() {
         let (mut config, path) = generate_config();
@@ -18,6 +18,8 @@ pub struct InspectionServiceConfig {
     pub address: String,
     pub port: u16,
     pub expose_configuration: bool,
+    pub expose_consensus_block_timelines: bool,
+    pub expose_equivocation_evidence: bool,
     pub expose_peer_information: bool,
     pub expose_system_information: bool,
 }
@@ -28,6 +30,8 @@ impl Default for InspectionServiceConfig {
             address: "0.0.0.0".to_string(),
             port: 9101,
             expose_configuration: false,
+            expose_consensus_block_timelines: false,
+            expose_equivocation_evidence: false,
             expose_peer_information: true,
             expose_system_information: true,
         }
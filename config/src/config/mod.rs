@@ -8,6 +8,8 @@ mod base_config;
 mod config_optimizer;
 mod config_sanitizer;
 mod consensus_config;
+mod consensus_observer_config;
+mod dag_consensus_config;
 mod error;
 mod execution_config;
 mod gas_estimation_config;
@@ -33,6 +35,8 @@ mod utils;
 pub use api_config::*;
 pub use base_config::*;
 pub use consensus_config::*;
+pub use consensus_observer_config::*;
+pub use dag_consensus_config::*;
 pub use error::*;
 pub use execution_config::*;
 pub use identity_config::*;
@@ -11,7 +11,7 @@ use crate::{
     utils,
 };
 use aptos_crypto::{x25519, Uniform};
-use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
+use aptos_secure_storage::{KVStorage, Storage};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_types::{
     account_address::from_identity_public_key, network_address::NetworkAddress,
@@ -53,6 +53,9 @@ pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const MAX_RECORDING_FILE_SIZE_BYTES: u64 = 256 * 1024 * 1024; /* 256 MiB */
+pub const MAX_NUM_RECORDING_FILES: usize = 8;
+pub const MAX_THROTTLED_EGRESS_BYTES_PER_PEER: usize = MAX_MESSAGE_SIZE;
 pub const INBOUND_TCP_RX_BUFFER_SIZE: u32 = 3 * 1024 * 1024; // 3MB ~6MB/s with 500ms latency
 pub const INBOUND_TCP_TX_BUFFER_SIZE: u32 = 512 * 1024; // 1MB use a bigger spoon
 pub const OUTBOUND_TCP_RX_BUFFER_SIZE: u32 = 3 * 1024 * 1024; // 3MB ~6MB/s with 500ms latency
@@ -103,6 +106,9 @@ pub struct NetworkConfig {
     pub max_frame_size: usize,
     /// Enables proxy protocol on incoming connections to get original source addresses
     pub enable_proxy_protocol: bool,
+    /// Enables the QUIC transport (in addition to TCP). QUIC connections are
+    /// accepted on the UDP port matching the TCP listen port.
+    pub enable_quic_transport: bool,
     /// Interval to send healthcheck pings to peers
     pub ping_interval_ms: u64,
     /// Timeout until a healthcheck ping is rejected
@@ -119,6 +125,10 @@ pub struct NetworkConfig {
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
     /// The maximum size of an inbound or outbound message (it may be divided into multiple frame)
     pub max_message_size: usize,
+    /// Network traffic recording configuration, if not specified, no traffic is recorded
+    pub network_recorder_config: Option<NetworkRecorderConfig>,
+    /// Per-protocol and per-peer-class egress bandwidth quotas
+    pub egress_quota_config: EgressQuotaConfig,
 }
 
 impl Default for NetworkConfig {
@@ -142,6 +152,7 @@ impl NetworkConfig {
             seeds: PeerSet::default(),
             max_frame_size: MAX_FRAME_SIZE,
             enable_proxy_protocol: false,
+            enable_quic_transport: false,
             max_connection_delay_ms: MAX_CONNECTION_DELAY_MS,
             connectivity_check_interval_ms: CONNECTIVITY_CHECK_INTERVAL_MS,
             network_channel_size: NETWORK_CHANNEL_SIZE,
@@ -155,6 +166,8 @@ impl NetworkConfig {
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
             max_message_size: MAX_MESSAGE_SIZE,
+            network_recorder_config: None,
+            egress_quota_config: EgressQuotaConfig::default(),
             inbound_rx_buffer_size_bytes: Some(INBOUND_TCP_RX_BUFFER_SIZE),
             inbound_tx_buffer_size_bytes: Some(INBOUND_TCP_TX_BUFFER_SIZE),
             outbound_rx_buffer_size_bytes: Some(OUTBOUND_TCP_RX_BUFFER_SIZE),
@@ -165,24 +178,9 @@ impl NetworkConfig {
     }
 
     pub fn identity_key(&self) -> x25519::PrivateKey {
-        let key = match &self.identity {
-            Identity::FromConfig(config) => Some(config.key.private_key()),
-            Identity::FromStorage(config) => {
-                let storage: Storage = (&config.backend).into();
-                let key = storage
-                    .export_private_key(&config.key_name)
-                    .expect("Unable to read key");
-                let key = x25519::PrivateKey::from_ed25519_private_bytes(&key.to_bytes())
-                    .expect("Unable to convert key");
-                Some(key)
-            },
-            Identity::FromFile(config) => {
-                let identity_blob: IdentityBlob = IdentityBlob::from_file(&config.path).unwrap();
-                Some(identity_blob.network_private_key)
-            },
-            Identity::None => None,
-        };
-        key.expect("identity key should be present")
+        self.identity
+            .private_key()
+            .expect("identity key should be present")
     }
 
     pub fn identity_from_storage(&self) -> IdentityFromStorage {
@@ -367,6 +365,113 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Records all inbound and outbound network messages to a set of rotating
+/// local files, so that incidents can be replayed offline.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkRecorderConfig {
+    /// The directory in which to store the recording files (each network
+    /// is recorded to a separate sub-directory)
+    pub recording_dir: PathBuf,
+    /// The maximum size of a single recording file before rotating to a new file
+    pub max_file_size_bytes: u64,
+    /// The maximum number of recording files to keep (the oldest files are deleted first)
+    pub max_num_files: usize,
+    /// The maximum number of messages pending a write before new messages are dropped
+    pub max_pending_messages: usize,
+}
+
+impl Default for NetworkRecorderConfig {
+    fn default() -> Self {
+        Self {
+            recording_dir: PathBuf::from("network_recordings"),
+            max_file_size_bytes: MAX_RECORDING_FILE_SIZE_BYTES,
+            max_num_files: MAX_NUM_RECORDING_FILES,
+            max_pending_messages: NETWORK_CHANNEL_SIZE,
+        }
+    }
+}
+
+/// Limits the egress bandwidth of specific protocols to specific classes of peers
+/// (e.g., to cap how much state sync data is served to public fullnodes). Traffic
+/// that exceeds a quota is held back (and eventually dropped), while all other
+/// traffic is sent immediately. Consensus traffic can never be limited.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EgressQuotaConfig {
+    /// The quotas to enforce (traffic without a quota is unlimited)
+    pub quotas: Vec<EgressQuota>,
+    /// The maximum number of bytes held back by the quotas (for each peer) before
+    /// new messages are dropped. This should be at least the maximum message size.
+    pub max_throttled_bytes_per_peer: usize,
+}
+
+impl Default for EgressQuotaConfig {
+    fn default() -> Self {
+        Self {
+            quotas: vec![],
+            max_throttled_bytes_per_peer: MAX_THROTTLED_EGRESS_BYTES_PER_PEER,
+        }
+    }
+}
+
+/// A token bucket quota for the egress traffic of a single protocol to a class of
+/// peers. The quota is shared by all peers of the class (on the same network).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EgressQuota {
+    /// The protocol to limit (e.g., "StorageServiceRpc")
+    pub protocol_id: String,
+    /// The class of peers to which the quota applies
+    pub peer_class: PeerClass,
+    /// The sustained egress rate (in bytes per second)
+    pub bytes_per_second: usize,
+    /// The maximum egress burst (in bytes). Must be at least the sustained rate.
+    pub burst_size_bytes: usize,
+}
+
+/// The class of a remote peer, as used by the egress quotas
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerClass {
+    Validator,
+    ValidatorFullnode,
+    PublicFullnode,
+}
+
+impl PeerClass {
+    pub fn all() -> &'static [PeerClass] {
+        &[
+            PeerClass::Validator,
+            PeerClass::ValidatorFullnode,
+            PeerClass::PublicFullnode,
+        ]
+    }
+
+    /// Returns the class of a peer with the given role
+    pub fn from_role(role: PeerRole) -> Self {
+        match role {
+            PeerRole::Validator => PeerClass::Validator,
+            PeerRole::ValidatorFullNode => PeerClass::ValidatorFullnode,
+            _ => PeerClass::PublicFullnode,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PeerClass::Validator => "validator",
+            PeerClass::ValidatorFullnode => "validator_fullnode",
+            PeerClass::PublicFullnode => "public_fullnode",
+        }
+    }
+}
+
+impl fmt::Display for PeerClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -6,16 +6,17 @@
 use crate::config::persistable_config::PersistableConfig;
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, IdentityBlob,
-        LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, Identity,
+        IdentityBlob, LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
     },
     keys::ConfigKey,
 };
-use aptos_crypto::{bls12381, Uniform};
+use aptos_crypto::{bls12381, x25519, Uniform};
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     net::{SocketAddr, ToSocketAddrs},
     path::PathBuf,
 };
@@ -93,14 +94,29 @@ impl ConfigSanitizer for SafetyRulesConfig {
             ));
         }
 
-        // Verify that the safety rules service is set to local for optimal performance
-        if chain_id.is_mainnet() && !safety_rules_config.service.is_local() {
+        // Verify that the safety rules service is set to local for optimal performance, unless it
+        // is deliberately run on a separate host over an authenticated channel
+        if chain_id.is_mainnet()
+            && !safety_rules_config.service.is_local()
+            && !safety_rules_config.service.is_authenticated_process()
+        {
             return Err(Error::ConfigSanitizerFailed(
                 sanitizer_name,
                 format!("The safety rules service should be set to local in mainnet for optimal performance! Given config: {:?}", &safety_rules_config.service)
             ));
         }
 
+        // Verify that an authenticated safety rules service allows at least one consensus
+        // instance to connect (otherwise, every connection is rejected)
+        if let SafetyRulesService::AuthenticatedProcess(service) = &safety_rules_config.service {
+            if service.client_public_keys.is_empty() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "The authenticated safety rules service must have at least one client public key!".to_string(),
+                ));
+            }
+        }
+
         // Verify that the safety rules test config is not enabled in mainnet
         if chain_id.is_mainnet() && safety_rules_config.test.is_some() {
             return Err(Error::ConfigSanitizerFailed(
@@ -161,13 +177,16 @@ impl InitialSafetyRulesConfig {
 }
 
 /// Defines how safety rules should be executed
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
     Local,
     /// This is the production, separate service approach
     Process(RemoteService),
+    /// This runs safety rules as a separate service, possibly on another host, over a mutually
+    /// authenticated and encrypted (Noise IK) channel
+    AuthenticatedProcess(AuthenticatedRemoteService),
     /// This runs safety rules in the same thread as event processor but data is passed through the
     /// light weight RPC (serializer)
     Serializer,
@@ -180,6 +199,11 @@ impl SafetyRulesService {
     fn is_local(&self) -> bool {
         matches!(self, SafetyRulesService::Local)
     }
+
+    /// Returns true iff the service is a separate process over an authenticated channel
+    fn is_authenticated_process(&self) -> bool {
+        matches!(self, SafetyRulesService::AuthenticatedProcess(_))
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -190,14 +214,51 @@ pub struct RemoteService {
 
 impl RemoteService {
     pub fn server_address(&self) -> SocketAddr {
-        self.server_address
-            .to_socket_addrs()
-            .expect("server_address invalid")
-            .next()
-            .expect("server_address invalid")
+        to_socket_address(&self.server_address)
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthenticatedRemoteService {
+    pub server_address: NetworkAddress,
+    /// The x25519 static key of this end of the channel (consensus or safety rules)
+    pub identity: Identity,
+    /// The x25519 public key of the safety rules service, used by consensus to authenticate it
+    pub server_public_key: x25519::PublicKey,
+    /// The x25519 public keys of the consensus instances allowed to connect to safety rules
+    #[serde(default)]
+    pub client_public_keys: HashSet<x25519::PublicKey>,
+    /// Duration in milliseconds after which consensus stops retrying a request and fails it
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+}
+
+fn default_request_timeout_ms() -> u64 {
+    // Default value of 60 seconds, i.e., twice the default network timeout
+    60_000
+}
+
+impl AuthenticatedRemoteService {
+    pub fn server_address(&self) -> SocketAddr {
+        to_socket_address(&self.server_address)
+    }
+
+    pub fn private_key(&self) -> x25519::PrivateKey {
+        self.identity
+            .private_key()
+            .expect("identity key should be present")
+    }
+}
+
+fn to_socket_address(address: &NetworkAddress) -> SocketAddr {
+    address
+        .to_socket_addrs()
+        .expect("server_address invalid")
+        .next()
+        .expect("server_address invalid")
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
@@ -294,6 +355,48 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_authenticated_process_for_mainnet() {
+        // Create a node config with an authenticated process service
+        let client_public_key = x25519::PrivateKey::generate_for_testing().public_key();
+        let mut node_config =
+            create_authenticated_process_config(HashSet::from([client_public_key]));
+
+        // Verify that the config sanitizer passes
+        SafetyRulesConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_authenticated_process_without_clients() {
+        // Create a node config with an authenticated process service that allows no clients
+        let mut node_config = create_authenticated_process_config(HashSet::new());
+
+        // Verify that the config sanitizer fails
+        let error =
+            SafetyRulesConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_authenticated_process_serialization() {
+        let service = SafetyRulesService::AuthenticatedProcess(AuthenticatedRemoteService {
+            server_address: NetworkAddress::mock(),
+            identity: Identity::from_file(PathBuf::from("/full/path/to/identity.yaml")),
+            server_public_key: x25519::PrivateKey::generate_for_testing().public_key(),
+            client_public_keys: HashSet::from([
+                x25519::PrivateKey::generate_for_testing().public_key()
+            ]),
+            request_timeout_ms: 10_000,
+        });
+        let serialized = serde_yaml::to_string(&service).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<SafetyRulesService>(&serialized).unwrap(),
+            service
+        );
+    }
+
     #[test]
     fn test_sanitize_test_config_on_mainnet() {
         // Create a node config with a test config
@@ -335,4 +438,36 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    /// Creates a validator node config with an authenticated process service that
+    /// allows the given clients to connect
+    fn create_authenticated_process_config(
+        client_public_keys: HashSet<x25519::PublicKey>,
+    ) -> NodeConfig {
+        let service = SafetyRulesService::AuthenticatedProcess(AuthenticatedRemoteService {
+            server_address: NetworkAddress::mock(),
+            identity: Identity::from_config(
+                x25519::PrivateKey::generate_for_testing(),
+                PeerId::ZERO,
+            ),
+            server_public_key: x25519::PrivateKey::generate_for_testing().public_key(),
+            client_public_keys,
+            request_timeout_ms: default_request_timeout_ms(),
+        });
+        NodeConfig {
+            consensus: ConsensusConfig {
+                safety_rules: SafetyRulesConfig {
+                    backend: SecureBackend::OnDiskStorage(Default::default()),
+                    service,
+                    initial_safety_rules_config: InitialSafetyRulesConfig::from_file(
+                        PathBuf::new(),
+                        WaypointConfig::None,
+                    ),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
 }
@@ -16,6 +16,7 @@ const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024; /* 4 MiB */
 // The maximum chunk sizes for data client requests and response
 const MAX_EPOCH_CHUNK_SIZE: u64 = 200;
 const MAX_STATE_CHUNK_SIZE: u64 = 4000;
+const MAX_STATE_VALUES_BY_KEYS_CHUNK_SIZE: u64 = 500;
 const MAX_TRANSACTION_CHUNK_SIZE: u64 = 2000;
 const MAX_TRANSACTION_OUTPUT_CHUNK_SIZE: u64 = 1000;
 
@@ -44,6 +45,10 @@ pub enum BootstrappingMode {
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
     ExecuteOrApplyFromGenesis,
+    /// Restores the epoch endings, latest state snapshot and transactions from
+    /// a trusted backup (see `StorageConfig::backup_restore`) and then applies
+    /// transaction outputs (from the network) to catch up.
+    RestoreFromBackup,
 }
 
 impl BootstrappingMode {
@@ -57,6 +62,7 @@ impl BootstrappingMode {
                 "execute_transactions_from_genesis"
             },
             BootstrappingMode::ExecuteOrApplyFromGenesis => "execute_or_apply_from_genesis",
+            BootstrappingMode::RestoreFromBackup => "restore_from_backup",
         }
     }
 }
@@ -158,6 +164,8 @@ pub struct StorageServiceConfig {
     pub max_optimistic_fetch_period: u64,
     /// Maximum number of state keys and values per chunk
     pub max_state_chunk_size: u64,
+    /// Maximum number of state keys per state values by keys request
+    pub max_state_values_by_keys_chunk_size: u64,
     /// Maximum number of transactions per chunk
     pub max_transaction_chunk_size: u64,
     /// Maximum number of transaction outputs per chunk
@@ -181,6 +189,7 @@ impl Default for StorageServiceConfig {
             max_network_chunk_bytes: MAX_MESSAGE_SIZE as u64,
             max_optimistic_fetch_period: 5000, // 5 seconds
             max_state_chunk_size: MAX_STATE_CHUNK_SIZE,
+            max_state_values_by_keys_chunk_size: MAX_STATE_VALUES_BY_KEYS_CHUNK_SIZE,
             max_transaction_chunk_size: MAX_TRANSACTION_CHUNK_SIZE,
             max_transaction_output_chunk_size: MAX_TRANSACTION_OUTPUT_CHUNK_SIZE,
             min_time_to_ignore_peers_secs: 300, // 5 minutes
@@ -286,10 +295,33 @@ impl Default for AptosDataClientConfig {
 
 impl ConfigSanitizer for StateSyncConfig {
     fn sanitize(
-        _node_config: &mut NodeConfig,
+        node_config: &mut NodeConfig,
         _node_type: NodeType,
         _chain_id: ChainId,
     ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let bootstrapping_mode = node_config.state_sync.state_sync_driver.bootstrapping_mode;
+
+        // If we're restoring from a backup, ensure that a single backup location is specified
+        if bootstrapping_mode == BootstrappingMode::RestoreFromBackup {
+            let backup_restore_config = &node_config.storage.backup_restore;
+            let num_backup_locations = [
+                &backup_restore_config.local_fs_dir,
+                &backup_restore_config.command_adapter_config,
+            ]
+            .iter()
+            .filter(|location| location.is_some())
+            .count();
+            if num_backup_locations != 1 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Exactly one backup location (local_fs_dir or command_adapter_config) must be \
+                    specified when restoring from a backup!"
+                        .into(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -372,6 +404,7 @@ impl ConfigOptimizer for DataStreamingServiceConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
 
     #[test]
     fn test_optimize_bootstrapping_mode_testnet_vfn() {
@@ -571,6 +604,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sanitize_restore_from_backup_location() {
+        // Create a node config that restores from a backup (without a backup location)
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::RestoreFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error =
+            StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Specify multiple backup locations and verify that the config fails sanitization
+        let backup_restore_config = &mut node_config.storage.backup_restore;
+        backup_restore_config.local_fs_dir = Some(PathBuf::from("/opt/aptos/backup"));
+        backup_restore_config.command_adapter_config =
+            Some(PathBuf::from("/opt/aptos/backup.yaml"));
+        let error =
+            StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Specify a single backup location and verify that the config passes sanitization
+        node_config.storage.backup_restore.command_adapter_config = None;
+        StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+            .unwrap();
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
@@ -119,6 +119,28 @@ pub struct StorageConfig {
     /// since genesis. To recover operation after data loss, or to bootstrap a node in fast sync
     /// mode, the indexer db needs to be copied in from another node.
     pub enable_indexer: bool,
+    /// The trusted backup to restore from when state sync bootstraps using
+    /// `BootstrappingMode::RestoreFromBackup`.
+    pub backup_restore: BackupRestoreConfig,
+}
+
+/// The location and options of a trusted backup (i.e., created by the backup-cli)
+/// from which an empty node can restore its database before syncing with peers.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupRestoreConfig {
+    /// The local directory holding the backup (i.e., for the LocalFs backup storage)
+    pub local_fs_dir: Option<PathBuf>,
+    /// The config file for the CommandAdapter backup storage
+    pub command_adapter_config: Option<PathBuf>,
+    /// The directory used to cache the backup metadata (defaults to a temporary directory)
+    pub metadata_cache_dir: Option<PathBuf>,
+    /// The number of concurrent downloads from the backup storage (defaults to the number of CPUs)
+    pub concurrent_downloads: Option<usize>,
+    /// The concurrency level used when replaying transactions (defaults to the number of CPUs)
+    pub replay_concurrency_level: Option<usize>,
+    /// Content newer than this version will not be restored (defaults to the latest version)
+    pub target_version: Option<u64>,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -267,6 +289,7 @@ impl Default for StorageConfig {
             enable_indexer: false,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            backup_restore: BackupRestoreConfig::default(),
         }
     }
 }
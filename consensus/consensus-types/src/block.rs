@@ -203,6 +203,14 @@ impl Block {
         }
     }
 
+    pub fn new_for_dag(block_data: BlockData) -> Self {
+        Block {
+            id: block_data.hash(),
+            block_data,
+            signature: None,
+        }
+    }
+
     pub fn new_proposal(
         payload: Payload,
         round: Round,
@@ -249,6 +257,7 @@ impl Block {
     pub fn validate_signature(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
+            BlockType::DAGBlock { .. } => bail!("We should not accept DAG blocks from others"),
             BlockType::NilBlock { .. } => self.quorum_cert().verify(validator),
             BlockType::Proposal { author, .. } => {
                 let signature = self
@@ -374,12 +383,16 @@ impl Block {
             self.epoch(),
             self.round(),
             self.author().unwrap_or(AccountAddress::ZERO),
-            // A bitvec of voters
-            self.quorum_cert()
-                .ledger_info()
-                .get_voters_bitvec()
-                .clone()
-                .into(),
+            // A bitvec of voters, or the anchor's parents for DAG blocks
+            match self.block_data.block_type() {
+                BlockType::DAGBlock { parents_bitvec, .. } => parents_bitvec.clone().into(),
+                _ => self
+                    .quorum_cert()
+                    .ledger_info()
+                    .get_voters_bitvec()
+                    .clone()
+                    .into(),
+            },
             // For nil block, we use 0x0 which is convention for nil address in move.
             self.block_data()
                 .failed_authors()
@@ -7,6 +7,7 @@ use crate::{
     quorum_cert::QuorumCert,
     vote_data::VoteData,
 };
+use aptos_bitvec::BitVec;
 use aptos_crypto::hash::HashValue;
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use aptos_types::{
@@ -42,6 +43,20 @@ pub enum BlockType {
     /// from the previous epoch.  The genesis block is used as the first root block of the
     /// BlockTree for all epochs.
     Genesis,
+    /// A block ordered by the DAG consensus. It is derived deterministically by all validators
+    /// from an anchor node and its (not yet ordered) causal history, so it carries no signature.
+    DAGBlock {
+        /// Author of the anchor node
+        author: Author,
+        /// The merged payload of all the nodes ordered by this block
+        payload: Payload,
+        /// Failed authors from the parent's block to this block, the anchors that were skipped.
+        failed_authors: Vec<(Round, Author)>,
+        /// The digests of the nodes ordered by this block
+        node_digests: Vec<HashValue>,
+        /// The authors of the anchor's parents, indexed by validator index
+        parents_bitvec: BitVec,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
@@ -79,10 +94,9 @@ pub struct BlockData {
 
 impl BlockData {
     pub fn author(&self) -> Option<Author> {
-        if let BlockType::Proposal { author, .. } = self.block_type {
-            Some(author)
-        } else {
-            None
+        match self.block_type {
+            BlockType::Proposal { author, .. } | BlockType::DAGBlock { author, .. } => Some(author),
+            _ => None,
         }
     }
 
@@ -99,10 +113,11 @@ impl BlockData {
     }
 
     pub fn payload(&self) -> Option<&Payload> {
-        if let BlockType::Proposal { payload, .. } = &self.block_type {
-            Some(payload)
-        } else {
-            None
+        match &self.block_type {
+            BlockType::Proposal { payload, .. } | BlockType::DAGBlock { payload, .. } => {
+                Some(payload)
+            },
+            _ => None,
         }
     }
 
@@ -126,6 +141,10 @@ impl BlockData {
         matches!(self.block_type, BlockType::NilBlock { .. })
     }
 
+    pub fn is_dag_block(&self) -> bool {
+        matches!(self.block_type, BlockType::DAGBlock { .. })
+    }
+
     /// the list of consecutive proposers from the immediately preceeding
     /// rounds that didn't produce a successful block
     pub fn failed_authors(&self) -> Option<&Vec<(Round, Author)>> {
@@ -134,6 +153,9 @@ impl BlockData {
                 ref failed_authors, ..
             } => Some(failed_authors),
             BlockType::NilBlock { ref failed_authors } => Some(failed_authors),
+            BlockType::DAGBlock {
+                ref failed_authors, ..
+            } => Some(failed_authors),
             BlockType::Genesis => None,
         }
     }
@@ -232,6 +254,43 @@ impl BlockData {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_for_dag(
+        epoch: u64,
+        round: Round,
+        timestamp_usecs: u64,
+        payload: Payload,
+        author: Author,
+        failed_authors: Vec<(Round, Author)>,
+        parent_block_info: BlockInfo,
+        parents_bitvec: BitVec,
+        node_digests: Vec<HashValue>,
+    ) -> Self {
+        // DAG blocks are chained by a placeholder quorum certificate to their parent, the ordering
+        // itself is certified by the DAG.
+        let quorum_cert = QuorumCert::new(
+            VoteData::new(parent_block_info.clone(), parent_block_info.clone()),
+            LedgerInfoWithSignatures::new(
+                LedgerInfo::new(parent_block_info, HashValue::zero()),
+                AggregateSignature::empty(),
+            ),
+        );
+
+        Self {
+            epoch,
+            round,
+            timestamp_usecs,
+            quorum_cert,
+            block_type: BlockType::DAGBlock {
+                author,
+                payload,
+                failed_authors,
+                node_digests,
+                parents_bitvec,
+            },
+        }
+    }
+
     /// It's a reconfiguration suffix block if the parent block's executed state indicates next epoch.
     pub fn is_reconfiguration_suffix(&self) -> bool {
         self.quorum_cert.certified_block().has_reconfiguration()
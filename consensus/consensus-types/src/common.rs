@@ -130,6 +130,33 @@ impl Payload {
         matches!(self, Payload::DirectMempool(_))
     }
 
+    /// Merges the other payload into this one, used to build a single block out of the payloads
+    /// of multiple DAG nodes. Batches included by more than one node are only kept once.
+    pub fn extend(self, other: Payload) -> Self {
+        match (self, other) {
+            (Payload::DirectMempool(mut txns), Payload::DirectMempool(other_txns)) => {
+                txns.extend(other_txns);
+                Payload::DirectMempool(txns)
+            },
+            (Payload::InQuorumStore(proof_with_status), Payload::InQuorumStore(other)) => {
+                let mut digests: HashSet<_> = proof_with_status
+                    .proofs
+                    .iter()
+                    .map(|proof| *proof.digest())
+                    .collect();
+                let mut proofs = proof_with_status.proofs;
+                proofs.extend(
+                    other
+                        .proofs
+                        .into_iter()
+                        .filter(|proof| digests.insert(*proof.digest())),
+                );
+                Payload::InQuorumStore(ProofWithData::new(proofs))
+            },
+            (_, _) => unreachable!("Cannot extend payloads of different types"),
+        }
+    }
+
     /// This is computationally expensive on the first call
     pub fn size(&self) -> usize {
         match self {
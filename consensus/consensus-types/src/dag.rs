@@ -0,0 +1,584 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Payload, Round};
+use anyhow::{ensure, Context};
+use aptos_crypto::{
+    bls12381,
+    hash::{CryptoHash, CryptoHasher},
+    CryptoMaterialError, HashValue,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_types::{
+    aggregate_signature::AggregateSignature, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    ops::Deref,
+};
+
+/// The identifying information of a DAG node, this is what validators sign to certify a node.
+#[derive(
+    Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, CryptoHasher, BCSCryptoHash,
+)]
+pub struct NodeMetadata {
+    epoch: u64,
+    round: Round,
+    author: Author,
+    timestamp_usecs: u64,
+    digest: HashValue,
+}
+
+impl NodeMetadata {
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn new_for_test(
+        epoch: u64,
+        round: Round,
+        author: Author,
+        timestamp_usecs: u64,
+        digest: HashValue,
+    ) -> Self {
+        Self {
+            epoch,
+            round,
+            author,
+            timestamp_usecs,
+            digest,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    pub fn author(&self) -> &Author {
+        &self.author
+    }
+
+    pub fn timestamp_usecs(&self) -> u64 {
+        self.timestamp_usecs
+    }
+
+    pub fn digest(&self) -> &HashValue {
+        &self.digest
+    }
+}
+
+impl Display for NodeMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[epoch: {}, round: {}, author: {}, digest: {}]",
+            self.epoch, self.round, self.author, self.digest
+        )
+    }
+}
+
+/// The contents of a node that its digest is computed over.
+#[derive(Serialize)]
+struct NodeWithoutDigest<'a> {
+    epoch: u64,
+    round: Round,
+    author: Author,
+    timestamp_usecs: u64,
+    payload: &'a Payload,
+    parents: &'a [NodeCertificate],
+}
+
+impl<'a> CryptoHash for NodeWithoutDigest<'a> {
+    type Hasher = NodeHasher;
+
+    fn hash(&self) -> HashValue {
+        let mut state = Self::Hasher::new();
+        let bytes = bcs::to_bytes(&self).expect("Unable to serialize node");
+        state.update(&bytes);
+        state.finish()
+    }
+}
+
+/// A node in the DAG, proposed by its author once per round. It carries the author's payload
+/// and the certificates of (at least 2f+1 voting power of) the nodes from the previous round.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, CryptoHasher)]
+pub struct Node {
+    metadata: NodeMetadata,
+    payload: Payload,
+    parents: Vec<NodeCertificate>,
+}
+
+impl Node {
+    pub fn new(
+        epoch: u64,
+        round: Round,
+        author: Author,
+        timestamp_usecs: u64,
+        payload: Payload,
+        parents: Vec<NodeCertificate>,
+    ) -> Self {
+        let digest = Self::calculate_digest_internal(
+            epoch,
+            round,
+            author,
+            timestamp_usecs,
+            &payload,
+            &parents,
+        );
+
+        Self {
+            metadata: NodeMetadata {
+                epoch,
+                round,
+                author,
+                timestamp_usecs,
+                digest,
+            },
+            payload,
+            parents,
+        }
+    }
+
+    fn calculate_digest_internal(
+        epoch: u64,
+        round: Round,
+        author: Author,
+        timestamp_usecs: u64,
+        payload: &Payload,
+        parents: &[NodeCertificate],
+    ) -> HashValue {
+        let node_with_out_digest = NodeWithoutDigest {
+            epoch,
+            round,
+            author,
+            timestamp_usecs,
+            payload,
+            parents,
+        };
+        node_with_out_digest.hash()
+    }
+
+    fn calculate_digest(&self) -> HashValue {
+        Self::calculate_digest_internal(
+            self.metadata.epoch,
+            self.metadata.round,
+            self.metadata.author,
+            self.metadata.timestamp_usecs,
+            &self.payload,
+            &self.parents,
+        )
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.metadata.digest
+    }
+
+    pub fn metadata(&self) -> &NodeMetadata {
+        &self.metadata
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    pub fn parents(&self) -> &[NodeCertificate] {
+        &self.parents
+    }
+
+    pub fn parents_metadata(&self) -> impl Iterator<Item = &NodeMetadata> {
+        self.parents.iter().map(|cert| cert.metadata())
+    }
+
+    pub fn author(&self) -> &Author {
+        self.metadata.author()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.metadata.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.metadata.round
+    }
+
+    pub fn timestamp_usecs(&self) -> u64 {
+        self.metadata.timestamp_usecs
+    }
+
+    /// Verifies that the node is well formed and that it is authored by the sender. The first
+    /// round of an epoch has no parents, every later round links to a quorum of the previous one.
+    pub fn verify(
+        &self,
+        sender: Author,
+        validator: &ValidatorVerifier,
+        quorum_store_enabled: bool,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            *self.author() == sender,
+            "Sender {} mismatch node author {}",
+            sender,
+            self.author()
+        );
+        ensure!(
+            self.digest() == self.calculate_digest(),
+            "Invalid node digest"
+        );
+        ensure!(self.round() > 0, "Node round must be positive");
+
+        let prev_round = self.round() - 1;
+        let mut parent_authors = HashSet::new();
+        for parent in &self.parents {
+            ensure!(
+                parent.epoch() == self.epoch(),
+                "Parent {} is in a different epoch",
+                parent.metadata()
+            );
+            ensure!(
+                parent.round() == prev_round,
+                "Parent {} is not from the previous round",
+                parent.metadata()
+            );
+            ensure!(
+                parent_authors.insert(*parent.author()),
+                "Duplicated parent author {}",
+                parent.author()
+            );
+        }
+        if prev_round > 0 {
+            validator
+                .check_voting_power(parent_authors.iter())
+                .context("Not enough voting power in parents")?;
+        }
+        for parent in &self.parents {
+            parent.verify(validator)?;
+        }
+
+        self.payload.verify(validator, quorum_store_enabled)
+    }
+}
+
+/// A quorum of signatures on the metadata of a node, the parents of a node are referenced by
+/// their certificates.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NodeCertificate {
+    metadata: NodeMetadata,
+    signatures: AggregateSignature,
+}
+
+impl NodeCertificate {
+    pub fn new(metadata: NodeMetadata, signatures: AggregateSignature) -> Self {
+        Self {
+            metadata,
+            signatures,
+        }
+    }
+
+    pub fn metadata(&self) -> &NodeMetadata {
+        &self.metadata
+    }
+
+    pub fn signatures(&self) -> &AggregateSignature {
+        &self.signatures
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify_multi_signatures(&self.metadata, &self.signatures)
+            .context("Failed to verify NodeCertificate")
+    }
+}
+
+impl Deref for NodeCertificate {
+    type Target = NodeMetadata;
+
+    fn deref(&self) -> &Self::Target {
+        &self.metadata
+    }
+}
+
+/// A node together with a quorum of signatures on its metadata. Only certified nodes are added
+/// to the DAG.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CertifiedNode {
+    node: Node,
+    signatures: AggregateSignature,
+}
+
+impl CertifiedNode {
+    pub fn new(node: Node, signatures: AggregateSignature) -> Self {
+        Self { node, signatures }
+    }
+
+    pub fn signatures(&self) -> &AggregateSignature {
+        &self.signatures
+    }
+
+    pub fn certificate(&self) -> NodeCertificate {
+        NodeCertificate::new(self.node.metadata.clone(), self.signatures.clone())
+    }
+
+    pub fn verify(
+        &self,
+        validator: &ValidatorVerifier,
+        quorum_store_enabled: bool,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            self.node.digest() == self.node.calculate_digest(),
+            "Invalid node digest"
+        );
+        validator
+            .verify_multi_signatures(self.node.metadata(), &self.signatures)
+            .context("Failed to verify CertifiedNode signatures")?;
+        self.node.payload.verify(validator, quorum_store_enabled)
+    }
+}
+
+impl Deref for CertifiedNode {
+    type Target = Node;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+/// A signature of a validator on the metadata of a node, sent back to the node's author.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Vote {
+    metadata: NodeMetadata,
+    signature: bls12381::Signature,
+}
+
+impl Vote {
+    pub fn new(
+        metadata: NodeMetadata,
+        validator_signer: &ValidatorSigner,
+    ) -> Result<Self, CryptoMaterialError> {
+        let signature = validator_signer.sign(&metadata)?;
+        Ok(Self::new_with_signature(metadata, signature))
+    }
+
+    pub fn new_with_signature(metadata: NodeMetadata, signature: bls12381::Signature) -> Self {
+        Self {
+            metadata,
+            signature,
+        }
+    }
+
+    pub fn metadata(&self) -> &NodeMetadata {
+        &self.metadata
+    }
+
+    pub fn signature(&self) -> &bls12381::Signature {
+        &self.signature
+    }
+
+    pub fn verify(&self, author: Author, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(author, &self.metadata, &self.signature)
+            .context("Failed to verify Vote")
+    }
+}
+
+/// The acknowledgement of a certified node, the author stops re-sending it once received.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CertifiedAck {
+    epoch: u64,
+}
+
+impl CertifiedAck {
+    pub fn new(epoch: u64) -> Self {
+        Self { epoch }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+/// Which nodes the requester already has, for each round starting at `first_round`, indexed by
+/// the validator index of the author.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DagSnapshotBitmask {
+    bitmask: Vec<Vec<bool>>,
+    first_round: Round,
+}
+
+impl DagSnapshotBitmask {
+    pub fn new(first_round: Round, bitmask: Vec<Vec<bool>>) -> Self {
+        Self {
+            bitmask,
+            first_round,
+        }
+    }
+
+    pub fn first_round(&self) -> Round {
+        self.first_round
+    }
+
+    pub fn has(&self, round: Round, author_idx: usize) -> bool {
+        round
+            .checked_sub(self.first_round)
+            .and_then(|idx| self.bitmask.get(idx as usize))
+            .and_then(|round_bitmask| round_bitmask.get(author_idx))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+/// Asks a peer for the causal history of the given targets, excluding the nodes the requester
+/// already has.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteFetchRequest {
+    epoch: u64,
+    targets: Vec<NodeMetadata>,
+    exists_bitmask: DagSnapshotBitmask,
+}
+
+impl RemoteFetchRequest {
+    pub fn new(epoch: u64, targets: Vec<NodeMetadata>, exists_bitmask: DagSnapshotBitmask) -> Self {
+        Self {
+            epoch,
+            targets,
+            exists_bitmask,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn targets(&self) -> &[NodeMetadata] {
+        &self.targets
+    }
+
+    pub fn exists_bitmask(&self) -> &DagSnapshotBitmask {
+        &self.exists_bitmask
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(!self.targets.is_empty(), "Empty fetch request");
+        for target in &self.targets {
+            ensure!(
+                target.epoch() == self.epoch,
+                "Fetch target {} is in a different epoch",
+                target
+            );
+            ensure!(
+                validator.get_voting_power(target.author()).is_some(),
+                "Fetch target {} has an unknown author",
+                target
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The certified nodes in the causal history of the requested targets.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FetchResponse {
+    epoch: u64,
+    certified_nodes: Vec<CertifiedNode>,
+}
+
+impl FetchResponse {
+    pub fn new(epoch: u64, certified_nodes: Vec<CertifiedNode>) -> Self {
+        Self {
+            epoch,
+            certified_nodes,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn certified_nodes(self) -> Vec<CertifiedNode> {
+        self.certified_nodes
+    }
+
+    pub fn verify(
+        &self,
+        request: &RemoteFetchRequest,
+        validator: &ValidatorVerifier,
+        quorum_store_enabled: bool,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            self.epoch == request.epoch(),
+            "Fetch response epoch {} mismatch request epoch {}",
+            self.epoch,
+            request.epoch()
+        );
+        for node in &self.certified_nodes {
+            ensure!(
+                node.epoch() == self.epoch,
+                "Fetched node {} is in a different epoch",
+                node.metadata()
+            );
+            node.verify(validator, quorum_store_enabled)?;
+        }
+        Ok(())
+    }
+}
+
+/// The serialized DAG protocol message, the epoch is kept outside so that messages can be
+/// routed without deserializing the inner message.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DAGNetworkMessage {
+    epoch: u64,
+    data: Vec<u8>,
+}
+
+impl DAGNetworkMessage {
+    pub fn new(epoch: u64, data: Vec<u8>) -> Self {
+        Self { epoch, data }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Identifies the node of an author in a round, at most one node per author is voted for in
+/// each round.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId {
+    epoch: u64,
+    round: Round,
+    author: Author,
+}
+
+impl NodeId {
+    pub fn new(epoch: u64, round: Round, author: Author) -> Self {
+        Self {
+            epoch,
+            round,
+            author,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    pub fn author(&self) -> &Author {
+        &self.author
+    }
+}
+
+impl From<&NodeMetadata> for NodeId {
+    fn from(metadata: &NodeMetadata) -> Self {
+        Self::new(metadata.epoch, metadata.round, metadata.author)
+    }
+}
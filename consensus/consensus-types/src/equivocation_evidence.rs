@@ -0,0 +1,247 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{block::Block, common::Author, vote::Vote};
+use anyhow::{ensure, format_err};
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{block_info::Round, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A pair of conflicting messages signed by the same validator for the same epoch and round.
+/// The evidence is self-contained: anyone holding the validator set of the epoch can check it
+/// with `verify`, without trusting the node that collected it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum EquivocationEvidence {
+    /// Two votes for different ledger infos in the same round
+    Vote(Vote, Vote),
+    /// Two proposals of different blocks in the same round
+    Proposal(Block, Block),
+}
+
+impl EquivocationEvidence {
+    /// Returns the name of the evidence kind (e.g., for logging and metrics)
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquivocationEvidence::Vote(..) => "vote",
+            EquivocationEvidence::Proposal(..) => "proposal",
+        }
+    }
+
+    /// Returns the author of the conflicting messages
+    pub fn author(&self) -> Option<Author> {
+        match self {
+            EquivocationEvidence::Vote(vote, _) => Some(vote.author()),
+            EquivocationEvidence::Proposal(block, _) => block.author(),
+        }
+    }
+
+    /// Returns the epoch of the conflicting messages
+    pub fn epoch(&self) -> u64 {
+        match self {
+            EquivocationEvidence::Vote(vote, _) => vote.epoch(),
+            EquivocationEvidence::Proposal(block, _) => block.epoch(),
+        }
+    }
+
+    /// Returns the round of the conflicting messages
+    pub fn round(&self) -> Round {
+        match self {
+            EquivocationEvidence::Vote(vote, _) => vote.vote_data().proposed().round(),
+            EquivocationEvidence::Proposal(block, _) => block.round(),
+        }
+    }
+
+    /// Verifies that both messages are correctly signed by the same author of the given
+    /// validator set, that they are for the same epoch and round, and that they conflict.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            EquivocationEvidence::Vote(first, second) => {
+                first.verify(validator)?;
+                second.verify(validator)?;
+                ensure!(
+                    first.author() == second.author(),
+                    "The votes have different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.vote_data().proposed().round())
+                        == (second.epoch(), second.vote_data().proposed().round()),
+                    "The votes are for different rounds"
+                );
+                ensure!(
+                    first.ledger_info().hash() != second.ledger_info().hash(),
+                    "The votes are for the same ledger info"
+                );
+            },
+            EquivocationEvidence::Proposal(first, second) => {
+                first.validate_signature(validator)?;
+                second.validate_signature(validator)?;
+                let author = first
+                    .author()
+                    .ok_or_else(|| format_err!("The first block is not a proposal"))?;
+                ensure!(
+                    second.author() == Some(author),
+                    "The proposals have different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.round()) == (second.epoch(), second.round()),
+                    "The proposals are for different rounds"
+                );
+                ensure!(
+                    first.id() != second.id(),
+                    "The proposals are for the same block"
+                );
+            },
+        }
+        Ok(())
+    }
+}
+
+impl Display for EquivocationEvidence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "EquivocationEvidence: [kind: {}, author: {:?}, epoch: {}, round: {}]",
+            self.name(),
+            self.author(),
+            self.epoch(),
+            self.round()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EquivocationEvidence;
+    use crate::{
+        block::{block_test_utils::certificate_for_genesis, Block},
+        common::Payload,
+        quorum_cert::QuorumCert,
+        timeout_2chain::TwoChainTimeout,
+        vote::Vote,
+        vote_data::VoteData,
+    };
+    use aptos_crypto::{hash::CryptoHash, HashValue};
+    use aptos_types::{
+        aggregate_signature::PartialSignatures,
+        block_info::BlockInfo,
+        ledger_info::{LedgerInfo, LedgerInfoWithPartialSignatures},
+        validator_signer::ValidatorSigner,
+        validator_verifier::{random_validator_verifier, ValidatorVerifier},
+    };
+
+    /// Creates a vote of the given signer for a random block in epoch 1 and the given round
+    fn create_vote(signer: &ValidatorSigner, round: u64) -> Vote {
+        let proposed = BlockInfo::new(1, round, HashValue::random(), HashValue::zero(), 0, 0, None);
+        let vote_data = VoteData::new(proposed, BlockInfo::random(0));
+        let ledger_info = LedgerInfo::new(BlockInfo::empty(), HashValue::zero());
+        Vote::new(vote_data, signer.author(), ledger_info, signer).unwrap()
+    }
+
+    /// Creates a quorum cert signed by all signers for a random block in the given round
+    fn create_quorum_cert(
+        signers: &[ValidatorSigner],
+        validators: &ValidatorVerifier,
+        round: u64,
+    ) -> QuorumCert {
+        let vote_data = VoteData::new(BlockInfo::random(round), BlockInfo::random(0));
+        let mut ledger_info = LedgerInfoWithPartialSignatures::new(
+            LedgerInfo::new(BlockInfo::empty(), vote_data.hash()),
+            PartialSignatures::empty(),
+        );
+        for signer in signers {
+            let signature = signer.sign(ledger_info.ledger_info()).unwrap();
+            ledger_info.add_signature(signer.author(), signature);
+        }
+        QuorumCert::new(
+            vote_data,
+            ledger_info.aggregate_signatures(validators).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_vote_evidence() {
+        let (signers, validators) = random_validator_verifier(4, None, false);
+
+        // Conflicting votes from the same author are valid evidence
+        let first_vote = create_vote(&signers[0], 1);
+        let second_vote = create_vote(&signers[0], 1);
+        let evidence = EquivocationEvidence::Vote(first_vote.clone(), second_vote);
+        evidence.verify(&validators).unwrap();
+        assert_eq!(evidence.author(), Some(signers[0].author()));
+        assert_eq!((evidence.epoch(), evidence.round()), (1, 1));
+
+        // The same vote twice is not evidence
+        EquivocationEvidence::Vote(first_vote.clone(), first_vote.clone())
+            .verify(&validators)
+            .unwrap_err();
+
+        // Votes from different authors are not evidence
+        EquivocationEvidence::Vote(first_vote.clone(), create_vote(&signers[1], 1))
+            .verify(&validators)
+            .unwrap_err();
+
+        // Votes for different rounds are not evidence
+        EquivocationEvidence::Vote(first_vote, create_vote(&signers[0], 2))
+            .verify(&validators)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_proposal_evidence() {
+        let (signers, validators) = random_validator_verifier(4, None, false);
+        let create_proposal = |signer: &ValidatorSigner, timestamp| {
+            Block::new_proposal(
+                Payload::empty(false),
+                1,
+                timestamp,
+                certificate_for_genesis(),
+                signer,
+                vec![],
+            )
+            .unwrap()
+        };
+
+        // Conflicting proposals from the same author are valid evidence
+        let first_block = create_proposal(&signers[0], 1);
+        let evidence =
+            EquivocationEvidence::Proposal(first_block.clone(), create_proposal(&signers[0], 2));
+        evidence.verify(&validators).unwrap();
+        assert_eq!(evidence.author(), Some(signers[0].author()));
+
+        // The same proposal twice is not evidence
+        EquivocationEvidence::Proposal(first_block.clone(), first_block.clone())
+            .verify(&validators)
+            .unwrap_err();
+
+        // Proposals from different authors are not evidence
+        EquivocationEvidence::Proposal(first_block, create_proposal(&signers[1], 2))
+            .verify(&validators)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_timeout_pair_is_not_evidence() {
+        let (signers, validators) = random_validator_verifier(4, None, false);
+        let vote = create_vote(&signers[0], 4);
+        let create_timeout_vote = |qc_round| {
+            let timeout =
+                TwoChainTimeout::new(1, 4, create_quorum_cert(&signers, &validators, qc_round));
+            let signature = timeout.sign(&signers[0]).unwrap();
+            let mut timeout_vote = vote.clone();
+            timeout_vote.add_2chain_timeout(timeout, signature);
+            timeout_vote
+        };
+
+        // A timeout legitimately re-signed with a higher highest quorum cert is not evidence,
+        // in either order (the order the timeouts were received in isn't signed)
+        let first_timeout_vote = create_timeout_vote(1);
+        let second_timeout_vote = create_timeout_vote(2);
+        EquivocationEvidence::Vote(first_timeout_vote.clone(), second_timeout_vote.clone())
+            .verify(&validators)
+            .unwrap_err();
+        EquivocationEvidence::Vote(second_timeout_vote, first_timeout_vote)
+            .verify(&validators)
+            .unwrap_err();
+    }
+}
@@ -11,10 +11,9 @@ use crate::{
 use aptos_crypto::hash::HashValue;
 use aptos_executor_types::StateComputeResult;
 use aptos_types::{
-    account_address::AccountAddress,
     block_info::BlockInfo,
     contract_event::ContractEvent,
-    transaction::{SignedTransaction, Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus},
 };
 use std::fmt::{Debug, Display, Formatter};
 
@@ -104,10 +103,11 @@ impl ExecutedBlock {
         )
     }
 
+    /// Returns the transactions committed by the block, given the transactions it was executed
+    /// with, in execution order.
     pub fn transactions_to_commit(
         &self,
-        validators: &[AccountAddress],
-        txns: Vec<SignedTransaction>,
+        executed_txns: Vec<Transaction>,
         block_gas_limit: Option<u64>,
     ) -> Vec<Transaction> {
         // reconfiguration suffix don't execute
@@ -116,9 +116,7 @@ impl ExecutedBlock {
             return vec![];
         }
 
-        let mut txns_with_state_checkpoint =
-            self.block
-                .transactions_to_execute(validators, txns, block_gas_limit);
+        let mut txns_with_state_checkpoint = executed_txns;
         if block_gas_limit.is_some() && !self.state_compute_result.has_reconfiguration() {
             // After the per-block gas limit change,
             // insert state checkpoint at the position
@@ -8,7 +8,9 @@ pub mod block;
 pub mod block_data;
 pub mod block_retrieval;
 pub mod common;
+pub mod dag;
 pub mod epoch_retrieval;
+pub mod equivocation_evidence;
 pub mod executed_block;
 pub mod experimental;
 pub mod proof_of_store;
@@ -5,6 +5,7 @@
 use crate::{ConsensusState, Error, SafetyRules, TSafetyRules};
 use aptos_consensus_types::{
     block_data::BlockData,
+    dag::NodeMetadata,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
     vote_proposal::VoteProposal,
@@ -72,4 +73,18 @@ impl TSafetyRules for LocalClient {
             .write()
             .sign_commit_vote(ledger_info, new_ledger_info)
     }
+
+    fn sign_dag_vote(&mut self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        self.internal.write().sign_dag_vote(metadata)
+    }
+
+    fn sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        self.internal
+            .write()
+            .sign_dag_commit_vote(ordered_ledger_info, new_ledger_info)
+    }
 }
@@ -54,6 +54,8 @@ pub enum LogEntry {
     State,
     Waypoint,
     SignCommitVote,
+    SignDagVote,
+    SignDagCommitVote,
 }
 
 impl LogEntry {
@@ -72,6 +74,8 @@ impl LogEntry {
             LogEntry::State => "state",
             LogEntry::Waypoint => "waypoint",
             LogEntry::SignCommitVote => "sign_commit_vote",
+            LogEntry::SignDagVote => "sign_dag_vote",
+            LogEntry::SignDagCommitVote => "sign_dag_commit_vote",
         }
     }
 }
@@ -8,6 +8,7 @@ use crate::{
     safety_rules_manager,
 };
 use aptos_config::config::{SafetyRulesConfig, SafetyRulesService};
+use aptos_secure_net::{NoiseClientConfig, NoiseServerConfig};
 use std::net::SocketAddr;
 
 pub struct Process {
@@ -18,24 +19,41 @@ impl Process {
     pub fn new(config: SafetyRulesConfig) -> Self {
         let storage = safety_rules_manager::storage(&config);
 
-        let service = match &config.service {
-            SafetyRulesService::Process(service) => service,
+        let (server_addr, noise) = match &config.service {
+            SafetyRulesService::Process(service) => (service.server_address(), None),
+            SafetyRulesService::AuthenticatedProcess(service) => {
+                let noise = NoiseServerConfig::new(
+                    service.private_key(),
+                    service.client_public_keys.clone(),
+                );
+                assert_eq!(
+                    noise.public_key(),
+                    service.server_public_key,
+                    "The identity of SafetyRules doesn't match the server public key"
+                );
+                (service.server_address(), Some(noise))
+            },
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
         };
-        let server_addr = service.server_address();
 
         Self {
             data: Some(ProcessData {
                 server_addr,
                 storage,
                 network_timeout: config.network_timeout_ms,
+                noise,
             }),
         }
     }
 
     pub fn start(&mut self) {
         let data = self.data.take().expect("Unable to retrieve ProcessData");
-        remote_service::execute(data.storage, data.server_addr, data.network_timeout);
+        remote_service::execute(
+            data.storage,
+            data.server_addr,
+            data.network_timeout,
+            data.noise,
+        );
     }
 }
 
@@ -44,11 +62,15 @@ struct ProcessData {
     storage: PersistentSafetyStorage,
     // Timeout in Seconds for network operations
     network_timeout: u64,
+    // Noise configuration of the server, if the clients must be authenticated
+    noise: Option<NoiseServerConfig>,
 }
 
 pub struct ProcessService {
     server_addr: SocketAddr,
     network_timeout_ms: u64,
+    noise: Option<NoiseClientConfig>,
+    request_timeout_ms: Option<u64>,
 }
 
 impl ProcessService {
@@ -56,6 +78,22 @@ impl ProcessService {
         Self {
             server_addr,
             network_timeout_ms: network_timeout,
+            noise: None,
+            request_timeout_ms: None,
+        }
+    }
+
+    pub fn new_authenticated(
+        server_addr: SocketAddr,
+        network_timeout: u64,
+        noise: NoiseClientConfig,
+        request_timeout_ms: u64,
+    ) -> Self {
+        Self {
+            server_addr,
+            network_timeout_ms: network_timeout,
+            noise: Some(noise),
+            request_timeout_ms: Some(request_timeout_ms),
         }
     }
 }
@@ -68,4 +106,12 @@ impl RemoteService for ProcessService {
     fn network_timeout_ms(&self) -> u64 {
         self.network_timeout_ms
     }
+
+    fn noise_config(&self) -> Option<NoiseClientConfig> {
+        self.noise.clone()
+    }
+
+    fn request_timeout_ms(&self) -> Option<u64> {
+        self.request_timeout_ms
+    }
 }
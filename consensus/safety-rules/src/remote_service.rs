@@ -8,17 +8,29 @@ use crate::{
     Error, SafetyRules, TSafetyRules,
 };
 use aptos_logger::warn;
-use aptos_secure_net::{NetworkClient, NetworkServer};
-use std::net::SocketAddr;
+use aptos_secure_net::{NetworkClient, NetworkServer, NoiseClientConfig, NoiseServerConfig};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
-        let network_client = NetworkClient::new(
-            "safety-rules",
-            self.server_address(),
-            self.network_timeout_ms(),
-        );
-        let service = Box::new(RemoteClient::new(network_client));
+        let network_client = match self.noise_config() {
+            Some(noise) => NetworkClient::new_with_noise(
+                "safety-rules",
+                self.server_address(),
+                self.network_timeout_ms(),
+                noise,
+            ),
+            None => NetworkClient::new(
+                "safety-rules",
+                self.server_address(),
+                self.network_timeout_ms(),
+            ),
+        };
+        let request_timeout = self.request_timeout_ms().map(Duration::from_millis);
+        let service = Box::new(RemoteClient::new(network_client, request_timeout));
         SerializerClient::new_client(service)
     }
 
@@ -26,16 +38,37 @@ pub trait RemoteService {
 
     /// Network Timeout in milliseconds.
     fn network_timeout_ms(&self) -> u64;
+
+    /// Noise configuration used to authenticate and encrypt the connection, if any.
+    fn noise_config(&self) -> Option<NoiseClientConfig> {
+        None
+    }
+
+    /// Request timeout in milliseconds, after which a failing request is no longer retried. If
+    /// there is none, requests are retried until they succeed.
+    fn request_timeout_ms(&self) -> Option<u64> {
+        None
+    }
 }
 
-pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, network_timeout_ms: u64) {
+pub fn execute(
+    storage: PersistentSafetyStorage,
+    listen_addr: SocketAddr,
+    network_timeout_ms: u64,
+    noise: Option<NoiseServerConfig>,
+) {
     let mut safety_rules = SafetyRules::new(storage);
     if let Err(e) = safety_rules.consensus_state() {
         warn!("Unable to print consensus state: {}", e);
     }
 
     let mut serializer_service = SerializerService::new(safety_rules);
-    let mut network_server = NetworkServer::new("safety-rules", listen_addr, network_timeout_ms);
+    let mut network_server = match noise {
+        Some(noise) => {
+            NetworkServer::new_with_noise("safety-rules", listen_addr, network_timeout_ms, noise)
+        },
+        None => NetworkServer::new("safety-rules", listen_addr, network_timeout_ms),
+    };
 
     loop {
         if let Err(e) = process_one_message(&mut network_server, &mut serializer_service) {
@@ -56,11 +89,15 @@ fn process_one_message(
 
 struct RemoteClient {
     network_client: NetworkClient,
+    request_timeout: Option<Duration>,
 }
 
 impl RemoteClient {
-    pub fn new(network_client: NetworkClient) -> Self {
-        Self { network_client }
+    pub fn new(network_client: NetworkClient, request_timeout: Option<Duration>) -> Self {
+        Self {
+            network_client,
+            request_timeout,
+        }
     }
 
     fn process_one_message(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
@@ -72,9 +109,22 @@ impl RemoteClient {
 impl TSerializerClient for RemoteClient {
     fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
         let input_message = serde_json::to_vec(&input)?;
+        let start = Instant::now();
         loop {
             match self.process_one_message(&input_message) {
-                Err(err) => warn!("Failed to communicate with SafetyRules service: {}", err),
+                Err(err) => {
+                    warn!("Failed to communicate with SafetyRules service: {}", err);
+                    // The connection is re-established by the next attempt
+                    if let Some(request_timeout) = self.request_timeout {
+                        if start.elapsed() >= request_timeout {
+                            return Err(Error::InternalError(format!(
+                                "SafetyRules request timed out after {} ms: {}",
+                                request_timeout.as_millis(),
+                                err
+                            )));
+                        }
+                    }
+                },
                 Ok(value) => return Ok(value),
             }
         }
@@ -13,6 +13,7 @@ use crate::{
 use aptos_consensus_types::{
     block_data::BlockData,
     common::{Author, Round},
+    dag::NodeMetadata,
     quorum_cert::QuorumCert,
     safety_data::SafetyData,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
@@ -360,6 +361,62 @@ impl SafetyRules {
 
         Ok(signature)
     }
+
+    fn guarded_sign_dag_vote(
+        &mut self,
+        metadata: &NodeMetadata,
+    ) -> Result<bls12381::Signature, Error> {
+        self.signer()?;
+
+        let safety_data = self.persistent_storage.safety_data()?;
+        self.verify_epoch(metadata.epoch(), &safety_data)?;
+
+        if self
+            .epoch_state()?
+            .verifier
+            .get_voting_power(metadata.author())
+            .is_none()
+        {
+            return Err(Error::InvalidProposal(format!(
+                "Author {} of node {} is not in the validator set",
+                metadata.author(),
+                metadata
+            )));
+        }
+
+        // At most one node per author and round is voted for, which the DAG enforces with the
+        // votes it persists before sending them.
+        self.sign(metadata)
+    }
+
+    fn guarded_sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        self.signer()?;
+
+        let safety_data = self.persistent_storage.safety_data()?;
+        self.verify_epoch(ordered_ledger_info.epoch(), &safety_data)?;
+
+        if !ordered_ledger_info.commit_info().is_ordered_only() {
+            return Err(Error::InvalidOrderedLedgerInfo(
+                ordered_ledger_info.to_string(),
+            ));
+        }
+
+        if !ordered_ledger_info
+            .commit_info()
+            .match_ordered_only(new_ledger_info.commit_info())
+        {
+            return Err(Error::InconsistentExecutionResult(
+                ordered_ledger_info.commit_info().to_string(),
+                new_ledger_info.commit_info().to_string(),
+            ));
+        }
+
+        self.sign(&new_ledger_info)
+    }
 }
 
 impl TSafetyRules for SafetyRules {
@@ -414,6 +471,22 @@ impl TSafetyRules for SafetyRules {
         let cb = || self.guarded_sign_commit_vote(ledger_info, new_ledger_info);
         run_and_log(cb, |log| log, LogEntry::SignCommitVote)
     }
+
+    fn sign_dag_vote(&mut self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        let round = metadata.round();
+        let cb = || self.guarded_sign_dag_vote(metadata);
+        run_and_log(cb, |log| log.round(round), LogEntry::SignDagVote)
+    }
+
+    fn sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        let round = ordered_ledger_info.round();
+        let cb = || self.guarded_sign_dag_commit_vote(ordered_ledger_info, new_ledger_info);
+        run_and_log(cb, |log| log.round(round), LogEntry::SignDagCommitVote)
+    }
 }
 
 fn run_and_log<F, L, R>(callback: F, log_cb: L, log_entry: LogEntry) -> Result<R, Error>
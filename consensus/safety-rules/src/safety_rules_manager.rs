@@ -13,6 +13,7 @@ use crate::{
 };
 use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
 use aptos_infallible::RwLock;
+use aptos_secure_net::NoiseClientConfig;
 use aptos_secure_storage::{KVStorage, Storage};
 use std::{convert::TryInto, net::SocketAddr, sync::Arc};
 
@@ -90,6 +91,15 @@ impl SafetyRulesManager {
         if let SafetyRulesService::Process(conf) = &config.service {
             return Self::new_process(conf.server_address(), config.network_timeout_ms);
         }
+        if let SafetyRulesService::AuthenticatedProcess(conf) = &config.service {
+            let noise = NoiseClientConfig::new(conf.private_key(), conf.server_public_key);
+            return Self::new_authenticated_process(
+                conf.server_address(),
+                config.network_timeout_ms,
+                noise,
+                conf.request_timeout_ms,
+            );
+        }
 
         let storage = storage(config);
         match config.service {
@@ -114,6 +124,19 @@ impl SafetyRulesManager {
         }
     }
 
+    pub fn new_authenticated_process(
+        server_addr: SocketAddr,
+        timeout_ms: u64,
+        noise: NoiseClientConfig,
+        request_timeout_ms: u64,
+    ) -> Self {
+        let process_service =
+            ProcessService::new_authenticated(server_addr, timeout_ms, noise, request_timeout_ms);
+        Self {
+            internal_safety_rules: SafetyRulesWrapper::Process(process_service),
+        }
+    }
+
     pub fn new_serializer(storage: PersistentSafetyStorage) -> Self {
         let safety_rules = SafetyRules::new(storage);
         let serializer_service = SerializerService::new(safety_rules);
@@ -5,6 +5,7 @@
 use crate::{counters, logging::LogEntry, ConsensusState, Error, SafetyRules, TSafetyRules};
 use aptos_consensus_types::{
     block_data::BlockData,
+    dag::NodeMetadata,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
     vote_proposal::VoteProposal,
@@ -29,6 +30,8 @@ pub enum SafetyRulesInput {
     ),
     ConstructAndSignVoteTwoChain(Box<VoteProposal>, Box<Option<TwoChainTimeoutCertificate>>),
     SignCommitVote(Box<LedgerInfoWithSignatures>, Box<LedgerInfo>),
+    SignDagVote(Box<NodeMetadata>),
+    SignDagCommitVote(Box<LedgerInfo>, Box<LedgerInfo>),
 }
 
 pub struct SerializerService {
@@ -69,6 +72,16 @@ impl SerializerService {
                     .internal
                     .sign_commit_vote(*ledger_info, *new_ledger_info),
             ),
+            SafetyRulesInput::SignDagVote(metadata) => {
+                serde_json::to_vec(&self.internal.sign_dag_vote(&metadata))
+            },
+            SafetyRulesInput::SignDagCommitVote(ordered_ledger_info, new_ledger_info) => {
+                serde_json::to_vec(
+                    &self
+                        .internal
+                        .sign_dag_commit_vote(*ordered_ledger_info, *new_ledger_info),
+                )
+            },
         };
 
         Ok(output?)
@@ -153,6 +166,25 @@ impl TSafetyRules for SerializerClient {
         ))?;
         serde_json::from_slice(&response)?
     }
+
+    fn sign_dag_vote(&mut self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        let _timer = counters::start_timer("external", LogEntry::SignDagVote.as_str());
+        let response = self.request(SafetyRulesInput::SignDagVote(Box::new(metadata.clone())))?;
+        serde_json::from_slice(&response)?
+    }
+
+    fn sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        let _timer = counters::start_timer("external", LogEntry::SignDagCommitVote.as_str());
+        let response = self.request(SafetyRulesInput::SignDagCommitVote(
+            Box::new(ordered_ledger_info),
+            Box::new(new_ledger_info),
+        ))?;
+        serde_json::from_slice(&response)?
+    }
 }
 
 pub trait TSerializerClient: Send + Sync {
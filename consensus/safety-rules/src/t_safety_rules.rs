@@ -5,6 +5,7 @@
 use crate::{ConsensusState, Error};
 use aptos_consensus_types::{
     block_data::BlockData,
+    dag::NodeMetadata,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
     vote_proposal::VoteProposal,
@@ -52,4 +53,16 @@ pub trait TSafetyRules {
         ledger_info: LedgerInfoWithSignatures,
         new_ledger_info: LedgerInfo,
     ) -> Result<bls12381::Signature, Error>;
+
+    /// As the holder of the private key, SafetyRules also signs the votes that certify the nodes
+    /// of the DAG. This returns the signature over the metadata of the node.
+    fn sign_dag_vote(&mut self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error>;
+
+    /// Signs a commit vote for a block ordered by the DAG. Unlike `sign_commit_vote`, the ordered
+    /// ledger info carries no quorum signatures, as the order is agreed upon through the DAG.
+    fn sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error>;
 }
@@ -2,8 +2,16 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, SafetyRulesManager};
+use crate::{remote_service, test_utils, SafetyRulesManager};
+use aptos_config::utils;
+use aptos_crypto::{x25519, Uniform};
+use aptos_secure_net::{NoiseClientConfig, NoiseServerConfig};
 use aptos_types::validator_signer::ValidatorSigner;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    thread,
+};
 
 #[test]
 fn test_reconnect() {
@@ -18,3 +26,50 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+#[test]
+fn test_authenticated_process() {
+    let signer = ValidatorSigner::from_int(0);
+    let storage = test_utils::test_storage(&signer);
+    // test values for network and request timeouts, in milliseconds.
+    let network_timeout = 5_000;
+    let request_timeout = 1_000;
+
+    let client_key = x25519::PrivateKey::generate_for_testing();
+    let client_public_key = client_key.public_key();
+    let server_noise = NoiseServerConfig::new(
+        x25519::PrivateKey::generate(&mut rand::rngs::OsRng),
+        HashSet::from([client_public_key]),
+    );
+    let server_public_key = server_noise.public_key();
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    thread::spawn(move || {
+        remote_service::execute(storage, server_addr, network_timeout, Some(server_noise))
+    });
+
+    // Verify that after a client has disconnected a new client will connect and resume operations
+    let safety_rules_manager = SafetyRulesManager::new_authenticated_process(
+        server_addr,
+        network_timeout,
+        NoiseClientConfig::new(client_key, server_public_key),
+        request_timeout,
+    );
+    let state0 = safety_rules_manager.client().consensus_state().unwrap();
+    let state1 = safety_rules_manager.client().consensus_state().unwrap();
+    assert_eq!(state0, state1);
+
+    // An untrusted client is rejected, and its requests time out
+    let untrusted_safety_rules_manager = SafetyRulesManager::new_authenticated_process(
+        server_addr,
+        network_timeout,
+        NoiseClientConfig::new(
+            x25519::PrivateKey::generate(&mut rand::rngs::OsRng),
+            server_public_key,
+        ),
+        request_timeout,
+    );
+    untrusted_safety_rules_manager
+        .client()
+        .consensus_state()
+        .unwrap_err();
+}
@@ -6,6 +6,7 @@ use crate::{test_utils, test_utils::make_timeout_cert, Error, TSafetyRules};
 use aptos_consensus_types::{
     block::block_test_utils::random_payload,
     common::{Payload, Round},
+    dag::Node,
     quorum_cert::QuorumCert,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote_proposal::VoteProposal,
@@ -56,6 +57,8 @@ pub fn run_test_suite(safety_rules: &Callback) {
     test_2chain_rules(safety_rules);
     test_2chain_timeout(safety_rules);
     test_sign_commit_vote(safety_rules);
+    test_sign_dag_vote(safety_rules);
+    test_sign_dag_commit_vote(safety_rules);
     test_bad_execution_output(safety_rules);
 }
 
@@ -669,3 +672,92 @@ fn test_sign_commit_vote(constructor: &Callback) {
         Error::InconsistentExecutionResult(_, _)
     ));
 }
+
+/// Test that the votes for DAG nodes are only signed for validators of the current epoch
+fn test_sign_dag_vote(constructor: &Callback) {
+    let (mut safety_rules, signer) = constructor();
+    let (proof, _genesis_qc) = test_utils::make_genesis(&signer);
+    let node = Node::new(1, 1, signer.author(), 0, Payload::empty(false), vec![]);
+
+    assert_eq!(
+        safety_rules.sign_dag_vote(node.metadata()).unwrap_err(),
+        Error::NotInitialized("validator_signer".into())
+    );
+
+    safety_rules.initialize(&proof).unwrap();
+    let signature = safety_rules.sign_dag_vote(node.metadata()).unwrap();
+    signature
+        .verify(node.metadata(), &signer.public_key())
+        .unwrap();
+
+    // node of a different epoch
+    let node = Node::new(2, 1, signer.author(), 0, Payload::empty(false), vec![]);
+    assert_eq!(
+        safety_rules.sign_dag_vote(node.metadata()).unwrap_err(),
+        Error::IncorrectEpoch(2, 1)
+    );
+
+    // node of an author outside of the validator set
+    let other_signer = ValidatorSigner::from_int(1);
+    let node = Node::new(
+        1,
+        1,
+        other_signer.author(),
+        0,
+        Payload::empty(false),
+        vec![],
+    );
+    assert!(matches!(
+        safety_rules.sign_dag_vote(node.metadata()).unwrap_err(),
+        Error::InvalidProposal(_)
+    ));
+}
+
+/// Test that the commit votes for blocks ordered by the DAG are only signed for an execution
+/// result matching the ordered block
+fn test_sign_dag_commit_vote(constructor: &Callback) {
+    let (mut safety_rules, signer) = constructor();
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+
+    let round = genesis_qc.certified_block().round();
+    safety_rules.initialize(&proof).unwrap();
+
+    let a1 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer);
+    let ordered_ledger_info = LedgerInfo::new(
+        a1.block()
+            .gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None),
+        HashValue::zero(),
+    );
+    let executed_ledger_info = LedgerInfo::new(
+        a1.block().gen_block_info(HashValue::random(), 100, None),
+        HashValue::zero(),
+    );
+
+    // the ordered ledger info carries no signatures
+    let signature = safety_rules
+        .sign_dag_commit_vote(ordered_ledger_info.clone(), executed_ledger_info.clone())
+        .unwrap();
+    signature
+        .verify(&executed_ledger_info, &signer.public_key())
+        .unwrap();
+
+    // executed ledger info as the ordered one
+    assert!(matches!(
+        safety_rules
+            .sign_dag_commit_vote(executed_ledger_info.clone(), executed_ledger_info)
+            .unwrap_err(),
+        Error::InvalidOrderedLedgerInfo(_)
+    ));
+
+    // inconsistent execution result
+    let bad_ledger_info = LedgerInfo::new(
+        BlockInfo::random(ordered_ledger_info.round()),
+        HashValue::zero(),
+    );
+    assert!(matches!(
+        safety_rules
+            .sign_dag_commit_vote(ordered_ledger_info, bad_ledger_info)
+            .unwrap_err(),
+        Error::InconsistentExecutionResult(_, _)
+    ));
+}
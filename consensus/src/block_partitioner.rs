@@ -0,0 +1,94 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_block_partitioner::sharded_block_partitioner::ShardedBlockPartitioner;
+use aptos_executor_types::ExecutableTransactions;
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_types::{on_chain_config::BlockPartitionerType, transaction::Transaction};
+use std::sync::Arc;
+
+/// Interface to partition the blocks for sharded execution
+pub trait BlockPartitioner: Send + Sync {
+    /// Returns the transactions to execute. The partitioning must be deterministic, as the
+    /// transactions are committed in the order of the sub-blocks, which is re-computed when the
+    /// block is committed.
+    fn partition(&self, txns: Vec<Transaction>) -> ExecutableTransactions;
+}
+
+/// No Op Partitioner, the blocks are executed in their original order
+pub struct NoOpPartitioner {}
+
+impl BlockPartitioner for NoOpPartitioner {
+    fn partition(&self, txns: Vec<Transaction>) -> ExecutableTransactions {
+        ExecutableTransactions::Unsharded(txns)
+    }
+}
+
+/// Partitions the blocks with the sharded block partitioner, to execute them on the executor
+/// shards
+pub struct ShardedPartitioner {
+    partitioner: Mutex<ShardedBlockPartitioner>,
+    num_partitioning_rounds: usize,
+}
+
+impl BlockPartitioner for ShardedPartitioner {
+    fn partition(&self, txns: Vec<Transaction>) -> ExecutableTransactions {
+        ExecutableTransactions::Sharded(
+            self.partitioner
+                .lock()
+                .partition_transactions(txns, self.num_partitioning_rounds),
+        )
+    }
+}
+
+/// Creates the partitioner of the given on-chain type. The blocks aren't partitioned while a
+/// block gas limit is set, as sharded execution can't enforce it across the shards.
+pub fn create_block_partitioner(
+    partitioner_type: BlockPartitionerType,
+    block_gas_limit: Option<u64>,
+) -> Arc<dyn BlockPartitioner> {
+    match partitioner_type {
+        BlockPartitionerType::NoPartitioning => Arc::new(NoOpPartitioner {}),
+        BlockPartitionerType::ShardedV1 { .. } if block_gas_limit.is_some() => {
+            warn!("Sharded block partitioning is disabled, as the block gas limit is set");
+            Arc::new(NoOpPartitioner {})
+        },
+        BlockPartitionerType::ShardedV1 {
+            num_shards,
+            num_partitioning_rounds,
+        } => {
+            info!(
+                "Using sharded block partitioning with {} shards and {} partitioning rounds",
+                num_shards, num_partitioning_rounds
+            );
+            Arc::new(ShardedPartitioner {
+                partitioner: Mutex::new(ShardedBlockPartitioner::new(num_shards.max(1) as usize)),
+                num_partitioning_rounds: num_partitioning_rounds.max(1) as usize,
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+
+    #[test]
+    fn test_no_partitioning_with_block_gas_limit() {
+        let partitioner_type = BlockPartitionerType::ShardedV1 {
+            num_shards: 2,
+            num_partitioning_rounds: 2,
+        };
+        let txns = vec![Transaction::StateCheckpoint(HashValue::zero())];
+
+        assert!(matches!(
+            create_block_partitioner(partitioner_type.clone(), Some(1_000)).partition(txns.clone()),
+            ExecutableTransactions::Unsharded(_)
+        ));
+        let partitioned = create_block_partitioner(partitioner_type, None).partition(txns.clone());
+        assert!(matches!(partitioned, ExecutableTransactions::Sharded(_)));
+        assert_eq!(partitioned.txns(), txns);
+    }
+}
@@ -46,13 +46,13 @@ const MAX_ORDERING_PIPELINE_LATENCY_REDUCTION: Duration = Duration::from_secs(1)
 
 fn update_counters_for_ordered_blocks(ordered_blocks: &[Arc<ExecutedBlock>]) {
     for block in ordered_blocks {
-        observe_block(block.block().timestamp_usecs(), BlockStage::ORDERED);
+        observe_block(block.block(), BlockStage::ORDERED);
     }
 }
 
 pub fn update_counters_for_committed_blocks(blocks_to_commit: &[Arc<ExecutedBlock>]) {
     for block in blocks_to_commit {
-        observe_block(block.block().timestamp_usecs(), BlockStage::COMMITTED);
+        observe_block(block.block(), BlockStage::COMMITTED);
         let txn_status = block.compute_result().compute_status();
         counters::NUM_TXNS_PER_BLOCK.observe(txn_status.len() as f64);
         counters::COMMITTED_BLOCKS_COUNT.inc();
@@ -436,10 +436,7 @@ impl BlockStore {
                     qc.certified_block(),
                     executed_block.block_info()
                 );
-                observe_block(
-                    executed_block.block().timestamp_usecs(),
-                    BlockStage::QC_ADDED,
-                );
+                observe_block(executed_block.block(), BlockStage::QC_ADDED);
             },
             None => bail!("Insert {} without having the block in store first", qc),
         };
@@ -3,19 +3,50 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::counters;
-use aptos_infallible::duration_since_epoch;
-use std::time::Duration;
+use aptos_consensus_types::{block::Block, common::Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::{duration_since_epoch, Mutex};
+use aptos_types::block_info::BlockInfo;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+/// The maximum number of blocks whose timelines are kept in memory
+const MAX_TRACED_BLOCKS: usize = 1000;
+
+/// The timelines of the most recently traced blocks
+static BLOCK_TIMELINES: Lazy<Mutex<BlockTimelines>> =
+    Lazy::new(|| Mutex::new(BlockTimelines::new(MAX_TRACED_BLOCKS)));
+
+/// Whether the block timelines are recorded (i.e., exposed by the inspection service)
+static BLOCK_TIMELINES_ENABLED: OnceCell<bool> = OnceCell::new();
+
+/// Sets whether the block timelines are recorded when invoked the first time
+pub fn set_block_timelines_enabled_once(enabled: bool) {
+    // Only the first call succeeds, due to OnceCell semantics.
+    BLOCK_TIMELINES_ENABLED.set(enabled).ok();
+}
+
+fn block_timelines_enabled() -> bool {
+    BLOCK_TIMELINES_ENABLED.get().copied().unwrap_or(false)
+}
 
 pub struct BlockStage;
 
 impl BlockStage {
     pub const COMMITTED: &'static str = "committed";
     pub const COMMIT_CERTIFIED: &'static str = "commit_certified";
+    pub const COMMIT_VOTED: &'static str = "commit_voted";
     pub const EPOCH_MANAGER_RECEIVED: &'static str = "epoch_manager_received";
     pub const EPOCH_MANAGER_VERIFIED: &'static str = "epoch_manager_verified";
     pub const EXECUTED: &'static str = "executed";
+    pub const EXECUTION_STARTED: &'static str = "execution_started";
     pub const NETWORK_RECEIVED: &'static str = "network_received";
     pub const ORDERED: &'static str = "ordered";
+    pub const PERSISTED: &'static str = "persisted";
     pub const QC_ADDED: &'static str = "qc_added";
     pub const QC_AGGREGATED: &'static str = "qc_aggregated";
     pub const ROUND_MANAGER_RECEIVED: &'static str = "round_manager_received";
@@ -24,11 +55,233 @@ impl BlockStage {
     pub const VOTED: &'static str = "voted";
 }
 
-/// Record the time during each stage of a block.
-pub fn observe_block(timestamp: u64, stage: &'static str) {
-    if let Some(t) = duration_since_epoch().checked_sub(Duration::from_micros(timestamp)) {
+/// The time at which a block reached a stage
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BlockStageTime {
+    /// The name of the stage (see BlockStage)
+    pub stage: &'static str,
+    /// The time at which the block reached the stage (since the unix epoch)
+    pub time_usecs: u64,
+    /// The time elapsed between the block timestamp and the stage
+    pub since_block_timestamp_usecs: u64,
+    /// The time elapsed between the previous stage of the block and the stage
+    pub since_previous_stage_usecs: u64,
+}
+
+/// The stages reached by a block, in the order they were reached. Only the first time a
+/// block reaches each stage is recorded (e.g., if the proposal is received twice).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BlockTimeline {
+    /// The id of the block
+    pub block_id: HashValue,
+    /// The epoch of the block
+    pub epoch: u64,
+    /// The round of the block
+    pub round: Round,
+    /// The timestamp of the block, set by its proposer
+    pub timestamp_usecs: u64,
+    /// The stages reached by the block, in order
+    pub stages: Vec<BlockStageTime>,
+}
+
+impl BlockTimeline {
+    fn new(block_id: HashValue, epoch: u64, round: Round, timestamp_usecs: u64) -> Self {
+        Self {
+            block_id,
+            epoch,
+            round,
+            timestamp_usecs,
+            stages: vec![],
+        }
+    }
+
+    fn add_stage(&mut self, stage: &'static str, time_usecs: u64) {
+        if self
+            .stages
+            .iter()
+            .any(|stage_time| stage_time.stage == stage)
+        {
+            return;
+        }
+        let previous_time_usecs = self
+            .stages
+            .last()
+            .map_or(self.timestamp_usecs, |stage_time| stage_time.time_usecs);
+        self.stages.push(BlockStageTime {
+            stage,
+            time_usecs,
+            since_block_timestamp_usecs: time_usecs.saturating_sub(self.timestamp_usecs),
+            since_previous_stage_usecs: time_usecs.saturating_sub(previous_time_usecs),
+        });
+    }
+}
+
+/// A ring buffer of block timelines, evicting the timeline of the block traced first
+/// once the capacity is reached.
+///
+/// Only verified blocks are traced, so peers can't evict the timelines with bogus blocks.
+/// The stages reached before the verification (e.g., receiving the proposal) are held
+/// aside, and added to the timeline once the block is verified.
+pub struct BlockTimelines {
+    capacity: usize,
+    timelines: HashMap<HashValue, BlockTimeline>,
+    // The ids of the traced blocks, in the order they were first traced
+    block_ids: VecDeque<HashValue>,
+    // The stages reached by the blocks that aren't verified yet, and their ids in the
+    // order they were first seen
+    unverified_stages: HashMap<HashValue, Vec<(&'static str, u64)>>,
+    unverified_block_ids: VecDeque<HashValue>,
+}
+
+impl BlockTimelines {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            timelines: HashMap::new(),
+            block_ids: VecDeque::new(),
+            unverified_stages: HashMap::new(),
+            unverified_block_ids: VecDeque::new(),
+        }
+    }
+
+    /// Records that the block reached the stage at the given time, before it was verified
+    pub fn observe_unverified(
+        &mut self,
+        block_id: HashValue,
+        stage: &'static str,
+        time_usecs: u64,
+    ) {
+        if self.capacity == 0 || self.timelines.contains_key(&block_id) {
+            return;
+        }
+        if !self.unverified_stages.contains_key(&block_id) {
+            if self.unverified_block_ids.len() == self.capacity {
+                if let Some(evicted_block_id) = self.unverified_block_ids.pop_front() {
+                    self.unverified_stages.remove(&evicted_block_id);
+                }
+            }
+            self.unverified_block_ids.push_back(block_id);
+        }
+        self.unverified_stages
+            .entry(block_id)
+            .or_default()
+            .push((stage, time_usecs));
+    }
+
+    /// Records that the (verified) block reached the stage at the given time
+    pub fn observe(
+        &mut self,
+        block_id: HashValue,
+        epoch: u64,
+        round: Round,
+        timestamp_usecs: u64,
+        stage: &'static str,
+        time_usecs: u64,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.timelines.contains_key(&block_id) {
+            if self.block_ids.len() == self.capacity {
+                if let Some(evicted_block_id) = self.block_ids.pop_front() {
+                    self.timelines.remove(&evicted_block_id);
+                }
+            }
+            self.block_ids.push_back(block_id);
+
+            let mut timeline = BlockTimeline::new(block_id, epoch, round, timestamp_usecs);
+            if let Some(unverified_stages) = self.unverified_stages.remove(&block_id) {
+                self.unverified_block_ids.retain(|id| *id != block_id);
+                for (unverified_stage, unverified_time_usecs) in unverified_stages {
+                    timeline.add_stage(unverified_stage, unverified_time_usecs);
+                }
+            }
+            self.timelines.insert(block_id, timeline);
+        }
+        if let Some(timeline) = self.timelines.get_mut(&block_id) {
+            timeline.add_stage(stage, time_usecs);
+        }
+    }
+
+    /// Returns the timeline of the given block, if it's still traced
+    pub fn get(&self, block_id: &HashValue) -> Option<BlockTimeline> {
+        self.timelines.get(block_id).cloned()
+    }
+
+    /// Returns the timelines of all the traced blocks, in the order they were first traced
+    pub fn get_all(&self) -> Vec<BlockTimeline> {
+        self.block_ids
+            .iter()
+            .filter_map(|block_id| self.timelines.get(block_id).cloned())
+            .collect()
+    }
+}
+
+/// Returns the timelines of the most recently traced blocks, oldest first
+pub fn get_block_timelines() -> Vec<BlockTimeline> {
+    BLOCK_TIMELINES.lock().get_all()
+}
+
+/// Returns the timeline of the given block, if it was recently traced
+pub fn get_block_timeline(block_id: &HashValue) -> Option<BlockTimeline> {
+    BLOCK_TIMELINES.lock().get(block_id)
+}
+
+/// Record the time during each stage of a (verified) block.
+pub fn observe_block(block: &Block, stage: &'static str) {
+    let now = observe_latency(block.timestamp_usecs(), stage);
+    if block_timelines_enabled() {
+        BLOCK_TIMELINES.lock().observe(
+            block.id(),
+            block.epoch(),
+            block.round(),
+            block.timestamp_usecs(),
+            stage,
+            now.as_micros() as u64,
+        );
+    }
+}
+
+/// Record the time during each stage of a block that isn't verified yet (e.g., a proposal
+/// received from the network). The stage is only added to the timeline of the block once
+/// the block is verified.
+pub fn observe_unverified_block(block: &Block, stage: &'static str) {
+    let now = observe_latency(block.timestamp_usecs(), stage);
+    if block_timelines_enabled() {
+        BLOCK_TIMELINES
+            .lock()
+            .observe_unverified(block.id(), stage, now.as_micros() as u64);
+    }
+}
+
+/// Record the time during each stage of a block, when only its block info is available
+/// (e.g., for the block certified by a quorum cert).
+pub fn observe_block_info(block_info: &BlockInfo, stage: &'static str) {
+    let now = observe_latency(block_info.timestamp_usecs(), stage);
+    if block_timelines_enabled() {
+        BLOCK_TIMELINES.lock().observe(
+            block_info.id(),
+            block_info.epoch(),
+            block_info.round(),
+            block_info.timestamp_usecs(),
+            stage,
+            now.as_micros() as u64,
+        );
+    }
+}
+
+/// Records the latency between the block timestamp and the stage, and returns the
+/// current time
+fn observe_latency(timestamp: u64, stage: &'static str) -> Duration {
+    let now = duration_since_epoch();
+    if let Some(t) = now.checked_sub(Duration::from_micros(timestamp)) {
         counters::BLOCK_TRACING
             .with_label_values(&[stage])
             .observe(t.as_secs_f64());
     }
+    now
 }
+
+#[cfg(test)]
+#[path = "tracing_test.rs"]
+mod tracing_test;
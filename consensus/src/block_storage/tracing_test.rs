@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::block_storage::tracing::{BlockStage, BlockStageTime, BlockTimelines};
+use aptos_crypto::HashValue;
+
+#[test]
+fn test_block_timeline_stages() {
+    let mut timelines = BlockTimelines::new(10);
+    let block_id = HashValue::random();
+    timelines.observe(block_id, 1, 5, 100, BlockStage::NETWORK_RECEIVED, 150);
+    timelines.observe(block_id, 1, 5, 100, BlockStage::VOTED, 180);
+    // Only the first time the block reaches a stage is recorded
+    timelines.observe(block_id, 1, 5, 100, BlockStage::NETWORK_RECEIVED, 200);
+    timelines.observe(block_id, 1, 5, 100, BlockStage::ORDERED, 250);
+
+    let timeline = timelines.get(&block_id).unwrap();
+    assert_eq!(timeline.epoch, 1);
+    assert_eq!(timeline.round, 5);
+    assert_eq!(timeline.timestamp_usecs, 100);
+    assert_eq!(timeline.stages, vec![
+        BlockStageTime {
+            stage: BlockStage::NETWORK_RECEIVED,
+            time_usecs: 150,
+            since_block_timestamp_usecs: 50,
+            since_previous_stage_usecs: 50,
+        },
+        BlockStageTime {
+            stage: BlockStage::VOTED,
+            time_usecs: 180,
+            since_block_timestamp_usecs: 80,
+            since_previous_stage_usecs: 30,
+        },
+        BlockStageTime {
+            stage: BlockStage::ORDERED,
+            time_usecs: 250,
+            since_block_timestamp_usecs: 150,
+            since_previous_stage_usecs: 70,
+        },
+    ]);
+    assert!(timelines.get(&HashValue::random()).is_none());
+}
+
+#[test]
+fn test_block_timelines_eviction() {
+    let mut timelines = BlockTimelines::new(3);
+    let block_ids: Vec<_> = (0..5).map(|_| HashValue::random()).collect();
+    for (round, block_id) in block_ids.iter().enumerate() {
+        timelines.observe(*block_id, 1, round as u64, 0, BlockStage::ORDERED, 10);
+        if round == 2 {
+            // Adding a stage to a traced block doesn't change the eviction order
+            timelines.observe(block_ids[0], 1, 0, 0, BlockStage::COMMITTED, 20);
+        }
+    }
+
+    let traced_block_ids: Vec<_> = timelines
+        .get_all()
+        .into_iter()
+        .map(|timeline| timeline.block_id)
+        .collect();
+    assert_eq!(traced_block_ids, block_ids[2..].to_vec());
+
+    // Blocks aren't traced if the capacity is zero
+    let mut timelines = BlockTimelines::new(0);
+    timelines.observe(block_ids[0], 1, 0, 0, BlockStage::ORDERED, 10);
+    assert!(timelines.get_all().is_empty());
+}
+
+#[test]
+fn test_block_timeline_unverified_stages() {
+    let mut timelines = BlockTimelines::new(2);
+    let block_id = HashValue::random();
+
+    // The stages reached before the verification aren't traced yet
+    timelines.observe_unverified(block_id, BlockStage::NETWORK_RECEIVED, 150);
+    timelines.observe_unverified(block_id, BlockStage::EPOCH_MANAGER_RECEIVED, 160);
+    assert!(timelines.get(&block_id).is_none());
+    assert!(timelines.get_all().is_empty());
+
+    // They are added to the timeline once the block is verified
+    timelines.observe(block_id, 1, 5, 100, BlockStage::EPOCH_MANAGER_VERIFIED, 170);
+    let stages: Vec<_> = timelines
+        .get(&block_id)
+        .unwrap()
+        .stages
+        .into_iter()
+        .map(|stage_time| (stage_time.stage, stage_time.time_usecs))
+        .collect();
+    assert_eq!(stages, vec![
+        (BlockStage::NETWORK_RECEIVED, 150),
+        (BlockStage::EPOCH_MANAGER_RECEIVED, 160),
+        (BlockStage::EPOCH_MANAGER_VERIFIED, 170),
+    ]);
+
+    // Unverified blocks never evict the traced ones, and are evicted first seen first
+    let unverified_block_ids: Vec<_> = (0..3).map(|_| HashValue::random()).collect();
+    for unverified_block_id in &unverified_block_ids {
+        timelines.observe_unverified(*unverified_block_id, BlockStage::NETWORK_RECEIVED, 200);
+    }
+    assert_eq!(timelines.get_all().len(), 1);
+    timelines.observe(unverified_block_ids[0], 1, 6, 100, BlockStage::VOTED, 300);
+    assert_eq!(
+        timelines
+            .get(&unverified_block_ids[0])
+            .unwrap()
+            .stages
+            .len(),
+        1
+    );
+    timelines.observe(unverified_block_ids[2], 1, 7, 100, BlockStage::VOTED, 300);
+    assert_eq!(
+        timelines
+            .get(&unverified_block_ids[2])
+            .unwrap()
+            .stages
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,328 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{counters::TXN_SHUFFLE_SECONDS, transaction_shuffler::TransactionShuffler};
+use aptos_types::transaction::{SignedTransaction, TransactionPayload};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// An implementation of transaction shuffler, which tries to spread transactions that are likely
+/// to conflict during parallel execution. Conflicts are estimated with cheap hints derived from the
+/// transaction itself:
+/// 1. Transactions from the same sender always conflict (on the sequence number).
+/// 2. Entry function calls of the same module with the same first address argument likely touch
+/// the same resources (e.g., transfers to the same account, or mints from the same collection).
+///
+/// The hints only depend on the block, as the shuffling must be deterministic across validators
+/// and is re-computed when the block is committed.
+///
+/// Similar to the `SenderAwareShuffler`, the shuffler maintains the conflict keys of the last
+/// `conflict_window_size` transactions added to the block. When selecting the next transaction,
+/// it first looks for a pending transaction that no longer conflicts with the window, then for
+/// the next non-conflicting transaction in the original order. If there is none, it adds the first
+/// pending transaction. The relative ordering of the transactions of the same sender is preserved,
+/// and a block without conflicts keeps its original order.
+pub struct ConflictAwareShuffler {
+    conflict_window_size: usize,
+}
+
+impl TransactionShuffler for ConflictAwareShuffler {
+    fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+        let _timer = TXN_SHUFFLE_SECONDS.start_timer();
+
+        // handle the corner case of conflict window being 0, in which case we don't do any shuffling
+        if self.conflict_window_size == 0 {
+            return txns;
+        }
+
+        let num_transactions = txns.len();
+        let mut sliding_window = ConflictWindow::new(self.conflict_window_size);
+        let mut pending_txns = PendingTransactions::new(self.conflict_window_size);
+        let mut orig_txns = txns.into_iter();
+        let mut shuffled_txns = Vec::with_capacity(num_transactions);
+        while shuffled_txns.len() < num_transactions {
+            let (txn, keys) = pending_txns
+                .remove_first_non_conflicting(&sliding_window)
+                .or_else(|| {
+                    // Iterate through the original transactions and try to find the next
+                    // candidate. Transactions of senders with pending transactions are pending
+                    // too, so that the ordering by sender is preserved.
+                    for txn in orig_txns.by_ref() {
+                        let keys = conflict_keys(&txn);
+                        if pending_txns.has_sender(&txn.sender())
+                            || sliding_window.has_conflict(&keys)
+                        {
+                            pending_txns.add_transaction(txn, keys);
+                        } else {
+                            return Some((txn, keys));
+                        }
+                    }
+                    None
+                })
+                .or_else(|| pending_txns.remove_first_pending())
+                .expect("Transaction expected");
+            sliding_window.add_transaction(keys);
+            shuffled_txns.push(txn);
+        }
+        shuffled_txns
+    }
+}
+
+impl ConflictAwareShuffler {
+    pub fn new(conflict_window_size: usize) -> Self {
+        Self {
+            conflict_window_size,
+        }
+    }
+}
+
+/// A hint of the state that a transaction is going to access
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ConflictKey {
+    Sender(AccountAddress),
+    EntryFunction(ModuleId, Option<AccountAddress>),
+}
+
+/// Returns the conflict keys of the transaction. Arguments are BCS encoded, so the first argument
+/// of the length of an address is used as the first address argument.
+fn conflict_keys(txn: &SignedTransaction) -> Vec<ConflictKey> {
+    let mut keys = vec![ConflictKey::Sender(txn.sender())];
+    if let TransactionPayload::EntryFunction(entry_function) = txn.payload() {
+        let first_address = entry_function
+            .args()
+            .iter()
+            .find(|arg| arg.len() == AccountAddress::LENGTH)
+            .and_then(|arg| AccountAddress::from_bytes(arg).ok());
+        keys.push(ConflictKey::EntryFunction(
+            entry_function.module().clone(),
+            first_address,
+        ));
+    }
+    keys
+}
+
+/// The transactions that are pending to be added to the block, in their original order. Only the
+/// first `max_scanned_txns` pending transactions are considered when looking for a
+/// non-conflicting one, which bounds the cost of shuffling.
+struct PendingTransactions {
+    txns: VecDeque<(SignedTransaction, Vec<ConflictKey>)>,
+    // Number of pending transactions per sender
+    senders: HashMap<AccountAddress, usize>,
+    max_scanned_txns: usize,
+}
+
+impl PendingTransactions {
+    pub fn new(max_scanned_txns: usize) -> Self {
+        Self {
+            txns: VecDeque::new(),
+            senders: HashMap::new(),
+            max_scanned_txns,
+        }
+    }
+
+    pub fn add_transaction(&mut self, txn: SignedTransaction, keys: Vec<ConflictKey>) {
+        *self.senders.entry(txn.sender()).or_insert(0) += 1;
+        self.txns.push_back((txn, keys));
+    }
+
+    pub fn has_sender(&self, sender: &AccountAddress) -> bool {
+        self.senders.contains_key(sender)
+    }
+
+    /// Removes the first pending transaction that doesn't conflict with the window, and that
+    /// is the first pending transaction of its sender.
+    pub fn remove_first_non_conflicting(
+        &mut self,
+        sliding_window: &ConflictWindow,
+    ) -> Option<(SignedTransaction, Vec<ConflictKey>)> {
+        let mut scanned_senders = HashSet::new();
+        let index = self
+            .txns
+            .iter()
+            .take(self.max_scanned_txns)
+            .position(|(txn, keys)| {
+                scanned_senders.insert(txn.sender()) && !sliding_window.has_conflict(keys)
+            })?;
+        self.remove(index)
+    }
+
+    pub fn remove_first_pending(&mut self) -> Option<(SignedTransaction, Vec<ConflictKey>)> {
+        self.remove(0)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<(SignedTransaction, Vec<ConflictKey>)> {
+        let (txn, keys) = self.txns.remove(index)?;
+        let sender = txn.sender();
+        let count = self.senders.get_mut(&sender).expect("Sender expected");
+        *count -= 1;
+        if *count == 0 {
+            self.senders.remove(&sender);
+        }
+        Some((txn, keys))
+    }
+}
+
+/// The conflict keys of the last `window_size` transactions added to the block
+struct ConflictWindow {
+    window_size: usize,
+    txn_keys: VecDeque<Vec<ConflictKey>>,
+    key_counts: HashMap<ConflictKey, usize>,
+}
+
+impl ConflictWindow {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window_size,
+            txn_keys: VecDeque::with_capacity(window_size),
+            key_counts: HashMap::new(),
+        }
+    }
+
+    pub fn add_transaction(&mut self, keys: Vec<ConflictKey>) {
+        if self.txn_keys.len() == self.window_size {
+            for key in self.txn_keys.pop_front().expect("Keys expected") {
+                if let Some(count) = self.key_counts.get_mut(&key) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.key_counts.remove(&key);
+                    }
+                }
+            }
+        }
+        for key in &keys {
+            *self.key_counts.entry(key.clone()).or_insert(0) += 1;
+        }
+        self.txn_keys.push_back(keys);
+    }
+
+    pub fn has_conflict(&self, keys: &[ConflictKey]) -> bool {
+        keys.iter().any(|key| self.key_counts.contains_key(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conflict_aware_shuffler::ConflictAwareShuffler, transaction_shuffler::TransactionShuffler,
+    };
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{EntryFunction, RawTransaction, SignedTransaction, TransactionPayload},
+    };
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    };
+    use rand::{rngs::OsRng, Rng};
+    use std::collections::HashMap;
+
+    /// Creates transfers from a new sender to the given recipients
+    fn create_transfers(recipients: &[AccountAddress]) -> Vec<SignedTransaction> {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = private_key.public_key();
+        let sender = AccountAddress::random();
+
+        recipients
+            .iter()
+            .enumerate()
+            .map(|(i, recipient)| {
+                let transaction_payload = TransactionPayload::EntryFunction(EntryFunction::new(
+                    ModuleId::new(
+                        AccountAddress::ONE,
+                        Identifier::new("aptos_account").unwrap(),
+                    ),
+                    Identifier::new("transfer").unwrap(),
+                    vec![],
+                    vec![
+                        bcs::to_bytes(recipient).unwrap(),
+                        bcs::to_bytes(&1u64).unwrap(),
+                    ],
+                ));
+                let raw_transaction = RawTransaction::new(
+                    sender,
+                    i as u64,
+                    transaction_payload,
+                    0,
+                    0,
+                    0,
+                    ChainId::new(10),
+                );
+                SignedTransaction::new(
+                    raw_transaction.clone(),
+                    public_key.clone(),
+                    private_key.sign(&raw_transaction).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_non_conflicting_txns() {
+        // Single sender
+        let txns = create_transfers(&[AccountAddress::random(), AccountAddress::random()]);
+        let optimized_txns = ConflictAwareShuffler::new(10).shuffle(txns.clone());
+        assert_eq!(txns, optimized_txns);
+
+        // Unique senders and recipients
+        let txns: Vec<_> = (0..50)
+            .flat_map(|_| create_transfers(&[AccountAddress::random()]))
+            .collect();
+        let optimized_txns = ConflictAwareShuffler::new(10).shuffle(txns.clone());
+        assert_eq!(txns, optimized_txns);
+    }
+
+    #[test]
+    fn test_hot_recipient_txns() {
+        // Transfers to a hot recipient from unique senders, followed by transfers to unique
+        // recipients
+        let window_size = 4;
+        let hot_recipient = AccountAddress::random();
+        let mut txns: Vec<_> = (0..10)
+            .flat_map(|_| create_transfers(&[hot_recipient]))
+            .collect();
+        txns.extend((0..40).flat_map(|_| create_transfers(&[AccountAddress::random()])));
+
+        let optimized_txns = ConflictAwareShuffler::new(window_size).shuffle(txns.clone());
+        assert_eq!(txns.len(), optimized_txns.len());
+        assert!(txns.iter().all(|txn| optimized_txns.contains(txn)));
+
+        // The transfers to the hot recipient are spread by the window size
+        let hot_indices: Vec<_> = optimized_txns
+            .iter()
+            .enumerate()
+            .filter(|(_, txn)| txns[..10].contains(txn))
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(hot_indices.len(), 10);
+        for indices in hot_indices.windows(2) {
+            assert!(indices[1] - indices[0] > window_size);
+        }
+    }
+
+    #[test]
+    fn test_sender_ordering() {
+        // Senders with several transfers to a small set of recipients
+        let recipients: Vec<_> = (0..5).map(|_| AccountAddress::random()).collect();
+        let mut txns = vec![];
+        for _ in 0..20 {
+            let num_txns = OsRng.gen_range(1, 10);
+            let sender_recipients: Vec<_> = (0..num_txns)
+                .map(|_| recipients[OsRng.gen_range(0, recipients.len())])
+                .collect();
+            txns.extend(create_transfers(&sender_recipients));
+        }
+        // Interleave the transactions of the senders, keeping the order by sender
+        txns.sort_by_key(|txn| txn.sequence_number());
+
+        let optimized_txns = ConflictAwareShuffler::new(8).shuffle(txns.clone());
+        assert_eq!(txns.len(), optimized_txns.len());
+        assert!(txns.iter().all(|txn| optimized_txns.contains(txn)));
+
+        // The sequence numbers of each sender are still in order
+        let mut sequence_numbers = HashMap::new();
+        for txn in optimized_txns {
+            let expected_sequence_number = sequence_numbers.entry(txn.sender()).or_insert(0);
+            assert_eq!(txn.sequence_number(), *expected_sequence_number);
+            *expected_sequence_number += 1;
+        }
+    }
+}
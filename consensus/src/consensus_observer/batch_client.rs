@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensus_observer::network_message::{BatchRetrievalRequest, ConsensusObserverMessage},
+    counters,
+    quorum_store::types::Batch,
+};
+use anyhow::bail;
+use aptos_config::{config::ConsensusObserverConfig, network_id::PeerNetworkId};
+use aptos_consensus_types::proof_of_store::ProofOfStore;
+use aptos_network::application::interface::{NetworkClient, NetworkClientInterface};
+use std::time::Duration;
+
+/// Retrieves quorum store batches from the upstream peers of a fullnode, and verifies
+/// them against the proofs of store that certify them.
+#[derive(Clone)]
+pub struct ObserverBatchClient {
+    network_client: NetworkClient<ConsensusObserverMessage>,
+    request_timeout: Duration,
+}
+
+impl ObserverBatchClient {
+    /// Creates a new batch client
+    pub fn new(
+        config: ConsensusObserverConfig,
+        network_client: NetworkClient<ConsensusObserverMessage>,
+    ) -> Self {
+        Self {
+            network_client,
+            request_timeout: Duration::from_millis(config.batch_request_timeout_ms),
+        }
+    }
+
+    /// Retrieves the batch certified by the given proof of store from the given peer.
+    /// The proof must have been verified by the caller (e.g., as part of a verified block).
+    pub async fn retrieve_batch(
+        &self,
+        peer_network_id: PeerNetworkId,
+        proof: &ProofOfStore,
+    ) -> anyhow::Result<Batch> {
+        let request = ConsensusObserverMessage::BatchRequest(BatchRetrievalRequest::new(
+            proof.epoch(),
+            *proof.digest(),
+        ));
+        let response = self
+            .network_client
+            .send_to_peer_rpc(request, self.request_timeout, peer_network_id)
+            .await?;
+
+        let result = match response {
+            ConsensusObserverMessage::BatchResponse(response) => {
+                response.verify_and_take_batch(proof)
+            },
+            message => bail!(
+                "Unexpected {} response to the batch request from {}",
+                message.name(),
+                peer_network_id
+            ),
+        };
+        counters::CONSENSUS_OBSERVER_RETRIEVED_BATCHES
+            .with_label_values(&[if result.is_ok() { "valid" } else { "invalid" }])
+            .inc();
+        result
+    }
+}
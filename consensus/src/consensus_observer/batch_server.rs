@@ -0,0 +1,156 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensus_observer::network_message::{
+        BatchRetrievalRequest, BatchRetrievalResponse, ConsensusObserverMessage,
+    },
+    counters,
+    quorum_store::batch_store::BatchReader,
+};
+use aptos_config::{config::ConsensusObserverConfig, network_id::PeerNetworkId};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_network::{
+    application::interface::NetworkServiceEvents,
+    protocols::{network::Event, rpc::error::RpcError},
+    ProtocolId,
+};
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use bytes::Bytes;
+use futures::{channel::oneshot, stream::select_all, StreamExt};
+use std::sync::Arc;
+
+/// Serves the quorum store batches of the node to its downstream consensus observers, so
+/// that they can execute the ordered blocks published by the node. Only the batches stored
+/// locally are served, so requests never trigger batch retrievals from other peers.
+pub struct ObserverBatchServer {
+    // The epoch and batch reader used to serve requests (None if quorum store is disabled)
+    epoch_batch_reader: Mutex<Option<(u64, Arc<dyn BatchReader>)>>,
+
+    // The rate limiter of the batch requests (per peer)
+    rate_limiter: TokenBucketRateLimiter<PeerNetworkId>,
+}
+
+impl ObserverBatchServer {
+    pub fn new(config: ConsensusObserverConfig) -> Self {
+        let max_requests_per_second = config.max_batch_requests_per_second as usize;
+        let rate_limiter = TokenBucketRateLimiter::new(
+            "consensus_observer_batch_requests",
+            "Consensus observer batch requests".into(),
+            100, // Peers can send a full second of requests as soon as they connect
+            max_requests_per_second,
+            max_requests_per_second,
+            None,
+        );
+
+        Self {
+            epoch_batch_reader: Mutex::new(None),
+            rate_limiter,
+        }
+    }
+
+    /// Updates the batch reader used to serve the requests of the new epoch
+    pub fn update_epoch(&self, epoch: u64, batch_reader: Option<Arc<dyn BatchReader>>) {
+        *self.epoch_batch_reader.lock() = batch_reader.map(|batch_reader| (epoch, batch_reader));
+    }
+
+    /// Returns the response to the given batch request
+    pub fn handle_request(
+        &self,
+        peer_network_id: PeerNetworkId,
+        request: &BatchRetrievalRequest,
+    ) -> BatchRetrievalResponse {
+        if self
+            .rate_limiter
+            .bucket(peer_network_id)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_err()
+        {
+            counters::CONSENSUS_OBSERVER_BATCH_REQUESTS
+                .with_label_values(&["rate_limited"])
+                .inc();
+            return BatchRetrievalResponse::RateLimited;
+        }
+
+        let batch = match &*self.epoch_batch_reader.lock() {
+            Some((epoch, batch_reader)) if *epoch == request.epoch() => {
+                batch_reader.get_local_batch(request.digest())
+            },
+            _ => None,
+        };
+        match batch {
+            Some(batch) => {
+                counters::CONSENSUS_OBSERVER_BATCH_REQUESTS
+                    .with_label_values(&["served"])
+                    .inc();
+                BatchRetrievalResponse::Batch(batch)
+            },
+            None => {
+                counters::CONSENSUS_OBSERVER_BATCH_REQUESTS
+                    .with_label_values(&["not_available"])
+                    .inc();
+                BatchRetrievalResponse::NotAvailable
+            },
+        }
+    }
+
+    /// Responds to the given batch request over the RPC response channel
+    pub fn respond_to_request(
+        &self,
+        peer_network_id: PeerNetworkId,
+        request: &BatchRetrievalRequest,
+        protocol: ProtocolId,
+        response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+    ) {
+        let response =
+            ConsensusObserverMessage::BatchResponse(self.handle_request(peer_network_id, request));
+        let result = protocol
+            .to_bytes(&response)
+            .map(|bytes| response_sender.send(Ok(bytes.into())));
+        if !matches!(result, Ok(Ok(()))) {
+            warn!(
+                "[ObserverBatchServer] Failed to respond to the batch request of {}",
+                peer_network_id
+            );
+        }
+    }
+
+    /// Starts the loop that serves the batch requests of the downstream peers
+    pub async fn start(
+        self: Arc<Self>,
+        network_service_events: NetworkServiceEvents<ConsensusObserverMessage>,
+    ) {
+        let network_events: Vec<_> = network_service_events
+            .into_network_and_events()
+            .into_iter()
+            .map(|(network_id, events)| events.map(move |event| (network_id, event)))
+            .collect();
+        let mut network_events = select_all(network_events).fuse();
+
+        info!("[ObserverBatchServer] Starting the batch server");
+        while let Some((network_id, event)) = network_events.next().await {
+            match event {
+                Event::RpcRequest(
+                    peer_id,
+                    ConsensusObserverMessage::BatchRequest(request),
+                    protocol,
+                    response_sender,
+                ) => {
+                    let peer_network_id = PeerNetworkId::new(network_id, peer_id);
+                    self.respond_to_request(peer_network_id, &request, protocol, response_sender);
+                },
+                Event::RpcRequest(peer_id, message, _, _) => {
+                    warn!(
+                        "[ObserverBatchServer] Unexpected {} request from {}",
+                        message.name(),
+                        peer_id
+                    );
+                },
+                _ => {}, // Validators don't follow the messages published by other nodes
+            }
+        }
+        info!("[ObserverBatchServer] The batch server has stopped");
+    }
+}
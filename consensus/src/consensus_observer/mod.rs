@@ -0,0 +1,18 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod batch_client;
+mod batch_server;
+mod network_message;
+mod observer;
+mod payload_store;
+mod publisher;
+#[cfg(test)]
+mod tests;
+
+pub use batch_client::ObserverBatchClient;
+pub(crate) use batch_server::ObserverBatchServer;
+pub use network_message::ConsensusObserverMessage;
+pub(crate) use observer::ConsensusObserver;
+pub(crate) use payload_store::ObserverPayloadStore;
+pub(crate) use publisher::ConsensusPublisher;
@@ -0,0 +1,203 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::types::Batch;
+use anyhow::ensure;
+use aptos_consensus_types::{block::Block, proof_of_store::ProofOfStore};
+use aptos_crypto::HashValue;
+use aptos_types::{epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures};
+use serde::{Deserialize, Serialize};
+
+/// The messages published by consensus to the observers following it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ConsensusObserverMessage {
+    /// A chain of blocks that was ordered (but not yet committed)
+    OrderedBlock(OrderedBlock),
+    /// A commit decision for previously ordered blocks
+    CommitDecision(CommitDecision),
+    /// A request for the transactions of a quorum store batch (sent as an RPC)
+    BatchRequest(BatchRetrievalRequest),
+    /// The response to a batch request
+    BatchResponse(BatchRetrievalResponse),
+}
+
+impl ConsensusObserverMessage {
+    /// Returns the name of the message (e.g., for logging and metrics)
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConsensusObserverMessage::OrderedBlock(_) => "ordered_block",
+            ConsensusObserverMessage::CommitDecision(_) => "commit_decision",
+            ConsensusObserverMessage::BatchRequest(_) => "batch_request",
+            ConsensusObserverMessage::BatchResponse(_) => "batch_response",
+        }
+    }
+
+    /// Returns the epoch of the published message (batch requests and responses
+    /// are only exchanged over RPC, so they are never published).
+    pub fn epoch(&self) -> Option<u64> {
+        match self {
+            ConsensusObserverMessage::OrderedBlock(ordered_block) => {
+                Some(ordered_block.ordered_proof.ledger_info().epoch())
+            },
+            ConsensusObserverMessage::CommitDecision(commit_decision) => {
+                Some(commit_decision.commit_proof.ledger_info().epoch())
+            },
+            ConsensusObserverMessage::BatchRequest(_)
+            | ConsensusObserverMessage::BatchResponse(_) => None,
+        }
+    }
+}
+
+/// A chain of blocks ordered by consensus, along with the proof of ordering.
+///
+/// The block payloads are sent as proposed: direct mempool payloads carry their
+/// transactions, and quorum store payloads carry the proofs of store of their batches,
+/// which the observers retrieve from the sending peer (see `ObserverBatchClient`). The
+/// payloads are authenticated by the block ids, which are chained to the ordered proof.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OrderedBlock {
+    blocks: Vec<Block>,
+    ordered_proof: LedgerInfoWithSignatures,
+}
+
+impl OrderedBlock {
+    pub fn new(blocks: Vec<Block>, ordered_proof: LedgerInfoWithSignatures) -> Self {
+        Self {
+            blocks,
+            ordered_proof,
+        }
+    }
+
+    pub fn blocks(&self) -> &Vec<Block> {
+        &self.blocks
+    }
+
+    pub fn ordered_proof(&self) -> &LedgerInfoWithSignatures {
+        &self.ordered_proof
+    }
+
+    /// Verifies that the blocks form a chain ending at the ordered block, and that the
+    /// ordered proof is signed by a quorum of the epoch.
+    pub fn verify(&self, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(!self.blocks.is_empty(), "No blocks in the ordered block");
+        let ordered_info = self.ordered_proof.commit_info();
+        ensure!(
+            ordered_info.epoch() == epoch_state.epoch,
+            "Ordered block epoch {} doesn't match the current epoch {}",
+            ordered_info.epoch(),
+            epoch_state.epoch
+        );
+        for blocks in self.blocks.windows(2) {
+            ensure!(
+                blocks[0].is_parent_of(&blocks[1]),
+                "Block {} is not the parent of block {}",
+                blocks[0].id(),
+                blocks[1].id()
+            );
+        }
+        let last_block = self.blocks.last().unwrap();
+        ensure!(
+            last_block.id() == ordered_info.id(),
+            "The last block {} doesn't match the ordered proof {}",
+            last_block.id(),
+            ordered_info
+        );
+        for block in &self.blocks {
+            ensure!(
+                block.epoch() == epoch_state.epoch,
+                "Block {} is not in the current epoch {}",
+                block.id(),
+                epoch_state.epoch
+            );
+        }
+        self.ordered_proof
+            .verify_signatures(&epoch_state.verifier)?;
+        Ok(())
+    }
+}
+
+/// The decision to commit the ordered blocks up to (and including) the commit proof
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CommitDecision {
+    commit_proof: LedgerInfoWithSignatures,
+}
+
+impl CommitDecision {
+    pub fn new(commit_proof: LedgerInfoWithSignatures) -> Self {
+        Self { commit_proof }
+    }
+
+    pub fn commit_proof(&self) -> &LedgerInfoWithSignatures {
+        &self.commit_proof
+    }
+
+    /// Verifies that the commit proof is signed by a quorum of the epoch
+    pub fn verify(&self, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(
+            self.commit_proof.ledger_info().epoch() == epoch_state.epoch,
+            "Commit decision epoch {} doesn't match the current epoch {}",
+            self.commit_proof.ledger_info().epoch(),
+            epoch_state.epoch
+        );
+        self.commit_proof.verify_signatures(&epoch_state.verifier)?;
+        Ok(())
+    }
+}
+
+/// A request for the transactions of a quorum store batch, sent by a fullnode to the
+/// upstream peer that published the block containing the proof of store of the batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BatchRetrievalRequest {
+    epoch: u64,
+    digest: HashValue,
+}
+
+impl BatchRetrievalRequest {
+    pub fn new(epoch: u64, digest: HashValue) -> Self {
+        Self { epoch, digest }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn digest(&self) -> &HashValue {
+        &self.digest
+    }
+}
+
+/// The response to a batch retrieval request
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum BatchRetrievalResponse {
+    /// The requested batch
+    Batch(Batch),
+    /// The batch isn't stored locally (e.g., it expired or is from another epoch)
+    NotAvailable,
+    /// The peer sent too many requests, and should retry later
+    RateLimited,
+}
+
+impl BatchRetrievalResponse {
+    /// Verifies that the response contains the batch certified by the given proof of store,
+    /// and returns it. The proof itself must have been verified by the caller (e.g., as part
+    /// of a verified block).
+    pub fn verify_and_take_batch(self, proof: &ProofOfStore) -> anyhow::Result<Batch> {
+        let batch = match self {
+            BatchRetrievalResponse::Batch(batch) => batch,
+            BatchRetrievalResponse::NotAvailable => {
+                anyhow::bail!("Batch {} is not available", proof.digest())
+            },
+            BatchRetrievalResponse::RateLimited => {
+                anyhow::bail!("Batch request for {} was rate limited", proof.digest())
+            },
+        };
+        ensure!(
+            batch.batch_info() == proof.info(),
+            "Batch info {:?} doesn't match the proof of store {:?}",
+            batch.batch_info(),
+            proof.info()
+        );
+        batch.verify()?;
+        Ok(batch)
+    }
+}
@@ -0,0 +1,605 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block_partitioner::create_block_partitioner,
+    consensus_observer::{
+        batch_client::ObserverBatchClient,
+        batch_server::ObserverBatchServer,
+        network_message::{CommitDecision, ConsensusObserverMessage, OrderedBlock},
+        payload_store::ObserverPayloadStore,
+        publisher::ConsensusPublisher,
+    },
+    counters,
+    payload_manager::PayloadManager,
+    quorum_store::{batch_store::BatchReader, types::Batch},
+    state_replication::StateComputer,
+    transaction_deduper::create_transaction_deduper,
+    transaction_shuffler::create_transaction_shuffler,
+};
+use aptos_config::{
+    config::{ConsensusObserverConfig, RoleType},
+    network_id::PeerNetworkId,
+};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Payload, Round},
+    executed_block::ExecutedBlock,
+    proof_of_store::ProofOfStore,
+};
+use aptos_crypto::HashValue;
+use aptos_event_notifications::ReconfigNotificationListener;
+use aptos_logger::prelude::*;
+use aptos_network::{
+    application::interface::{NetworkClient, NetworkServiceEvents},
+    protocols::network::Event,
+};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{OnChainConfigPayload, OnChainExecutionConfig, ValidatorSet},
+    transaction::SignedTransaction,
+};
+use futures::{future::join_all, stream::select_all, StreamExt};
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Handle;
+
+/// Follows the blocks ordered by the upstream validators without participating in consensus.
+/// Ordered blocks are executed speculatively as soon as their quorum store batches are
+/// retrieved from the upstream peer that sent them, and committed once the
+/// matching commit decision is received. If the observer misses blocks, diverges from the
+/// commit decision or stops making progress, it falls back to state sync.
+pub struct ConsensusObserver {
+    // The configuration of the consensus observer
+    config: ConsensusObserverConfig,
+
+    // The reader used to fetch the latest committed ledger info
+    db_reader: Arc<dyn DbReader>,
+
+    // The execution client used to execute and commit the ordered blocks
+    execution_client: Arc<dyn StateComputer>,
+
+    // The payload manager that serves the transactions of the ordered blocks to execution
+    payload_manager: Arc<PayloadManager>,
+
+    // The transactions of the ordered blocks that are not yet committed (and their batches)
+    payload_store: Arc<ObserverPayloadStore>,
+
+    // The client used to retrieve the quorum store batches of the ordered blocks
+    batch_client: ObserverBatchClient,
+
+    // The publisher used to forward the verified messages to downstream observers
+    consensus_publisher: Option<ConsensusPublisher>,
+
+    // The server of the retrieved batches to downstream observers (if publishing is enabled)
+    batch_server: Option<Arc<ObserverBatchServer>>,
+
+    // The listener for reconfigurations (i.e., new epochs)
+    reconfig_events: ReconfigNotificationListener,
+
+    // The state of the current epoch (None while waiting for the next epoch to start)
+    epoch_state: Option<Arc<EpochState>>,
+
+    // The latest committed ledger info, i.e., the root of the pending blocks
+    root: LedgerInfoWithSignatures,
+
+    // The executed blocks that are waiting for a commit decision (keyed by round)
+    pending_blocks: BTreeMap<Round, Arc<ExecutedBlock>>,
+
+    // The time of the last commit or sync (None if state sync is in control)
+    last_progress_time: Option<Instant>,
+}
+
+impl ConsensusObserver {
+    pub fn new(
+        config: ConsensusObserverConfig,
+        db_reader: Arc<dyn DbReader>,
+        execution_client: Arc<dyn StateComputer>,
+        reconfig_events: ReconfigNotificationListener,
+        network_client: NetworkClient<ConsensusObserverMessage>,
+        runtime: &Handle,
+    ) -> Self {
+        let payload_store = Arc::new(ObserverPayloadStore::default());
+        let payload_manager = Arc::new(PayloadManager::ConsensusObserver(payload_store.clone()));
+        let batch_client = ObserverBatchClient::new(config, network_client.clone());
+
+        // Fullnodes republish the verified messages (and serve the batches) to their own
+        // downstream peers.
+        let (consensus_publisher, batch_server) = if config.publisher_enabled {
+            (
+                Some(ConsensusPublisher::new(
+                    RoleType::FullNode,
+                    network_client,
+                    runtime,
+                )),
+                Some(Arc::new(ObserverBatchServer::new(config))),
+            )
+        } else {
+            (None, None)
+        };
+        let root = db_reader
+            .get_latest_ledger_info()
+            .expect("Failed to read the latest ledger info!");
+
+        Self {
+            config,
+            db_reader,
+            execution_client,
+            payload_manager,
+            payload_store,
+            batch_client,
+            consensus_publisher,
+            batch_server,
+            reconfig_events,
+            epoch_state: None,
+            root,
+            pending_blocks: BTreeMap::new(),
+            last_progress_time: None,
+        }
+    }
+
+    /// Returns true iff the observer made progress recently. Otherwise,
+    /// state sync has taken over and the observer must sync before committing.
+    fn is_in_control(&self) -> bool {
+        let fallback_duration = Duration::from_millis(self.config.observer_fallback_duration_ms);
+        self.last_progress_time.map_or(false, |progress_time| {
+            progress_time.elapsed() < fallback_duration
+        })
+    }
+
+    /// Returns the round and id of the last ordered block known to the observer
+    fn last_ordered_block(&self) -> (Round, HashValue) {
+        if let Some((round, executed_block)) = self.pending_blocks.iter().next_back() {
+            return (*round, executed_block.id());
+        }
+
+        // The blocks of a new epoch descend from the virtual genesis block of the epoch
+        let root = self.root.ledger_info();
+        if root.ends_epoch() {
+            (0, Block::make_genesis_block_from_ledger_info(root).id())
+        } else {
+            (root.round(), root.consensus_block_id())
+        }
+    }
+
+    /// Drops all the pending blocks (and their transactions)
+    fn clear_pending_blocks(&mut self) {
+        self.pending_blocks.clear();
+        self.payload_store.clear();
+    }
+
+    /// Verifies and processes a message received from an upstream peer
+    async fn process_network_message(
+        &mut self,
+        peer_network_id: PeerNetworkId,
+        message: ConsensusObserverMessage,
+    ) {
+        let message_type = message.name();
+        let epoch_state = match &self.epoch_state {
+            Some(epoch_state) if Some(epoch_state.epoch) == message.epoch() => epoch_state.clone(),
+            _ => {
+                counters::CONSENSUS_OBSERVER_RECEIVED_MESSAGES
+                    .with_label_values(&[message_type, "wrong_epoch"])
+                    .inc();
+                return;
+            },
+        };
+
+        // Skip the messages we already processed before paying for the verification
+        let is_duplicate = match &message {
+            ConsensusObserverMessage::OrderedBlock(ordered_block) => {
+                ordered_block.ordered_proof().commit_info().round() <= self.last_ordered_block().0
+            },
+            ConsensusObserverMessage::CommitDecision(commit_decision) => {
+                commit_decision.commit_proof().ledger_info().epoch()
+                    == self.root.ledger_info().epoch()
+                    && commit_decision.commit_proof().commit_info().round()
+                        <= self.root.commit_info().round()
+            },
+            ConsensusObserverMessage::BatchRequest(_)
+            | ConsensusObserverMessage::BatchResponse(_) => {
+                unreachable!("Batch retrieval messages are filtered out by the network loop!")
+            },
+        };
+        if is_duplicate {
+            counters::CONSENSUS_OBSERVER_RECEIVED_MESSAGES
+                .with_label_values(&[message_type, "duplicate"])
+                .inc();
+            return;
+        }
+
+        let verification_result = match &message {
+            ConsensusObserverMessage::OrderedBlock(ordered_block) => {
+                ordered_block.verify(&epoch_state)
+            },
+            ConsensusObserverMessage::CommitDecision(commit_decision) => {
+                commit_decision.verify(&epoch_state)
+            },
+            ConsensusObserverMessage::BatchRequest(_)
+            | ConsensusObserverMessage::BatchResponse(_) => {
+                unreachable!("Batch retrieval messages are filtered out by the network loop!")
+            },
+        };
+        if let Err(error) = verification_result {
+            warn!(
+                "[ConsensusObserver] Received an invalid {} message from {}: {:?}",
+                message_type, peer_network_id, error
+            );
+            counters::CONSENSUS_OBSERVER_RECEIVED_MESSAGES
+                .with_label_values(&[message_type, "invalid"])
+                .inc();
+            return;
+        }
+        counters::CONSENSUS_OBSERVER_RECEIVED_MESSAGES
+            .with_label_values(&[message_type, "valid"])
+            .inc();
+
+        match message {
+            ConsensusObserverMessage::OrderedBlock(ordered_block) => {
+                self.process_ordered_block(peer_network_id, ordered_block)
+                    .await
+            },
+            ConsensusObserverMessage::CommitDecision(commit_decision) => {
+                // Forward the commit decision to our own observers before processing it
+                if let Some(consensus_publisher) = &self.consensus_publisher {
+                    consensus_publisher.publish(ConsensusObserverMessage::CommitDecision(
+                        commit_decision.clone(),
+                    ));
+                }
+                self.process_commit_decision(commit_decision).await
+            },
+            ConsensusObserverMessage::BatchRequest(_)
+            | ConsensusObserverMessage::BatchResponse(_) => {
+                unreachable!("Batch retrieval messages are filtered out by the network loop!")
+            },
+        }
+    }
+
+    /// Retrieves the batches of the given (verified) ordered block from the peer that sent it,
+    /// and executes the blocks speculatively.
+    async fn process_ordered_block(
+        &mut self,
+        peer_network_id: PeerNetworkId,
+        ordered_block: OrderedBlock,
+    ) {
+        // While state sync is in control, blocks are dropped until the next commit decision
+        if !self.is_in_control() {
+            self.clear_pending_blocks();
+            return;
+        }
+
+        // Only execute the blocks that extend the chain of ordered blocks
+        let (last_ordered_round, last_ordered_id) = self.last_ordered_block();
+        let new_blocks: Vec<_> = ordered_block
+            .blocks()
+            .iter()
+            .filter(|block| block.round() > last_ordered_round)
+            .collect();
+        let first_block = match new_blocks.first() {
+            Some(block) => block,
+            None => return, // All blocks were already ordered
+        };
+        if first_block.parent_id() != last_ordered_id {
+            warn!(
+                "[ConsensusObserver] Ordered block {} doesn't extend the last ordered block {}. \
+                Waiting for the next commit decision to catch up.",
+                first_block, last_ordered_id
+            );
+            return;
+        }
+        if self.pending_blocks.len() + new_blocks.len()
+            > self.config.max_num_pending_blocks as usize
+        {
+            warn!(
+                "[ConsensusObserver] Too many pending blocks ({}). Dropping ordered block {}.",
+                self.pending_blocks.len(),
+                ordered_block.ordered_proof().commit_info()
+            );
+            return;
+        }
+
+        // Retrieve the transactions of all blocks before executing any of them
+        let mut block_transactions = Vec::with_capacity(new_blocks.len());
+        for block in &new_blocks {
+            match self.get_block_transactions(peer_network_id, block).await {
+                Ok(transactions) => block_transactions.push(transactions),
+                Err(error) => {
+                    warn!(
+                        "[ConsensusObserver] Failed to retrieve the transactions of block {} \
+                        from {}: {:?}",
+                        block, peer_network_id, error
+                    );
+                    return;
+                },
+            }
+        }
+
+        // Forward the ordered block to our own observers, now that we can serve its batches
+        if let Some(consensus_publisher) = &self.consensus_publisher {
+            consensus_publisher.publish(ConsensusObserverMessage::OrderedBlock(
+                ordered_block.clone(),
+            ));
+        }
+
+        let mut parent_block_id = last_ordered_id;
+        for (block, transactions) in new_blocks.into_iter().zip(block_transactions) {
+            self.payload_store
+                .insert_transactions(block.id(), transactions);
+            match self.execution_client.compute(block, parent_block_id).await {
+                Ok(compute_result) => {
+                    let executed_block = ExecutedBlock::new(block.clone(), compute_result);
+                    self.pending_blocks
+                        .insert(block.round(), Arc::new(executed_block));
+                    parent_block_id = block.id();
+                },
+                Err(error) => {
+                    warn!(
+                        "[ConsensusObserver] Failed to execute block {}: {:?}",
+                        block, error
+                    );
+                    self.clear_pending_blocks();
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Returns the transactions of the given (verified) block. The quorum store batches
+    /// are retrieved from the given peer, unless they were already retrieved for
+    /// previous blocks.
+    async fn get_block_transactions(
+        &self,
+        peer_network_id: PeerNetworkId,
+        block: &Block,
+    ) -> anyhow::Result<Vec<SignedTransaction>> {
+        let proof_with_data = match block.payload() {
+            None => return Ok(vec![]),
+            Some(Payload::DirectMempool(transactions)) => return Ok(transactions.clone()),
+            Some(Payload::InQuorumStore(proof_with_data)) => proof_with_data,
+        };
+
+        // Expired batches are skipped by execution, so they are never retrieved
+        let batches = join_all(
+            proof_with_data
+                .proofs
+                .iter()
+                .filter(|proof| block.timestamp_usecs() <= proof.expiration())
+                .map(|proof| self.get_batch(peer_network_id, proof)),
+        )
+        .await;
+        let mut transactions = vec![];
+        for batch in batches {
+            transactions.extend(batch?.into_transactions());
+        }
+        Ok(transactions)
+    }
+
+    /// Returns the batch certified by the given proof of store, retrieving it from
+    /// the given peer if it isn't stored locally.
+    async fn get_batch(
+        &self,
+        peer_network_id: PeerNetworkId,
+        proof: &ProofOfStore,
+    ) -> anyhow::Result<Batch> {
+        if let Some(batch) = self.payload_store.get_local_batch(proof.digest()) {
+            return Ok(batch);
+        }
+        let batch = self
+            .batch_client
+            .retrieve_batch(peer_network_id, proof)
+            .await?;
+        self.payload_store.insert_batch(batch.clone());
+        Ok(batch)
+    }
+
+    /// Commits the pending blocks if their execution matches the given (verified) commit
+    /// decision. Otherwise, the observer fell behind or diverged, so it syncs to the decision.
+    async fn process_commit_decision(&mut self, commit_decision: CommitDecision) {
+        let commit_proof = commit_decision.commit_proof().clone();
+        let commit_info = commit_proof.commit_info();
+        if self.is_in_control() {
+            if let Some(executed_block) = self.pending_blocks.get(&commit_info.round()) {
+                let compute_result = executed_block.compute_result();
+                if executed_block.id() == commit_info.id()
+                    && compute_result.root_hash() == commit_info.executed_state_id()
+                    && compute_result.version() == commit_info.version()
+                {
+                    return self.commit_pending_blocks(commit_proof).await;
+                }
+                warn!(
+                    "[ConsensusObserver] Execution of block {} diverged from the commit decision {}! \
+                    Rolling back.",
+                    executed_block.block_info(),
+                    commit_info
+                );
+            }
+        }
+
+        self.sync_to_commit_decision(commit_proof).await;
+    }
+
+    /// Commits the pending blocks up to (and including) the block of the commit proof
+    async fn commit_pending_blocks(&mut self, commit_proof: LedgerInfoWithSignatures) {
+        let commit_round = commit_proof.commit_info().round();
+        let uncommitted_blocks = self.pending_blocks.split_off(&(commit_round + 1));
+        let blocks_to_commit: Vec<_> =
+            std::mem::replace(&mut self.pending_blocks, uncommitted_blocks)
+                .into_values()
+                .collect();
+
+        // The progress time must be updated before state sync learns about the commit
+        self.last_progress_time = Some(Instant::now());
+        if let Err(error) = self
+            .execution_client
+            .commit(
+                &blocks_to_commit,
+                commit_proof.clone(),
+                Box::new(|_: &[Arc<ExecutedBlock>], _: LedgerInfoWithSignatures| {}),
+            )
+            .await
+        {
+            warn!(
+                "[ConsensusObserver] Failed to commit blocks up to {}: {:?}",
+                commit_proof.commit_info(),
+                error
+            );
+            self.clear_pending_blocks();
+            return;
+        }
+
+        let committed_block_ids: Vec<_> = blocks_to_commit.iter().map(|block| block.id()).collect();
+        self.payload_store.remove_transactions(&committed_block_ids);
+        if let Some(last_committed_block) = blocks_to_commit.last() {
+            self.payload_store
+                .remove_expired_batches(last_committed_block.timestamp_usecs());
+        }
+        counters::CONSENSUS_OBSERVER_LAST_COMMITTED_ROUND.set(commit_round as i64);
+        self.update_root(commit_proof);
+    }
+
+    /// Drops the pending blocks and asks state sync to catch up to the commit proof
+    async fn sync_to_commit_decision(&mut self, commit_proof: LedgerInfoWithSignatures) {
+        info!(
+            "[ConsensusObserver] Falling back to state sync. Sync target: {}",
+            commit_proof.commit_info()
+        );
+        counters::CONSENSUS_OBSERVER_FALLBACK_SYNCS.inc();
+        self.clear_pending_blocks();
+
+        // The progress time must be updated before state sync completes the request
+        self.last_progress_time = Some(Instant::now());
+        match self.execution_client.sync_to(commit_proof.clone()).await {
+            Ok(()) => self.update_root(commit_proof),
+            Err(error) => {
+                warn!(
+                    "[ConsensusObserver] Failed to sync to {}: {:?}",
+                    commit_proof.commit_info(),
+                    error
+                );
+                self.last_progress_time = None;
+            },
+        }
+    }
+
+    /// Updates the root to the given committed ledger info
+    fn update_root(&mut self, ledger_info: LedgerInfoWithSignatures) {
+        let ends_epoch = ledger_info.ledger_info().ends_epoch();
+        self.root = ledger_info;
+
+        // Wait for the reconfiguration notification to start the next epoch
+        if ends_epoch {
+            self.clear_pending_blocks();
+            self.execution_client.end_epoch();
+            self.epoch_state = None;
+        }
+    }
+
+    /// Starts a new epoch using the on-chain configs of the reconfiguration
+    fn start_new_epoch(&mut self, payload: OnChainConfigPayload) {
+        let validator_set: ValidatorSet = payload
+            .get()
+            .expect("Failed to get the validator set from the payload!");
+        let epoch_state = Arc::new(EpochState {
+            epoch: payload.epoch(),
+            verifier: (&validator_set).into(),
+        });
+        let execution_config: OnChainExecutionConfig = payload.get().unwrap_or_else(|error| {
+            error!(
+                "[ConsensusObserver] Failed to read the on-chain execution config: {}",
+                error
+            );
+            OnChainExecutionConfig::default()
+        });
+        info!(
+            "[ConsensusObserver] Starting epoch {} with validators: {}",
+            epoch_state.epoch, epoch_state.verifier
+        );
+
+        self.execution_client.new_epoch(
+            &epoch_state,
+            self.payload_manager.clone(),
+            create_transaction_shuffler(execution_config.transaction_shuffler_type()),
+            execution_config.block_gas_limit(),
+            create_transaction_deduper(execution_config.transaction_deduper_type()),
+            create_block_partitioner(
+                execution_config.block_partitioner_type(),
+                execution_config.block_gas_limit(),
+            ),
+        );
+        self.clear_pending_blocks();
+        if let Some(batch_server) = &self.batch_server {
+            batch_server.update_epoch(epoch_state.epoch, Some(self.payload_store.clone()));
+        }
+        match self.db_reader.get_latest_ledger_info() {
+            Ok(ledger_info) => self.root = ledger_info,
+            Err(error) => warn!(
+                "[ConsensusObserver] Failed to read the latest ledger info: {:?}",
+                error
+            ),
+        }
+        self.epoch_state = Some(epoch_state);
+    }
+
+    /// Starts the consensus observer loop
+    pub async fn start(
+        mut self,
+        network_service_events: NetworkServiceEvents<ConsensusObserverMessage>,
+    ) {
+        let network_events: Vec<_> = network_service_events
+            .into_network_and_events()
+            .into_iter()
+            .map(|(network_id, events)| events.map(move |event| (network_id, event)))
+            .collect();
+        let mut network_events = select_all(network_events).fuse();
+
+        info!("[ConsensusObserver] Starting the consensus observer");
+        loop {
+            tokio::select! {
+                Some((network_id, event)) = network_events.next() => match event {
+                    Event::Message(
+                        peer_id,
+                        ConsensusObserverMessage::BatchRequest(_)
+                        | ConsensusObserverMessage::BatchResponse(_),
+                    ) => {
+                        warn!(
+                            "[ConsensusObserver] Unexpected batch retrieval message from {}",
+                            peer_id
+                        );
+                    },
+                    Event::Message(peer_id, message) => {
+                        let peer_network_id = PeerNetworkId::new(network_id, peer_id);
+                        self.process_network_message(peer_network_id, message).await;
+                    },
+                    Event::RpcRequest(
+                        peer_id,
+                        ConsensusObserverMessage::BatchRequest(request),
+                        protocol,
+                        response_sender,
+                    ) => {
+                        // Batch requests are only served if we republish the ordered blocks
+                        if let Some(batch_server) = &self.batch_server {
+                            let peer_network_id = PeerNetworkId::new(network_id, peer_id);
+                            batch_server.respond_to_request(
+                                peer_network_id,
+                                &request,
+                                protocol,
+                                response_sender,
+                            );
+                        }
+                    },
+                    _ => {},
+                },
+                Some(reconfig_notification) = self.reconfig_events.next() => {
+                    self.start_new_epoch(reconfig_notification.on_chain_configs);
+                },
+                else => break,
+            }
+        }
+        info!("[ConsensusObserver] The consensus observer has stopped");
+    }
+}
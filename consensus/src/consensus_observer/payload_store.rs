@@ -0,0 +1,83 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::{batch_store::BatchReader, types::Batch};
+use aptos_consensus_types::proof_of_store::ProofOfStore;
+use aptos_crypto::HashValue;
+use aptos_executor_types::Error;
+use aptos_infallible::Mutex;
+use aptos_types::{transaction::SignedTransaction, PeerId};
+use std::collections::HashMap;
+use tokio::sync::oneshot;
+
+/// Holds the transactions of the blocks received by the consensus observer until
+/// they are committed, so that execution can fetch them through the `PayloadManager`.
+/// The retrieved quorum store batches are held until they expire, so that they can be
+/// served to the downstream observers.
+#[derive(Default)]
+pub struct ObserverPayloadStore {
+    transactions: Mutex<HashMap<HashValue, Vec<SignedTransaction>>>,
+    batches: Mutex<HashMap<HashValue, Batch>>,
+}
+
+impl ObserverPayloadStore {
+    /// Inserts the transactions of the given block
+    pub fn insert_transactions(&self, block_id: HashValue, transactions: Vec<SignedTransaction>) {
+        self.transactions.lock().insert(block_id, transactions);
+    }
+
+    /// Returns the transactions of the given block (if any)
+    pub fn get_transactions(&self, block_id: &HashValue) -> Option<Vec<SignedTransaction>> {
+        self.transactions.lock().get(block_id).cloned()
+    }
+
+    /// Removes the transactions of the given blocks (e.g., once they are committed)
+    pub fn remove_transactions(&self, block_ids: &[HashValue]) {
+        let mut transactions = self.transactions.lock();
+        for block_id in block_ids {
+            transactions.remove(block_id);
+        }
+    }
+
+    /// Inserts the given (verified) batch
+    pub fn insert_batch(&self, batch: Batch) {
+        self.batches.lock().insert(*batch.digest(), batch);
+    }
+
+    /// Removes the batches that expired before the given timestamp (e.g., that of
+    /// the last committed block)
+    pub fn remove_expired_batches(&self, timestamp_usecs: u64) {
+        self.batches
+            .lock()
+            .retain(|_, batch| batch.expiration() >= timestamp_usecs);
+    }
+
+    /// Removes the transactions of all blocks and all batches
+    pub fn clear(&self) {
+        self.transactions.lock().clear();
+        self.batches.lock().clear();
+    }
+}
+
+impl BatchReader for ObserverPayloadStore {
+    fn exists(&self, digest: &HashValue) -> Option<PeerId> {
+        self.batches.lock().get(digest).map(|batch| batch.author())
+    }
+
+    fn get_batch(
+        &self,
+        proof: ProofOfStore,
+    ) -> oneshot::Receiver<Result<Vec<SignedTransaction>, Error>> {
+        let (tx, rx) = oneshot::channel();
+        let result = self
+            .get_local_batch(proof.digest())
+            .map(|batch| batch.into_transactions())
+            .ok_or(Error::CouldNotGetData);
+        let _ = tx.send(result);
+        rx
+    }
+
+    fn get_local_batch(&self, digest: &HashValue) -> Option<Batch> {
+        self.batches.lock().get(digest).cloned()
+    }
+}
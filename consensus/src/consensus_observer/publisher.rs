@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensus_observer::network_message::{CommitDecision, ConsensusObserverMessage, OrderedBlock},
+    counters,
+};
+use aptos_config::{config::RoleType, network_id::PeerNetworkId};
+use aptos_consensus_types::executed_block::ExecutedBlock;
+use aptos_logger::prelude::*;
+use aptos_network::application::interface::{NetworkClient, NetworkClientInterface};
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use futures::StreamExt;
+use futures_channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use tokio::runtime::Handle;
+
+/// Publishes ordered blocks and commit decisions to the downstream consensus observers.
+/// Validators publish to their VFNs, and fullnodes republish to their public peers.
+///
+/// The messages are published in order by a separate task, so the callers never wait
+/// for the network.
+#[derive(Clone)]
+pub struct ConsensusPublisher {
+    publish_tx: UnboundedSender<ConsensusObserverMessage>,
+}
+
+impl ConsensusPublisher {
+    pub fn new(
+        role: RoleType,
+        network_client: NetworkClient<ConsensusObserverMessage>,
+        runtime: &Handle,
+    ) -> Self {
+        let (publish_tx, publish_rx) = unbounded();
+        let inner = PublisherInner {
+            role,
+            network_client,
+        };
+        runtime.spawn(inner.start(publish_rx));
+        Self { publish_tx }
+    }
+
+    /// Publishes the given ordered blocks. The observers retrieve the quorum store
+    /// batches of the blocks from this node.
+    pub fn publish_ordered_blocks(
+        &self,
+        ordered_blocks: &[ExecutedBlock],
+        ordered_proof: LedgerInfoWithSignatures,
+    ) {
+        let blocks = ordered_blocks
+            .iter()
+            .map(|executed_block| executed_block.block().clone())
+            .collect();
+        self.publish(ConsensusObserverMessage::OrderedBlock(OrderedBlock::new(
+            blocks,
+            ordered_proof,
+        )));
+    }
+
+    /// Publishes the given commit decision
+    pub fn publish_commit_decision(&self, commit_proof: LedgerInfoWithSignatures) {
+        self.publish(ConsensusObserverMessage::CommitDecision(
+            CommitDecision::new(commit_proof),
+        ));
+    }
+
+    /// Sends the message to all downstream peers that support the consensus observer
+    pub fn publish(&self, message: ConsensusObserverMessage) {
+        if self.publish_tx.unbounded_send(message).is_err() {
+            warn!("[ConsensusPublisher] The publishing task has stopped");
+        }
+    }
+}
+
+/// The state of the publishing task
+struct PublisherInner {
+    role: RoleType,
+    network_client: NetworkClient<ConsensusObserverMessage>,
+}
+
+impl PublisherInner {
+    async fn start(self, mut publish_rx: UnboundedReceiver<ConsensusObserverMessage>) {
+        while let Some(message) = publish_rx.next().await {
+            self.publish(message);
+        }
+        info!("[ConsensusPublisher] The publishing task has stopped");
+    }
+
+    /// Sends the message to all downstream peers that support the consensus observer
+    fn publish(&self, message: ConsensusObserverMessage) {
+        let downstream_peers = self.get_downstream_peers();
+        if downstream_peers.is_empty() {
+            return;
+        }
+
+        counters::CONSENSUS_OBSERVER_PUBLISHED_MESSAGES
+            .with_label_values(&[message.name()])
+            .inc();
+        if let Err(error) = self
+            .network_client
+            .send_to_peers(message, &downstream_peers)
+        {
+            warn!(
+                "[ConsensusPublisher] Failed to publish the message to {} peers: {:?}",
+                downstream_peers.len(),
+                error
+            );
+        }
+    }
+
+    /// Returns the connected peers that follow this node. Fullnodes never publish
+    /// on the VFN network, as their peer there is the upstream validator.
+    fn get_downstream_peers(&self) -> Vec<PeerNetworkId> {
+        match self.network_client.get_available_peers() {
+            Ok(peers) => peers
+                .into_iter()
+                .filter(|peer| self.role.is_validator() || !peer.network_id().is_vfn_network())
+                .collect(),
+            Err(error) => {
+                warn!(
+                    "[ConsensusPublisher] Failed to get the available peers: {:?}",
+                    error
+                );
+                vec![]
+            },
+        }
+    }
+}
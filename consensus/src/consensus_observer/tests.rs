@@ -0,0 +1,362 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensus_observer::{
+        network_message::{
+            BatchRetrievalRequest, BatchRetrievalResponse, CommitDecision, OrderedBlock,
+        },
+        ObserverBatchServer, ObserverPayloadStore,
+    },
+    quorum_store::{batch_store::BatchReader, types::Batch},
+};
+use aptos_config::{
+    config::ConsensusObserverConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_consensus_types::{
+    block::{
+        block_test_utils::{
+            certificate_for_genesis, placeholder_certificate_for_block, random_payload,
+        },
+        Block,
+    },
+    common::Payload,
+    proof_of_store::{BatchId, ProofOfStore},
+};
+use aptos_crypto::HashValue;
+use aptos_executor_types::Error;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    epoch_state::EpochState,
+    ledger_info::{generate_ledger_info_with_sig, LedgerInfo, LedgerInfoWithSignatures},
+    transaction::SignedTransaction,
+    validator_signer::ValidatorSigner,
+    validator_verifier::generate_validator_verifier,
+    PeerId,
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::oneshot;
+
+#[test]
+fn test_verify_ordered_block() {
+    // Create the signers and the epoch state
+    let signers: Vec<_> = (0..4).map(|i| ValidatorSigner::random([i; 32])).collect();
+    let epoch_state = EpochState {
+        epoch: 1,
+        verifier: generate_validator_verifier(&signers),
+    };
+
+    // Create a chain of two blocks
+    let block_1 = Block::new_proposal(
+        Payload::empty(false),
+        1,
+        1,
+        certificate_for_genesis(),
+        &signers[0],
+        vec![],
+    )
+    .unwrap();
+    let block_2 = Block::new_proposal(
+        Payload::empty(false),
+        2,
+        2,
+        placeholder_certificate_for_block(
+            &signers,
+            block_1.id(),
+            block_1.round(),
+            block_1.quorum_cert().certified_block().id(),
+            block_1.quorum_cert().certified_block().round(),
+        ),
+        &signers[1],
+        vec![],
+    )
+    .unwrap();
+    let blocks = vec![block_1, block_2.clone()];
+
+    // Verify the ordered block with a valid proof
+    let ordered_proof = create_ordered_proof(&signers, &block_2);
+    OrderedBlock::new(blocks.clone(), ordered_proof.clone())
+        .verify(&epoch_state)
+        .unwrap();
+
+    // Verify the ordered block with the blocks out of order
+    let reversed_blocks = blocks.iter().rev().cloned().collect();
+    OrderedBlock::new(reversed_blocks, ordered_proof.clone())
+        .verify(&epoch_state)
+        .unwrap_err();
+
+    // Verify the ordered block without any blocks
+    OrderedBlock::new(vec![], ordered_proof.clone())
+        .verify(&epoch_state)
+        .unwrap_err();
+
+    // Verify the ordered block with an unsigned proof
+    let unsigned_proof = LedgerInfoWithSignatures::new(
+        ordered_proof.ledger_info().clone(),
+        AggregateSignature::empty(),
+    );
+    OrderedBlock::new(blocks.clone(), unsigned_proof)
+        .verify(&epoch_state)
+        .unwrap_err();
+
+    // Verify the ordered block in the wrong epoch
+    let next_epoch_state = EpochState {
+        epoch: 2,
+        verifier: epoch_state.verifier.clone(),
+    };
+    OrderedBlock::new(blocks, ordered_proof.clone())
+        .verify(&next_epoch_state)
+        .unwrap_err();
+
+    // Verify the commit decision in the current and the wrong epoch
+    let commit_decision = CommitDecision::new(ordered_proof);
+    commit_decision.verify(&epoch_state).unwrap();
+    commit_decision.verify(&next_epoch_state).unwrap_err();
+}
+
+#[test]
+fn test_payload_store() {
+    // Insert the transactions of several blocks
+    let payload_store = ObserverPayloadStore::default();
+    let block_ids: Vec<_> = (0..3).map(|_| HashValue::random()).collect();
+    for block_id in &block_ids {
+        payload_store.insert_transactions(*block_id, get_transactions(&random_payload(2)));
+    }
+    for block_id in &block_ids {
+        assert_eq!(payload_store.get_transactions(block_id).unwrap().len(), 2);
+    }
+
+    // Remove the transactions of the first block
+    payload_store.remove_transactions(&block_ids[..1]);
+    assert!(payload_store.get_transactions(&block_ids[0]).is_none());
+    assert!(payload_store.get_transactions(&block_ids[1]).is_some());
+
+    // Clear the store
+    payload_store.clear();
+    for block_id in &block_ids {
+        assert!(payload_store.get_transactions(block_id).is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_payload_store_batches() {
+    // Insert two batches with different expirations
+    let payload_store = ObserverPayloadStore::default();
+    let batch = create_batch(1, 100);
+    let expiring_batch = create_batch(1, 10);
+    payload_store.insert_batch(batch.clone());
+    payload_store.insert_batch(expiring_batch.clone());
+
+    // Verify that the batches are served locally and through proofs of store
+    assert_eq!(payload_store.exists(batch.digest()), Some(batch.author()));
+    assert_eq!(
+        payload_store
+            .get_local_batch(batch.digest())
+            .unwrap()
+            .batch_info(),
+        batch.batch_info()
+    );
+    let transactions = payload_store
+        .get_batch(create_proof_of_store(&batch))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(transactions, batch.clone().into_transactions());
+
+    // Verify that unknown batches are not served
+    let unknown_batch = create_batch(1, 100);
+    assert!(payload_store.exists(unknown_batch.digest()).is_none());
+    payload_store
+        .get_batch(create_proof_of_store(&unknown_batch))
+        .await
+        .unwrap()
+        .unwrap_err();
+
+    // Remove the expired batches (batches are still used at their expiration time)
+    payload_store.remove_expired_batches(10);
+    assert!(payload_store.exists(expiring_batch.digest()).is_some());
+    payload_store.remove_expired_batches(11);
+    assert!(payload_store.exists(expiring_batch.digest()).is_none());
+    assert!(payload_store.exists(batch.digest()).is_some());
+
+    // Clear the store
+    payload_store.clear();
+    assert!(payload_store.exists(batch.digest()).is_none());
+}
+
+#[test]
+fn test_batch_server() {
+    // Create a batch server with a batch in the current epoch
+    let batch_server = ObserverBatchServer::new(ConsensusObserverConfig::default());
+    let batch = create_batch(1, u64::MAX);
+    let batch_reader = Arc::new(MockBatchReader::new(vec![batch.clone()]));
+    let peer = PeerNetworkId::new(NetworkId::Vfn, PeerId::random());
+
+    // Verify that nothing is served before the epoch starts
+    let request = BatchRetrievalRequest::new(1, *batch.digest());
+    assert!(matches!(
+        batch_server.handle_request(peer, &request),
+        BatchRetrievalResponse::NotAvailable
+    ));
+
+    // Verify that the batch is served once the epoch starts
+    batch_server.update_epoch(1, Some(batch_reader));
+    match batch_server.handle_request(peer, &request) {
+        BatchRetrievalResponse::Batch(served_batch) => {
+            assert_eq!(served_batch.batch_info(), batch.batch_info())
+        },
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    // Verify that unknown batches and batches of other epochs are not served
+    let unknown_request = BatchRetrievalRequest::new(1, HashValue::random());
+    assert!(matches!(
+        batch_server.handle_request(peer, &unknown_request),
+        BatchRetrievalResponse::NotAvailable
+    ));
+    let wrong_epoch_request = BatchRetrievalRequest::new(2, *batch.digest());
+    assert!(matches!(
+        batch_server.handle_request(peer, &wrong_epoch_request),
+        BatchRetrievalResponse::NotAvailable
+    ));
+
+    // Verify that nothing is served once quorum store is disabled
+    batch_server.update_epoch(2, None);
+    assert!(matches!(
+        batch_server.handle_request(peer, &wrong_epoch_request),
+        BatchRetrievalResponse::NotAvailable
+    ));
+}
+
+#[test]
+fn test_batch_server_rate_limiting() {
+    // Create a batch server that serves two requests per second to each peer
+    let max_batch_requests_per_second = 2;
+    let batch_server = ObserverBatchServer::new(ConsensusObserverConfig {
+        max_batch_requests_per_second,
+        ..Default::default()
+    });
+    let batch = create_batch(1, u64::MAX);
+    batch_server.update_epoch(1, Some(Arc::new(MockBatchReader::new(vec![batch.clone()]))));
+    let request = BatchRetrievalRequest::new(1, *batch.digest());
+
+    // Verify that the requests are rate limited once the peer runs out of tokens
+    let peer = PeerNetworkId::new(NetworkId::Vfn, PeerId::random());
+    for _ in 0..max_batch_requests_per_second {
+        assert!(matches!(
+            batch_server.handle_request(peer, &request),
+            BatchRetrievalResponse::Batch(_)
+        ));
+    }
+    assert!(matches!(
+        batch_server.handle_request(peer, &request),
+        BatchRetrievalResponse::RateLimited
+    ));
+
+    // Verify that other peers are still served
+    let other_peer = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    assert!(matches!(
+        batch_server.handle_request(other_peer, &request),
+        BatchRetrievalResponse::Batch(_)
+    ));
+}
+
+#[test]
+fn test_verify_batch_response() {
+    // Create a batch and its proof of store
+    let batch = create_batch(1, u64::MAX);
+    let proof = create_proof_of_store(&batch);
+
+    // Verify the response with the certified batch
+    let verified_batch = BatchRetrievalResponse::Batch(batch.clone())
+        .verify_and_take_batch(&proof)
+        .unwrap();
+    assert_eq!(verified_batch.batch_info(), batch.batch_info());
+
+    // Verify the response with another batch
+    BatchRetrievalResponse::Batch(create_batch(1, u64::MAX))
+        .verify_and_take_batch(&proof)
+        .unwrap_err();
+
+    // Verify the responses without a batch
+    BatchRetrievalResponse::NotAvailable
+        .verify_and_take_batch(&proof)
+        .unwrap_err();
+    BatchRetrievalResponse::RateLimited
+        .verify_and_take_batch(&proof)
+        .unwrap_err();
+}
+
+/// A batch reader that only holds the given batches locally
+struct MockBatchReader {
+    batches: HashMap<HashValue, Batch>,
+}
+
+impl MockBatchReader {
+    fn new(batches: Vec<Batch>) -> Self {
+        Self {
+            batches: batches
+                .into_iter()
+                .map(|batch| (*batch.digest(), batch))
+                .collect(),
+        }
+    }
+}
+
+impl BatchReader for MockBatchReader {
+    fn exists(&self, digest: &HashValue) -> Option<PeerId> {
+        self.batches.get(digest).map(|batch| batch.author())
+    }
+
+    fn get_batch(
+        &self,
+        proof: ProofOfStore,
+    ) -> oneshot::Receiver<Result<Vec<SignedTransaction>, Error>> {
+        let (tx, rx) = oneshot::channel();
+        let result = self
+            .get_local_batch(proof.digest())
+            .map(|batch| batch.into_transactions())
+            .ok_or(Error::CouldNotGetData);
+        let _ = tx.send(result);
+        rx
+    }
+
+    fn get_local_batch(&self, digest: &HashValue) -> Option<Batch> {
+        self.batches.get(digest).cloned()
+    }
+}
+
+/// Creates a batch with random transactions in the given epoch
+fn create_batch(epoch: u64, expiration: u64) -> Batch {
+    Batch::new(
+        BatchId::new_for_test(1),
+        get_transactions(&random_payload(3)),
+        epoch,
+        expiration,
+        PeerId::random(),
+        0,
+    )
+}
+
+/// Creates an (unsigned) proof of store for the given batch
+fn create_proof_of_store(batch: &Batch) -> ProofOfStore {
+    ProofOfStore::new(batch.batch_info().clone(), AggregateSignature::empty())
+}
+
+/// Creates an ordered proof for the given block signed by all signers
+fn create_ordered_proof(signers: &[ValidatorSigner], block: &Block) -> LedgerInfoWithSignatures {
+    let ledger_info = LedgerInfo::new(
+        block.gen_block_info(HashValue::zero(), 0, None),
+        HashValue::zero(),
+    );
+    generate_ledger_info_with_sig(signers, ledger_info)
+}
+
+/// Returns the transactions of the given direct mempool payload
+fn get_transactions(payload: &Payload) -> Vec<SignedTransaction> {
+    match payload {
+        Payload::DirectMempool(transactions) => transactions.clone(),
+        _ => unreachable!("Expected a direct mempool payload!"),
+    }
+}
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_storage::tracing::set_block_timelines_enabled_once,
+    consensus_observer::{ConsensusObserver, ConsensusObserverMessage, ObserverBatchServer},
     counters,
     epoch_manager::EpochManager,
     network::NetworkTask,
@@ -36,9 +38,18 @@ pub fn start_consensus(
     consensus_to_mempool_sender: mpsc::Sender<QuorumStoreRequest>,
     aptos_db: DbReaderWriter,
     reconfig_events: ReconfigNotificationListener,
+    consensus_publisher_network_client: Option<NetworkClient<ConsensusObserverMessage>>,
+    consensus_publisher_network_events: Option<NetworkServiceEvents<ConsensusObserverMessage>>,
 ) -> Runtime {
     let runtime = aptos_runtimes::spawn_named_runtime("consensus".into(), None);
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
+
+    // Only record the block timelines if the inspection service exposes them
+    set_block_timelines_enabled_once(
+        node_config
+            .inspection_service
+            .expose_consensus_block_timelines,
+    );
     let quorum_store_db = Arc::new(QuorumStoreDB::new(node_config.storage.dir()));
 
     let txn_notifier = Arc::new(MempoolNotifier::new(
@@ -59,6 +70,14 @@ pub fn start_consensus(
         aptos_channels::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
     let (self_sender, self_receiver) = aptos_channels::new(1_024, &counters::PENDING_SELF_MESSAGES);
 
+    // Serve the batch requests of the downstream consensus observers
+    let observer_batch_server = consensus_publisher_network_events.map(|network_events| {
+        let observer_batch_server =
+            Arc::new(ObserverBatchServer::new(node_config.consensus_observer));
+        runtime.spawn(observer_batch_server.clone().start(network_events));
+        observer_batch_server
+    });
+
     let consensus_network_client = ConsensusNetworkClient::new(network_client);
     let bounded_executor = BoundedExecutor::new(8, runtime.handle().clone());
     let epoch_mgr = EpochManager::new(
@@ -73,6 +92,8 @@ pub fn start_consensus(
         quorum_store_db,
         reconfig_events,
         bounded_executor,
+        consensus_publisher_network_client,
+        observer_batch_server,
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_service_events, self_receiver);
@@ -83,3 +104,42 @@ pub fn start_consensus(
     debug!("Consensus started.");
     runtime
 }
+
+/// Helper function to start the consensus observer (on fullnodes) and return the runtime
+pub fn start_consensus_observer(
+    node_config: &NodeConfig,
+    network_client: NetworkClient<ConsensusObserverMessage>,
+    network_service_events: NetworkServiceEvents<ConsensusObserverMessage>,
+    state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
+    consensus_to_mempool_sender: mpsc::Sender<QuorumStoreRequest>,
+    aptos_db: DbReaderWriter,
+    reconfig_events: ReconfigNotificationListener,
+) -> Runtime {
+    let runtime = aptos_runtimes::spawn_named_runtime("observer".into(), None);
+
+    let txn_notifier = Arc::new(MempoolNotifier::new(
+        consensus_to_mempool_sender,
+        node_config.consensus.mempool_executed_txn_timeout_ms,
+    ));
+
+    let db_reader = aptos_db.reader.clone();
+    let execution_client = Arc::new(ExecutionProxy::new(
+        Arc::new(BlockExecutor::<AptosVM>::new(aptos_db)),
+        txn_notifier,
+        state_sync_notifier,
+        runtime.handle(),
+    ));
+
+    let consensus_observer = ConsensusObserver::new(
+        node_config.consensus_observer,
+        db_reader,
+        execution_client,
+        reconfig_events,
+        network_client,
+        runtime.handle(),
+    );
+    runtime.spawn(consensus_observer.start(network_service_events));
+
+    debug!("Consensus observer started.");
+    runtime
+}
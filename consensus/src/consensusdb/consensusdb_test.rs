@@ -3,8 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use aptos_consensus_types::block::block_test_utils::certificate_for_genesis;
+use aptos_consensus_types::{
+    block::block_test_utils::certificate_for_genesis,
+    common::{Author, Payload},
+    dag::{CertifiedNode, Node},
+};
 use aptos_temppath::TempPath;
+use aptos_types::{aggregate_signature::AggregateSignature, validator_signer::ValidatorSigner};
 
 #[test]
 fn test_put_get() {
@@ -70,3 +75,61 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_dag() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+
+    let node = Node::new(1, 1, Author::random(), 1, Payload::empty(true), vec![]);
+    db.put::<NodeSchema>(&(), &node).unwrap();
+    assert_eq!(db.get::<NodeSchema>(&()).unwrap(), Some(node.clone()));
+
+    let certified_node = CertifiedNode::new(node.clone(), AggregateSignature::empty());
+    db.put::<CertifiedNodeSchema>(&node.digest(), &certified_node)
+        .unwrap();
+    assert_eq!(db.get_all::<CertifiedNodeSchema>().unwrap(), vec![(
+        node.digest(),
+        certified_node
+    )]);
+
+    db.delete::<NodeSchema>(vec![()]).unwrap();
+    db.delete::<CertifiedNodeSchema>(vec![node.digest()])
+        .unwrap();
+    assert!(db.get::<NodeSchema>(&()).unwrap().is_none());
+    assert!(db.get_all::<CertifiedNodeSchema>().unwrap().is_empty());
+}
+
+#[test]
+fn test_equivocation_evidence() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+    assert!(db.get_equivocation_evidence().unwrap().is_empty());
+
+    let signer = ValidatorSigner::random(None);
+    let create_proposal = |timestamp| {
+        Block::new_proposal(
+            Payload::empty(false),
+            1,
+            timestamp,
+            certificate_for_genesis(),
+            &signer,
+            vec![],
+        )
+        .unwrap()
+    };
+    let evidence = EquivocationEvidence::Proposal(create_proposal(1), create_proposal(2));
+    db.save_equivocation_evidence(&evidence).unwrap();
+
+    // Only the first evidence is kept for the same author, round and kind
+    let other_evidence = EquivocationEvidence::Proposal(create_proposal(1), create_proposal(3));
+    db.save_equivocation_evidence(&other_evidence).unwrap();
+    assert_eq!(db.get_equivocation_evidence().unwrap(), vec![
+        evidence.clone()
+    ]);
+
+    // The evidence can be read while the DB is open
+    assert_eq!(read_equivocation_evidence(&tmp_dir).unwrap(), vec![
+        evidence
+    ]);
+}
@@ -8,18 +8,29 @@ mod schema;
 
 use crate::{
     consensusdb::schema::{
-        block::BlockSchema,
-        quorum_certificate::QCSchema,
+        equivocation_evidence::EquivocationEvidenceSchema,
         single_entry::{SingleEntryKey, SingleEntrySchema},
     },
     error::DbError,
 };
 use anyhow::Result;
-use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
+use aptos_consensus_types::{
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
-use aptos_schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
-use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
+use aptos_schemadb::{
+    schema::Schema, ColumnFamilyName, Options, ReadOptions, SchemaBatch, DB,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
+pub(crate) use schema::{
+    block::BlockSchema, certified_node::CertifiedNodeSchema, dag_vote::DagVoteSchema,
+    node::NodeSchema, quorum_certificate::QCSchema,
+};
+use schema::{
+    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, EQUIVOCATION_EVIDENCE_CF_NAME,
+    NODE_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME,
+};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
 /// The name of the consensus db file
@@ -41,26 +52,56 @@ pub fn create_checkpoint<P: AsRef<Path> + Clone>(db_path: P, checkpoint_path: P)
     Ok(())
 }
 
+/// Reads all the equivocation evidence from the ConsensusDB under `db_root_path`. The DB is
+/// opened read-only, so this can be used while the node is running.
+pub fn read_equivocation_evidence<P: AsRef<Path>>(
+    db_root_path: P,
+) -> Result<Vec<EquivocationEvidence>> {
+    Ok(ConsensusDB::open(db_root_path, true)?.get_equivocation_evidence()?)
+}
+
 pub struct ConsensusDB {
     db: DB,
 }
 
 impl ConsensusDB {
-    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        let column_families = vec![
+    fn column_families() -> Vec<ColumnFamilyName> {
+        vec![
             /* UNUSED CF = */ DEFAULT_COLUMN_FAMILY_NAME,
             BLOCK_CF_NAME,
             QC_CF_NAME,
             SINGLE_ENTRY_CF_NAME,
-        ];
+            NODE_CF_NAME,
+            DAG_VOTE_CF_NAME,
+            CERTIFIED_NODE_CF_NAME,
+            EQUIVOCATION_EVIDENCE_CF_NAME,
+        ]
+    }
+
+    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        Self::open(db_root_path, false).expect("ConsensusDB open failed; unable to continue")
+    }
+
+    /// Opens the ConsensusDB under `db_root_path`. A read-only DB isn't created if missing, and
+    /// can be opened while the node is running.
+    pub fn open<P: AsRef<Path>>(db_root_path: P, readonly: bool) -> Result<Self> {
+        let column_families = Self::column_families();
 
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
         let instant = Instant::now();
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
-        opts.create_missing_column_families(true);
-        let db = DB::open(path.clone(), "consensus", column_families, &opts)
-            .expect("ConsensusDB open failed; unable to continue");
+        let db = if readonly {
+            DB::open_cf_readonly(
+                &Options::default(),
+                path.clone(),
+                "consensus_readonly",
+                column_families,
+            )?
+        } else {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            opts.create_missing_column_families(true);
+            DB::open(path.clone(), "consensus", column_families, &opts)?
+        };
 
         info!(
             "Opened ConsensusDB at {:?} in {} ms",
@@ -68,7 +109,7 @@ impl ConsensusDB {
             instant.elapsed().as_millis()
         );
 
-        Self { db }
+        Ok(Self { db })
     }
 
     pub fn get_data(
@@ -140,6 +181,58 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Saves the evidence of an equivocation. Only the first evidence of each kind is kept
+    /// for an author and round.
+    pub fn save_equivocation_evidence(
+        &self,
+        evidence: &EquivocationEvidence,
+    ) -> Result<(), DbError> {
+        let author = evidence
+            .author()
+            .ok_or_else(|| anyhow::anyhow!("Equivocation evidence without an author!"))?;
+        let key = (
+            evidence.epoch(),
+            evidence.round(),
+            author,
+            evidence.name().to_string(),
+        );
+        if self.db.get::<EquivocationEvidenceSchema>(&key)?.is_none() {
+            self.put::<EquivocationEvidenceSchema>(&key, evidence)?;
+        }
+        Ok(())
+    }
+
+    /// Get all the equivocation evidence.
+    pub fn get_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>, DbError> {
+        Ok(self
+            .get_all::<EquivocationEvidenceSchema>()?
+            .into_iter()
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+
+    pub fn put<S: Schema>(&self, key: &S::Key, value: &S::Value) -> Result<(), DbError> {
+        let batch = SchemaBatch::new();
+        batch.put::<S>(key, value)?;
+        self.commit(batch)
+    }
+
+    pub fn delete<S: Schema>(&self, keys: Vec<S::Key>) -> Result<(), DbError> {
+        let batch = SchemaBatch::new();
+        keys.iter().try_for_each(|key| batch.delete::<S>(key))?;
+        self.commit(batch)
+    }
+
+    pub fn get<S: Schema>(&self, key: &S::Key) -> Result<Option<S::Value>, DbError> {
+        Ok(self.db.get::<S>(key)?)
+    }
+
+    pub fn get_all<S: Schema>(&self) -> Result<Vec<(S::Key, S::Value)>, DbError> {
+        let mut iter = self.db.iter::<S>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.collect::<Result<Vec<(S::Key, S::Value)>>>()?)
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
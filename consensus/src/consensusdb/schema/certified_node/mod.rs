@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the certified nodes in the DAG.
+//!
+//! Serialized certified node identified by the node digest.
+//! ```text
+//! |<--key-->|<-----value---->|
+//! | digest  | certified_node |
+//! ```
+
+use super::CERTIFIED_NODE_CF_NAME;
+use anyhow::Result;
+use aptos_consensus_types::dag::CertifiedNode;
+use aptos_crypto::HashValue;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+
+define_schema!(
+    CertifiedNodeSchema,
+    HashValue,
+    CertifiedNode,
+    CERTIFIED_NODE_CF_NAME
+);
+
+impl KeyCodec<CertifiedNodeSchema> for HashValue {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(HashValue::from_slice(data)?)
+    }
+}
+
+impl ValueCodec<CertifiedNodeSchema> for CertifiedNode {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
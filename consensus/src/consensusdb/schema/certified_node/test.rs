@@ -0,0 +1,23 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::{common::Payload, dag::Node};
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::{account_address::AccountAddress, aggregate_signature::AggregateSignature};
+
+#[test]
+fn test_encode_decode() {
+    let node = Node::new(
+        1,
+        1,
+        AccountAddress::random(),
+        1,
+        Payload::empty(true),
+        vec![],
+    );
+    let certified_node = CertifiedNode::new(node, AggregateSignature::empty());
+    assert_encode_decode::<CertifiedNodeSchema>(&certified_node.digest(), &certified_node);
+}
+
+test_no_panic_decoding!(CertifiedNodeSchema);
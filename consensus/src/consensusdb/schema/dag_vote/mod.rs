@@ -0,0 +1,43 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the votes on DAG nodes.
+//!
+//! Serialized vote identified by the id of the node voted for.
+//! ```text
+//! |<--key-->|<--value-->|
+//! | node_id |   vote    |
+//! ```
+
+use super::DAG_VOTE_CF_NAME;
+use anyhow::Result;
+use aptos_consensus_types::dag::{NodeId, Vote};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+
+define_schema!(DagVoteSchema, NodeId, Vote, DAG_VOTE_CF_NAME);
+
+impl KeyCodec<DagVoteSchema> for NodeId {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<DagVoteSchema> for Vote {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,18 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::dag::NodeMetadata;
+use aptos_crypto::HashValue;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let metadata = NodeMetadata::new_for_test(1, 2, signer.author(), 3, HashValue::random());
+    let vote = Vote::new(metadata.clone(), &signer).unwrap();
+    assert_encode_decode::<DagVoteSchema>(&NodeId::from(&metadata), &vote);
+}
+
+test_no_panic_decoding!(DagVoteSchema);
@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the evidence of equivocating validators.
+//!
+//! Serialized evidence identified by the epoch, round, author and kind of the equivocation.
+//! Only the first evidence of each kind is kept per author and round.
+//! ```text
+//! |<-----------------key----------------->|<--value-->|
+//! | epoch | round | author | evidence kind |  evidence |
+//! ```
+
+use super::EQUIVOCATION_EVIDENCE_CF_NAME;
+use anyhow::Result;
+use aptos_consensus_types::{
+    common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
+};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+
+/// The (epoch, round, author, evidence kind) of an equivocation
+pub type EquivocationEvidenceKey = (u64, Round, Author, String);
+
+define_schema!(
+    EquivocationEvidenceSchema,
+    EquivocationEvidenceKey,
+    EquivocationEvidence,
+    EQUIVOCATION_EVIDENCE_CF_NAME
+);
+
+impl KeyCodec<EquivocationEvidenceSchema> for EquivocationEvidenceKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<EquivocationEvidenceSchema> for EquivocationEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
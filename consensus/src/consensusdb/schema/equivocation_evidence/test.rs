@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+};
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let create_proposal = |timestamp| {
+        Block::new_proposal(
+            Payload::empty(false),
+            1,
+            timestamp,
+            certificate_for_genesis(),
+            &signer,
+            vec![],
+        )
+        .unwrap()
+    };
+    let evidence = EquivocationEvidence::Proposal(create_proposal(1), create_proposal(2));
+    let key = (
+        evidence.epoch(),
+        evidence.round(),
+        signer.author(),
+        evidence.name().to_string(),
+    );
+    assert_encode_decode::<EquivocationEvidenceSchema>(&key, &evidence);
+}
+
+test_no_panic_decoding!(EquivocationEvidenceSchema);
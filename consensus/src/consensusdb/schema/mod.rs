@@ -3,6 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub(crate) mod block;
+pub(crate) mod certified_node;
+pub(crate) mod dag_vote;
+pub(crate) mod equivocation_evidence;
+pub(crate) mod node;
 pub(crate) mod quorum_certificate;
 pub(crate) mod single_entry;
 
@@ -12,6 +16,10 @@ use aptos_schemadb::ColumnFamilyName;
 pub(super) const BLOCK_CF_NAME: ColumnFamilyName = "block";
 pub(super) const QC_CF_NAME: ColumnFamilyName = "quorum_certificate";
 pub(super) const SINGLE_ENTRY_CF_NAME: ColumnFamilyName = "single_entry";
+pub(super) const NODE_CF_NAME: ColumnFamilyName = "node";
+pub(super) const DAG_VOTE_CF_NAME: ColumnFamilyName = "dag_vote";
+pub(super) const CERTIFIED_NODE_CF_NAME: ColumnFamilyName = "certified_node";
+pub(super) const EQUIVOCATION_EVIDENCE_CF_NAME: ColumnFamilyName = "equivocation_evidence";
 
 fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
     ensure!(
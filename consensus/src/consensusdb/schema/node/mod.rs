@@ -0,0 +1,43 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the DAG node this validator is broadcasting.
+//!
+//! There is at most one pending node, so the key carries no information.
+//! ```text
+//! |<--key-->|<--value-->|
+//! |   ()    |   node    |
+//! ```
+
+use super::NODE_CF_NAME;
+use anyhow::Result;
+use aptos_consensus_types::dag::Node;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+
+define_schema!(NodeSchema, (), Node, NODE_CF_NAME);
+
+impl KeyCodec<NodeSchema> for () {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<NodeSchema> for Node {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
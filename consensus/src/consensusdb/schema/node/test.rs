@@ -0,0 +1,22 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::common::Payload;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::account_address::AccountAddress;
+
+#[test]
+fn test_encode_decode() {
+    let node = Node::new(
+        1,
+        1,
+        AccountAddress::random(),
+        1,
+        Payload::empty(true),
+        vec![],
+    );
+    assert_encode_decode::<NodeSchema>(&(), &node);
+}
+
+test_no_panic_decoding!(NodeSchema);
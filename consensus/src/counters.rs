@@ -738,6 +738,26 @@ pub static RPC_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counters(queued,dequeued,dropped) related to DAG rpc request channel
+pub static DAG_RPC_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_dag_rpc_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to DAG rpc request channel",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Counters(queued,dequeued,dropped) related to DAG rpc per epoch task
+pub static DAG_RPC_TASK_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_dag_rpc_task_msgs_count",
+        "Counters(queued,dequeued,dropped) related to DAG rpc task",
+        &["state"]
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to block retrieval per epoch task
 pub static BLOCK_RETRIEVAL_TASK_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -792,3 +812,81 @@ pub static BATCH_WAIT_DURATION: Lazy<DurationHistogram> = Lazy::new(|| {
         .unwrap(),
     )
 });
+
+/// Counters(queued,dequeued,dropped) related to pending network notifications to the consensus observer
+pub static PENDING_CONSENSUS_OBSERVER_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_observer_pending_network_events",
+        "Counters(queued,dequeued,dropped) related to pending network notifications to the consensus observer",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Count of the messages published to consensus observers, by message type
+pub static CONSENSUS_OBSERVER_PUBLISHED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_observer_published_messages_count",
+        "Count of the messages published to consensus observers, by message type",
+        &["message_type"]
+    )
+    .unwrap()
+});
+
+/// Count of the messages received by the consensus observer, by message type and result
+pub static CONSENSUS_OBSERVER_RECEIVED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_observer_received_messages_count",
+        "Count of the messages received by the consensus observer, by message type and result",
+        &["message_type", "result"]
+    )
+    .unwrap()
+});
+
+/// Count of the times the consensus observer fell back to state sync
+pub static CONSENSUS_OBSERVER_FALLBACK_SYNCS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_observer_fallback_syncs_count",
+        "Count of the times the consensus observer fell back to state sync"
+    )
+    .unwrap()
+});
+
+/// The round of the last block committed by the consensus observer
+pub static CONSENSUS_OBSERVER_LAST_COMMITTED_ROUND: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_observer_last_committed_round",
+        "The round of the last block committed by the consensus observer"
+    )
+    .unwrap()
+});
+
+/// Count of the batch requests served to consensus observers, by result
+pub static CONSENSUS_OBSERVER_BATCH_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_observer_batch_requests_count",
+        "Count of the batch requests served to consensus observers, by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Count of the batches retrieved from upstream peers, by verification result
+pub static CONSENSUS_OBSERVER_RETRIEVED_BATCHES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_observer_retrieved_batches_count",
+        "Count of the batches retrieved from upstream peers, by verification result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Count of the equivocation evidence collected by consensus, by kind
+pub static EQUIVOCATION_EVIDENCE: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_equivocation_evidence_count",
+        "Count of the equivocation evidence collected by consensus, by kind",
+        &["kind"]
+    )
+    .unwrap()
+});
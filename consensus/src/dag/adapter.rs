@@ -0,0 +1,154 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{dag_store::Dag, storage::DAGStorage},
+    experimental::buffer_manager::OrderedBlocks,
+};
+use anyhow::anyhow;
+use aptos_bitvec::BitVec;
+use aptos_consensus_types::{
+    block::Block,
+    block_data::BlockData,
+    common::{Author, Round},
+    dag::{CertifiedNode, NodeId},
+    executed_block::ExecutedBlock,
+};
+use aptos_crypto::HashValue;
+use aptos_executor_types::StateComputeResult;
+use aptos_infallible::RwLock;
+use aptos_logger::error;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
+use futures_channel::mpsc::UnboundedSender;
+use std::sync::Arc;
+
+/// Receives the ordered nodes of each anchor, in order.
+pub trait Notifier: Send {
+    fn send_ordered_nodes(
+        &mut self,
+        ordered_nodes: Vec<Arc<CertifiedNode>>,
+        anchor: Arc<CertifiedNode>,
+        failed_anchors: Vec<(Round, Author)>,
+    ) -> anyhow::Result<()>;
+}
+
+/// Turns the ordered nodes of an anchor into a block and sends it to the execution pipeline,
+/// the committed blocks prune the DAG.
+pub struct NotifierAdapter {
+    executor_channel: UnboundedSender<OrderedBlocks>,
+    dag: Arc<RwLock<Dag>>,
+    storage: Arc<dyn DAGStorage>,
+    parent_block_info: BlockInfo,
+    epoch_state: Arc<EpochState>,
+    dag_window_rounds: Round,
+}
+
+impl NotifierAdapter {
+    pub fn new(
+        executor_channel: UnboundedSender<OrderedBlocks>,
+        dag: Arc<RwLock<Dag>>,
+        storage: Arc<dyn DAGStorage>,
+        parent_block_info: BlockInfo,
+        epoch_state: Arc<EpochState>,
+        dag_window_rounds: Round,
+    ) -> Self {
+        Self {
+            executor_channel,
+            dag,
+            storage,
+            parent_block_info,
+            epoch_state,
+            dag_window_rounds,
+        }
+    }
+}
+
+impl Notifier for NotifierAdapter {
+    fn send_ordered_nodes(
+        &mut self,
+        ordered_nodes: Vec<Arc<CertifiedNode>>,
+        anchor: Arc<CertifiedNode>,
+        failed_anchors: Vec<(Round, Author)>,
+    ) -> anyhow::Result<()> {
+        let verifier = &self.epoch_state.verifier;
+        let mut parents_bitvec = BitVec::with_num_bits(verifier.len() as u16);
+        for parent in anchor.parents_metadata() {
+            if let Some(index) = verifier.address_to_validator_index().get(parent.author()) {
+                parents_bitvec.set(*index as u16);
+            }
+        }
+        let node_digests = ordered_nodes.iter().map(|node| node.digest()).collect();
+        let payload = ordered_nodes
+            .iter()
+            .map(|node| node.payload().clone())
+            .reduce(|payload, other| payload.extend(other))
+            .ok_or_else(|| anyhow!("Anchor {} orders no nodes", anchor.metadata()))?;
+        // Block timestamps must strictly increase.
+        let timestamp_usecs = anchor
+            .timestamp_usecs()
+            .max(self.parent_block_info.timestamp_usecs() + 1);
+
+        let block = ExecutedBlock::new(
+            Block::new_for_dag(BlockData::new_for_dag(
+                self.epoch_state.epoch,
+                anchor.round(),
+                timestamp_usecs,
+                payload,
+                *anchor.author(),
+                failed_anchors,
+                self.parent_block_info.clone(),
+                parents_bitvec,
+                node_digests,
+            )),
+            StateComputeResult::new_dummy(),
+        );
+        let block_info = block.block_info();
+        self.parent_block_info = block_info.clone();
+
+        let dag = self.dag.clone();
+        let storage = self.storage.clone();
+        let dag_window_rounds = self.dag_window_rounds;
+        let epoch = self.epoch_state.epoch;
+        self.executor_channel
+            .unbounded_send(OrderedBlocks {
+                ordered_blocks: vec![block],
+                ordered_proof: LedgerInfoWithSignatures::new(
+                    LedgerInfo::new(block_info, HashValue::zero()),
+                    AggregateSignature::empty(),
+                ),
+                callback: Box::new(
+                    move |committed_blocks: &[Arc<ExecutedBlock>],
+                          _commit_decision: LedgerInfoWithSignatures| {
+                        let committed_round = match committed_blocks.last() {
+                            Some(block) => block.round(),
+                            None => return,
+                        };
+                        let prune_round = committed_round.saturating_sub(dag_window_rounds);
+                        dag.write().prune(prune_round);
+                        // The votes of the pruned rounds are not needed anymore.
+                        match storage.get_votes() {
+                            Ok(votes) => {
+                                let expired: Vec<NodeId> = votes
+                                    .into_iter()
+                                    .map(|(node_id, _)| node_id)
+                                    .filter(|node_id| {
+                                        node_id.epoch() != epoch || node_id.round() < prune_round
+                                    })
+                                    .collect();
+                                if let Err(e) = storage.delete_votes(expired) {
+                                    error!(error = ?e, "Failed to delete expired votes");
+                                }
+                            },
+                            Err(e) => error!(error = ?e, "Failed to read votes"),
+                        }
+                    },
+                ),
+            })
+            .map_err(|e| anyhow!("Failed to send ordered blocks: {:?}", e))
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus_types::common::{Author, Round};
+
+/// Elects the anchor of an (even) round, every validator must elect the same anchor.
+pub trait AnchorElection: Send + Sync {
+    fn get_anchor(&self, round: Round) -> Author;
+}
+
+pub struct RoundRobinAnchorElection {
+    validators: Vec<Author>,
+}
+
+impl RoundRobinAnchorElection {
+    pub fn new(validators: Vec<Author>) -> Self {
+        assert!(!validators.is_empty(), "Anchor election needs validators");
+        Self { validators }
+    }
+}
+
+impl AnchorElection for RoundRobinAnchorElection {
+    fn get_anchor(&self, round: Round) -> Author {
+        // Only even rounds have anchors, rotate over all the validators.
+        self.validators[((round / 2) % self.validators.len() as u64) as usize]
+    }
+}
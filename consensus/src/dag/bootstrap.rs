@@ -0,0 +1,145 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        adapter::NotifierAdapter,
+        anchor_election::{AnchorElection, RoundRobinAnchorElection},
+        dag_driver::DagDriver,
+        dag_fetcher::{DagFetcher, FetchRequestHandler},
+        dag_handler::NetworkHandler,
+        dag_store::Dag,
+        order_rule::OrderRule,
+        rb_handler::{DagVoteSigner, NodeBroadcastHandler},
+        reliable_broadcast::{DAGNetworkSender, ReliableBroadcast},
+        storage::DAGStorage,
+    },
+    experimental::buffer_manager::OrderedBlocks,
+    state_replication::PayloadClient,
+    util::time_service::TimeService,
+};
+use aptos_config::config::DagConsensusConfig;
+use aptos_consensus_types::{block::Block, common::Author, quorum_cert::QuorumCert};
+use aptos_infallible::RwLock;
+use aptos_logger::info;
+use aptos_types::{epoch_state::EpochState, ledger_info::LedgerInfo};
+use futures_channel::mpsc::UnboundedSender;
+use std::{sync::Arc, time::Duration};
+use tokio_retry::strategy::ExponentialBackoff;
+
+/// Recovers the DAG of the epoch from storage and builds the components of the DAG consensus.
+/// The ordered blocks are sent to the execution pipeline through the executor channel, the first
+/// one extends the latest committed ledger info.
+#[allow(clippy::too_many_arguments)]
+pub async fn bootstrap_dag(
+    self_peer: Author,
+    vote_signer: Arc<dyn DagVoteSigner>,
+    epoch_state: Arc<EpochState>,
+    latest_ledger_info: LedgerInfo,
+    storage: Arc<dyn DAGStorage>,
+    network_sender: Arc<dyn DAGNetworkSender>,
+    time_service: Arc<dyn TimeService>,
+    payload_client: Arc<dyn PayloadClient>,
+    executor_channel: UnboundedSender<OrderedBlocks>,
+    config: DagConsensusConfig,
+    quorum_store_enabled: bool,
+) -> NetworkHandler {
+    let validators = epoch_state.verifier.get_ordered_account_addresses();
+    let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+    let anchor_election = Arc::new(RoundRobinAnchorElection::new(validators.clone()));
+
+    // The first block either extends the virtual genesis block of the epoch, or the last
+    // committed block (the block of the last committed anchor) when recovering within an epoch.
+    let (parent_block_info, committed_round) = if latest_ledger_info.ends_epoch() {
+        let genesis = Block::make_genesis_block_from_ledger_info(&latest_ledger_info);
+        let genesis_qc =
+            QuorumCert::certificate_for_genesis_from_ledger_info(&latest_ledger_info, genesis.id());
+        (genesis_qc.certified_block().clone(), 0)
+    } else {
+        (
+            latest_ledger_info.commit_info().clone(),
+            latest_ledger_info.round(),
+        )
+    };
+    if committed_round > 0 {
+        let mut dag_writer = dag.write();
+        dag_writer.prune(committed_round.saturating_sub(config.dag_window_rounds));
+        let anchor_author = anchor_election.get_anchor(committed_round);
+        match dag_writer
+            .get_node_by_round_author(committed_round, &anchor_author)
+            .cloned()
+        {
+            Some(anchor) => {
+                let ordered_nodes = dag_writer.reachable(&[anchor.metadata().clone()], None, true);
+                dag_writer.mark_as_ordered(&ordered_nodes);
+            },
+            None => dag_writer.mark_rounds_as_ordered(committed_round),
+        }
+    }
+    let lowest_unordered_anchor_round = committed_round - committed_round % 2 + 2;
+    info!(
+        epoch = epoch_state.epoch,
+        "Bootstrapping DAG from committed round {}", committed_round
+    );
+
+    let notifier = NotifierAdapter::new(
+        executor_channel,
+        dag.clone(),
+        storage.clone(),
+        parent_block_info,
+        epoch_state.clone(),
+        config.dag_window_rounds,
+    );
+    let order_rule = OrderRule::new(
+        epoch_state.clone(),
+        lowest_unordered_anchor_round,
+        dag.clone(),
+        anchor_election,
+        Box::new(notifier),
+    );
+
+    let rb_config = config.rb_config;
+    let backoff_policy = ExponentialBackoff::from_millis(rb_config.backoff_policy_base_ms)
+        .factor(rb_config.backoff_policy_factor)
+        .max_delay(Duration::from_millis(rb_config.backoff_policy_max_delay_ms));
+    let reliable_broadcast = Arc::new(ReliableBroadcast::new(
+        validators,
+        network_sender.clone(),
+        backoff_policy,
+        Duration::from_millis(rb_config.rpc_timeout_ms),
+    ));
+
+    let dag_driver = DagDriver::new(
+        self_peer,
+        epoch_state.clone(),
+        dag.clone(),
+        payload_client,
+        reliable_broadcast,
+        time_service,
+        storage.clone(),
+        order_rule,
+        config.node_payload_config,
+        quorum_store_enabled,
+    )
+    .await;
+    let node_receiver =
+        NodeBroadcastHandler::new(dag.clone(), vote_signer, epoch_state.clone(), storage);
+    let fetcher = DagFetcher::new(
+        epoch_state.clone(),
+        network_sender,
+        dag.clone(),
+        config.fetcher_config,
+        quorum_store_enabled,
+    );
+    let fetch_handler = FetchRequestHandler::new(dag.clone(), epoch_state.clone());
+
+    NetworkHandler::new(
+        epoch_state,
+        dag,
+        node_receiver,
+        dag_driver,
+        fetcher,
+        fetch_handler,
+        quorum_store_enabled,
+    )
+}
@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::signing_phase::CommitSignerProvider, metrics_safety_rules::MetricsSafetyRules,
+};
+use aptos_crypto::bls12381;
+use aptos_infallible::Mutex;
+use aptos_safety_rules::{Error, TSafetyRules};
+use aptos_types::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
+use std::sync::Arc;
+
+/// Signs the commit ledger info of the blocks ordered by the DAG through safety rules. The
+/// ordering is already agreed upon through the DAG, so the ordered ledger info carries no quorum
+/// signatures to check.
+pub struct DagCommitSigner {
+    safety_rules: Arc<Mutex<MetricsSafetyRules>>,
+}
+
+impl DagCommitSigner {
+    pub fn new(safety_rules: Arc<Mutex<MetricsSafetyRules>>) -> Self {
+        Self { safety_rules }
+    }
+}
+
+impl CommitSignerProvider for DagCommitSigner {
+    fn sign_commit_vote(
+        &self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        self.safety_rules
+            .lock()
+            .sign_dag_commit_vote(ledger_info.ledger_info().clone(), new_ledger_info)
+    }
+}
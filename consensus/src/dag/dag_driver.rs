@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        dag_store::Dag,
+        order_rule::OrderRule,
+        reliable_broadcast::ReliableBroadcast,
+        storage::DAGStorage,
+        types::{CertificateAckState, SignatureBuilder},
+    },
+    state_replication::PayloadClient,
+    util::time_service::TimeService,
+};
+use anyhow::bail;
+use aptos_config::config::DagPayloadConfig;
+use aptos_consensus_types::{
+    common::{Author, Payload, PayloadFilter, Round},
+    dag::{CertifiedNode, Node, NodeCertificate},
+};
+use aptos_infallible::RwLock;
+use aptos_logger::{debug, error};
+use aptos_types::epoch_state::EpochState;
+use futures::{
+    future::{AbortHandle, Abortable},
+    FutureExt,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Creates the node of this validator for every round: a new round is entered once the DAG has
+/// a quorum of certified nodes of the current round. The node is reliably broadcast to collect
+/// its certificate, then the certified node is reliably broadcast to be added to every DAG.
+pub struct DagDriver {
+    author: Author,
+    epoch_state: Arc<EpochState>,
+    dag: Arc<RwLock<Dag>>,
+    payload_client: Arc<dyn PayloadClient>,
+    reliable_broadcast: Arc<ReliableBroadcast>,
+    current_round: Round,
+    time_service: Arc<dyn TimeService>,
+    rb_abort_handle: Option<AbortHandle>,
+    storage: Arc<dyn DAGStorage>,
+    order_rule: OrderRule,
+    payload_config: DagPayloadConfig,
+    quorum_store_enabled: bool,
+}
+
+impl DagDriver {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
+        author: Author,
+        epoch_state: Arc<EpochState>,
+        dag: Arc<RwLock<Dag>>,
+        payload_client: Arc<dyn PayloadClient>,
+        reliable_broadcast: Arc<ReliableBroadcast>,
+        time_service: Arc<dyn TimeService>,
+        storage: Arc<dyn DAGStorage>,
+        order_rule: OrderRule,
+        payload_config: DagPayloadConfig,
+        quorum_store_enabled: bool,
+    ) -> Self {
+        let pending_node = storage
+            .get_pending_node()
+            .unwrap_or_else(|e| {
+                error!(error = ?e, "Failed to read the pending node");
+                None
+            })
+            .filter(|node| node.epoch() == epoch_state.epoch);
+
+        let mut driver = Self {
+            author,
+            epoch_state,
+            dag,
+            payload_client,
+            reliable_broadcast,
+            current_round: 0,
+            time_service,
+            rb_abort_handle: None,
+            storage,
+            order_rule,
+            payload_config,
+            quorum_store_enabled,
+        };
+
+        // A node that was broadcast before a restart must be re-broadcast as is, a different node
+        // for the same round would be equivocation.
+        if let Some(node) = pending_node {
+            driver.current_round = node.round();
+            driver.broadcast_node(node);
+        } else {
+            let (round, strong_links) = driver.highest_strong_links();
+            driver.enter_new_round(round + 1, strong_links).await;
+        }
+        driver
+    }
+
+    /// The highest round with a quorum of certified nodes, and their certificates.
+    fn highest_strong_links(&self) -> (Round, Vec<NodeCertificate>) {
+        let dag = self.dag.read();
+        let mut round = dag.highest_round();
+        while round >= dag.lowest_round() && round > 0 {
+            if let Some(strong_links) =
+                dag.get_strong_links_for_round(round, &self.epoch_state.verifier)
+            {
+                return (round, strong_links);
+            }
+            round -= 1;
+        }
+        (0, vec![])
+    }
+
+    pub fn current_round(&self) -> Round {
+        self.current_round
+    }
+
+    /// Adds a certified node to the DAG, checks whether it orders anchors and whether it allows
+    /// this validator to enter the next round.
+    pub async fn add_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
+        let metadata = node.metadata().clone();
+        self.dag.write().add_node(node)?;
+        if *metadata.author() == self.author {
+            if let Err(e) = self.storage.delete_pending_node() {
+                error!(error = ?e, "Failed to delete the pending node");
+            }
+        }
+        self.order_rule.process_new_node(&metadata);
+
+        if metadata.round() >= self.current_round {
+            let strong_links = self
+                .dag
+                .read()
+                .get_strong_links_for_round(metadata.round(), &self.epoch_state.verifier);
+            if let Some(strong_links) = strong_links {
+                self.enter_new_round(metadata.round() + 1, strong_links)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn enter_new_round(&mut self, new_round: Round, strong_links: Vec<NodeCertificate>) {
+        debug!("Entering DAG round {}", new_round);
+        let payload_filter = {
+            let dag = self.dag.read();
+            let payloads = dag.payloads();
+            PayloadFilter::from(&payloads)
+        };
+        let payload = match self
+            .payload_client
+            .pull_payload(
+                Duration::from_millis(0),
+                self.payload_config.max_sending_txns_per_round,
+                self.payload_config.max_sending_size_per_round_bytes,
+                payload_filter,
+                async {}.boxed(),
+                false,
+                0,
+                0.0,
+            )
+            .await
+        {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!(error = ?e, "Failed to pull payload, using an empty one");
+                Payload::empty(self.quorum_store_enabled)
+            },
+        };
+        let timestamp = self.time_service.get_current_timestamp().as_micros() as u64;
+        let new_node = Node::new(
+            self.epoch_state.epoch,
+            new_round,
+            self.author,
+            timestamp,
+            payload,
+            strong_links,
+        );
+        if let Err(e) = self.storage.save_pending_node(&new_node) {
+            error!(error = ?e, "Failed to persist the new node, not broadcasting it");
+            return;
+        }
+        self.current_round = new_round;
+        self.broadcast_node(new_node);
+    }
+
+    fn broadcast_node(&mut self, node: Node) {
+        let rb = self.reliable_broadcast.clone();
+        let signature_builder =
+            SignatureBuilder::new(node.metadata().clone(), self.epoch_state.clone());
+        let cert_ack_set = CertificateAckState::new(self.epoch_state.verifier.len());
+        let core_task = async move {
+            let certificate = rb.broadcast(node.clone(), signature_builder).await;
+            debug!("Certified node {}", certificate.metadata());
+            let certified_node = CertifiedNode::new(node, certificate.signatures().clone());
+            rb.broadcast(certified_node, cert_ack_set).await
+        };
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        tokio::spawn(Abortable::new(core_task, abort_registration));
+        if let Some(prev_handle) = self.rb_abort_handle.replace(abort_handle) {
+            prev_handle.abort();
+        }
+    }
+
+    /// Checks that the payload of a node received from the network is within the limits.
+    pub fn validate_payload(&self, payload: &Payload) -> anyhow::Result<()> {
+        let num_txns = payload.len() as u64;
+        let size = payload.size() as u64;
+        if num_txns > self.payload_config.max_receiving_txns_per_round
+            || size > self.payload_config.max_receiving_size_per_round_bytes
+        {
+            bail!(
+                "Payload of {} txns and {} bytes exceeds the limits of {} txns and {} bytes",
+                num_txns,
+                size,
+                self.payload_config.max_receiving_txns_per_round,
+                self.payload_config.max_receiving_size_per_round_bytes
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DagDriver {
+    fn drop(&mut self) {
+        if let Some(handle) = self.rb_abort_handle.take() {
+            handle.abort();
+        }
+    }
+}
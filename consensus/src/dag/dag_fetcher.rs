@@ -0,0 +1,134 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    dag_store::Dag,
+    reliable_broadcast::{DAGNetworkSender, RBMessage},
+    types::DAGMessage,
+};
+use anyhow::{anyhow, ensure};
+use aptos_config::config::DagFetcherConfig;
+use aptos_consensus_types::{
+    common::Author,
+    dag::{CertifiedNode, FetchResponse, NodeMetadata, RemoteFetchRequest},
+};
+use aptos_infallible::RwLock;
+use aptos_logger::debug;
+use aptos_types::epoch_state::EpochState;
+use std::{sync::Arc, time::Duration};
+
+/// Fetches the missing causal history of nodes from the peers that are known to have it.
+pub struct DagFetcher {
+    epoch_state: Arc<EpochState>,
+    network: Arc<dyn DAGNetworkSender>,
+    dag: Arc<RwLock<Dag>>,
+    config: DagFetcherConfig,
+    quorum_store_enabled: bool,
+}
+
+impl DagFetcher {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        network: Arc<dyn DAGNetworkSender>,
+        dag: Arc<RwLock<Dag>>,
+        config: DagFetcherConfig,
+        quorum_store_enabled: bool,
+    ) -> Self {
+        Self {
+            epoch_state,
+            network,
+            dag,
+            config,
+            quorum_store_enabled,
+        }
+    }
+
+    /// Returns the nodes in the causal history of the targets that are missing from the DAG,
+    /// lowest round first. The responders are tried in order, up to the configured retries.
+    pub async fn fetch(
+        &self,
+        targets: Vec<NodeMetadata>,
+        responders: Vec<Author>,
+    ) -> anyhow::Result<Vec<CertifiedNode>> {
+        ensure!(!responders.is_empty(), "No responder to fetch from");
+        let target_round = targets
+            .iter()
+            .map(NodeMetadata::round)
+            .max()
+            .ok_or_else(|| anyhow!("No target to fetch"))?;
+        let request = RemoteFetchRequest::new(
+            self.epoch_state.epoch,
+            targets,
+            self.dag.read().bitmask(target_round),
+        );
+        let rpc_timeout = Duration::from_millis(self.config.rpc_timeout_ms);
+        let mut last_error = anyhow!("Fetch was not attempted");
+        for attempt in 0..self.config.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_millis(self.config.retry_interval_ms)).await;
+            }
+            let responder = responders[attempt as usize % responders.len()];
+            let message = DAGMessage::FetchRequest(request.clone()).into_network_message();
+            match self
+                .network
+                .send_rpc(responder, message, rpc_timeout)
+                .await
+                .and_then(FetchResponse::from_network_message)
+                .and_then(|response| {
+                    response.verify(
+                        &request,
+                        &self.epoch_state.verifier,
+                        self.quorum_store_enabled,
+                    )?;
+                    Ok(response)
+                }) {
+                Ok(response) => {
+                    let mut nodes = response.certified_nodes();
+                    nodes.sort_by_key(|node| node.round());
+                    return Ok(nodes);
+                },
+                Err(e) => {
+                    debug!(remote_peer = responder, error = ?e, "Failed to fetch nodes");
+                    last_error = e;
+                },
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Serves the fetch requests of the peers.
+pub struct FetchRequestHandler {
+    dag: Arc<RwLock<Dag>>,
+    epoch_state: Arc<EpochState>,
+}
+
+impl FetchRequestHandler {
+    pub fn new(dag: Arc<RwLock<Dag>>, epoch_state: Arc<EpochState>) -> Self {
+        Self { dag, epoch_state }
+    }
+
+    pub fn process(&self, request: RemoteFetchRequest) -> anyhow::Result<FetchResponse> {
+        ensure!(
+            request.epoch() == self.epoch_state.epoch,
+            "Fetch request is for epoch {}",
+            request.epoch()
+        );
+        let dag = self.dag.read();
+        ensure!(
+            dag.all_exists(request.targets().iter()),
+            "Some of the fetch targets are missing"
+        );
+        let bitmask = request.exists_bitmask();
+        let certified_nodes = dag
+            .reachable(request.targets(), None, false)
+            .into_iter()
+            .filter(|node| {
+                dag.author_index(node.author())
+                    .map_or(true, |index| !bitmask.has(node.round(), index))
+            })
+            .map(|node| node.as_ref().clone())
+            .collect();
+        Ok(FetchResponse::new(self.epoch_state.epoch, certified_nodes))
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        dag_driver::DagDriver,
+        dag_fetcher::{DagFetcher, FetchRequestHandler},
+        dag_store::Dag,
+        rb_handler::NodeBroadcastHandler,
+        types::DAGMessage,
+    },
+    network::IncomingDAGRequest,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_channels::aptos_channel;
+use aptos_consensus_types::{
+    common::Author,
+    dag::{CertifiedAck, CertifiedNode, NodeMetadata},
+};
+use aptos_infallible::RwLock;
+use aptos_logger::{info, warn};
+use aptos_types::epoch_state::EpochState;
+use futures::{channel::oneshot, StreamExt};
+use std::sync::Arc;
+
+/// The main loop of the DAG, processes the rpc requests of the peers one at a time.
+pub struct NetworkHandler {
+    epoch_state: Arc<EpochState>,
+    dag: Arc<RwLock<Dag>>,
+    node_receiver: NodeBroadcastHandler,
+    dag_driver: DagDriver,
+    fetcher: DagFetcher,
+    fetch_handler: FetchRequestHandler,
+    quorum_store_enabled: bool,
+}
+
+impl NetworkHandler {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        dag: Arc<RwLock<Dag>>,
+        node_receiver: NodeBroadcastHandler,
+        dag_driver: DagDriver,
+        fetcher: DagFetcher,
+        fetch_handler: FetchRequestHandler,
+        quorum_store_enabled: bool,
+    ) -> Self {
+        Self {
+            epoch_state,
+            dag,
+            node_receiver,
+            dag_driver,
+            fetcher,
+            fetch_handler,
+            quorum_store_enabled,
+        }
+    }
+
+    pub async fn start(
+        mut self,
+        mut dag_rpc_rx: aptos_channel::Receiver<Author, IncomingDAGRequest>,
+        mut shutdown_rx: oneshot::Receiver<oneshot::Sender<()>>,
+    ) {
+        info!(epoch = self.epoch_state.epoch, "DAG network handler starts");
+        loop {
+            tokio::select! {
+                Some(request) = dag_rpc_rx.next() => {
+                    if let Err(e) = self.process_rpc(request).await {
+                        warn!(epoch = self.epoch_state.epoch, error = ?e, "Failed to process DAG rpc");
+                    }
+                },
+                ack_tx = &mut shutdown_rx => {
+                    // Drop the driver first to abort its broadcast.
+                    drop(self);
+                    if let Ok(ack_tx) = ack_tx {
+                        let _ = ack_tx.send(());
+                    }
+                    break;
+                },
+            }
+        }
+    }
+
+    async fn process_rpc(&mut self, rpc_request: IncomingDAGRequest) -> anyhow::Result<()> {
+        let sender = rpc_request.sender;
+        let dag_message: DAGMessage = rpc_request.req.try_into()?;
+        ensure!(
+            dag_message.epoch() == self.epoch_state.epoch,
+            "{} from {} is for epoch {}",
+            dag_message.name(),
+            sender,
+            dag_message.epoch()
+        );
+
+        let response: DAGMessage = match dag_message {
+            DAGMessage::NodeMsg(node) => {
+                node.verify(
+                    sender,
+                    &self.epoch_state.verifier,
+                    self.quorum_store_enabled,
+                )?;
+                self.dag_driver.validate_payload(node.payload())?;
+                self.fetch_missing_parents(node.parents_metadata(), sender)
+                    .await?;
+                self.node_receiver.process(node)?.into()
+            },
+            DAGMessage::CertifiedNodeMsg(node) => {
+                if !self.dag.read().exists(node.metadata()) {
+                    node.verify(&self.epoch_state.verifier, self.quorum_store_enabled)?;
+                    self.fetch_missing_parents(node.parents_metadata(), sender)
+                        .await?;
+                    self.add_node(node).await?;
+                }
+                CertifiedAck::new(self.epoch_state.epoch).into()
+            },
+            DAGMessage::FetchRequest(request) => {
+                request.verify(&self.epoch_state.verifier)?;
+                self.fetch_handler.process(request)?.into()
+            },
+            DAGMessage::VoteMsg(_)
+            | DAGMessage::CertifiedAckMsg(_)
+            | DAGMessage::FetchResponse(_) => {
+                bail!("Unexpected {} request from {}", dag_message.name(), sender)
+            },
+        };
+
+        let response_bytes = rpc_request
+            .protocol
+            .to_bytes(&response.into_network_message())?;
+        rpc_request
+            .response_sender
+            .send(Ok(response_bytes.into()))
+            .map_err(|_| anyhow!("Response channel closed"))
+    }
+
+    async fn add_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
+        let needed = {
+            let dag = self.dag.read();
+            node.round() >= dag.lowest_round() && !dag.exists(node.metadata())
+        };
+        if needed {
+            self.dag_driver.add_node(node).await?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the parents that are missing from the DAG (and their missing causal history) from
+    /// the sender, the parents below the lowest round of the DAG are not needed.
+    async fn fetch_missing_parents<'a>(
+        &mut self,
+        parents: impl Iterator<Item = &'a NodeMetadata>,
+        sender: Author,
+    ) -> anyhow::Result<()> {
+        let missing: Vec<_> = {
+            let dag = self.dag.read();
+            parents
+                .filter(|parent| parent.round() >= dag.lowest_round() && !dag.exists(parent))
+                .cloned()
+                .collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let nodes = self.fetcher.fetch(missing, vec![sender]).await?;
+        for node in nodes {
+            self.add_node(node).await?;
+        }
+        Ok(())
+    }
+}
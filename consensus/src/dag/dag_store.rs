@@ -0,0 +1,309 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::storage::DAGStorage;
+use anyhow::{anyhow, ensure};
+use aptos_consensus_types::{
+    common::{Author, Payload, Round},
+    dag::{CertifiedNode, DagSnapshotBitmask, NodeCertificate, NodeMetadata},
+};
+use aptos_crypto::HashValue;
+use aptos_logger::error;
+use aptos_types::{epoch_state::EpochState, validator_verifier::ValidatorVerifier};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+#[derive(Clone)]
+pub enum NodeStatus {
+    Unordered(Arc<CertifiedNode>),
+    Ordered(Arc<CertifiedNode>),
+}
+
+impl NodeStatus {
+    pub fn as_node(&self) -> &Arc<CertifiedNode> {
+        match self {
+            NodeStatus::Unordered(node) | NodeStatus::Ordered(node) => node,
+        }
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        matches!(self, NodeStatus::Ordered(_))
+    }
+
+    pub fn mark_as_ordered(&mut self) {
+        if let NodeStatus::Unordered(node) = self {
+            *self = NodeStatus::Ordered(node.clone());
+        }
+    }
+}
+
+/// The in-memory DAG of certified nodes, indexed by round and by the validator index of the
+/// author. Every node added is persisted, the rounds below the pruned round are removed from
+/// both memory and storage.
+pub struct Dag {
+    nodes_by_round: BTreeMap<Round, Vec<Option<NodeStatus>>>,
+    /// Map between peer id to vector index
+    author_to_index: HashMap<Author, usize>,
+    storage: Arc<dyn DAGStorage>,
+    /// The lowest round that is kept, nodes of this round are allowed to miss their parents
+    lowest_round: Round,
+}
+
+impl Dag {
+    pub fn new(epoch_state: Arc<EpochState>, storage: Arc<dyn DAGStorage>) -> Self {
+        let epoch = epoch_state.epoch;
+        let author_to_index = epoch_state.verifier.address_to_validator_index().clone();
+        let mut all_nodes = storage.get_certified_nodes().unwrap_or_else(|e| {
+            error!(error = ?e, "Failed to read certified nodes from storage");
+            vec![]
+        });
+        all_nodes.sort_unstable_by_key(|(_, node)| node.round());
+
+        let mut dag = Self {
+            nodes_by_round: BTreeMap::new(),
+            author_to_index,
+            storage: storage.clone(),
+            lowest_round: 1,
+        };
+        let mut expired = vec![];
+        for (digest, node) in all_nodes {
+            if node.epoch() != epoch {
+                expired.push(digest);
+                continue;
+            }
+            if dag.nodes_by_round.is_empty() {
+                dag.lowest_round = node.round();
+            }
+            if let Err(e) = dag.insert_in_memory(Arc::new(node)) {
+                error!(error = ?e, "Failed to recover certified node");
+                expired.push(digest);
+            }
+        }
+        if let Err(e) = storage.delete_certified_nodes(expired) {
+            error!(error = ?e, "Failed to delete expired certified nodes");
+        }
+        dag
+    }
+
+    pub fn lowest_round(&self) -> Round {
+        self.lowest_round
+    }
+
+    pub fn highest_round(&self) -> Round {
+        self.nodes_by_round
+            .last_key_value()
+            .map(|(round, _)| *round)
+            .unwrap_or(self.lowest_round - 1)
+    }
+
+    pub fn add_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
+        let node = Arc::new(node);
+        ensure!(
+            !self.exists(node.metadata()),
+            "Node {} already exists",
+            node.metadata()
+        );
+        self.storage.save_certified_node(&node)?;
+        self.insert_in_memory(node)
+    }
+
+    fn insert_in_memory(&mut self, node: Arc<CertifiedNode>) -> anyhow::Result<()> {
+        let round = node.round();
+        ensure!(
+            round >= self.lowest_round,
+            "Node {} is below the lowest round {}",
+            node.metadata(),
+            self.lowest_round
+        );
+        ensure!(
+            round == self.lowest_round || self.all_exists(node.parents_metadata()),
+            "Parents of node {} are missing",
+            node.metadata()
+        );
+        let author_index = *self
+            .author_to_index
+            .get(node.author())
+            .ok_or_else(|| anyhow!("Unknown author {}", node.author()))?;
+        let num_validators = self.author_to_index.len();
+        let round_ref = self
+            .nodes_by_round
+            .entry(round)
+            .or_insert_with(|| vec![None; num_validators]);
+        ensure!(
+            round_ref[author_index].is_none(),
+            "Author {} already has a node in round {}",
+            node.author(),
+            round
+        );
+        round_ref[author_index] = Some(NodeStatus::Unordered(node));
+        Ok(())
+    }
+
+    fn get_node_ref(&self, round: Round, author: &Author) -> Option<&NodeStatus> {
+        let index = self.author_to_index.get(author)?;
+        self.nodes_by_round.get(&round)?.get(*index)?.as_ref()
+    }
+
+    fn get_node_ref_mut(&mut self, round: Round, author: &Author) -> Option<&mut NodeStatus> {
+        let index = self.author_to_index.get(author)?;
+        self.nodes_by_round
+            .get_mut(&round)?
+            .get_mut(*index)?
+            .as_mut()
+    }
+
+    pub fn exists(&self, metadata: &NodeMetadata) -> bool {
+        self.get_node_ref(metadata.round(), metadata.author())
+            .map_or(false, |status| {
+                status.as_node().digest() == *metadata.digest()
+            })
+    }
+
+    pub fn all_exists<'a>(&self, mut nodes: impl Iterator<Item = &'a NodeMetadata>) -> bool {
+        nodes.all(|metadata| self.exists(metadata))
+    }
+
+    pub fn get_node(&self, metadata: &NodeMetadata) -> Option<Arc<CertifiedNode>> {
+        self.get_node_by_round_author(metadata.round(), metadata.author())
+            .filter(|node| node.digest() == *metadata.digest())
+            .cloned()
+    }
+
+    pub fn get_node_by_round_author(
+        &self,
+        round: Round,
+        author: &Author,
+    ) -> Option<&Arc<CertifiedNode>> {
+        self.get_node_ref(round, author).map(NodeStatus::as_node)
+    }
+
+    /// Returns the certificates of the nodes in the round if they carry a quorum of voting power.
+    pub fn get_strong_links_for_round(
+        &self,
+        round: Round,
+        verifier: &ValidatorVerifier,
+    ) -> Option<Vec<NodeCertificate>> {
+        let nodes: Vec<_> = self
+            .nodes_by_round
+            .get(&round)?
+            .iter()
+            .flatten()
+            .map(NodeStatus::as_node)
+            .collect();
+        verifier
+            .check_voting_power(nodes.iter().map(|node| node.author()))
+            .ok()?;
+        Some(nodes.iter().map(|node| node.certificate()).collect())
+    }
+
+    /// Returns the authors of the nodes in the next round that link to the given node.
+    pub fn get_voters(&self, metadata: &NodeMetadata) -> Vec<Author> {
+        self.nodes_by_round
+            .get(&(metadata.round() + 1))
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .flatten()
+                    .map(NodeStatus::as_node)
+                    .filter(|node| node.parents_metadata().any(|parent| parent == metadata))
+                    .map(|node| *node.author())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the nodes in the causal history of the targets (including the targets) down to
+    /// the `until` round, highest round first. With `unordered_only` the traversal stops at the
+    /// ordered nodes, whose causal history is always ordered too.
+    pub fn reachable(
+        &self,
+        targets: &[NodeMetadata],
+        until: Option<Round>,
+        unordered_only: bool,
+    ) -> Vec<Arc<CertifiedNode>> {
+        let until = until.unwrap_or(self.lowest_round).max(self.lowest_round);
+        let start_round = match targets.iter().map(NodeMetadata::round).max() {
+            Some(round) if round >= until => round,
+            _ => return vec![],
+        };
+        let mut reachable: HashSet<HashValue> =
+            targets.iter().map(|target| *target.digest()).collect();
+        let mut result = vec![];
+        for (_, nodes) in self.nodes_by_round.range(until..=start_round).rev() {
+            for status in nodes.iter().flatten() {
+                let node = status.as_node();
+                if !reachable.contains(&node.digest()) || (unordered_only && status.is_ordered()) {
+                    continue;
+                }
+                reachable.extend(node.parents_metadata().map(|parent| *parent.digest()));
+                result.push(node.clone());
+            }
+        }
+        result
+    }
+
+    pub fn mark_as_ordered(&mut self, nodes: &[Arc<CertifiedNode>]) {
+        for node in nodes {
+            if let Some(status) = self.get_node_ref_mut(node.round(), node.author()) {
+                status.mark_as_ordered();
+            }
+        }
+    }
+
+    /// Marks all the nodes up to (including) the round as ordered, used when recovering without
+    /// the last ordered anchor.
+    pub fn mark_rounds_as_ordered(&mut self, until: Round) {
+        for (_, nodes) in self.nodes_by_round.range_mut(..=until) {
+            for status in nodes.iter_mut().flatten() {
+                status.mark_as_ordered();
+            }
+        }
+    }
+
+    /// Which nodes exist from the lowest round to the target round.
+    pub fn bitmask(&self, target_round: Round) -> DagSnapshotBitmask {
+        let num_validators = self.author_to_index.len();
+        let bitmask = (self.lowest_round..=target_round)
+            .map(|round| match self.nodes_by_round.get(&round) {
+                Some(nodes) => nodes.iter().map(Option::is_some).collect(),
+                None => vec![false; num_validators],
+            })
+            .collect();
+        DagSnapshotBitmask::new(self.lowest_round, bitmask)
+    }
+
+    pub fn author_index(&self, author: &Author) -> Option<usize> {
+        self.author_to_index.get(author).copied()
+    }
+
+    /// The payloads of all the nodes in the DAG, they are excluded from new nodes.
+    pub fn payloads(&self) -> Vec<&Payload> {
+        self.nodes_by_round
+            .values()
+            .flatten()
+            .flatten()
+            .map(|status| status.as_node().payload())
+            .collect()
+    }
+
+    /// Removes the rounds below `until_round` from memory and storage.
+    pub fn prune(&mut self, until_round: Round) {
+        if until_round <= self.lowest_round {
+            return;
+        }
+        let to_keep = self.nodes_by_round.split_off(&until_round);
+        let to_prune = std::mem::replace(&mut self.nodes_by_round, to_keep);
+        self.lowest_round = until_round;
+        let digests = to_prune
+            .values()
+            .flatten()
+            .flatten()
+            .map(|status| status.as_node().digest())
+            .collect();
+        if let Err(e) = self.storage.delete_certified_nodes(digests) {
+            error!(error = ?e, "Failed to delete pruned certified nodes");
+        }
+    }
+}
@@ -2,7 +2,25 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-#[allow(dead_code)]
+mod adapter;
+mod anchor_election;
+mod bootstrap;
+mod commit_signer;
+mod dag_driver;
+mod dag_fetcher;
+mod dag_handler;
+mod dag_store;
+mod order_rule;
+mod rb_handler;
 mod reliable_broadcast;
+mod storage;
 #[cfg(test)]
 mod tests;
+mod types;
+
+pub use bootstrap::bootstrap_dag;
+pub use commit_signer::DagCommitSigner;
+pub use reliable_broadcast::DAGNetworkSender;
+pub use storage::DAGStorage;
+#[cfg(any(test, feature = "fuzzing"))]
+pub use storage::MockDAGStorage;
@@ -0,0 +1,151 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{adapter::Notifier, anchor_election::AnchorElection, dag_store::Dag};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    dag::{CertifiedNode, NodeMetadata},
+};
+use aptos_infallible::RwLock;
+use aptos_logger::{error, info};
+use aptos_types::epoch_state::EpochState;
+use std::sync::Arc;
+
+/// Bullshark style ordering: the anchor of an even round is committed once f+1 nodes of the next
+/// round link to it. Before ordering a committed anchor, the skipped anchors that are reachable
+/// from it are ordered first, then each anchor orders its not yet ordered causal history.
+pub struct OrderRule {
+    epoch_state: Arc<EpochState>,
+    lowest_unordered_anchor_round: Round,
+    dag: Arc<RwLock<Dag>>,
+    anchor_election: Arc<dyn AnchorElection>,
+    notifier: Box<dyn Notifier>,
+}
+
+impl OrderRule {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        lowest_unordered_anchor_round: Round,
+        dag: Arc<RwLock<Dag>>,
+        anchor_election: Arc<dyn AnchorElection>,
+        notifier: Box<dyn Notifier>,
+    ) -> Self {
+        assert!(
+            lowest_unordered_anchor_round % 2 == 0,
+            "Anchors are only in even rounds"
+        );
+        let mut order_rule = Self {
+            epoch_state,
+            lowest_unordered_anchor_round,
+            dag,
+            anchor_election,
+            notifier,
+        };
+        // The recovered DAG might already be able to order some anchors.
+        let highest_round = order_rule.dag.read().highest_round();
+        order_rule.check_ordering_between(lowest_unordered_anchor_round, highest_round);
+        order_rule
+    }
+
+    pub fn lowest_unordered_anchor_round(&self) -> Round {
+        self.lowest_unordered_anchor_round
+    }
+
+    /// Whether the voters carry more than f voting power, i.e. at least one honest voter.
+    fn check_minority_voting_power(&self, voters: &[Author]) -> bool {
+        let verifier = &self.epoch_state.verifier;
+        let voting_power: u128 = voters
+            .iter()
+            .filter_map(|author| verifier.get_voting_power(author))
+            .map(|power| power as u128)
+            .sum();
+        voting_power > verifier.total_voting_power() - verifier.quorum_voting_power()
+    }
+
+    /// Finds the first anchor from `start_round` (below `target_round`) with enough votes.
+    fn find_first_anchor_with_enough_votes(
+        &self,
+        mut start_round: Round,
+        target_round: Round,
+    ) -> Option<Arc<CertifiedNode>> {
+        let dag = self.dag.read();
+        while start_round < target_round {
+            let anchor_author = self.anchor_election.get_anchor(start_round);
+            if let Some(anchor_node) = dag.get_node_by_round_author(start_round, &anchor_author) {
+                if self.check_minority_voting_power(&dag.get_voters(anchor_node.metadata())) {
+                    return Some(anchor_node.clone());
+                }
+            }
+            start_round += 2;
+        }
+        None
+    }
+
+    /// Walks back from a committed anchor to the first unordered anchor reachable from it.
+    fn find_first_anchor_to_order(&self, mut anchor: Arc<CertifiedNode>) -> Arc<CertifiedNode> {
+        let dag = self.dag.read();
+        let mut round = anchor.round();
+        while round >= self.lowest_unordered_anchor_round + 2 {
+            round -= 2;
+            let anchor_author = self.anchor_election.get_anchor(round);
+            if let Some(previous_anchor) = dag
+                .reachable(&[anchor.metadata().clone()], Some(round), true)
+                .into_iter()
+                .find(|node| node.round() == round && *node.author() == anchor_author)
+            {
+                anchor = previous_anchor;
+            }
+        }
+        anchor
+    }
+
+    fn finalize_order(&mut self, anchor: Arc<CertifiedNode>) {
+        let mut ordered_nodes = {
+            let mut dag = self.dag.write();
+            let ordered_nodes = dag.reachable(&[anchor.metadata().clone()], None, true);
+            dag.mark_as_ordered(&ordered_nodes);
+            ordered_nodes
+        };
+        // Oldest first, the order within a round is by author.
+        ordered_nodes.sort_by_key(|node| (node.round(), *node.author()));
+        let failed_anchors = (self.lowest_unordered_anchor_round..anchor.round())
+            .step_by(2)
+            .map(|round| (round, self.anchor_election.get_anchor(round)))
+            .collect();
+        info!(
+            "Ordered anchor {} with {} nodes",
+            anchor.metadata(),
+            ordered_nodes.len()
+        );
+        self.lowest_unordered_anchor_round = anchor.round() + 2;
+        if let Err(e) = self
+            .notifier
+            .send_ordered_nodes(ordered_nodes, anchor, failed_anchors)
+        {
+            error!(error = ?e, "Failed to send ordered nodes");
+        }
+    }
+
+    fn check_ordering_between(&mut self, mut start_round: Round, round: Round) {
+        while start_round <= round {
+            match self.find_first_anchor_with_enough_votes(start_round, round) {
+                Some(direct_anchor) => {
+                    let ordered_anchor = self.find_first_anchor_to_order(direct_anchor);
+                    self.finalize_order(ordered_anchor);
+                    start_round = self.lowest_unordered_anchor_round;
+                },
+                None => break,
+            }
+        }
+    }
+
+    /// Called for every node added to the DAG.
+    pub fn process_new_node(&mut self, node_metadata: &NodeMetadata) {
+        let round = node_metadata.round();
+        // A node can only vote for (and thus commit) anchors of lower rounds.
+        if round <= self.lowest_unordered_anchor_round {
+            return;
+        }
+        self.check_ordering_between(self.lowest_unordered_anchor_round, round);
+    }
+}
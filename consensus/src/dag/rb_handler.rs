@@ -0,0 +1,148 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{dag_store::Dag, storage::DAGStorage},
+    metrics_safety_rules::MetricsSafetyRules,
+};
+use anyhow::{bail, ensure};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    dag::{Node, NodeId, NodeMetadata, Vote},
+};
+use aptos_crypto::bls12381;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_logger::error;
+use aptos_safety_rules::{Error, TSafetyRules};
+use aptos_types::epoch_state::EpochState;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// Signs the votes for the nodes of the other validators, implemented by safety rules.
+pub trait DagVoteSigner: Send + Sync {
+    fn sign_vote(&self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error>;
+}
+
+impl DagVoteSigner for Mutex<MetricsSafetyRules> {
+    fn sign_vote(&self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        self.lock().sign_dag_vote(metadata)
+    }
+}
+
+/// Votes for the nodes broadcast by the other validators, at most one node per author and round
+/// is voted for and the votes are persisted before they are sent.
+pub struct NodeBroadcastHandler {
+    dag: Arc<RwLock<Dag>>,
+    votes_by_round_peer: BTreeMap<Round, HashMap<Author, Vote>>,
+    vote_signer: Arc<dyn DagVoteSigner>,
+    epoch_state: Arc<EpochState>,
+    storage: Arc<dyn DAGStorage>,
+}
+
+impl NodeBroadcastHandler {
+    pub fn new(
+        dag: Arc<RwLock<Dag>>,
+        vote_signer: Arc<dyn DagVoteSigner>,
+        epoch_state: Arc<EpochState>,
+        storage: Arc<dyn DAGStorage>,
+    ) -> Self {
+        let epoch = epoch_state.epoch;
+        let votes_by_round_peer = read_votes_from_storage(&storage, epoch);
+
+        Self {
+            dag,
+            votes_by_round_peer,
+            vote_signer,
+            epoch_state,
+            storage,
+        }
+    }
+
+    /// Removes the votes below the lowest round of the DAG.
+    fn gc(&mut self) {
+        let lowest_round = self.dag.read().lowest_round();
+        self.votes_by_round_peer = self.votes_by_round_peer.split_off(&lowest_round);
+    }
+
+    fn validate(&self, node: &Node) -> anyhow::Result<()> {
+        ensure!(
+            node.epoch() == self.epoch_state.epoch,
+            "Node {} is from a different epoch",
+            node.metadata()
+        );
+        let dag = self.dag.read();
+        ensure!(
+            node.round() >= dag.lowest_round(),
+            "Node {} is below the lowest round {}",
+            node.metadata(),
+            dag.lowest_round()
+        );
+        ensure!(
+            node.round() == dag.lowest_round() || dag.all_exists(node.parents_metadata()),
+            "Parents of node {} are missing",
+            node.metadata()
+        );
+        Ok(())
+    }
+
+    pub fn process(&mut self, node: Node) -> anyhow::Result<Vote> {
+        self.validate(&node)?;
+        self.gc();
+
+        let votes_by_peer = self
+            .votes_by_round_peer
+            .entry(node.round())
+            .or_insert_with(HashMap::new);
+        match votes_by_peer.get(node.author()) {
+            None => {
+                let signature = self.vote_signer.sign_vote(node.metadata())?;
+                let vote = Vote::new_with_signature(node.metadata().clone(), signature);
+                self.storage
+                    .save_vote(&NodeId::from(node.metadata()), &vote)?;
+                votes_by_peer.insert(*node.author(), vote.clone());
+                Ok(vote)
+            },
+            Some(vote) => {
+                if vote.metadata() == node.metadata() {
+                    Ok(vote.clone())
+                } else {
+                    bail!(
+                        "Equivocating node {}, already voted for {}",
+                        node.metadata(),
+                        vote.metadata()
+                    )
+                }
+            },
+        }
+    }
+}
+
+fn read_votes_from_storage(
+    storage: &Arc<dyn DAGStorage>,
+    epoch: u64,
+) -> BTreeMap<Round, HashMap<Author, Vote>> {
+    let mut votes_by_round_peer = BTreeMap::new();
+
+    let all_votes = storage.get_votes().unwrap_or_else(|e| {
+        error!(error = ?e, "Failed to read votes from storage");
+        vec![]
+    });
+    let mut to_delete = vec![];
+    for (node_id, vote) in all_votes {
+        if node_id.epoch() == epoch {
+            votes_by_round_peer
+                .entry(node_id.round())
+                .or_insert_with(HashMap::new)
+                .insert(*node_id.author(), vote);
+        } else {
+            to_delete.push(node_id);
+        }
+    }
+    if let Err(e) = storage.delete_votes(to_delete) {
+        error!(error = ?e, "Failed to delete votes of previous epochs");
+    }
+
+    votes_by_round_peer
+}
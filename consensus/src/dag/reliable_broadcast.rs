@@ -3,23 +3,22 @@
 
 use crate::network_interface::ConsensusMsg;
 use aptos_consensus_types::common::Author;
+use aptos_logger::debug;
 use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, StreamExt};
-use std::{future::Future, sync::Arc, time::Duration};
-use tokio::sync::oneshot;
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+use tokio_retry::strategy::ExponentialBackoff;
 
-pub trait DAGMessage: Sized + Clone {
+pub trait RBMessage: Send + Sync + Clone {
     fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self>;
 
     fn into_network_message(self) -> ConsensusMsg;
 }
 
-pub trait BroadcastStatus {
-    type Message: DAGMessage;
-    type Ack: DAGMessage;
-    type Aggregated;
-
-    fn empty(validators: Vec<Author>) -> Self;
+pub trait BroadcastStatus: Send + Sync {
+    type Message: RBMessage;
+    type Ack: RBMessage;
+    type Aggregated: Send;
 
     fn add(&mut self, peer: Author, ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>>;
 }
@@ -37,62 +36,83 @@ pub trait DAGNetworkSender: Send + Sync {
 pub struct ReliableBroadcast {
     validators: Vec<Author>,
     network_sender: Arc<dyn DAGNetworkSender>,
+    backoff_policy: ExponentialBackoff,
+    rpc_timeout_duration: Duration,
 }
 
 impl ReliableBroadcast {
-    pub fn new(validators: Vec<Author>, network_sender: Arc<dyn DAGNetworkSender>) -> Self {
+    pub fn new(
+        validators: Vec<Author>,
+        network_sender: Arc<dyn DAGNetworkSender>,
+        backoff_policy: ExponentialBackoff,
+        rpc_timeout_duration: Duration,
+    ) -> Self {
         Self {
             validators,
             network_sender,
+            backoff_policy,
+            rpc_timeout_duration,
         }
     }
 
+    /// Sends the message to all the validators until the aggregating status is complete, failed
+    /// rpcs and invalid acks are retried with a per receiver backoff. The returned future is
+    /// cancelled by dropping (or aborting) it.
     pub fn broadcast<S: BroadcastStatus>(
         &self,
         message: S::Message,
-        return_tx: oneshot::Sender<S::Aggregated>,
-        mut cancel_rx: oneshot::Receiver<()>,
-    ) -> impl Future<Output = ()> {
+        mut aggregating: S,
+    ) -> impl Future<Output = S::Aggregated> {
         let receivers: Vec<_> = self.validators.clone();
         let network_message = message.into_network_message();
         let network_sender = self.network_sender.clone();
+        let rpc_timeout_duration = self.rpc_timeout_duration;
+        let mut backoff_policies: HashMap<Author, ExponentialBackoff> = self
+            .validators
+            .iter()
+            .map(|author| (*author, self.backoff_policy.clone()))
+            .collect();
         async move {
-            let mut aggregating = S::empty(receivers.clone());
-            let mut fut = FuturesUnordered::new();
-            let send_message = |receiver, message| {
+            let send_message = |receiver, message, sleep_duration: Option<Duration>| {
                 let network_sender = network_sender.clone();
                 async move {
+                    if let Some(duration) = sleep_duration {
+                        tokio::time::sleep(duration).await;
+                    }
                     (
                         receiver,
                         network_sender
-                            .send_rpc(receiver, message, Duration::from_millis(500))
+                            .send_rpc(receiver, message, rpc_timeout_duration)
                             .await,
                     )
                 }
             };
+            let mut fut = FuturesUnordered::new();
             for receiver in receivers {
-                fut.push(send_message(receiver, network_message.clone()));
+                fut.push(send_message(receiver, network_message.clone(), None));
             }
-            loop {
-                tokio::select! {
-                    Some((receiver, result)) = fut.next() => {
-                        match result {
-                            Ok(msg) =>  {
-                                if let Ok(ack) = S::Ack::from_network_message(msg) {
-                                    if let Ok(Some(aggregated)) = aggregating.add(receiver, ack) {
-                                        let _ = return_tx.send(aggregated);
-                                        return;
-                                    }
-                                }
-                            },
-                            Err(_) => fut.push(send_message(receiver, network_message.clone())),
-                        }
-                    }
-                    _ = &mut cancel_rx => {
-                        return;
-                    }
+            while let Some((receiver, result)) = fut.next().await {
+                match result
+                    .and_then(S::Ack::from_network_message)
+                    .and_then(|ack| aggregating.add(receiver, ack))
+                {
+                    Ok(Some(aggregated)) => return aggregated,
+                    Ok(None) => (),
+                    Err(e) => {
+                        debug!(remote_peer = receiver, error = ?e, "rpc failed, retrying");
+                        let backoff_strategy = backoff_policies
+                            .get_mut(&receiver)
+                            .expect("should be present");
+                        let duration = backoff_strategy.next().expect("should produce value");
+                        fut.push(send_message(
+                            receiver,
+                            network_message.clone(),
+                            Some(duration),
+                        ));
+                    },
                 }
             }
+            unreachable!("Should aggregate with all responses");
         }
     }
 }
@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::consensusdb::{CertifiedNodeSchema, ConsensusDB, DagVoteSchema, NodeSchema};
+use aptos_consensus_types::dag::{CertifiedNode, Node, NodeId, Vote};
+use aptos_crypto::HashValue;
+#[cfg(any(test, feature = "fuzzing"))]
+use aptos_infallible::Mutex;
+#[cfg(any(test, feature = "fuzzing"))]
+use std::collections::HashMap;
+
+/// The persisted state of the DAG: the node this validator is currently broadcasting, the votes
+/// it has cast (to never vote twice for the same author and round), and the certified nodes.
+pub trait DAGStorage: Send + Sync {
+    fn save_pending_node(&self, node: &Node) -> anyhow::Result<()>;
+
+    fn get_pending_node(&self) -> anyhow::Result<Option<Node>>;
+
+    fn delete_pending_node(&self) -> anyhow::Result<()>;
+
+    fn save_vote(&self, node_id: &NodeId, vote: &Vote) -> anyhow::Result<()>;
+
+    fn get_votes(&self) -> anyhow::Result<Vec<(NodeId, Vote)>>;
+
+    fn delete_votes(&self, node_ids: Vec<NodeId>) -> anyhow::Result<()>;
+
+    fn save_certified_node(&self, node: &CertifiedNode) -> anyhow::Result<()>;
+
+    fn get_certified_nodes(&self) -> anyhow::Result<Vec<(HashValue, CertifiedNode)>>;
+
+    fn delete_certified_nodes(&self, digests: Vec<HashValue>) -> anyhow::Result<()>;
+}
+
+impl DAGStorage for ConsensusDB {
+    fn save_pending_node(&self, node: &Node) -> anyhow::Result<()> {
+        Ok(self.put::<NodeSchema>(&(), node)?)
+    }
+
+    fn get_pending_node(&self) -> anyhow::Result<Option<Node>> {
+        Ok(self.get::<NodeSchema>(&())?)
+    }
+
+    fn delete_pending_node(&self) -> anyhow::Result<()> {
+        Ok(self.delete::<NodeSchema>(vec![()])?)
+    }
+
+    fn save_vote(&self, node_id: &NodeId, vote: &Vote) -> anyhow::Result<()> {
+        Ok(self.put::<DagVoteSchema>(node_id, vote)?)
+    }
+
+    fn get_votes(&self) -> anyhow::Result<Vec<(NodeId, Vote)>> {
+        Ok(self.get_all::<DagVoteSchema>()?)
+    }
+
+    fn delete_votes(&self, node_ids: Vec<NodeId>) -> anyhow::Result<()> {
+        Ok(self.delete::<DagVoteSchema>(node_ids)?)
+    }
+
+    fn save_certified_node(&self, node: &CertifiedNode) -> anyhow::Result<()> {
+        Ok(self.put::<CertifiedNodeSchema>(&node.digest(), node)?)
+    }
+
+    fn get_certified_nodes(&self) -> anyhow::Result<Vec<(HashValue, CertifiedNode)>> {
+        Ok(self.get_all::<CertifiedNodeSchema>()?)
+    }
+
+    fn delete_certified_nodes(&self, digests: Vec<HashValue>) -> anyhow::Result<()> {
+        Ok(self.delete::<CertifiedNodeSchema>(digests)?)
+    }
+}
+
+/// An in-memory DAG storage, used by the tests that don't run with a ConsensusDB.
+#[cfg(any(test, feature = "fuzzing"))]
+#[derive(Default)]
+pub struct MockDAGStorage {
+    pending_node: Mutex<Option<Node>>,
+    votes: Mutex<HashMap<NodeId, Vote>>,
+    certified_nodes: Mutex<HashMap<HashValue, CertifiedNode>>,
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl MockDAGStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl DAGStorage for MockDAGStorage {
+    fn save_pending_node(&self, node: &Node) -> anyhow::Result<()> {
+        self.pending_node.lock().replace(node.clone());
+        Ok(())
+    }
+
+    fn get_pending_node(&self) -> anyhow::Result<Option<Node>> {
+        Ok(self.pending_node.lock().clone())
+    }
+
+    fn delete_pending_node(&self) -> anyhow::Result<()> {
+        self.pending_node.lock().take();
+        Ok(())
+    }
+
+    fn save_vote(&self, node_id: &NodeId, vote: &Vote) -> anyhow::Result<()> {
+        self.votes.lock().insert(node_id.clone(), vote.clone());
+        Ok(())
+    }
+
+    fn get_votes(&self) -> anyhow::Result<Vec<(NodeId, Vote)>> {
+        Ok(self
+            .votes
+            .lock()
+            .iter()
+            .map(|(node_id, vote)| (node_id.clone(), vote.clone()))
+            .collect())
+    }
+
+    fn delete_votes(&self, node_ids: Vec<NodeId>) -> anyhow::Result<()> {
+        let mut votes = self.votes.lock();
+        for node_id in node_ids {
+            votes.remove(&node_id);
+        }
+        Ok(())
+    }
+
+    fn save_certified_node(&self, node: &CertifiedNode) -> anyhow::Result<()> {
+        self.certified_nodes
+            .lock()
+            .insert(node.digest(), node.clone());
+        Ok(())
+    }
+
+    fn get_certified_nodes(&self) -> anyhow::Result<Vec<(HashValue, CertifiedNode)>> {
+        Ok(self
+            .certified_nodes
+            .lock()
+            .iter()
+            .map(|(digest, node)| (*digest, node.clone()))
+            .collect())
+    }
+
+    fn delete_certified_nodes(&self, digests: Vec<HashValue>) -> anyhow::Result<()> {
+        let mut certified_nodes = self.certified_nodes.lock();
+        for digest in digests {
+            certified_nodes.remove(&digest);
+        }
+        Ok(())
+    }
+}
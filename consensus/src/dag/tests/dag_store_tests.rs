@@ -0,0 +1,147 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    dag_store::Dag,
+    storage::{DAGStorage, MockDAGStorage},
+    tests::helpers::new_certified_node,
+};
+use aptos_consensus_types::common::Author;
+use aptos_types::{epoch_state::EpochState, validator_verifier::random_validator_verifier};
+use std::sync::Arc;
+
+fn setup(num_validators: usize) -> (Vec<Author>, Arc<EpochState>, Arc<MockDAGStorage>) {
+    let (signers, validator_verifier) = random_validator_verifier(num_validators, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let authors = signers.iter().map(|signer| signer.author()).collect();
+    (authors, epoch_state, Arc::new(MockDAGStorage::new()))
+}
+
+#[test]
+fn test_dag_insertion_succeed() {
+    let (authors, epoch_state, storage) = setup(4);
+    let mut dag = Dag::new(epoch_state.clone(), storage);
+
+    // Round 1 - nodes 0, 1, 2 links to vec![]
+    for author in &authors[0..3] {
+        let node = new_certified_node(1, *author, vec![]);
+        assert!(dag.add_node(node).is_ok());
+    }
+    let parents = dag
+        .get_strong_links_for_round(1, &epoch_state.verifier)
+        .unwrap();
+    assert_eq!(parents.len(), 3);
+
+    // Round 2 nodes 0, 1, 2 links to 0, 1, 2
+    for author in &authors[0..3] {
+        let node = new_certified_node(2, *author, parents.clone());
+        assert!(dag.add_node(node).is_ok());
+    }
+
+    // Round 3 nodes 1, 2 links to 0, 1, 2
+    let parents = dag
+        .get_strong_links_for_round(2, &epoch_state.verifier)
+        .unwrap();
+    for author in &authors[1..3] {
+        let node = new_certified_node(3, *author, parents.clone());
+        assert!(dag.add_node(node).is_ok());
+    }
+    assert_eq!(dag.highest_round(), 3);
+    // Not enough voting power for round 3
+    assert!(dag
+        .get_strong_links_for_round(3, &epoch_state.verifier)
+        .is_none());
+}
+
+#[test]
+fn test_dag_insertion_failure() {
+    let (authors, epoch_state, storage) = setup(4);
+    let mut dag = Dag::new(epoch_state.clone(), storage);
+
+    // Round 1 - nodes 0, 1, 2 links to vec![]
+    for author in &authors[0..3] {
+        let node = new_certified_node(1, *author, vec![]);
+        assert!(dag.add_node(node).is_ok());
+    }
+    // Duplicate node of author 0 in round 1
+    let node = new_certified_node(1, authors[0], vec![]);
+    assert!(dag.add_node(node.clone()).is_err());
+
+    // Round 2 node linking to a missing parent
+    let missing_node = new_certified_node(1, authors[3], vec![]);
+    let mut parents = dag
+        .get_strong_links_for_round(1, &epoch_state.verifier)
+        .unwrap();
+    parents.push(missing_node.certificate());
+    let node = new_certified_node(2, authors[0], parents.clone());
+    assert!(dag.add_node(node).is_err());
+
+    // Once the parent is added, the node can be added
+    assert!(dag.add_node(missing_node).is_ok());
+    let node = new_certified_node(2, authors[0], parents);
+    assert!(dag.add_node(node).is_ok());
+}
+
+#[test]
+fn test_dag_recover_from_storage() {
+    let (authors, epoch_state, storage) = setup(4);
+    let mut dag = Dag::new(epoch_state.clone(), storage.clone());
+
+    let mut metadatas = vec![];
+    for round in 1..10 {
+        let parents = dag
+            .get_strong_links_for_round(round - 1, &epoch_state.verifier)
+            .unwrap_or_default();
+        for author in &authors[0..3] {
+            let node = new_certified_node(round, *author, parents.clone());
+            metadatas.push(node.metadata().clone());
+            assert!(dag.add_node(node).is_ok());
+        }
+    }
+
+    let new_dag = Dag::new(epoch_state.clone(), storage.clone());
+    for metadata in &metadatas {
+        assert!(new_dag.exists(metadata));
+    }
+
+    // The nodes of a previous epoch are deleted
+    let new_epoch_state = Arc::new(EpochState {
+        epoch: 2,
+        verifier: epoch_state.verifier.clone(),
+    });
+    let _new_epoch_dag = Dag::new(new_epoch_state, storage.clone());
+    assert!(storage.get_certified_nodes().unwrap().is_empty());
+}
+
+#[test]
+fn test_dag_prune() {
+    let (authors, epoch_state, storage) = setup(4);
+    let mut dag = Dag::new(epoch_state.clone(), storage.clone());
+
+    for round in 1..10 {
+        let parents = dag
+            .get_strong_links_for_round(round - 1, &epoch_state.verifier)
+            .unwrap_or_default();
+        for author in &authors {
+            let node = new_certified_node(round, *author, parents.clone());
+            assert!(dag.add_node(node).is_ok());
+        }
+    }
+
+    dag.prune(5);
+    assert_eq!(dag.lowest_round(), 5);
+    assert!(dag.get_node_by_round_author(4, &authors[0]).is_none());
+    assert!(dag.get_node_by_round_author(5, &authors[0]).is_some());
+    assert_eq!(
+        storage.get_certified_nodes().unwrap().len(),
+        5 * authors.len()
+    );
+
+    // The causal history stops at the lowest round
+    let anchor = dag.get_node_by_round_author(9, &authors[0]).unwrap();
+    let reachable = dag.reachable(&[anchor.metadata().clone()], None, false);
+    assert_eq!(reachable.len(), 1 + 4 * authors.len());
+}
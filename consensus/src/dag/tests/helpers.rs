@@ -0,0 +1,28 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::rb_handler::DagVoteSigner;
+use aptos_consensus_types::{
+    common::{Author, Payload, Round},
+    dag::{CertifiedNode, Node, NodeCertificate, NodeMetadata},
+};
+use aptos_crypto::bls12381;
+use aptos_safety_rules::Error;
+use aptos_types::{aggregate_signature::AggregateSignature, validator_signer::ValidatorSigner};
+
+/// Signs the votes with the key directly, without the checks of safety rules.
+impl DagVoteSigner for ValidatorSigner {
+    fn sign_vote(&self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        self.sign(metadata)
+            .map_err(|err| Error::SerializationError(err.to_string()))
+    }
+}
+
+pub(crate) fn new_certified_node(
+    round: Round,
+    author: Author,
+    parents: Vec<NodeCertificate>,
+) -> CertifiedNode {
+    let node = Node::new(1, round, author, 0, Payload::empty(false), parents);
+    CertifiedNode::new(node, AggregateSignature::empty())
+}
@@ -1,4 +1,8 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod dag_store_tests;
+mod helpers;
+mod order_rule_tests;
+mod rb_handler_tests;
 mod reliable_broadcast_tests;
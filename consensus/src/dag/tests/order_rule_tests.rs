@@ -0,0 +1,205 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    adapter::Notifier,
+    anchor_election::{AnchorElection, RoundRobinAnchorElection},
+    dag_store::Dag,
+    order_rule::OrderRule,
+    storage::MockDAGStorage,
+    tests::helpers::new_certified_node,
+};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    dag::{CertifiedNode, NodeMetadata},
+};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_types::{epoch_state::EpochState, validator_verifier::random_validator_verifier};
+use std::sync::Arc;
+
+type OrderedAnchor = (NodeMetadata, Vec<NodeMetadata>, Vec<(Round, Author)>);
+
+struct TestNotifier {
+    ordered: Arc<Mutex<Vec<OrderedAnchor>>>,
+}
+
+impl Notifier for TestNotifier {
+    fn send_ordered_nodes(
+        &mut self,
+        ordered_nodes: Vec<Arc<CertifiedNode>>,
+        anchor: Arc<CertifiedNode>,
+        failed_anchors: Vec<(Round, Author)>,
+    ) -> anyhow::Result<()> {
+        self.ordered.lock().push((
+            anchor.metadata().clone(),
+            ordered_nodes
+                .iter()
+                .map(|node| node.metadata().clone())
+                .collect(),
+            failed_anchors,
+        ));
+        Ok(())
+    }
+}
+
+struct TestSetup {
+    authors: Vec<Author>,
+    epoch_state: Arc<EpochState>,
+    dag: Arc<RwLock<Dag>>,
+    anchor_election: Arc<RoundRobinAnchorElection>,
+    order_rule: OrderRule,
+    ordered: Arc<Mutex<Vec<OrderedAnchor>>>,
+}
+
+fn setup() -> TestSetup {
+    let (signers, validator_verifier) = random_validator_verifier(4, None, false);
+    let authors: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let dag = Arc::new(RwLock::new(Dag::new(
+        epoch_state.clone(),
+        Arc::new(MockDAGStorage::new()),
+    )));
+    let anchor_election = Arc::new(RoundRobinAnchorElection::new(
+        epoch_state.verifier.get_ordered_account_addresses(),
+    ));
+    let ordered = Arc::new(Mutex::new(vec![]));
+    let order_rule = OrderRule::new(
+        epoch_state.clone(),
+        2,
+        dag.clone(),
+        anchor_election.clone(),
+        Box::new(TestNotifier {
+            ordered: ordered.clone(),
+        }),
+    );
+    TestSetup {
+        authors,
+        epoch_state,
+        dag,
+        anchor_election,
+        order_rule,
+        ordered,
+    }
+}
+
+impl TestSetup {
+    /// Adds the nodes of the authors in the round, linking to all the nodes of the previous round.
+    fn add_round(&mut self, round: Round, authors: &[Author]) {
+        let parents = self
+            .dag
+            .read()
+            .get_strong_links_for_round(round - 1, &self.epoch_state.verifier)
+            .unwrap_or_default();
+        for author in authors {
+            self.add_node(new_certified_node(round, *author, parents.clone()));
+        }
+    }
+
+    fn add_node(&mut self, node: CertifiedNode) {
+        let metadata = node.metadata().clone();
+        self.dag.write().add_node(node).unwrap();
+        self.order_rule.process_new_node(&metadata);
+    }
+}
+
+#[test]
+fn test_order_rule_commits_anchor_with_minority_votes() {
+    let mut setup = setup();
+    let authors = setup.authors.clone();
+
+    setup.add_round(1, &authors);
+    setup.add_round(2, &authors);
+    // A single vote is not enough to commit the anchor of round 2
+    setup.add_round(3, &authors[0..1]);
+    assert!(setup.ordered.lock().is_empty());
+    setup.add_round(3, &authors[1..2]);
+
+    let ordered = setup.ordered.lock().clone();
+    assert_eq!(ordered.len(), 1);
+    let (anchor, ordered_nodes, failed_anchors) = &ordered[0];
+    assert_eq!(anchor.round(), 2);
+    assert_eq!(*anchor.author(), setup.anchor_election.get_anchor(2));
+    // The anchor and its causal history, oldest first
+    assert_eq!(ordered_nodes.len(), authors.len() + 1);
+    assert_eq!(ordered_nodes.last(), Some(anchor));
+    assert!(ordered_nodes[..authors.len()]
+        .iter()
+        .all(|node| node.round() == 1));
+    assert!(failed_anchors.is_empty());
+    assert_eq!(setup.order_rule.lowest_unordered_anchor_round(), 4);
+}
+
+#[test]
+fn test_order_rule_orders_skipped_anchor() {
+    let mut setup = setup();
+    let authors = setup.authors.clone();
+    let anchor_2 = setup.anchor_election.get_anchor(2);
+    let anchor_4 = setup.anchor_election.get_anchor(4);
+    let voter = *authors.iter().find(|author| **author != anchor_2).unwrap();
+
+    setup.add_round(1, &authors);
+    setup.add_round(2, &authors);
+    // Only one node of round 3 links to the anchor of round 2
+    let round_2_links = setup
+        .dag
+        .read()
+        .get_strong_links_for_round(2, &setup.epoch_state.verifier)
+        .unwrap();
+    let without_anchor: Vec<_> = round_2_links
+        .iter()
+        .filter(|certificate| *certificate.metadata().author() != anchor_2)
+        .cloned()
+        .collect();
+    for author in &authors {
+        let parents = if *author == voter {
+            round_2_links.clone()
+        } else {
+            without_anchor.clone()
+        };
+        setup.add_node(new_certified_node(3, *author, parents));
+    }
+    assert!(setup.ordered.lock().is_empty());
+
+    // The anchor of round 4 is committed, the anchor of round 2 is reachable through the voter
+    setup.add_round(4, &authors);
+    setup.add_round(5, &authors);
+
+    let ordered = setup.ordered.lock().clone();
+    assert_eq!(ordered.len(), 2);
+    assert_eq!(ordered[0].0.round(), 2);
+    assert_eq!(*ordered[0].0.author(), anchor_2);
+    assert_eq!(ordered[1].0.round(), 4);
+    assert_eq!(*ordered[1].0.author(), anchor_4);
+    assert!(ordered[1].2.is_empty());
+    // Every node up to the anchor of round 4 is ordered exactly once
+    let num_ordered: usize = ordered.iter().map(|(_, nodes, _)| nodes.len()).sum();
+    assert_eq!(num_ordered, 3 * authors.len() + 1);
+}
+
+#[test]
+fn test_order_rule_skips_missing_anchor() {
+    let mut setup = setup();
+    let authors = setup.authors.clone();
+    let anchor_2 = setup.anchor_election.get_anchor(2);
+    let others: Vec<_> = authors
+        .iter()
+        .filter(|author| **author != anchor_2)
+        .cloned()
+        .collect();
+
+    setup.add_round(1, &authors);
+    setup.add_round(2, &others);
+    setup.add_round(3, &authors);
+    setup.add_round(4, &authors);
+    setup.add_round(5, &authors);
+
+    let ordered = setup.ordered.lock().clone();
+    assert_eq!(ordered.len(), 1);
+    let (anchor, ordered_nodes, failed_anchors) = &ordered[0];
+    assert_eq!(anchor.round(), 4);
+    assert_eq!(failed_anchors, &vec![(2, anchor_2)]);
+    assert_eq!(ordered_nodes.len(), 3 * authors.len() - 1 + 1);
+}
@@ -0,0 +1,95 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    dag_store::Dag,
+    rb_handler::NodeBroadcastHandler,
+    storage::{DAGStorage, MockDAGStorage},
+    tests::helpers::new_certified_node,
+};
+use aptos_consensus_types::{
+    common::Payload,
+    dag::{Node, NodeId},
+};
+use aptos_infallible::RwLock;
+use aptos_types::{epoch_state::EpochState, validator_verifier::random_validator_verifier};
+use std::sync::Arc;
+
+#[test]
+fn test_node_broadcast_receiver_succeed() {
+    let (signers, validator_verifier) = random_validator_verifier(4, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let storage = Arc::new(MockDAGStorage::new());
+    let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+
+    let mut rb_receiver = NodeBroadcastHandler::new(
+        dag.clone(),
+        Arc::new(signers[3].clone()),
+        epoch_state.clone(),
+        storage.clone(),
+    );
+
+    let node = Node::new(1, 1, signers[0].author(), 0, Payload::empty(false), vec![]);
+    let vote = rb_receiver.process(node.clone()).unwrap();
+    assert!(vote
+        .verify(signers[3].author(), &epoch_state.verifier)
+        .is_ok());
+    assert_eq!(vote.metadata(), node.metadata());
+    assert_eq!(storage.get_votes().unwrap().len(), 1);
+
+    // The same node gets the same vote
+    assert_eq!(rb_receiver.process(node.clone()).unwrap(), vote);
+
+    // An equivocating node of the same author and round is rejected
+    let equivocating_node = Node::new(1, 1, signers[0].author(), 1, Payload::empty(false), vec![]);
+    assert!(rb_receiver.process(equivocating_node).is_err());
+
+    // The votes are recovered from storage
+    let mut new_rb_receiver = NodeBroadcastHandler::new(
+        dag,
+        Arc::new(signers[3].clone()),
+        epoch_state,
+        storage.clone(),
+    );
+    assert_eq!(new_rb_receiver.process(node.clone()).unwrap(), vote);
+    assert_eq!(
+        storage.get_votes().unwrap()[0].0,
+        NodeId::from(node.metadata())
+    );
+}
+
+#[test]
+fn test_node_broadcast_receiver_failure() {
+    let (signers, validator_verifier) = random_validator_verifier(4, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let storage = Arc::new(MockDAGStorage::new());
+    let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+
+    let mut rb_receiver = NodeBroadcastHandler::new(
+        dag.clone(),
+        Arc::new(signers[3].clone()),
+        epoch_state,
+        storage,
+    );
+
+    // Node of a different epoch
+    let node = Node::new(2, 1, signers[0].author(), 0, Payload::empty(false), vec![]);
+    assert!(rb_receiver.process(node).is_err());
+
+    // Node with missing parents
+    let parent = new_certified_node(1, signers[1].author(), vec![]);
+    let node = Node::new(1, 2, signers[0].author(), 0, Payload::empty(false), vec![
+        parent.certificate(),
+    ]);
+    assert!(rb_receiver.process(node.clone()).is_err());
+
+    // Once the parent is in the DAG the node is voted for
+    dag.write().add_node(parent).unwrap();
+    assert!(rb_receiver.process(node).is_ok());
+}
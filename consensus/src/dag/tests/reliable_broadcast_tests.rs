@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    dag::reliable_broadcast::{BroadcastStatus, DAGMessage, DAGNetworkSender, ReliableBroadcast},
+    dag::reliable_broadcast::{BroadcastStatus, DAGNetworkSender, RBMessage, ReliableBroadcast},
     network_interface::ConsensusMsg,
 };
 use anyhow::bail;
@@ -10,18 +10,19 @@ use aptos_consensus_types::common::Author;
 use aptos_infallible::Mutex;
 use aptos_types::validator_verifier::random_validator_verifier;
 use async_trait::async_trait;
+use futures::future::abortable;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
     sync::Arc,
     time::Duration,
 };
-use tokio::sync::oneshot;
+use tokio_retry::strategy::ExponentialBackoff;
 
 #[derive(Serialize, Deserialize, Clone)]
 struct TestMessage(Vec<u8>);
 
-impl DAGMessage for TestMessage {
+impl RBMessage for TestMessage {
     fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
         match msg {
             ConsensusMsg::DAGTestMessage(payload) => Ok(Self(payload)),
@@ -37,7 +38,7 @@ impl DAGMessage for TestMessage {
 #[derive(Serialize, Deserialize, Clone)]
 struct TestAck;
 
-impl DAGMessage for TestAck {
+impl RBMessage for TestAck {
     fn from_network_message(_: ConsensusMsg) -> anyhow::Result<Self> {
         Ok(TestAck)
     }
@@ -52,17 +53,19 @@ struct TestBroadcastStatus {
     received: HashSet<Author>,
 }
 
-impl BroadcastStatus for TestBroadcastStatus {
-    type Ack = TestAck;
-    type Aggregated = HashSet<Author>;
-    type Message = TestMessage;
-
-    fn empty(receivers: Vec<Author>) -> Self {
+impl TestBroadcastStatus {
+    fn new(receivers: &[Author]) -> Self {
         Self {
             threshold: receivers.len(),
             received: HashSet::new(),
         }
     }
+}
+
+impl BroadcastStatus for TestBroadcastStatus {
+    type Ack = TestAck;
+    type Aggregated = HashSet<Author>;
+    type Message = TestMessage;
 
     fn add(&mut self, peer: Author, _ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
         self.received.insert(peer);
@@ -114,18 +117,31 @@ impl DAGNetworkSender for TestDAGSender {
     }
 }
 
+fn create_reliable_broadcast(
+    validators: Vec<Author>,
+    sender: Arc<TestDAGSender>,
+) -> ReliableBroadcast {
+    ReliableBroadcast::new(
+        validators,
+        sender,
+        ExponentialBackoff::from_millis(10),
+        Duration::from_millis(500),
+    )
+}
+
 #[tokio::test]
 async fn test_reliable_broadcast() {
     let (_, validator_verifier) = random_validator_verifier(5, None, false);
     let validators = validator_verifier.get_ordered_account_addresses();
     let failures = HashMap::from([(validators[0], 1), (validators[2], 3)]);
     let sender = Arc::new(TestDAGSender::new(failures));
-    let rb = ReliableBroadcast::new(validators.clone(), sender);
+    let rb = create_reliable_broadcast(validators.clone(), sender);
     let message = TestMessage(vec![1, 2, 3]);
-    let (tx, rx) = oneshot::channel();
-    let (_cancel_tx, cancel_rx) = oneshot::channel();
-    tokio::spawn(rb.broadcast::<TestBroadcastStatus>(message, tx, cancel_rx));
-    assert_eq!(rx.await.unwrap(), validators.into_iter().collect());
+    let aggregating = TestBroadcastStatus::new(&validators);
+    let aggregated = tokio::spawn(rb.broadcast(message, aggregating))
+        .await
+        .unwrap();
+    assert_eq!(aggregated, validators.into_iter().collect());
 }
 
 #[tokio::test]
@@ -134,20 +150,11 @@ async fn test_reliable_broadcast_cancel() {
     let validators = validator_verifier.get_ordered_account_addresses();
     let failures = HashMap::from([(validators[0], 1), (validators[2], 3)]);
     let sender = Arc::new(TestDAGSender::new(failures));
-    let rb = ReliableBroadcast::new(validators.clone(), sender);
+    let rb = create_reliable_broadcast(validators.clone(), sender);
     let message = TestMessage(vec![1, 2, 3]);
 
-    // explicit send cancel
-    let (tx, rx) = oneshot::channel();
-    let (cancel_tx, cancel_rx) = oneshot::channel();
-    cancel_tx.send(()).unwrap();
-    tokio::spawn(rb.broadcast::<TestBroadcastStatus>(message.clone(), tx, cancel_rx));
-    assert!(rx.await.is_err());
-
-    // implicit drop cancel
-    let (tx, rx) = oneshot::channel();
-    let (cancel_tx, cancel_rx) = oneshot::channel();
-    drop(cancel_tx);
-    tokio::spawn(rb.broadcast::<TestBroadcastStatus>(message, tx, cancel_rx));
-    assert!(rx.await.is_err());
+    let (fut, abort_handle) =
+        abortable(rb.broadcast(message, TestBroadcastStatus::new(&validators)));
+    abort_handle.abort();
+    assert!(tokio::spawn(fut).await.unwrap().is_err());
 }
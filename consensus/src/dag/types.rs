@@ -0,0 +1,210 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::reliable_broadcast::{BroadcastStatus, RBMessage},
+    network_interface::ConsensusMsg,
+};
+use anyhow::{bail, ensure};
+use aptos_consensus_types::{
+    common::Author,
+    dag::{
+        CertifiedAck, CertifiedNode, DAGNetworkMessage, FetchResponse, Node, NodeCertificate,
+        NodeMetadata, RemoteFetchRequest, Vote,
+    },
+};
+use aptos_types::{aggregate_signature::PartialSignatures, epoch_state::EpochState};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+
+/// The messages of the DAG protocol, all of them are sent as rpc requests or responses.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum DAGMessage {
+    NodeMsg(Node),
+    VoteMsg(Vote),
+    CertifiedNodeMsg(CertifiedNode),
+    CertifiedAckMsg(CertifiedAck),
+    FetchRequest(RemoteFetchRequest),
+    FetchResponse(FetchResponse),
+}
+
+impl DAGMessage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DAGMessage::NodeMsg(_) => "NodeMsg",
+            DAGMessage::VoteMsg(_) => "VoteMsg",
+            DAGMessage::CertifiedNodeMsg(_) => "CertifiedNodeMsg",
+            DAGMessage::CertifiedAckMsg(_) => "CertifiedAckMsg",
+            DAGMessage::FetchRequest(_) => "FetchRequest",
+            DAGMessage::FetchResponse(_) => "FetchResponse",
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            DAGMessage::NodeMsg(node) => node.epoch(),
+            DAGMessage::VoteMsg(vote) => vote.metadata().epoch(),
+            DAGMessage::CertifiedNodeMsg(node) => node.epoch(),
+            DAGMessage::CertifiedAckMsg(ack) => ack.epoch(),
+            DAGMessage::FetchRequest(request) => request.epoch(),
+            DAGMessage::FetchResponse(response) => response.epoch(),
+        }
+    }
+
+    pub fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGMessage(DAGNetworkMessage::new(
+            self.epoch(),
+            bcs::to_bytes(&self).expect("Unable to serialize DAG message"),
+        ))
+    }
+}
+
+impl TryFrom<DAGNetworkMessage> for DAGMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: DAGNetworkMessage) -> Result<Self, Self::Error> {
+        let dag_message: DAGMessage = bcs::from_bytes(msg.data())?;
+        ensure!(
+            dag_message.epoch() == msg.epoch(),
+            "Epoch {} of {} mismatch network message epoch {}",
+            dag_message.epoch(),
+            dag_message.name(),
+            msg.epoch()
+        );
+        Ok(dag_message)
+    }
+}
+
+impl TryFrom<ConsensusMsg> for DAGMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: ConsensusMsg) -> Result<Self, Self::Error> {
+        match msg {
+            ConsensusMsg::DAGMessage(msg) => msg.try_into(),
+            _ => bail!("Unexpected {} instead of a DAG message", msg.name()),
+        }
+    }
+}
+
+macro_rules! impl_dag_message {
+    ($type:ident, $variant:ident) => {
+        impl From<$type> for DAGMessage {
+            fn from(msg: $type) -> Self {
+                DAGMessage::$variant(msg)
+            }
+        }
+
+        impl TryFrom<DAGMessage> for $type {
+            type Error = anyhow::Error;
+
+            fn try_from(msg: DAGMessage) -> Result<Self, Self::Error> {
+                match msg {
+                    DAGMessage::$variant(msg) => Ok(msg),
+                    _ => bail!(
+                        "Unexpected {} instead of {}",
+                        msg.name(),
+                        stringify!($variant)
+                    ),
+                }
+            }
+        }
+
+        impl RBMessage for $type {
+            fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+                DAGMessage::try_from(msg)?.try_into()
+            }
+
+            fn into_network_message(self) -> ConsensusMsg {
+                DAGMessage::from(self).into_network_message()
+            }
+        }
+    };
+}
+
+impl_dag_message!(Node, NodeMsg);
+impl_dag_message!(Vote, VoteMsg);
+impl_dag_message!(CertifiedNode, CertifiedNodeMsg);
+impl_dag_message!(CertifiedAck, CertifiedAckMsg);
+impl_dag_message!(RemoteFetchRequest, FetchRequest);
+impl_dag_message!(FetchResponse, FetchResponse);
+
+/// Aggregates the votes on a node into its certificate.
+pub struct SignatureBuilder {
+    metadata: NodeMetadata,
+    partial_signatures: PartialSignatures,
+    epoch_state: Arc<EpochState>,
+}
+
+impl SignatureBuilder {
+    pub fn new(metadata: NodeMetadata, epoch_state: Arc<EpochState>) -> Self {
+        Self {
+            metadata,
+            partial_signatures: PartialSignatures::empty(),
+            epoch_state,
+        }
+    }
+}
+
+impl BroadcastStatus for SignatureBuilder {
+    type Ack = Vote;
+    type Aggregated = NodeCertificate;
+    type Message = Node;
+
+    fn add(&mut self, peer: Author, ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
+        ensure!(
+            self.metadata == *ack.metadata(),
+            "Vote from {} is for a different node {}",
+            peer,
+            ack.metadata()
+        );
+        ack.verify(peer, &self.epoch_state.verifier)?;
+        self.partial_signatures
+            .add_signature(peer, ack.signature().clone());
+        if self
+            .epoch_state
+            .verifier
+            .check_voting_power(self.partial_signatures.signatures().keys())
+            .is_err()
+        {
+            return Ok(None);
+        }
+        let aggregated_signature = self
+            .epoch_state
+            .verifier
+            .aggregate_signatures(&self.partial_signatures)?;
+        Ok(Some(NodeCertificate::new(
+            self.metadata.clone(),
+            aggregated_signature,
+        )))
+    }
+}
+
+/// Collects the acknowledgements of a certified node from all the validators.
+pub struct CertificateAckState {
+    num_validators: usize,
+    received: HashSet<Author>,
+}
+
+impl CertificateAckState {
+    pub fn new(num_validators: usize) -> Self {
+        Self {
+            num_validators,
+            received: HashSet::new(),
+        }
+    }
+}
+
+impl BroadcastStatus for CertificateAckState {
+    type Ack = CertifiedAck;
+    type Aggregated = ();
+    type Message = CertifiedNode;
+
+    fn add(&mut self, peer: Author, _ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
+        self.received.insert(peer);
+        if self.received.len() == self.num_validators {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inspection and repair of the databases of consensus, for validators that are stuck after a
+//! crash. The ConsensusDB, the QuorumstoreDB and the secure storage are only read, except by the
+//! prune to the committed block, which requires the node to be stopped.
+
+use crate::{
+    consensusdb::{BlockSchema, ConsensusDB, QCSchema},
+    quorum_store::{
+        quorum_store_db::{QuorumStoreDB, QuorumStoreStorage},
+        types::StorageMode,
+    },
+};
+use anyhow::{bail, Result};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Author, Round},
+    proof_of_store::BatchId,
+    quorum_cert::QuorumCert,
+    safety_data::SafetyData,
+    timeout_2chain::TwoChainTimeoutCertificate,
+    vote::Vote,
+};
+use aptos_crypto::HashValue;
+use aptos_global_constants::SAFETY_DATA;
+use aptos_secure_storage::{KVStorage, OnDiskStorage};
+use aptos_types::{block_info::BlockInfo, ledger_info::LedgerInfo};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// The content of the ConsensusDB
+#[derive(Debug, Serialize)]
+pub struct ConsensusDbReport {
+    /// The last vote sent by the validator
+    pub last_vote: Option<VoteSummary>,
+    /// The highest 2-chain timeout certificate known by the validator
+    pub highest_2chain_timeout_certificate: Option<TimeoutCertificateSummary>,
+    /// The trees of blocks, rooted at the blocks whose parent isn't in the DB
+    pub block_tree: Vec<BlockTreeNode>,
+    /// The quorum certs whose certified block isn't in the DB
+    pub orphaned_quorum_certs: Vec<QuorumCertSummary>,
+}
+
+/// A vote of the validator
+#[derive(Debug, Serialize)]
+pub struct VoteSummary {
+    /// Epoch of the vote
+    pub epoch: u64,
+    /// Round of the vote
+    pub round: Round,
+    /// Id of the voted block
+    pub block_id: HashValue,
+    /// Whether the vote also signs a timeout
+    pub is_timeout: bool,
+}
+
+/// A 2-chain timeout certificate
+#[derive(Debug, Serialize)]
+pub struct TimeoutCertificateSummary {
+    /// Epoch of the timeout
+    pub epoch: u64,
+    /// Round of the timeout
+    pub round: Round,
+    /// Highest round of the quorum certs of the signers
+    pub highest_hqc_round: Round,
+}
+
+/// A block of the ConsensusDB, and the blocks that extend it
+#[derive(Debug, Serialize)]
+pub struct BlockTreeNode {
+    /// Id of the block
+    pub id: HashValue,
+    /// Epoch of the block
+    pub epoch: u64,
+    /// Round of the block
+    pub round: Round,
+    /// Proposer of the block, if it isn't a NIL or genesis block
+    pub author: Option<Author>,
+    /// Timestamp of the block
+    pub timestamp_usecs: u64,
+    /// Number of transactions, or of proofs of store, of the payload
+    pub payload_len: usize,
+    /// Id of the parent block
+    pub parent_id: HashValue,
+    /// The quorum cert certifying the block, if it was persisted
+    pub quorum_cert: Option<QuorumCertSummary>,
+    /// The blocks whose parent is the block
+    pub children: Vec<BlockTreeNode>,
+}
+
+/// A quorum cert
+#[derive(Debug, Serialize)]
+pub struct QuorumCertSummary {
+    /// Id of the certified block
+    pub certified_block_id: HashValue,
+    /// Round of the certified block
+    pub certified_round: Round,
+    /// Id and round of the block committed by the quorum cert, if any
+    pub committed_block: Option<(HashValue, Round)>,
+    /// Whether the quorum cert ends the epoch
+    pub ends_epoch: bool,
+}
+
+impl From<&QuorumCert> for QuorumCertSummary {
+    fn from(qc: &QuorumCert) -> Self {
+        let commit_info = qc.commit_info();
+        Self {
+            certified_block_id: qc.certified_block().id(),
+            certified_round: qc.certified_block().round(),
+            committed_block: if *commit_info == BlockInfo::empty() {
+                None
+            } else {
+                Some((commit_info.id(), commit_info.round()))
+            },
+            ends_epoch: qc.ends_epoch(),
+        }
+    }
+}
+
+/// A batch of the QuorumstoreDB
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    /// Digest of the batch
+    pub digest: HashValue,
+    /// Author of the batch
+    pub author: Author,
+    /// Epoch of the batch
+    pub epoch: u64,
+    /// Id of the batch, for its author
+    pub batch_id: BatchId,
+    /// Expiration timestamp of the batch
+    pub expiration: u64,
+    /// Number of transactions of the batch
+    pub num_txns: u64,
+    /// Size of the transactions of the batch
+    pub num_bytes: u64,
+    /// Whether the transactions of the batch are persisted
+    pub has_payload: bool,
+}
+
+/// The data to delete to prune the databases to the committed block
+#[derive(Debug, Serialize)]
+pub struct PrunePlan {
+    /// Id of the committed block, which is the root of consensus on restart
+    pub committed_block_id: HashValue,
+    /// Round of the committed block
+    pub committed_round: Round,
+    /// Whether the committed block and the quorum certs required to restart from it are in the
+    /// ConsensusDB. If not, consensus recovers from the ledger, and everything is pruned.
+    pub root_found: bool,
+    /// Ids of the blocks to delete
+    pub blocks_to_prune: Vec<HashValue>,
+    /// Ids of the certified blocks of the quorum certs to delete
+    pub quorum_certs_to_prune: Vec<HashValue>,
+    /// Digests of the expired batches to delete
+    pub batches_to_prune: Vec<HashValue>,
+}
+
+/// Reads the ConsensusDB under `db_root_path`, without modifying it
+pub fn inspect_consensus_db<P: AsRef<Path>>(db_root_path: P) -> Result<ConsensusDbReport> {
+    let (last_vote, highest_2chain_timeout_certificate, blocks, quorum_certs) =
+        ConsensusDB::open(db_root_path, true)?.get_data()?;
+
+    let last_vote = last_vote
+        .map(|bytes| bcs::from_bytes::<Vote>(&bytes))
+        .transpose()?
+        .map(|vote| VoteSummary {
+            epoch: vote.epoch(),
+            round: vote.vote_data().proposed().round(),
+            block_id: vote.vote_data().proposed().id(),
+            is_timeout: vote.is_timeout(),
+        });
+    let highest_2chain_timeout_certificate = highest_2chain_timeout_certificate
+        .map(|bytes| bcs::from_bytes::<TwoChainTimeoutCertificate>(&bytes))
+        .transpose()?
+        .map(|tc| TimeoutCertificateSummary {
+            epoch: tc.epoch(),
+            round: tc.round(),
+            highest_hqc_round: tc.highest_hqc_round(),
+        });
+
+    let mut quorum_certs: HashMap<_, _> = quorum_certs
+        .iter()
+        .map(|qc| (qc.certified_block().id(), qc))
+        .collect();
+    let block_ids: HashSet<_> = blocks.iter().map(|block| block.id()).collect();
+    let mut children: HashMap<HashValue, Vec<&Block>> = HashMap::new();
+    let mut roots = vec![];
+    for block in &blocks {
+        if block_ids.contains(&block.parent_id()) {
+            children.entry(block.parent_id()).or_default().push(block);
+        } else {
+            roots.push(block);
+        }
+    }
+    roots.sort_by_key(|block| (block.epoch(), block.round()));
+    let block_tree = roots
+        .into_iter()
+        .map(|block| build_block_tree(block, &mut children, &mut quorum_certs))
+        .collect();
+
+    let mut orphaned_quorum_certs: Vec<QuorumCertSummary> = quorum_certs
+        .into_values()
+        .map(QuorumCertSummary::from)
+        .collect();
+    orphaned_quorum_certs.sort_by_key(|qc| qc.certified_round);
+
+    Ok(ConsensusDbReport {
+        last_vote,
+        highest_2chain_timeout_certificate,
+        block_tree,
+        orphaned_quorum_certs,
+    })
+}
+
+fn build_block_tree(
+    block: &Block,
+    children: &mut HashMap<HashValue, Vec<&Block>>,
+    quorum_certs: &mut HashMap<HashValue, &QuorumCert>,
+) -> BlockTreeNode {
+    let mut block_children = children.remove(&block.id()).unwrap_or_default();
+    block_children.sort_by_key(|child| child.round());
+    BlockTreeNode {
+        id: block.id(),
+        epoch: block.epoch(),
+        round: block.round(),
+        author: block.author(),
+        timestamp_usecs: block.timestamp_usecs(),
+        payload_len: block.payload().map_or(0, |payload| payload.len()),
+        parent_id: block.parent_id(),
+        quorum_cert: quorum_certs
+            .remove(&block.id())
+            .map(QuorumCertSummary::from),
+        children: block_children
+            .into_iter()
+            .map(|child| build_block_tree(child, children, quorum_certs))
+            .collect(),
+    }
+}
+
+/// Reads the batches of the QuorumstoreDB under `db_root_path`, without modifying it
+pub fn inspect_quorum_store_db<P: AsRef<Path>>(db_root_path: P) -> Result<Vec<BatchSummary>> {
+    let mut batches: Vec<_> = QuorumStoreDB::open(db_root_path, true)?
+        .get_all_batches()?
+        .into_iter()
+        .map(|(digest, value)| BatchSummary {
+            digest,
+            author: value.author(),
+            epoch: value.epoch(),
+            batch_id: value.batch_id(),
+            expiration: value.expiration(),
+            num_txns: value.num_txns(),
+            num_bytes: value.num_bytes(),
+            has_payload: value.payload_storage_mode() == StorageMode::MemoryAndPersisted,
+        })
+        .collect();
+    batches.sort_by_key(|batch| (batch.epoch, batch.expiration));
+    Ok(batches)
+}
+
+/// Reads the safety data from the on-disk secure storage at `path`, without modifying it
+pub fn read_safety_data<P: AsRef<Path>>(path: P) -> Result<SafetyData> {
+    let path = path.as_ref();
+    // The on-disk storage creates the file if it's missing
+    if !path.is_file() {
+        bail!("Secure storage file {:?} not found", path);
+    }
+    Ok(OnDiskStorage::new(path.to_path_buf())
+        .get::<SafetyData>(SAFETY_DATA)?
+        .value)
+}
+
+/// Computes the data to delete from the databases under `db_root_path` so that only the
+/// committed block of `committed_ledger_info` remains, along with the quorum certs that
+/// consensus requires to restart from it. The remaining blocks are fetched again from the other
+/// validators, and the safety data isn't modified, so this doesn't affect safety. The batches
+/// that expired before the committed block are pruned too, as quorum store does on restart.
+pub fn plan_prune_to_committed<P: AsRef<Path>>(
+    db_root_path: P,
+    committed_ledger_info: &LedgerInfo,
+) -> Result<PrunePlan> {
+    let (_, _, blocks, quorum_certs) = ConsensusDB::open(&db_root_path, true)?.get_data()?;
+
+    // If the ledger info ends the epoch, consensus restarts from a virtual genesis block
+    let committed_block_id = if committed_ledger_info.ends_epoch() {
+        None
+    } else {
+        Some(committed_ledger_info.consensus_block_id())
+    };
+    // The root requires the quorum cert certifying it, and the one committing it
+    let certifies_root = |qc: &QuorumCert| Some(qc.certified_block().id()) == committed_block_id;
+    let commits_root = |qc: &QuorumCert| Some(qc.commit_info().id()) == committed_block_id;
+    let root_found = blocks
+        .iter()
+        .any(|block| Some(block.id()) == committed_block_id)
+        && quorum_certs.iter().any(certifies_root)
+        && quorum_certs.iter().any(commits_root);
+    let root_qc_ids: HashSet<_> = quorum_certs
+        .iter()
+        .filter(|qc| certifies_root(qc) || commits_root(qc))
+        .map(|qc| qc.certified_block().id())
+        .collect();
+
+    let mut blocks_to_prune: Vec<_> = blocks
+        .iter()
+        .map(|block| block.id())
+        .filter(|id| !root_found || Some(*id) != committed_block_id)
+        .collect();
+    blocks_to_prune.sort();
+    let mut quorum_certs_to_prune: Vec<_> = quorum_certs
+        .iter()
+        .map(|qc| qc.certified_block().id())
+        .filter(|id| !root_found || !root_qc_ids.contains(id))
+        .collect();
+    quorum_certs_to_prune.sort();
+
+    let committed_timestamp = committed_ledger_info.timestamp_usecs();
+    let mut batches_to_prune: Vec<_> = QuorumStoreDB::open(&db_root_path, true)?
+        .get_all_batches()?
+        .into_iter()
+        .filter(|(_, value)| value.expiration() <= committed_timestamp)
+        .map(|(digest, _)| digest)
+        .collect();
+    batches_to_prune.sort();
+
+    Ok(PrunePlan {
+        committed_block_id: committed_ledger_info.consensus_block_id(),
+        committed_round: committed_ledger_info.round(),
+        root_found,
+        blocks_to_prune,
+        quorum_certs_to_prune,
+        batches_to_prune,
+    })
+}
+
+/// Deletes the data of the plan from the databases under `db_root_path`. The databases can't be
+/// opened while the node is running.
+pub fn prune_to_committed<P: AsRef<Path>>(db_root_path: P, plan: &PrunePlan) -> Result<()> {
+    // The quorum cert committing the root is stored with its certified block, which is pruned
+    let consensus_db = ConsensusDB::open(&db_root_path, false)?;
+    consensus_db.delete::<BlockSchema>(plan.blocks_to_prune.clone())?;
+    consensus_db.delete::<QCSchema>(plan.quorum_certs_to_prune.clone())?;
+    QuorumStoreDB::open(&db_root_path, false)?.delete_batches(plan.batches_to_prune.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "db_inspector_test.rs"]
+mod db_inspector_test;
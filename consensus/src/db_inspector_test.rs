@@ -0,0 +1,209 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensusdb::ConsensusDB,
+    db_inspector::{
+        inspect_consensus_db, inspect_quorum_store_db, plan_prune_to_committed, prune_to_committed,
+        read_safety_data,
+    },
+    quorum_store::{
+        quorum_store_db::{QuorumStoreDB, QuorumStoreStorage},
+        types::PersistedValue,
+    },
+};
+use aptos_consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        Block,
+    },
+    common::Payload,
+    proof_of_store::{BatchId, BatchInfo},
+    quorum_cert::QuorumCert,
+    safety_data::SafetyData,
+};
+use aptos_crypto::HashValue;
+use aptos_global_constants::SAFETY_DATA;
+use aptos_secure_storage::{KVStorage, OnDiskStorage};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress, block_info::BlockInfo, ledger_info::LedgerInfo,
+    validator_signer::ValidatorSigner,
+};
+
+fn make_child(
+    parent: &Block,
+    parent_qc: QuorumCert,
+    round: u64,
+    signer: &ValidatorSigner,
+) -> Block {
+    Block::new_proposal(
+        Payload::empty(false),
+        round,
+        parent.timestamp_usecs() + 1,
+        parent_qc,
+        signer,
+        vec![],
+    )
+    .unwrap()
+}
+
+fn make_qc(block: &Block, signer: &ValidatorSigner, committed: Option<BlockInfo>) -> QuorumCert {
+    gen_test_certificate(
+        &[signer.clone()],
+        block.gen_block_info(HashValue::zero(), 0, None),
+        block.quorum_cert().certified_block().clone(),
+        committed,
+    )
+}
+
+#[test]
+fn test_inspect_and_prune_to_committed() {
+    let tmp_dir = TempPath::new();
+    let signer = ValidatorSigner::random(None);
+
+    // genesis <- b1 <- b2 <- b3, and b1 <- fork
+    let genesis = Block::make_genesis_block();
+    let genesis_qc = certificate_for_genesis();
+    let b1 = make_child(&genesis, genesis_qc.clone(), 1, &signer);
+    let b1_qc = make_qc(&b1, &signer, None);
+    let b2 = make_child(&b1, b1_qc.clone(), 2, &signer);
+    let b1_info = b1.gen_block_info(HashValue::zero(), 0, None);
+    let b2_qc = make_qc(&b2, &signer, Some(b1_info.clone()));
+    let b3 = make_child(&b2, b2_qc.clone(), 3, &signer);
+    let fork = make_child(&b1, b1_qc.clone(), 4, &signer);
+    {
+        let db = ConsensusDB::new(&tmp_dir);
+        db.save_blocks_and_quorum_certificates(
+            vec![
+                genesis.clone(),
+                b1.clone(),
+                b2.clone(),
+                b3.clone(),
+                fork.clone(),
+            ],
+            vec![genesis_qc, b1_qc, b2_qc],
+        )
+        .unwrap();
+
+        let qs_db = QuorumStoreDB::new(&tmp_dir);
+        let source = AccountAddress::random();
+        for (batch_id, expiration, maybe_payload) in
+            [(1, b1.timestamp_usecs(), Some(vec![])), (2, u64::MAX, None)]
+        {
+            let info = BatchInfo::new(
+                source,
+                BatchId::new_for_test(batch_id),
+                1,
+                expiration,
+                HashValue::random(),
+                0,
+                0,
+                0,
+            );
+            qs_db
+                .save_batch(PersistedValue::new(info, maybe_payload))
+                .unwrap();
+        }
+    }
+
+    let report = inspect_consensus_db(&tmp_dir).unwrap();
+    assert!(report.last_vote.is_none());
+    assert!(report.orphaned_quorum_certs.is_empty());
+    assert_eq!(report.block_tree.len(), 1);
+    let genesis_node = &report.block_tree[0];
+    assert_eq!(genesis_node.id, genesis.id());
+    assert!(genesis_node.quorum_cert.is_some());
+    let b1_node = &genesis_node.children[0];
+    assert_eq!(b1_node.id, b1.id());
+    assert_eq!(b1_node.children.len(), 2);
+    assert_eq!(b1_node.children[0].id, b2.id());
+    assert_eq!(
+        b1_node.children[0]
+            .quorum_cert
+            .as_ref()
+            .unwrap()
+            .committed_block,
+        Some((b1.id(), 1))
+    );
+    assert_eq!(b1_node.children[0].children[0].id, b3.id());
+    assert!(b1_node.children[0].children[0].quorum_cert.is_none());
+    assert_eq!(b1_node.children[1].id, fork.id());
+
+    let batches = inspect_quorum_store_db(&tmp_dir).unwrap();
+    assert_eq!(batches.len(), 2);
+    assert!(batches[0].has_payload);
+    assert!(!batches[1].has_payload);
+
+    let committed = LedgerInfo::new(b1_info, HashValue::zero());
+    let plan = plan_prune_to_committed(&tmp_dir, &committed).unwrap();
+    assert!(plan.root_found);
+    assert_eq!(plan.committed_block_id, b1.id());
+    let mut expected_blocks = vec![genesis.id(), b2.id(), b3.id(), fork.id()];
+    expected_blocks.sort();
+    assert_eq!(plan.blocks_to_prune, expected_blocks);
+    assert_eq!(plan.quorum_certs_to_prune, vec![genesis.id()]);
+    assert_eq!(plan.batches_to_prune, vec![batches[0].digest]);
+
+    // Planning doesn't modify the databases
+    assert_eq!(
+        plan_prune_to_committed(&tmp_dir, &committed)
+            .unwrap()
+            .blocks_to_prune,
+        expected_blocks
+    );
+
+    prune_to_committed(&tmp_dir, &plan).unwrap();
+    let report = inspect_consensus_db(&tmp_dir).unwrap();
+    assert_eq!(report.block_tree.len(), 1);
+    assert_eq!(report.block_tree[0].id, b1.id());
+    assert!(report.block_tree[0].children.is_empty());
+    assert!(report.block_tree[0].quorum_cert.is_some());
+    // The quorum cert committing the root remains, even though its certified block is pruned
+    assert_eq!(report.orphaned_quorum_certs.len(), 1);
+    assert_eq!(report.orphaned_quorum_certs[0].certified_block_id, b2.id());
+
+    let batches = inspect_quorum_store_db(&tmp_dir).unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].expiration, u64::MAX);
+}
+
+#[test]
+fn test_prune_without_root() {
+    let tmp_dir = TempPath::new();
+    let signer = ValidatorSigner::random(None);
+
+    let genesis = Block::make_genesis_block();
+    let genesis_qc = certificate_for_genesis();
+    let b1 = make_child(&genesis, genesis_qc.clone(), 1, &signer);
+    ConsensusDB::new(&tmp_dir)
+        .save_blocks_and_quorum_certificates(vec![genesis, b1.clone()], vec![genesis_qc])
+        .unwrap();
+
+    // The quorum cert committing b1 isn't persisted, so consensus can't restart from it
+    let committed = LedgerInfo::new(
+        b1.gen_block_info(HashValue::zero(), 0, None),
+        HashValue::zero(),
+    );
+    let plan = plan_prune_to_committed(&tmp_dir, &committed).unwrap();
+    assert!(!plan.root_found);
+    assert_eq!(plan.blocks_to_prune.len(), 2);
+    assert_eq!(plan.quorum_certs_to_prune.len(), 1);
+
+    prune_to_committed(&tmp_dir, &plan).unwrap();
+    let report = inspect_consensus_db(&tmp_dir).unwrap();
+    assert!(report.block_tree.is_empty());
+    assert!(report.orphaned_quorum_certs.is_empty());
+}
+
+#[test]
+fn test_read_safety_data() {
+    let tmp_path = TempPath::new();
+    assert!(read_safety_data(&tmp_path).is_err());
+    assert!(!tmp_path.path().exists());
+
+    let safety_data = SafetyData::new(2, 5, 4, 3, None);
+    let mut storage = OnDiskStorage::new(tmp_path.path().to_path_buf());
+    storage.set(SAFETY_DATA, safety_data.clone()).unwrap();
+    assert_eq!(read_safety_data(&tmp_path).unwrap(), safety_data);
+}
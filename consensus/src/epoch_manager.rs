@@ -3,22 +3,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_partitioner::create_block_partitioner,
     block_storage::{
-        tracing::{observe_block, BlockStage},
+        tracing::{observe_block, observe_unverified_block, BlockStage},
         BlockStore,
     },
+    consensus_observer::{ConsensusObserverMessage, ConsensusPublisher, ObserverBatchServer},
     counters,
+    dag::{bootstrap_dag, DagCommitSigner},
     error::{error_kind, DbError},
     experimental::{
         buffer_manager::{OrderedBlocks, ResetRequest},
         decoupled_execution_utils::prepare_phases_and_buffer_manager,
         ordering_state_computer::OrderingStateComputer,
+        signing_phase::CommitSignerProvider,
     },
     liveness::{
         cached_proposer_election::CachedProposerElection,
         leader_reputation::{
             extract_epoch_to_proposers, AptosDBBackend, LeaderReputation,
-            ProposerAndVoterHeuristic, ReputationHeuristic,
+            LeaderReputationHeuristicConfig,
         },
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -32,11 +36,12 @@ use crate::{
     metrics_safety_rules::MetricsSafetyRules,
     monitor,
     network::{
-        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, IncomingRpcRequest,
-        NetworkReceivers, NetworkSender,
+        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, IncomingDAGRequest,
+        IncomingRpcRequest, NetworkReceivers, NetworkSender,
     },
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     payload_client::QuorumStoreClient,
+    payload_manager::PayloadManager,
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
     quorum_store::{
         quorum_store_builder::{DirectMempoolInnerBuilder, InnerBuilder, QuorumStoreBuilder},
@@ -53,7 +58,7 @@ use crate::{
 use anyhow::{bail, ensure, Context};
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::config::{ConsensusConfig, NodeConfig};
+use aptos_config::config::{ConsensusConfig, NodeConfig, RoleType};
 use aptos_consensus_types::{
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
@@ -69,8 +74,8 @@ use aptos_types::{
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     on_chain_config::{
-        LeaderReputationType, OnChainConfigPayload, OnChainConsensusConfig, OnChainExecutionConfig,
-        ProposerElectionType, ValidatorSet,
+        OnChainConfigPayload, OnChainConsensusConfig, OnChainExecutionConfig, ProposerElectionType,
+        ValidatorSet,
     },
     validator_verifier::ValidatorVerifier,
 };
@@ -98,7 +103,7 @@ use std::{
 const PROPSER_ELECTION_CACHING_WINDOW_ADDITION: usize = 3;
 /// Number of rounds we expect storage to be ahead of the proposer round,
 /// used for fetching data from DB.
-const PROPSER_ROUND_BEHIND_STORAGE_BUFFER: usize = 10;
+pub(crate) const PROPSER_ROUND_BEHIND_STORAGE_BUFFER: usize = 10;
 
 #[allow(clippy::large_enum_variant)]
 pub enum LivenessStorageData {
@@ -140,6 +145,13 @@ pub struct EpochManager {
     bounded_executor: BoundedExecutor,
     // recovery_mode is set to true when the recovery manager is spawned
     recovery_mode: bool,
+    // channels to DAG
+    dag_rpc_tx: Option<aptos_channel::Sender<AccountAddress, IncomingDAGRequest>>,
+    dag_shutdown_tx: Option<oneshot::Sender<oneshot::Sender<()>>>,
+    // client for publishing to consensus observers (if enabled)
+    consensus_publisher_client: Option<NetworkClient<ConsensusObserverMessage>>,
+    // server for the batch requests of consensus observers (if enabled)
+    observer_batch_server: Option<Arc<ObserverBatchServer>>,
 }
 
 impl EpochManager {
@@ -155,6 +167,8 @@ impl EpochManager {
         quorum_store_storage: Arc<dyn QuorumStoreStorage>,
         reconfig_events: ReconfigNotificationListener,
         bounded_executor: BoundedExecutor,
+        consensus_publisher_client: Option<NetworkClient<ConsensusObserverMessage>>,
+        observer_batch_server: Option<Arc<ObserverBatchServer>>,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -186,6 +200,10 @@ impl EpochManager {
             batch_retrieval_tx: None,
             bounded_executor,
             recovery_mode: false,
+            dag_rpc_tx: None,
+            dag_shutdown_tx: None,
+            consensus_publisher_client,
+            observer_batch_server,
         }
     }
 
@@ -232,38 +250,19 @@ impl EpochManager {
                 Box::new(RotatingProposer::new(vec![proposer], *contiguous_rounds))
             },
             ProposerElectionType::LeaderReputation(leader_reputation_type) => {
-                let (
+                let heuristic_config = LeaderReputationHeuristicConfig::new(
+                    self.author,
+                    proposers.len(),
+                    leader_reputation_type,
+                );
+                let first_epoch_to_consider =
+                    heuristic_config.first_epoch_to_consider(epoch_state.epoch);
+                let LeaderReputationHeuristicConfig {
                     heuristic,
                     window_size,
                     weight_by_voting_power,
-                    use_history_from_previous_epoch_max_count,
-                ) = match &leader_reputation_type {
-                    LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
-                    | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => {
-                        let proposer_window_size = proposers.len()
-                            * proposer_and_voter_config.proposer_window_num_validators_multiplier;
-                        let voter_window_size = proposers.len()
-                            * proposer_and_voter_config.voter_window_num_validators_multiplier;
-                        let heuristic: Box<dyn ReputationHeuristic> =
-                            Box::new(ProposerAndVoterHeuristic::new(
-                                self.author,
-                                proposer_and_voter_config.active_weight,
-                                proposer_and_voter_config.inactive_weight,
-                                proposer_and_voter_config.failed_weight,
-                                proposer_and_voter_config.failure_threshold_percent,
-                                voter_window_size,
-                                proposer_window_size,
-                                leader_reputation_type.use_reputation_window_from_stale_end(),
-                            ));
-                        (
-                            heuristic,
-                            std::cmp::max(proposer_window_size, voter_window_size),
-                            proposer_and_voter_config.weight_by_voting_power,
-                            proposer_and_voter_config.use_history_from_previous_epoch_max_count,
-                        )
-                    },
-                };
-
+                    ..
+                } = heuristic_config;
                 let seek_len = onchain_config.leader_reputation_exclude_round() as usize
                     + onchain_config.max_failed_authors_to_store()
                     + PROPSER_ROUND_BEHIND_STORAGE_BUFFER;
@@ -282,16 +281,6 @@ impl EpochManager {
                     vec![1; proposers.len()]
                 };
 
-                // Genesis is epoch=0
-                // First block (after genesis) is epoch=1, and is the only block in that epoch.
-                // It has no votes, so we skip it unless we are in epoch 1, as otherwise it will
-                // skew leader elections for exclude_round number of rounds.
-                let first_epoch_to_consider = std::cmp::max(
-                    if epoch_state.epoch == 1 { 1 } else { 2 },
-                    epoch_state
-                        .epoch
-                        .saturating_sub(use_history_from_previous_epoch_max_count as u64),
-                );
                 // If we are considering beyond the current epoch, we need to fetch validators for those epochs
                 let epoch_to_proposers = if epoch_state.epoch > first_epoch_to_consider {
                     self.storage
@@ -324,7 +313,7 @@ impl EpochManager {
                     epoch_to_proposers,
                     voting_powers,
                     backend,
-                    heuristic,
+                    Box::new(heuristic),
                     onchain_config.leader_reputation_exclude_round(),
                     leader_reputation_type.use_root_hash_for_seed(),
                     self.config.window_for_chain_health,
@@ -480,12 +469,16 @@ impl EpochManager {
     }
 
     /// this function spawns the phases and a buffer manager
-    /// it sets `self.commit_msg_tx` to a new aptos_channel::Sender and returns an OrderingStateComputer
+    /// it sets `self.commit_msg_tx` to a new aptos_channel::Sender and returns the channels to send
+    /// the ordered blocks and the reset requests to the buffer manager
     fn spawn_decoupled_execution(
         &mut self,
-        safety_rules_container: Arc<Mutex<MetricsSafetyRules>>,
+        commit_signer_provider: Arc<dyn CommitSignerProvider>,
         verifier: ValidatorVerifier,
-    ) -> OrderingStateComputer {
+    ) -> (
+        UnboundedSender<OrderedBlocks>,
+        UnboundedSender<ResetRequest>,
+    ) {
         let network_sender = NetworkSender::new(
             self.author,
             self.network_sender.clone(),
@@ -505,17 +498,26 @@ impl EpochManager {
         self.buffer_manager_msg_tx = Some(commit_msg_tx);
         self.buffer_manager_reset_tx = Some(reset_tx.clone());
 
+        let consensus_publisher = self.consensus_publisher_client.clone().map(|client| {
+            ConsensusPublisher::new(
+                RoleType::Validator,
+                client,
+                &tokio::runtime::Handle::current(),
+            )
+        });
+
         let (execution_phase, signing_phase, persisting_phase, buffer_manager) =
             prepare_phases_and_buffer_manager(
                 self.author,
                 self.commit_state_computer.clone(),
-                safety_rules_container,
+                commit_signer_provider,
                 network_sender,
                 commit_msg_rx,
                 self.commit_state_computer.clone(),
                 block_rx,
                 reset_rx,
                 verifier,
+                consensus_publisher,
             );
 
         tokio::spawn(execution_phase.start());
@@ -523,7 +525,7 @@ impl EpochManager {
         tokio::spawn(persisting_phase.start());
         tokio::spawn(buffer_manager.start());
 
-        OrderingStateComputer::new(block_tx, self.commit_state_computer.clone(), reset_tx)
+        (block_tx, reset_tx)
     }
 
     async fn shutdown_current_processor(&mut self) {
@@ -539,6 +541,16 @@ impl EpochManager {
         }
         self.round_manager_tx = None;
 
+        if let Some(close_tx) = self.dag_shutdown_tx.take() {
+            // Release the previous DAG, this aborts its reliable broadcasts
+            let (ack_tx, ack_rx) = oneshot::channel();
+            close_tx
+                .send(ack_tx)
+                .expect("[EpochManager] Fail to drop DAG");
+            ack_rx.await.expect("[EpochManager] Fail to drop DAG");
+        }
+        self.dag_rpc_tx = None;
+
         // Shutdown the previous buffer manager, to release the SafetyRule client
         self.buffer_manager_msg_tx = None;
         if let Some(mut tx) = self.buffer_manager_reset_tx.take() {
@@ -599,6 +611,155 @@ impl EpochManager {
         tokio::spawn(recovery_manager.start(recovery_manager_rx, close_rx));
     }
 
+    /// Builds the QuorumStore, or the DirectMempool when it's not enabled, returns the payload
+    /// manager, the client to pull the payloads with and the builder to start it with.
+    fn init_payload_provider(
+        &mut self,
+        epoch_state: &EpochState,
+        network_sender: NetworkSender,
+    ) -> (Arc<PayloadManager>, QuorumStoreClient, QuorumStoreBuilder) {
+        // Start QuorumStore
+        let (consensus_to_quorum_store_tx, consensus_to_quorum_store_rx) =
+            mpsc::channel(self.config.intra_consensus_channel_buffer_size);
+
+        let mut quorum_store_builder = if self.quorum_store_enabled {
+            info!("Building QuorumStore");
+            QuorumStoreBuilder::QuorumStore(InnerBuilder::new(
+                self.epoch(),
+                self.author,
+                epoch_state.verifier.len() as u64,
+                self.config.quorum_store.clone(),
+                consensus_to_quorum_store_rx,
+                self.quorum_store_to_mempool_sender.clone(),
+                self.config.mempool_txn_pull_timeout_ms,
+                self.storage.aptos_db().clone(),
+                network_sender,
+                epoch_state.verifier.clone(),
+                self.config.safety_rules.backend.clone(),
+                self.quorum_store_storage.clone(),
+            ))
+        } else {
+            info!("Building DirectMempool");
+            QuorumStoreBuilder::DirectMempool(DirectMempoolInnerBuilder::new(
+                consensus_to_quorum_store_rx,
+                self.quorum_store_to_mempool_sender.clone(),
+                self.config.mempool_txn_pull_timeout_ms,
+            ))
+        };
+
+        let (payload_manager, quorum_store_msg_tx) = quorum_store_builder.init_payload_manager();
+        self.quorum_store_msg_tx = quorum_store_msg_tx;
+        if let Some(observer_batch_server) = &self.observer_batch_server {
+            observer_batch_server.update_epoch(self.epoch(), payload_manager.batch_reader());
+        }
+
+        let payload_client = QuorumStoreClient::new(
+            consensus_to_quorum_store_tx,
+            self.config.quorum_store_pull_timeout_ms,
+            self.config.wait_for_full_blocks_above_recent_fill_threshold,
+            self.config.wait_for_full_blocks_above_pending_blocks,
+        );
+        (payload_manager, payload_client, quorum_store_builder)
+    }
+
+    fn init_commit_state_computer(
+        &self,
+        epoch_state: &EpochState,
+        payload_manager: Arc<PayloadManager>,
+        onchain_execution_config: &OnChainExecutionConfig,
+    ) {
+        let transaction_shuffler =
+            create_transaction_shuffler(onchain_execution_config.transaction_shuffler_type());
+        let block_gas_limit = onchain_execution_config.block_gas_limit();
+        let transaction_deduper =
+            create_transaction_deduper(onchain_execution_config.transaction_deduper_type());
+        let block_partitioner = create_block_partitioner(
+            onchain_execution_config.block_partitioner_type(),
+            block_gas_limit,
+        );
+        self.commit_state_computer.new_epoch(
+            epoch_state,
+            payload_manager,
+            transaction_shuffler,
+            block_gas_limit,
+            transaction_deduper,
+            block_partitioner,
+        );
+    }
+
+    async fn start_dag(
+        &mut self,
+        ledger_data: LedgerRecoveryData,
+        epoch_state: EpochState,
+        onchain_execution_config: OnChainExecutionConfig,
+    ) {
+        let epoch = epoch_state.epoch;
+        counters::EPOCH.set(epoch_state.epoch as i64);
+        counters::CURRENT_EPOCH_VALIDATORS.set(epoch_state.verifier.len() as i64);
+        info!(
+            epoch = epoch_state.epoch,
+            validators = epoch_state.verifier.to_string(),
+            committed_round = ledger_data.committed_round(),
+            "Starting new epoch with DAG",
+        );
+
+        info!(epoch = epoch, "Update SafetyRules");
+        let mut safety_rules =
+            MetricsSafetyRules::new(self.safety_rules_manager.client(), self.storage.clone());
+        if let Err(error) = safety_rules.perform_initialize() {
+            error!(
+                epoch = epoch,
+                error = error,
+                "Unable to initialize safety rules.",
+            );
+        }
+        let safety_rules_container = Arc::new(Mutex::new(safety_rules));
+
+        let network_sender = NetworkSender::new(
+            self.author,
+            self.network_sender.clone(),
+            self.self_sender.clone(),
+            epoch_state.verifier.clone(),
+        );
+
+        let (payload_manager, payload_client, quorum_store_builder) =
+            self.init_payload_provider(&epoch_state, network_sender.clone());
+        self.init_commit_state_computer(&epoch_state, payload_manager, &onchain_execution_config);
+        let (block_tx, _reset_tx) = self.spawn_decoupled_execution(
+            Arc::new(DagCommitSigner::new(safety_rules_container.clone())),
+            epoch_state.verifier.clone(),
+        );
+        if let Some((quorum_store_coordinator_tx, batch_retrieval_rx)) =
+            quorum_store_builder.start()
+        {
+            self.quorum_store_coordinator_tx = Some(quorum_store_coordinator_tx);
+            self.batch_retrieval_tx = Some(batch_retrieval_rx);
+        }
+
+        info!(epoch = epoch, "Bootstrap DAG");
+        let network_handler = bootstrap_dag(
+            self.author,
+            safety_rules_container,
+            Arc::new(epoch_state),
+            ledger_data.ledger_info().ledger_info().clone(),
+            self.storage.dag_storage(),
+            Arc::new(network_sender),
+            self.time_service.clone(),
+            Arc::new(payload_client),
+            block_tx,
+            self.config.dag_consensus.clone(),
+            self.quorum_store_enabled,
+        )
+        .await;
+
+        let (dag_rpc_tx, dag_rpc_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 100, Some(&counters::DAG_RPC_TASK_MSGS));
+        self.dag_rpc_tx = Some(dag_rpc_tx);
+        let (dag_shutdown_tx, dag_shutdown_rx) = oneshot::channel();
+        self.dag_shutdown_tx = Some(dag_shutdown_tx);
+        tokio::spawn(network_handler.start(dag_rpc_rx, dag_shutdown_rx));
+    }
+
     async fn start_round_manager(
         &mut self,
         recovery_data: RecoveryData,
@@ -649,60 +810,22 @@ impl EpochManager {
 
         let safety_rules_container = Arc::new(Mutex::new(safety_rules));
 
-        // Start QuorumStore
-        let (consensus_to_quorum_store_tx, consensus_to_quorum_store_rx) =
-            mpsc::channel(self.config.intra_consensus_channel_buffer_size);
-
-        let mut quorum_store_builder = if self.quorum_store_enabled {
-            info!("Building QuorumStore");
-            QuorumStoreBuilder::QuorumStore(InnerBuilder::new(
-                self.epoch(),
-                self.author,
-                epoch_state.verifier.len() as u64,
-                self.config.quorum_store.clone(),
-                consensus_to_quorum_store_rx,
-                self.quorum_store_to_mempool_sender.clone(),
-                self.config.mempool_txn_pull_timeout_ms,
-                self.storage.aptos_db().clone(),
-                network_sender.clone(),
-                epoch_state.verifier.clone(),
-                self.config.safety_rules.backend.clone(),
-                self.quorum_store_storage.clone(),
-            ))
-        } else {
-            info!("Building DirectMempool");
-            QuorumStoreBuilder::DirectMempool(DirectMempoolInnerBuilder::new(
-                consensus_to_quorum_store_rx,
-                self.quorum_store_to_mempool_sender.clone(),
-                self.config.mempool_txn_pull_timeout_ms,
-            ))
-        };
-
-        let (payload_manager, quorum_store_msg_tx) = quorum_store_builder.init_payload_manager();
-        let transaction_shuffler =
-            create_transaction_shuffler(onchain_execution_config.transaction_shuffler_type());
-        let block_gas_limit = onchain_execution_config.block_gas_limit();
-        let transaction_deduper =
-            create_transaction_deduper(onchain_execution_config.transaction_deduper_type());
-        self.quorum_store_msg_tx = quorum_store_msg_tx;
-
-        let payload_client = QuorumStoreClient::new(
-            consensus_to_quorum_store_tx,
-            self.config.quorum_store_pull_timeout_ms,
-            self.config.wait_for_full_blocks_above_recent_fill_threshold,
-            self.config.wait_for_full_blocks_above_pending_blocks,
-        );
-        self.commit_state_computer.new_epoch(
+        let (payload_manager, payload_client, quorum_store_builder) =
+            self.init_payload_provider(&epoch_state, network_sender.clone());
+        self.init_commit_state_computer(
             &epoch_state,
             payload_manager.clone(),
-            transaction_shuffler,
-            block_gas_limit,
-            transaction_deduper,
+            &onchain_execution_config,
         );
         let state_computer = if onchain_consensus_config.decoupled_execution() {
-            Arc::new(self.spawn_decoupled_execution(
+            let (block_tx, reset_tx) = self.spawn_decoupled_execution(
                 safety_rules_container.clone(),
                 epoch_state.verifier.clone(),
+            );
+            Arc::new(OrderingStateComputer::new(
+                block_tx,
+                self.commit_state_computer.clone(),
+                reset_tx,
             ))
         } else {
             self.commit_state_computer.clone()
@@ -809,6 +932,22 @@ impl EpochManager {
 
         self.epoch_state = Some(Arc::new(epoch_state.clone()));
 
+        if let Ok(consensus_config) = &onchain_consensus_config {
+            if consensus_config.dag_enabled() {
+                // The DAG recovers its own state, only the committed ledger info is needed.
+                self.quorum_store_enabled = self.enable_quorum_store(consensus_config);
+                self.recovery_mode = false;
+                let ledger_data = self.storage.recover_from_ledger();
+                self.start_dag(
+                    ledger_data,
+                    epoch_state,
+                    onchain_execution_config.unwrap_or_default(),
+                )
+                .await;
+                return;
+            }
+        }
+
         match self.storage.start() {
             LivenessStorageData::FullRecoveryData(initial_data) => {
                 let consensus_config = onchain_consensus_config.unwrap_or_default();
@@ -845,10 +984,7 @@ impl EpochManager {
         });
 
         if let ConsensusMsg::ProposalMsg(proposal) = &consensus_msg {
-            observe_block(
-                proposal.proposal().timestamp_usecs(),
-                BlockStage::EPOCH_MANAGER_RECEIVED,
-            );
+            observe_unverified_block(proposal.proposal(), BlockStage::EPOCH_MANAGER_RECEIVED);
         }
         // we can't verify signatures from a different epoch
         let maybe_unverified_event = self.check_epoch(peer_id, consensus_msg).await?;
@@ -1010,10 +1146,7 @@ impl EpochManager {
         event: VerifiedEvent,
     ) {
         if let VerifiedEvent::ProposalMsg(proposal) = &event {
-            observe_block(
-                proposal.proposal().timestamp_usecs(),
-                BlockStage::EPOCH_MANAGER_VERIFIED,
-            );
+            observe_block(proposal.proposal(), BlockStage::EPOCH_MANAGER_VERIFIED);
         }
         if let Err(e) = match event {
             quorum_store_event @ (VerifiedEvent::SignedBatchInfo(_)
@@ -1039,7 +1172,7 @@ impl EpochManager {
     }
 
     fn process_rpc_request(
-        &self,
+        &mut self,
         peer_id: Author,
         request: IncomingRpcRequest,
     ) -> anyhow::Result<()> {
@@ -1064,6 +1197,28 @@ impl EpochManager {
         }
     }
 
+    fn process_dag_rpc_request(
+        &mut self,
+        peer_id: Author,
+        request: IncomingDAGRequest,
+    ) -> anyhow::Result<()> {
+        fail_point!("consensus::process::any", |_| {
+            Err(anyhow::anyhow!("Injected error in process_dag_rpc_request"))
+        });
+        let request_epoch = request.req.epoch();
+        if request_epoch != self.epoch() {
+            return monitor!(
+                "process_different_epoch_dag_rpc",
+                self.process_different_epoch(request_epoch, peer_id)
+            );
+        }
+        if let Some(tx) = &self.dag_rpc_tx {
+            tx.push(peer_id, request)
+        } else {
+            Err(anyhow::anyhow!("DAG not started"))
+        }
+    }
+
     fn process_local_timeout(&mut self, round: u64) {
         let peer_id = self.author;
         let event = VerifiedEvent::LocalTimeout(round);
@@ -1119,6 +1274,12 @@ impl EpochManager {
                         error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
                     });
                 },
+                (peer, request) = network_receivers.dag_rpc_rx.select_next_some() => {
+                    monitor!("epoch_manager_process_dag_rpc",
+                    if let Err(e) = self.process_dag_rpc_request(peer, request) {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    });
+                },
                 round = round_timeout_sender_rx.select_next_some() => {
                     monitor!("epoch_manager_process_round_timeout",
                     self.process_local_timeout(round));
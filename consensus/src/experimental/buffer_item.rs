@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{experimental::hashable::Hashable, state_replication::StateComputerCommitCallBackType};
+use crate::{
+    experimental::hashable::Hashable,
+    state_replication::{StateComputer, StateComputerCommitCallBackType},
+};
 use anyhow::anyhow;
 use aptos_consensus_types::{
     common::Author, executed_block::ExecutedBlock, experimental::commit_vote::CommitVote,
@@ -48,6 +51,22 @@ fn verify_signatures(
     )
 }
 
+/// Saves the fingerprints of the local execution of the blocks, which doesn't match the commit
+/// decision of the other validators, so the first transaction it diverged on can be found after
+/// the node halts.
+fn record_execution_divergence(
+    executed_blocks: &[ExecutedBlock],
+    commit_info: &BlockInfo,
+    execution_proxy: &dyn StateComputer,
+) {
+    error!(
+        block_ids = ?executed_blocks.iter().map(|b| b.id()).collect::<Vec<_>>(),
+        "Local execution diverged from the commit decision {}",
+        commit_info
+    );
+    execution_proxy.record_execution_divergence(executed_blocks);
+}
+
 fn generate_executed_item_from_ordered(
     commit_info: BlockInfo,
     executed_blocks: Vec<ExecutedBlock>,
@@ -147,6 +166,7 @@ impl BufferItem {
         executed_blocks: Vec<ExecutedBlock>,
         validator: &ValidatorVerifier,
         epoch_end_timestamp: Option<u64>,
+        execution_proxy: &dyn StateComputer,
     ) -> Self {
         match self {
             Self::Ordered(ordered_item) => {
@@ -171,6 +191,13 @@ impl BufferItem {
                 if let Some(commit_proof) = commit_proof {
                     // We have already received the commit proof in fast forward sync path,
                     // we can just use that proof and proceed to aggregated
+                    if *commit_proof.commit_info() != commit_info {
+                        record_execution_divergence(
+                            &executed_blocks,
+                            commit_proof.commit_info(),
+                            execution_proxy,
+                        );
+                    }
                     assert_eq!(commit_proof.commit_info().clone(), commit_info);
                     debug!(
                         "{} advance to aggregated from ordered",
@@ -257,6 +284,7 @@ impl BufferItem {
     pub fn try_advance_to_aggregated_with_ledger_info(
         self,
         commit_proof: LedgerInfoWithSignatures,
+        execution_proxy: &dyn StateComputer,
     ) -> Self {
         match self {
             Self::Signed(signed_item) => {
@@ -266,6 +294,13 @@ impl BufferItem {
                     partial_commit_proof: local_commit_proof,
                     ..
                 } = *signed_item;
+                if local_commit_proof.commit_info() != commit_proof.commit_info() {
+                    record_execution_divergence(
+                        &executed_blocks,
+                        commit_proof.commit_info(),
+                        execution_proxy,
+                    );
+                }
                 assert_eq!(local_commit_proof.commit_info(), commit_proof.commit_info(),);
                 debug!(
                     "{} advance to aggregated with commit decision",
@@ -284,6 +319,13 @@ impl BufferItem {
                     commit_info,
                     ..
                 } = *executed_item;
+                if commit_info != *commit_proof.commit_info() {
+                    record_execution_divergence(
+                        &executed_blocks,
+                        commit_proof.commit_info(),
+                        execution_proxy,
+                    );
+                }
                 assert_eq!(commit_info, *commit_proof.commit_info());
                 debug!(
                     "{} advance to aggregated with commit decision",
@@ -4,6 +4,7 @@
 
 use crate::{
     block_storage::tracing::{observe_block, BlockStage},
+    consensus_observer::ConsensusPublisher,
     counters,
     experimental::{
         buffer::{Buffer, Cursor},
@@ -16,7 +17,7 @@ use crate::{
     monitor,
     network::NetworkSender,
     round_manager::VerifiedEvent,
-    state_replication::StateComputerCommitCallBackType,
+    state_replication::{StateComputer, StateComputerCommitCallBackType},
 };
 use aptos_consensus_types::{common::Author, executed_block::ExecutedBlock};
 use aptos_crypto::HashValue;
@@ -95,6 +96,8 @@ pub struct BufferManager {
     verifier: ValidatorVerifier,
 
     ongoing_tasks: Arc<AtomicU64>,
+    // records the local execution when it diverges from the commit decision
+    execution_proxy: Arc<dyn StateComputer>,
     // Since proposal_generator is not aware of reconfiguration any more, the suffix blocks
     // will not have the same timestamp as the reconfig block which violates the invariant
     // that block.timestamp == state.timestamp because no txn is executed in suffix blocks.
@@ -104,6 +107,9 @@ pub struct BufferManager {
     // being updated on-chain.
     end_epoch_timestamp: OnceCell<u64>,
     previous_commit_time: Instant,
+
+    // publishes the ordered blocks and commit decisions to consensus observers (if enabled)
+    consensus_publisher: Option<ConsensusPublisher>,
 }
 
 impl BufferManager {
@@ -120,6 +126,8 @@ impl BufferManager {
         reset_rx: UnboundedReceiver<ResetRequest>,
         verifier: ValidatorVerifier,
         ongoing_tasks: Arc<AtomicU64>,
+        execution_proxy: Arc<dyn StateComputer>,
+        consensus_publisher: Option<ConsensusPublisher>,
     ) -> Self {
         let buffer = Buffer::<BufferItem>::new();
 
@@ -147,8 +155,10 @@ impl BufferManager {
 
             verifier,
             ongoing_tasks,
+            execution_proxy,
             end_epoch_timestamp: OnceCell::new(),
             previous_commit_time: Instant::now(),
+            consensus_publisher,
         }
     }
 
@@ -185,6 +195,9 @@ impl BufferManager {
             ordered_proof.commit_info(),
             self.buffer.len() + 1,
         );
+        if let Some(consensus_publisher) = &self.consensus_publisher {
+            consensus_publisher.publish_ordered_blocks(&ordered_blocks, ordered_proof.clone());
+        }
         let item = BufferItem::new_ordered(ordered_blocks, ordered_proof, callback);
         self.buffer.push_back(item);
     }
@@ -269,8 +282,14 @@ impl BufferManager {
             }
             if item.block_id() == target_block_id {
                 let aggregated_item = item.unwrap_aggregated();
+                for executed_block in &aggregated_item.executed_blocks {
+                    observe_block(executed_block.block(), BlockStage::COMMIT_CERTIFIED);
+                }
                 let block = aggregated_item.executed_blocks.last().unwrap().block();
-                observe_block(block.timestamp_usecs(), BlockStage::COMMIT_CERTIFIED);
+                if let Some(consensus_publisher) = &self.consensus_publisher {
+                    consensus_publisher
+                        .publish_commit_decision(aggregated_item.commit_proof.clone());
+                }
                 // if we're the proposer for the block, we're responsible to broadcast the commit decision.
                 if block.author() == Some(self.author) {
                     self.commit_msg_tx
@@ -379,6 +398,7 @@ impl BufferManager {
             executed_blocks,
             &self.verifier,
             self.end_epoch_timestamp.get().cloned(),
+            self.execution_proxy.as_ref(),
         );
         let aggregated = new_item.is_aggregated();
         self.buffer.set(&current_cursor, new_item);
@@ -414,6 +434,9 @@ impl BufferManager {
             if item.is_executed() {
                 // we have found the buffer item
                 let signed_item = item.advance_to_signed(self.author, signature);
+                for executed_block in &signed_item.unwrap_signed_ref().executed_blocks {
+                    observe_block(executed_block.block(), BlockStage::COMMIT_VOTED);
+                }
                 let maybe_proposer = signed_item
                     .unwrap_signed_ref()
                     .executed_blocks
@@ -484,6 +507,7 @@ impl BufferManager {
                     let item = self.buffer.take(&cursor);
                     let new_item = item.try_advance_to_aggregated_with_ledger_info(
                         commit_proof.ledger_info().clone(),
+                        self.execution_proxy.as_ref(),
                     );
                     let aggregated = new_item.is_aggregated();
                     self.buffer.set(&cursor, new_item);
@@ -3,21 +3,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    consensus_observer::ConsensusPublisher,
     experimental::{
         buffer_manager::{create_channel, BufferManager, OrderedBlocks, ResetRequest},
         execution_phase::{ExecutionPhase, ExecutionRequest, ExecutionResponse},
         persisting_phase::{PersistingPhase, PersistingRequest},
         pipeline_phase::{CountedRequest, PipelinePhase},
-        signing_phase::{SigningPhase, SigningRequest, SigningResponse},
+        signing_phase::{CommitSignerProvider, SigningPhase, SigningRequest, SigningResponse},
     },
-    metrics_safety_rules::MetricsSafetyRules,
     network::NetworkSender,
     round_manager::VerifiedEvent,
     state_replication::StateComputer,
 };
 use aptos_channels::aptos_channel::Receiver;
 use aptos_consensus_types::common::Author;
-use aptos_infallible::Mutex;
 use aptos_types::{account_address::AccountAddress, validator_verifier::ValidatorVerifier};
 use futures::channel::mpsc::UnboundedReceiver;
 use std::sync::{atomic::AtomicU64, Arc};
@@ -26,13 +25,14 @@ use std::sync::{atomic::AtomicU64, Arc};
 pub fn prepare_phases_and_buffer_manager(
     author: Author,
     execution_proxy: Arc<dyn StateComputer>,
-    safety_rules: Arc<Mutex<MetricsSafetyRules>>,
+    safety_rules: Arc<dyn CommitSignerProvider>,
     commit_msg_tx: NetworkSender,
     commit_msg_rx: Receiver<AccountAddress, VerifiedEvent>,
     persisting_proxy: Arc<dyn StateComputer>,
     block_rx: UnboundedReceiver<OrderedBlocks>,
     sync_rx: UnboundedReceiver<ResetRequest>,
     verifier: ValidatorVerifier,
+    consensus_publisher: Option<ConsensusPublisher>,
 ) -> (
     PipelinePhase<ExecutionPhase>,
     PipelinePhase<SigningPhase>,
@@ -47,7 +47,7 @@ pub fn prepare_phases_and_buffer_manager(
 
     let ongoing_tasks = Arc::new(AtomicU64::new(0));
 
-    let execution_phase_processor = ExecutionPhase::new(execution_proxy);
+    let execution_phase_processor = ExecutionPhase::new(execution_proxy.clone());
     let execution_phase = PipelinePhase::new(
         execution_phase_request_rx,
         Some(execution_phase_response_tx),
@@ -95,6 +95,8 @@ pub fn prepare_phases_and_buffer_manager(
             sync_rx,
             verifier,
             ongoing_tasks,
+            execution_proxy,
+            consensus_publisher,
         ),
     )
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_partitioner::BlockPartitioner,
     error::StateSyncError,
     experimental::{
         buffer_manager::{OrderedBlocks, ResetAck, ResetRequest},
@@ -128,8 +129,14 @@ impl StateComputer for OrderingStateComputer {
         _: Arc<dyn TransactionShuffler>,
         _: Option<u64>,
         _: Arc<dyn TransactionDeduper>,
+        _: Arc<dyn BlockPartitioner>,
     ) {
     }
 
     fn end_epoch(&self) {}
+
+    fn record_execution_divergence(&self, blocks: &[ExecutedBlock]) {
+        self.state_computer_for_sync
+            .record_execution_divergence(blocks)
+    }
 }
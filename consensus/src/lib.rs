@@ -15,9 +15,11 @@
 
 extern crate core;
 
+mod block_partitioner;
 mod block_storage;
 mod consensusdb;
 mod dag;
+mod db_inspector;
 mod epoch_manager;
 mod error;
 mod experimental;
@@ -42,6 +44,9 @@ mod twins;
 mod txn_notifier;
 mod util;
 
+mod conflict_aware_shuffler;
+/// Lets fullnodes follow the blocks ordered by consensus.
+pub mod consensus_observer;
 /// AptosBFT implementation
 pub mod consensus_provider;
 /// Required by the telemetry service
@@ -55,12 +60,33 @@ mod transaction_shuffler;
 mod txn_hash_and_authenticator_deduper;
 
 use aptos_metrics_core::IntGauge;
+/// Required by the inspection service to export the latency breakdown of recent blocks
+pub use block_storage::tracing::{
+    get_block_timeline, get_block_timelines, BlockStageTime, BlockTimeline,
+};
 pub use consensusdb::create_checkpoint;
+/// Required by the inspection service and the CLI to report equivocating validators
+pub use consensusdb::read_equivocation_evidence;
 /// Required by the smoke tests
 pub use consensusdb::CONSENSUS_DB_NAME;
+/// Required by the db tool to inspect and repair the databases of consensus
+pub use db_inspector::{
+    inspect_consensus_db, inspect_quorum_store_db, plan_prune_to_committed, prune_to_committed,
+    read_safety_data, BatchSummary, BlockTreeNode, ConsensusDbReport, PrunePlan, QuorumCertSummary,
+    TimeoutCertificateSummary, VoteSummary,
+};
+/// Required by the CLI to build the explainer history
+pub use liveness::leader_reputation::VersionedNewBlockEvent;
+/// Required by the CLI to explain and simulate leader elections
+pub use liveness::leader_reputation_explainer::{
+    EpochValidators, LeaderElectionExplanation, LeaderReputationExplainer, LeaderReputationParams,
+    ValidatorReputation,
+};
 pub use quorum_store::quorum_store_db::QUORUM_STORE_DB_NAME;
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
+/// Required by the executor benchmark to shuffle blocks as consensus does
+pub use transaction_shuffler::{create_transaction_shuffler, TransactionShuffler};
 
 struct IntGaugeGuard {
     gauge: IntGauge,
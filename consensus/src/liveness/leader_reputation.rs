@@ -24,6 +24,7 @@ use aptos_types::{
     account_config::{new_block_event_key, NewBlockEvent},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
+    on_chain_config::LeaderReputationType,
 };
 use std::{
     cmp::max,
@@ -84,25 +85,10 @@ impl AptosDBBackend {
         // we cannot reorder those two functions, as if get_events is first,
         // and then new entry gets added before get_latest_version is called,
         // we would incorrectly think that we have a newer version.
-        let events = self.aptos_db.get_events(
-            &new_block_event_key(),
-            u64::max_value(),
-            Order::Descending,
-            limit as u64,
-            latest_db_version,
-        )?;
-
-        let max_returned_version = events.first().map_or(0, |first| first.transaction_version);
-
-        let new_block_events = events
-            .into_iter()
-            .map(|event| {
-                Ok(VersionedNewBlockEvent {
-                    event: bcs::from_bytes::<NewBlockEvent>(event.event.event_data())?,
-                    version: event.transaction_version,
-                })
-            })
-            .collect::<Result<Vec<VersionedNewBlockEvent>, bcs::Error>>()?;
+        let new_block_events =
+            fetch_new_block_events(self.aptos_db.as_ref(), limit as u64, latest_db_version)?;
+
+        let max_returned_version = new_block_events.first().map_or(0, |first| first.version);
 
         let hit_end = new_block_events.len() < limit;
 
@@ -137,16 +123,8 @@ impl AptosDBBackend {
             }
         }
 
-        let mut max_version = 0;
-        let mut result = vec![];
-        for event in events {
-            if (event.event.epoch(), event.event.round()) <= (target_epoch, target_round)
-                && result.len() < self.window_size
-            {
-                max_version = std::cmp::max(max_version, event.version);
-                result.push(event.event.clone());
-            }
-        }
+        let (result, max_version) =
+            select_window(events, target_epoch, target_round, self.window_size);
 
         if result.len() < self.window_size && !hit_end {
             error!(
@@ -170,6 +148,52 @@ impl AptosDBBackend {
     }
 }
 
+/// Returns the most recent NewBlockEvents committed at or before `latest_version`, from the most
+/// recent one.
+pub(crate) fn fetch_new_block_events(
+    aptos_db: &dyn DbReader,
+    limit: u64,
+    latest_version: u64,
+) -> Result<Vec<VersionedNewBlockEvent>> {
+    let events = aptos_db.get_events(
+        &new_block_event_key(),
+        u64::max_value(),
+        Order::Descending,
+        limit,
+        latest_version,
+    )?;
+    Ok(events
+        .into_iter()
+        .map(|event| {
+            Ok(VersionedNewBlockEvent {
+                event: bcs::from_bytes::<NewBlockEvent>(event.event.event_data())?,
+                version: event.transaction_version,
+            })
+        })
+        .collect::<Result<Vec<VersionedNewBlockEvent>, bcs::Error>>()?)
+}
+
+/// Returns the window of at most `window_size` events at or before the target round, from
+/// events sorted from the most recent one, and the max version of the events in the window.
+pub(crate) fn select_window(
+    events: &[VersionedNewBlockEvent],
+    target_epoch: u64,
+    target_round: Round,
+    window_size: usize,
+) -> (Vec<NewBlockEvent>, u64) {
+    let mut max_version = 0;
+    let mut result = vec![];
+    for event in events {
+        if (event.event.epoch(), event.event.round()) <= (target_epoch, target_round)
+            && result.len() < window_size
+        {
+            max_version = std::cmp::max(max_version, event.version);
+            result.push(event.event.clone());
+        }
+    }
+    (result, max_version)
+}
+
 impl MetadataBackend for AptosDBBackend {
     // assume the target_round only increases
     fn get_block_metadata(
@@ -516,6 +540,68 @@ impl ProposerAndVoterHeuristic {
     }
 }
 
+impl ProposerAndVoterHeuristic {
+    /// Returns the aggregation of the history used for the weights
+    pub fn aggregation(&self) -> &NewBlockEventAggregation {
+        &self.aggregation
+    }
+}
+
+/// The heuristic of a leader reputation type, with the history it needs
+pub struct LeaderReputationHeuristicConfig {
+    pub heuristic: ProposerAndVoterHeuristic,
+    /// Number of NewBlockEvents needed by the heuristic
+    pub window_size: usize,
+    pub weight_by_voting_power: bool,
+    pub use_history_from_previous_epoch_max_count: u32,
+}
+
+impl LeaderReputationHeuristicConfig {
+    pub fn new(
+        author: Author,
+        num_proposers: usize,
+        leader_reputation_type: &LeaderReputationType,
+    ) -> Self {
+        match leader_reputation_type {
+            LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
+            | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => {
+                let proposer_window_size = num_proposers
+                    * proposer_and_voter_config.proposer_window_num_validators_multiplier;
+                let voter_window_size = num_proposers
+                    * proposer_and_voter_config.voter_window_num_validators_multiplier;
+                Self {
+                    heuristic: ProposerAndVoterHeuristic::new(
+                        author,
+                        proposer_and_voter_config.active_weight,
+                        proposer_and_voter_config.inactive_weight,
+                        proposer_and_voter_config.failed_weight,
+                        proposer_and_voter_config.failure_threshold_percent,
+                        voter_window_size,
+                        proposer_window_size,
+                        leader_reputation_type.use_reputation_window_from_stale_end(),
+                    ),
+                    window_size: std::cmp::max(proposer_window_size, voter_window_size),
+                    weight_by_voting_power: proposer_and_voter_config.weight_by_voting_power,
+                    use_history_from_previous_epoch_max_count: proposer_and_voter_config
+                        .use_history_from_previous_epoch_max_count,
+                }
+            },
+        }
+    }
+
+    /// Returns the first epoch whose history is considered in the given epoch.
+    pub fn first_epoch_to_consider(&self, epoch: u64) -> u64 {
+        // Genesis is epoch=0
+        // First block (after genesis) is epoch=1, and is the only block in that epoch.
+        // It has no votes, so we skip it unless we are in epoch 1, as otherwise it will
+        // skew leader elections for exclude_round number of rounds.
+        std::cmp::max(
+            if epoch == 1 { 1 } else { 2 },
+            epoch.saturating_sub(self.use_history_from_previous_epoch_max_count as u64),
+        )
+    }
+}
+
 impl ReputationHeuristic for ProposerAndVoterHeuristic {
     fn get_weights(
         &self,
@@ -705,20 +791,7 @@ impl ProposerElection for LeaderReputation {
             .map(|(i, w)| *w as u128 * self.voting_powers[i] as u128)
             .collect();
 
-        let state = if self.use_root_hash {
-            [
-                root_hash.to_vec(),
-                self.epoch.to_le_bytes().to_vec(),
-                round.to_le_bytes().to_vec(),
-            ]
-            .concat()
-        } else {
-            [
-                self.epoch.to_le_bytes().to_vec(),
-                round.to_le_bytes().to_vec(),
-            ]
-            .concat()
-        };
+        let state = leader_election_seed(self.use_root_hash, root_hash, self.epoch, round);
 
         let chosen_index = choose_index(stake_weights, state);
         (proposers[chosen_index], voting_power_participation_ratio)
@@ -735,37 +808,78 @@ impl ProposerElection for LeaderReputation {
     }
 }
 
+/// Returns the seed of the weighted leader election of the round
+pub(crate) fn leader_election_seed(
+    use_root_hash: bool,
+    root_hash: HashValue,
+    epoch: u64,
+    round: Round,
+) -> Vec<u8> {
+    if use_root_hash {
+        [
+            root_hash.to_vec(),
+            epoch.to_le_bytes().to_vec(),
+            round.to_le_bytes().to_vec(),
+        ]
+        .concat()
+    } else {
+        [epoch.to_le_bytes().to_vec(), round.to_le_bytes().to_vec()].concat()
+    }
+}
+
 pub(crate) fn extract_epoch_to_proposers_impl(
     next_epoch_states_and_cur_epoch_rounds: &[(&EpochState, u64)],
     epoch: u64,
     proposers: &[Author],
     needed_rounds: u64,
 ) -> Result<HashMap<u64, Vec<Author>>> {
-    let last_index = next_epoch_states_and_cur_epoch_rounds.len() - 1;
+    extract_epoch_to_proposers_from_validators(
+        &next_epoch_states_and_cur_epoch_rounds
+            .iter()
+            .map(|(next_epoch_state, cur_epoch_rounds)| {
+                (
+                    next_epoch_state.epoch,
+                    next_epoch_state
+                        .verifier
+                        .get_ordered_account_addresses_iter()
+                        .collect(),
+                    *cur_epoch_rounds,
+                )
+            })
+            .collect::<Vec<_>>(),
+        epoch,
+        proposers,
+        needed_rounds,
+    )
+}
+
+/// Same as `extract_epoch_to_proposers_impl`, with the epoch and the ordered proposers of each
+/// next epoch state.
+pub(crate) fn extract_epoch_to_proposers_from_validators(
+    next_epochs_and_cur_epoch_rounds: &[(u64, Vec<Author>, u64)],
+    epoch: u64,
+    proposers: &[Author],
+    needed_rounds: u64,
+) -> Result<HashMap<u64, Vec<Author>>> {
+    let last_index = next_epochs_and_cur_epoch_rounds.len() - 1;
     let mut num_rounds = 0;
     let mut result = HashMap::new();
-    for (index, (next_epoch_state, cur_epoch_rounds)) in next_epoch_states_and_cur_epoch_rounds
-        .iter()
-        .enumerate()
-        .rev()
+    for (index, (next_epoch, next_epoch_proposers, cur_epoch_rounds)) in
+        next_epochs_and_cur_epoch_rounds.iter().enumerate().rev()
     {
-        let next_epoch_proposers = next_epoch_state
-            .verifier
-            .get_ordered_account_addresses_iter()
-            .collect::<Vec<_>>();
         if index == last_index {
             ensure!(
-                epoch == next_epoch_state.epoch,
+                epoch == *next_epoch,
                 "fetched epoch_ending ledger_infos are for a wrong epoch {} vs {}",
                 epoch,
-                next_epoch_state.epoch
+                next_epoch
             );
             ensure!(
-                proposers == next_epoch_proposers,
+                proposers == next_epoch_proposers.as_slice(),
                 "proposers from state and fetched epoch_ending ledger_infos are missaligned"
             );
         }
-        result.insert(next_epoch_state.epoch, next_epoch_proposers);
+        result.insert(*next_epoch, next_epoch_proposers.clone());
 
         if num_rounds > needed_rounds {
             break;
@@ -0,0 +1,399 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    epoch_manager::PROPSER_ROUND_BEHIND_STORAGE_BUFFER,
+    liveness::{
+        leader_reputation::{
+            extract_epoch_to_proposers_from_validators, fetch_new_block_events,
+            leader_election_seed, select_window, LeaderReputationHeuristicConfig,
+            ReputationHeuristic, VersionedNewBlockEvent,
+        },
+        proposer_election::choose_index,
+    },
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    epoch_state::EpochState,
+    on_chain_config::{LeaderReputationType, OnChainConsensusConfig, ProposerElectionType},
+};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+/// The validator set of an epoch, as needed to reproduce its leader elections
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct EpochValidators {
+    /// Epoch of the validator set
+    pub epoch: u64,
+    /// Validators, in the order of the validator set
+    pub validators: Vec<Author>,
+    /// Voting powers of the validators
+    pub voting_powers: Vec<u64>,
+    /// Round of the epoch ending ledger info, None if the epoch didn't end
+    pub last_round: Option<Round>,
+}
+
+/// The on-chain parameters of the leader reputation, which can be changed to simulate a
+/// different config.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct LeaderReputationParams {
+    /// Heuristic and its parameters
+    pub leader_reputation_type: LeaderReputationType,
+    /// Number of most recent rounds excluded from the history
+    pub exclude_round: u64,
+    /// Max number of failed proposers stored in a block
+    pub max_failed_authors_to_store: usize,
+}
+
+impl LeaderReputationParams {
+    /// Returns the parameters of the on-chain consensus config, if it uses leader reputation
+    pub fn from_onchain_config(onchain_config: &OnChainConsensusConfig) -> Result<Self> {
+        match onchain_config.proposer_election_type() {
+            ProposerElectionType::LeaderReputation(leader_reputation_type) => Ok(Self {
+                leader_reputation_type: leader_reputation_type.clone(),
+                exclude_round: onchain_config.leader_reputation_exclude_round(),
+                max_failed_authors_to_store: onchain_config.max_failed_authors_to_store(),
+            }),
+            proposer_election_type => bail!(
+                "Proposer election doesn't use leader reputation: {:?}",
+                proposer_election_type
+            ),
+        }
+    }
+}
+
+/// The history of a validator in the reputation window, and the resulting weights
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorReputation {
+    /// Validator
+    pub author: Author,
+    /// Voting power of the validator in the epoch
+    pub voting_power: u64,
+    /// Number of successful votes in the voter window
+    pub votes: u32,
+    /// Number of successful proposals in the proposer window
+    pub proposals: u32,
+    /// Number of failed proposals in the proposer window
+    pub failed_proposals: u32,
+    /// Weight assigned by the reputation heuristic
+    pub reputation_weight: u64,
+    /// Reputation weight multiplied by the voting power, if weighted by voting power
+    pub selection_weight: u128,
+    /// Probability to be elected in any round with the same history
+    pub selection_probability: f64,
+}
+
+/// The inputs and the outcome of the leader election of a round
+#[derive(Clone, Debug, Serialize)]
+pub struct LeaderElectionExplanation {
+    /// Epoch of the election
+    pub epoch: u64,
+    /// Round of the election
+    pub round: Round,
+    /// Parameters used for the election
+    pub params: LeaderReputationParams,
+    /// Last round of the history used for the election
+    pub target_round: Round,
+    /// Number of NewBlockEvents needed by the heuristic
+    pub window_size: usize,
+    /// Epoch and round of the NewBlockEvents in the window, from the most recent
+    pub window: Vec<(u64, Round)>,
+    /// Max version of the NewBlockEvents in the window, whose accumulator root hash seeds
+    /// the election
+    pub window_version: u64,
+    /// Accumulator root hash at the window version
+    pub root_hash: HashValue,
+    /// Epochs whose NewBlockEvents are considered
+    pub epochs_considered: Vec<u64>,
+    /// Elected proposer
+    pub proposer: Author,
+    /// Proposer of the committed block of the round, if any
+    pub committed_proposer: Option<Author>,
+    /// History and weights of the validators of the epoch
+    pub validators: Vec<ValidatorReputation>,
+}
+
+/// Reproduces the `LeaderReputation` computation from the committed NewBlockEvents and the
+/// validator sets of the epochs. The computation is the same as the one of the validators,
+/// assuming that their storage was up to date when electing the leader.
+pub struct LeaderReputationExplainer {
+    epochs: BTreeMap<u64, EpochValidators>,
+    /// NewBlockEvents, from the most recent
+    history: Vec<VersionedNewBlockEvent>,
+}
+
+impl LeaderReputationExplainer {
+    /// Creates the explainer from the validator sets and the NewBlockEvents of the epochs
+    pub fn new(epochs: Vec<EpochValidators>, mut history: Vec<VersionedNewBlockEvent>) -> Self {
+        history.sort_by_key(|event| Reverse((event.event.epoch(), event.event.round())));
+        Self {
+            epochs: epochs
+                .into_iter()
+                .map(|validators| (validators.epoch, validators))
+                .collect(),
+            history,
+        }
+    }
+
+    /// Reads the validator sets of the epochs since `start_epoch` and the last `num_events`
+    /// NewBlockEvents at or before `ledger_version` from the DB.
+    pub fn from_db(
+        aptos_db: &dyn DbReader,
+        start_epoch: u64,
+        num_events: u64,
+        ledger_version: u64,
+    ) -> Result<Self> {
+        let history = fetch_new_block_events(aptos_db, num_events, ledger_version)?;
+
+        let latest_epoch_state = aptos_db.get_latest_epoch_state()?;
+        let mut epochs = BTreeMap::new();
+        let mut add_epoch_state = |epoch_state: &EpochState| {
+            let validators: Vec<_> = epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .collect();
+            let voting_powers = validators
+                .iter()
+                .map(|validator| {
+                    epoch_state
+                        .verifier
+                        .get_voting_power(validator)
+                        .unwrap_or_default()
+                })
+                .collect();
+            epochs.insert(epoch_state.epoch, EpochValidators {
+                epoch: epoch_state.epoch,
+                validators,
+                voting_powers,
+                last_round: None,
+            });
+        };
+        let mut last_rounds = vec![];
+        if start_epoch < latest_epoch_state.epoch {
+            let proof = aptos_db.get_epoch_ending_ledger_infos(
+                start_epoch.saturating_sub(1),
+                latest_epoch_state.epoch,
+            )?;
+            for ledger_info in &proof.ledger_info_with_sigs {
+                let ledger_info = ledger_info.ledger_info();
+                last_rounds.push((ledger_info.epoch(), ledger_info.round()));
+                if let Some(next_epoch_state) = ledger_info.next_epoch_state() {
+                    add_epoch_state(next_epoch_state);
+                }
+            }
+        }
+        add_epoch_state(&latest_epoch_state);
+        for (epoch, last_round) in last_rounds {
+            if let Some(validators) = epochs.get_mut(&epoch) {
+                validators.last_round = Some(last_round);
+            }
+        }
+
+        Ok(Self::new(epochs.into_values().collect(), history))
+    }
+
+    /// Returns the number of NewBlockEvents the validators read to elect the leaders of an
+    /// epoch with `num_validators` validators.
+    pub fn num_events_needed(params: &LeaderReputationParams, num_validators: usize) -> u64 {
+        let heuristic_config = LeaderReputationHeuristicConfig::new(
+            Author::ZERO,
+            num_validators,
+            &params.leader_reputation_type,
+        );
+        (heuristic_config.window_size + seek_len(params)) as u64
+    }
+
+    /// Returns the version whose accumulator root hash seeds the election of the round
+    pub fn window_version(
+        &self,
+        params: &LeaderReputationParams,
+        epoch: u64,
+        round: Round,
+    ) -> Result<u64> {
+        let heuristic_config = self.heuristic_config(params, epoch)?;
+        let (_, window_version) = select_window(
+            &self.history,
+            epoch,
+            round.saturating_sub(params.exclude_round),
+            heuristic_config.window_size,
+        );
+        Ok(window_version)
+    }
+
+    /// Reproduces the leader election of the round, where `root_hash` is the accumulator root
+    /// hash at the `window_version` (only used if the leader reputation type seeds with it).
+    pub fn explain(
+        &self,
+        params: &LeaderReputationParams,
+        epoch: u64,
+        round: Round,
+        root_hash: HashValue,
+    ) -> Result<LeaderElectionExplanation> {
+        let heuristic_config = self.heuristic_config(params, epoch)?;
+        let epoch_validators = &self.epochs[&epoch];
+        let epoch_to_proposers = self.epoch_to_proposers(
+            epoch,
+            heuristic_config.first_epoch_to_consider(epoch),
+            (heuristic_config.window_size + seek_len(params)) as u64,
+        )?;
+
+        let target_round = round.saturating_sub(params.exclude_round);
+        let (window, window_version) = select_window(
+            &self.history,
+            epoch,
+            target_round,
+            heuristic_config.window_size,
+        );
+
+        let heuristic = &heuristic_config.heuristic;
+        let reputation_weights = heuristic.get_weights(epoch, &epoch_to_proposers, &window);
+        let votes = heuristic
+            .aggregation()
+            .count_votes(&epoch_to_proposers, &window);
+        let proposals = heuristic
+            .aggregation()
+            .count_proposals(&epoch_to_proposers, &window);
+        let failed_proposals = heuristic
+            .aggregation()
+            .count_failed_proposals(&epoch_to_proposers, &window);
+
+        let selection_weights: Vec<u128> = reputation_weights
+            .iter()
+            .zip(epoch_validators.voting_powers.iter())
+            .map(|(weight, voting_power)| {
+                if heuristic_config.weight_by_voting_power {
+                    *weight as u128 * *voting_power as u128
+                } else {
+                    *weight as u128
+                }
+            })
+            .collect();
+        let total_weight: u128 = selection_weights.iter().sum();
+        ensure!(total_weight > 0, "All the validators have a zero weight");
+
+        let seed = leader_election_seed(
+            params.leader_reputation_type.use_root_hash_for_seed(),
+            root_hash,
+            epoch,
+            round,
+        );
+        let proposer = epoch_validators.validators[choose_index(selection_weights.clone(), seed)];
+        let committed_proposer = self
+            .history
+            .iter()
+            .find(|event| event.event.epoch() == epoch && event.event.round() == round)
+            .map(|event| event.event.proposer());
+
+        let validators = epoch_validators
+            .validators
+            .iter()
+            .enumerate()
+            .map(|(index, author)| ValidatorReputation {
+                author: *author,
+                voting_power: epoch_validators.voting_powers[index],
+                votes: votes.get(author).copied().unwrap_or_default(),
+                proposals: proposals.get(author).copied().unwrap_or_default(),
+                failed_proposals: failed_proposals.get(author).copied().unwrap_or_default(),
+                reputation_weight: reputation_weights[index],
+                selection_weight: selection_weights[index],
+                selection_probability: selection_weights[index] as f64 / total_weight as f64,
+            })
+            .collect();
+
+        let mut epochs_considered: Vec<_> = epoch_to_proposers.into_keys().collect();
+        epochs_considered.sort_unstable();
+        Ok(LeaderElectionExplanation {
+            epoch,
+            round,
+            params: params.clone(),
+            target_round,
+            window_size: heuristic_config.window_size,
+            window: window
+                .iter()
+                .map(|event| (event.epoch(), event.round()))
+                .collect(),
+            window_version,
+            root_hash,
+            epochs_considered,
+            proposer,
+            committed_proposer,
+            validators,
+        })
+    }
+
+    fn heuristic_config(
+        &self,
+        params: &LeaderReputationParams,
+        epoch: u64,
+    ) -> Result<LeaderReputationHeuristicConfig> {
+        let epoch_validators = self
+            .epochs
+            .get(&epoch)
+            .ok_or_else(|| anyhow!("Validators of epoch {} are unknown", epoch))?;
+        Ok(LeaderReputationHeuristicConfig::new(
+            // The author only selects which node reports the metrics
+            Author::ZERO,
+            epoch_validators.validators.len(),
+            &params.leader_reputation_type,
+        ))
+    }
+
+    /// Returns the proposers of the epochs whose history is considered, the same way as the
+    /// epoch manager does from the epoch ending ledger infos.
+    fn epoch_to_proposers(
+        &self,
+        epoch: u64,
+        first_epoch_to_consider: u64,
+        needed_rounds: u64,
+    ) -> Result<HashMap<u64, Vec<Author>>> {
+        let proposers = &self.epochs[&epoch].validators;
+        if epoch <= first_epoch_to_consider {
+            return Ok(HashMap::from([(epoch, proposers.clone())]));
+        }
+
+        let next_epochs_and_cur_epoch_rounds = (first_epoch_to_consider..=epoch)
+            .map(|next_epoch| {
+                let next_epoch_validators = self
+                    .epochs
+                    .get(&next_epoch)
+                    .ok_or_else(|| anyhow!("Validators of epoch {} are unknown", next_epoch))?;
+                // The rounds of the epoch before the first one considered are never used
+                let cur_epoch_rounds = if next_epoch == first_epoch_to_consider {
+                    0
+                } else {
+                    self.epochs
+                        .get(&(next_epoch - 1))
+                        .and_then(|validators| validators.last_round)
+                        .ok_or_else(|| {
+                            anyhow!("Last round of epoch {} is unknown", next_epoch - 1)
+                        })?
+                };
+                Ok((
+                    next_epoch,
+                    next_epoch_validators.validators.clone(),
+                    cur_epoch_rounds,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        extract_epoch_to_proposers_from_validators(
+            &next_epochs_and_cur_epoch_rounds,
+            epoch,
+            proposers,
+            needed_rounds,
+        )
+    }
+}
+
+/// Returns the number of rounds the validators read on top of the window, the same way as the
+/// epoch manager does.
+fn seek_len(params: &LeaderReputationParams) -> usize {
+    params.exclude_round as usize
+        + params.max_failed_authors_to_store
+        + PROPSER_ROUND_BEHIND_STORAGE_BUFFER
+}
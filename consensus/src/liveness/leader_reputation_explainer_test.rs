@@ -0,0 +1,216 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    leader_reputation::{
+        select_window, LeaderReputation, MetadataBackend, ProposerAndVoterHeuristic,
+        VersionedNewBlockEvent,
+    },
+    leader_reputation_explainer::{
+        EpochValidators, LeaderReputationExplainer, LeaderReputationParams,
+    },
+    proposer_election::ProposerElection,
+};
+use aptos_bitvec::BitVec;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_config::NewBlockEvent,
+    on_chain_config::{LeaderReputationType, ProposerAndVoterConfig},
+};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+struct InMemoryBackend {
+    history: Vec<VersionedNewBlockEvent>,
+    window_size: usize,
+    root_hash: HashValue,
+}
+
+impl MetadataBackend for InMemoryBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let (window, _) =
+            select_window(&self.history, target_epoch, target_round, self.window_size);
+        (window, self.root_hash)
+    }
+}
+
+/// Creates the blocks of an epoch, where the validator at index 3 never proposes and the
+/// validators after it never vote nor propose.
+fn create_epoch_history(
+    epoch: u64,
+    validators: &[Author],
+    num_rounds: Round,
+    version: &mut u64,
+) -> Vec<VersionedNewBlockEvent> {
+    let mut events = vec![];
+    let mut failed_proposer_indices = vec![];
+    for round in 1..=num_rounds {
+        let leader = (round % 4) as usize;
+        if leader == 3 {
+            failed_proposer_indices.push(3);
+            continue;
+        }
+        let mut votes = BitVec::with_num_bits(validators.len() as u16);
+        for voter in 0..4 {
+            votes.set(voter);
+        }
+        *version += 1;
+        events.push(VersionedNewBlockEvent {
+            event: NewBlockEvent::new(
+                Author::random(),
+                epoch,
+                round,
+                *version,
+                votes.into(),
+                validators[leader],
+                std::mem::take(&mut failed_proposer_indices),
+                *version,
+            ),
+            version: *version,
+        });
+    }
+    events
+}
+
+fn leader_reputation_params(failed_weight: u64) -> LeaderReputationParams {
+    LeaderReputationParams {
+        leader_reputation_type: LeaderReputationType::ProposerAndVoterV2(ProposerAndVoterConfig {
+            active_weight: 1000,
+            inactive_weight: 10,
+            failed_weight,
+            failure_threshold_percent: 10,
+            proposer_window_num_validators_multiplier: 10,
+            voter_window_num_validators_multiplier: 1,
+            weight_by_voting_power: true,
+            use_history_from_previous_epoch_max_count: 5,
+        }),
+        exclude_round: 4,
+        max_failed_authors_to_store: 10,
+    }
+}
+
+#[test]
+fn test_explainer_matches_leader_reputation() {
+    let validators: Vec<Author> = (0..5).map(|_| Author::random()).sorted().collect();
+    let epoch_2_validators = validators[..4].to_vec();
+    let voting_powers: Vec<u64> = (1..=5).collect();
+
+    let mut version = 0;
+    let mut history = create_epoch_history(2, &epoch_2_validators, 20, &mut version);
+    history.extend(create_epoch_history(3, &validators, 40, &mut version));
+    let epochs = vec![
+        EpochValidators {
+            epoch: 2,
+            validators: epoch_2_validators.clone(),
+            voting_powers: voting_powers[..4].to_vec(),
+            last_round: Some(20),
+        },
+        EpochValidators {
+            epoch: 3,
+            validators: validators.clone(),
+            voting_powers: voting_powers.clone(),
+            last_round: None,
+        },
+    ];
+    let explainer = LeaderReputationExplainer::new(epochs, history.clone());
+
+    let params = leader_reputation_params(1);
+    let window_size = validators.len() * 10;
+    let root_hash = HashValue::random();
+    history.reverse();
+    let leader_reputation = LeaderReputation::new(
+        3,
+        HashMap::from([(2, epoch_2_validators), (3, validators.clone())]),
+        voting_powers.clone(),
+        Box::new(InMemoryBackend {
+            history,
+            window_size,
+            root_hash,
+        }),
+        Box::new(ProposerAndVoterHeuristic::new(
+            validators[0],
+            1000,
+            10,
+            1,
+            10,
+            validators.len(),
+            window_size,
+            false,
+        )),
+        params.exclude_round,
+        true,
+        30,
+    );
+
+    for round in 1..50 {
+        let explanation = explainer.explain(&params, 3, round, root_hash).unwrap();
+        assert_eq!(
+            explanation.proposer,
+            leader_reputation.get_valid_proposer(round)
+        );
+        assert_eq!(explanation.epochs_considered, vec![2, 3]);
+    }
+
+    let explanation = explainer.explain(&params, 3, 41, root_hash).unwrap();
+    assert_eq!(explanation.target_round, 37);
+    assert_eq!(explanation.window.first(), Some(&(3, 37)));
+    // Rounds 1 to 37 of epoch 3 and the whole epoch 2, without the failed rounds
+    assert_eq!(explanation.window.len(), 28 + 15);
+    assert_eq!(explanation.committed_proposer, None);
+    assert_eq!(
+        explainer
+            .explain(&params, 3, 1, root_hash)
+            .unwrap()
+            .committed_proposer,
+        Some(validators[1])
+    );
+
+    // The failing validator has the failed weight, the absent one the inactive weight
+    let reputation_weights: Vec<_> = explanation
+        .validators
+        .iter()
+        .map(|validator| validator.reputation_weight)
+        .collect();
+    assert_eq!(reputation_weights, vec![1000, 1000, 1000, 1, 10]);
+    assert_eq!(explanation.validators[3].proposals, 0);
+    assert!(explanation.validators[3].failed_proposals > 0);
+    assert_eq!(explanation.validators[4].votes, 0);
+    let total_probability: f64 = explanation
+        .validators
+        .iter()
+        .map(|validator| validator.selection_probability)
+        .sum();
+    assert!((total_probability - 1.0).abs() < 1e-9);
+
+    // Simulate a config that doesn't penalize failures
+    let simulated = explainer
+        .explain(&leader_reputation_params(1000), 3, 41, root_hash)
+        .unwrap();
+    assert_eq!(simulated.validators[3].reputation_weight, 1000);
+}
+
+#[test]
+fn test_explainer_missing_epochs() {
+    let validators: Vec<Author> = (0..4).map(|_| Author::random()).sorted().collect();
+    let mut version = 0;
+    let history = create_epoch_history(3, &validators, 10, &mut version);
+    let explainer = LeaderReputationExplainer::new(
+        vec![EpochValidators {
+            epoch: 3,
+            validators,
+            voting_powers: vec![1; 4],
+            last_round: None,
+        }],
+        history,
+    );
+
+    // The history of epoch 2 is considered, but its validators are unknown
+    let params = leader_reputation_params(1);
+    assert!(explainer.explain(&params, 3, 5, HashValue::zero()).is_err());
+    assert!(explainer.explain(&params, 4, 5, HashValue::zero()).is_err());
+}
@@ -4,6 +4,7 @@
 
 pub(crate) mod cached_proposer_election;
 pub(crate) mod leader_reputation;
+pub(crate) mod leader_reputation_explainer;
 pub(crate) mod proposal_generator;
 pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
@@ -14,6 +15,8 @@ pub(crate) mod unequivocal_proposer_election;
 #[cfg(test)]
 mod cached_proposer_election_test;
 #[cfg(test)]
+mod leader_reputation_explainer_test;
+#[cfg(test)]
 mod leader_reputation_test;
 #[cfg(test)]
 mod rotating_proposer_test;
@@ -17,7 +17,8 @@ use std::cmp::Ordering;
 // the same leader proposes multiple blocks.
 pub struct UnequivocalProposerElection {
     proposer_election: Box<dyn ProposerElection + Send + Sync>,
-    already_proposed: Mutex<(Round, HashValue)>,
+    // the first valid proposal of the latest round (kept as evidence in case of equivocation)
+    already_proposed: Mutex<Option<Block>>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
@@ -35,7 +36,7 @@ impl UnequivocalProposerElection {
     pub fn new(proposer_election: Box<dyn ProposerElection + Send + Sync>) -> Self {
         Self {
             proposer_election,
-            already_proposed: Mutex::new((0, HashValue::zero())),
+            already_proposed: Mutex::new(None),
         }
     }
 
@@ -60,21 +61,25 @@ impl UnequivocalProposerElection {
                 return false;
             }
             let mut already_proposed = self.already_proposed.lock();
+            let (proposed_round, proposed_id) = already_proposed
+                .as_ref()
+                .map_or((0, HashValue::zero()), |proposal| {
+                    (proposal.round(), proposal.id())
+                });
             // detect if the leader proposes more than once in this round
-            match block.round().cmp(&already_proposed.0) {
+            match block.round().cmp(&proposed_round) {
                 Ordering::Greater => {
-                    already_proposed.0 = block.round();
-                    already_proposed.1 = block.id();
+                    *already_proposed = Some(block.clone());
                     true
                 },
                 Ordering::Equal => {
-                    if already_proposed.1 != block.id() {
+                    if proposed_id != block.id() {
                         error!(
                             SecurityEvent::InvalidConsensusProposal,
                             "Multiple proposals from {} for round {}: {} and {}",
                             author,
                             block.round(),
-                            already_proposed.1,
+                            proposed_id,
                             block.id()
                         );
                         false
@@ -89,4 +94,18 @@ impl UnequivocalProposerElection {
             }
         })
     }
+
+    // Return the previously accepted proposal if the given block conflicts with it,
+    // i.e., it is a different block proposed by the same author for the same round.
+    pub fn get_conflicting_proposal(&self, block: &Block) -> Option<Block> {
+        self.already_proposed
+            .lock()
+            .as_ref()
+            .filter(|proposal| {
+                proposal.round() == block.round()
+                    && proposal.author() == block.author()
+                    && proposal.id() != block.id()
+            })
+            .cloned()
+    }
 }
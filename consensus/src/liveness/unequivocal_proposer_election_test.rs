@@ -97,6 +97,14 @@ fn test_is_valid_proposal() {
     // good proposal still passes
     assert!(pe.is_valid_proposal(&good_proposal));
 
+    // the duplicate proposal conflicts with the good one, the others don't
+    assert_eq!(
+        pe.get_conflicting_proposal(&bad_duplicate_proposal),
+        Some(good_proposal.clone())
+    );
+    assert_eq!(pe.get_conflicting_proposal(&good_proposal), None);
+    assert_eq!(pe.get_conflicting_proposal(&bad_author_proposal), None);
+
     // going to the next round:
     assert!(pe.is_valid_proposal(&next_good_proposal));
     assert!(!pe.is_valid_proposal(&next_bad_duplicate_proposal));
@@ -5,6 +5,7 @@
 use crate::{monitor, persistent_liveness_storage::PersistentLivenessStorage};
 use aptos_consensus_types::{
     block_data::BlockData,
+    dag::NodeMetadata,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
     vote_proposal::VoteProposal,
@@ -131,6 +132,23 @@ impl TSafetyRules for MetricsSafetyRules {
             )
         })
     }
+
+    fn sign_dag_vote(&mut self, metadata: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+        self.retry(|inner| monitor!("safety_rules", inner.sign_dag_vote(metadata)))
+    }
+
+    fn sign_dag_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfo,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        self.retry(|inner| {
+            monitor!(
+                "safety_rules",
+                inner.sign_dag_commit_vote(ordered_ledger_info.clone(), new_ledger_info.clone())
+            )
+        })
+    }
 }
 
 #[cfg(test)]
@@ -138,6 +156,7 @@ mod tests {
     use crate::{metrics_safety_rules::MetricsSafetyRules, test_utils::EmptyStorage};
     use aptos_consensus_types::{
         block_data::BlockData,
+        dag::NodeMetadata,
         timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
         vote::Vote,
         vote_proposal::VoteProposal,
@@ -220,6 +239,18 @@ mod tests {
         ) -> Result<bls12381::Signature, Error> {
             unimplemented!()
         }
+
+        fn sign_dag_vote(&mut self, _: &NodeMetadata) -> Result<bls12381::Signature, Error> {
+            unimplemented!()
+        }
+
+        fn sign_dag_commit_vote(
+            &mut self,
+            _: LedgerInfo,
+            _: LedgerInfo,
+        ) -> Result<bls12381::Signature, Error> {
+            unimplemented!()
+        }
     }
 
     #[test]
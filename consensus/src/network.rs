@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::tracing::{observe_block, BlockStage},
+    block_storage::tracing::{observe_unverified_block, BlockStage},
     counters,
+    dag::DAGNetworkSender,
     logging::LogEvent,
     monitor,
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
@@ -16,6 +17,7 @@ use aptos_config::network_id::NetworkId;
 use aptos_consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, MAX_BLOCKS_PER_REQUEST},
     common::Author,
+    dag::DAGNetworkMessage,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
     proof_of_store::{ProofOfStore, ProofOfStoreMsg, SignedBatchInfo, SignedBatchInfoMsg},
     proposal_msg::ProposalMsg,
@@ -60,6 +62,14 @@ pub struct IncomingBatchRetrievalRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+#[derive(Debug)]
+pub struct IncomingDAGRequest {
+    pub req: DAGNetworkMessage,
+    pub sender: Author,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 #[derive(Debug)]
 pub enum IncomingRpcRequest {
     BlockRetrieval(IncomingBlockRetrievalRequest),
@@ -83,6 +93,7 @@ pub struct NetworkReceivers {
         (AccountAddress, ConsensusMsg),
     >,
     pub rpc_rx: aptos_channel::Receiver<AccountAddress, (AccountAddress, IncomingRpcRequest)>,
+    pub dag_rpc_rx: aptos_channel::Receiver<AccountAddress, (AccountAddress, IncomingDAGRequest)>,
 }
 
 #[async_trait::async_trait]
@@ -116,7 +127,8 @@ pub struct NetworkSender {
     consensus_network_client: ConsensusNetworkClient<NetworkClient<ConsensusMsg>>,
     // Self sender and self receivers provide a shortcut for sending the messages to itself.
     // (self sending is not supported by the networking API).
-    // Note that we do not support self rpc requests as it might cause infinite recursive calls.
+    // Note that self rpc requests are only supported for the DAG messages, other rpc requests
+    // might cause infinite recursive calls.
     self_sender: aptos_channels::Sender<Event<ConsensusMsg>>,
     validators: ValidatorVerifier,
 }
@@ -380,6 +392,39 @@ impl QuorumStoreSender for NetworkSender {
     }
 }
 
+#[async_trait::async_trait]
+impl DAGNetworkSender for NetworkSender {
+    async fn send_rpc(
+        &self,
+        receiver: Author,
+        message: ConsensusMsg,
+        timeout: Duration,
+    ) -> anyhow::Result<ConsensusMsg> {
+        fail_point!("consensus::send::any", |_| {
+            Err(anyhow::anyhow!("Injected error in send_rpc"))
+        });
+        counters::CONSENSUS_SENT_MSGS
+            .with_label_values(&[message.name()])
+            .inc();
+        if receiver != self.author {
+            return Ok(self
+                .consensus_network_client
+                .send_rpc(receiver, message, timeout)
+                .await?);
+        }
+
+        // Self rpc goes through the self channel, the response is delivered via the callback.
+        let (tx, rx) = oneshot::channel();
+        let protocol = ProtocolId::ConsensusRpcBcs;
+        let self_msg = Event::RpcRequest(self.author, message, protocol, tx);
+        self.self_sender.clone().send(self_msg).await?;
+        let response_bytes = tokio::time::timeout(timeout, rx)
+            .await
+            .map_err(|_| anyhow!("Timeout on self rpc"))???;
+        protocol.from_bytes(&response_bytes)
+    }
+}
+
 pub struct NetworkTask {
     consensus_messages_tx: aptos_channel::Sender<
         (AccountAddress, Discriminant<ConsensusMsg>),
@@ -394,6 +439,7 @@ pub struct NetworkTask {
         (AccountAddress, ConsensusMsg),
     >,
     rpc_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, IncomingRpcRequest)>,
+    dag_rpc_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, IncomingDAGRequest)>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
 }
 
@@ -421,6 +467,10 @@ impl NetworkTask {
         );
         let (rpc_tx, rpc_rx) =
             aptos_channel::new(QueueStyle::LIFO, 1, Some(&counters::RPC_CHANNEL_MSGS));
+        // The DAG keeps several rpcs in flight to each peer (node broadcast, certified node
+        // broadcast and fetch), which must not be dropped in favor of the latest one.
+        let (dag_rpc_tx, dag_rpc_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 10, Some(&counters::DAG_RPC_CHANNEL_MSGS));
 
         // Verify the network events have been constructed correctly
         let network_and_events = network_service_events.into_network_and_events();
@@ -441,6 +491,7 @@ impl NetworkTask {
                 buffer_manager_messages_tx,
                 quorum_store_messages_tx,
                 rpc_tx,
+                dag_rpc_tx,
                 all_events,
             },
             NetworkReceivers {
@@ -448,6 +499,7 @@ impl NetworkTask {
                 buffer_manager_messages,
                 quorum_store_messages,
                 rpc_rx,
+                dag_rpc_rx,
             },
         )
     }
@@ -476,7 +528,9 @@ impl NetworkTask {
                         .with_label_values(&[msg.name()])
                         .inc();
                     match msg {
-                        ConsensusMsg::BatchRequestMsg(_) | ConsensusMsg::BatchResponse(_) => {
+                        ConsensusMsg::BatchRequestMsg(_)
+                        | ConsensusMsg::BatchResponse(_)
+                        | ConsensusMsg::DAGMessage(_) => {
                             warn!("unexpected rpc msg");
                         },
                         quorum_store_msg @ (ConsensusMsg::SignedBatchInfo(_)
@@ -498,8 +552,8 @@ impl NetworkTask {
                         },
                         consensus_msg => {
                             if let ConsensusMsg::ProposalMsg(proposal) = &consensus_msg {
-                                observe_block(
-                                    proposal.proposal().timestamp_usecs(),
+                                observe_unverified_block(
+                                    proposal.proposal(),
                                     BlockStage::NETWORK_RECEIVED,
                                 );
                             }
@@ -556,6 +610,21 @@ impl NetworkTask {
                             warn!(error = ?e, "aptos channel closed");
                         }
                     },
+                    ConsensusMsg::DAGMessage(request) => {
+                        counters::CONSENSUS_RECEIVED_MSGS
+                            .with_label_values(&["DAGRequest"])
+                            .inc();
+                        let req_with_callback = IncomingDAGRequest {
+                            req: request,
+                            sender: peer_id,
+                            protocol,
+                            response_sender: callback,
+                        };
+                        if let Err(e) = self.dag_rpc_tx.push(peer_id, (peer_id, req_with_callback))
+                        {
+                            warn!(error = ?e, "aptos channel closed");
+                        }
+                    },
                     _ => {
                         warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                         continue;
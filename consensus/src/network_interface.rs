@@ -8,6 +8,7 @@ use crate::quorum_store::types::{Batch, BatchMsg, BatchRequest};
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    dag::DAGNetworkMessage,
     epoch_retrieval::EpochRetrievalRequest,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
     proof_of_store::{ProofOfStoreMsg, SignedBatchInfoMsg},
@@ -61,6 +62,8 @@ pub enum ConsensusMsg {
     SignedBatchInfo(Box<SignedBatchInfoMsg>),
     /// Quorum Store: Broadcast a certified proof of store (a digest that received 2f+1 votes).
     ProofOfStoreMsg(Box<ProofOfStoreMsg>),
+    /// DAG protocol message
+    DAGMessage(DAGNetworkMessage),
     #[cfg(test)]
     DAGTestMessage(Vec<u8>),
 }
@@ -85,6 +88,7 @@ impl ConsensusMsg {
             ConsensusMsg::BatchResponse(_) => "BatchResponse",
             ConsensusMsg::SignedBatchInfo(_) => "SignedBatchInfo",
             ConsensusMsg::ProofOfStoreMsg(_) => "ProofOfStoreMsg",
+            ConsensusMsg::DAGMessage(_) => "DAGMessage",
             #[cfg(test)]
             ConsensusMsg::DAGTestMessage(_) => "DAGTestMessage",
         }
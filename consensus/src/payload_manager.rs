@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    consensus_observer::ObserverPayloadStore,
     counters,
     network::NetworkSender,
     quorum_store::{
@@ -24,9 +25,11 @@ use tokio::sync::oneshot;
 
 /// Responsible to extract the transactions out of the payload and notify QuorumStore about commits.
 /// If QuorumStore is enabled, has to ask BatchReader for the transaction behind the proofs of availability in the payload.
+/// Consensus observers receive the transactions along with the blocks, so they look them up locally.
 pub enum PayloadManager {
     DirectMempool,
     InQuorumStore(Arc<BatchStore<NetworkSender>>, Sender<CoordinatorCommand>),
+    ConsensusObserver(Arc<ObserverPayloadStore>),
 }
 
 impl PayloadManager {
@@ -58,7 +61,7 @@ impl PayloadManager {
     ///Pass commit information to BatchReader and QuorumStore wrapper for their internal cleanups.
     pub async fn notify_commit(&self, block_timestamp: u64, payloads: Vec<Payload>) {
         match self {
-            PayloadManager::DirectMempool => {},
+            PayloadManager::DirectMempool | PayloadManager::ConsensusObserver(_) => {},
             PayloadManager::InQuorumStore(batch_store, coordinator_tx) => {
                 // TODO: move this to somewhere in quorum store, so this can be a batch reader
                 batch_store
@@ -94,6 +97,14 @@ impl PayloadManager {
         }
     }
 
+    /// Returns the batch reader of quorum store (if it is enabled)
+    pub fn batch_reader(&self) -> Option<Arc<dyn BatchReader>> {
+        match self {
+            PayloadManager::DirectMempool | PayloadManager::ConsensusObserver(_) => None,
+            PayloadManager::InQuorumStore(batch_store, _) => Some(batch_store.clone()),
+        }
+    }
+
     /// Called from consensus to pre-fetch the transaction behind the batches in the block.
     pub async fn prefetch_payload_data(&self, block: &Block) {
         let payload = match block.payload() {
@@ -101,7 +112,7 @@ impl PayloadManager {
             None => return,
         };
         match self {
-            PayloadManager::DirectMempool => {},
+            PayloadManager::DirectMempool | PayloadManager::ConsensusObserver(_) => {},
             PayloadManager::InQuorumStore(batch_store, _) => match payload {
                 Payload::InQuorumStore(proof_with_status) => {
                     if proof_with_status.status.lock().is_none() {
@@ -134,6 +145,9 @@ impl PayloadManager {
 
         match (self, payload) {
             (PayloadManager::DirectMempool, Payload::DirectMempool(txns)) => Ok(txns.clone()),
+            (PayloadManager::ConsensusObserver(payload_store), _) => payload_store
+                .get_transactions(&block.id())
+                .ok_or_else(|| DataNotFound(block.id())),
             (
                 PayloadManager::InQuorumStore(batch_store, _),
                 Payload::InQuorumStore(proof_with_data),
@@ -11,6 +11,7 @@
 use crate::counters;
 use aptos_consensus_types::{
     common::Author,
+    equivocation_evidence::EquivocationEvidence,
     quorum_cert::QuorumCert,
     timeout_2chain::{TwoChainTimeoutCertificate, TwoChainTimeoutWithPartialSignatures},
     vote::Vote,
@@ -38,7 +39,8 @@ pub enum VoteReceptionResult {
     /// The very same vote message has been processed in past.
     DuplicateVote,
     /// The very same author has already voted for another proposal in this round (equivocation).
-    EquivocateVote,
+    /// Carries the conflicting pair of signed votes.
+    EquivocateVote(Box<EquivocationEvidence>),
     /// This block has just been certified after adding the vote.
     NewQuorumCertificate(Arc<QuorumCert>),
     /// The vote completes a new TwoChainTimeoutCertificate
@@ -100,6 +102,24 @@ impl PendingVotes {
         {
             // is it the same vote?
             if &li_digest == previous_li_digest {
+                // did the author time out again with a lower quorum cert? An honest author only
+                // re-signs its timeout with a higher one (e.g., after a restart). The order the
+                // timeouts were received in isn't signed, so this is only logged locally and
+                // never reported as evidence.
+                if let (Some((previous_timeout, _)), Some((timeout, _))) = (
+                    previously_seen_vote.two_chain_timeout(),
+                    vote.two_chain_timeout(),
+                ) {
+                    if timeout.hqc_round() < previous_timeout.hqc_round() {
+                        warn!(
+                            remote_peer = vote.author(),
+                            vote = vote,
+                            previous_vote = previously_seen_vote,
+                            "Received a timeout with a lower quorum cert than a previous one"
+                        );
+                    }
+                }
+
                 // we've already seen an equivalent vote before
                 let new_timeout_vote = vote.is_timeout() && !previously_seen_vote.is_timeout();
                 if !new_timeout_vote {
@@ -115,7 +135,10 @@ impl PendingVotes {
                     previous_vote = previously_seen_vote
                 );
 
-                return VoteReceptionResult::EquivocateVote;
+                return VoteReceptionResult::EquivocateVote(Box::new(EquivocationEvidence::Vote(
+                    previously_seen_vote.clone(),
+                    vote.clone(),
+                )));
             }
         }
 
@@ -339,7 +362,10 @@ impl fmt::Display for PendingVotes {
 mod tests {
     use super::{PendingVotes, VoteReceptionResult};
     use aptos_consensus_types::{
-        block::block_test_utils::certificate_for_genesis, vote::Vote, vote_data::VoteData,
+        block::block_test_utils::{certificate_for_genesis, placeholder_certificate_for_block},
+        equivocation_evidence::EquivocationEvidence,
+        vote::Vote,
+        vote_data::VoteData,
     };
     use aptos_crypto::HashValue;
     use aptos_types::{
@@ -401,7 +427,10 @@ mod tests {
         .unwrap();
         assert_eq!(
             pending_votes.insert_vote(&vote_data_2_author_0, &validator),
-            VoteReceptionResult::EquivocateVote
+            VoteReceptionResult::EquivocateVote(Box::new(EquivocationEvidence::Vote(
+                vote_data_1_author_0.clone(),
+                vote_data_2_author_0.clone()
+            )))
         );
 
         // a different author voting for a different result -> VoteAdded
@@ -506,4 +535,91 @@ mod tests {
             },
         };
     }
+
+    #[test]
+    /// Verify that a timeout re-signed with a lower quorum cert is not reported as equivocation
+    fn test_2chain_timeout_resigned_with_lower_quorum_cert() {
+        ::aptos_logger::Logger::init_for_testing();
+
+        // set up 4 validators
+        let (signers, validator) = random_validator_verifier(4, None, false);
+        let mut pending_votes = PendingVotes::new();
+
+        // submit a timeout vote from validator[0] with a quorum cert for round 1 -> VoteAdded
+        let li = random_ledger_info();
+        let vote_data = random_vote_data();
+        let vote = Vote::new(vote_data, signers[0].author(), li, &signers[0]).unwrap();
+        let mut timeout_vote_1 = vote.clone();
+        let quorum_cert = placeholder_certificate_for_block(
+            &signers,
+            HashValue::random(),
+            1,
+            HashValue::random(),
+            0,
+        );
+        let timeout = vote.generate_2chain_timeout(quorum_cert);
+        let signature = timeout.sign(&signers[0]).unwrap();
+        timeout_vote_1.add_2chain_timeout(timeout, signature);
+        assert_eq!(
+            pending_votes.insert_vote(&timeout_vote_1, &validator),
+            VoteReceptionResult::VoteAdded(1)
+        );
+
+        // the same timeout vote again -> DuplicateVote
+        assert_eq!(
+            pending_votes.insert_vote(&timeout_vote_1, &validator),
+            VoteReceptionResult::DuplicateVote
+        );
+
+        // the same vote with a timeout for a lower quorum cert -> DuplicateVote (the order of
+        // the timeouts isn't signed, so it can't be told apart from a legitimate re-sign)
+        let mut timeout_vote_2 = vote.clone();
+        let timeout = vote.generate_2chain_timeout(certificate_for_genesis());
+        let signature = timeout.sign(&signers[0]).unwrap();
+        timeout_vote_2.add_2chain_timeout(timeout, signature);
+        assert_eq!(
+            pending_votes.insert_vote(&timeout_vote_2, &validator),
+            VoteReceptionResult::DuplicateVote
+        );
+    }
+
+    #[test]
+    /// Verify that a timeout re-signed with a higher quorum cert is not reported as equivocation
+    fn test_2chain_timeout_resigned_with_higher_quorum_cert() {
+        ::aptos_logger::Logger::init_for_testing();
+
+        // set up 4 validators
+        let (signers, validator) = random_validator_verifier(4, None, false);
+        let mut pending_votes = PendingVotes::new();
+
+        // submit a timeout vote from validator[0] with the genesis quorum cert -> VoteAdded
+        let li = random_ledger_info();
+        let vote_data = random_vote_data();
+        let vote = Vote::new(vote_data, signers[0].author(), li, &signers[0]).unwrap();
+        let mut timeout_vote_1 = vote.clone();
+        let timeout = vote.generate_2chain_timeout(certificate_for_genesis());
+        let signature = timeout.sign(&signers[0]).unwrap();
+        timeout_vote_1.add_2chain_timeout(timeout, signature);
+        assert_eq!(
+            pending_votes.insert_vote(&timeout_vote_1, &validator),
+            VoteReceptionResult::VoteAdded(1)
+        );
+
+        // the same vote re-signed with a timeout for a higher quorum cert -> DuplicateVote
+        let mut timeout_vote_2 = vote.clone();
+        let quorum_cert = placeholder_certificate_for_block(
+            &signers,
+            HashValue::random(),
+            1,
+            HashValue::random(),
+            0,
+        );
+        let timeout = vote.generate_2chain_timeout(quorum_cert);
+        let signature = timeout.sign(&signers[0]).unwrap();
+        timeout_vote_2.add_2chain_timeout(timeout, signature);
+        assert_eq!(
+            pending_votes.insert_vote(&timeout_vote_2, &validator),
+            VoteReceptionResult::DuplicateVote
+        );
+    }
 }
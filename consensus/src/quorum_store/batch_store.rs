@@ -7,7 +7,7 @@ use crate::{
         batch_requester::BatchRequester,
         counters,
         quorum_store_db::QuorumStoreStorage,
-        types::{PersistedValue, StorageMode},
+        types::{Batch, PersistedValue, StorageMode},
         utils::TimeExpirations,
     },
 };
@@ -396,6 +396,9 @@ pub trait BatchReader: Send + Sync {
         &self,
         proof: ProofOfStore,
     ) -> oneshot::Receiver<Result<Vec<SignedTransaction>, Error>>;
+
+    /// Return the batch if it is stored locally, without requesting it from other validators
+    fn get_local_batch(&self, digest: &HashValue) -> Option<Batch>;
 }
 
 impl<T: QuorumStoreSender + Clone + Send + Sync + 'static> BatchReader for BatchStore<T> {
@@ -423,4 +426,10 @@ impl<T: QuorumStoreSender + Clone + Send + Sync + 'static> BatchReader for Batch
         }
         rx
     }
+
+    fn get_local_batch(&self, digest: &HashValue) -> Option<Batch> {
+        self.get_batch_from_local(digest)
+            .ok()
+            .and_then(|value| Batch::try_from(value).ok())
+    }
 }
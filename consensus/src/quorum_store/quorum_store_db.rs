@@ -40,16 +40,30 @@ pub struct QuorumStoreDB {
 
 impl QuorumStoreDB {
     pub(crate) fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        Self::open(db_root_path, false).expect("QuorumstoreDB open failed; unable to continue")
+    }
+
+    /// Opens the QuorumstoreDB under `db_root_path`. A read-only DB isn't created if missing,
+    /// and can be opened while the node is running.
+    pub(crate) fn open<P: AsRef<Path>>(db_root_path: P, readonly: bool) -> Result<Self> {
         let column_families = vec![BATCH_CF_NAME, BATCH_ID_CF_NAME];
 
         // TODO: this fails twins tests because it assumes a unique path per process
         let path = db_root_path.as_ref().join(QUORUM_STORE_DB_NAME);
         let instant = Instant::now();
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
-        opts.create_missing_column_families(true);
-        let db = DB::open(path.clone(), QUORUM_STORE_DB_NAME, column_families, &opts)
-            .expect("QuorumstoreDB open failed; unable to continue");
+        let db = if readonly {
+            DB::open_cf_readonly(
+                &Options::default(),
+                path.clone(),
+                "quorumstoreDB_readonly",
+                column_families,
+            )?
+        } else {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            opts.create_missing_column_families(true);
+            DB::open(path.clone(), QUORUM_STORE_DB_NAME, column_families, &opts)?
+        };
 
         info!(
             "Opened QuorumstoreDB at {:?} in {} ms",
@@ -57,7 +71,7 @@ impl QuorumStoreDB {
             instant.elapsed().as_millis()
         );
 
-        Self { db }
+        Ok(Self { db })
     }
 }
 
@@ -34,6 +34,10 @@ impl BatchReader for MockBatchReader {
     fn get_batch(&self, _proof: ProofOfStore) -> Receiver<Result<Vec<SignedTransaction>, Error>> {
         unimplemented!();
     }
+
+    fn get_local_batch(&self, _digest: &HashValue) -> Option<Batch> {
+        unimplemented!();
+    }
 }
 
 #[tokio::test(flavor = "multi_thread")]
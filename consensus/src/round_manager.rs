@@ -4,7 +4,7 @@
 
 use crate::{
     block_storage::{
-        tracing::{observe_block, BlockStage},
+        tracing::{observe_block, observe_block_info, BlockStage},
         BlockReader, BlockRetriever, BlockStore,
     },
     counters,
@@ -30,6 +30,7 @@ use aptos_config::config::ConsensusConfig;
 use aptos_consensus_types::{
     block::Block,
     common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
     proof_of_store::{ProofOfStoreMsg, SignedBatchInfoMsg},
     proposal_msg::ProposalMsg,
@@ -405,7 +406,7 @@ impl RoundManager {
         let signature = self.safety_rules.lock().sign_proposal(&proposal)?;
         let signed_proposal =
             Block::new_proposal_from_block_data_and_signature(proposal, signature);
-        observe_block(signed_proposal.timestamp_usecs(), BlockStage::SIGNED);
+        observe_block(&signed_proposal, BlockStage::SIGNED);
         info!(self.new_log(LogEvent::Propose), "{}", signed_proposal);
         Ok(ProposalMsg::new(
             signed_proposal,
@@ -421,10 +422,7 @@ impl RoundManager {
             Err(anyhow::anyhow!("Injected error in process_proposal_msg"))
         });
 
-        observe_block(
-            proposal_msg.proposal().timestamp_usecs(),
-            BlockStage::ROUND_MANAGER_RECEIVED,
-        );
+        observe_block(proposal_msg.proposal(), BlockStage::ROUND_MANAGER_RECEIVED);
         info!(
             self.new_log(LogEvent::ReceiveProposal)
                 .remote_peer(proposal_msg.proposer()),
@@ -663,6 +661,12 @@ impl RoundManager {
                 .max_receiving_block_bytes(self.onchain_config.quorum_store_enabled()),
         );
 
+        if let Some(first_proposal) = self.proposer_election.get_conflicting_proposal(&proposal) {
+            self.save_equivocation_evidence(&EquivocationEvidence::Proposal(
+                first_proposal,
+                proposal.clone(),
+            ));
+        }
         ensure!(
             self.proposer_election.is_valid_proposal(&proposal),
             "[RoundManager] Proposer {} for block {} is not a valid proposer for this round or created duplicate proposal",
@@ -695,7 +699,7 @@ impl RoundManager {
             self.round_state.current_round_deadline(),
         );
 
-        observe_block(proposal.timestamp_usecs(), BlockStage::SYNCED);
+        observe_block(&proposal, BlockStage::SYNCED);
         if self.decoupled_execution() && self.block_store.vote_back_pressure() {
             // In case of back pressure, we delay processing proposal. This is done by resending the
             // same proposal to self after some time. Even if processing proposal is delayed, we add
@@ -804,7 +808,7 @@ impl RoundManager {
             executed_block.block()
         ))?;
         if !executed_block.block().is_nil_block() {
-            observe_block(executed_block.block().timestamp_usecs(), BlockStage::VOTED);
+            observe_block(executed_block.block(), BlockStage::VOTED);
         }
 
         self.storage
@@ -886,10 +890,7 @@ impl RoundManager {
         {
             VoteReceptionResult::NewQuorumCertificate(qc) => {
                 if !vote.is_timeout() {
-                    observe_block(
-                        qc.certified_block().timestamp_usecs(),
-                        BlockStage::QC_AGGREGATED,
-                    );
+                    observe_block_info(qc.certified_block(), BlockStage::QC_AGGREGATED);
                 }
                 self.new_qc_aggregated(qc, vote.author()).await
             },
@@ -902,10 +903,24 @@ impl RoundManager {
             VoteReceptionResult::VoteAdded(_)
             | VoteReceptionResult::EchoTimeout(_)
             | VoteReceptionResult::DuplicateVote => Ok(()),
+            VoteReceptionResult::EquivocateVote(evidence) => {
+                self.save_equivocation_evidence(&evidence);
+                Err(anyhow::anyhow!("{}", evidence))
+            },
             e => Err(anyhow::anyhow!("{:?}", e)),
         }
     }
 
+    /// Records the evidence of an equivocating validator, so that it can be reported later
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) {
+        counters::EQUIVOCATION_EVIDENCE
+            .with_label_values(&[evidence.name()])
+            .inc();
+        if let Err(e) = self.storage.save_equivocation_evidence(evidence) {
+            warn!(error = ?e, "[RoundManager] Failed to save {}", evidence);
+        }
+    }
+
     async fn new_qc_aggregated(
         &mut self,
         qc: Arc<QuorumCert>,
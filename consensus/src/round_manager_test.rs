@@ -38,6 +38,7 @@ use aptos_consensus_types::{
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
     common::{Author, Payload, Round},
+    equivocation_evidence::EquivocationEvidence,
     experimental::commit_decision::CommitDecision,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
@@ -918,6 +919,51 @@ fn no_vote_on_invalid_proposer() {
     });
 }
 
+#[test]
+/// We record the evidence of a proposer that proposes two different blocks for the same round
+fn save_evidence_on_equivocating_proposal() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2, None);
+    let mut node = nodes.remove(0);
+    let genesis_qc = certificate_for_genesis();
+    let create_proposal = |timestamp| {
+        let block = Block::new_proposal(
+            Payload::empty(false),
+            1,
+            timestamp,
+            genesis_qc.clone(),
+            &node.signer,
+            Vec::new(),
+        )
+        .unwrap();
+        ProposalMsg::new(
+            block,
+            SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+        )
+    };
+    let first_proposal = create_proposal(1);
+    let second_proposal = create_proposal(2);
+    timed_block_on(&runtime, async {
+        node.round_manager
+            .process_proposal_msg(first_proposal.clone())
+            .await
+            .unwrap();
+        assert!(node
+            .round_manager
+            .process_proposal_msg(second_proposal.clone())
+            .await
+            .is_err());
+    });
+    assert_eq!(
+        *node.storage.shared_storage.equivocation_evidence.lock(),
+        vec![EquivocationEvidence::Proposal(
+            first_proposal.proposal().clone(),
+            second_proposal.proposal().clone()
+        )]
+    );
+}
+
 #[test]
 /// We allow to 'skip' round if proposal carries timeout certificate for next round
 fn new_round_on_timeout_certificate() {
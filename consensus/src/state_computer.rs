@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_partitioner::BlockPartitioner,
     block_storage::tracing::{observe_block, BlockStage},
     counters,
     error::StateSyncError,
@@ -17,7 +18,9 @@ use anyhow::Result;
 use aptos_consensus_notifications::ConsensusNotificationSender;
 use aptos_consensus_types::{block::Block, common::Round, executed_block::ExecutedBlock};
 use aptos_crypto::HashValue;
-use aptos_executor_types::{BlockExecutorTrait, Error as ExecutionError, StateComputeResult};
+use aptos_executor_types::{
+    BlockExecutorTrait, Error as ExecutionError, ExecutableBlock, StateComputeResult,
+};
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_types::{
@@ -60,6 +63,7 @@ pub struct ExecutionProxy {
     transaction_shuffler: Mutex<Option<Arc<dyn TransactionShuffler>>>,
     maybe_block_gas_limit: Mutex<Option<u64>>,
     transaction_deduper: Mutex<Option<Arc<dyn TransactionDeduper>>>,
+    block_partitioner: Mutex<Option<Arc<dyn BlockPartitioner>>>,
 }
 
 impl ExecutionProxy {
@@ -95,6 +99,7 @@ impl ExecutionProxy {
             transaction_shuffler: Mutex::new(None),
             maybe_block_gas_limit: Mutex::new(None),
             transaction_deduper: Mutex::new(None),
+            block_partitioner: Mutex::new(None),
         }
     }
 }
@@ -124,6 +129,7 @@ impl StateComputer for ExecutionProxy {
         let payload_manager = self.payload_manager.lock().as_ref().unwrap().clone();
         let txn_deduper = self.transaction_deduper.lock().as_ref().unwrap().clone();
         let txn_shuffler = self.transaction_shuffler.lock().as_ref().unwrap().clone();
+        let block_partitioner = self.block_partitioner.lock().as_ref().unwrap().clone();
         let txns = payload_manager.get_transactions(block).await?;
 
         let deduped_txns = txn_deduper.dedup(txns);
@@ -134,17 +140,18 @@ impl StateComputer for ExecutionProxy {
         // TODO: figure out error handling for the prologue txn
         let executor = self.executor.clone();
 
-        let transactions_to_execute = block.transactions_to_execute(
+        let transactions_to_execute = block_partitioner.partition(block.transactions_to_execute(
             &self.validators.lock(),
             shuffled_txns.clone(),
             block_gas_limit,
-        );
+        ));
 
+        observe_block(block, BlockStage::EXECUTION_STARTED);
         let compute_result = monitor!(
             "execute_block",
             tokio::task::spawn_blocking(move || {
                 executor.execute_block(
-                    (block_id, transactions_to_execute).into(),
+                    ExecutableBlock::new(block_id, transactions_to_execute),
                     parent_block_id,
                     block_gas_limit,
                 )
@@ -153,7 +160,7 @@ impl StateComputer for ExecutionProxy {
         )
         .expect("spawn_blocking failed")?;
 
-        observe_block(block.timestamp_usecs(), BlockStage::EXECUTED);
+        observe_block(block, BlockStage::EXECUTED);
 
         // notify mempool about failed transaction
         if let Err(e) = self
@@ -190,6 +197,7 @@ impl StateComputer for ExecutionProxy {
         let payload_manager = self.payload_manager.lock().as_ref().unwrap().clone();
         let txn_deduper = self.transaction_deduper.lock().as_ref().unwrap().clone();
         let txn_shuffler = self.transaction_shuffler.lock().as_ref().unwrap().clone();
+        let block_partitioner = self.block_partitioner.lock().as_ref().unwrap().clone();
 
         let block_gas_limit = *self.maybe_block_gas_limit.lock();
 
@@ -204,11 +212,15 @@ impl StateComputer for ExecutionProxy {
             let deduped_txns = txn_deduper.dedup(signed_txns);
             let shuffled_txns = txn_shuffler.shuffle(deduped_txns);
 
-            txns.extend(block.transactions_to_commit(
-                &self.validators.lock(),
-                shuffled_txns,
-                block_gas_limit,
-            ));
+            // The blocks are committed in the order they were executed in
+            let executed_txns = block_partitioner
+                .partition(block.block().transactions_to_execute(
+                    &self.validators.lock(),
+                    shuffled_txns,
+                    block_gas_limit,
+                ))
+                .txns();
+            txns.extend(block.transactions_to_commit(executed_txns, block_gas_limit));
             reconfig_events.extend(block.reconfig_event());
         }
 
@@ -224,6 +236,9 @@ impl StateComputer for ExecutionProxy {
             .await
         )
         .expect("spawn_blocking failed");
+        for block in blocks {
+            observe_block(block.block(), BlockStage::PERSISTED);
+        }
 
         let blocks = blocks.to_vec();
         let wrapped_callback = move || {
@@ -303,6 +318,7 @@ impl StateComputer for ExecutionProxy {
         transaction_shuffler: Arc<dyn TransactionShuffler>,
         block_gas_limit: Option<u64>,
         transaction_deduper: Arc<dyn TransactionDeduper>,
+        block_partitioner: Arc<dyn BlockPartitioner>,
     ) {
         *self.validators.lock() = epoch_state
             .verifier
@@ -314,6 +330,7 @@ impl StateComputer for ExecutionProxy {
             .replace(transaction_shuffler);
         *self.maybe_block_gas_limit.lock() = block_gas_limit;
         self.transaction_deduper.lock().replace(transaction_deduper);
+        self.block_partitioner.lock().replace(block_partitioner);
     }
 
     // Clears the epoch-specific state. Only a sync_to call is expected before calling new_epoch
@@ -322,21 +339,28 @@ impl StateComputer for ExecutionProxy {
         *self.validators.lock() = vec![];
         self.payload_manager.lock().take();
     }
+
+    fn record_execution_divergence(&self, blocks: &[ExecutedBlock]) {
+        let block_ids = blocks.iter().map(|block| block.id()).collect();
+        if let Err(e) = self.executor.save_execution_fingerprints(block_ids) {
+            warn!(error = ?e, "Failed to save the execution fingerprints");
+        }
+    }
 }
 
 #[tokio::test]
 async fn test_commit_sync_race() {
     use crate::{
-        error::MempoolError, transaction_deduper::create_transaction_deduper,
+        block_partitioner::create_block_partitioner, error::MempoolError,
+        transaction_deduper::create_transaction_deduper,
         transaction_shuffler::create_transaction_shuffler,
     };
     use aptos_consensus_notifications::Error;
-    use aptos_executor_types::ExecutableBlock;
     use aptos_types::{
         aggregate_signature::AggregateSignature,
         block_info::BlockInfo,
         ledger_info::LedgerInfo,
-        on_chain_config::{TransactionDeduperType, TransactionShufflerType},
+        on_chain_config::{BlockPartitionerType, TransactionDeduperType, TransactionShufflerType},
         transaction::SignedTransaction,
     };
 
@@ -375,6 +399,13 @@ async fn test_commit_sync_race() {
             Ok(())
         }
 
+        fn save_execution_fingerprints(
+            &self,
+            _block_ids: Vec<HashValue>,
+        ) -> Result<(), ExecutionError> {
+            Ok(())
+        }
+
         fn finish(&self) {}
     }
 
@@ -440,6 +471,7 @@ async fn test_commit_sync_race() {
         create_transaction_shuffler(TransactionShufflerType::NoShuffling),
         None,
         create_transaction_deduper(TransactionDeduperType::NoDedup),
+        create_block_partitioner(BlockPartitionerType::NoPartitioning, None),
     );
     executor
         .commit(&[], generate_li(1, 1), callback.clone())
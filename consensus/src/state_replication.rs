@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_partitioner::BlockPartitioner,
     error::{QuorumStoreError, StateSyncError},
     payload_manager::PayloadManager,
     transaction_deduper::TransactionDeduper,
@@ -80,8 +81,13 @@ pub trait StateComputer: Send + Sync {
         transaction_shuffler: Arc<dyn TransactionShuffler>,
         block_gas_limit: Option<u64>,
         transaction_deduper: Arc<dyn TransactionDeduper>,
+        block_partitioner: Arc<dyn BlockPartitioner>,
     );
 
     // Reconfigure to clear epoch state at end of epoch.
     fn end_epoch(&self);
+
+    /// Records the fingerprints of the execution of the blocks, whose result doesn't match the
+    /// commit decision of the other validators, to find the first transaction it diverged on.
+    fn record_execution_divergence(&self, blocks: &[ExecutedBlock]);
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_partitioner::BlockPartitioner,
     error::StateSyncError,
     experimental::buffer_manager::OrderedBlocks,
     payload_manager::PayloadManager,
@@ -141,10 +142,13 @@ impl StateComputer for MockStateComputer {
         _: Arc<dyn TransactionShuffler>,
         _: Option<u64>,
         _: Arc<dyn TransactionDeduper>,
+        _: Arc<dyn BlockPartitioner>,
     ) {
     }
 
     fn end_epoch(&self) {}
+
+    fn record_execution_divergence(&self, _blocks: &[ExecutedBlock]) {}
 }
 
 pub struct EmptyStateComputer;
@@ -179,10 +183,13 @@ impl StateComputer for EmptyStateComputer {
         _: Arc<dyn TransactionShuffler>,
         _: Option<u64>,
         _: Arc<dyn TransactionDeduper>,
+        _: Arc<dyn BlockPartitioner>,
     ) {
     }
 
     fn end_epoch(&self) {}
+
+    fn record_execution_divergence(&self, _blocks: &[ExecutedBlock]) {}
 }
 
 /// Random Compute Result State Computer
@@ -241,8 +248,11 @@ impl StateComputer for RandomComputeResultStateComputer {
         _: Arc<dyn TransactionShuffler>,
         _: Option<u64>,
         _: Arc<dyn TransactionDeduper>,
+        _: Arc<dyn BlockPartitioner>,
     ) {
     }
 
     fn end_epoch(&self) {}
+
+    fn record_execution_divergence(&self, _blocks: &[ExecutedBlock]) {}
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    dag::{DAGStorage, MockDAGStorage},
     epoch_manager::LivenessStorageData,
     persistent_liveness_storage::{
         LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata,
@@ -10,7 +11,8 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
@@ -33,6 +35,10 @@ pub struct MockSharedStorage {
     // Liveness state
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
     pub validator_set: ValidatorSet,
+    pub equivocation_evidence: Mutex<Vec<EquivocationEvidence>>,
+
+    // DAG state
+    pub dag: Arc<MockDAGStorage>,
 }
 
 impl MockSharedStorage {
@@ -44,6 +50,8 @@ impl MockSharedStorage {
             last_vote: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
             validator_set,
+            equivocation_evidence: Mutex::new(vec![]),
+            dag: Arc::new(MockDAGStorage::new()),
         }
     }
 }
@@ -84,6 +92,10 @@ impl MockStorage {
     pub fn commit_to_storage(&self, ledger: LedgerInfo) {
         *self.storage_ledger.lock() = ledger;
 
+        // The blocks ordered by the DAG are not saved in the block tree, there's nothing to check.
+        if self.shared_storage.block.lock().is_empty() {
+            return;
+        }
         if let Err(e) = self.verify_consistency() {
             panic!("invalid db after commit: {}", e);
         }
@@ -219,6 +231,14 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(())
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        self.shared_storage
+            .equivocation_evidence
+            .lock()
+            .push(evidence.clone());
+        Ok(())
+    }
+
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
         let lis = self
             .shared_storage
@@ -233,6 +253,10 @@ impl PersistentLivenessStorage for MockStorage {
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn dag_storage(&self) -> Arc<dyn DAGStorage> {
+        self.shared_storage.dag.clone()
+    }
 }
 
 /// A storage that ignores any requests, used in the tests that don't care about the storage.
@@ -294,6 +318,10 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(())
     }
 
+    fn save_equivocation_evidence(&self, _: &EquivocationEvidence) -> Result<()> {
+        Ok(())
+    }
+
     fn retrieve_epoch_change_proof(&self, _version: u64) -> Result<EpochChangeProof> {
         Ok(EpochChangeProof::new(vec![], false))
     }
@@ -301,4 +329,8 @@ impl PersistentLivenessStorage for EmptyStorage {
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn dag_storage(&self) -> Arc<dyn DAGStorage> {
+        Arc::new(MockDAGStorage::new())
+    }
 }
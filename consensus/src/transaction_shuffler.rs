@@ -1,12 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sender_aware_shuffler::SenderAwareShuffler;
+use crate::{
+    conflict_aware_shuffler::ConflictAwareShuffler, sender_aware_shuffler::SenderAwareShuffler,
+};
 use aptos_logger::info;
 use aptos_types::{
     on_chain_config::{
         TransactionShufflerType,
-        TransactionShufflerType::{NoShuffling, SenderAwareV1},
+        TransactionShufflerType::{ConflictAwareV1, NoShuffling, SenderAwareV1},
     },
     transaction::SignedTransaction,
 };
@@ -14,6 +16,7 @@ use std::sync::Arc;
 
 /// Interface to shuffle transactions
 pub trait TransactionShuffler: Send + Sync {
+    /// Returns the transactions in the order in which they are executed
     fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction>;
 }
 
@@ -26,6 +29,7 @@ impl TransactionShuffler for NoOpShuffler {
     }
 }
 
+/// Creates the shuffler of the given on-chain type
 pub fn create_transaction_shuffler(
     shuffler_type: TransactionShufflerType,
 ) -> Arc<dyn TransactionShuffler> {
@@ -38,5 +42,12 @@ pub fn create_transaction_shuffler(
             );
             Arc::new(SenderAwareShuffler::new(confict_window_size as usize))
         },
+        ConflictAwareV1(conflict_window_size) => {
+            info!(
+                "Using conflict aware transaction shuffling with conflict window size {}",
+                conflict_window_size
+            );
+            Arc::new(ConflictAwareShuffler::new(conflict_window_size as usize))
+        },
     }
 }
@@ -9,8 +9,9 @@ use crate::{
     twins::twins_node::SMRNode,
 };
 use aptos_consensus_types::{block::Block, common::Round};
-use aptos_types::on_chain_config::ProposerElectionType::{
-    FixedProposer, RotatingProposer, RoundProposer,
+use aptos_types::on_chain_config::{
+    ConsensusConfigV1, OnChainConsensusConfig,
+    ProposerElectionType::{FixedProposer, RotatingProposer, RoundProposer},
 };
 use futures::StreamExt;
 use std::collections::HashMap;
@@ -313,3 +314,54 @@ fn twins_commit_test() {
         }
     });
 }
+
+#[test]
+/// This test checks that the blocks ordered by the DAG are committed
+/// consistently by all the nodes
+///
+/// Setup:
+///
+/// 4 honest nodes running the DAG, and 0 twins
+///
+/// Test:
+///
+/// Wait until every node commits an anchor of round 6 or above, check
+/// that the nodes committed the same block for the same round
+///
+/// Run the test:
+/// cargo xtest -p consensus dag_commit_test -- --nocapture
+fn dag_commit_test() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let num_nodes = 4;
+    let num_twins = 0;
+
+    let mut nodes = SMRNode::start_num_nodes_with_consensus_config(
+        num_nodes,
+        num_twins,
+        &mut playground,
+        |_| OnChainConsensusConfig::DagV1(ConsensusConfigV1::default()),
+    );
+    runtime.spawn(playground.start());
+
+    timed_block_on(&runtime, async {
+        let mut committed_blocks = HashMap::new();
+        for node in &mut nodes {
+            loop {
+                let commit = node
+                    .commit_cb_receiver
+                    .next()
+                    .await
+                    .expect("[TwinsTest] Test failed due to no commit(s)");
+                let commit_info = commit.ledger_info().commit_info();
+                let block_id = *committed_blocks
+                    .entry(commit_info.round())
+                    .or_insert_with(|| commit_info.id());
+                assert_eq!(block_id, commit_info.id());
+                if commit_info.round() >= 6 {
+                    break;
+                }
+            }
+        }
+    });
+}
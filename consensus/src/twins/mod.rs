@@ -3,4 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
+mod scenario;
+mod scenario_runner;
+mod twins_fuzz_test;
 mod twins_node;
@@ -0,0 +1,273 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_consensus_types::common::Round;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A twins scenario: the leader and the network partitions of each round.
+///
+/// Nodes are identified by their index: `0..num_nodes` are the nodes sorted by author and
+/// `num_nodes + i` is the twin of node `i`. A node with a twin (and the twin itself) is
+/// byzantine, all the other nodes are honest. Rounds without partitions are fully connected
+/// and rounds without a leader default to the first node.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TwinsScenario {
+    pub num_nodes: usize,
+    pub num_twins: usize,
+    pub round_proposers: BTreeMap<Round, usize>,
+    pub round_partitions: BTreeMap<Round, Vec<Vec<usize>>>,
+}
+
+impl TwinsScenario {
+    /// Returns the last round with a scheduled leader or partition
+    pub fn num_rounds(&self) -> Round {
+        let last_proposer_round = self.round_proposers.keys().last().copied();
+        let last_partition_round = self.round_partitions.keys().last().copied();
+        last_proposer_round
+            .max(last_partition_round)
+            .unwrap_or_default()
+    }
+
+    /// Returns the index of the node whose author is used by the given node
+    pub fn author_index(&self, node: usize) -> usize {
+        if node >= self.num_nodes {
+            node - self.num_nodes
+        } else {
+            node
+        }
+    }
+
+    /// Returns true iff the given node runs with the same author as another node
+    pub fn is_byzantine(&self, node: usize) -> bool {
+        self.author_index(node) < self.num_twins
+    }
+
+    /// Returns the indices of the honest nodes
+    pub fn honest_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.num_nodes).filter(move |node| !self.is_byzantine(*node))
+    }
+
+    /// Returns the leader of the given round
+    pub fn proposer(&self, round: Round) -> usize {
+        self.round_proposers
+            .get(&round)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// A round is synchronous if its leader is honest and all the honest nodes are in the same
+    /// partition. Honest nodes always form a quorum, so such a round makes progress.
+    pub fn is_synchronous_round(&self, round: Round) -> bool {
+        let honest_nodes: Vec<_> = self.honest_nodes().collect();
+        let all_honest_connected = match self.round_partitions.get(&round) {
+            None => true,
+            Some(partitions) => partitions.iter().any(|partition| {
+                honest_nodes
+                    .iter()
+                    .all(|honest_node| partition.contains(honest_node))
+            }),
+        };
+        all_honest_connected && !self.is_byzantine(self.proposer(round))
+    }
+
+    /// Round timeouts are disabled in the twins tests, so progress is only expected if the
+    /// first `liveness_rounds` rounds are synchronous.
+    pub fn expects_liveness(&self, liveness_rounds: Round) -> bool {
+        (1..=liveness_rounds).all(|round| self.is_synchronous_round(round))
+    }
+
+    /// Returns simpler variants of the scenario, the most aggressive ones first: without the
+    /// last round, without the last twin and without the partitions of a single round.
+    pub fn shrink_candidates(&self) -> Vec<TwinsScenario> {
+        let mut candidates = vec![];
+
+        let num_rounds = self.num_rounds();
+        if num_rounds > 0 {
+            let mut candidate = self.clone();
+            candidate.round_proposers.remove(&num_rounds);
+            candidate.round_partitions.remove(&num_rounds);
+            candidates.push(candidate);
+        }
+
+        if self.num_twins > 0 {
+            let removed_twin = self.num_nodes + self.num_twins - 1;
+            let mut candidate = self.clone();
+            candidate.num_twins -= 1;
+            for partitions in candidate.round_partitions.values_mut() {
+                partitions.iter_mut().for_each(|partition| {
+                    partition.retain(|node| *node != removed_twin);
+                });
+                partitions.retain(|partition| !partition.is_empty());
+            }
+            candidates.push(candidate);
+        }
+
+        for round in self.round_partitions.keys() {
+            let mut candidate = self.clone();
+            candidate.round_partitions.remove(round);
+            candidates.push(candidate);
+        }
+
+        candidates
+    }
+
+    /// Greedily shrinks the scenario while it keeps failing, re-running at most
+    /// `max_attempts` candidates.
+    pub fn shrink(
+        &self,
+        max_attempts: usize,
+        mut still_fails: impl FnMut(&TwinsScenario) -> bool,
+    ) -> TwinsScenario {
+        let mut scenario = self.clone();
+        let mut attempts = 0;
+        'shrinking: loop {
+            for candidate in scenario.shrink_candidates() {
+                if attempts >= max_attempts {
+                    break 'shrinking;
+                }
+                attempts += 1;
+                if still_fails(&candidate) {
+                    scenario = candidate;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+        scenario
+    }
+
+    /// Writes the scenario to the given file, so that it can be replayed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads a scenario written by `save`
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+/// Generates the scenarios of a given number of nodes, twins and rounds. The scenario space
+/// is the product, over all rounds, of the leaders and of the partitions of the nodes (and
+/// their twins) into at most `max_partitions` sets. It can be enumerated or sampled.
+pub struct TwinsScenarioGenerator {
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: Round,
+    partitions: Vec<Vec<Vec<usize>>>,
+}
+
+impl TwinsScenarioGenerator {
+    pub fn new(
+        num_nodes: usize,
+        num_twins: usize,
+        num_rounds: Round,
+        max_partitions: usize,
+    ) -> Self {
+        assert!(num_nodes > 0 && num_twins <= num_nodes && max_partitions > 0);
+        Self {
+            num_nodes,
+            num_twins,
+            num_rounds,
+            partitions: set_partitions(num_nodes + num_twins, max_partitions),
+        }
+    }
+
+    /// Returns the number of choices for a single round
+    fn num_round_choices(&self) -> u128 {
+        (self.num_nodes * self.partitions.len()) as u128
+    }
+
+    /// Returns the number of scenarios (saturated at `u128::MAX`)
+    pub fn num_scenarios(&self) -> u128 {
+        (0..self.num_rounds).fold(1u128, |total, _| {
+            total.saturating_mul(self.num_round_choices())
+        })
+    }
+
+    /// Returns the scenario at the given index of the enumeration
+    pub fn scenario(&self, mut index: u128) -> TwinsScenario {
+        let choices = (1..=self.num_rounds)
+            .map(|round| {
+                let choice = (index % self.num_round_choices()) as usize;
+                index /= self.num_round_choices();
+                (round, choice)
+            })
+            .collect::<Vec<_>>();
+        self.build_scenario(choices)
+    }
+
+    /// Enumerates all the scenarios
+    pub fn enumerate(&self) -> impl Iterator<Item = TwinsScenario> + '_ {
+        (0..self.num_scenarios()).map(move |index| self.scenario(index))
+    }
+
+    /// Returns a uniformly sampled scenario
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> TwinsScenario {
+        let choices = (1..=self.num_rounds)
+            .map(|round| {
+                let choice = rng.gen_range(0, self.num_round_choices()) as usize;
+                (round, choice)
+            })
+            .collect::<Vec<_>>();
+        self.build_scenario(choices)
+    }
+
+    /// Builds the scenario from the choice of each round, where a choice encodes the
+    /// leader and the partitions of the round.
+    fn build_scenario(&self, choices: Vec<(Round, usize)>) -> TwinsScenario {
+        let mut scenario = TwinsScenario {
+            num_nodes: self.num_nodes,
+            num_twins: self.num_twins,
+            round_proposers: BTreeMap::new(),
+            round_partitions: BTreeMap::new(),
+        };
+        for (round, choice) in choices {
+            scenario
+                .round_proposers
+                .insert(round, choice % self.num_nodes);
+            let partitions = &self.partitions[choice / self.num_nodes];
+            // A single partition is a fully connected network
+            if partitions.len() > 1 {
+                scenario.round_partitions.insert(round, partitions.clone());
+            }
+        }
+        scenario
+    }
+}
+
+/// Returns all the partitions of `0..num_elements` into at most `max_sets` non-empty sets.
+/// Each partition is generated once, as the elements are assigned to sets in order and
+/// a new set is only opened for an element after all the previous sets are used.
+pub fn set_partitions(num_elements: usize, max_sets: usize) -> Vec<Vec<Vec<usize>>> {
+    fn assign(
+        element: usize,
+        num_elements: usize,
+        max_sets: usize,
+        sets: &mut Vec<Vec<usize>>,
+        partitions: &mut Vec<Vec<Vec<usize>>>,
+    ) {
+        if element == num_elements {
+            partitions.push(sets.clone());
+            return;
+        }
+        for set in 0..sets.len() {
+            sets[set].push(element);
+            assign(element + 1, num_elements, max_sets, sets, partitions);
+            sets[set].pop();
+        }
+        if sets.len() < max_sets {
+            sets.push(vec![element]);
+            assign(element + 1, num_elements, max_sets, sets, partitions);
+            sets.pop();
+        }
+    }
+
+    let mut partitions = vec![];
+    assign(0, num_elements, max_sets, &mut vec![], &mut partitions);
+    partitions
+}
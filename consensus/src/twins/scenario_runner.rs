@@ -0,0 +1,275 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::{NetworkPlayground, TwinId},
+    test_utils::consensus_runtime,
+    twins::{
+        scenario::{TwinsScenario, TwinsScenarioGenerator},
+        twins_node::SMRNode,
+    },
+};
+use aptos_consensus_types::common::Round;
+use aptos_crypto::HashValue;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, on_chain_config::ProposerElectionType::RoundProposer,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// The interval at which the commits of the nodes are collected
+const COMMIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A violated invariant of a twins scenario
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScenarioFailure {
+    /// Honest nodes committed conflicting blocks
+    Safety(String),
+    /// Honest nodes did not commit within the time bound, although they should have
+    Liveness(String),
+}
+
+impl Display for ScenarioFailure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ScenarioFailure::Safety(error) => write!(f, "Safety violation: {}", error),
+            ScenarioFailure::Liveness(error) => write!(f, "Liveness violation: {}", error),
+        }
+    }
+}
+
+/// Runs the scenario with the twins nodes for at most `duration` and checks that the honest
+/// nodes never commit conflicting blocks, and that they commit if the first `liveness_rounds`
+/// rounds are synchronous. The run stops early once all the honest nodes committed a round
+/// past the schedule of the scenario.
+pub fn run_scenario(
+    scenario: &TwinsScenario,
+    duration: Duration,
+    liveness_rounds: Round,
+) -> Result<(), ScenarioFailure> {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = SMRNode::start_num_nodes_with_twins(
+        scenario.num_nodes,
+        scenario.num_twins,
+        &mut playground,
+        RoundProposer(HashMap::new()),
+        Some(scenario.round_proposers.clone().into_iter().collect()),
+    );
+
+    // The nodes are started in the order of their scenario index
+    let twin_ids: Vec<TwinId> = nodes.iter().map(|node| node.id).collect();
+    let round_partitions: HashMap<Round, Vec<Vec<TwinId>>> = scenario
+        .round_partitions
+        .iter()
+        .map(|(round, partitions)| {
+            let partitions = partitions
+                .iter()
+                .map(|partition| partition.iter().map(|node| twin_ids[*node]).collect())
+                .collect();
+            (*round, partitions)
+        })
+        .collect();
+    assert!(playground.split_network_round(&round_partitions));
+    runtime.spawn(playground.start());
+
+    let mut commits: Vec<Vec<LedgerInfoWithSignatures>> = vec![vec![]; nodes.len()];
+    let honest_nodes: Vec<_> = scenario.honest_nodes().collect();
+    runtime.block_on(async {
+        let start_time = Instant::now();
+        while start_time.elapsed() < duration {
+            tokio::time::sleep(COMMIT_POLL_INTERVAL).await;
+            for (node, node_commits) in nodes.iter_mut().zip(commits.iter_mut()) {
+                while let Ok(Some(commit)) = node.commit_cb_receiver.try_next() {
+                    node_commits.push(commit);
+                }
+            }
+            let schedule_completed = honest_nodes.iter().all(|node| {
+                commits[*node].last().map_or(false, |commit| {
+                    commit.ledger_info().commit_info().round() > scenario.num_rounds()
+                })
+            });
+            if schedule_completed {
+                break;
+            }
+        }
+    });
+
+    // Shut down the nodes outside of the async context
+    drop(nodes);
+    drop(runtime);
+
+    check_safety(scenario, &commits)?;
+    if scenario.expects_liveness(liveness_rounds) {
+        check_liveness(scenario, &commits)?;
+    }
+    Ok(())
+}
+
+/// Checks that the honest nodes committed the same block in every round
+fn check_safety(
+    scenario: &TwinsScenario,
+    commits: &[Vec<LedgerInfoWithSignatures>],
+) -> Result<(), ScenarioFailure> {
+    let mut committed_blocks: BTreeMap<Round, (usize, HashValue)> = BTreeMap::new();
+    for node in scenario.honest_nodes() {
+        for commit in &commits[node] {
+            let commit_info = commit.ledger_info().commit_info();
+            let (first_node, block_id) = *committed_blocks
+                .entry(commit_info.round())
+                .or_insert((node, commit_info.id()));
+            if block_id != commit_info.id() {
+                return Err(ScenarioFailure::Safety(format!(
+                    "nodes {} and {} committed blocks {} and {} in round {}",
+                    first_node,
+                    node,
+                    block_id,
+                    commit_info.id(),
+                    commit_info.round()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every honest node committed at least one block
+fn check_liveness(
+    scenario: &TwinsScenario,
+    commits: &[Vec<LedgerInfoWithSignatures>],
+) -> Result<(), ScenarioFailure> {
+    match scenario
+        .honest_nodes()
+        .find(|node| commits[*node].is_empty())
+    {
+        Some(node) => Err(ScenarioFailure::Liveness(format!(
+            "node {} did not commit any block",
+            node
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The parameters and the budget of a twins fuzzing session. The defaults fit in a CI run;
+/// every field can be overridden with the environment variable documented on `from_env`.
+#[derive(Clone, Debug)]
+pub struct TwinsFuzzConfig {
+    pub seed: u64,
+    pub num_nodes: usize,
+    pub max_twins: usize,
+    pub num_rounds: Round,
+    pub max_partitions: usize,
+    pub liveness_rounds: Round,
+    pub scenario_duration: Duration,
+    pub max_shrink_attempts: usize,
+    pub seed_dir: PathBuf,
+}
+
+impl Default for TwinsFuzzConfig {
+    fn default() -> Self {
+        Self {
+            seed: rand::thread_rng().gen(),
+            num_nodes: 4,
+            max_twins: 1,
+            num_rounds: 4,
+            max_partitions: 2,
+            liveness_rounds: 3,
+            scenario_duration: Duration::from_secs(3),
+            max_shrink_attempts: 10,
+            seed_dir: env::temp_dir(),
+        }
+    }
+}
+
+impl TwinsFuzzConfig {
+    /// Reads the config from the environment: `TWINS_FUZZ_SEED`, `TWINS_NUM_NODES`,
+    /// `TWINS_MAX_TWINS`, `TWINS_NUM_ROUNDS`, `TWINS_MAX_PARTITIONS`, `TWINS_LIVENESS_ROUNDS`,
+    /// `TWINS_SCENARIO_DURATION_MS`, `TWINS_MAX_SHRINK_ATTEMPTS` and `TWINS_SEED_DIR`.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            seed: read_env("TWINS_FUZZ_SEED").unwrap_or(default.seed),
+            num_nodes: read_env("TWINS_NUM_NODES").unwrap_or(default.num_nodes),
+            max_twins: read_env("TWINS_MAX_TWINS").unwrap_or(default.max_twins),
+            num_rounds: read_env("TWINS_NUM_ROUNDS").unwrap_or(default.num_rounds),
+            max_partitions: read_env("TWINS_MAX_PARTITIONS").unwrap_or(default.max_partitions),
+            liveness_rounds: read_env("TWINS_LIVENESS_ROUNDS").unwrap_or(default.liveness_rounds),
+            scenario_duration: read_env("TWINS_SCENARIO_DURATION_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.scenario_duration),
+            max_shrink_attempts: read_env("TWINS_MAX_SHRINK_ATTEMPTS")
+                .unwrap_or(default.max_shrink_attempts),
+            seed_dir: read_env("TWINS_SEED_DIR").unwrap_or(default.seed_dir),
+        }
+    }
+}
+
+/// Parses the given environment variable, if it is set
+pub fn read_env<T: FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid value for {}: {}", name, value))
+    })
+}
+
+/// Runs randomly sampled scenarios while `keep_going` returns true for the number of the
+/// scenarios already run. Scenario `i` is sampled with the seed `config.seed + i`, with at
+/// most a third of the nodes as twins. A failing scenario is shrunk and saved to the seed
+/// directory before panicking, so that it can be replayed with `replay_scenario`.
+pub fn fuzz_scenarios(config: &TwinsFuzzConfig, mut keep_going: impl FnMut(usize) -> bool) {
+    let max_twins = config.max_twins.min((config.num_nodes - 1) / 3);
+    let generators: Vec<_> = (0..=max_twins)
+        .map(|num_twins| {
+            TwinsScenarioGenerator::new(
+                config.num_nodes,
+                num_twins,
+                config.num_rounds,
+                config.max_partitions,
+            )
+        })
+        .collect();
+
+    let mut num_scenarios = 0;
+    while keep_going(num_scenarios) {
+        let seed = config.seed.wrapping_add(num_scenarios as u64);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let generator = &generators[rng.gen_range(0, generators.len())];
+        let scenario = generator.sample(&mut rng);
+        num_scenarios += 1;
+
+        let run = |scenario: &TwinsScenario| {
+            run_scenario(scenario, config.scenario_duration, config.liveness_rounds)
+        };
+        if let Err(failure) = run(&scenario) {
+            let shrunk_scenario = scenario.shrink(config.max_shrink_attempts, |candidate| {
+                run(candidate).is_err()
+            });
+            let path = config
+                .seed_dir
+                .join(format!("twins_scenario_{}.json", seed));
+            shrunk_scenario
+                .save(&path)
+                .expect("Failed to save the failing scenario");
+            panic!(
+                "[TwinsTest] Scenario with seed {} failed: {}. Replay it with TWINS_SCENARIO_FILE={}",
+                seed,
+                failure,
+                path.display()
+            );
+        }
+    }
+}
+
+/// Replays a scenario saved by `fuzz_scenarios`
+pub fn replay_scenario(path: &Path, config: &TwinsFuzzConfig) -> Result<(), ScenarioFailure> {
+    let scenario = TwinsScenario::load(path).expect("Failed to load the scenario");
+    run_scenario(&scenario, config.scenario_duration, config.liveness_rounds)
+}
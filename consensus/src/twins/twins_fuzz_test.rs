@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::{
+    scenario::{set_partitions, TwinsScenario, TwinsScenarioGenerator},
+    scenario_runner::{fuzz_scenarios, read_env, replay_scenario, TwinsFuzzConfig},
+};
+use aptos_temppath::TempPath;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+#[test]
+/// This test checks that the generator enumerates every scenario exactly once
+fn twins_scenario_generator_test() {
+    // The number of partitions of 4 elements into at most 2 sets, and into any number of sets
+    assert_eq!(set_partitions(4, 2).len(), 8);
+    assert_eq!(set_partitions(4, 4).len(), 15);
+
+    // Every scenario is enumerated once
+    let generator = TwinsScenarioGenerator::new(4, 1, 2, 2);
+    let num_scenarios = (4 * set_partitions(5, 2).len() as u128).pow(2);
+    assert_eq!(generator.num_scenarios(), num_scenarios);
+    let scenarios: Vec<_> = generator.enumerate().collect();
+    assert_eq!(scenarios.len() as u128, num_scenarios);
+    let unique_scenarios: HashSet<_> = scenarios
+        .iter()
+        .map(|scenario| serde_json::to_string(scenario).unwrap())
+        .collect();
+    assert_eq!(unique_scenarios.len(), scenarios.len());
+
+    // Sampling is reproducible from the seed
+    let sample = |seed| generator.sample(&mut StdRng::seed_from_u64(seed));
+    assert_eq!(sample(7), sample(7));
+    assert!(scenarios.contains(&sample(7)));
+}
+
+#[test]
+/// This test checks the classification of the nodes and rounds of a scenario
+fn twins_scenario_liveness_test() {
+    // Node 0 (and its twin 4) are byzantine
+    let mut scenario = TwinsScenario {
+        num_nodes: 4,
+        num_twins: 1,
+        round_proposers: BTreeMap::from([(1, 1), (2, 2), (3, 0)]),
+        round_partitions: BTreeMap::from([(2, vec![vec![0, 1, 2, 3], vec![4]])]),
+    };
+    assert_eq!(scenario.honest_nodes().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert!(scenario.is_byzantine(4));
+    assert_eq!(scenario.num_rounds(), 3);
+
+    // Round 3 has a byzantine leader
+    assert!(scenario.expects_liveness(2));
+    assert!(!scenario.expects_liveness(3));
+
+    // Round 2 splits the honest nodes
+    scenario
+        .round_partitions
+        .insert(2, vec![vec![0, 1], vec![2, 3, 4]]);
+    assert!(!scenario.expects_liveness(2));
+}
+
+#[test]
+/// This test checks that a failing scenario is shrunk to a minimal one and can be saved
+fn twins_scenario_shrink_test() {
+    let generator = TwinsScenarioGenerator::new(4, 1, 4, 2);
+    let scenario = generator
+        .enumerate()
+        .find(|scenario| scenario.round_partitions.len() == 4)
+        .unwrap();
+
+    // The scenario fails as long as round 2 is partitioned
+    let shrunk_scenario =
+        scenario.shrink(100, |candidate| candidate.round_partitions.contains_key(&2));
+    assert_eq!(shrunk_scenario.num_twins, 0);
+    assert_eq!(shrunk_scenario.num_rounds(), 2);
+    assert_eq!(
+        shrunk_scenario.round_partitions.keys().collect::<Vec<_>>(),
+        vec![&2]
+    );
+
+    // The shrunk scenario can be replayed from its file
+    let path = TempPath::new();
+    shrunk_scenario.save(path.path()).unwrap();
+    assert_eq!(TwinsScenario::load(path.path()).unwrap(), shrunk_scenario);
+}
+
+#[test]
+/// This test runs a few random scenarios and checks that the honest nodes
+/// never commit conflicting blocks, and make progress in synchronous rounds
+///
+/// Run the test with a given budget and seed:
+/// TWINS_FUZZ_ITERATIONS=10 TWINS_FUZZ_SEED=42 cargo xtest -p consensus twins_random_scenarios_test -- --nocapture
+fn twins_random_scenarios_test() {
+    let config = TwinsFuzzConfig::from_env();
+    let num_iterations: usize = read_env("TWINS_FUZZ_ITERATIONS").unwrap_or(2);
+    fuzz_scenarios(&config, |num_scenarios| num_scenarios < num_iterations);
+}
+
+#[test]
+#[ignore]
+/// This test runs random scenarios until the time budget is exhausted
+///
+/// Run the test:
+/// TWINS_FUZZ_DURATION_SECS=3600 cargo xtest -p consensus twins_fuzz_test -- --ignored --nocapture
+fn twins_fuzz_test() {
+    let config = TwinsFuzzConfig::from_env();
+    let duration = Duration::from_secs(read_env("TWINS_FUZZ_DURATION_SECS").unwrap_or(600));
+    let start_time = Instant::now();
+    fuzz_scenarios(&config, |_| start_time.elapsed() < duration);
+}
+
+#[test]
+#[ignore]
+/// This test replays a scenario saved by a failing fuzzing run
+///
+/// Run the test:
+/// TWINS_SCENARIO_FILE=/tmp/twins_scenario_42.json cargo xtest -p consensus twins_replay_test -- --ignored --nocapture
+fn twins_replay_test() {
+    let path: PathBuf = read_env("TWINS_SCENARIO_FILE").expect("TWINS_SCENARIO_FILE is not set");
+    if let Err(failure) = replay_scenario(&path, &TwinsFuzzConfig::from_env()) {
+        panic!("[TwinsTest] {}", failure);
+    }
+}
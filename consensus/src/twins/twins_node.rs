@@ -153,6 +153,8 @@ impl SMRNode {
             quorum_store_storage,
             reconfig_listener,
             bounded_executor,
+            None,
+            None,
         );
         let (network_task, network_receiver) =
             NetworkTask::new(network_service_events, self_receiver);
@@ -191,6 +193,41 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ProposerElectionType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        Self::start_num_nodes_with_consensus_config(
+            num_nodes,
+            num_twins,
+            playground,
+            |node_configs| {
+                let proposer_type = match proposer_type {
+                    RoundProposer(_) => {
+                        let mut round_proposers: HashMap<Round, Author> = HashMap::new();
+
+                        if let Some(proposers) = round_proposers_idx {
+                            proposers.iter().for_each(|(round, idx)| {
+                                round_proposers
+                                    .insert(*round, author_from_config(&node_configs[*idx]));
+                            })
+                        }
+                        RoundProposer(round_proposers)
+                    },
+                    _ => proposer_type,
+                };
+                OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                    proposer_election_type: proposer_type,
+                    ..ConsensusConfigV1::default()
+                })
+            },
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, the on-chain consensus config is built from
+    /// the node configs sorted by the peer id.
+    pub fn start_num_nodes_with_consensus_config(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        consensus_config_fn: impl FnOnce(&[NodeConfig]) -> OnChainConsensusConfig,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
@@ -229,19 +266,7 @@ impl SMRNode {
         );
         // sort by the peer id
 
-        let proposer_type = match proposer_type {
-            RoundProposer(_) => {
-                let mut round_proposers: HashMap<Round, Author> = HashMap::new();
-
-                if let Some(proposers) = round_proposers_idx {
-                    proposers.iter().for_each(|(round, idx)| {
-                        round_proposers.insert(*round, author_from_config(&node_configs[*idx]));
-                    })
-                }
-                RoundProposer(round_proposers)
-            },
-            _ => proposer_type,
-        };
+        let consensus_config = consensus_config_fn(&node_configs);
 
         // We don't add twins to ValidatorSet or round_proposers above
         // because a node with twins should be treated the same at the
@@ -273,15 +298,10 @@ impl SMRNode {
 
             let twin_id = TwinId { id: smr_id, author };
 
-            let consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
-                proposer_election_type: proposer_type.clone(),
-                ..ConsensusConfigV1::default()
-            });
-
             smr_nodes.push(Self::start(
                 playground,
                 config,
-                consensus_config,
+                consensus_config.clone(),
                 storage,
                 twin_id,
             ));
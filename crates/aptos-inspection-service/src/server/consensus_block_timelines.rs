@@ -0,0 +1,44 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{CONTENT_TYPE_JSON, CONTENT_TYPE_TEXT};
+use aptos_config::config::NodeConfig;
+use hyper::{Body, StatusCode};
+
+// The message to display when the consensus block timelines endpoint is disabled
+pub const CONSENSUS_BLOCK_TIMELINES_DISABLED_MESSAGE: &str =
+    "This endpoint is disabled! Enable it in the node config at inspection_service.expose_consensus_block_timelines: true";
+
+/// Handles a new consensus block timelines request
+pub fn handle_consensus_block_timelines_request(
+    node_config: &NodeConfig,
+) -> (StatusCode, Body, String) {
+    // Only return the block timelines if the endpoint is enabled
+    if !node_config
+        .inspection_service
+        .expose_consensus_block_timelines
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Body::from(CONSENSUS_BLOCK_TIMELINES_DISABLED_MESSAGE),
+            CONTENT_TYPE_TEXT.into(),
+        );
+    }
+
+    // Encode the timelines of the recent blocks as JSON
+    match serde_json::to_string(&aptos_consensus::get_block_timelines()) {
+        Ok(timelines) => (
+            StatusCode::OK,
+            Body::from(timelines),
+            CONTENT_TYPE_JSON.into(),
+        ),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Body::from(format!(
+                "Failed to encode the consensus block timelines: {}",
+                error
+            )),
+            CONTENT_TYPE_TEXT.into(),
+        ),
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{CONTENT_TYPE_JSON, CONTENT_TYPE_TEXT};
+use aptos_config::config::NodeConfig;
+use hyper::{Body, StatusCode};
+
+// The message to display when the equivocation evidence endpoint is disabled
+pub const EQUIVOCATION_EVIDENCE_DISABLED_MESSAGE: &str =
+    "This endpoint is disabled! Enable it in the node config at inspection_service.expose_equivocation_evidence: true";
+
+/// Handles a new equivocation evidence request
+pub fn handle_equivocation_evidence_request(
+    node_config: &NodeConfig,
+) -> (StatusCode, Body, String) {
+    // Only return the equivocation evidence if the endpoint is enabled
+    if !node_config.inspection_service.expose_equivocation_evidence {
+        return (
+            StatusCode::FORBIDDEN,
+            Body::from(EQUIVOCATION_EVIDENCE_DISABLED_MESSAGE),
+            CONTENT_TYPE_TEXT.into(),
+        );
+    }
+
+    // Read the evidence from the consensus db and encode it as JSON
+    let evidence = aptos_consensus::read_equivocation_evidence(node_config.storage.dir())
+        .and_then(|evidence| Ok(serde_json::to_string(&evidence)?));
+    match evidence {
+        Ok(evidence) => (
+            StatusCode::OK,
+            Body::from(evidence),
+            CONTENT_TYPE_JSON.into(),
+        ),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Body::from(format!(
+                "Failed to read the equivocation evidence: {}",
+                error
+            )),
+            CONTENT_TYPE_TEXT.into(),
+        ),
+    }
+}
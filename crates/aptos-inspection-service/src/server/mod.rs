@@ -17,6 +17,8 @@ use std::{
 };
 
 mod configuration;
+mod consensus_block_timelines;
+mod equivocation_evidence;
 mod index;
 mod json_encoder;
 mod metrics;
@@ -29,6 +31,8 @@ mod tests;
 
 // The list of endpoints offered by the inspection service
 pub const CONFIGURATION_PATH: &str = "/configuration";
+pub const CONSENSUS_BLOCK_TIMELINES_PATH: &str = "/consensus_block_timelines";
+pub const EQUIVOCATION_EVIDENCE_PATH: &str = "/equivocation_evidence";
 pub const FORGE_METRICS_PATH: &str = "/forge_metrics";
 pub const INDEX_PATH: &str = "/";
 pub const JSON_METRICS_PATH: &str = "/json_metrics";
@@ -102,6 +106,16 @@ async fn serve_requests(
             // Exposes the node configuration
             configuration::handle_configuration_request(&node_config)
         },
+        CONSENSUS_BLOCK_TIMELINES_PATH => {
+            // /consensus_block_timelines
+            // Exposes the latency breakdown of the recent consensus blocks
+            consensus_block_timelines::handle_consensus_block_timelines_request(&node_config)
+        },
+        EQUIVOCATION_EVIDENCE_PATH => {
+            // /equivocation_evidence
+            // Exposes the evidence of equivocating validators
+            equivocation_evidence::handle_equivocation_evidence_request(&node_config)
+        },
         FORGE_METRICS_PATH => {
             // /forge_metrics
             // Exposes forge encoded metrics
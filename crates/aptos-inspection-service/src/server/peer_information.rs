@@ -62,6 +62,21 @@ fn get_peer_information(peers_and_metadata: Arc<PeersAndMetadata>) -> String {
     }
     peer_information.push("\n".into());
 
+    // Display the egress bandwidth (and quotas) for each network
+    peer_information.push("Egress bandwidth for each network:".into());
+    for network in &registered_networks {
+        if let Some(egress_quotas) = peers_and_metadata.get_egress_quotas(network) {
+            peer_information.push(format!("\t- Network: {}", network));
+            for egress_summary in egress_quotas.get_summaries() {
+                peer_information.push(format!(
+                    "\t\t- {}",
+                    serde_json::to_string(&egress_summary).unwrap_or_default()
+                ));
+            }
+        }
+    }
+    peer_information.push("\n".into());
+
     // Display the entire set of trusted peers
     peer_information.push("Trusted peers:".into());
     for network in registered_networks {
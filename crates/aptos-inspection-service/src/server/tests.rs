@@ -4,11 +4,14 @@
 use crate::{
     server::{
         configuration::CONFIGURATION_DISABLED_MESSAGE,
+        consensus_block_timelines::CONSENSUS_BLOCK_TIMELINES_DISABLED_MESSAGE,
+        equivocation_evidence::EQUIVOCATION_EVIDENCE_DISABLED_MESSAGE,
         peer_information::PEER_INFO_DISABLED_MESSAGE, serve_requests,
         system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
     },
-    CONFIGURATION_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH, METRICS_PATH,
-    PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    CONFIGURATION_PATH, CONSENSUS_BLOCK_TIMELINES_PATH, EQUIVOCATION_EVIDENCE_PATH,
+    FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH,
+    SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::NodeConfig;
 use aptos_network::application::storage::PeersAndMetadata;
@@ -53,6 +56,54 @@ async fn test_inspect_configuration() {
     assert!(response_body_string.contains("expose_configuration: true"));
 }
 
+#[tokio::test]
+async fn test_inspect_consensus_block_timelines() {
+    // Create a validator node config
+    let mut config = NodeConfig::get_default_validator_config();
+
+    // Disable the consensus block timelines endpoint and ping it
+    config.inspection_service.expose_consensus_block_timelines = false;
+    let mut response = send_get_request_to_path(&config, CONSENSUS_BLOCK_TIMELINES_PATH).await;
+    let response_body = block_on(body::to_bytes(response.body_mut())).unwrap();
+
+    // Verify that the response contains an error
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response_body, CONSENSUS_BLOCK_TIMELINES_DISABLED_MESSAGE);
+
+    // Enable the consensus block timelines endpoint and ping it
+    config.inspection_service.expose_consensus_block_timelines = true;
+    let mut response = send_get_request_to_path(&config, CONSENSUS_BLOCK_TIMELINES_PATH).await;
+    let response_body = block_on(body::to_bytes(response.body_mut())).unwrap();
+
+    // Verify that the response contains the (empty) JSON list of timelines
+    assert_eq!(response.status(), StatusCode::OK);
+    let timelines: Vec<serde_json::Value> = serde_json::from_slice(&response_body).unwrap();
+    assert!(timelines.is_empty());
+}
+
+#[tokio::test]
+async fn test_inspect_equivocation_evidence() {
+    // Create a validator node config without a consensus db
+    let mut config = NodeConfig::get_default_validator_config();
+    config.storage.dir = "/nonexistent_directory".into();
+
+    // Disable the equivocation evidence endpoint and ping it
+    config.inspection_service.expose_equivocation_evidence = false;
+    let mut response = send_get_request_to_path(&config, EQUIVOCATION_EVIDENCE_PATH).await;
+    let response_body = block_on(body::to_bytes(response.body_mut())).unwrap();
+
+    // Verify that the response contains an error
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response_body, EQUIVOCATION_EVIDENCE_DISABLED_MESSAGE);
+
+    // Enable the equivocation evidence endpoint and ping it
+    config.inspection_service.expose_equivocation_evidence = true;
+    let response = send_get_request_to_path(&config, EQUIVOCATION_EVIDENCE_PATH).await;
+
+    // Verify that the missing consensus db is reported
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
 #[tokio::test]
 async fn test_inspect_forge_metrics() {
     // Create a VFN config
@@ -234,6 +234,13 @@ impl Bucket {
         }
     }
 
+    /// Sets the time the bucket was last refilled, for buckets that are refilled with a
+    /// clock other than the system clock (see `acquire_tokens_at`)
+    pub fn with_last_refresh_time(mut self, last_refresh_time: Instant) -> Self {
+        self.last_refresh_time = last_refresh_time;
+        self
+    }
+
     /// A fully open rate limiter, to allow for ignoring rate limiting for tests
     pub fn open(label: String) -> Self {
         Self {
@@ -253,7 +260,14 @@ impl Bucket {
 
     /// Refill tokens based on how many seconds have passed since last refresh
     pub(crate) fn refill(&mut self) {
-        let num_intervals = self.last_refresh_time.elapsed().as_secs();
+        self.refill_at(Instant::now())
+    }
+
+    /// Refill tokens based on how many seconds have passed since last refresh, at `now`
+    fn refill_at(&mut self, now: Instant) {
+        let num_intervals = now
+            .saturating_duration_since(self.last_refresh_time)
+            .as_secs();
         if num_intervals > 0 {
             // Log how many were throttled in the period before refill
             if self.allowed_in_period > 0 || self.throttled_in_period > 0 {
@@ -318,13 +332,19 @@ impl Bucket {
     /// Returns `usize` of tokens allowed.  May be less than requested.
     /// For best effort, caller should return unused tokens with `add_tokens`
     pub fn acquire_tokens(&mut self, requested: usize) -> Result<usize, Instant> {
+        self.acquire_tokens_at(requested, Instant::now())
+    }
+
+    /// Same as `acquire_tokens`, but refills the bucket at `now` instead of with the system
+    /// clock, e.g., to drive the bucket with a mockable time service
+    pub fn acquire_tokens_at(&mut self, requested: usize, now: Instant) -> Result<usize, Instant> {
         // Skip over if we purposely have an open throttle
         if !self.enabled || requested == 0 {
             return Ok(requested);
         }
 
         // Refill if needed
-        self.refill();
+        self.refill_at(now);
 
         let allowed = self.deduct_tokens(requested);
         if allowed > 0 {
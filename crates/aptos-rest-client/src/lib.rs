@@ -37,7 +37,7 @@ use aptos_types::{
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
     contract_event::EventWithVersion,
     state_store::state_key::StateKey,
-    transaction::SignedTransaction,
+    transaction::{ExecutionFingerprint, SignedTransaction},
 };
 use move_core_types::language_storage::StructTag;
 use reqwest::{
@@ -813,6 +813,18 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Gets the execution fingerprints the node recorded for the transactions from `start`, when
+    /// its execution diverged. The versions without a recorded fingerprint are skipped.
+    pub async fn get_transaction_fingerprints_bcs(
+        &self,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<(u64, ExecutionFingerprint)>>> {
+        let url = self.build_path("transactions/fingerprints")?;
+        let response = self.get_bcs_with_page(url, start, limit).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -0,0 +1,248 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::fork::executor::ForkExecutor;
+use anyhow::{format_err, Result};
+use aptos_gas::{
+    AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters,
+    LATEST_GAS_FEATURE_VERSION,
+};
+use aptos_state_view::TStateView;
+use aptos_storage_interface::state_view::DbStateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{CoinStoreResource, CORE_CODE_ADDRESS},
+    chain_id::ChainId,
+    contract_event::ContractEvent,
+    on_chain_config::{CurrentTimeMicroseconds, Features, OnChainConfig, TimedFeatures},
+    state_store::state_key::StateKey,
+    transaction::Version,
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+use aptos_vm::{
+    data_cache::StorageAdapter,
+    move_vm_ext::{MoveVmExt, SessionExt, SessionId},
+};
+use move_binary_format::errors::VMResult;
+use move_core_types::{
+    ident_str,
+    language_storage::ModuleId,
+    move_resource::MoveResource,
+    value::{serialize_values, MoveValue},
+};
+use move_vm_types::gas::UnmeteredGasMeter;
+use poem::{
+    handler,
+    listener::TcpListener,
+    post,
+    web::{Data, Json},
+    EndpointExt, Route, Server,
+};
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+/// Runs a Move VM session on the given state, and returns its writes and events.
+fn run_session<F>(state_view: &DbStateView, f: F) -> Result<(WriteSet, Vec<ContractEvent>)>
+where
+    F: FnOnce(&mut SessionExt) -> VMResult<()>,
+{
+    let state_view_storage = StorageAdapter::new(state_view);
+    let features = Features::fetch_config(&state_view_storage).unwrap_or_default();
+    let move_vm = MoveVmExt::new(
+        NativeGasParameters::zeros(),
+        AbstractValueSizeGasParameters::zeros(),
+        LATEST_GAS_FEATURE_VERSION,
+        ChainId::test().id(),
+        features,
+        TimedFeatures::enable_all(),
+    )
+    .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    let mut session = move_vm.new_session(&state_view_storage, SessionId::Void, true);
+    f(&mut session).map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    let change_set = session
+        .finish(
+            &mut (),
+            &ChangeSetConfigs::unlimited_at_gas_feature_version(LATEST_GAS_FEATURE_VERSION),
+        )
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+        .try_materialize(state_view)
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    let (write_set, _, events) = change_set.unpack();
+    Ok((write_set, events))
+}
+
+/// Returns the write op replacing the value of the given key, creating it if it doesn't exist.
+fn write_value(state_view: &DbStateView, state_key: &StateKey, data: Vec<u8>) -> Result<WriteOp> {
+    Ok(match state_view.get_state_value(state_key)? {
+        Some(state_value) => match state_value.into_metadata() {
+            Some(metadata) => WriteOp::ModificationWithMetadata { data, metadata },
+            None => WriteOp::Modification(data),
+        },
+        None => WriteOp::Creation(data),
+    })
+}
+
+/// Changes to the state of the fork which can't be made with transactions.
+pub struct ForkCheats {
+    executor: Arc<ForkExecutor>,
+}
+
+impl ForkCheats {
+    pub fn new(executor: Arc<ForkExecutor>) -> Self {
+        Self { executor }
+    }
+
+    /// Sets the APT balance of the account, creating the account if it doesn't exist.
+    pub fn set_balance(&self, address: AccountAddress, amount: u64) -> Result<Version> {
+        self.executor.execute_cheat("set_balance", |state_view| {
+            let coin_store_key =
+                StateKey::access_path(AccessPath::new(address, CoinStoreResource::resource_path()));
+            let (write_set, events) = if state_view.get_state_value(&coin_store_key)?.is_some() {
+                (WriteSet::default(), vec![])
+            } else {
+                run_session(state_view, |session| {
+                    session
+                        .execute_function_bypass_visibility(
+                            &ModuleId::new(
+                                CORE_CODE_ADDRESS,
+                                ident_str!("aptos_account").to_owned(),
+                            ),
+                            ident_str!("create_account"),
+                            vec![],
+                            serialize_values(&vec![MoveValue::Address(address)]),
+                            &mut UnmeteredGasMeter,
+                        )
+                        .map(|_| ())
+                })?
+            };
+
+            let mut write_set = write_set.into_mut();
+            let coin_store = match write_set.get(&coin_store_key) {
+                Some(write_op) => write_op
+                    .bytes()
+                    .map(bcs::from_bytes::<CoinStoreResource>)
+                    .transpose()?,
+                None => state_view
+                    .get_state_value(&coin_store_key)?
+                    .map(|state_value| bcs::from_bytes::<CoinStoreResource>(state_value.bytes()))
+                    .transpose()?,
+            };
+            let coin_store = coin_store.ok_or_else(|| format_err!("Coin store not found"))?;
+            let data = bcs::to_bytes(&CoinStoreResource::new(
+                amount,
+                coin_store.frozen(),
+                coin_store.deposit_events().clone(),
+                coin_store.withdraw_events().clone(),
+            ))?;
+            // Keeps the creation by the session, if the account has just been created.
+            let write_op = match write_set.get(&coin_store_key) {
+                Some(WriteOp::CreationWithMetadata { metadata, .. }) => {
+                    WriteOp::CreationWithMetadata {
+                        data,
+                        metadata: metadata.clone(),
+                    }
+                },
+                Some(WriteOp::Creation(_)) => WriteOp::Creation(data),
+                _ => write_value(state_view, &coin_store_key, data)?,
+            };
+            write_set.insert((coin_store_key, write_op));
+            Ok((write_set.freeze()?, events))
+        })
+    }
+
+    /// Accepts the transactions sent on behalf of the account with any signature, without
+    /// changing its state.
+    pub fn impersonate(&self, address: AccountAddress) {
+        self.executor.impersonate(address)
+    }
+
+    /// Moves the on-chain time forward.
+    pub fn advance_time(&self, duration: Duration) -> Result<Version> {
+        self.executor.execute_cheat("advance_time", |state_view| {
+            let timestamp_key = StateKey::access_path(CurrentTimeMicroseconds::access_path()?);
+            let state_value = state_view
+                .get_state_value(&timestamp_key)?
+                .ok_or_else(|| format_err!("On-chain time not found"))?;
+            let timestamp = bcs::from_bytes::<CurrentTimeMicroseconds>(state_value.bytes())?;
+            let data = bcs::to_bytes(&CurrentTimeMicroseconds {
+                microseconds: timestamp.microseconds + duration.as_micros() as u64,
+            })?;
+            let write_op = write_value(state_view, &timestamp_key, data)?;
+            Ok((
+                WriteSetMut::new(vec![(timestamp_key, write_op)]).freeze()?,
+                vec![],
+            ))
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SetBalanceRequest {
+    address: AccountAddress,
+    /// The balance in Octas
+    amount: u64,
+}
+
+#[derive(Deserialize)]
+struct ImpersonateRequest {
+    address: AccountAddress,
+}
+
+#[derive(Deserialize)]
+struct AdvanceTimeRequest {
+    seconds: u64,
+}
+
+/// The version at which a cheat is committed
+#[derive(Serialize)]
+struct CheatResponse {
+    version: Version,
+}
+
+async fn run_cheat<F>(cheat: F) -> poem::Result<Json<CheatResponse>>
+where
+    F: FnOnce() -> Result<Version> + Send + 'static,
+{
+    let version = tokio::task::spawn_blocking(cheat)
+        .await
+        .map_err(|err| poem::Error::from(anyhow::Error::from(err)))??;
+    Ok(Json(CheatResponse { version }))
+}
+
+#[handler]
+async fn set_balance(
+    cheats: Data<&Arc<ForkCheats>>,
+    Json(request): Json<SetBalanceRequest>,
+) -> poem::Result<Json<CheatResponse>> {
+    let cheats = cheats.clone();
+    run_cheat(move || cheats.set_balance(request.address, request.amount)).await
+}
+
+#[handler]
+async fn impersonate(cheats: Data<&Arc<ForkCheats>>, Json(request): Json<ImpersonateRequest>) {
+    cheats.impersonate(request.address)
+}
+
+#[handler]
+async fn advance_time(
+    cheats: Data<&Arc<ForkCheats>>,
+    Json(request): Json<AdvanceTimeRequest>,
+) -> poem::Result<Json<CheatResponse>> {
+    let cheats = cheats.clone();
+    run_cheat(move || cheats.advance_time(Duration::from_secs(request.seconds))).await
+}
+
+/// Serves the cheats over HTTP, e.g. `POST http://localhost:<port>/set_balance` with
+/// `{"address": "0x1234", "amount": 100000000}`.
+pub async fn run_cheats_server(
+    cheats: Arc<ForkCheats>,
+    address: SocketAddr,
+) -> std::io::Result<()> {
+    let app = Route::new()
+        .at("/set_balance", post(set_balance))
+        .at("/impersonate", post(impersonate))
+        .at("/advance_time", post(advance_time))
+        .data(cheats);
+    Server::new(TcpListener::bind(address)).run(app).await
+}
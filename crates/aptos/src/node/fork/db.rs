@@ -0,0 +1,542 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::fork::state_cache::ForkStateCache;
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher},
+    HashValue,
+};
+use aptos_storage_interface::{DbReader, Order};
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{NewBlockEvent, CORE_CODE_ADDRESS},
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    contract_event::{ContractEvent, EventWithVersion},
+    event::EventKey,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::{ConfigurationResource, CurrentTimeMicroseconds, OnChainConfig},
+    proof::{
+        accumulator::InMemoryAccumulator, AccumulatorProof, AccumulatorRangeProof,
+        TransactionInfoListWithProof, TransactionInfoWithProof,
+    },
+    state_store::{
+        state_key::{StateKey, StateKeyTag},
+        state_key_prefix::StateKeyPrefix,
+        state_storage_usage::StateStorageUsage,
+        state_value::StateValue,
+    },
+    transaction::{
+        AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionOutput,
+        TransactionOutputListWithProof, TransactionStatus, TransactionWithProof, Version,
+    },
+    write_set::{TransactionWrite, WriteSet},
+};
+use aptos_validator_interface::AptosValidatorInterface;
+use move_core_types::move_resource::MoveResource;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, RwLock},
+};
+use tokio::runtime::Handle;
+
+/// A transaction of the local ledger of the fork.
+#[derive(Clone)]
+struct LocalTransaction {
+    transaction: Transaction,
+    info: TransactionInfo,
+    write_set: WriteSet,
+    events: Vec<ContractEvent>,
+    timestamp_usecs: u64,
+}
+
+impl LocalTransaction {
+    fn output(&self) -> TransactionOutput {
+        TransactionOutput::new(
+            self.write_set.clone(),
+            self.events.clone(),
+            self.info.gas_used(),
+            TransactionStatus::Keep(self.info.status().clone()),
+        )
+    }
+
+    fn with_proof(&self, version: Version, fetch_events: bool) -> TransactionWithProof {
+        TransactionWithProof::new(
+            version,
+            self.transaction.clone(),
+            fetch_events.then(|| self.events.clone()),
+            TransactionInfoWithProof::new(AccumulatorProof::new(vec![]), self.info.clone()),
+        )
+    }
+}
+
+struct LocalLedger {
+    /// The transactions from the fork version, the first one being the transaction at the fork
+    /// version fetched from the forked network.
+    transactions: Vec<LocalTransaction>,
+    versions_by_hash: HashMap<HashValue, Version>,
+    /// The values written by the local transactions, by version.
+    state: HashMap<StateKey, BTreeMap<Version, Option<StateValue>>>,
+}
+
+/// A ledger forked from a remote network at a pinned version.
+///
+/// The state at the fork version is fetched lazily from the remote network and cached on disk,
+/// while the transactions executed on top of it are kept in memory. Each local transaction is
+/// committed as its own block, and the proofs and accumulator hashes are left empty.
+pub struct ForkDb {
+    remote: Arc<dyn AptosValidatorInterface + Send>,
+    runtime: Handle,
+    fork_version: Version,
+    fork_block_height: u64,
+    cache: ForkStateCache,
+    ledger: RwLock<LocalLedger>,
+}
+
+impl ForkDb {
+    pub fn new(
+        remote: Arc<dyn AptosValidatorInterface + Send>,
+        runtime: Handle,
+        fork_version: Version,
+        fork_block_height: u64,
+        fork_timestamp_usecs: u64,
+        (fork_transaction, fork_transaction_info): (Transaction, TransactionInfo),
+        cache: ForkStateCache,
+    ) -> Self {
+        let ledger = LocalLedger {
+            versions_by_hash: HashMap::from([(
+                fork_transaction_info.transaction_hash(),
+                fork_version,
+            )]),
+            transactions: vec![LocalTransaction {
+                transaction: fork_transaction,
+                info: fork_transaction_info,
+                write_set: WriteSet::default(),
+                events: vec![],
+                timestamp_usecs: fork_timestamp_usecs,
+            }],
+            state: HashMap::new(),
+        };
+        Self {
+            remote,
+            runtime,
+            fork_version,
+            fork_block_height,
+            cache,
+            ledger: RwLock::new(ledger),
+        }
+    }
+
+    pub fn fork_version(&self) -> Version {
+        self.fork_version
+    }
+
+    pub fn latest_version(&self) -> Version {
+        self.fork_version + self.ledger.read().unwrap().transactions.len() as Version - 1
+    }
+
+    /// Commits a transaction executed on top of the latest version, and returns its version.
+    pub fn commit(&self, transaction: Transaction, output: TransactionOutput) -> Result<Version> {
+        let (write_set, events, gas_used, status) = output.unpack();
+        let status = match status {
+            TransactionStatus::Keep(status) => status,
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => {
+                bail!("Only the transactions kept can be committed")
+            },
+        };
+        let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+        let info = TransactionInfo::new(
+            CryptoHash::hash(&transaction),
+            CryptoHash::hash(&write_set),
+            InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash(),
+            None,
+            gas_used,
+            status,
+        );
+        let timestamp_key = StateKey::access_path(CurrentTimeMicroseconds::access_path()?);
+        let timestamp_usecs = match write_set.get(&timestamp_key).and_then(|op| op.bytes()) {
+            Some(bytes) => Some(bcs::from_bytes::<CurrentTimeMicroseconds>(bytes)?.microseconds),
+            None => None,
+        };
+
+        let mut ledger = self.ledger.write().unwrap();
+        let version = self.fork_version + ledger.transactions.len() as Version;
+        let timestamp_usecs = timestamp_usecs.unwrap_or_else(|| {
+            ledger
+                .transactions
+                .last()
+                .expect("The fork transaction should be in the ledger")
+                .timestamp_usecs
+        });
+        for (state_key, write_op) in write_set.iter() {
+            ledger
+                .state
+                .entry(state_key.clone())
+                .or_default()
+                .insert(version, write_op.as_state_value());
+        }
+        ledger
+            .versions_by_hash
+            .insert(info.transaction_hash(), version);
+        ledger.transactions.push(LocalTransaction {
+            transaction,
+            info,
+            write_set,
+            events,
+            timestamp_usecs,
+        });
+        Ok(version)
+    }
+
+    fn get_local_transaction(&self, version: Version) -> Result<LocalTransaction> {
+        ensure!(
+            version >= self.fork_version,
+            "Version {} is before the fork version {}",
+            version,
+            self.fork_version
+        );
+        self.ledger
+            .read()
+            .unwrap()
+            .transactions
+            .get((version - self.fork_version) as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("Version {} is not committed yet", version))
+    }
+
+    /// Runs a request to the remote network on the runtime of the fork and waits for its result.
+    /// The storage is read synchronously, sometimes from async contexts, so the requests can't
+    /// block on the runtime.
+    fn run_remote<T, F>(&self, request: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.runtime
+            .spawn(async move { sender.send(request.await) });
+        receiver.recv()?
+    }
+
+    fn get_remote_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if let Some(state_value) = self.cache.get(state_key) {
+            return Ok(state_value);
+        }
+        let remote = self.remote.clone();
+        let key = state_key.clone();
+        let version = self.fork_version;
+        let state_value =
+            self.run_remote(async move { remote.get_state_value_by_version(&key, version).await })?;
+        self.cache.insert(state_key.clone(), state_value.clone())?;
+        Ok(state_value)
+    }
+
+    fn get_epoch(&self, version: Version) -> Result<u64> {
+        let state_key = StateKey::access_path(AccessPath::new(
+            CORE_CODE_ADDRESS,
+            ConfigurationResource::resource_path(),
+        ));
+        let state_value = self
+            .get_state_value_by_version(&state_key, version)?
+            .ok_or_else(|| format_err!("Configuration resource not found"))?;
+        Ok(bcs::from_bytes::<ConfigurationResource>(state_value.bytes())?.epoch())
+    }
+
+    fn new_block_event(&self, version: Version) -> Result<NewBlockEvent> {
+        let txn = self.get_local_transaction(version)?;
+        let height = self.fork_block_height + (version - self.fork_version);
+        Ok(NewBlockEvent::new(
+            AccountAddress::new(*txn.info.transaction_hash()),
+            self.get_epoch(version)?,
+            height,
+            height,
+            vec![],
+            AccountAddress::ZERO,
+            vec![],
+            txn.timestamp_usecs,
+        ))
+    }
+}
+
+impl DbReader for ForkDb {
+    fn get_first_viable_txn_version(&self) -> Result<Version> {
+        Ok(self.fork_version)
+    }
+
+    fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
+        let version = self.latest_version();
+        let txn = self.get_local_transaction(version)?;
+        let block_info = BlockInfo::new(
+            self.get_epoch(version)?,
+            0,
+            HashValue::zero(),
+            HashValue::zero(),
+            version,
+            txn.timestamp_usecs,
+            None,
+        );
+        Ok(Some(LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info, HashValue::zero()),
+            AggregateSignature::empty(),
+        )))
+    }
+
+    fn get_latest_state_checkpoint_version(&self) -> Result<Option<Version>> {
+        Ok(Some(self.latest_version()))
+    }
+
+    fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        ensure!(
+            version >= self.fork_version,
+            "Version {} is before the fork version {}",
+            version,
+            self.fork_version
+        );
+        let local_value = self
+            .ledger
+            .read()
+            .unwrap()
+            .state
+            .get(state_key)
+            .and_then(|values| values.range(..=version).next_back())
+            .map(|(_, value)| value.clone());
+        match local_value {
+            Some(value) => Ok(value),
+            None => self.get_remote_state_value(state_key),
+        }
+    }
+
+    /// Only supports the prefixes of accounts. The modules of the accounts at the fork version
+    /// are not listed, since the remote network serves their resources only.
+    fn get_prefixed_state_value_iterator(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + '_>> {
+        let encoded_prefix = key_prefix.encode()?;
+        ensure!(
+            encoded_prefix.len() == 1 + AccountAddress::LENGTH
+                && encoded_prefix[0] == StateKeyTag::AccessPath as u8,
+            "Only the prefixes of accounts are supported"
+        );
+        let address = AccountAddress::from_bytes(&encoded_prefix[1..])?;
+
+        let remote = self.remote.clone();
+        let fork_version = self.fork_version;
+        let account_state = self.run_remote(async move {
+            remote
+                .get_account_state_by_version(address, fork_version)
+                .await
+        })?;
+        let mut values = BTreeMap::new();
+        if let Some(account_state) = account_state {
+            for (path, value) in account_state.iter() {
+                values.insert(
+                    StateKey::access_path(AccessPath::new(address, path.clone())),
+                    Some(StateValue::new_legacy(value.clone())),
+                );
+            }
+        }
+        for (state_key, local_values) in self.ledger.read().unwrap().state.iter() {
+            if !key_prefix.is_prefix(state_key)? {
+                continue;
+            }
+            if let Some((_, value)) = local_values.range(..=version).next_back() {
+                values.insert(state_key.clone(), value.clone());
+            }
+        }
+
+        let values: Vec<_> = values
+            .into_iter()
+            .filter(|(state_key, _)| cursor.map_or(true, |cursor| state_key >= cursor))
+            .filter_map(|(state_key, value)| value.map(|value| Ok((state_key, value))))
+            .collect();
+        Ok(Box::new(values.into_iter()))
+    }
+
+    fn get_state_storage_usage(&self, _version: Option<Version>) -> Result<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+
+    fn get_transaction_by_version(
+        &self,
+        version: Version,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<TransactionWithProof> {
+        ensure!(
+            version <= ledger_version,
+            "Version {} is after the ledger version {}",
+            version,
+            ledger_version
+        );
+        Ok(self
+            .get_local_transaction(version)?
+            .with_proof(version, fetch_events))
+    }
+
+    fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        let version = self
+            .ledger
+            .read()
+            .unwrap()
+            .versions_by_hash
+            .get(&hash)
+            .copied();
+        match version {
+            Some(version) if version <= ledger_version => Ok(Some(
+                self.get_local_transaction(version)?
+                    .with_proof(version, fetch_events),
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_transaction_outputs(
+        &self,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<TransactionOutputListWithProof> {
+        ensure!(
+            start_version >= self.fork_version,
+            "Version {} is before the fork version {}",
+            start_version,
+            self.fork_version
+        );
+        let end_version = std::cmp::min(
+            start_version.saturating_add(limit),
+            std::cmp::min(ledger_version, self.latest_version()) + 1,
+        );
+        if start_version >= end_version {
+            return Ok(TransactionOutputListWithProof::new_empty());
+        }
+
+        let mut transactions_and_outputs = vec![];
+        let mut infos = vec![];
+        for version in start_version..end_version {
+            let txn = self.get_local_transaction(version)?;
+            transactions_and_outputs.push((txn.transaction.clone(), txn.output()));
+            infos.push(txn.info);
+        }
+        Ok(TransactionOutputListWithProof::new(
+            transactions_and_outputs,
+            Some(start_version),
+            TransactionInfoListWithProof::new(AccumulatorRangeProof::new_empty(), infos),
+        ))
+    }
+
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        seq_num: u64,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionsWithProof> {
+        let ledger = self.ledger.read().unwrap();
+        let txns = ledger
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(i, txn)| (self.fork_version + i as Version, txn))
+            .filter(|(version, txn)| {
+                *version <= ledger_version
+                    && matches!(
+                        &txn.transaction,
+                        Transaction::UserTransaction(user_txn)
+                            if user_txn.sender() == address
+                                && user_txn.sequence_number() >= seq_num
+                    )
+            })
+            .take(limit as usize)
+            .map(|(version, txn)| txn.with_proof(version, include_events))
+            .collect();
+        Ok(AccountTransactionsWithProof::new(txns))
+    }
+
+    /// Only the events emitted by the local transactions are served.
+    fn get_events(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        let ledger = self.ledger.read().unwrap();
+        let events = ledger
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(i, txn)| (self.fork_version + i as Version, txn))
+            .filter(|(version, _)| *version <= ledger_version)
+            .flat_map(|(version, txn)| {
+                txn.events
+                    .iter()
+                    .filter(|event| event.key() == event_key)
+                    .map(move |event| EventWithVersion::new(version, event.clone()))
+            });
+        let events = match order {
+            Order::Ascending => events
+                .filter(|event| event.event.sequence_number() >= start)
+                .take(limit as usize)
+                .collect(),
+            Order::Descending => {
+                let events: Vec<_> = events
+                    .filter(|event| event.event.sequence_number() <= start)
+                    .collect();
+                events.into_iter().rev().take(limit as usize).collect()
+            },
+        };
+        Ok(events)
+    }
+
+    fn get_block_timestamp(&self, version: Version) -> Result<u64> {
+        Ok(self.get_local_transaction(version)?.timestamp_usecs)
+    }
+
+    fn get_next_block_event(&self, version: Version) -> Result<(Version, NewBlockEvent)> {
+        let version = std::cmp::max(version, self.fork_version);
+        Ok((version, self.new_block_event(version)?))
+    }
+
+    fn get_block_info_by_version(
+        &self,
+        version: Version,
+    ) -> Result<(Version, Version, NewBlockEvent)> {
+        Ok((version, version, self.new_block_event(version)?))
+    }
+
+    fn get_block_info_by_height(&self, height: u64) -> Result<(Version, Version, NewBlockEvent)> {
+        ensure!(
+            height >= self.fork_block_height,
+            "Block {} is before the fork block {}",
+            height,
+            self.fork_block_height
+        );
+        let version = self.fork_version + (height - self.fork_block_height);
+        self.get_block_info_by_version(version)
+    }
+
+    fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
+        Ok(HashValue::zero())
+    }
+
+    fn indexer_enabled(&self) -> bool {
+        false
+    }
+}
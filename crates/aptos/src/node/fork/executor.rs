@@ -0,0 +1,160 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::fork::db::ForkDb;
+use anyhow::{format_err, Result};
+use aptos_mempool::{MempoolClientRequest, MempoolEventsReceiver, SubmissionStatus};
+use aptos_storage_interface::state_view::DbStateView;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    contract_event::ContractEvent,
+    event::EventKey,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    transaction::{
+        ChangeSet, ExecutionStatus, SignedTransaction, Transaction, TransactionOutput,
+        TransactionStatus, Version, WriteSetPayload,
+    },
+    write_set::WriteSet,
+};
+use aptos_vm::{AptosVM, VMExecutor};
+use futures::StreamExt;
+use move_core_types::{
+    ident_str,
+    language_storage::{StructTag, TypeTag},
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Returns the event tagging the write set transactions committed by the cheats, so they can be
+/// told apart from a genesis transaction. It is emitted by the zero address, with the name of the
+/// cheat as a `0x1::string::String`.
+pub fn cheat_event(name: &str) -> Result<ContractEvent> {
+    let type_tag = TypeTag::Struct(Box::new(StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: ident_str!("string").to_owned(),
+        name: ident_str!("String").to_owned(),
+        type_params: vec![],
+    }));
+    Ok(ContractEvent::new(
+        EventKey::new(0, AccountAddress::ZERO),
+        0,
+        type_tag,
+        bcs::to_bytes(name)?,
+    ))
+}
+
+/// Executes the transactions submitted to the fork and the cheats on top of its latest version,
+/// one at a time.
+pub struct ForkExecutor {
+    db: Arc<ForkDb>,
+    /// The accounts whose transactions are executed without checking their signatures.
+    impersonated: RwLock<HashSet<AccountAddress>>,
+    /// Held while executing, so that each transaction reads the state it is committed on top of.
+    execution_lock: Mutex<()>,
+}
+
+impl ForkExecutor {
+    pub fn new(db: Arc<ForkDb>) -> Self {
+        Self {
+            db,
+            impersonated: RwLock::new(HashSet::new()),
+            execution_lock: Mutex::new(()),
+        }
+    }
+
+    fn latest_state_view(&self) -> DbStateView {
+        DbStateView {
+            db: self.db.clone(),
+            version: Some(self.db.latest_version()),
+        }
+    }
+
+    /// Executes the next transactions of the account without checking their signatures. The
+    /// state of the account is left untouched, so they must still carry the public key matching
+    /// its authentication key, as when simulating them.
+    pub fn impersonate(&self, address: AccountAddress) {
+        self.impersonated.write().unwrap().insert(address);
+    }
+
+    /// Executes a user transaction as its own block, and commits it unless it is discarded.
+    pub fn execute_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
+        let _guard = self.execution_lock.lock().unwrap();
+
+        let state_view = self.latest_state_view();
+        let output = if !txn.signature_is_valid()
+            && self.impersonated.read().unwrap().contains(&txn.sender())
+        {
+            // Simulation runs the transaction like execution does, but skips the signature check.
+            AptosVM::simulate_signed_transaction(&txn, &state_view).1
+        } else {
+            AptosVM::execute_block(
+                vec![Transaction::UserTransaction(txn.clone())],
+                &state_view,
+                None,
+            )
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+            .pop()
+            .expect("Executing a transaction should produce an output")
+        };
+        match output.status() {
+            TransactionStatus::Keep(_) => {
+                self.db.commit(Transaction::UserTransaction(txn), output)?;
+                Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None))
+            },
+            TransactionStatus::Discard(status) => Ok((
+                MempoolStatus::new(MempoolStatusCode::VmError),
+                Some(*status),
+            )),
+            TransactionStatus::Retry => {
+                Ok((MempoolStatus::new(MempoolStatusCode::UnknownStatus), None))
+            },
+        }
+    }
+
+    /// Commits the writes and events computed from the latest state by the cheat, as a write set
+    /// transaction tagged with the [`cheat_event`] of its name.
+    pub fn execute_cheat<F>(&self, name: &str, cheat: F) -> Result<Version>
+    where
+        F: FnOnce(&DbStateView) -> Result<(WriteSet, Vec<ContractEvent>)>,
+    {
+        let _guard = self.execution_lock.lock().unwrap();
+
+        let (write_set, mut events) = cheat(&self.latest_state_view())?;
+        events.push(cheat_event(name)?);
+        let txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::new(
+            write_set.clone(),
+            events.clone(),
+        )));
+        let output = TransactionOutput::new(
+            write_set,
+            events,
+            0,
+            TransactionStatus::Keep(ExecutionStatus::Success),
+        );
+        self.db.commit(txn, output)
+    }
+
+    /// Serves the requests of the REST API to the mempool. The transactions submitted are
+    /// executed right away, so none of them is ever pending.
+    pub async fn run(self: Arc<Self>, mut mempool_receiver: MempoolEventsReceiver) {
+        while let Some(request) = mempool_receiver.next().await {
+            match request {
+                MempoolClientRequest::SubmitTransaction(txn, callback) => {
+                    let executor = self.clone();
+                    let result =
+                        tokio::task::spawn_blocking(move || executor.execute_transaction(txn))
+                            .await
+                            .map_err(anyhow::Error::from)
+                            .and_then(|result| result);
+                    let _ = callback.send(result);
+                },
+                MempoolClientRequest::GetTransactionByHash(_, callback) => {
+                    let _ = callback.send(None);
+                },
+            }
+        }
+    }
+}
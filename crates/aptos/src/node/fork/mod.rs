@@ -0,0 +1,151 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod cheats;
+pub mod db;
+pub mod executor;
+pub mod state_cache;
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    common::types::{CliError, CliTypedResult},
+    node::fork::{
+        cheats::{run_cheats_server, ForkCheats},
+        db::ForkDb,
+        executor::ForkExecutor,
+        state_cache::ForkStateCache,
+    },
+};
+use aptos_config::config::NodeConfig;
+use aptos_rest_client::Client;
+use aptos_types::{chain_id::ChainId, transaction::Version};
+use aptos_validator_interface::{AptosValidatorInterface, RestDebuggerInterface};
+use clap::Parser;
+use futures::FutureExt;
+use reqwest::Url;
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    sync::Arc,
+};
+
+/// The size of the channel between the REST API and the executor of the fork
+const MEMPOOL_CHANNEL_SIZE: usize = 1024;
+
+#[derive(Parser)]
+pub struct ForkArgs {
+    /// URL of the REST API of a network to fork, instead of starting a new chain
+    ///
+    /// The state of the network at the fork version is fetched lazily and cached in `test-dir`,
+    /// and the transactions submitted to the local node are executed on top of it, without
+    /// consensus.  Cheats are served alongside to set balances, impersonate accounts and advance
+    /// the on-chain time.
+    #[clap(long, conflicts_with_all = &["with-faucet", "config-path", "test-config-override"])]
+    fork_url: Option<Url>,
+
+    /// Version of the network to fork
+    ///
+    /// Defaults to the latest version of the network
+    #[clap(long, requires = "fork-url")]
+    fork_version: Option<Version>,
+
+    /// Port to run the REST API of the fork on
+    #[clap(long, default_value = "8080")]
+    fork_api_port: u16,
+
+    /// Port to run the cheats of the fork on
+    ///
+    /// When running, you'll be able to use the cheats with POST requests e.g.
+    /// `http://localhost:<port>/set_balance` with `{"address": "0x1234", "amount": 100000000}`,
+    /// `http://localhost:<port>/impersonate` with `{"address": "0x1234"}` and
+    /// `http://localhost:<port>/advance_time` with `{"seconds": 3600}`. The transactions of an
+    /// impersonated account are accepted with any signature, as long as they carry the public key
+    /// matching its authentication key.
+    #[clap(long, default_value = "8082")]
+    cheats_port: u16,
+
+    /// Address of the interface to run the REST API and the cheats of the fork on
+    ///
+    /// Defaults to the loopback interface, as anyone reaching the cheats can change any state of
+    /// the fork. Set it to e.g. `0.0.0.0` to serve them on all the interfaces.
+    #[clap(long, default_value = "127.0.0.1")]
+    bind_to: IpAddr,
+}
+
+impl ForkArgs {
+    pub fn is_enabled(&self) -> bool {
+        self.fork_url.is_some()
+    }
+
+    /// Runs a local node forked from the remote network, until one of its components stops.
+    pub async fn run(self, test_dir: &Path) -> CliTypedResult<()> {
+        let fork_url = self.fork_url.ok_or_else(|| {
+            CliError::CommandArgumentError("Missing URL of the network to fork".to_string())
+        })?;
+        let client = Client::new(fork_url.clone());
+        let remote = Arc::new(RestDebuggerInterface::new(client.clone()));
+
+        let chain_id = ChainId::new(client.get_ledger_information().await?.inner().chain_id);
+        let fork_version = match self.fork_version {
+            Some(fork_version) => fork_version,
+            None => remote.get_latest_version().await?,
+        };
+        let fork_block = client
+            .get_block_by_version_bcs(fork_version, false)
+            .await?
+            .into_inner();
+        let (mut txns, mut txn_infos) = remote.get_committed_transactions(fork_version, 1).await?;
+        let fork_transaction = match (txns.pop(), txn_infos.pop()) {
+            (Some(txn), Some(txn_info)) => (txn, txn_info),
+            _ => {
+                return Err(CliError::UnexpectedError(format!(
+                    "Failed to fetch the transaction at version {}",
+                    fork_version
+                )))
+            },
+        };
+
+        let cache = ForkStateCache::open(
+            &test_dir
+                .join("fork")
+                .join(format!("state-cache-{}-{}.bcs", chain_id, fork_version)),
+        )?;
+        let db = Arc::new(ForkDb::new(
+            remote,
+            tokio::runtime::Handle::current(),
+            fork_version,
+            fork_block.block_height,
+            fork_block.block_timestamp,
+            fork_transaction,
+            cache,
+        ));
+        let executor = Arc::new(ForkExecutor::new(db.clone()));
+        let cheats = Arc::new(ForkCheats::new(executor.clone()));
+
+        let mut config = NodeConfig::default();
+        config.api.address = SocketAddr::new(self.bind_to, self.fork_api_port);
+        // Gas estimation reads the history of the blocks, which isn't available on the fork.
+        config.api.gas_estimation.enabled = false;
+        let (mempool_sender, mempool_receiver) =
+            futures::channel::mpsc::channel(MEMPOOL_CHANNEL_SIZE);
+        let api_runtime = aptos_api::bootstrap(&config, chain_id, db, mempool_sender)?;
+
+        let cheats_address = SocketAddr::new(self.bind_to, self.cheats_port);
+        eprintln!(
+            "Forked {} ({}) at version {}\n\tREST API: http://{}\n\tCheats: http://{}",
+            fork_url, chain_id, fork_version, config.api.address, cheats_address
+        );
+
+        let executor_future = executor.run(mempool_receiver);
+        let cheats_future = run_cheats_server(cheats, cheats_address).map(|result| {
+            eprintln!("Cheats server stopped unexpectedly {:#?}", result);
+        });
+        futures::future::select(Box::pin(executor_future), Box::pin(cheats_future)).await;
+        api_runtime.shutdown_background();
+
+        Err(CliError::UnexpectedError(
+            "One of the components stopped unexpectedly".to_string(),
+        ))
+    }
+}
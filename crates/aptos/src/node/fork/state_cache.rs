@@ -0,0 +1,88 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufReader, ErrorKind, Read, Write},
+    path::Path,
+    sync::{Mutex, RwLock},
+};
+
+/// An on-disk cache of the state values fetched from the forked network at the pinned version,
+/// so restarting the fork doesn't fetch them again.
+///
+/// The values are appended to a single file as length prefixed BCS records, and all loaded in
+/// memory when the cache is opened.
+pub struct ForkStateCache {
+    values: RwLock<HashMap<StateKey, Option<StateValue>>>,
+    file: Mutex<File>,
+}
+
+impl ForkStateCache {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        let mut values = HashMap::new();
+        let mut valid_len = 0;
+        let mut reader = BufReader::new(&mut file);
+        loop {
+            let mut len = [0u8; 4];
+            let mut record = vec![];
+            let read = reader.read_exact(&mut len).and_then(|()| {
+                record.resize(u32::from_le_bytes(len) as usize, 0);
+                reader.read_exact(&mut record)
+            });
+            match read {
+                Ok(()) => (),
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            }
+            let (state_key, state_value): (StateKey, Option<StateValue>) = bcs::from_bytes(&record)
+                .with_context(|| format!("Corrupted state cache {}", path.display()))?;
+            values.insert(state_key, state_value);
+            valid_len += (len.len() + record.len()) as u64;
+        }
+        // Drops a record cut short by an interrupted write, its value is fetched again.
+        file.set_len(valid_len)?;
+
+        Ok(Self {
+            values: RwLock::new(values),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn get(&self, state_key: &StateKey) -> Option<Option<StateValue>> {
+        self.values.read().unwrap().get(state_key).cloned()
+    }
+
+    pub fn insert(&self, state_key: StateKey, state_value: Option<StateValue>) -> Result<()> {
+        let record = bcs::to_bytes(&(&state_key, &state_value))?;
+        {
+            let mut file = self.file.lock().unwrap();
+            file.write_all(&(record.len() as u32).to_le_bytes())?;
+            file.write_all(&record)?;
+        }
+        self.values.write().unwrap().insert(state_key, state_value);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::fork::{db::ForkDb, state_cache::ForkStateCache};
+use anyhow::{bail, Result};
+use aptos_crypto::HashValue;
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{
+        ExecutionFingerprint, ExecutionStatus, Transaction, TransactionInfo, TransactionOutput,
+        TransactionStatus, Version,
+    },
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+use aptos_validator_interface::AptosValidatorInterface;
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const FORK_VERSION: Version = 10;
+
+/// A remote network serving the state values at the fork version only.
+struct MockRemote {
+    values: HashMap<StateKey, StateValue>,
+    num_requests: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl AptosValidatorInterface for MockRemote {
+    async fn get_account_state_by_version(
+        &self,
+        _account: AccountAddress,
+        _version: Version,
+    ) -> Result<Option<AccountState>> {
+        bail!("Not served by the mock remote")
+    }
+
+    async fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        assert_eq!(version, FORK_VERSION);
+        self.num_requests.fetch_add(1, Ordering::SeqCst);
+        Ok(self.values.get(state_key).cloned())
+    }
+
+    async fn get_committed_transactions(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+        bail!("Not served by the mock remote")
+    }
+
+    async fn get_committed_transactions_with_write_sets(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>, Vec<WriteSet>)> {
+        bail!("Not served by the mock remote")
+    }
+
+    async fn get_execution_fingerprints(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        bail!("Not served by the mock remote")
+    }
+
+    async fn get_latest_version(&self) -> Result<Version> {
+        Ok(FORK_VERSION)
+    }
+
+    async fn get_version_by_account_sequence(
+        &self,
+        _account: AccountAddress,
+        _seq: u64,
+    ) -> Result<Option<Version>> {
+        bail!("Not served by the mock remote")
+    }
+}
+
+fn state_key(name: &str) -> StateKey {
+    StateKey::raw(name.as_bytes().to_vec())
+}
+
+fn state_value(data: &str) -> StateValue {
+    StateValue::new_legacy(data.as_bytes().to_vec())
+}
+
+fn write_set_output(writes: Vec<(StateKey, WriteOp)>) -> TransactionOutput {
+    TransactionOutput::new(
+        WriteSetMut::new(writes).freeze().unwrap(),
+        vec![],
+        0,
+        TransactionStatus::Keep(ExecutionStatus::Success),
+    )
+}
+
+#[test]
+fn test_state_cache_drops_truncated_record() {
+    let path = TempPath::new();
+    {
+        let cache = ForkStateCache::open(path.path()).unwrap();
+        cache
+            .insert(state_key("a"), Some(state_value("1")))
+            .unwrap();
+        cache.insert(state_key("b"), None).unwrap();
+    }
+    let valid_len = std::fs::metadata(path.path()).unwrap().len();
+
+    // Appends a record cut short by an interrupted write: its full length, and half its bytes.
+    let record = bcs::to_bytes(&(state_key("c"), Some(state_value("3")))).unwrap();
+    {
+        let mut file = OpenOptions::new().append(true).open(path.path()).unwrap();
+        file.write_all(&(record.len() as u32).to_le_bytes())
+            .unwrap();
+        file.write_all(&record[..record.len() / 2]).unwrap();
+    }
+
+    let cache = ForkStateCache::open(path.path()).unwrap();
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&state_key("a")), Some(Some(state_value("1"))));
+    assert_eq!(cache.get(&state_key("b")), Some(None));
+    assert_eq!(cache.get(&state_key("c")), None);
+    assert_eq!(std::fs::metadata(path.path()).unwrap().len(), valid_len);
+
+    // The records appended afterwards are read back.
+    cache
+        .insert(state_key("c"), Some(state_value("3")))
+        .unwrap();
+    drop(cache);
+    let cache = ForkStateCache::open(path.path()).unwrap();
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get(&state_key("c")), Some(Some(state_value("3"))));
+}
+
+#[test]
+fn test_get_state_value_by_version_with_local_overrides() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let remote = Arc::new(MockRemote {
+        values: HashMap::from([
+            (state_key("a"), state_value("remote a")),
+            (state_key("b"), state_value("remote b")),
+        ]),
+        num_requests: AtomicUsize::new(0),
+    });
+    let path = TempPath::new();
+    let db = ForkDb::new(
+        remote.clone(),
+        runtime.handle().clone(),
+        FORK_VERSION,
+        0,
+        0,
+        (
+            Transaction::StateCheckpoint(HashValue::zero()),
+            TransactionInfo::new(
+                HashValue::zero(),
+                HashValue::zero(),
+                HashValue::zero(),
+                None,
+                0,
+                ExecutionStatus::Success,
+            ),
+        ),
+        ForkStateCache::open(path.path()).unwrap(),
+    );
+
+    // Version 11 overrides `a`, version 12 deletes it and creates `c`.
+    let version = db
+        .commit(
+            Transaction::StateCheckpoint(HashValue::random()),
+            write_set_output(vec![(
+                state_key("a"),
+                WriteOp::Modification(b"local a".to_vec()),
+            )]),
+        )
+        .unwrap();
+    assert_eq!(version, FORK_VERSION + 1);
+    let version = db
+        .commit(
+            Transaction::StateCheckpoint(HashValue::random()),
+            write_set_output(vec![
+                (state_key("a"), WriteOp::Deletion),
+                (state_key("c"), WriteOp::Creation(b"local c".to_vec())),
+            ]),
+        )
+        .unwrap();
+    assert_eq!(version, FORK_VERSION + 2);
+
+    let get = |name: &str, version: Version| {
+        db.get_state_value_by_version(&state_key(name), version)
+            .unwrap()
+    };
+    assert_eq!(get("a", FORK_VERSION), Some(state_value("remote a")));
+    assert_eq!(get("a", FORK_VERSION + 1), Some(state_value("local a")));
+    assert_eq!(get("a", FORK_VERSION + 2), None);
+    assert_eq!(get("b", FORK_VERSION + 2), Some(state_value("remote b")));
+    assert_eq!(get("c", FORK_VERSION + 1), None);
+    assert_eq!(get("c", FORK_VERSION + 2), Some(state_value("local c")));
+    assert!(db
+        .get_state_value_by_version(&state_key("a"), FORK_VERSION - 1)
+        .is_err());
+
+    // The values at the fork version are fetched once, then served from the cache.
+    let num_requests = remote.num_requests.load(Ordering::SeqCst);
+    assert_eq!(get("a", FORK_VERSION), Some(state_value("remote a")));
+    assert_eq!(get("b", FORK_VERSION), Some(state_value("remote b")));
+    assert_eq!(remote.num_requests.load(Ordering::SeqCst), num_requests);
+}
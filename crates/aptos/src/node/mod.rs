@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod analyze;
+pub mod fork;
 
 use crate::{
     common::{
@@ -14,9 +15,12 @@ use crate::{
     },
     config::GlobalConfig,
     genesis::git::from_yaml,
-    node::analyze::{
-        analyze_validators::{AnalyzeValidators, ValidatorStats},
-        fetch_metadata::FetchMetadata,
+    node::{
+        analyze::{
+            analyze_validators::{AnalyzeValidators, ValidatorStats},
+            fetch_metadata::FetchMetadata,
+        },
+        fork::ForkArgs,
     },
 };
 use aptos_backup_cli::{
@@ -25,8 +29,18 @@ use aptos_backup_cli::{
     utils::GlobalRestoreOpt,
 };
 use aptos_cached_packages::aptos_stdlib;
-use aptos_config::config::NodeConfig;
-use aptos_crypto::{bls12381, bls12381::PublicKey, x25519, ValidCryptoMaterialStringExt};
+use aptos_config::config::{
+    NodeConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_consensus::{
+    EpochValidators, LeaderElectionExplanation, LeaderReputationExplainer, LeaderReputationParams,
+};
+use aptos_consensus_types::equivocation_evidence::EquivocationEvidence;
+use aptos_crypto::{
+    bls12381, bls12381::PublicKey, x25519, HashValue, ValidCryptoMaterialStringExt,
+};
+use aptos_db::AptosDB;
 use aptos_faucet_core::server::{FunderKeyEnum, RunConfig};
 use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
 use aptos_logger::Level;
@@ -34,14 +48,19 @@ use aptos_network_checker::args::{
     validate_address, CheckEndpointArgs, HandshakeArgs, NodeAddressArgs,
 };
 use aptos_rest_client::{aptos_api_types::VersionedEvent, Client, State};
+use aptos_storage_interface::DbReader;
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{BlockResource, CORE_CODE_ADDRESS},
     chain_id::ChainId,
     network_address::NetworkAddress,
-    on_chain_config::{ConfigurationResource, ConsensusScheme, ValidatorSet},
+    on_chain_config::{
+        ConfigurationResource, ConsensusScheme, LeaderReputationType, OnChainConfig,
+        OnChainConsensusConfig, ProposerAndVoterConfig, ValidatorSet,
+    },
     stake_pool::StakePool,
     staking_contract::StakingContractStore,
+    state_store::state_key::StateKey,
     validator_info::ValidatorInfo,
     validator_performances::ValidatorPerformances,
     vesting::VestingAdminStore,
@@ -58,7 +77,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
-    path::PathBuf,
+    path::{Path, PathBuf},
     pin::Pin,
     thread,
     time::Duration,
@@ -76,12 +95,14 @@ pub enum NodeTool {
     AnalyzeValidatorPerformance(AnalyzeValidatorPerformance),
     BootstrapDb(BootstrapDb),
     CheckNetworkConnectivity(CheckNetworkConnectivity),
+    ExplainLeaderReputation(ExplainLeaderReputation),
     GetPerformance(GetPerformance),
     GetStakePool(GetStakePool),
     InitializeValidator(InitializeValidator),
     JoinValidatorSet(JoinValidatorSet),
     LeaveValidatorSet(LeaveValidatorSet),
     ShowEpochInfo(ShowEpochInfo),
+    ShowEquivocationEvidence(ShowEquivocationEvidence),
     ShowValidatorConfig(ShowValidatorConfig),
     ShowValidatorSet(ShowValidatorSet),
     ShowValidatorStake(ShowValidatorStake),
@@ -100,12 +121,14 @@ impl NodeTool {
                     .await
             },
             CheckNetworkConnectivity(tool) => tool.execute_serialized().await,
+            ExplainLeaderReputation(tool) => tool.execute_serialized().await,
             GetPerformance(tool) => tool.execute_serialized().await,
             GetStakePool(tool) => tool.execute_serialized().await,
             InitializeValidator(tool) => tool.execute_serialized().await,
             JoinValidatorSet(tool) => tool.execute_serialized().await,
             LeaveValidatorSet(tool) => tool.execute_serialized().await,
             ShowEpochInfo(tool) => tool.execute_serialized().await,
+            ShowEquivocationEvidence(tool) => tool.execute_serialized().await,
             ShowValidatorSet(tool) => tool.execute_serialized().await,
             ShowValidatorStake(tool) => tool.execute_serialized().await,
             ShowValidatorConfig(tool) => tool.execute_serialized().await,
@@ -1095,6 +1118,9 @@ pub struct RunLocalTestnet {
     #[clap(long)]
     do_not_delegate: bool,
 
+    #[clap(flatten)]
+    fork_args: ForkArgs,
+
     #[clap(flatten)]
     prompt_options: PromptOptions,
 }
@@ -1130,6 +1156,11 @@ impl CliCommand<()> for RunLocalTestnet {
             })?;
         }
 
+        // Run a fork of the remote network instead of a new chain
+        if self.fork_args.is_enabled() {
+            return self.fork_args.run(&test_dir).await;
+        }
+
         // Spawn the node in a separate thread
         let config_path = self.config_path.clone();
         let test_dir_copy = test_dir.clone();
@@ -1591,6 +1622,418 @@ impl CliCommand<EpochInfo> for ShowEpochInfo {
     }
 }
 
+/// Show the equivocation evidence collected by a node
+///
+/// Reads the evidence of validators that double-voted or double-proposed from the consensus
+/// db of a node. The evidence contains the conflicting signed messages, so it can be
+/// verified by anyone against the validator set of the epoch.
+#[derive(Parser)]
+pub struct ShowEquivocationEvidence {
+    /// Path to the storage directory of the node (i.e., the one containing `consensus_db`)
+    #[clap(long, parse(from_os_str))]
+    pub(crate) storage_dir: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<Vec<EquivocationEvidence>> for ShowEquivocationEvidence {
+    fn command_name(&self) -> &'static str {
+        "ShowEquivocationEvidence"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<EquivocationEvidence>> {
+        aptos_consensus::read_equivocation_evidence(&self.storage_dir)
+            .map_err(|error| CliError::UnexpectedError(error.to_string()))
+    }
+}
+
+/// Explain the leader election of a round
+///
+/// Reproduces the leader reputation computation of the validators for an epoch and round
+/// from the NewBlockEvents of the chain. Shows the votes, proposals and failed proposals of
+/// each validator in the reputation window, and the resulting weights. Any of the leader
+/// reputation parameters can be overridden, to simulate a change of the on-chain config.
+#[derive(Parser)]
+pub struct ExplainLeaderReputation {
+    /// Epoch of the election
+    ///
+    /// Defaults to the latest epoch
+    #[clap(long)]
+    pub epoch: Option<u64>,
+
+    /// Round of the election
+    ///
+    /// Defaults to the round after the last committed block of the epoch
+    #[clap(long)]
+    pub round: Option<u64>,
+
+    /// Directory of a node's DB to read the history from, instead of the REST API
+    #[clap(long, parse(from_os_str))]
+    pub db_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) simulation_args: LeaderReputationSimulationArgs,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// Overrides of the on-chain leader reputation parameters
+#[derive(Parser)]
+pub struct LeaderReputationSimulationArgs {
+    /// Selection weight for active validators with proposer failures below threshold
+    #[clap(long)]
+    pub active_weight: Option<u64>,
+    /// Selection weight for inactive validators with proposer failures below threshold
+    #[clap(long)]
+    pub inactive_weight: Option<u64>,
+    /// Selection weight for validators with proposer failures above threshold
+    #[clap(long)]
+    pub failed_weight: Option<u64>,
+    /// Threshold of failures in the rounds the validator was the proposer, in percents
+    #[clap(long)]
+    pub failure_threshold_percent: Option<u32>,
+    /// Window of the proposer statistics, as a multiplier of the number of validators
+    #[clap(long)]
+    pub proposer_window_num_validators_multiplier: Option<usize>,
+    /// Window of the voter statistics, as a multiplier of the number of validators
+    #[clap(long)]
+    pub voter_window_num_validators_multiplier: Option<usize>,
+    /// Whether to multiply the weights by the voting power
+    #[clap(long)]
+    pub weight_by_voting_power: Option<bool>,
+    /// Number of previous epochs whose history is considered
+    #[clap(long)]
+    pub use_history_from_previous_epoch_max_count: Option<u32>,
+    /// Number of most recent rounds excluded from the history
+    #[clap(long)]
+    pub exclude_round: Option<u64>,
+}
+
+impl LeaderReputationSimulationArgs {
+    /// Returns the parameters with the overrides, if there are any
+    fn simulate(&self, params: &LeaderReputationParams) -> Option<LeaderReputationParams> {
+        if self.active_weight.is_none()
+            && self.inactive_weight.is_none()
+            && self.failed_weight.is_none()
+            && self.failure_threshold_percent.is_none()
+            && self.proposer_window_num_validators_multiplier.is_none()
+            && self.voter_window_num_validators_multiplier.is_none()
+            && self.weight_by_voting_power.is_none()
+            && self.use_history_from_previous_epoch_max_count.is_none()
+            && self.exclude_round.is_none()
+        {
+            return None;
+        }
+
+        let simulate_config = |config: &ProposerAndVoterConfig| ProposerAndVoterConfig {
+            active_weight: self.active_weight.unwrap_or(config.active_weight),
+            inactive_weight: self.inactive_weight.unwrap_or(config.inactive_weight),
+            failed_weight: self.failed_weight.unwrap_or(config.failed_weight),
+            failure_threshold_percent: self
+                .failure_threshold_percent
+                .unwrap_or(config.failure_threshold_percent),
+            proposer_window_num_validators_multiplier: self
+                .proposer_window_num_validators_multiplier
+                .unwrap_or(config.proposer_window_num_validators_multiplier),
+            voter_window_num_validators_multiplier: self
+                .voter_window_num_validators_multiplier
+                .unwrap_or(config.voter_window_num_validators_multiplier),
+            weight_by_voting_power: self
+                .weight_by_voting_power
+                .unwrap_or(config.weight_by_voting_power),
+            use_history_from_previous_epoch_max_count: self
+                .use_history_from_previous_epoch_max_count
+                .unwrap_or(config.use_history_from_previous_epoch_max_count),
+        };
+        Some(LeaderReputationParams {
+            leader_reputation_type: match &params.leader_reputation_type {
+                LeaderReputationType::ProposerAndVoter(config) => {
+                    LeaderReputationType::ProposerAndVoter(simulate_config(config))
+                },
+                LeaderReputationType::ProposerAndVoterV2(config) => {
+                    LeaderReputationType::ProposerAndVoterV2(simulate_config(config))
+                },
+            },
+            exclude_round: self.exclude_round.unwrap_or(params.exclude_round),
+            max_failed_authors_to_store: params.max_failed_authors_to_store,
+        })
+    }
+}
+
+/// Returns the number of previous epochs whose history is considered by any of the parameters
+fn history_epochs(
+    onchain_params: &LeaderReputationParams,
+    simulated_params: Option<&LeaderReputationParams>,
+) -> u64 {
+    [Some(onchain_params), simulated_params]
+        .into_iter()
+        .flatten()
+        .map(|params| match &params.leader_reputation_type {
+            LeaderReputationType::ProposerAndVoter(config)
+            | LeaderReputationType::ProposerAndVoterV2(config) => {
+                config.use_history_from_previous_epoch_max_count as u64
+            },
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// The leader election with the on-chain parameters, and with the simulated ones
+#[derive(Debug, Serialize)]
+pub struct LeaderReputationReport {
+    pub onchain: LeaderElectionExplanation,
+    pub simulated: Option<LeaderElectionExplanation>,
+}
+
+#[async_trait]
+impl CliCommand<LeaderReputationReport> for ExplainLeaderReputation {
+    fn command_name(&self) -> &'static str {
+        "ExplainLeaderReputation"
+    }
+
+    async fn execute(self) -> CliTypedResult<LeaderReputationReport> {
+        match &self.db_dir {
+            Some(db_dir) => self.explain_from_db(db_dir),
+            None => self.explain_from_rest().await,
+        }
+    }
+}
+
+impl ExplainLeaderReputation {
+    /// Explains the election from the NewBlockEvents and the validator sets of the REST API
+    async fn explain_from_rest(&self) -> CliTypedResult<LeaderReputationReport> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let epoch = match self.epoch {
+            Some(epoch) => epoch,
+            None => client.get_ledger_information().await?.into_inner().epoch,
+        };
+
+        // The on-chain config of the epoch is the one at its first block
+        let target_epoch_info = FetchMetadata::fetch_new_block_events(
+            &client,
+            Some(epoch as i64),
+            Some(epoch as i64 + 1),
+        )
+        .await?
+        .pop()
+        .filter(|epoch_info| epoch_info.epoch == epoch && !epoch_info.blocks.is_empty())
+        .ok_or_else(|| CliError::UnexpectedError(format!("No blocks found for epoch {}", epoch)))?;
+        let consensus_config_bytes: Vec<u8> = client
+            .get_account_resource_at_version_bcs(
+                CORE_CODE_ADDRESS,
+                "0x1::consensus_config::ConsensusConfig",
+                target_epoch_info.blocks[0].version,
+            )
+            .await?
+            .into_inner();
+        let onchain_params =
+            LeaderReputationParams::from_onchain_config(
+                &bcs::from_bytes::<OnChainConsensusConfig>(&consensus_config_bytes)?,
+            )?;
+        let simulated_params = self.simulation_args.simulate(&onchain_params);
+
+        let history_epochs = history_epochs(&onchain_params, simulated_params.as_ref());
+        let mut epoch_infos = FetchMetadata::fetch_new_block_events(
+            &client,
+            Some(epoch.saturating_sub(history_epochs) as i64),
+            Some(epoch as i64),
+        )
+        .await?;
+        epoch_infos.push(target_epoch_info);
+
+        let round = self.round.unwrap_or_else(|| {
+            epoch_infos
+                .last()
+                .and_then(|epoch_info| epoch_info.blocks.last())
+                .map_or(0, |block| block.event.round())
+                + 1
+        });
+        let epochs = epoch_infos
+            .iter()
+            .map(|epoch_info| EpochValidators {
+                epoch: epoch_info.epoch,
+                validators: epoch_info
+                    .validators
+                    .iter()
+                    .map(|validator| validator.address)
+                    .collect(),
+                voting_powers: epoch_info
+                    .validators
+                    .iter()
+                    .map(|validator| validator.voting_power)
+                    .collect(),
+                last_round: epoch_info
+                    .blocks
+                    .last()
+                    .filter(|_| !epoch_info.partial)
+                    .map(|block| block.event.round()),
+            })
+            .collect();
+        let history = epoch_infos
+            .into_iter()
+            .flat_map(|epoch_info| epoch_info.blocks)
+            .map(|block| aptos_consensus::VersionedNewBlockEvent {
+                event: block.event,
+                version: block.version,
+            })
+            .collect();
+        let explainer = LeaderReputationExplainer::new(epochs, history);
+
+        let simulated = match simulated_params {
+            Some(params) => {
+                Some(explain_leader_election(&client, &explainer, params, epoch, round).await?)
+            },
+            None => None,
+        };
+        Ok(LeaderReputationReport {
+            onchain: explain_leader_election(&client, &explainer, onchain_params, epoch, round)
+                .await?,
+            simulated,
+        })
+    }
+
+    /// Explains the election from the NewBlockEvents and the epoch ending ledger infos of a
+    /// node's DB
+    fn explain_from_db(&self, db_dir: &Path) -> CliTypedResult<LeaderReputationReport> {
+        let db = AptosDB::open(
+            db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            RocksdbConfigs::default(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        let latest_epoch = db.get_latest_epoch_state()?.epoch;
+        let epoch = self.epoch.unwrap_or(latest_epoch);
+        if epoch == 0 || epoch > latest_epoch {
+            return Err(CliError::CommandArgumentError(format!(
+                "Epoch {} has no blocks, epochs from 1 to {} are in the DB",
+                epoch, latest_epoch
+            )));
+        }
+
+        // The epoch starts after the ledger info ending the previous one, and ends with its own
+        // ledger info if it already ended
+        let epoch_ending_ledger_infos = db
+            .get_epoch_ending_ledger_infos(epoch - 1, std::cmp::min(epoch + 1, latest_epoch))?
+            .ledger_info_with_sigs;
+        let start_ledger_info = epoch_ending_ledger_infos
+            .first()
+            .ok_or_else(|| {
+                CliError::UnexpectedError(format!(
+                    "No ledger info found ending epoch {}",
+                    epoch - 1
+                ))
+            })?
+            .ledger_info();
+        let num_validators = start_ledger_info
+            .next_epoch_state()
+            .map_or(0, |epoch_state| epoch_state.verifier.len());
+        let (end_version, last_round) = match epoch_ending_ledger_infos.get(1) {
+            Some(end_ledger_info) => (
+                end_ledger_info.ledger_info().version(),
+                end_ledger_info.ledger_info().round(),
+            ),
+            None => {
+                let latest_ledger_info = db.get_latest_ledger_info()?;
+                let latest_ledger_info = latest_ledger_info.ledger_info();
+                let last_round = if latest_ledger_info.epoch() == epoch {
+                    latest_ledger_info.round()
+                } else {
+                    0
+                };
+                (latest_ledger_info.version(), last_round)
+            },
+        };
+        let round = self.round.unwrap_or(last_round + 1);
+
+        // The on-chain config of the epoch is the one at its start
+        let consensus_config = db
+            .get_state_value_by_version(
+                &StateKey::access_path(OnChainConsensusConfig::access_path()?),
+                start_ledger_info.version(),
+            )?
+            .ok_or_else(|| {
+                CliError::UnexpectedError(format!("No consensus config found for epoch {}", epoch))
+            })?;
+        let onchain_params = LeaderReputationParams::from_onchain_config(
+            &OnChainConsensusConfig::deserialize_into_config(consensus_config.bytes())?,
+        )?;
+        let simulated_params = self.simulation_args.simulate(&onchain_params);
+
+        // The events committed after the round in the epoch are read on top of the window
+        let num_events = [Some(&onchain_params), simulated_params.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|params| LeaderReputationExplainer::num_events_needed(params, num_validators))
+            .max()
+            .unwrap_or_default()
+            + last_round.saturating_sub(round);
+        let explainer = LeaderReputationExplainer::from_db(
+            &db,
+            epoch.saturating_sub(history_epochs(&onchain_params, simulated_params.as_ref())),
+            num_events,
+            end_version,
+        )?;
+
+        Ok(LeaderReputationReport {
+            simulated: simulated_params
+                .map(|params| {
+                    explain_leader_election_from_db(&db, &explainer, params, epoch, round)
+                })
+                .transpose()?,
+            onchain: explain_leader_election_from_db(
+                &db,
+                &explainer,
+                onchain_params,
+                epoch,
+                round,
+            )?,
+        })
+    }
+}
+
+/// Explains the election, fetching the root hash that seeds it if needed
+async fn explain_leader_election(
+    client: &Client,
+    explainer: &LeaderReputationExplainer,
+    params: LeaderReputationParams,
+    epoch: u64,
+    round: u64,
+) -> CliTypedResult<LeaderElectionExplanation> {
+    let root_hash = if params.leader_reputation_type.use_root_hash_for_seed() {
+        let window_version = explainer.window_version(&params, epoch, round)?;
+        client
+            .get_transaction_by_version(window_version)
+            .await?
+            .into_inner()
+            .transaction_info()?
+            .accumulator_root_hash
+            .into()
+    } else {
+        HashValue::zero()
+    };
+    Ok(explainer.explain(&params, epoch, round, root_hash)?)
+}
+
+/// Explains the election, reading the root hash that seeds it from the DB if needed
+fn explain_leader_election_from_db(
+    db: &AptosDB,
+    explainer: &LeaderReputationExplainer,
+    params: LeaderReputationParams,
+    epoch: u64,
+    round: u64,
+) -> CliTypedResult<LeaderElectionExplanation> {
+    let root_hash = if params.leader_reputation_type.use_root_hash_for_seed() {
+        db.get_accumulator_root_hash(explainer.window_version(&params, epoch, round)?)?
+    } else {
+        HashValue::zero()
+    };
+    Ok(explainer.explain(&params, epoch, round, root_hash)?)
+}
+
 async fn get_epoch_info(client: &Client) -> CliTypedResult<EpochInfo> {
     let (block_resource, state): (BlockResource, State) = client
         .get_account_resource_bcs(CORE_CODE_ADDRESS, "0x1::block::BlockResource")
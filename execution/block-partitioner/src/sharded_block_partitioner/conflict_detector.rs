@@ -6,7 +6,7 @@ use crate::{
 };
 use aptos_types::transaction::analyzed_transaction::{AnalyzedTransaction, StorageLocation};
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashSet},
     hash::{Hash, Hasher},
     sync::Arc,
 };
@@ -39,8 +39,14 @@ impl CrossShardConflictDetector {
         let mut accepted_txns = Vec::new();
         let mut accepted_txn_dependencies = Vec::new();
         let mut rejected_txns = Vec::new();
-        for (_, txn) in txns.into_iter().enumerate() {
-            if self.check_for_cross_shard_conflict(self.shard_id, &txn, cross_shard_rw_set) {
+        // Once a transaction is rejected, the following transactions of its sender are rejected
+        // as well, so that the transactions of a sender are never reordered.
+        let mut rejected_senders = HashSet::new();
+        for txn in txns.into_iter() {
+            if rejected_senders.contains(&txn.sender())
+                || self.check_for_cross_shard_conflict(self.shard_id, &txn, cross_shard_rw_set)
+            {
+                rejected_senders.insert(txn.sender());
                 rejected_txns.push(txn);
             } else {
                 accepted_txn_dependencies.push(self.get_dependencies_for_frozen_txn(
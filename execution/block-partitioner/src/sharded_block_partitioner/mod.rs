@@ -11,12 +11,13 @@ use crate::{
         },
         partitioning_shard::PartitioningShard,
     },
-    types::{ShardId, SubBlock},
+    types::{CrossShardDependencies, ShardId, SubBlock, TransactionWithDependencies},
 };
 use aptos_logger::{error, info};
-use aptos_types::transaction::analyzed_transaction::AnalyzedTransaction;
+use aptos_types::transaction::{analyzed_transaction::AnalyzedTransaction, Transaction};
 use std::{
     collections::HashMap,
+    iter::once,
     sync::{
         mpsc::{Receiver, Sender},
         Arc,
@@ -341,6 +342,130 @@ impl ShardedBlockPartitioner {
             .chain(remaining_frozen_chunks.into_iter())
             .collect::<Vec<SubBlock>>()
     }
+
+    /// Partitions a block of transactions to execute, which may start and end with non-user
+    /// transactions (e.g., the block metadata and the state checkpoint). The leading non-user
+    /// transactions form a first round assigned to the first shard, which all the user
+    /// transactions depend on. The trailing ones form a last round assigned to the first shard,
+    /// depending on all the preceding transactions (except for the state checkpoints, which don't
+    /// read the state). If the hints of some user transactions aren't known, or if non-user
+    /// transactions are interleaved with them, the whole block is assigned to the first shard.
+    pub fn partition_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+        num_partitioning_rounds: usize,
+    ) -> Vec<SubBlock> {
+        let is_user_txn = |txn: &Transaction| matches!(txn, Transaction::UserTransaction(_));
+        let num_leading_txns = transactions
+            .iter()
+            .take_while(|txn| !is_user_txn(txn))
+            .count();
+        let num_trailing_txns = transactions[num_leading_txns..]
+            .iter()
+            .rev()
+            .take_while(|txn| !is_user_txn(txn))
+            .count();
+        let trailing_start_index = transactions.len() - num_trailing_txns;
+        if num_leading_txns == trailing_start_index
+            || !transactions[num_leading_txns..trailing_start_index]
+                .iter()
+                .all(AnalyzedTransaction::has_known_hints)
+        {
+            return self.round_on_first_shard(
+                0,
+                transactions
+                    .into_iter()
+                    .map(|txn| {
+                        TransactionWithDependencies::new(
+                            AnalyzedTransaction::new_with_no_hints(txn),
+                            CrossShardDependencies::default(),
+                        )
+                    })
+                    .collect(),
+            );
+        }
+
+        let mut leading_txns = transactions;
+        let trailing_txns = leading_txns.split_off(trailing_start_index);
+        let user_txns = leading_txns.split_off(num_leading_txns);
+
+        let mut sub_blocks = vec![];
+        if num_leading_txns > 0 {
+            sub_blocks.extend(
+                self.round_on_first_shard(
+                    0,
+                    leading_txns
+                        .into_iter()
+                        .map(|txn| {
+                            TransactionWithDependencies::new(
+                                AnalyzedTransaction::new_with_no_hints(txn),
+                                CrossShardDependencies::default(),
+                            )
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        // The user transactions are indexed after the leading transactions, and depend on all of
+        // them
+        let user_sub_blocks = self.partition(
+            user_txns
+                .into_iter()
+                .map(AnalyzedTransaction::from)
+                .collect(),
+            num_partitioning_rounds,
+        );
+        sub_blocks.extend(user_sub_blocks.into_iter().map(|sub_block| {
+            let transactions = sub_block
+                .transactions
+                .into_iter()
+                .map(|txn| {
+                    let mut dependencies = CrossShardDependencies::default();
+                    for txn_index in 0..num_leading_txns {
+                        dependencies.add_depends_on_txn(txn_index);
+                    }
+                    for txn_index in txn.cross_shard_dependencies.depends_on() {
+                        dependencies.add_depends_on_txn(txn_index + num_leading_txns);
+                    }
+                    TransactionWithDependencies::new(txn.txn, dependencies)
+                })
+                .collect();
+            SubBlock::new(sub_block.start_index + num_leading_txns, transactions)
+        }));
+
+        if num_trailing_txns > 0 {
+            let trailing_txns = trailing_txns
+                .into_iter()
+                .map(|txn| {
+                    let mut dependencies = CrossShardDependencies::default();
+                    if !matches!(txn, Transaction::StateCheckpoint(_)) {
+                        for txn_index in 0..trailing_start_index {
+                            dependencies.add_depends_on_txn(txn_index);
+                        }
+                    }
+                    TransactionWithDependencies::new(
+                        AnalyzedTransaction::new_with_no_hints(txn),
+                        dependencies,
+                    )
+                })
+                .collect();
+            sub_blocks.extend(self.round_on_first_shard(trailing_start_index, trailing_txns));
+        }
+        sub_blocks
+    }
+
+    // Returns a partitioning round in which all the transactions are assigned to the first shard
+    fn round_on_first_shard(
+        &self,
+        start_index: usize,
+        txns: Vec<TransactionWithDependencies>,
+    ) -> Vec<SubBlock> {
+        let end_index = start_index + txns.len();
+        once(SubBlock::new(start_index, txns))
+            .chain((1..self.num_shards).map(|_| SubBlock::new(end_index, vec![])))
+            .collect()
+    }
 }
 
 impl Drop for ShardedBlockPartitioner {
@@ -390,7 +515,11 @@ mod tests {
         },
         types::SubBlock,
     };
-    use aptos_types::transaction::analyzed_transaction::AnalyzedTransaction;
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        block_metadata::BlockMetadata,
+        transaction::{analyzed_transaction::AnalyzedTransaction, Transaction},
+    };
     use move_core_types::account_address::AccountAddress;
     use rand::{rngs::OsRng, Rng};
     use std::collections::HashMap;
@@ -654,4 +783,120 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    // Generates conflicting transactions from a few senders, partitioned in multiple rounds, and
+    // ensures that the transactions of each sender are never reordered.
+    fn test_sender_order_is_preserved() {
+        let mut rng = OsRng;
+        let num_shards = 4;
+        let mut accounts = Vec::new();
+        for _ in 0..8 {
+            accounts.push(generate_test_account());
+        }
+        let mut transactions = Vec::new();
+        for _ in 0..200 {
+            let sender_index = rng.gen_range(0, accounts.len());
+            let mut sender = accounts.swap_remove(sender_index);
+            let receiver_index = rng.gen_range(0, accounts.len());
+            let receiver = accounts.get(receiver_index).unwrap();
+            transactions.push(create_signed_p2p_transaction(&mut sender, vec![receiver]).remove(0));
+            accounts.push(sender)
+        }
+
+        let partitioner = ShardedBlockPartitioner::new(num_shards);
+        let partitioned_txns = partitioner.partition(transactions.clone(), 3);
+        let mut txns_by_sender = HashMap::new();
+        for txn in &transactions {
+            txns_by_sender
+                .entry(txn.sender())
+                .or_insert_with(Vec::new)
+                .push(txn.clone());
+        }
+        let mut partitioned_txns_by_sender = HashMap::new();
+        let mut expected_start_index = 0;
+        for sub_block in partitioned_txns.iter() {
+            assert_eq!(sub_block.start_index, expected_start_index);
+            expected_start_index += sub_block.len();
+            for txn in sub_block.transactions_with_deps() {
+                partitioned_txns_by_sender
+                    .entry(txn.txn().sender())
+                    .or_insert_with(Vec::new)
+                    .push(txn.txn().clone());
+            }
+        }
+        assert_eq!(expected_start_index, transactions.len());
+        assert_eq!(partitioned_txns_by_sender, txns_by_sender);
+    }
+
+    #[test]
+    fn test_partition_transactions_with_non_user_txns() {
+        let num_shards = 2;
+        let block_metadata = Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::zero(),
+            0,
+            0,
+            AccountAddress::ZERO,
+            vec![],
+            vec![],
+            1,
+        ));
+        let state_checkpoint = Transaction::StateCheckpoint(HashValue::zero());
+        let mut user_txns = Vec::new();
+        for _ in 0..4 {
+            user_txns.push(create_non_conflicting_p2p_transaction().into_inner());
+        }
+        let transactions: Vec<Transaction> = std::iter::once(block_metadata.clone())
+            .chain(user_txns.clone())
+            .chain(std::iter::once(state_checkpoint.clone()))
+            .collect();
+
+        let partitioner = ShardedBlockPartitioner::new(num_shards);
+        let sub_blocks = partitioner.partition_transactions(transactions.clone(), 2);
+        // One round for the block metadata, one for the user transactions and one for the state
+        // checkpoint
+        assert_eq!(sub_blocks.len(), 3 * num_shards);
+        assert_eq!(sub_blocks[0].len(), 1);
+        assert_eq!(
+            sub_blocks[0].transactions[0].txn().transaction(),
+            &block_metadata
+        );
+        assert!(sub_blocks[1].is_empty());
+
+        for sub_block in &sub_blocks[num_shards..2 * num_shards] {
+            assert_eq!(sub_block.len(), 2);
+            for txn in sub_block.transactions_with_deps() {
+                // The user transactions only depend on the block metadata
+                assert_eq!(txn.cross_shard_dependencies().len(), 1);
+                assert!(txn.cross_shard_dependencies().is_depends_on(0));
+            }
+        }
+        assert_eq!(sub_blocks[2].start_index, 1);
+        assert_eq!(sub_blocks[3].start_index, 3);
+
+        assert_eq!(sub_blocks[4].start_index, 5);
+        assert_eq!(
+            sub_blocks[4].transactions[0].txn().transaction(),
+            &state_checkpoint
+        );
+        assert!(sub_blocks[4].transactions[0]
+            .cross_shard_dependencies()
+            .is_empty());
+        assert!(sub_blocks[5].is_empty());
+
+        // Non-user transactions between the user transactions can't be partitioned
+        let mut interleaved_txns = transactions;
+        interleaved_txns.insert(2, state_checkpoint);
+        let sub_blocks = partitioner.partition_transactions(interleaved_txns.clone(), 2);
+        assert_eq!(sub_blocks.len(), num_shards);
+        assert_eq!(
+            sub_blocks[0]
+                .transactions_with_deps()
+                .iter()
+                .map(|txn| txn.txn().transaction().clone())
+                .collect::<Vec<_>>(),
+            interleaved_txns
+        );
+        assert!(sub_blocks[1].is_empty());
+    }
 }
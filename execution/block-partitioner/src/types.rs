@@ -33,6 +33,10 @@ impl CrossShardDependencies {
         self.depends_on.contains(&txn_index)
     }
 
+    pub fn depends_on(&self) -> &HashSet<TxnIndex> {
+        &self.depends_on
+    }
+
     pub fn add_depends_on_txn(&mut self, txn_index: TxnIndex) {
         self.depends_on.insert(txn_index);
     }
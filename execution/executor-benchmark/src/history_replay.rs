@@ -0,0 +1,148 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{
+        authenticator::TransactionAuthenticator, SignedTransaction, Transaction,
+        TransactionPayload, Version,
+    },
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+};
+
+/// Loads the user transactions committed in `[begin_version, begin_version + limit)` in the DB
+/// at `db_dir`, e.g. a DB restored from a backup with the db-tool.
+pub fn load_recorded_transactions(
+    db_dir: impl AsRef<Path>,
+    begin_version: Version,
+    limit: u64,
+) -> Vec<SignedTransaction> {
+    let db = AptosDB::open(
+        db_dir,
+        true, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        RocksdbConfigs::default(),
+        false,
+        BUFFERED_STATE_TARGET_ITEMS,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    )
+    .expect("History DB should open.");
+
+    let mut transactions = vec![];
+    let mut version = begin_version;
+    while version < begin_version + limit {
+        let batch_size = std::cmp::min(MAX_REQUEST_LIMIT, begin_version + limit - version);
+        transactions.extend(
+            db.get_transaction_iterator(version, batch_size)
+                .unwrap()
+                .filter_map(|txn| match txn.unwrap() {
+                    Transaction::UserTransaction(txn) => Some(txn),
+                    _ => None,
+                }),
+        );
+        version += batch_size;
+    }
+    transactions
+}
+
+/// Whether the address is one of the special addresses `0x0` to `0xa`, which hold the code
+/// published at genesis (the framework, the token standards...).
+fn is_special_address(address: &AccountAddress) -> bool {
+    let bytes = address.into_bytes();
+    bytes[..AccountAddress::LENGTH - 1]
+        .iter()
+        .all(|byte| *byte == 0)
+        && bytes[AccountAddress::LENGTH - 1] <= 0xA
+}
+
+/// The number of recorded transactions replayed, and skipped by reason.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplayStats {
+    pub replayed: usize,
+    /// Multi-agent transactions, which need the original secondary signers
+    pub skipped_multi_agent: usize,
+    /// Transactions calling code which isn't published at genesis, so doesn't exist in the
+    /// benchmark DB, or running scripts and multisig transactions
+    pub skipped_unsupported_payload: usize,
+}
+
+/// Keeps the recorded transactions which can be replayed on the benchmark DB, and returns them
+/// with the number of transactions kept and skipped by reason.
+pub fn filter_replayable_transactions(
+    recorded_transactions: Vec<SignedTransaction>,
+) -> (Vec<SignedTransaction>, ReplayStats) {
+    let mut stats = ReplayStats::default();
+    let replayable_transactions: Vec<_> = recorded_transactions
+        .into_iter()
+        .filter(|txn| {
+            if let TransactionAuthenticator::MultiAgent { .. } = txn.authenticator_ref() {
+                stats.skipped_multi_agent += 1;
+                return false;
+            }
+            match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function)
+                    if is_special_address(entry_function.module().address()) =>
+                {
+                    true
+                },
+                _ => {
+                    stats.skipped_unsupported_payload += 1;
+                    false
+                },
+            }
+        })
+        .collect();
+    stats.replayed = replayable_transactions.len();
+    (replayable_transactions, stats)
+}
+
+/// Re-signs recorded transactions by the benchmark accounts, each original sender being assigned
+/// to a benchmark account the first time it is seen. When there are more original senders than
+/// benchmark accounts, the accounts are assigned round robin, so some of them stand for more than
+/// one original sender.
+///
+/// Only the sender is remapped: the addresses in the arguments of the transactions are kept as
+/// they are, and the gas parameters of the transactions are kept too.
+pub struct SignerRemapper {
+    transaction_factory: TransactionFactory,
+    assigned_accounts: HashMap<AccountAddress, usize>,
+}
+
+impl SignerRemapper {
+    pub fn new(transaction_factory: TransactionFactory) -> Self {
+        Self {
+            transaction_factory,
+            assigned_accounts: HashMap::new(),
+        }
+    }
+
+    /// Returns the transaction re-signed by the benchmark account assigned to its sender. The
+    /// transaction must be replayable (see `filter_replayable_transactions`).
+    pub fn remap(
+        &mut self,
+        accounts: &mut VecDeque<LocalAccount>,
+        txn: &SignedTransaction,
+    ) -> SignedTransaction {
+        let num_assigned = self.assigned_accounts.len();
+        let index = *self
+            .assigned_accounts
+            .entry(txn.sender())
+            .or_insert(num_assigned % accounts.len());
+        accounts[index].sign_with_transaction_builder(
+            self.transaction_factory
+                .payload(txn.payload().clone())
+                .max_gas_amount(txn.max_gas_amount())
+                .gas_unit_price(txn.gas_unit_price()),
+        )
+    }
+}
@@ -6,26 +6,26 @@ mod account_generator;
 pub mod db_access;
 pub mod db_generator;
 mod db_reliable_submitter;
+pub mod history_replay;
 mod metrics;
 pub mod native_executor;
 pub mod pipeline;
+pub mod report;
 pub mod transaction_committer;
 pub mod transaction_executor;
 pub mod transaction_generator;
 
 use crate::{
-    pipeline::Pipeline, transaction_committer::TransactionCommitter,
-    transaction_executor::TransactionExecutor, transaction_generator::TransactionGenerator,
+    history_replay::{filter_replayable_transactions, load_recorded_transactions, ReplayStats},
+    pipeline::Pipeline,
+    report::{BenchmarkReport, StageTimers},
+    transaction_committer::TransactionCommitter,
+    transaction_executor::TransactionExecutor,
+    transaction_generator::TransactionGenerator,
 };
 use aptos_config::config::{NodeConfig, PrunerConfig};
 use aptos_db::AptosDB;
-use aptos_executor::{
-    block_executor::{BlockExecutor, TransactionBlockExecutor},
-    metrics::{
-        APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS, APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS,
-        APTOS_EXECUTOR_OTHER_TIMERS_SECONDS, APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS,
-    },
-};
+use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_jellyfish_merkle::metrics::{
     APTOS_JELLYFISH_INTERNAL_ENCODED_BYTES, APTOS_JELLYFISH_LEAF_ENCODED_BYTES,
 };
@@ -34,11 +34,10 @@ use aptos_storage_interface::DbReaderWriter;
 use aptos_transaction_generator_lib::{
     create_txn_generator_creator, TransactionGeneratorCreator, TransactionType,
 };
-use aptos_vm::counters::TXN_GAS_USAGE;
+use aptos_types::{on_chain_config::TransactionShufflerType, transaction::Version};
 use db_reliable_submitter::DbReliableTransactionSubmitter;
 use pipeline::PipelineConfig;
 use std::{
-    collections::HashMap,
     fs,
     path::Path,
     sync::{atomic::AtomicUsize, Arc},
@@ -98,6 +97,7 @@ pub fn run_benchmark<V>(
     use_state_kv_db: bool,
     use_sharded_state_merkle_db: bool,
     pipeline_config: PipelineConfig,
+    report_path: Option<&Path>,
 ) where
     V: TransactionBlockExecutor + 'static,
 {
@@ -130,6 +130,7 @@ pub fn run_benchmark<V>(
                 skip_commit: false,
                 allow_discards: false,
                 allow_aborts: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
         )
     });
@@ -138,6 +139,7 @@ pub fn run_benchmark<V>(
 
     let (pipeline, block_sender) =
         Pipeline::new(executor, version, pipeline_config.clone(), Some(num_blocks));
+    let pipeline_block_latencies = pipeline.block_latencies();
     let mut generator = TransactionGenerator::new_with_existing_db(
         db.clone(),
         genesis_key,
@@ -148,37 +150,7 @@ pub fn run_benchmark<V>(
     );
 
     let mut start_time = Instant::now();
-    let start_gas = TXN_GAS_USAGE.get_sample_sum();
-
-    let start_execution_total = APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum();
-    let start_vm_only = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum();
-    let other_labels = vec![
-        ("1.", true, "verified_state_view"),
-        ("2.", true, "apply_to_ledger"),
-        ("2.1.", false, "sort_transactions"),
-        ("2.2.", false, "calculate_for_transaction_block"),
-        ("2.2.1.", false, "get_sharded_state_updates"),
-        ("2.2.2.", false, "calculate_block_state_updates"),
-        ("2.2.3.", false, "calculate_usage"),
-        ("2.2.4.", false, "make_checkpoint"),
-        ("2.3.", false, "assemble_ledger_diff_for_block"),
-        ("2.3.1.", false, "calculate_events_and_writeset_hashes"),
-        ("3.", true, "as_state_compute_result"),
-        ("4.", true, "get_txns_to_commit"),
-    ];
-
-    let start_by_other = other_labels
-        .iter()
-        .map(|(_prefix, _top_level, other_label)| {
-            (
-                other_label.to_string(),
-                APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
-                    .with_label_values(&[other_label])
-                    .get_sample_sum(),
-            )
-        })
-        .collect::<HashMap<_, _>>();
-    let start_commit_total = APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum();
+    let stage_timers = StageTimers::snapshot();
 
     if let Some(transaction_generator_creator) = transaction_generator_creator {
         generator.run_workload(
@@ -198,56 +170,131 @@ pub fn run_benchmark<V>(
     pipeline.join();
 
     let elapsed = start_time.elapsed().as_secs_f64();
-    let delta_v = (db.reader.get_latest_version().unwrap() - version) as f64;
-    let delta_gas = TXN_GAS_USAGE.get_sample_sum() - start_gas;
-    info!(
-        "Executed workload {}",
+    let delta_v = db.reader.get_latest_version().unwrap() - version;
+    let report = stage_timers.report(
         if let Some(ttype) = transaction_type {
             format!("{:?} via txn generator", ttype)
         } else {
             "raw transfer".to_string()
-        }
+        },
+        delta_v,
+        elapsed,
+        &pipeline_block_latencies.get(),
     );
-    info!("Overall TPS: {} txn/s", delta_v / elapsed);
-    info!("Overall GPS: {} gas/s", delta_gas / elapsed);
+    report.log();
+    write_report(&report, report_path);
+
+    if verify_sequence_numbers {
+        generator.verify_sequence_numbers(db.reader);
+    }
+}
 
-    let time_in_execution =
-        APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum() - start_execution_total;
+/// Replays the user transactions committed in `[begin_version, begin_version + limit)` in the
+/// DB at `history_dir`, signed by the main signer accounts instead of their original senders,
+/// and returns the number of transactions replayed and skipped.
+pub fn replay_history<V>(
+    history_dir: impl AsRef<Path>,
+    begin_version: Version,
+    limit: u64,
+    block_size: usize,
+    num_main_signer_accounts: usize,
+    source_dir: impl AsRef<Path>,
+    checkpoint_dir: impl AsRef<Path>,
+    pruner_config: PrunerConfig,
+    use_state_kv_db: bool,
+    use_sharded_state_merkle_db: bool,
+    pipeline_config: PipelineConfig,
+    report_path: Option<&Path>,
+) -> ReplayStats
+where
+    V: TransactionBlockExecutor + 'static,
+{
+    assert!(limit > 0, "The limit must be greater than 0");
+    assert!(block_size > 0, "The block size must be greater than 0");
+
+    let recorded_transactions = load_recorded_transactions(history_dir, begin_version, limit);
     info!(
-        "Overall fraction of total: {:.3} in execution (component TPS: {})",
-        time_in_execution / elapsed,
-        delta_v / time_in_execution
+        "Loaded {} user transactions from versions {} to {}",
+        recorded_transactions.len(),
+        begin_version,
+        begin_version + limit - 1
     );
-    let time_in_vm = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum() - start_vm_only;
+    let (replayable_transactions, replay_stats) =
+        filter_replayable_transactions(recorded_transactions);
     info!(
-        "Overall fraction of execution {:.3} in VM (component TPS: {})",
-        time_in_vm / time_in_execution,
-        delta_v / time_in_vm
+        "Replaying {} transactions, skipped {} multi-agent transactions and {} transactions with unsupported payloads",
+        replay_stats.replayed,
+        replay_stats.skipped_multi_agent,
+        replay_stats.skipped_unsupported_payload
+    );
+
+    create_checkpoint(
+        source_dir.as_ref(),
+        checkpoint_dir.as_ref(),
+        use_sharded_state_merkle_db,
+    );
+
+    let (mut config, genesis_key) = aptos_genesis::test_utils::test_config();
+    config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
+    config.storage.storage_pruner_config = pruner_config;
+    config.storage.rocksdb_configs.use_state_kv_db = use_state_kv_db;
+    config.storage.rocksdb_configs.use_sharded_state_merkle_db = use_sharded_state_merkle_db;
+
+    let (db, executor) = init_db_and_executor::<V>(&config);
+    let version = db.reader.get_latest_version().unwrap();
+
+    // The recorded history has failed transactions, and the ones calling into code which differs
+    // from the benchmark genesis may fail as well.
+    let pipeline_config = PipelineConfig {
+        allow_aborts: true,
+        ..pipeline_config
+    };
+    let num_blocks = (replayable_transactions.len() + block_size - 1) / block_size;
+    let (pipeline, block_sender) =
+        Pipeline::new(executor, version, pipeline_config.clone(), Some(num_blocks));
+    let pipeline_block_latencies = pipeline.block_latencies();
+    let mut generator = TransactionGenerator::new_with_existing_db(
+        db.clone(),
+        genesis_key,
+        block_sender,
+        source_dir,
+        version,
+        Some(num_main_signer_accounts),
     );
-    for (prefix, top_level, other_label) in other_labels {
-        let time_in_label = APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
-            .with_label_values(&[other_label])
-            .get_sample_sum()
-            - start_by_other.get(other_label).unwrap();
-        if top_level || time_in_label / time_in_execution > 0.01 {
-            info!(
-                "Overall fraction of execution {:.3} in {} {} (component TPS: {})",
-                time_in_label / time_in_execution,
-                prefix,
-                other_label,
-                delta_v / time_in_label
-            );
-        }
+
+    let mut start_time = Instant::now();
+    let stage_timers = StageTimers::snapshot();
+
+    generator.run_replay(block_size, replayable_transactions);
+    if pipeline_config.delay_execution_start {
+        start_time = Instant::now();
     }
-    let time_in_commit = APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum() - start_commit_total;
-    info!(
-        "Overall fraction of total: {:.3} in commit (component TPS: {})",
-        time_in_commit / elapsed,
-        delta_v / time_in_commit
+    pipeline.start_execution();
+    generator.drop_sender();
+    pipeline.join();
+
+    let elapsed = start_time.elapsed().as_secs_f64();
+    let delta_v = db.reader.get_latest_version().unwrap() - version;
+    let report = stage_timers.report(
+        format!(
+            "history replay of versions {} to {}",
+            begin_version,
+            begin_version + limit - 1
+        ),
+        delta_v,
+        elapsed,
+        &pipeline_block_latencies.get(),
     );
+    report.log();
+    write_report(&report, report_path);
+    replay_stats
+}
 
-    if verify_sequence_numbers {
-        generator.verify_sequence_numbers(db.reader);
+fn write_report(report: &BenchmarkReport, report_path: Option<&Path>) {
+    if let Some(report_path) = report_path {
+        fs::write(report_path, serde_json::to_vec_pretty(report).unwrap())
+            .expect("Failed to write the benchmark report.");
+        println!("Benchmark report saved to {}", report_path.display());
     }
 }
 
@@ -434,6 +481,7 @@ mod tests {
     use aptos_executor::block_executor::TransactionBlockExecutor;
     use aptos_temppath::TempPath;
     use aptos_transaction_generator_lib::args::TransactionTypeArg;
+    use aptos_types::on_chain_config::TransactionShufflerType;
     use aptos_vm::AptosVM;
 
     fn test_generic_benchmark<E>(
@@ -465,6 +513,7 @@ mod tests {
                 skip_commit: false,
                 allow_discards: false,
                 allow_aborts: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
         );
 
@@ -489,7 +538,79 @@ mod tests {
                 skip_commit: false,
                 allow_discards: false,
                 allow_aborts: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
+            None,
+        );
+    }
+
+    #[test]
+    fn test_replay_history() {
+        aptos_logger::Logger::new().init();
+
+        let storage_dir = TempPath::new();
+        let checkpoint_dir = TempPath::new();
+        let report_path = TempPath::new();
+        let pipeline_config = PipelineConfig {
+            delay_execution_start: false,
+            split_stages: false,
+            skip_commit: false,
+            allow_discards: false,
+            allow_aborts: false,
+            transaction_shuffler_type: TransactionShufflerType::NoShuffling,
+        };
+
+        crate::db_generator::create_db_with_accounts::<AptosVM>(
+            100,         /* num_accounts */
+            100_000_000, /* init_account_balance */
+            5,           /* block_size */
+            storage_dir.as_ref(),
+            NO_OP_STORAGE_PRUNER_CONFIG, /* prune_window */
+            false,
+            false,
+            false,
+            pipeline_config.clone(),
+        );
+
+        // Replays the transactions creating the accounts, on top of the DB they created.
+        let num_recorded_txns = crate::history_replay::load_recorded_transactions(
+            storage_dir.as_ref(),
+            0,  /* begin_version */
+            50, /* limit */
+        )
+        .len();
+        let replay_stats = super::replay_history::<AptosVM>(
+            storage_dir.as_ref(),
+            0,  /* begin_version */
+            50, /* limit */
+            6,  /* block_size */
+            25, /* num_main_signer_accounts */
+            storage_dir.as_ref(),
+            checkpoint_dir,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            false,
+            false,
+            pipeline_config,
+            Some(report_path.path()),
+        );
+
+        // The accounts are created by entry functions of the framework, so none is skipped.
+        assert!(replay_stats.replayed > 0);
+        assert_eq!(replay_stats.replayed, num_recorded_txns);
+        assert_eq!(replay_stats.skipped_multi_agent, 0);
+        assert_eq!(replay_stats.skipped_unsupported_payload, 0);
+
+        // Every block of 6 replayed transactions is committed with a state checkpoint.
+        let num_blocks = (replay_stats.replayed + 5) / 6;
+        let report: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(report_path.path()).unwrap()).unwrap();
+        assert_eq!(
+            report["num_txns"].as_u64().unwrap(),
+            (replay_stats.replayed + num_blocks) as u64
+        );
+        assert_eq!(
+            report["block_latencies"][0]["num_blocks"].as_u64().unwrap(),
+            num_blocks as u64
         );
     }
 
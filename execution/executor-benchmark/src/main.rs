@@ -5,15 +5,18 @@
 use aptos_config::config::{
     EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, StateMerklePrunerConfig,
 };
+use aptos_crypto::x25519;
 use aptos_executor::block_executor::TransactionBlockExecutor;
 use aptos_executor_benchmark::{native_executor::NativeExecutor, pipeline::PipelineConfig};
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_push_metrics::MetricsPusher;
 use aptos_transaction_generator_lib::args::TransactionTypeArg;
+use aptos_types::on_chain_config::TransactionShufflerType;
 use aptos_vm::AptosVM;
-use clap::{Parser, Subcommand};
+use clap::{ArgEnum, Parser, Subcommand};
 use once_cell::sync::Lazy;
 use std::{
+    net::SocketAddr,
     path::PathBuf,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -92,6 +95,11 @@ pub struct PipelineOpt {
     allow_discards: bool,
     #[clap(long)]
     allow_aborts: bool,
+    /// Shuffles the transactions of each block before execution, as consensus does
+    #[clap(long, arg_enum, ignore_case = true, default_value = "no-shuffling")]
+    transaction_shuffler: TransactionShufflerArg,
+    #[clap(long, default_value = "32")]
+    shuffler_conflict_window_size: u32,
 }
 
 impl PipelineOpt {
@@ -102,10 +110,26 @@ impl PipelineOpt {
             skip_commit: self.skip_commit,
             allow_discards: self.allow_discards,
             allow_aborts: self.allow_aborts,
+            transaction_shuffler_type: match self.transaction_shuffler {
+                TransactionShufflerArg::NoShuffling => TransactionShufflerType::NoShuffling,
+                TransactionShufflerArg::SenderAware => {
+                    TransactionShufflerType::SenderAwareV1(self.shuffler_conflict_window_size)
+                },
+                TransactionShufflerArg::ConflictAware => {
+                    TransactionShufflerType::ConflictAwareV1(self.shuffler_conflict_window_size)
+                },
+            },
         }
     }
 }
 
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum TransactionShufflerArg {
+    NoShuffling,
+    SenderAware,
+    ConflictAware,
+}
+
 #[derive(Parser, Debug)]
 struct Opt {
     #[clap(long, default_value = "10000")]
@@ -120,6 +144,21 @@ struct Opt {
     #[clap(long, default_value = "1")]
     num_executor_shards: usize,
 
+    /// The addresses of the remote executor shards (see aptos-executor-service), in order by
+    /// shard id. If set, the blocks are executed by the remote shards.
+    #[clap(long, multiple_values = true)]
+    remote_executor_addresses: Vec<SocketAddr>,
+
+    /// The x25519 static public keys of the remote executor shards, in the order of their
+    /// addresses
+    #[clap(long, multiple_values = true, parse(try_from_str = aptos_executor_service::parse_public_key))]
+    remote_executor_public_keys: Vec<x25519::PublicKey>,
+
+    /// A file with the hex encoded x25519 static key the executor authenticates to the remote
+    /// executor shards with, required if there are any
+    #[clap(long)]
+    remote_executor_identity_file: Option<PathBuf>,
+
     #[clap(flatten)]
     pruner_opt: PrunerOpt,
 
@@ -141,6 +180,11 @@ struct Opt {
 
     #[clap(long)]
     use_native_executor: bool,
+
+    /// Write the TPS and the per-stage latencies of the run as JSON to this file, to compare them
+    /// across releases
+    #[clap(long, parse(from_os_str))]
+    report_path: Option<PathBuf>,
 }
 
 impl Opt {
@@ -197,6 +241,29 @@ enum Command {
         #[clap(long, parse(from_os_str))]
         checkpoint_dir: PathBuf,
     },
+    /// Replay a range of the user transactions committed in another DB (e.g. restored from a
+    /// backup), signed by the benchmark accounts instead of their original senders. Only the
+    /// entry functions published at genesis are replayed.
+    ReplayHistory {
+        /// The DB to read the recorded transactions from
+        #[clap(long, parse(from_os_str))]
+        history_dir: PathBuf,
+
+        #[clap(long)]
+        begin_version: u64,
+
+        #[clap(long)]
+        limit: u64,
+
+        #[clap(long, default_value = "1000000")]
+        main_signer_accounts: usize,
+
+        #[clap(long, parse(from_os_str))]
+        data_dir: PathBuf,
+
+        #[clap(long, parse(from_os_str))]
+        checkpoint_dir: PathBuf,
+    },
     AddAccounts {
         #[clap(long, parse(from_os_str))]
         data_dir: PathBuf,
@@ -257,6 +324,30 @@ where
                 opt.use_state_kv_db,
                 opt.use_sharded_state_merkle_db,
                 opt.pipeline_opt.pipeline_config(),
+                opt.report_path.as_deref(),
+            );
+        },
+        Command::ReplayHistory {
+            history_dir,
+            begin_version,
+            limit,
+            main_signer_accounts,
+            data_dir,
+            checkpoint_dir,
+        } => {
+            aptos_executor_benchmark::replay_history::<E>(
+                history_dir,
+                begin_version,
+                limit,
+                opt.block_size,
+                main_signer_accounts,
+                data_dir,
+                checkpoint_dir,
+                opt.pruner_opt.pruner_config(),
+                opt.use_state_kv_db,
+                opt.use_sharded_state_merkle_db,
+                opt.pipeline_opt.pipeline_config(),
+                opt.report_path.as_deref(),
             );
         },
         Command::AddAccounts {
@@ -298,6 +389,27 @@ fn main() {
         .expect("Failed to build rayon global thread pool.");
     AptosVM::set_concurrency_level_once(opt.concurrency_level());
     AptosVM::set_num_shards_once(opt.num_executor_shards);
+    if !opt.remote_executor_addresses.is_empty() {
+        assert_eq!(
+            opt.remote_executor_addresses.len(),
+            opt.remote_executor_public_keys.len(),
+            "Each remote executor shard requires a public key"
+        );
+        let identity_file = opt
+            .remote_executor_identity_file
+            .as_ref()
+            .expect("The remote executor shards require an identity file");
+        let identity = aptos_executor_service::load_identity(identity_file)
+            .expect("Failed to load the identity of the executor");
+        aptos_executor_service::set_remote_shards_once(
+            identity,
+            opt.remote_executor_addresses
+                .iter()
+                .copied()
+                .zip(opt.remote_executor_public_keys.iter().copied())
+                .collect(),
+        );
+    }
     NativeExecutor::set_concurrency_level_once(opt.concurrency_level());
 
     if opt.use_native_executor {
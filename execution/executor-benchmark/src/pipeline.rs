@@ -1,11 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{TransactionCommitter, TransactionExecutor};
+use crate::{report::BlockLatencies, TransactionCommitter, TransactionExecutor};
+use aptos_consensus::{create_transaction_shuffler, TransactionShuffler};
 use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_logger::info;
-use aptos_types::transaction::{Transaction, Version};
+use aptos_types::{
+    on_chain_config::TransactionShufflerType,
+    transaction::{Transaction, Version},
+};
 use aptos_vm::counters::TXN_GAS_USAGE;
 use std::{
     marker::PhantomData,
@@ -24,12 +28,15 @@ pub struct PipelineConfig {
     pub skip_commit: bool,
     pub allow_discards: bool,
     pub allow_aborts: bool,
+    /// The shuffler applied to the user transactions of each block, as consensus does
+    pub transaction_shuffler_type: TransactionShufflerType,
 }
 
 pub struct Pipeline<V> {
     join_handles: Vec<JoinHandle<()>>,
     phantom: PhantomData<V>,
     start_execution_tx: Option<SyncSender<()>>,
+    block_latencies: Arc<BlockLatencies>,
 }
 
 impl<V> Pipeline<V>
@@ -76,6 +83,8 @@ where
             (None, None)
         };
 
+        let shuffler = create_transaction_shuffler(config.transaction_shuffler_type.clone());
+
         let exe_thread = std::thread::Builder::new()
             .name("txn_executor".to_string())
             .spawn(move || {
@@ -95,7 +104,7 @@ where
                 while let Ok(transactions) = block_receiver.recv() {
                     info!("Received block of size {:?} to execute", transactions.len());
                     executed += transactions.len();
-                    exe.execute_block(transactions);
+                    exe.execute_block(shuffle_user_transactions(shuffler.as_ref(), transactions));
                     info!("Finished executing block");
                 }
 
@@ -118,6 +127,8 @@ where
             .expect("Failed to spawn transaction executor thread.");
 
         let skip_commit = config.skip_commit;
+        let block_latencies = Arc::new(BlockLatencies::default());
+        let committer_block_latencies = block_latencies.clone();
 
         let commit_thread = std::thread::Builder::new()
             .name("txn_committer".to_string())
@@ -125,8 +136,12 @@ where
                 start_commit_rx.map(|rx| rx.recv());
                 info!("Starting commit thread");
                 if !skip_commit {
-                    let mut committer =
-                        TransactionCommitter::new(executor_2, version, commit_receiver);
+                    let mut committer = TransactionCommitter::new(
+                        executor_2,
+                        version,
+                        commit_receiver,
+                        committer_block_latencies,
+                    );
                     committer.run();
                 }
             })
@@ -138,6 +153,7 @@ where
                 join_handles,
                 phantom: PhantomData,
                 start_execution_tx,
+                block_latencies,
             },
             block_sender,
         )
@@ -147,9 +163,39 @@ where
         self.start_execution_tx.as_ref().map(|tx| tx.send(()));
     }
 
+    /// The latencies of the blocks committed so far, none if the commit is skipped.
+    pub fn block_latencies(&self) -> Arc<BlockLatencies> {
+        self.block_latencies.clone()
+    }
+
     pub fn join(self) {
         for handle in self.join_handles {
             handle.join().unwrap()
         }
     }
 }
+
+/// Shuffles the user transactions of the block, keeping the other transactions (e.g., the
+/// trailing state checkpoint) at their position.
+fn shuffle_user_transactions(
+    shuffler: &dyn TransactionShuffler,
+    transactions: Vec<Transaction>,
+) -> Vec<Transaction> {
+    let user_txns = transactions
+        .iter()
+        .filter_map(|txn| match txn {
+            Transaction::UserTransaction(user_txn) => Some(user_txn.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut shuffled_txns = shuffler.shuffle(user_txns).into_iter();
+    transactions
+        .into_iter()
+        .map(|txn| match txn {
+            Transaction::UserTransaction(_) => Transaction::UserTransaction(
+                shuffled_txns.next().expect("Shuffled transaction expected"),
+            ),
+            txn => txn,
+        })
+        .collect()
+}
@@ -0,0 +1,268 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_executor::metrics::{
+    APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS, APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS,
+    APTOS_EXECUTOR_OTHER_TIMERS_SECONDS, APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::info;
+use aptos_vm::counters::TXN_GAS_USAGE;
+use serde::Serialize;
+use std::{collections::HashMap, time::Duration};
+
+/// The sub-stages of the execution timed by the executor, as (prefix, top level, label).
+const EXECUTION_STAGES: &[(&str, bool, &str)] = &[
+    ("1.", true, "verified_state_view"),
+    ("2.", true, "apply_to_ledger"),
+    ("2.1.", false, "sort_transactions"),
+    ("2.2.", false, "calculate_for_transaction_block"),
+    ("2.2.1.", false, "get_sharded_state_updates"),
+    ("2.2.2.", false, "calculate_block_state_updates"),
+    ("2.2.3.", false, "calculate_usage"),
+    ("2.2.4.", false, "make_checkpoint"),
+    ("2.3.", false, "assemble_ledger_diff_for_block"),
+    ("2.3.1.", false, "calculate_events_and_writeset_hashes"),
+    ("3.", true, "as_state_compute_result"),
+    ("4.", true, "get_txns_to_commit"),
+];
+
+/// The time spent by a block in each stage of the pipeline.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockLatency {
+    pub num_txns: usize,
+    pub execution: Duration,
+    pub commit: Duration,
+    /// From the start of the execution to the end of the commit, including the time spent
+    /// waiting for the committer.
+    pub end_to_end: Duration,
+}
+
+/// The latencies of the blocks committed by the pipeline, in commit order.
+#[derive(Default)]
+pub struct BlockLatencies(Mutex<Vec<BlockLatency>>);
+
+impl BlockLatencies {
+    pub fn record(&self, block_latency: BlockLatency) {
+        self.0.lock().push(block_latency);
+    }
+
+    pub fn get(&self) -> Vec<BlockLatency> {
+        self.0.lock().clone()
+    }
+}
+
+/// The cumulative timers of the executor, snapshotted before running a workload so the time
+/// spent in each stage while running it can be reported.
+pub struct StageTimers {
+    gas: f64,
+    execution_total: f64,
+    vm_only: f64,
+    by_execution_stage: HashMap<&'static str, f64>,
+    commit_total: f64,
+}
+
+impl StageTimers {
+    pub fn snapshot() -> Self {
+        Self {
+            gas: TXN_GAS_USAGE.get_sample_sum(),
+            execution_total: APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum(),
+            vm_only: APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum(),
+            by_execution_stage: EXECUTION_STAGES
+                .iter()
+                .map(|(_prefix, _top_level, label)| {
+                    (
+                        *label,
+                        APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
+                            .with_label_values(&[label])
+                            .get_sample_sum(),
+                    )
+                })
+                .collect(),
+            commit_total: APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum(),
+        }
+    }
+
+    /// Reports the time spent in each stage since the snapshot, for a workload which committed
+    /// `num_txns` transactions in `elapsed` seconds.
+    pub fn report(
+        &self,
+        workload: String,
+        num_txns: u64,
+        elapsed: f64,
+        block_latencies: &[BlockLatency],
+    ) -> BenchmarkReport {
+        let delta_v = num_txns as f64;
+        let time_in_execution =
+            APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum() - self.execution_total;
+        let time_in_vm = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum() - self.vm_only;
+        let time_in_commit =
+            APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum() - self.commit_total;
+
+        let mut stages = vec![
+            StageReport::new("execution", true, time_in_execution, elapsed, delta_v),
+            StageReport::new("vm", true, time_in_vm, time_in_execution, delta_v),
+        ];
+        for (prefix, top_level, label) in EXECUTION_STAGES {
+            let time_in_label = APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
+                .with_label_values(&[label])
+                .get_sample_sum()
+                - self.by_execution_stage[label];
+            stages.push(StageReport::new(
+                &format!("{} {}", prefix, label),
+                *top_level,
+                time_in_label,
+                time_in_execution,
+                delta_v,
+            ));
+        }
+        stages.push(StageReport::new(
+            "commit",
+            true,
+            time_in_commit,
+            elapsed,
+            delta_v,
+        ));
+
+        BenchmarkReport {
+            workload,
+            num_txns,
+            elapsed_secs: elapsed,
+            tps: delta_v / elapsed,
+            gps: (TXN_GAS_USAGE.get_sample_sum() - self.gas) / elapsed,
+            stages,
+            block_latencies: vec![
+                LatencyReport::new("execution", block_latencies, |block| block.execution),
+                LatencyReport::new("commit", block_latencies, |block| block.commit),
+                LatencyReport::new("end_to_end", block_latencies, |block| block.end_to_end),
+            ],
+        }
+    }
+}
+
+/// The TPS of a workload and the breakdown of its time across the stages of the pipeline, in a
+/// format which can be compared across releases.
+#[derive(Debug, Serialize)]
+pub struct BenchmarkReport {
+    pub workload: String,
+    pub num_txns: u64,
+    pub elapsed_secs: f64,
+    pub tps: f64,
+    pub gps: f64,
+    pub stages: Vec<StageReport>,
+    /// The latencies of the execution, the commit and the whole pipeline (always in this order).
+    /// They are recorded by the committer, so they have no blocks when the commit is skipped.
+    pub block_latencies: Vec<LatencyReport>,
+}
+
+impl BenchmarkReport {
+    pub fn log(&self) {
+        info!("Executed workload {}", self.workload);
+        info!("Overall TPS: {} txn/s", self.tps);
+        info!("Overall GPS: {} gas/s", self.gps);
+        for stage in &self.stages {
+            match stage.name.as_str() {
+                "execution" | "commit" => info!(
+                    "Overall fraction of total: {:.3} in {} (component TPS: {})",
+                    stage.fraction, stage.name, stage.tps
+                ),
+                "vm" => info!(
+                    "Overall fraction of execution {:.3} in VM (component TPS: {})",
+                    stage.fraction, stage.tps
+                ),
+                _ => {
+                    if stage.top_level || stage.fraction > 0.01 {
+                        info!(
+                            "Overall fraction of execution {:.3} in {} (component TPS: {})",
+                            stage.fraction, stage.name, stage.tps
+                        );
+                    }
+                },
+            }
+        }
+        for latency in &self.block_latencies {
+            if latency.num_blocks > 0 {
+                info!(
+                    "Block {} latency over {} blocks: mean {:.1} ms, p50 {:.1} ms, p90 {:.1} ms, p99 {:.1} ms, max {:.1} ms",
+                    latency.stage,
+                    latency.num_blocks,
+                    latency.mean_ms,
+                    latency.p50_ms,
+                    latency.p90_ms,
+                    latency.p99_ms,
+                    latency.max_ms
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageReport {
+    pub name: String,
+    #[serde(skip)]
+    top_level: bool,
+    pub seconds: f64,
+    /// Of the total time for the execution and the commit, of the execution time otherwise
+    pub fraction: f64,
+    /// The TPS if the whole pipeline was as fast as this stage
+    pub tps: f64,
+}
+
+impl StageReport {
+    fn new(name: &str, top_level: bool, seconds: f64, parent_seconds: f64, delta_v: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            top_level,
+            seconds,
+            fraction: seconds / parent_seconds,
+            tps: delta_v / seconds,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LatencyReport {
+    pub stage: String,
+    pub num_blocks: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyReport {
+    fn new(
+        stage: &str,
+        block_latencies: &[BlockLatency],
+        latency: impl Fn(&BlockLatency) -> Duration,
+    ) -> Self {
+        let mut latencies_ms: Vec<f64> = block_latencies
+            .iter()
+            .map(|block| latency(block).as_secs_f64() * 1000.0)
+            .collect();
+        latencies_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let percentile = |p: f64| -> f64 {
+            if latencies_ms.is_empty() {
+                0.0
+            } else {
+                latencies_ms[((latencies_ms.len() - 1) as f64 * p).round() as usize]
+            }
+        };
+
+        Self {
+            stage: stage.to_string(),
+            num_blocks: latencies_ms.len(),
+            mean_ms: if latencies_ms.is_empty() {
+                0.0
+            } else {
+                latencies_ms.iter().sum::<f64>() / latencies_ms.len() as f64
+            },
+            p50_ms: percentile(0.5),
+            p90_ms: percentile(0.9),
+            p99_ms: percentile(0.99),
+            max_ms: latencies_ms.last().copied().unwrap_or(0.0),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::report::{BlockLatencies, BlockLatency};
 use aptos_crypto::hash::HashValue;
 use aptos_db::metrics::API_LATENCY_SECONDS;
 use aptos_executor::{
@@ -50,6 +51,7 @@ pub struct TransactionCommitter<V> {
     executor: Arc<BlockExecutor<V>>,
     version: Version,
     block_receiver: mpsc::Receiver<(HashValue, HashValue, Instant, Instant, Duration, usize)>,
+    block_latencies: Arc<BlockLatencies>,
 }
 
 impl<V> TransactionCommitter<V>
@@ -60,11 +62,13 @@ where
         executor: Arc<BlockExecutor<V>>,
         version: Version,
         block_receiver: mpsc::Receiver<(HashValue, HashValue, Instant, Instant, Duration, usize)>,
+        block_latencies: Arc<BlockLatencies>,
     ) -> Self {
         Self {
             version,
             executor,
             block_receiver,
+            block_latencies,
         }
     }
 
@@ -87,6 +91,12 @@ where
             self.executor
                 .commit_blocks_ext(vec![block_id], ledger_info_with_sigs, false)
                 .unwrap();
+            self.block_latencies.record(BlockLatency {
+                num_txns,
+                execution: execution_time,
+                commit: commit_start.elapsed(),
+                end_to_end: execution_start_time.elapsed(),
+            });
 
             report_block(
                 start_version,
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::account_generator::{AccountCache, AccountGenerator};
+use crate::{
+    account_generator::{AccountCache, AccountGenerator},
+    history_replay::SignerRemapper,
+};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue};
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use aptos_state_view::account_with_state_view::AsAccountWithStateView;
@@ -13,7 +16,7 @@ use aptos_types::{
     account_config::aptos_test_root_address,
     account_view::AccountView,
     chain_id::ChainId,
-    transaction::{Transaction, Version},
+    transaction::{SignedTransaction, Transaction, Version},
 };
 use chrono::Local;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -300,6 +303,33 @@ impl TransactionGenerator {
         }
     }
 
+    /// Replays the replayable recorded transactions in blocks of `block_size`, signed by the main
+    /// signer accounts instead of their original senders.
+    pub fn run_replay(
+        &mut self,
+        block_size: usize,
+        replayable_transactions: Vec<SignedTransaction>,
+    ) {
+        assert!(self.block_sender.is_some());
+        let mut remapper = SignerRemapper::new(self.transaction_factory.clone());
+        let accounts = &mut self.main_signer_accounts.as_mut().unwrap().accounts;
+        let replayed_transactions = replayable_transactions
+            .iter()
+            .map(|txn| remapper.remap(accounts, txn));
+
+        for chunk in &replayed_transactions.chunks(block_size) {
+            let transactions: Vec<_> = chunk
+                .map(Transaction::UserTransaction)
+                .chain(once(Transaction::StateCheckpoint(HashValue::random())))
+                .collect();
+            self.version += transactions.len() as Version;
+
+            if let Some(sender) = &self.block_sender {
+                sender.send(transactions).unwrap();
+            }
+        }
+    }
+
     pub fn create_seed_accounts(
         &mut self,
         reader: Arc<dyn DbReader>,
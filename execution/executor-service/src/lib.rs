@@ -0,0 +1,143 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs the shards of the sharded block executor as separate processes. The coordinator
+//! dispatches the partitions of a block to the shards through a [RemoteExecutorClient], and each
+//! shard runs an [ExecutorService] that executes its partition. The shards don't have access to
+//! the storage of the coordinator: the state values they read are requested from the coordinator
+//! (over the same connection) and cached by the shard for the duration of the block. The values
+//! hinted by the partitioner are prefetched in one request per sub-block, so only the reads it
+//! didn't anticipate cost a round trip each. The cross-shard messages of the shards are relayed by
+//! the coordinator as well.
+//!
+//! The connections are authenticated and encrypted with Noise: the coordinator knows the static
+//! public keys of the shards, and the shards only accept the coordinators they trust.
+
+use anyhow::{Context, Result};
+use aptos_block_partitioner::types::ShardId;
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_secure_net::NoiseClientConfig;
+use aptos_types::{
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    vm_status::VMStatus,
+};
+use aptos_vm::sharded_block_executor::{CrossShardMsg, ShardSubBlock, TransactionOutputWithDeltas};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::Path};
+
+mod remote_cross_shard_client;
+mod remote_executor_client;
+mod remote_executor_service;
+mod remote_state_view;
+#[cfg(test)]
+mod tests;
+
+pub use remote_executor_client::RemoteExecutorClient;
+pub use remote_executor_service::ExecutorService;
+
+/// The default read, write and connect timeout of the connections to the remote shards
+pub const DEFAULT_NETWORK_TIMEOUT_MS: u64 = 60_000;
+
+/// A remote executor shard, and the Noise configuration the coordinator authenticates to it with
+#[derive(Clone)]
+pub struct RemoteShard {
+    pub address: SocketAddr,
+    pub noise: NoiseClientConfig,
+}
+
+impl RemoteShard {
+    /// Returns the shards at the given addresses, authenticating with their static public keys,
+    /// that the coordinator connects to with the given static key
+    pub fn with_identity(
+        identity: x25519::PrivateKey,
+        remote_shards: Vec<(SocketAddr, x25519::PublicKey)>,
+    ) -> Vec<Self> {
+        let noise = match remote_shards.first() {
+            Some((_, public_key)) => NoiseClientConfig::new(identity, *public_key),
+            None => return vec![],
+        };
+        remote_shards
+            .into_iter()
+            .map(|(address, public_key)| Self {
+                address,
+                noise: noise.with_server_public_key(public_key),
+            })
+            .collect()
+    }
+}
+
+static REMOTE_SHARDS: OnceCell<Vec<RemoteShard>> = OnceCell::new();
+
+/// Sets the remote executor shards used by the executor (in order by shard id), given the static
+/// key of the coordinator and the address and static public key of each shard. Only the first
+/// call succeeds.
+pub fn set_remote_shards_once(
+    identity: x25519::PrivateKey,
+    remote_shards: Vec<(SocketAddr, x25519::PublicKey)>,
+) {
+    REMOTE_SHARDS
+        .set(RemoteShard::with_identity(identity, remote_shards))
+        .ok();
+}
+
+/// Returns the remote executor shards, if the blocks should be executed by remote shards
+pub fn get_remote_shards() -> Option<&'static [RemoteShard]> {
+    REMOTE_SHARDS
+        .get()
+        .filter(|remote_shards| !remote_shards.is_empty())
+        .map(|remote_shards| remote_shards.as_slice())
+}
+
+/// Reads a hex encoded x25519 private key from a file, e.g. the static key of a coordinator or
+/// of a shard
+pub fn load_identity(path: &Path) -> Result<x25519::PrivateKey> {
+    let encoded = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    x25519::PrivateKey::from_encoded_string(encoded.trim())
+        .with_context(|| format!("Invalid x25519 private key in {}", path.display()))
+}
+
+/// Parses a hex encoded x25519 public key, e.g. from the command line
+pub fn parse_public_key(encoded: &str) -> Result<x25519::PublicKey> {
+    x25519::PublicKey::from_encoded_string(encoded)
+        .with_context(|| format!("Invalid x25519 public key {}", encoded))
+}
+
+/// The messages sent by the coordinator to a remote executor shard
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RemoteExecutionRequest {
+    /// Executes the partition of a block
+    ExecuteBlock(ExecuteBlockCommand),
+    /// The values of the state keys requested by the shard during the execution of the block, in
+    /// the order of the keys
+    StateValues(Result<Vec<Option<StateValue>>, String>),
+    /// The next cross-shard message sent to the shard by the other shards
+    CrossShardMsg(CrossShardMsg),
+}
+
+/// The messages sent by a remote executor shard to the coordinator
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RemoteExecutionResponse {
+    /// Requests the values of state keys that aren't cached by the shard
+    GetStateValues(Vec<StateKey>),
+    /// Sends a cross-shard message to another shard. The coordinator doesn't reply.
+    SendCrossShardMsg(ShardId, CrossShardMsg),
+    /// Requests the next cross-shard message sent to the shard, blocking until there is one
+    ReceiveCrossShardMsg,
+    /// The outputs of the partition of the block
+    ExecuteBlockResult(Result<Vec<TransactionOutputWithDeltas>, VMStatus>),
+}
+
+/// The partition of a block to execute, and the properties of the state view of the
+/// coordinator that aren't read through state keys
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExecuteBlockCommand {
+    pub sub_blocks: Vec<ShardSubBlock>,
+    pub concurrency_level: usize,
+    pub maybe_block_gas_limit: Option<u64>,
+    pub is_genesis: bool,
+    pub state_storage_usage: StateStorageUsage,
+}
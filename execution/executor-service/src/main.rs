@@ -0,0 +1,65 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::x25519;
+use aptos_executor_service::{
+    load_identity, parse_public_key, ExecutorService, DEFAULT_NETWORK_TIMEOUT_MS,
+};
+use aptos_secure_net::NoiseServerConfig;
+use clap::Parser;
+use std::{net::SocketAddr, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "aptos-executor-service",
+    about = "Runs a remote shard of the sharded block executor"
+)]
+struct Args {
+    /// The id of the shard (only used for logging)
+    #[clap(long, default_value = "0")]
+    shard_id: usize,
+
+    /// The total number of executor shards
+    #[clap(long)]
+    num_shards: usize,
+
+    /// The number of threads executing the transactions, defaults to the number of cpus
+    #[clap(long)]
+    num_executor_threads: Option<usize>,
+
+    /// The address the coordinator connects to
+    #[clap(long)]
+    listen_addr: SocketAddr,
+
+    /// The read, write and connect timeout of the connection to the coordinator
+    #[clap(long, default_value_t = DEFAULT_NETWORK_TIMEOUT_MS)]
+    network_timeout_ms: u64,
+
+    /// A file with the hex encoded x25519 static key the shard authenticates to the coordinator
+    /// with
+    #[clap(long)]
+    identity_file: PathBuf,
+
+    /// The x25519 static public keys of the coordinators allowed to connect
+    #[clap(long, required = true, multiple_values = true, parse(try_from_str = parse_public_key))]
+    coordinator_public_keys: Vec<x25519::PublicKey>,
+}
+
+fn main() {
+    let args = Args::parse();
+    aptos_logger::Logger::new().init();
+
+    let identity =
+        load_identity(&args.identity_file).expect("Failed to load the identity of the shard");
+    let noise =
+        NoiseServerConfig::new(identity, args.coordinator_public_keys.into_iter().collect());
+    let executor_service = ExecutorService::new(
+        args.shard_id,
+        args.num_shards,
+        args.num_executor_threads.unwrap_or_else(num_cpus::get),
+        args.listen_addr,
+        args.network_timeout_ms,
+        noise,
+    );
+    executor_service.start();
+}
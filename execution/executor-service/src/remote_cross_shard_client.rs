@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{RemoteExecutionRequest, RemoteExecutionResponse};
+use anyhow::{bail, Result};
+use aptos_block_partitioner::types::ShardId;
+use aptos_infallible::Mutex;
+use aptos_secure_net::NetworkServer;
+use aptos_vm::sharded_block_executor::{CrossShardClient, CrossShardMsg};
+use std::sync::Arc;
+
+/// The cross-shard client of a remote executor shard for the execution of a block. The messages
+/// are relayed by the coordinator, over the connection the block was received on.
+pub struct RemoteCrossShardClient {
+    network_server: Arc<Mutex<NetworkServer>>,
+}
+
+impl RemoteCrossShardClient {
+    pub fn new(network_server: Arc<Mutex<NetworkServer>>) -> Self {
+        Self { network_server }
+    }
+}
+
+impl CrossShardClient for RemoteCrossShardClient {
+    fn send_cross_shard_msg(&self, shard_id: ShardId, msg: CrossShardMsg) -> Result<()> {
+        self.network_server.lock().write(&bcs::to_bytes(
+            &RemoteExecutionResponse::SendCrossShardMsg(shard_id, msg),
+        )?)?;
+        Ok(())
+    }
+
+    fn receive_cross_shard_msg(&self) -> Result<CrossShardMsg> {
+        // The sub-blocks are executed one at a time, so no state values are requested while the
+        // connection is held
+        let mut network_server = self.network_server.lock();
+        network_server.write(&bcs::to_bytes(
+            &RemoteExecutionResponse::ReceiveCrossShardMsg,
+        )?)?;
+        match bcs::from_bytes(&network_server.read()?)? {
+            RemoteExecutionRequest::CrossShardMsg(msg) => Ok(msg),
+            request => bail!(
+                "Unexpected request while waiting for a cross-shard message: {:?}",
+                request
+            ),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ExecuteBlockCommand, RemoteExecutionRequest, RemoteExecutionResponse, RemoteShard};
+use anyhow::Result;
+use aptos_block_partitioner::types::{ShardId, TxnIndex};
+use aptos_infallible::Mutex;
+use aptos_logger::{error, warn};
+use aptos_secure_net::NetworkClient;
+use aptos_state_view::StateView;
+use aptos_types::vm_status::{StatusCode, VMStatus};
+use aptos_vm::sharded_block_executor::{
+    CrossShardMsg, ExecutorClient, ShardSubBlock, TransactionOutputWithDeltas,
+};
+use std::{
+    collections::HashSet,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// An executor client that dispatches the partitions of a block to remote executor shards (see
+/// [crate::ExecutorService]), serves the state values they read from its state view, and relays
+/// their cross-shard messages.
+pub struct RemoteExecutorClient {
+    // The connections to the shards, in order by shard id
+    shard_clients: Vec<Mutex<NetworkClient>>,
+    network_timeout_ms: u64,
+}
+
+/// Relays the cross-shard messages of a shard during the execution of a block. The messages are
+/// kept for the whole block, so that the partition can be executed again on a new connection.
+struct CrossShardRelay {
+    message_txs: Vec<Sender<CrossShardMsg>>,
+    message_rx: Receiver<CrossShardMsg>,
+    // The messages delivered to the shard, in order
+    delivered_msgs: Vec<CrossShardMsg>,
+    // The messages sent by the shard, by destination shard and transaction index
+    sent_msgs: HashSet<(ShardId, TxnIndex)>,
+    timeout: Duration,
+}
+
+impl CrossShardRelay {
+    fn send(&mut self, shard_id: ShardId, msg: CrossShardMsg) {
+        if self.sent_msgs.insert((shard_id, msg.txn_index)) {
+            // The destination shard only stops receiving once its partition is done, so the
+            // message isn't needed anymore if this fails
+            self.message_txs[shard_id].send(msg).ok();
+        }
+    }
+
+    /// Returns the delivered message at the given position, waiting for it if it wasn't
+    /// delivered yet
+    fn receive(&mut self, position: usize) -> Result<CrossShardMsg> {
+        if position == self.delivered_msgs.len() {
+            let msg = self.message_rx.recv_timeout(self.timeout)?;
+            self.delivered_msgs.push(msg);
+        }
+        Ok(self.delivered_msgs[position].clone())
+    }
+
+    /// Unblocks the shards waiting for the messages of a shard that failed
+    fn send_remaining(&mut self, sub_blocks: &[ShardSubBlock]) {
+        for sub_block in sub_blocks {
+            for (txn_index, dependents) in &sub_block.dependents {
+                for dependent_shard in &dependents.shards {
+                    self.send(*dependent_shard, CrossShardMsg {
+                        txn_index: *txn_index,
+                        writes: vec![],
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl RemoteExecutorClient {
+    pub fn new(remote_shards: &[RemoteShard], network_timeout_ms: u64) -> Self {
+        assert!(
+            !remote_shards.is_empty(),
+            "At least one remote executor shard is required"
+        );
+        let shard_clients = remote_shards
+            .iter()
+            .map(|remote_shard| {
+                Mutex::new(NetworkClient::new_with_noise(
+                    "executor-service",
+                    remote_shard.address,
+                    network_timeout_ms,
+                    remote_shard.noise.clone(),
+                ))
+            })
+            .collect();
+        Self {
+            shard_clients,
+            network_timeout_ms,
+        }
+    }
+
+    /// Sends the command to the shard, and serves its state value requests and cross-shard
+    /// messages until it returns the outputs of the partition
+    fn execute_partition<S: StateView>(
+        network_client: &mut NetworkClient,
+        state_view: &S,
+        command: &ExecuteBlockCommand,
+        relay: &mut CrossShardRelay,
+    ) -> Result<Result<Vec<TransactionOutputWithDeltas>, VMStatus>> {
+        network_client.write(&bcs::to_bytes(&RemoteExecutionRequest::ExecuteBlock(
+            command.clone(),
+        ))?)?;
+        // The position of the next message to deliver in this attempt
+        let mut next_msg = 0;
+        loop {
+            match bcs::from_bytes(&network_client.read()?)? {
+                RemoteExecutionResponse::GetStateValues(state_keys) => {
+                    let state_values = state_keys
+                        .iter()
+                        .map(|state_key| state_view.get_state_value(state_key))
+                        .collect::<Result<Vec<_>>>()
+                        .map_err(|error| error.to_string());
+                    network_client.write(&bcs::to_bytes(&RemoteExecutionRequest::StateValues(
+                        state_values,
+                    ))?)?;
+                },
+                RemoteExecutionResponse::SendCrossShardMsg(shard_id, msg) => {
+                    relay.send(shard_id, msg);
+                },
+                RemoteExecutionResponse::ReceiveCrossShardMsg => {
+                    let msg = relay.receive(next_msg)?;
+                    next_msg += 1;
+                    network_client
+                        .write(&bcs::to_bytes(&RemoteExecutionRequest::CrossShardMsg(msg))?)?;
+                },
+                RemoteExecutionResponse::ExecuteBlockResult(result) => return Ok(result),
+            }
+        }
+    }
+}
+
+impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorClient {
+    fn num_shards(&self) -> usize {
+        self.shard_clients.len()
+    }
+
+    fn execute_block(
+        &self,
+        state_view: Arc<S>,
+        sub_blocks: Vec<Vec<ShardSubBlock>>,
+        concurrency_level_per_shard: usize,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<Vec<Vec<TransactionOutputWithDeltas>>, VMStatus> {
+        let is_genesis = state_view.is_genesis();
+        let state_storage_usage = state_view
+            .get_usage()
+            .map_err(|error| VMStatus::Error(StatusCode::STORAGE_ERROR, Some(error.to_string())))?;
+
+        let (message_txs, message_rxs): (Vec<_>, Vec<_>) =
+            (0..self.shard_clients.len()).map(|_| channel()).unzip();
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = sub_blocks
+                .into_iter()
+                .zip(self.shard_clients.iter())
+                .zip(message_rxs)
+                .enumerate()
+                .map(|(shard_id, ((sub_blocks, shard_client), message_rx))| {
+                    let command = ExecuteBlockCommand {
+                        sub_blocks,
+                        concurrency_level: concurrency_level_per_shard,
+                        maybe_block_gas_limit,
+                        is_genesis,
+                        state_storage_usage,
+                    };
+                    let mut relay = CrossShardRelay {
+                        message_txs: message_txs.clone(),
+                        message_rx,
+                        delivered_msgs: vec![],
+                        sent_msgs: HashSet::new(),
+                        timeout: Duration::from_millis(self.network_timeout_ms),
+                    };
+                    let state_view = state_view.as_ref();
+                    scope.spawn(move || {
+                        let mut shard_client = shard_client.lock();
+                        // The connection may have been closed by the shard since the previous
+                        // block (e.g., after a timeout). The execution is deterministic, so the
+                        // partition can be sent again on a new connection, with the same
+                        // cross-shard messages.
+                        Self::execute_partition(&mut shard_client, state_view, &command, &mut relay)
+                            .or_else(|error| {
+                                warn!(
+                                    "Failed to execute the partition on shard {}, retrying: {}",
+                                    shard_id, error
+                                );
+                                Self::execute_partition(
+                                    &mut shard_client,
+                                    state_view,
+                                    &command,
+                                    &mut relay,
+                                )
+                            })
+                            .map_err(|error| {
+                                relay.send_remaining(&command.sub_blocks);
+                                (shard_id, error)
+                            })
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .expect("Failed to join the shard client thread")
+                })
+                .collect()
+        });
+
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|(shard_id, error)| {
+                    error!("Remote executor shard {} failed: {}", shard_id, error);
+                    Err(VMStatus::Error(
+                        StatusCode::STORAGE_ERROR,
+                        Some(format!("Remote executor shard {} failed", shard_id)),
+                    ))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateView,
+    RemoteExecutionRequest, RemoteExecutionResponse,
+};
+use anyhow::{bail, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, trace, warn};
+use aptos_secure_net::{NetworkServer, NoiseServerConfig};
+use aptos_vm::sharded_block_executor::execute_sub_blocks;
+use aptos_vm_logging::disable_speculative_logging;
+use std::{net::SocketAddr, sync::Arc};
+
+/// A remote executor shard, executing the partitions of the blocks received from the
+/// coordinator (see [execute_sub_blocks]). It serves one coordinator at a time, among the ones
+/// whose static public keys it trusts.
+pub struct ExecutorService {
+    shard_id: usize,
+    network_server: Arc<Mutex<NetworkServer>>,
+    executor_thread_pool: Arc<rayon::ThreadPool>,
+}
+
+impl ExecutorService {
+    pub fn new(
+        shard_id: usize,
+        num_executor_shards: usize,
+        num_executor_threads: usize,
+        listen_addr: SocketAddr,
+        network_timeout_ms: u64,
+        noise: NoiseServerConfig,
+    ) -> Self {
+        let executor_thread_pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_executor_threads)
+                .build()
+                .unwrap(),
+        );
+
+        if num_executor_shards > 1 {
+            // todo: speculative logging is not yet compatible with sharded block executor.
+            disable_speculative_logging();
+        }
+
+        let public_key = noise.public_key();
+        let network_server = NetworkServer::new_with_noise(
+            "executor-service",
+            listen_addr,
+            network_timeout_ms,
+            noise,
+        );
+        info!(
+            "Executor shard {} listening on {} with public key {} and {} executor threads",
+            shard_id, listen_addr, public_key, num_executor_threads
+        );
+        Self {
+            shard_id,
+            network_server: Arc::new(Mutex::new(network_server)),
+            executor_thread_pool,
+        }
+    }
+
+    /// Serves the requests of the coordinator, forever
+    pub fn start(&self) {
+        loop {
+            if let Err(e) = self.process_one_request() {
+                warn!("Shard {} failed to process request: {}", self.shard_id, e);
+            }
+        }
+    }
+
+    fn process_one_request(&self) -> Result<()> {
+        let request = self.network_server.lock().read()?;
+        let command = match bcs::from_bytes(&request)? {
+            RemoteExecutionRequest::ExecuteBlock(command) => command,
+            request => bail!("Unexpected request outside of a block: {:?}", request),
+        };
+        trace!(
+            "Shard {} received ExecuteBlock command of {} sub-blocks",
+            self.shard_id,
+            command.sub_blocks.len()
+        );
+
+        let state_view = RemoteStateView::new(
+            self.network_server.clone(),
+            command.is_genesis,
+            command.state_storage_usage,
+        );
+        for sub_block in &command.sub_blocks {
+            state_view.prefetch_state_values(&sub_block.read_hints)?;
+        }
+        let cross_shard_client = RemoteCrossShardClient::new(self.network_server.clone());
+        let result = execute_sub_blocks(
+            self.shard_id,
+            command.sub_blocks,
+            &state_view,
+            &cross_shard_client,
+            self.executor_thread_pool.clone(),
+            command.concurrency_level,
+            command.maybe_block_gas_limit,
+        );
+        trace!(
+            "Shard {} executed the block, reading {} state values from the coordinator",
+            self.shard_id,
+            state_view.num_state_values()
+        );
+
+        let response = bcs::to_bytes(&RemoteExecutionResponse::ExecuteBlockResult(result))?;
+        self.network_server.lock().write(&response)?;
+        Ok(())
+    }
+}
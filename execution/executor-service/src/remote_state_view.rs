@@ -0,0 +1,118 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{RemoteExecutionRequest, RemoteExecutionResponse};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_infallible::Mutex;
+use aptos_secure_net::NetworkServer;
+use aptos_state_view::TStateView;
+use aptos_types::state_store::{
+    state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+};
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// The state view of a remote executor shard for the execution of a block. The state values
+/// that aren't cached yet are requested from the coordinator, over the connection the block was
+/// received on, either ahead of the execution (see [RemoteStateView::prefetch_state_values]) or
+/// one at a time when they are read.
+pub struct RemoteStateView {
+    network_server: Arc<Mutex<NetworkServer>>,
+    state_values: DashMap<StateKey, Option<StateValue>>,
+    is_genesis: bool,
+    state_storage_usage: StateStorageUsage,
+}
+
+impl RemoteStateView {
+    pub fn new(
+        network_server: Arc<Mutex<NetworkServer>>,
+        is_genesis: bool,
+        state_storage_usage: StateStorageUsage,
+    ) -> Self {
+        Self {
+            network_server,
+            state_values: DashMap::new(),
+            is_genesis,
+            state_storage_usage,
+        }
+    }
+
+    /// Returns the number of state values read by the shard
+    pub fn num_state_values(&self) -> usize {
+        self.state_values.len()
+    }
+
+    /// Fetches the values of the state keys that aren't cached yet in a single request, e.g. the
+    /// keys hinted by the partitioner for a sub-block
+    pub fn prefetch_state_values(&self, state_keys: &[StateKey]) -> Result<()> {
+        let state_keys: Vec<_> = state_keys
+            .iter()
+            .filter(|state_key| !self.state_values.contains_key(state_key))
+            .cloned()
+            .collect();
+        if state_keys.is_empty() {
+            return Ok(());
+        }
+        let state_values = self.fetch_state_values(state_keys.clone())?;
+        for (state_key, state_value) in state_keys.into_iter().zip(state_values) {
+            self.state_values.insert(state_key, state_value);
+        }
+        Ok(())
+    }
+
+    fn fetch_state_values(&self, state_keys: Vec<StateKey>) -> Result<Vec<Option<StateValue>>> {
+        let num_state_keys = state_keys.len();
+        // The requests of the execution threads are serialized, as the coordinator serves them
+        // in order on a single connection
+        let mut network_server = self.network_server.lock();
+        network_server.write(&bcs::to_bytes(&RemoteExecutionResponse::GetStateValues(
+            state_keys,
+        ))?)?;
+        let state_values = match bcs::from_bytes(&network_server.read()?)? {
+            RemoteExecutionRequest::StateValues(state_values) => state_values.map_err(|error| {
+                anyhow!(
+                    "The coordinator failed to read {} state values: {}",
+                    num_state_keys,
+                    error
+                )
+            })?,
+            request => bail!(
+                "Unexpected request while waiting for {} state values: {:?}",
+                num_state_keys,
+                request
+            ),
+        };
+        ensure!(
+            state_values.len() == num_state_keys,
+            "The coordinator returned {} state values instead of {}",
+            state_values.len(),
+            num_state_keys
+        );
+        Ok(state_values)
+    }
+}
+
+impl TStateView for RemoteStateView {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if let Some(state_value) = self.state_values.get(state_key) {
+            return Ok(state_value.clone());
+        }
+        let state_value = self
+            .fetch_state_values(vec![state_key.clone()])?
+            .pop()
+            .expect("The number of state values is checked");
+        self.state_values
+            .insert(state_key.clone(), state_value.clone());
+        Ok(state_value)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.is_genesis
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        Ok(self.state_storage_usage)
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ExecutorService, RemoteExecutorClient, RemoteShard, DEFAULT_NETWORK_TIMEOUT_MS};
+use aptos_block_partitioner::sharded_block_partitioner::ShardedBlockPartitioner;
+use aptos_config::utils;
+use aptos_crypto::x25519;
+use aptos_language_e2e_tests::{common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use aptos_secure_net::NoiseServerConfig;
+use aptos_types::transaction::{ExecutionStatus, Transaction, TransactionStatus};
+use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    thread,
+};
+
+// Starts the shards trusting the given coordinator, and returns their addresses and public keys
+fn start_remote_shards(
+    num_shards: usize,
+    coordinator_public_key: x25519::PublicKey,
+) -> Vec<(SocketAddr, x25519::PublicKey)> {
+    (0..num_shards)
+        .map(|shard_id| {
+            let listen_addr =
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+            let shard_noise = NoiseServerConfig::new(
+                x25519::PrivateKey::from([shard_id as u8 + 1; 32]),
+                HashSet::from([coordinator_public_key]),
+            );
+            let shard_public_key = shard_noise.public_key();
+            let executor_service = ExecutorService::new(
+                shard_id,
+                num_shards,
+                2,
+                listen_addr,
+                DEFAULT_NETWORK_TIMEOUT_MS,
+                shard_noise,
+            );
+            thread::spawn(move || executor_service.start());
+            (listen_addr, shard_public_key)
+        })
+        .collect()
+}
+
+// Generates transfers between a few accounts, so that the transactions conflict across shards
+fn generate_transfers(executor: &mut FakeExecutor, num_transfers: usize) -> Vec<Transaction> {
+    let accounts = executor.create_accounts(4, 1_000_000_000, 0);
+    let mut sequence_numbers = vec![0; accounts.len()];
+    (0..num_transfers)
+        .map(|i| {
+            let sender = i % accounts.len();
+            let receiver = (i + 1) % accounts.len();
+            sequence_numbers[sender] += 1;
+            Transaction::UserTransaction(peer_to_peer_txn(
+                &accounts[sender],
+                &accounts[receiver],
+                sequence_numbers[sender] - 1,
+                1_000,
+                100,
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn test_remote_shards_match_local_shards() {
+    let num_shards = 2;
+    let mut executor = FakeExecutor::from_head_genesis();
+    let transactions = generate_transfers(&mut executor, 20);
+    let state_view = Arc::new(executor.get_state_view().clone());
+    let sub_blocks =
+        ShardedBlockPartitioner::new(num_shards).partition_transactions(transactions.clone(), 2);
+
+    let local_executor = ShardedBlockExecutor::new(num_shards, Some(2));
+    let expected_outputs = local_executor
+        .execute_block(state_view.clone(), sub_blocks.clone(), 2, None)
+        .unwrap();
+    assert_eq!(expected_outputs.len(), transactions.len());
+    for output in &expected_outputs {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+
+    let coordinator_identity = x25519::PrivateKey::from([0; 32]);
+    let shards = start_remote_shards(num_shards, coordinator_identity.public_key());
+    let remote_shards = RemoteShard::with_identity(coordinator_identity, shards);
+    let remote_executor = ShardedBlockExecutor::with_executor_client(Box::new(
+        RemoteExecutorClient::new(&remote_shards, DEFAULT_NETWORK_TIMEOUT_MS),
+    ));
+    assert_eq!(remote_executor.num_shards(), num_shards);
+
+    // The connections to the shards are reused across blocks
+    for _ in 0..2 {
+        let outputs = remote_executor
+            .execute_block(state_view.clone(), sub_blocks.clone(), 2, None)
+            .unwrap();
+        assert_eq!(outputs, expected_outputs);
+    }
+}
+
+#[test]
+fn test_remote_shards_reject_untrusted_coordinator() {
+    let num_shards = 2;
+    let mut executor = FakeExecutor::from_head_genesis();
+    let transactions = generate_transfers(&mut executor, 4);
+    let state_view = Arc::new(executor.get_state_view().clone());
+    let sub_blocks =
+        ShardedBlockPartitioner::new(num_shards).partition_transactions(transactions, 2);
+
+    // The shards trust another coordinator than the one connecting to them
+    let trusted_public_key = x25519::PrivateKey::from([0; 32]).public_key();
+    let remote_shards = RemoteShard::with_identity(
+        x25519::PrivateKey::from([42; 32]),
+        start_remote_shards(num_shards, trusted_public_key),
+    );
+    let remote_executor = ShardedBlockExecutor::with_executor_client(Box::new(
+        RemoteExecutorClient::new(&remote_shards, DEFAULT_NETWORK_TIMEOUT_MS),
+    ));
+    assert!(remote_executor
+        .execute_block(state_view, sub_blocks, 2, None)
+        .is_err());
+}
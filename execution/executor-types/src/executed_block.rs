@@ -12,7 +12,9 @@ use aptos_types::{
     epoch_state::EpochState,
     proof::accumulator::InMemoryAccumulator,
     state_store::ShardedStateUpdates,
-    transaction::{Transaction, TransactionStatus, TransactionToCommit},
+    transaction::{
+        ExecutionFingerprint, Transaction, TransactionStatus, TransactionToCommit, Version,
+    },
 };
 use std::sync::Arc;
 
@@ -88,4 +90,17 @@ impl ExecutedBlock {
             self.reconfig_events.clone(),
         )
     }
+
+    /// The first version of the block, and the fingerprints of the execution of its
+    /// transactions.
+    pub fn execution_fingerprints(&self) -> (Version, Vec<ExecutionFingerprint>) {
+        let first_version =
+            self.result_view.txn_accumulator().num_leaves() - self.to_commit.len() as u64;
+        let execution_fingerprints = self
+            .to_commit
+            .iter()
+            .map(|txn| ExecutionFingerprint::from_txn_info(txn.transaction_info()))
+            .collect();
+        (first_version, execution_fingerprints)
+    }
 }
@@ -13,7 +13,10 @@ use aptos_types::{
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     state_store::create_empty_sharded_state_updates,
-    transaction::{Transaction, TransactionInfo, TransactionStatus, TransactionToCommit},
+    transaction::{
+        ExecutionFingerprint, Transaction, TransactionInfo, TransactionStatus, TransactionToCommit,
+        Version,
+    },
 };
 
 #[derive(Default)]
@@ -75,6 +78,19 @@ impl ExecutedChunk {
             .collect()
     }
 
+    /// The first version of the chunk, and the fingerprints of the execution of its
+    /// transactions.
+    pub fn execution_fingerprints(&self) -> (Version, Vec<ExecutionFingerprint>) {
+        let first_version =
+            self.result_view.txn_accumulator().num_leaves() - self.to_commit.len() as u64;
+        let execution_fingerprints = self
+            .to_commit
+            .iter()
+            .map(|(_, txn_data)| ExecutionFingerprint::from_txn_info(&txn_data.txn_info))
+            .collect();
+        (first_version, execution_fingerprints)
+    }
+
     pub fn has_reconfiguration(&self) -> bool {
         self.next_epoch_state.is_some()
     }
@@ -123,6 +123,11 @@ pub trait BlockExecutorTrait: Send + Sync {
         )
     }
 
+    /// Saves the fingerprints of the execution of the blocks, whose result doesn't match the one
+    /// they are committed with, so they can be compared with the ones of the other nodes even
+    /// though the blocks are never committed.
+    fn save_execution_fingerprints(&self, block_ids: Vec<HashValue>) -> Result<(), Error>;
+
     /// Finishes the block executor by releasing memory held by inner data structures(SMT).
     fn finish(&self);
 }
@@ -161,6 +166,23 @@ impl ExecutableTransactions {
             },
         }
     }
+
+    /// The transactions in the order they're committed in, which is the order of the sub-blocks
+    /// for a partitioned block.
+    pub fn txns(&self) -> Vec<Transaction> {
+        match self {
+            ExecutableTransactions::Unsharded(transactions) => transactions.clone(),
+            ExecutableTransactions::Sharded(sub_blocks) => sub_blocks
+                .iter()
+                .flat_map(|sub_block| {
+                    sub_block
+                        .transactions_with_deps()
+                        .iter()
+                        .map(|txn| txn.txn.transaction().clone())
+                })
+                .collect(),
+        }
+    }
 }
 
 impl From<Vec<Transaction>> for ExecutableTransactions {
@@ -129,6 +129,14 @@ where
             .commit_blocks_ext(block_ids, ledger_info_with_sigs, save_state_snapshots)
     }
 
+    fn save_execution_fingerprints(&self, block_ids: Vec<HashValue>) -> Result<(), Error> {
+        self.inner
+            .read()
+            .as_ref()
+            .expect("BlockExecutor is not reset")
+            .save_execution_fingerprints(block_ids)
+    }
+
     fn finish(&self) {
         *self.inner.write() = None;
     }
@@ -351,4 +359,20 @@ where
 
         Ok(())
     }
+
+    fn save_execution_fingerprints(&self, block_ids: Vec<HashValue>) -> Result<(), Error> {
+        for block in self.block_tree.get_blocks(&block_ids)? {
+            let (first_version, execution_fingerprints) = block.output.execution_fingerprints();
+            info!(
+                LogSchema::new(LogEntry::BlockExecutor).block_id(block.id),
+                "Saving the fingerprints of {} transactions from version {}.",
+                execution_fingerprints.len(),
+                first_version,
+            );
+            self.db
+                .writer
+                .save_execution_fingerprints(first_version, &execution_fingerprints)?;
+        }
+        Ok(())
+    }
 }
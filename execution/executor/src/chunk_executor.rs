@@ -32,8 +32,8 @@ use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{
-        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
-        TransactionOutputListWithProof, TransactionStatus, Version,
+        ExecutionFingerprint, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionOutput, TransactionOutputListWithProof, TransactionStatus, Version,
     },
     write_set::WriteSet,
 };
@@ -136,6 +136,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
     }
 
     fn apply_chunk_output_for_state_sync(
+        &self,
         verified_target_li: &LedgerInfoWithSignatures,
         epoch_change_li: Option<&LedgerInfoWithSignatures>,
         latest_view: &ExecutedTrees,
@@ -146,13 +147,49 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
             chunk_output.apply_to_ledger(latest_view, None)?;
         ensure_no_discard(to_discard)?;
         ensure_no_retry(to_retry)?;
-        executed_chunk.ledger_info = executed_chunk
-            .maybe_select_chunk_ending_ledger_info(verified_target_li, epoch_change_li)?;
-        executed_chunk.ensure_transaction_infos_match(transaction_infos)?;
+        let verified = executed_chunk
+            .maybe_select_chunk_ending_ledger_info(verified_target_li, epoch_change_li)
+            .and_then(|ledger_info| {
+                executed_chunk.ensure_transaction_infos_match(transaction_infos)?;
+                Ok(ledger_info)
+            });
+        match verified {
+            Ok(ledger_info) => executed_chunk.ledger_info = ledger_info,
+            Err(err) => {
+                let (first_version, execution_fingerprints) =
+                    executed_chunk.execution_fingerprints();
+                self.record_execution_divergence(first_version, &execution_fingerprints, &err);
+                return Err(err);
+            },
+        }
 
         Ok(executed_chunk)
     }
 
+    /// Records the fingerprints of a local execution which doesn't match the transaction infos or
+    /// the ledger info it was verified against, so they can be compared with the ones of the
+    /// other nodes even though it is never committed.
+    fn record_execution_divergence(
+        &self,
+        first_version: Version,
+        execution_fingerprints: &[ExecutionFingerprint],
+        err: &anyhow::Error,
+    ) {
+        error!(
+            first_version = first_version,
+            num_txns = execution_fingerprints.len(),
+            error = ?err,
+            "Local execution diverged, recording its fingerprints.",
+        );
+        if let Err(err) = self
+            .db
+            .writer
+            .save_execution_fingerprints(first_version, execution_fingerprints)
+        {
+            warn!(error = ?err, "Failed to record the execution fingerprints.");
+        }
+    }
+
     fn commit_chunk_impl(&self) -> Result<Arc<ExecutedChunk>> {
         let (base_view, to_commit) = self.commit_queue.lock().next_chunk_to_commit()?;
         let txns_to_commit = to_commit.transactions_to_commit()?;
@@ -206,7 +243,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
             // State sync executor shouldn't have block gas limit.
             ChunkOutput::by_transaction_execution::<V>(transactions.into(), state_view, None)?
         };
-        let executed_chunk = Self::apply_chunk_output_for_state_sync(
+        let executed_chunk = self.apply_chunk_output_for_state_sync(
             verified_target_li,
             epoch_change_li,
             &latest_view,
@@ -256,7 +293,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         // Apply transaction outputs.
         let state_view = self.state_view(&latest_view)?;
         let chunk_output = ChunkOutput::by_transaction_output(txns_and_outputs, state_view)?;
-        let executed_chunk = Self::apply_chunk_output_for_state_sync(
+        let executed_chunk = self.apply_chunk_output_for_state_sync(
             verified_target_li,
             epoch_change_li,
             &latest_view,
@@ -547,6 +584,9 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
                 Some(write_set),
                 Some(events),
             ) {
+                if let Some(execution_fingerprint) = txn_out.execution_fingerprint() {
+                    self.record_execution_divergence(version, &[execution_fingerprint], &err);
+                }
                 if verify_execution_mode.is_lazy_quit() {
                     error!("(Not quitting right away.) {}", err);
                     verify_execution_mode.mark_seen_error();
@@ -6,7 +6,9 @@
 
 use crate::{components::apply_chunk_output::ApplyChunkOutput, metrics};
 use anyhow::Result;
+use aptos_block_partitioner::types::SubBlock;
 use aptos_crypto::HashValue;
+use aptos_executor_service::{get_remote_shards, RemoteExecutorClient, DEFAULT_NETWORK_TIMEOUT_MS};
 use aptos_executor_types::{ExecutableTransactions, ExecutedBlock, ExecutedChunk};
 use aptos_infallible::Mutex;
 use aptos_logger::{sample, sample::SampleRate, trace, warn};
@@ -26,10 +28,16 @@ use std::{ops::Deref, sync::Arc, time::Duration};
 
 pub static SHARDED_BLOCK_EXECUTOR: Lazy<Arc<Mutex<ShardedBlockExecutor<CachedStateView>>>> =
     Lazy::new(|| {
-        Arc::new(Mutex::new(ShardedBlockExecutor::new(
-            AptosVM::get_num_shards(),
-            None, // Defaults to num_cpus / num_shards
-        )))
+        let sharded_block_executor = match get_remote_shards() {
+            Some(remote_shards) => ShardedBlockExecutor::with_executor_client(Box::new(
+                RemoteExecutorClient::new(remote_shards, DEFAULT_NETWORK_TIMEOUT_MS),
+            )),
+            None => ShardedBlockExecutor::new(
+                AptosVM::get_num_shards(),
+                None, // Defaults to num_cpus / num_shards
+            ),
+        };
+        Arc::new(Mutex::new(sharded_block_executor))
     });
 
 pub struct ChunkOutput {
@@ -49,6 +57,8 @@ impl ChunkOutput {
         state_view: CachedStateView,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Self> {
+        // Only the blocks partitioned by consensus are executed on the executor shards, the
+        // chunks replayed by state sync must be executed in the order they were committed in.
         match transactions {
             ExecutableTransactions::Unsharded(txns) => {
                 Self::by_transaction_execution_unsharded::<V>(
@@ -57,10 +67,12 @@ impl ChunkOutput {
                     maybe_block_gas_limit,
                 )
             },
-            ExecutableTransactions::Sharded(_) => {
-                // TODO(skedia): Change this into sharded once we move partitioner out of the
-                // sharded block executor.
-                todo!("sharded execution integration is not yet done")
+            ExecutableTransactions::Sharded(sub_blocks) => {
+                Self::by_transaction_execution_sharded::<V>(
+                    sub_blocks,
+                    state_view,
+                    maybe_block_gas_limit,
+                )
             },
         }
     }
@@ -85,14 +97,26 @@ impl ChunkOutput {
         })
     }
 
+    /// Executes the sub-blocks of a partitioned block on the executor shards. The transactions
+    /// are committed in the order of the sub-blocks, which may differ from the order they were
+    /// proposed in.
     pub fn by_transaction_execution_sharded<V: VMExecutor>(
-        transactions: Vec<Transaction>,
+        sub_blocks: Vec<SubBlock>,
         state_view: CachedStateView,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Self> {
+        let transactions: Vec<Transaction> = sub_blocks
+            .iter()
+            .flat_map(|sub_block| {
+                sub_block
+                    .transactions_with_deps()
+                    .iter()
+                    .map(|txn| txn.txn.transaction().clone())
+            })
+            .collect();
         let state_view_arc = Arc::new(state_view);
         let transaction_outputs = Self::execute_block_sharded::<V>(
-            transactions.clone(),
+            sub_blocks,
             state_view_arc.clone(),
             maybe_block_gas_limit,
         )?;
@@ -173,13 +197,13 @@ impl ChunkOutput {
     }
 
     fn execute_block_sharded<V: VMExecutor>(
-        transactions: Vec<Transaction>,
+        sub_blocks: Vec<SubBlock>,
         state_view: Arc<CachedStateView>,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>> {
         Ok(V::execute_block_sharded(
             SHARDED_BLOCK_EXECUTOR.lock().deref(),
-            transactions,
+            sub_blocks,
             state_view,
             maybe_block_gas_limit,
         )?)
@@ -7,6 +7,7 @@ use crate::{
     components::chunk_output::ChunkOutput,
 };
 use anyhow::Result;
+use aptos_block_partitioner::types::SubBlock;
 use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_executor_types::{BlockExecutorTrait, ExecutableTransactions};
 use aptos_state_view::StateView;
@@ -67,7 +68,7 @@ impl TransactionBlockExecutor for FakeVM {
 impl VMExecutor for FakeVM {
     fn execute_block_sharded<S: StateView + Send + Sync>(
         _sharded_block_executor: &ShardedBlockExecutor<S>,
-        _transactions: Vec<Transaction>,
+        _sub_blocks: Vec<SubBlock>,
         _state_view: Arc<S>,
         _maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
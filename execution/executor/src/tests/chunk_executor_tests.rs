@@ -20,6 +20,7 @@ use aptos_types::{
     test_helpers::transaction_test_helpers::{block, BLOCK_GAS_LIMIT},
     transaction::{TransactionListWithProof, TransactionOutputListWithProof},
 };
+use aptos_vm::AptosVM;
 use rand::Rng;
 
 pub struct TestExecutor {
@@ -97,6 +98,27 @@ fn execute_and_commit_chunk(
     assert_eq!(li, ledger_info);
 }
 
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_execute_and_commit_chunk_with_executor_shards() {
+    // The chunks are replayed in the order they were committed in, whatever the number of executor
+    // shards, so they match the proven transaction infos
+    AptosVM::set_num_shards_once(4);
+    let (chunks, ledger_info) = tests::create_transaction_chunks(vec![1..21, 21..51, 51..71]);
+    let TestExecutor {
+        _path,
+        db,
+        executor,
+    } = TestExecutor::new();
+
+    for chunk in chunks {
+        executor.execute_chunk(chunk, &ledger_info, None).unwrap();
+        executor.commit_chunk().unwrap();
+    }
+    let li = db.reader.get_latest_ledger_info().unwrap();
+    assert_eq!(li, ledger_info);
+}
+
 #[test]
 #[cfg_attr(feature = "consensus-only-perf-test", ignore)]
 fn test_executor_execute_or_apply_and_commit_chunk() {
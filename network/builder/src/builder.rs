@@ -12,10 +12,10 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE,
-        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
-        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
-        NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, EgressQuotaConfig, NetworkConfig, NetworkRecorderConfig, Peer, PeerRole,
+        PeerSet, RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
+        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -25,6 +25,7 @@ use aptos_logger::prelude::*;
 use aptos_netcore::transport::tcp::TCPBufferCfg;
 use aptos_network::{
     application::storage::PeersAndMetadata,
+    bandwidth::EgressQuotas,
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     constants::MAX_MESSAGE_SIZE,
     logging::NetworkSchema,
@@ -39,6 +40,7 @@ use aptos_network::{
             NewNetworkSender,
         },
     },
+    recorder::NetworkRecorder,
 };
 use aptos_network_discovery::DiscoveryChangeListener;
 use aptos_time_service::TimeService;
@@ -203,6 +205,16 @@ impl NetworkBuilder {
             ),
         );
 
+        if config.enable_quic_transport {
+            network_builder.enable_quic_transport();
+        }
+
+        if let Some(network_recorder_config) = &config.network_recorder_config {
+            network_builder.add_network_recorder(network_recorder_config);
+        }
+
+        network_builder.add_egress_quotas(&config.egress_quota_config);
+
         network_builder.add_connection_monitoring(
             config.ping_interval_ms,
             config.ping_timeout_ms,
@@ -320,6 +332,50 @@ impl NetworkBuilder {
         self.peer_manager_builder.listen_address()
     }
 
+    /// Enable the QUIC transport (in addition to TCP) for the network.
+    ///
+    /// Peers advertising `/quic/<port>` addresses are dialed over QUIC, and
+    /// QUIC connections are accepted on the same port as the TCP listener.
+    pub fn enable_quic_transport(&mut self) -> &mut Self {
+        self.peer_manager_builder.enable_quic_transport();
+        self
+    }
+
+    /// Add a `network::recorder::NetworkRecorder` to the network.
+    ///
+    /// The recorder writes all inbound and outbound peer messages to a set of
+    /// rotating local files, so that incidents can be replayed offline.
+    pub fn add_network_recorder(
+        &mut self,
+        network_recorder_config: &NetworkRecorderConfig,
+    ) -> &mut Self {
+        let network_recorder = NetworkRecorder::new(
+            network_recorder_config,
+            self.network_context.network_id(),
+            self.time_service.clone(),
+        )
+        .expect("Failed to create the network recorder!");
+        self.peer_manager_builder
+            .set_network_recorder(Arc::new(network_recorder));
+        self
+    }
+
+    /// Add the `network::bandwidth::EgressQuotas` to the network.
+    ///
+    /// The quotas account for the egress bytes of every protocol and peer class,
+    /// and limit the outbound traffic of the configured protocols.
+    pub fn add_egress_quotas(&mut self, egress_quota_config: &EgressQuotaConfig) -> &mut Self {
+        let network_id = self.network_context.network_id();
+        let egress_quotas = Arc::new(
+            EgressQuotas::new(egress_quota_config, network_id, self.time_service.clone())
+                .expect("Failed to create the egress quotas!"),
+        );
+        self.peers_and_metadata
+            .set_egress_quotas(network_id, egress_quotas.clone());
+        self.peer_manager_builder.set_egress_quotas(egress_quotas);
+        self
+    }
+
     /// Add a `network::connectivity_manager::ConnectivityManager` to the network.
     ///
     /// `network::connectivity_manager::ConnectivityManager` is responsible for ensuring that we are connected
@@ -0,0 +1,394 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! QUIC Transport
+//!
+//! Connections are established using [`quinn`] with an ephemeral, self-signed
+//! TLS certificate. Note: the TLS layer only provides encryption and independent,
+//! congestion controlled streams. Peers are *not* authenticated by their
+//! certificates, so upper layers are responsible for authenticating the connection
+//! (e.g., by running a handshake over the control stream and then verifying the
+//! [channel binding](QuicConnection::export_channel_binding)).
+
+use crate::transport::Transport;
+use aptos_types::{
+    network_address::{parse_dns_quic, parse_ip_quic, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    io::{AsyncRead, AsyncWrite},
+    ready,
+    stream::{self, BoxStream, StreamExt},
+};
+use std::{
+    error::Error,
+    fmt, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tokio::{io::ReadBuf, net::lookup_host};
+
+/// The ALPN protocol identifier for connections established by this transport
+const ALPN_PROTOCOL: &[u8] = b"aptosnet";
+/// The server name used by all connections (certificates are never verified by name)
+const SERVER_NAME: &str = "aptosnet";
+/// The label used to export the keying material for channel binding
+const CHANNEL_BINDING_LABEL: &[u8] = b"EXPORTER-aptosnet-quic-channel-binding";
+/// The size (in bytes) of the exported channel binding
+pub const CHANNEL_BINDING_SIZE: usize = 32;
+
+/// The interval at which keep-alive packets are sent on idle connections
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+/// The duration after which an unresponsive connection is closed
+const MAX_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Transport to build QUIC connections
+#[derive(Clone)]
+pub struct QuicTransport {
+    server_config: quinn::ServerConfig,
+    client_config: quinn::ClientConfig,
+    client_endpoints: Arc<Mutex<ClientEndpoints>>,
+}
+
+/// The UDP endpoints that all outbound connections of a transport are multiplexed
+/// over (one per IP version, created on the first dial).
+#[derive(Default)]
+struct ClientEndpoints {
+    ipv4: Option<quinn::Endpoint>,
+    ipv6: Option<quinn::Endpoint>,
+}
+
+impl ClientEndpoints {
+    /// Returns the endpoint for connecting to the given remote address
+    fn get_or_create(&mut self, remote_addr: SocketAddr) -> io::Result<quinn::Endpoint> {
+        let (endpoint, bind_addr) = if remote_addr.is_ipv4() {
+            (
+                &mut self.ipv4,
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            )
+        } else {
+            (
+                &mut self.ipv6,
+                SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+            )
+        };
+        if let Some(endpoint) = endpoint.as_ref() {
+            return Ok(endpoint.clone());
+        }
+
+        let new_endpoint = quinn::Endpoint::client(bind_addr)?;
+        *endpoint = Some(new_endpoint.clone());
+        Ok(new_endpoint)
+    }
+}
+
+impl QuicTransport {
+    /// Creates a new QUIC transport with a freshly generated (self-signed) certificate
+    pub fn new() -> io::Result<Self> {
+        let mut transport_config = quinn::TransportConfig::default();
+        transport_config
+            .keep_alive_interval(Some(KEEP_ALIVE_INTERVAL))
+            .max_idle_timeout(Some(MAX_IDLE_TIMEOUT.try_into().map_err(to_io_error)?));
+        let transport_config = Arc::new(transport_config);
+
+        // Create the server config using a self-signed certificate
+        let certificate =
+            rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()]).map_err(to_io_error)?;
+        let certificate_chain = vec![rustls::Certificate(
+            certificate.serialize_der().map_err(to_io_error)?,
+        )];
+        let private_key = rustls::PrivateKey(certificate.serialize_private_key_der());
+        let mut server_crypto = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certificate_chain, private_key)
+            .map_err(to_io_error)?;
+        server_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport_config(transport_config.clone());
+
+        // Create the client config (the server certificate is authenticated by upper layers)
+        let mut client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+            .with_no_client_auth();
+        client_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        let mut client_config = quinn::ClientConfig::new(Arc::new(client_crypto));
+        client_config.transport_config(transport_config);
+
+        Ok(Self {
+            server_config,
+            client_config,
+            client_endpoints: Arc::new(Mutex::new(ClientEndpoints::default())),
+        })
+    }
+}
+
+impl fmt::Debug for QuicTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "QuicTransport")
+    }
+}
+
+impl Transport for QuicTransport {
+    type Error = io::Error;
+    type Inbound = BoxFuture<'static, io::Result<QuicConnection>>;
+    type Listener = BoxStream<'static, io::Result<(Self::Inbound, NetworkAddress)>>;
+    type Outbound = BoxFuture<'static, io::Result<QuicConnection>>;
+    type Output = QuicConnection;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        let ((ipaddr, port), addr_suffix) =
+            parse_ip_quic(addr.as_slice()).ok_or_else(|| invalid_addr_error(&addr))?;
+        if !addr_suffix.is_empty() {
+            return Err(invalid_addr_error(&addr));
+        }
+
+        let endpoint =
+            quinn::Endpoint::server(self.server_config.clone(), SocketAddr::new(ipaddr, port))?;
+        let listen_addr = quic_network_address(endpoint.local_addr()?);
+
+        // Each incoming connection is accepted (i.e., the QUIC handshake is
+        // completed) in its own inbound future, so it doesn't block the listener.
+        let listener = stream::unfold(endpoint, |endpoint| async move {
+            let connecting = endpoint.accept().await?;
+            let dialer_addr = quic_network_address(connecting.remote_address());
+            let inbound = accept_connection(connecting).boxed();
+            Some((Ok((inbound, dialer_addr)), endpoint))
+        })
+        .boxed();
+
+        Ok((listener, listen_addr))
+    }
+
+    fn dial(&self, _peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        let protos = addr.as_slice();
+
+        // ensure addr is well formed to save some work before potentially
+        // spawning a dial task that will fail anyway.
+        parse_ip_quic(protos)
+            .map(|_| ())
+            .or_else(|| parse_dns_quic(protos).map(|_| ()))
+            .ok_or_else(|| invalid_addr_error(&addr))?;
+
+        Ok(resolve_and_connect(
+            addr,
+            self.client_config.clone(),
+            self.client_endpoints.clone(),
+        )
+        .boxed())
+    }
+}
+
+/// An established QUIC connection and its bidirectional control stream (which
+/// is opened by the dialer).
+#[derive(Debug)]
+pub struct QuicConnection {
+    connection: quinn::Connection,
+    control_stream: QuicStream,
+}
+
+impl QuicConnection {
+    pub fn new(connection: quinn::Connection, control_stream: QuicStream) -> Self {
+        Self {
+            connection,
+            control_stream,
+        }
+    }
+
+    /// Exports keying material that is unique to the TLS session of this
+    /// connection. Both ends of the connection export the same value, so upper
+    /// layers can exchange it over an authenticated channel to verify that the
+    /// (unauthenticated) TLS session hasn't been intercepted.
+    pub fn export_channel_binding(&self) -> io::Result<[u8; CHANNEL_BINDING_SIZE]> {
+        let mut channel_binding = [0u8; CHANNEL_BINDING_SIZE];
+        self.connection
+            .export_keying_material(&mut channel_binding, CHANNEL_BINDING_LABEL, &[])
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    "Failed to export the keying material of the QUIC connection",
+                )
+            })?;
+        Ok(channel_binding)
+    }
+
+    pub fn into_parts(self) -> (quinn::Connection, QuicStream) {
+        (self.connection, self.control_stream)
+    }
+}
+
+/// A bidirectional QUIC stream
+#[derive(Debug)]
+pub struct QuicStream {
+    send_stream: quinn::SendStream,
+    recv_stream: quinn::RecvStream,
+}
+
+impl QuicStream {
+    pub fn new(send_stream: quinn::SendStream, recv_stream: quinn::RecvStream) -> Self {
+        Self {
+            send_stream,
+            recv_stream,
+        }
+    }
+
+    pub fn into_parts(self) -> (quinn::SendStream, quinn::RecvStream) {
+        (self.send_stream, self.recv_stream)
+    }
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut read_buf = ReadBuf::new(buf);
+        ready!(tokio::io::AsyncRead::poll_read(
+            Pin::new(&mut self.recv_stream),
+            context,
+            &mut read_buf
+        ))?;
+        Poll::Ready(Ok(read_buf.filled().len()))
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.send_stream), context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.send_stream), context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.send_stream), context)
+    }
+}
+
+/// Completes the QUIC handshake for an incoming connection and accepts the control stream
+async fn accept_connection(connecting: quinn::Connecting) -> io::Result<QuicConnection> {
+    let connection = connecting.await.map_err(to_io_error)?;
+    let (send_stream, recv_stream) = connection.accept_bi().await.map_err(to_io_error)?;
+    Ok(QuicConnection::new(
+        connection,
+        QuicStream::new(send_stream, recv_stream),
+    ))
+}
+
+/// Note: we need to take ownership of this `NetworkAddress` (instead of just
+/// borrowing the `&[Protocol]` slice) so this future can be `Send + 'static`.
+async fn resolve_and_connect(
+    addr: NetworkAddress,
+    client_config: quinn::ClientConfig,
+    client_endpoints: Arc<Mutex<ClientEndpoints>>,
+) -> io::Result<QuicConnection> {
+    let protos = addr.as_slice();
+
+    let socket_addrs: Vec<SocketAddr> =
+        if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_quic(protos) {
+            // this is an /ip4 or /ip6 address, so we can just connect without any
+            // extra resolving or filtering.
+            vec![SocketAddr::new(ipaddr, port)]
+        } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_quic(protos) {
+            // resolve dns name and filter
+            lookup_host((dns_name.as_ref(), port))
+                .await?
+                .filter(|socketaddr| ip_filter.matches(socketaddr.ip()))
+                .collect()
+        } else {
+            return Err(invalid_addr_error(&addr));
+        };
+
+    // try to connect until the first succeeds
+    let mut last_err = None;
+    for socketaddr in socket_addrs {
+        match connect(socketaddr, client_config.clone(), &client_endpoints).await {
+            Ok(connection) => return Ok(connection),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("could not resolve dns name to any address: {}", addr),
+        )
+    }))
+}
+
+/// Connects to the given remote address and opens the control stream
+async fn connect(
+    remote_addr: SocketAddr,
+    client_config: quinn::ClientConfig,
+    client_endpoints: &Mutex<ClientEndpoints>,
+) -> io::Result<QuicConnection> {
+    // All outbound connections share the (ephemeral) UDP socket of the transport
+    let endpoint = client_endpoints
+        .lock()
+        .expect("The client endpoints lock should not be poisoned!")
+        .get_or_create(remote_addr)?;
+
+    let connection = endpoint
+        .connect_with(client_config, remote_addr, SERVER_NAME)
+        .map_err(to_io_error)?
+        .await
+        .map_err(to_io_error)?;
+    let (send_stream, recv_stream) = connection.open_bi().await.map_err(to_io_error)?;
+    Ok(QuicConnection::new(
+        connection,
+        QuicStream::new(send_stream, recv_stream),
+    ))
+}
+
+/// Returns the QUIC network address (i.e., `/ip4/<addr>/quic/<port>`) for the socket address
+fn quic_network_address(socketaddr: SocketAddr) -> NetworkAddress {
+    NetworkAddress::from_protocols(vec![
+        Protocol::from(socketaddr.ip()),
+        Protocol::Quic(socketaddr.port()),
+    ])
+    .expect("An ip followed by a transport protocol is always a valid network address")
+}
+
+fn invalid_addr_error(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+fn to_io_error<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+/// A certificate verifier that accepts all server certificates. This is safe
+/// because peers are authenticated by upper layers (and the TLS session is
+/// bound to that authentication, see [`QuicConnection::export_channel_binding`]).
+struct SkipServerVerification;
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
         error::Error,
         metadata::{ConnectionState, PeerMetadata},
     },
+    bandwidth::EgressQuotas,
     transport::{ConnectionId, ConnectionMetadata},
     ProtocolId,
 };
@@ -30,6 +31,7 @@ use std::{
 pub struct PeersAndMetadata {
     peers_and_metadata: HashMap<NetworkId, RwLock<HashMap<PeerId, PeerMetadata>>>,
     trusted_peers: HashMap<NetworkId, Arc<RwLock<PeerSet>>>,
+    egress_quotas: RwLock<HashMap<NetworkId, Arc<EgressQuotas>>>,
 }
 
 impl PeersAndMetadata {
@@ -38,6 +40,7 @@ impl PeersAndMetadata {
         let mut peers_and_metadata = PeersAndMetadata {
             peers_and_metadata: HashMap::new(),
             trusted_peers: HashMap::new(),
+            egress_quotas: RwLock::new(HashMap::new()),
         };
 
         // Initialize each network mapping and trusted peer set
@@ -140,6 +143,16 @@ impl PeersAndMetadata {
         })
     }
 
+    /// Returns the egress quotas (and bandwidth accounting) for the given network ID
+    pub fn get_egress_quotas(&self, network_id: &NetworkId) -> Option<Arc<EgressQuotas>> {
+        self.egress_quotas.read().get(network_id).cloned()
+    }
+
+    /// Sets the egress quotas for the given network ID (so they can be inspected)
+    pub fn set_egress_quotas(&self, network_id: NetworkId, egress_quotas: Arc<EgressQuotas>) {
+        self.egress_quotas.write().insert(network_id, egress_quotas);
+    }
+
     /// Updates the connection metadata associated with the given peer.
     /// If no peer metadata exists, a new one is created.
     pub fn insert_connection_metadata(
@@ -0,0 +1,489 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bandwidth accounting and per-protocol egress quotas.
+//!
+//! The [`EgressQuotas`] of a network account for the egress bytes of every protocol
+//! and class of remote peer (see [`PeerClass`]), and enforce the token bucket quotas
+//! configured in the [`EgressQuotaConfig`]. Each quota is shared by all peers of the
+//! class, e.g., to cap how much state sync data is served to public fullnodes
+//! regardless of how many of them are connected.
+//!
+//! Each [`Peer`] actor uses a [`PeerEgressQueue`] to decide whether an outbound
+//! message can be sent immediately. Messages that exceed their quota are held back
+//! in a per-protocol queue (instead of the writer queue), so that they never delay
+//! the traffic of other protocols. Consensus traffic is always prioritized: it can
+//! never be limited, so it's never held back.
+//!
+//! Note: only direct send messages and rpc responses are subject to the quotas
+//! (outbound rpc requests are only accounted for).
+//!
+//! [`Peer`]: crate::peer::Peer
+
+use crate::{
+    counters::{self, DROPPED_LABEL, SENT_LABEL, THROTTLED_LABEL},
+    protocols::wire::messaging::v1::NetworkMessage,
+    ProtocolId,
+};
+use anyhow::{anyhow, Result};
+use aptos_config::{
+    config::{EgressQuotaConfig, PeerClass, PeerRole},
+    network_id::NetworkId,
+};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::{Bucket, SharedBucket};
+use aptos_time_service::{Sleep, TimeService, TimeServiceTrait};
+use futures::{
+    future::Future,
+    ready,
+    stream::{FusedStream, Stream},
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The protocols that are always prioritized (and can never be limited)
+const PRIORITIZED_PROTOCOLS: &[ProtocolId] = &[
+    ProtocolId::ConsensusRpcBcs,
+    ProtocolId::ConsensusDirectSendBcs,
+    ProtocolId::ConsensusDirectSendJson,
+    ProtocolId::ConsensusRpcJson,
+    ProtocolId::ConsensusRpcCompressed,
+    ProtocolId::ConsensusDirectSendCompressed,
+];
+
+/// A single egress quota (and the token bucket enforcing it)
+#[derive(Debug)]
+struct Quota {
+    bytes_per_second: usize,
+    burst_size_bytes: usize,
+    bucket: SharedBucket,
+}
+
+/// The live egress counters of a single protocol and peer class
+#[derive(Debug, Default)]
+struct EgressStats {
+    sent_bytes: AtomicU64,
+    throttled_bytes: AtomicU64,
+    dropped_bytes: AtomicU64,
+    queued_bytes: AtomicU64,
+}
+
+/// A snapshot of the egress counters (and quota) of a single protocol and peer class
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct EgressSummary {
+    pub protocol_id: ProtocolId,
+    pub peer_class: PeerClass,
+    /// The sustained rate of the quota (if one is configured)
+    pub bytes_per_second: Option<usize>,
+    /// The maximum burst of the quota (if one is configured)
+    pub burst_size_bytes: Option<usize>,
+    /// The total number of bytes sent (including those that were held back)
+    pub sent_bytes: u64,
+    /// The total number of bytes held back by the quota
+    pub throttled_bytes: u64,
+    /// The total number of bytes dropped because too many bytes were held back
+    pub dropped_bytes: u64,
+    /// The number of bytes currently held back by the quota
+    pub queued_bytes: u64,
+}
+
+/// The egress quotas and bandwidth accounting of a single network
+#[derive(Debug)]
+pub struct EgressQuotas {
+    network_id: NetworkId,
+    quotas: HashMap<(ProtocolId, PeerClass), Quota>,
+    stats: HashMap<(ProtocolId, PeerClass), EgressStats>,
+    max_throttled_bytes_per_peer: usize,
+}
+
+impl EgressQuotas {
+    /// Creates the quotas of the network. The token buckets are refilled with the given time
+    /// service (which must be the one of the peers).
+    pub fn new(
+        config: &EgressQuotaConfig,
+        network_id: NetworkId,
+        time_service: TimeService,
+    ) -> Result<Self> {
+        let mut quotas = HashMap::new();
+        for quota in &config.quotas {
+            let protocol_id = ProtocolId::all()
+                .iter()
+                .find(|protocol_id| protocol_id.as_str() == quota.protocol_id)
+                .copied()
+                .ok_or_else(|| anyhow!("Unknown egress quota protocol: {}", quota.protocol_id))?;
+            if PRIORITIZED_PROTOCOLS.contains(&protocol_id) {
+                return Err(anyhow!(
+                    "Consensus traffic cannot be limited! Found an egress quota for: {}",
+                    protocol_id
+                ));
+            }
+            if quota.bytes_per_second == 0 || quota.burst_size_bytes < quota.bytes_per_second {
+                return Err(anyhow!(
+                    "Invalid egress quota for {} ({}): the rate must be non-zero and \
+                     the burst size must be at least the rate",
+                    protocol_id,
+                    quota.peer_class
+                ));
+            }
+
+            // Create the (initially full) token bucket for the quota
+            let bucket = Bucket::new(
+                "egress_quota".into(),
+                network_id.to_string(),
+                format!("{}:{}", protocol_id, quota.peer_class),
+                quota.burst_size_bytes,
+                quota.burst_size_bytes,
+                quota.bytes_per_second,
+                None,
+            )
+            .with_last_refresh_time(time_service.now());
+            let quota_key = (protocol_id, quota.peer_class);
+            let quota = Quota {
+                bytes_per_second: quota.bytes_per_second,
+                burst_size_bytes: quota.burst_size_bytes,
+                bucket: Arc::new(Mutex::new(bucket)),
+            };
+            if quotas.insert(quota_key, quota).is_some() {
+                return Err(anyhow!(
+                    "Found duplicate egress quotas for {} ({})",
+                    quota_key.0,
+                    quota_key.1
+                ));
+            }
+        }
+
+        // Account for the traffic of all protocols and peer classes
+        let mut stats = HashMap::new();
+        for protocol_id in ProtocolId::all() {
+            for peer_class in PeerClass::all() {
+                stats.insert((*protocol_id, *peer_class), EgressStats::default());
+            }
+        }
+
+        Ok(Self {
+            network_id,
+            quotas,
+            stats,
+            max_throttled_bytes_per_peer: config.max_throttled_bytes_per_peer,
+        })
+    }
+
+    /// Returns the egress summaries of all protocols and peer classes that have
+    /// a quota or have sent any traffic.
+    pub fn get_summaries(&self) -> Vec<EgressSummary> {
+        let mut summaries = vec![];
+        for protocol_id in ProtocolId::all() {
+            for peer_class in PeerClass::all() {
+                let key = (*protocol_id, *peer_class);
+                let quota = self.quotas.get(&key);
+                let stats = self.get_stats(*protocol_id, *peer_class);
+                let sent_bytes = stats.sent_bytes.load(Ordering::Relaxed);
+                if quota.is_none() && sent_bytes == 0 {
+                    continue;
+                }
+
+                summaries.push(EgressSummary {
+                    protocol_id: *protocol_id,
+                    peer_class: *peer_class,
+                    bytes_per_second: quota.map(|quota| quota.bytes_per_second),
+                    burst_size_bytes: quota.map(|quota| quota.burst_size_bytes),
+                    sent_bytes,
+                    throttled_bytes: stats.throttled_bytes.load(Ordering::Relaxed),
+                    dropped_bytes: stats.dropped_bytes.load(Ordering::Relaxed),
+                    queued_bytes: stats.queued_bytes.load(Ordering::Relaxed),
+                });
+            }
+        }
+        summaries
+    }
+
+    fn get_bucket(&self, protocol_id: ProtocolId, peer_class: PeerClass) -> Option<&SharedBucket> {
+        self.quotas
+            .get(&(protocol_id, peer_class))
+            .map(|quota| &quota.bucket)
+    }
+
+    fn get_stats(&self, protocol_id: ProtocolId, peer_class: PeerClass) -> &EgressStats {
+        self.stats
+            .get(&(protocol_id, peer_class))
+            .expect("The egress stats should exist for all protocols and peer classes!")
+    }
+
+    /// Records the bytes sent (or released after being held back)
+    fn record_sent(&self, protocol_id: ProtocolId, peer_class: PeerClass, num_bytes: usize) {
+        self.get_stats(protocol_id, peer_class)
+            .sent_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        counters::network_egress_bytes(self.network_id, protocol_id, peer_class, SENT_LABEL)
+            .inc_by(num_bytes as u64);
+    }
+
+    /// Records the bytes held back by the quota
+    fn record_throttled(&self, protocol_id: ProtocolId, peer_class: PeerClass, num_bytes: usize) {
+        let stats = self.get_stats(protocol_id, peer_class);
+        stats
+            .throttled_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        stats
+            .queued_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        counters::network_egress_bytes(self.network_id, protocol_id, peer_class, THROTTLED_LABEL)
+            .inc_by(num_bytes as u64);
+    }
+
+    /// Records the bytes released after being held back by the quota
+    fn record_released(&self, protocol_id: ProtocolId, peer_class: PeerClass, num_bytes: usize) {
+        self.get_stats(protocol_id, peer_class)
+            .queued_bytes
+            .fetch_sub(num_bytes as u64, Ordering::Relaxed);
+        self.record_sent(protocol_id, peer_class, num_bytes);
+    }
+
+    /// Records the bytes dropped because too many bytes were held back
+    fn record_dropped(&self, protocol_id: ProtocolId, peer_class: PeerClass, num_bytes: usize) {
+        self.get_stats(protocol_id, peer_class)
+            .dropped_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        counters::network_egress_bytes(self.network_id, protocol_id, peer_class, DROPPED_LABEL)
+            .inc_by(num_bytes as u64);
+    }
+}
+
+/// The outcome of an outbound message processed by the [`PeerEgressQueue`]
+#[derive(Debug, PartialEq)]
+pub enum EgressDecision {
+    /// The message can be sent immediately
+    Send(NetworkMessage),
+    /// The message was held back (it will be returned by the queue once released)
+    Throttled,
+    /// The message was dropped (too many bytes are already held back)
+    Dropped,
+}
+
+/// An outbound message held back by its egress quota
+#[derive(Debug)]
+struct ThrottledMessage {
+    message: NetworkMessage,
+    num_bytes: usize,
+    num_acquired_bytes: usize,
+}
+
+/// Applies the egress quotas to the outbound messages of a single peer. Held back
+/// messages are returned by the queue (as a `Stream`) once their quota allows it.
+pub struct PeerEgressQueue {
+    egress_quotas: Option<Arc<EgressQuotas>>,
+    peer_class: PeerClass,
+    /// The held back messages of each protocol (in the order they were sent)
+    throttled_messages: HashMap<ProtocolId, VecDeque<ThrottledMessage>>,
+    num_throttled_bytes: usize,
+    /// The delay until the quotas of the held back messages are refilled
+    refill_delay: Option<Pin<Box<Sleep>>>,
+    time_service: TimeService,
+}
+
+impl PeerEgressQueue {
+    pub fn new(
+        egress_quotas: Option<Arc<EgressQuotas>>,
+        peer_role: PeerRole,
+        time_service: TimeService,
+    ) -> Self {
+        Self {
+            egress_quotas,
+            peer_class: PeerClass::from_role(peer_role),
+            throttled_messages: HashMap::new(),
+            num_throttled_bytes: 0,
+            refill_delay: None,
+            time_service,
+        }
+    }
+
+    /// Returns the number of bytes currently held back by the quotas
+    pub fn num_throttled_bytes(&self) -> usize {
+        self.num_throttled_bytes
+    }
+
+    /// Accounts for an outbound message that isn't subject to the quotas
+    /// (e.g., an outbound rpc request).
+    pub fn record_unthrottled_message(&self, protocol_id: ProtocolId, num_bytes: usize) {
+        if let Some(egress_quotas) = &self.egress_quotas {
+            egress_quotas.record_sent(protocol_id, self.peer_class, num_bytes);
+        }
+    }
+
+    /// Decides whether the given outbound message can be sent immediately
+    pub fn process_message(
+        &mut self,
+        protocol_id: ProtocolId,
+        message: NetworkMessage,
+    ) -> EgressDecision {
+        let egress_quotas = match &self.egress_quotas {
+            Some(egress_quotas) => egress_quotas,
+            None => return EgressDecision::Send(message),
+        };
+        let peer_class = self.peer_class;
+        let num_bytes = get_payload_size(&message);
+
+        // Messages without a quota are always sent immediately
+        let bucket = match egress_quotas.get_bucket(protocol_id, peer_class) {
+            Some(bucket) => bucket,
+            None => {
+                egress_quotas.record_sent(protocol_id, peer_class, num_bytes);
+                return EgressDecision::Send(message);
+            },
+        };
+
+        // Send the message immediately if the quota allows it (and no other
+        // messages of the protocol are being held back, to preserve their order).
+        let mut num_acquired_bytes = 0;
+        if !self.throttled_messages.contains_key(&protocol_id) {
+            let now = self.time_service.now();
+            if let Ok(num_allowed_bytes) = bucket.lock().acquire_tokens_at(num_bytes, now) {
+                if num_allowed_bytes == num_bytes {
+                    egress_quotas.record_sent(protocol_id, peer_class, num_bytes);
+                    return EgressDecision::Send(message);
+                }
+                num_acquired_bytes = num_allowed_bytes;
+            }
+        }
+
+        // Drop the message if too many bytes are already held back
+        if self.num_throttled_bytes + num_bytes > egress_quotas.max_throttled_bytes_per_peer {
+            bucket.lock().return_tokens(num_acquired_bytes);
+            egress_quotas.record_dropped(protocol_id, peer_class, num_bytes);
+            return EgressDecision::Dropped;
+        }
+
+        // Otherwise, hold back the message
+        egress_quotas.record_throttled(protocol_id, peer_class, num_bytes);
+        self.num_throttled_bytes += num_bytes;
+        self.throttled_messages
+            .entry(protocol_id)
+            .or_default()
+            .push_back(ThrottledMessage {
+                message,
+                num_bytes,
+                num_acquired_bytes,
+            });
+        EgressDecision::Throttled
+    }
+
+    /// Acquires the quota for the first held back message of each protocol, and
+    /// returns the first message that can be released (if any). Otherwise, returns
+    /// the earliest time at which the quotas will be refilled.
+    fn try_release_message(&mut self) -> Result<NetworkMessage, Instant> {
+        let egress_quotas = self
+            .egress_quotas
+            .as_ref()
+            .expect("Messages are only held back if egress quotas exist!");
+        let peer_class = self.peer_class;
+
+        let now = self.time_service.now();
+        let mut next_refill_time = None;
+        let protocol_ids: Vec<_> = self.throttled_messages.keys().copied().collect();
+        for protocol_id in protocol_ids {
+            let bucket = egress_quotas
+                .get_bucket(protocol_id, peer_class)
+                .expect("Messages are only held back if a quota exists!");
+            let messages = self
+                .throttled_messages
+                .get_mut(&protocol_id)
+                .expect("The held back messages should exist!");
+            let throttled_message = messages
+                .front_mut()
+                .expect("Empty queues of held back messages should be removed!");
+
+            // Acquire as many of the remaining bytes as possible
+            let num_remaining_bytes =
+                throttled_message.num_bytes - throttled_message.num_acquired_bytes;
+            let mut bucket = bucket.lock();
+            match bucket.acquire_tokens_at(num_remaining_bytes, now) {
+                Ok(num_allowed_bytes) => throttled_message.num_acquired_bytes += num_allowed_bytes,
+                Err(refill_time) => {
+                    next_refill_time = Some(
+                        next_refill_time
+                            .map_or(refill_time, |time| std::cmp::min(time, refill_time)),
+                    );
+                    continue;
+                },
+            }
+
+            // Release the message once its quota has been fully acquired
+            if throttled_message.num_acquired_bytes < throttled_message.num_bytes {
+                let refill_time = bucket.time_of_next_refill();
+                next_refill_time = Some(
+                    next_refill_time.map_or(refill_time, |time| std::cmp::min(time, refill_time)),
+                );
+                continue;
+            }
+            let throttled_message = messages
+                .pop_front()
+                .expect("The held back message should exist!");
+            if messages.is_empty() {
+                self.throttled_messages.remove(&protocol_id);
+            }
+            self.num_throttled_bytes -= throttled_message.num_bytes;
+            egress_quotas.record_released(protocol_id, peer_class, throttled_message.num_bytes);
+            return Ok(throttled_message.message);
+        }
+
+        Err(next_refill_time.expect("At least one message is held back!"))
+    }
+}
+
+impl Stream for PeerEgressQueue {
+    type Item = NetworkMessage;
+
+    /// Note: this stream never terminates. If no messages are held back, it's
+    /// pending (without registering a waker), so it must be polled again after
+    /// new messages are processed.
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            // Wait until the quotas are refilled
+            if let Some(refill_delay) = this.refill_delay.as_mut() {
+                ready!(refill_delay.as_mut().poll(context));
+                this.refill_delay = None;
+            }
+
+            if this.throttled_messages.is_empty() {
+                return Poll::Pending;
+            }
+            match this.try_release_message() {
+                Ok(message) => return Poll::Ready(Some(message)),
+                Err(refill_time) => {
+                    let refill_delay =
+                        refill_time.saturating_duration_since(this.time_service.now());
+                    this.refill_delay = Some(Box::pin(this.time_service.sleep(refill_delay)));
+                },
+            }
+        }
+    }
+}
+
+impl FusedStream for PeerEgressQueue {
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+/// Returns the size of the application payload of the given message
+fn get_payload_size(message: &NetworkMessage) -> usize {
+    match message {
+        NetworkMessage::DirectSendMsg(message) => message.raw_msg.len(),
+        NetworkMessage::RpcRequest(request) => request.raw_request.len(),
+        NetworkMessage::RpcResponse(response) => response.raw_response.len(),
+        NetworkMessage::Error(_) => 0,
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bandwidth::{EgressDecision, EgressQuotas, EgressSummary, PeerEgressQueue},
+    protocols::wire::messaging::v1::{DirectSendMsg, NetworkMessage},
+    ProtocolId,
+};
+use aptos_config::{
+    config::{EgressQuota, EgressQuotaConfig, PeerClass, PeerRole},
+    network_id::NetworkId,
+};
+use aptos_time_service::TimeService;
+use futures::{FutureExt, StreamExt};
+use std::{sync::Arc, time::Duration};
+
+#[test]
+fn test_invalid_egress_quotas() {
+    // Verify that consensus traffic cannot be limited
+    let egress_quota_config =
+        create_egress_quota_config(ProtocolId::ConsensusRpcBcs.as_str(), 100, 100, 1000);
+    assert!(EgressQuotas::new(
+        &egress_quota_config,
+        NetworkId::Validator,
+        TimeService::mock()
+    )
+    .is_err());
+
+    // Verify that unknown protocols are rejected
+    let egress_quota_config = create_egress_quota_config("UnknownProtocol", 100, 100, 1000);
+    assert!(
+        EgressQuotas::new(&egress_quota_config, NetworkId::Public, TimeService::mock()).is_err()
+    );
+
+    // Verify that the burst size must be at least the rate
+    let egress_quota_config =
+        create_egress_quota_config(ProtocolId::StorageServiceRpc.as_str(), 100, 99, 1000);
+    assert!(
+        EgressQuotas::new(&egress_quota_config, NetworkId::Public, TimeService::mock()).is_err()
+    );
+
+    // Verify that duplicate quotas are rejected
+    let mut egress_quota_config =
+        create_egress_quota_config(ProtocolId::StorageServiceRpc.as_str(), 100, 100, 1000);
+    egress_quota_config
+        .quotas
+        .push(egress_quota_config.quotas[0].clone());
+    assert!(
+        EgressQuotas::new(&egress_quota_config, NetworkId::Public, TimeService::mock()).is_err()
+    );
+}
+
+#[test]
+fn test_send_without_quotas() {
+    // Create an egress queue without any quotas
+    let mut egress_queue = PeerEgressQueue::new(None, PeerRole::Unknown, TimeService::mock());
+
+    // Verify that all messages are sent immediately
+    for protocol_id in [ProtocolId::StorageServiceRpc, ProtocolId::MempoolDirectSend] {
+        let message = create_direct_send_message(protocol_id, 10_000);
+        assert_eq!(
+            egress_queue.process_message(protocol_id, message.clone()),
+            EgressDecision::Send(message)
+        );
+    }
+    assert_eq!(egress_queue.num_throttled_bytes(), 0);
+}
+
+#[tokio::test]
+async fn test_throttle_and_release() {
+    // Create an egress queue for a public fullnode (with a small quota)
+    let protocol_id = ProtocolId::StorageServiceRpc;
+    let egress_quota_config = create_egress_quota_config(protocol_id.as_str(), 100, 100, 1000);
+    let time_service = TimeService::mock();
+    let egress_quotas = Arc::new(
+        EgressQuotas::new(
+            &egress_quota_config,
+            NetworkId::Public,
+            time_service.clone(),
+        )
+        .unwrap(),
+    );
+    let mut egress_queue = PeerEgressQueue::new(
+        Some(egress_quotas.clone()),
+        PeerRole::Unknown,
+        time_service.clone(),
+    );
+
+    // Send a message that fits within the quota and verify it's sent immediately
+    let message_1 = create_direct_send_message(protocol_id, 80);
+    assert_eq!(
+        egress_queue.process_message(protocol_id, message_1.clone()),
+        EgressDecision::Send(message_1)
+    );
+
+    // Send two messages that exceed the quota and verify they're held back
+    let message_2 = create_direct_send_message(protocol_id, 50);
+    let message_3 = create_direct_send_message(protocol_id, 10);
+    for message in [message_2.clone(), message_3.clone()] {
+        assert_eq!(
+            egress_queue.process_message(protocol_id, message),
+            EgressDecision::Throttled
+        );
+    }
+    assert_eq!(egress_queue.num_throttled_bytes(), 60);
+
+    // Verify that messages without a quota are still sent immediately
+    let mempool_message = create_direct_send_message(ProtocolId::MempoolDirectSend, 500);
+    assert_eq!(
+        egress_queue.process_message(ProtocolId::MempoolDirectSend, mempool_message.clone()),
+        EgressDecision::Send(mempool_message)
+    );
+
+    // Verify that the held back messages are released in order (once the quota is refilled)
+    assert!(egress_queue.next().now_or_never().is_none());
+    time_service
+        .into_mock()
+        .advance_async(Duration::from_secs(1))
+        .await;
+    for expected_message in [message_2, message_3] {
+        assert_eq!(
+            egress_queue.next().now_or_never(),
+            Some(Some(expected_message))
+        );
+    }
+    assert_eq!(egress_queue.num_throttled_bytes(), 0);
+
+    // Verify the egress summaries
+    let summaries = egress_quotas.get_summaries();
+    assert_eq!(summaries.len(), 4); // One quota per peer class and the mempool traffic
+    assert!(summaries.contains(&EgressSummary {
+        protocol_id,
+        peer_class: PeerClass::PublicFullnode,
+        bytes_per_second: Some(100),
+        burst_size_bytes: Some(100),
+        sent_bytes: 140,
+        throttled_bytes: 60,
+        dropped_bytes: 0,
+        queued_bytes: 0,
+    }));
+    assert!(summaries.contains(&EgressSummary {
+        protocol_id: ProtocolId::MempoolDirectSend,
+        peer_class: PeerClass::PublicFullnode,
+        bytes_per_second: None,
+        burst_size_bytes: None,
+        sent_bytes: 500,
+        throttled_bytes: 0,
+        dropped_bytes: 0,
+        queued_bytes: 0,
+    }));
+}
+
+#[tokio::test]
+async fn test_release_with_time_service() {
+    // Create an egress queue for a public fullnode (with a small quota)
+    let protocol_id = ProtocolId::StorageServiceRpc;
+    let egress_quota_config = create_egress_quota_config(protocol_id.as_str(), 100, 100, 1000);
+    let time_service = TimeService::mock();
+    let egress_quotas = Arc::new(
+        EgressQuotas::new(
+            &egress_quota_config,
+            NetworkId::Public,
+            time_service.clone(),
+        )
+        .unwrap(),
+    );
+    let mut egress_queue =
+        PeerEgressQueue::new(Some(egress_quotas), PeerRole::Unknown, time_service.clone());
+
+    // Exhaust the quota and hold back a message
+    let message_1 = create_direct_send_message(protocol_id, 100);
+    assert_eq!(
+        egress_queue.process_message(protocol_id, message_1.clone()),
+        EgressDecision::Send(message_1)
+    );
+    let message_2 = create_direct_send_message(protocol_id, 50);
+    assert_eq!(
+        egress_queue.process_message(protocol_id, message_2.clone()),
+        EgressDecision::Throttled
+    );
+
+    // Verify that the message isn't released before the time service reaches the refill
+    // (the buckets refill every second)
+    let time_service = time_service.into_mock();
+    assert!(egress_queue.next().now_or_never().is_none());
+    time_service.advance_async(Duration::from_millis(900)).await;
+    assert!(egress_queue.next().now_or_never().is_none());
+
+    // Advance the time service to the refill and verify that the message is released
+    time_service.advance_async(Duration::from_millis(100)).await;
+    assert_eq!(egress_queue.next().now_or_never(), Some(Some(message_2)));
+    assert_eq!(egress_queue.num_throttled_bytes(), 0);
+}
+
+#[test]
+fn test_drop_when_too_many_bytes_held_back() {
+    // Create an egress queue with a small limit on the held back bytes
+    let protocol_id = ProtocolId::StorageServiceRpc;
+    let egress_quota_config = create_egress_quota_config(protocol_id.as_str(), 100, 100, 150);
+    let time_service = TimeService::mock();
+    let egress_quotas = Arc::new(
+        EgressQuotas::new(
+            &egress_quota_config,
+            NetworkId::Public,
+            time_service.clone(),
+        )
+        .unwrap(),
+    );
+    let mut egress_queue = PeerEgressQueue::new(
+        Some(egress_quotas.clone()),
+        PeerRole::Unknown,
+        time_service.clone(),
+    );
+
+    // Exhaust the quota and hold back a message
+    let message = create_direct_send_message(protocol_id, 150);
+    assert_eq!(
+        egress_queue.process_message(protocol_id, message),
+        EgressDecision::Throttled
+    );
+    let message = create_direct_send_message(protocol_id, 100);
+    assert_eq!(
+        egress_queue.process_message(protocol_id, message),
+        EgressDecision::Dropped
+    );
+
+    // Verify that the peers of other classes have separate quotas
+    let mut validator_egress_queue = PeerEgressQueue::new(
+        Some(egress_quotas.clone()),
+        PeerRole::Validator,
+        time_service,
+    );
+    let message = create_direct_send_message(protocol_id, 100);
+    assert_eq!(
+        validator_egress_queue.process_message(protocol_id, message.clone()),
+        EgressDecision::Send(message)
+    );
+
+    // Verify the egress summary of the public fullnodes
+    let summary = egress_quotas
+        .get_summaries()
+        .into_iter()
+        .find(|summary| {
+            summary.protocol_id == protocol_id && summary.peer_class == PeerClass::PublicFullnode
+        })
+        .unwrap();
+    assert_eq!(summary.sent_bytes, 0);
+    assert_eq!(summary.throttled_bytes, 150);
+    assert_eq!(summary.dropped_bytes, 100);
+    assert_eq!(summary.queued_bytes, 150);
+}
+
+/// Creates an egress quota config with the same quota for all peer classes
+fn create_egress_quota_config(
+    protocol_id: &str,
+    bytes_per_second: usize,
+    burst_size_bytes: usize,
+    max_throttled_bytes_per_peer: usize,
+) -> EgressQuotaConfig {
+    let quotas = PeerClass::all()
+        .iter()
+        .map(|peer_class| EgressQuota {
+            protocol_id: protocol_id.into(),
+            peer_class: *peer_class,
+            bytes_per_second,
+            burst_size_bytes,
+        })
+        .collect();
+    EgressQuotaConfig {
+        quotas,
+        max_throttled_bytes_per_peer,
+    }
+}
+
+/// Creates a direct send message with a payload of the given size
+fn create_direct_send_message(protocol_id: ProtocolId, num_bytes: usize) -> NetworkMessage {
+    NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id,
+        priority: 0,
+        raw_msg: vec![0; num_bytes],
+    })
+}
@@ -3,10 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::protocols::wire::handshake::v1::ProtocolId;
-use aptos_config::network_id::NetworkContext;
+use aptos_config::{
+    config::PeerClass,
+    network_id::{NetworkContext, NetworkId},
+};
 use aptos_metrics_core::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
-    Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec,
 };
 use aptos_netcore::transport::ConnectionOrigin;
 use aptos_short_hex_str::AsShortHexStr;
@@ -24,6 +28,8 @@ pub const RECEIVED_LABEL: &str = "received";
 pub const SENT_LABEL: &str = "sent";
 pub const SUCCEEDED_LABEL: &str = "succeeded";
 pub const FAILED_LABEL: &str = "failed";
+pub const THROTTLED_LABEL: &str = "throttled";
+pub const DROPPED_LABEL: &str = "dropped";
 
 pub static APTOS_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -557,3 +563,36 @@ pub fn network_application_outbound_traffic(
         ])
         .observe(size as f64);
 }
+
+/// Counter for network messages dropped by the network recorder (i.e., the writer fell behind)
+pub static NETWORK_RECORDER_DROPPED_MESSAGES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_network_recorder_dropped_messages",
+        "Number of network messages dropped by the network recorder"
+    )
+    .unwrap()
+});
+
+/// Counters for the egress bytes of each protocol and peer class (by egress quota outcome)
+pub static NETWORK_EGRESS_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_egress_bytes",
+        "Number of egress bytes by protocol, peer class and egress quota outcome",
+        &["network_id", "protocol_id", "peer_class", "state"]
+    )
+    .unwrap()
+});
+
+pub fn network_egress_bytes(
+    network_id: NetworkId,
+    protocol_id: ProtocolId,
+    peer_class: PeerClass,
+    state: &str,
+) -> IntCounter {
+    NETWORK_EGRESS_BYTES.with_label_values(&[
+        network_id.as_str(),
+        protocol_id.as_str(),
+        peer_class.as_str(),
+        state,
+    ])
+}
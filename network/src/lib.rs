@@ -12,6 +12,7 @@
 // #![doc = include_str!("../README.md")]
 
 pub mod application;
+pub mod bandwidth;
 pub mod connectivity_manager;
 pub mod constants;
 pub mod counters;
@@ -21,6 +22,7 @@ pub mod noise;
 pub mod peer;
 pub mod peer_manager;
 pub mod protocols;
+pub mod recorder;
 pub mod transport;
 
 #[cfg(feature = "fuzzing")]
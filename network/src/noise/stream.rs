@@ -61,6 +61,14 @@ impl<TSocket> NoiseStream<TSocket> {
     pub fn get_remote_static(&self) -> x25519::PublicKey {
         self.session.get_remote_static()
     }
+
+    /// Consumes the stream and returns the underlying socket. Note: any data that
+    /// is written to (or read from) the socket afterwards is no longer encrypted,
+    /// so this should only be called once the remote has no more noise payloads
+    /// in flight (e.g., after a request-response exchange has completed).
+    pub fn into_inner(self) -> TSocket {
+        self.socket
+    }
 }
 
 //
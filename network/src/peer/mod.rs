@@ -16,6 +16,7 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
+    bandwidth::{EgressDecision, EgressQuotas, PeerEgressQueue},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         RECEIVED_LABEL, SENT_LABEL,
@@ -31,6 +32,7 @@ use crate::{
             MultiplexMessageStream, NetworkMessage, Priority, ReadError, WriteError,
         },
     },
+    recorder::{MessageDirection, NetworkRecorder},
     transport::{self, Connection, ConnectionMetadata},
     ProtocolId,
 };
@@ -50,7 +52,7 @@ use futures::{
 };
 use futures_util::stream::select;
 use serde::Serialize;
-use std::{fmt, panic, time::Duration};
+use std::{fmt, panic, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -138,6 +140,10 @@ pub struct Peer<TSocket> {
     max_message_size: usize,
     /// Inbound stream buffer
     inbound_stream: InboundStreamBuffer,
+    /// The recorder for all inbound and outbound messages (if enabled)
+    network_recorder: Option<Arc<NetworkRecorder>>,
+    /// The queue of outbound messages held back by the egress quotas
+    egress_queue: PeerEgressQueue,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -158,6 +164,8 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        network_recorder: Option<Arc<NetworkRecorder>>,
+        egress_quotas: Option<Arc<EgressQuotas>>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -165,6 +173,11 @@ where
         } = connection;
         let remote_peer_id = connection_metadata.remote_peer_id;
         let max_fragments = max_message_size / max_frame_size;
+        let egress_queue = PeerEgressQueue::new(
+            egress_quotas,
+            connection_metadata.role,
+            time_service.clone(),
+        );
         Self {
             network_context,
             executor,
@@ -191,6 +204,8 @@ where
             max_frame_size,
             max_message_size,
             inbound_stream: InboundStreamBuffer::new(max_fragments),
+            network_recorder,
+            egress_queue,
         }
     }
 
@@ -228,6 +243,7 @@ where
             writer,
             self.max_frame_size,
             self.max_message_size,
+            self.network_recorder.clone(),
         );
 
         // Start main Peer event loop.
@@ -269,8 +285,8 @@ where
                 },
                 // Drive the queue of pending inbound rpcs. When one is fulfilled
                 // by an upstream protocol, send the response to the remote peer.
-                maybe_response = self.inbound_rpcs.next_completed_response() => {
-                    if let Err(err) = self.inbound_rpcs.send_outbound_response(&mut write_reqs_tx, maybe_response).await {
+                (protocol_id, maybe_response) = self.inbound_rpcs.next_completed_response() => {
+                    if let Err(err) = self.inbound_rpcs.send_outbound_response(&mut write_reqs_tx, &mut self.egress_queue, protocol_id, maybe_response).await {
                         warn!(
                             NetworkSchema::new(&self.network_context).connection_metadata(&self.connection_metadata),
                             error = %err,
@@ -282,6 +298,21 @@ where
                 // successfully or unsuccessfully completed request.
                 (request_id, maybe_completed_request) = self.outbound_rpcs.next_completed_request() => {
                     self.outbound_rpcs.handle_completed_request(request_id, maybe_completed_request);
+                },
+                // Send the outbound messages that were held back by the egress
+                // quotas (once the quotas allow it).
+                message = self.egress_queue.select_next_some() => {
+                    if let Err(err) = write_reqs_tx.send(message).await {
+                        warn!(
+                            NetworkSchema::new(&self.network_context)
+                                .connection_metadata(&self.connection_metadata),
+                            error = %err,
+                            "{} Failed to send a held back message to peer: {}, error: {}",
+                            self.network_context,
+                            remote_peer_id.short_str(),
+                            err
+                        );
+                    }
                 }
             }
         };
@@ -306,6 +337,7 @@ where
         mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
+        network_recorder: Option<Arc<NetworkRecorder>>,
     ) -> (aptos_channels::Sender<NetworkMessage>, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
         let (write_reqs_tx, mut write_reqs_rx): (aptos_channels::Sender<NetworkMessage>, _) =
@@ -381,6 +413,16 @@ where
             loop {
                 futures::select! {
                     message = write_reqs_rx.select_next_some() => {
+                        // Record the outbound message (if the recorder is enabled)
+                        if let Some(network_recorder) = &network_recorder {
+                            network_recorder.record_message(
+                                network_context.network_id(),
+                                remote_peer_id,
+                                MessageDirection::Outbound,
+                                &message,
+                            );
+                        }
+
                         // either channel full would block the other one
                         let result = if outbound_stream.should_stream(&message) {
                             outbound_stream.stream_message(message).await
@@ -411,6 +453,16 @@ where
         &mut self,
         message: NetworkMessage,
     ) -> Result<(), PeerManagerError> {
+        // Record the inbound message (if the recorder is enabled)
+        if let Some(network_recorder) = &self.network_recorder {
+            network_recorder.record_message(
+                self.network_context.network_id(),
+                self.remote_peer_id(),
+                MessageDirection::Inbound,
+                &message,
+            );
+        }
+
         match message {
             NetworkMessage::DirectSendMsg(message) => self.handle_inbound_direct_send(message),
             NetworkMessage::Error(error_msg) => {
@@ -571,7 +623,25 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.send(message).await {
+                // Apply the egress quotas (the message may be held back or dropped)
+                let result = match self.egress_queue.process_message(protocol_id, message) {
+                    EgressDecision::Send(message) => write_reqs_tx.send(message).await,
+                    EgressDecision::Throttled => Ok(()),
+                    EgressDecision::Dropped => {
+                        sample!(
+                            SampleRate::Duration(Duration::from_secs(10)),
+                            warn!(
+                                NetworkSchema::new(&self.network_context)
+                                    .connection_metadata(&self.connection_metadata),
+                                "Dropped direct send message for protocol {} to peer: {}. The egress quota was exceeded!",
+                                protocol_id,
+                                self.remote_peer_id().short_str(),
+                            )
+                        );
+                        return;
+                    },
+                };
+                match result {
                     Ok(_) => {
                         counters::direct_send_messages(&self.network_context, SENT_LABEL).inc();
                         counters::direct_send_bytes(&self.network_context, SENT_LABEL)
@@ -597,6 +667,8 @@ where
                     protocol_id,
                     request.data.len() as u64,
                 );
+                self.egress_queue
+                    .record_unthrottled_message(protocol_id, request.data.len());
                 if let Err(e) = self
                     .outbound_rpcs
                     .handle_outbound_request(request, write_reqs_tx)
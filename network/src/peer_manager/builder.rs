@@ -4,6 +4,7 @@
 
 use crate::{
     application::storage::PeersAndMetadata,
+    bandwidth::EgressQuotas,
     counters,
     noise::{stream::NoiseStream, HandshakeAuthMode},
     peer_manager::{
@@ -14,7 +15,12 @@ use crate::{
         network::{NetworkClientConfig, NetworkServiceConfig},
         wire::handshake::v1::ProtocolIdSet,
     },
-    transport::{self, AptosNetTransport, Connection, APTOS_TCP_TRANSPORT},
+    recorder::NetworkRecorder,
+    transport::{
+        self,
+        quic::{TcpOrQuicSocket, TcpOrQuicTransport},
+        AptosNetTransport, Connection, APTOS_TCP_TRANSPORT,
+    },
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
@@ -24,6 +30,7 @@ use aptos_logger::prelude::*;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 use aptos_netcore::transport::memory::MemoryTransport;
 use aptos_netcore::transport::{
+    quic::QuicTransport,
     tcp::{TCPBufferCfg, TcpSocket, TcpTransport},
     Transport,
 };
@@ -52,6 +59,7 @@ struct TransportContext {
     authentication_mode: AuthenticationMode,
     peers_and_metadata: Arc<PeersAndMetadata>,
     enable_proxy_protocol: bool,
+    enable_quic_transport: bool,
 }
 
 impl TransportContext {
@@ -79,6 +87,8 @@ struct PeerManagerContext {
     max_message_size: usize,
     inbound_connection_limit: usize,
     tcp_buffer_cfg: TCPBufferCfg,
+    network_recorder: Option<Arc<NetworkRecorder>>,
+    egress_quotas: Option<Arc<EgressQuotas>>,
 }
 
 impl PeerManagerContext {
@@ -119,6 +129,8 @@ impl PeerManagerContext {
             max_message_size,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            network_recorder: None,
+            egress_quotas: None,
         }
     }
 
@@ -136,17 +148,29 @@ impl PeerManagerContext {
         self.connection_event_handlers.push(tx);
         rx
     }
+
+    fn set_network_recorder(&mut self, network_recorder: Arc<NetworkRecorder>) -> &mut Self {
+        self.network_recorder = Some(network_recorder);
+        self
+    }
+
+    fn set_egress_quotas(&mut self, egress_quotas: Arc<EgressQuotas>) -> &mut Self {
+        self.egress_quotas = Some(egress_quotas);
+        self
+    }
 }
 
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 type MemoryPeerManager =
     PeerManager<AptosNetTransport<MemoryTransport>, NoiseStream<aptos_memsocket::MemorySocket>>;
 type TcpPeerManager = PeerManager<AptosNetTransport<TcpTransport>, NoiseStream<TcpSocket>>;
+type TcpOrQuicPeerManager = PeerManager<TcpOrQuicTransport, TcpOrQuicSocket>;
 
 enum TransportPeerManager {
     #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
     Memory(MemoryPeerManager),
     Tcp(TcpPeerManager),
+    TcpOrQuic(TcpOrQuicPeerManager),
 }
 
 pub struct PeerManagerBuilder {
@@ -197,6 +221,7 @@ impl PeerManagerBuilder {
                 authentication_mode,
                 peers_and_metadata: peers_and_metadata.clone(),
                 enable_proxy_protocol,
+                enable_quic_transport: false,
             }),
             peer_manager_context: Some(PeerManagerContext::new(
                 pm_reqs_tx,
@@ -255,6 +280,7 @@ impl PeerManagerBuilder {
         let protos = transport_context.supported_protocols;
         let chain_id = transport_context.chain_id;
         let enable_proxy_protocol = transport_context.enable_proxy_protocol;
+        let enable_quic_transport = transport_context.enable_quic_transport;
 
         let (key, auth_mode) = match transport_context.authentication_mode {
             AuthenticationMode::MaybeMutual(key) => (
@@ -273,20 +299,30 @@ impl PeerManagerBuilder {
 
         self.peer_manager = match self.listen_address.as_slice() {
             [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => {
-                Some(TransportPeerManager::Tcp(self.build_with_transport(
-                    AptosNetTransport::new(
-                        aptos_tcp_transport,
-                        self.network_context,
-                        self.time_service.clone(),
-                        key,
-                        auth_mode,
-                        HANDSHAKE_VERSION,
-                        chain_id,
-                        protos,
-                        enable_proxy_protocol,
-                    ),
-                    executor,
-                )))
+                let tcp_transport = AptosNetTransport::new(
+                    aptos_tcp_transport,
+                    self.network_context,
+                    self.time_service.clone(),
+                    key,
+                    auth_mode,
+                    HANDSHAKE_VERSION,
+                    chain_id,
+                    protos,
+                    enable_proxy_protocol,
+                );
+                if enable_quic_transport {
+                    let quic_transport =
+                        QuicTransport::new().expect("Failed to create the QUIC transport!");
+                    let max_frame_size = self.peer_manager_context().max_frame_size;
+                    Some(TransportPeerManager::TcpOrQuic(self.build_with_transport(
+                        TcpOrQuicTransport::new(tcp_transport, quic_transport, max_frame_size),
+                        executor,
+                    )))
+                } else {
+                    Some(TransportPeerManager::Tcp(
+                        self.build_with_transport(tcp_transport, executor),
+                    ))
+                }
             },
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
             [Memory(_)] => Some(TransportPeerManager::Memory(self.build_with_transport(
@@ -346,6 +382,8 @@ impl PeerManagerBuilder {
             pm_context.max_frame_size,
             pm_context.max_message_size,
             pm_context.inbound_connection_limit,
+            pm_context.network_recorder,
+            pm_context.egress_quotas,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -376,6 +414,7 @@ impl PeerManagerBuilder {
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
             TransportPeerManager::Memory(pm) => self.start_peer_manager(pm, executor),
             TransportPeerManager::Tcp(pm) => self.start_peer_manager(pm, executor),
+            TransportPeerManager::TcpOrQuic(pm) => self.start_peer_manager(pm, executor),
         }
     }
 
@@ -386,6 +425,28 @@ impl PeerManagerBuilder {
             .add_connection_event_listener()
     }
 
+    /// Records all inbound and outbound peer messages using the given recorder
+    pub fn set_network_recorder(&mut self, network_recorder: Arc<NetworkRecorder>) {
+        self.peer_manager_context
+            .as_mut()
+            .expect("Cannot set the network recorder if PeerManager has already been built.")
+            .set_network_recorder(network_recorder);
+    }
+
+    /// Applies the given egress quotas to the outbound messages of all peers
+    pub fn set_egress_quotas(&mut self, egress_quotas: Arc<EgressQuotas>) {
+        self.peer_manager_context
+            .as_mut()
+            .expect("Cannot set the egress quotas if PeerManager has already been built.")
+            .set_egress_quotas(egress_quotas);
+    }
+
+    /// Also listens for (and dials) QUIC connections when listening over TCP.
+    /// See `transport::quic::TcpOrQuicTransport` for more details.
+    pub fn enable_quic_transport(&mut self) {
+        self.transport_context().enable_quic_transport = true;
+    }
+
     pub fn get_tcp_buffers_cfg(&self) -> TCPBufferCfg {
         self.peer_manager_context
             .as_ref()
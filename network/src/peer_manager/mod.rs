@@ -12,10 +12,12 @@
 //!  notification about new/lost Peers to the rest of the network stack.
 //!  * An actor responsible for dialing and listening for new connections.
 use crate::{
+    bandwidth::EgressQuotas,
     constants,
     counters::{self},
     logging::*,
     peer::{Peer, PeerNotification, PeerRequest},
+    recorder::NetworkRecorder,
     transport::{
         Connection, ConnectionId, ConnectionMetadata, TSocket as TransportTSocket,
         TRANSPORT_TIMEOUT,
@@ -119,6 +121,10 @@ where
     max_message_size: usize,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
+    /// The recorder for all inbound and outbound peer messages (if enabled)
+    network_recorder: Option<Arc<NetworkRecorder>>,
+    /// The egress quotas applied to the outbound messages of all peers (if any)
+    egress_quotas: Option<Arc<EgressQuotas>>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -147,6 +153,8 @@ where
         max_frame_size: usize,
         max_message_size: usize,
         inbound_connection_limit: usize,
+        network_recorder: Option<Arc<NetworkRecorder>>,
+        egress_quotas: Option<Arc<EgressQuotas>>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
             channel_size,
@@ -189,6 +197,8 @@ where
             max_frame_size,
             max_message_size,
             inbound_connection_limit,
+            network_recorder,
+            egress_quotas,
         }
     }
 
@@ -673,6 +683,8 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            self.network_recorder.clone(),
+            self.egress_quotas.clone(),
         );
         self.executor.spawn(peer.start());
 
@@ -45,6 +45,7 @@
 //! [`Peer`]: crate::peer::Peer
 
 use crate::{
+    bandwidth::{EgressDecision, PeerEgressQueue},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         CANCELED_LABEL, DECLINED_LABEL, FAILED_LABEL, RECEIVED_LABEL, REQUEST_LABEL,
@@ -173,9 +174,11 @@ pub struct InboundRpcs {
     time_service: TimeService,
     /// The PeerId of this connection's remote peer. Used for logging.
     remote_peer_id: PeerId,
-    /// The core async queue of pending inbound rpc tasks. The tasks are driven
-    /// to completion by the `InboundRpcs::next_completed_response()` method.
-    inbound_rpc_tasks: FuturesUnordered<BoxFuture<'static, Result<RpcResponse, RpcError>>>,
+    /// The core async queue of pending inbound rpc tasks (and their protocols).
+    /// The tasks are driven to completion by the `InboundRpcs::next_completed_response()`
+    /// method.
+    inbound_rpc_tasks:
+        FuturesUnordered<BoxFuture<'static, (ProtocolId, Result<RpcResponse, RpcError>)>>,
     /// A blanket timeout on all inbound rpc requests. If the application handler
     /// doesn't respond to the request before this timeout, the request will be
     /// dropped.
@@ -272,7 +275,7 @@ impl InboundRpcs {
                     Ok(_) => timer.stop_and_record(),
                     Err(_) => timer.stop_and_discard(),
                 };
-                maybe_response
+                (protocol_id, maybe_response)
             })
             .boxed();
 
@@ -288,16 +291,18 @@ impl InboundRpcs {
     /// `futures::select!`.
     pub fn next_completed_response(
         &mut self,
-    ) -> impl Future<Output = Result<RpcResponse, RpcError>> + FusedFuture + '_ {
+    ) -> impl Future<Output = (ProtocolId, Result<RpcResponse, RpcError>)> + FusedFuture + '_ {
         self.inbound_rpc_tasks.select_next_some()
     }
 
     /// Handle a completed response from the application handler. If successful,
     /// we update the appropriate counters and enqueue the response message onto
-    /// the outbound write queue.
+    /// the outbound write queue (unless it's held back by the egress quotas).
     pub async fn send_outbound_response(
         &mut self,
         write_reqs_tx: &mut aptos_channels::Sender<NetworkMessage>,
+        egress_queue: &mut PeerEgressQueue,
+        protocol_id: ProtocolId,
         maybe_response: Result<RpcResponse, RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
@@ -319,7 +324,14 @@ impl InboundRpcs {
             response.request_id,
         );
         let message = NetworkMessage::RpcResponse(response);
-        write_reqs_tx.send(message).await?;
+        match egress_queue.process_message(protocol_id, message) {
+            EgressDecision::Send(message) => write_reqs_tx.send(message).await?,
+            EgressDecision::Throttled => {},
+            EgressDecision::Dropped => {
+                counters::rpc_messages(network_context, RESPONSE_LABEL, FAILED_LABEL).inc();
+                return Err(RpcError::EgressQuotaExceeded(protocol_id));
+            },
+        }
 
         // Collect counters for sent response.
         counters::rpc_messages(network_context, RESPONSE_LABEL, SENT_LABEL).inc();
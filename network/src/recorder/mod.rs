@@ -0,0 +1,299 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An opt-in recorder for all network traffic that flows through the [`Peer`] actors.
+//!
+//! Each inbound and outbound [`NetworkMessage`] is recorded (together with the
+//! network, remote peer and a timestamp) to a set of rotating local files. The
+//! recordings can then be read back (see [`read_recorded_messages`]) and replayed
+//! against a single node (see [`replay::NetworkReplayer`]) to deterministically
+//! re-run incidents without a live cluster.
+//!
+//! Each recording file is a sequence of length-prefixed records, where each
+//! record is the BCS serialized [`RecordedMessage`] prefixed by its length
+//! (as a little endian `u32`).
+//!
+//! [`Peer`]: crate::peer::Peer
+
+use crate::{counters, protocols::wire::messaging::v1::NetworkMessage, ProtocolId};
+use anyhow::{anyhow, Result};
+use aptos_config::{config::NetworkRecorderConfig, network_id::NetworkId};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, SyncSender, TrySendError},
+    thread,
+};
+
+#[cfg(any(test, feature = "testing", feature = "fuzzing"))]
+pub mod replay;
+#[cfg(test)]
+mod tests;
+
+// The prefix and extension of all recording files
+const RECORDING_FILE_PREFIX: &str = "network_recording_";
+const RECORDING_FILE_EXTENSION: &str = "bcs";
+
+/// The direction of a recorded message (relative to the recording node)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A single network message recorded by the [`NetworkRecorder`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RecordedMessage {
+    /// The time (in microseconds since the unix epoch) at which the message was recorded
+    pub timestamp_usecs: u64,
+    /// The network on which the message was sent or received
+    pub network_id: NetworkId,
+    /// The remote peer that sent (or will receive) the message
+    pub peer_id: PeerId,
+    /// The direction of the message
+    pub direction: MessageDirection,
+    /// The message itself (including the protocol id and BCS bytes)
+    pub message: NetworkMessage,
+}
+
+impl RecordedMessage {
+    /// Returns the protocol id of the message (if the message has one). Note:
+    /// rpc responses and errors are not tagged with a protocol id on the wire.
+    pub fn protocol_id(&self) -> Option<ProtocolId> {
+        match &self.message {
+            NetworkMessage::RpcRequest(request) => Some(request.protocol_id),
+            NetworkMessage::DirectSendMsg(message) => Some(message.protocol_id),
+            NetworkMessage::RpcResponse(_) | NetworkMessage::Error(_) => None,
+        }
+    }
+}
+
+/// The recorder hands messages off to a dedicated writer thread so that
+/// recording never blocks the peer actors. If the writer falls behind, new
+/// messages are dropped (and counted) instead of applying backpressure.
+#[derive(Debug)]
+pub struct NetworkRecorder {
+    message_sender: SyncSender<RecordedMessage>,
+    time_service: TimeService,
+}
+
+impl NetworkRecorder {
+    /// Creates a new recorder (for the given network) and spawns the writer thread.
+    /// The recordings of each network are stored in a separate sub-directory.
+    pub fn new(
+        config: &NetworkRecorderConfig,
+        network_id: NetworkId,
+        time_service: TimeService,
+    ) -> Result<Self> {
+        let mut recording_writer = RecordingWriter::new(
+            &config.recording_dir.join(network_id.as_str()),
+            config.max_file_size_bytes,
+            config.max_num_files,
+        )?;
+
+        // Spawn the writer thread (this will exit once the recorder is dropped)
+        let (message_sender, message_receiver) = mpsc::sync_channel(config.max_pending_messages);
+        thread::Builder::new()
+            .name("net-recorder".into())
+            .spawn(move || {
+                while let Ok(recorded_message) = message_receiver.recv() {
+                    // Write the message (and any other pending messages) before flushing
+                    let result = std::iter::once(recorded_message)
+                        .chain(message_receiver.try_iter())
+                        .try_for_each(|recorded_message| {
+                            recording_writer.write_message(&recorded_message)
+                        })
+                        .and_then(|_| recording_writer.flush());
+                    if let Err(error) = result {
+                        error!("Failed to write the network recording! Error: {:?}", error);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            message_sender,
+            time_service,
+        })
+    }
+
+    /// Records the given message for the specified network and peer
+    pub fn record_message(
+        &self,
+        network_id: NetworkId,
+        peer_id: PeerId,
+        direction: MessageDirection,
+        message: &NetworkMessage,
+    ) {
+        let recorded_message = RecordedMessage {
+            timestamp_usecs: self.time_service.now_unix_time().as_micros() as u64,
+            network_id,
+            peer_id,
+            direction,
+            message: message.clone(),
+        };
+        match self.message_sender.try_send(recorded_message) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => {
+                counters::NETWORK_RECORDER_DROPPED_MESSAGES.inc();
+            },
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("The network recorder writer thread has terminated!");
+            },
+        }
+    }
+}
+
+/// Writes recorded messages to a set of rotating files in the recording directory
+pub(crate) struct RecordingWriter {
+    recording_dir: PathBuf,
+    max_file_size_bytes: u64,
+    max_num_files: usize,
+    next_file_index: u64,
+    current_file: Option<BufWriter<File>>,
+    current_file_size_bytes: u64,
+}
+
+impl RecordingWriter {
+    pub(crate) fn new(
+        recording_dir: &Path,
+        max_file_size_bytes: u64,
+        max_num_files: usize,
+    ) -> Result<Self> {
+        if max_num_files == 0 {
+            return Err(anyhow!("At least one recording file must be kept!"));
+        }
+        fs::create_dir_all(recording_dir)?;
+
+        // Never overwrite existing recordings, start after the latest file instead
+        let next_file_index = get_recording_files(recording_dir)?
+            .last()
+            .map(|(file_index, _)| file_index + 1)
+            .unwrap_or(0);
+
+        Ok(Self {
+            recording_dir: recording_dir.to_path_buf(),
+            max_file_size_bytes,
+            max_num_files,
+            next_file_index,
+            current_file: None,
+            current_file_size_bytes: 0,
+        })
+    }
+
+    /// Appends the given message to the current recording file (rotating if required)
+    pub(crate) fn write_message(&mut self, recorded_message: &RecordedMessage) -> Result<()> {
+        let message_bytes = bcs::to_bytes(recorded_message)?;
+        let record_size_bytes = (message_bytes.len() + std::mem::size_of::<u32>()) as u64;
+
+        // Rotate the file if it would exceed the maximum size (files always hold at least one record)
+        if self.current_file.is_none()
+            || (self.current_file_size_bytes > 0
+                && self.current_file_size_bytes + record_size_bytes > self.max_file_size_bytes)
+        {
+            self.rotate_file()?;
+        }
+
+        let current_file = self
+            .current_file
+            .as_mut()
+            .expect("The current recording file should exist!");
+        current_file.write_all(&(message_bytes.len() as u32).to_le_bytes())?;
+        current_file.write_all(&message_bytes)?;
+        self.current_file_size_bytes += record_size_bytes;
+
+        Ok(())
+    }
+
+    /// Flushes all buffered records to the current recording file
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        if let Some(current_file) = self.current_file.as_mut() {
+            current_file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Closes the current recording file, opens a new one and deletes
+    /// the oldest files to respect the maximum number of files.
+    fn rotate_file(&mut self) -> Result<()> {
+        self.flush()?;
+
+        // Open the next file
+        let file_path = self
+            .recording_dir
+            .join(get_recording_file_name(self.next_file_index));
+        self.current_file = Some(BufWriter::new(File::create(file_path)?));
+        self.current_file_size_bytes = 0;
+        self.next_file_index += 1;
+
+        // Delete the oldest files
+        let recording_files = get_recording_files(&self.recording_dir)?;
+        let num_files_to_delete = recording_files.len().saturating_sub(self.max_num_files);
+        for (_, file_path) in recording_files.iter().take(num_files_to_delete) {
+            fs::remove_file(file_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads all recorded messages (in the order they were recorded) from the given directory
+pub fn read_recorded_messages(recording_dir: &Path) -> Result<Vec<RecordedMessage>> {
+    let mut recorded_messages = vec![];
+    for (_, file_path) in get_recording_files(recording_dir)? {
+        let mut reader = BufReader::new(File::open(&file_path)?);
+        loop {
+            // Read the length prefix (a clean end of file means there are no more records)
+            let mut length_bytes = [0u8; 4];
+            match reader.read_exact(&mut length_bytes) {
+                Ok(()) => {},
+                Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error.into()),
+            }
+
+            // Read and deserialize the record
+            let mut message_bytes = vec![0u8; u32::from_le_bytes(length_bytes) as usize];
+            reader.read_exact(&mut message_bytes).map_err(|error| {
+                anyhow!(
+                    "Failed to read a record from the recording file: {:?}. Error: {:?}",
+                    file_path,
+                    error
+                )
+            })?;
+            recorded_messages.push(bcs::from_bytes(&message_bytes)?);
+        }
+    }
+    Ok(recorded_messages)
+}
+
+/// Returns the name of the recording file with the given index
+fn get_recording_file_name(file_index: u64) -> String {
+    format!(
+        "{}{:010}.{}",
+        RECORDING_FILE_PREFIX, file_index, RECORDING_FILE_EXTENSION
+    )
+}
+
+/// Returns all recording files (and their indices) in the given directory, sorted by index
+fn get_recording_files(recording_dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let mut recording_files = vec![];
+    for entry in fs::read_dir(recording_dir)? {
+        let file_path = entry?.path();
+        let file_index = file_path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_prefix(RECORDING_FILE_PREFIX))
+            .and_then(|file_name| file_name.strip_suffix(&format!(".{}", RECORDING_FILE_EXTENSION)))
+            .and_then(|file_index| file_index.parse::<u64>().ok());
+        if let Some(file_index) = file_index {
+            recording_files.push((file_index, file_path));
+        }
+    }
+    recording_files.sort();
+    Ok(recording_files)
+}
@@ -0,0 +1,170 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A harness that replays recorded inbound network messages into a single node.
+//!
+//! The [`NetworkReplayer`] hands out a [`NetworkEvents`] stream that can be given
+//! to an application (e.g., consensus or state sync) in place of the real network.
+//! Each replayed message first advances the [`MockTimeService`] to the time at
+//! which the message was originally recorded, so that timers in the application
+//! fire in the same order relative to the messages as they did during the incident.
+
+use crate::{
+    constants::NETWORK_CHANNEL_SIZE,
+    peer_manager::{ConnectionNotification, PeerManagerNotification},
+    protocols::{
+        direct_send::Message,
+        network::{self, NetworkEvents, NewNetworkEvents, RpcError},
+        rpc::InboundRpcRequest,
+        wire::messaging::v1::NetworkMessage,
+    },
+    recorder::{read_recorded_messages, MessageDirection, RecordedMessage},
+    ProtocolId,
+};
+use anyhow::Result;
+use aptos_channels::{aptos_channel, message_queues::QueueStyle};
+use aptos_config::network_id::NetworkId;
+use aptos_time_service::MockTimeService;
+use aptos_types::PeerId;
+use bytes::Bytes;
+use futures::channel::oneshot;
+use std::{collections::VecDeque, path::Path, time::Duration};
+
+/// A recorded message that has been replayed to the application
+#[derive(Debug)]
+pub struct ReplayedMessage {
+    /// The original recorded message
+    pub recorded_message: RecordedMessage,
+    /// The channel on which the application will respond (only for rpc requests)
+    pub response_receiver: Option<oneshot::Receiver<Result<Bytes, RpcError>>>,
+}
+
+/// Replays recorded inbound messages (for a single network and set of protocols)
+pub struct NetworkReplayer {
+    recorded_messages: VecDeque<RecordedMessage>,
+    time_service: MockTimeService,
+    last_timestamp_usecs: Option<u64>,
+    peer_mgr_notifs_tx: aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
+    _connection_notifs_tx: aptos_channel::Sender<PeerId, ConnectionNotification>,
+}
+
+impl NetworkReplayer {
+    /// Creates a new replayer (and the network events to hand to the application)
+    /// for the recorded inbound messages of the given network and protocols.
+    pub fn new<TMessage: network::Message>(
+        recorded_messages: Vec<RecordedMessage>,
+        network_id: NetworkId,
+        protocol_ids: &[ProtocolId],
+        time_service: MockTimeService,
+    ) -> (Self, NetworkEvents<TMessage>) {
+        // Only replay inbound messages that the application can handle
+        let recorded_messages = recorded_messages
+            .into_iter()
+            .filter(|recorded_message| {
+                recorded_message.network_id == network_id
+                    && recorded_message.direction == MessageDirection::Inbound
+                    && recorded_message
+                        .protocol_id()
+                        .map_or(false, |protocol_id| protocol_ids.contains(&protocol_id))
+            })
+            .collect();
+
+        // Create the network events
+        let (peer_mgr_notifs_tx, peer_mgr_notifs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, NETWORK_CHANNEL_SIZE, None);
+        let (connection_notifs_tx, connection_notifs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, NETWORK_CHANNEL_SIZE, None);
+        let network_events = NetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
+
+        let network_replayer = Self {
+            recorded_messages,
+            time_service,
+            last_timestamp_usecs: None,
+            peer_mgr_notifs_tx,
+            _connection_notifs_tx: connection_notifs_tx,
+        };
+        (network_replayer, network_events)
+    }
+
+    /// Creates a new replayer for the recordings in the given directory
+    pub fn from_recording_dir<TMessage: network::Message>(
+        recording_dir: &Path,
+        network_id: NetworkId,
+        protocol_ids: &[ProtocolId],
+        time_service: MockTimeService,
+    ) -> Result<(Self, NetworkEvents<TMessage>)> {
+        let recorded_messages = read_recorded_messages(recording_dir)?;
+        Ok(Self::new(
+            recorded_messages,
+            network_id,
+            protocol_ids,
+            time_service,
+        ))
+    }
+
+    /// Returns the number of messages that have not yet been replayed
+    pub fn num_remaining_messages(&self) -> usize {
+        self.recorded_messages.len()
+    }
+
+    /// Advances the mock time to the timestamp of the next recorded message and
+    /// replays it to the application. Returns `None` if all messages have been replayed.
+    pub async fn replay_next_message(&mut self) -> Option<ReplayedMessage> {
+        let recorded_message = self.recorded_messages.pop_front()?;
+
+        // Advance the mock time by the time elapsed since the previous message
+        let timestamp_usecs = recorded_message.timestamp_usecs;
+        if let Some(last_timestamp_usecs) = self.last_timestamp_usecs {
+            let elapsed_usecs = timestamp_usecs.saturating_sub(last_timestamp_usecs);
+            self.time_service
+                .advance_async(Duration::from_micros(elapsed_usecs))
+                .await;
+        }
+        self.last_timestamp_usecs = Some(timestamp_usecs);
+
+        // Notify the application of the message
+        let peer_id = recorded_message.peer_id;
+        let (notification, response_receiver) = match &recorded_message.message {
+            NetworkMessage::DirectSendMsg(message) => {
+                let notification = PeerManagerNotification::RecvMessage(peer_id, Message {
+                    protocol_id: message.protocol_id,
+                    mdata: Bytes::from(message.raw_msg.clone()),
+                });
+                (notification, None)
+            },
+            NetworkMessage::RpcRequest(request) => {
+                let (res_tx, res_rx) = oneshot::channel();
+                let notification = PeerManagerNotification::RecvRpc(peer_id, InboundRpcRequest {
+                    protocol_id: request.protocol_id,
+                    data: Bytes::from(request.raw_request.clone()),
+                    res_tx,
+                });
+                (notification, Some(res_rx))
+            },
+            message => unreachable!(
+                "Only messages with a protocol id are replayed: {:?}",
+                message
+            ),
+        };
+        let protocol_id = recorded_message
+            .protocol_id()
+            .expect("Replayed messages should have a protocol id!");
+        self.peer_mgr_notifs_tx
+            .push((peer_id, protocol_id), notification)
+            .expect("Failed to replay the message! The network events have been dropped!");
+
+        Some(ReplayedMessage {
+            recorded_message,
+            response_receiver,
+        })
+    }
+
+    /// Replays all remaining messages to the application
+    pub async fn replay_all_messages(&mut self) -> Vec<ReplayedMessage> {
+        let mut replayed_messages = vec![];
+        while let Some(replayed_message) = self.replay_next_message().await {
+            replayed_messages.push(replayed_message);
+        }
+        replayed_messages
+    }
+}
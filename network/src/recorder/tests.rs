@@ -0,0 +1,255 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    protocols::{
+        network::Event,
+        wire::messaging::v1::{DirectSendMsg, NetworkMessage, RpcRequest, RpcResponse},
+    },
+    recorder::{
+        get_recording_files, read_recorded_messages, replay::NetworkReplayer, MessageDirection,
+        RecordedMessage, RecordingWriter,
+    },
+    ProtocolId,
+};
+use aptos_config::network_id::NetworkId;
+use aptos_temppath::TempPath;
+use aptos_time_service::{MockTimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use futures::StreamExt;
+use std::time::Duration;
+
+#[test]
+fn test_write_and_read_recorded_messages() {
+    // Create a recording writer
+    let recording_dir = create_recording_dir();
+    let mut recording_writer = RecordingWriter::new(recording_dir.path(), 1024 * 1024, 10).unwrap();
+
+    // Write several messages
+    let recorded_messages = create_recorded_messages(100);
+    for recorded_message in &recorded_messages {
+        recording_writer.write_message(recorded_message).unwrap();
+    }
+    recording_writer.flush().unwrap();
+
+    // Verify the messages are read back in order (from a single file)
+    assert_eq!(get_recording_files(recording_dir.path()).unwrap().len(), 1);
+    assert_eq!(
+        read_recorded_messages(recording_dir.path()).unwrap(),
+        recorded_messages
+    );
+}
+
+#[test]
+fn test_recording_file_rotation() {
+    // Create a recording writer with tiny files (so that each file holds a single message)
+    let max_num_files = 3;
+    let recording_dir = create_recording_dir();
+    let mut recording_writer =
+        RecordingWriter::new(recording_dir.path(), 1, max_num_files).unwrap();
+
+    // Write several messages
+    let recorded_messages = create_recorded_messages(10);
+    for recorded_message in &recorded_messages {
+        recording_writer.write_message(recorded_message).unwrap();
+    }
+    recording_writer.flush().unwrap();
+
+    // Verify that only the latest files (and messages) are kept
+    assert_eq!(
+        get_recording_files(recording_dir.path()).unwrap().len(),
+        max_num_files
+    );
+    assert_eq!(
+        read_recorded_messages(recording_dir.path()).unwrap(),
+        recorded_messages[recorded_messages.len() - max_num_files..].to_vec()
+    );
+}
+
+#[test]
+fn test_recording_resumes_after_existing_files() {
+    let recording_dir = create_recording_dir();
+    let recorded_messages = create_recorded_messages(20);
+
+    // Write messages using several writers (e.g., across node restarts)
+    for recorded_messages_chunk in recorded_messages.chunks(5) {
+        let mut recording_writer =
+            RecordingWriter::new(recording_dir.path(), 1024 * 1024, 10).unwrap();
+        for recorded_message in recorded_messages_chunk {
+            recording_writer.write_message(recorded_message).unwrap();
+        }
+        recording_writer.flush().unwrap();
+    }
+
+    // Verify that no recordings were overwritten
+    assert_eq!(get_recording_files(recording_dir.path()).unwrap().len(), 4);
+    assert_eq!(
+        read_recorded_messages(recording_dir.path()).unwrap(),
+        recorded_messages
+    );
+}
+
+#[tokio::test]
+async fn test_replay_inbound_messages() {
+    // Create the recorded messages (only the first two should be replayed)
+    let peer_id = PeerId::random();
+    let recorded_messages = vec![
+        create_direct_send_message(
+            1_000_000,
+            NetworkId::Validator,
+            peer_id,
+            MessageDirection::Inbound,
+            ProtocolId::ConsensusDirectSendBcs,
+            10,
+        ),
+        create_rpc_request_message(
+            1_500_000,
+            NetworkId::Validator,
+            peer_id,
+            ProtocolId::ConsensusRpcBcs,
+            20,
+        ),
+        create_direct_send_message(
+            2_000_000,
+            NetworkId::Validator,
+            peer_id,
+            MessageDirection::Outbound,
+            ProtocolId::ConsensusDirectSendBcs,
+            30,
+        ),
+        create_direct_send_message(
+            2_500_000,
+            NetworkId::Public,
+            peer_id,
+            MessageDirection::Inbound,
+            ProtocolId::ConsensusDirectSendBcs,
+            40,
+        ),
+        create_direct_send_message(
+            3_000_000,
+            NetworkId::Validator,
+            peer_id,
+            MessageDirection::Inbound,
+            ProtocolId::MempoolDirectSend,
+            50,
+        ),
+    ];
+
+    // Create the network replayer
+    let time_service = MockTimeService::new();
+    let (mut network_replayer, mut network_events) = NetworkReplayer::new::<u64>(
+        recorded_messages,
+        NetworkId::Validator,
+        &[
+            ProtocolId::ConsensusDirectSendBcs,
+            ProtocolId::ConsensusRpcBcs,
+        ],
+        time_service.clone(),
+    );
+    assert_eq!(network_replayer.num_remaining_messages(), 2);
+
+    // Replay the direct send message and verify the event
+    let start_time = time_service.now_unix_time();
+    let replayed_message = network_replayer.replay_next_message().await.unwrap();
+    assert!(replayed_message.response_receiver.is_none());
+    assert_eq!(
+        network_events.next().await.unwrap(),
+        Event::Message(peer_id, 10)
+    );
+
+    // Replay the rpc request and verify the event and elapsed time
+    let replayed_message = network_replayer.replay_next_message().await.unwrap();
+    assert!(replayed_message.response_receiver.is_some());
+    match network_events.next().await.unwrap() {
+        Event::RpcRequest(remote_peer_id, message, protocol_id, _) => {
+            assert_eq!(remote_peer_id, peer_id);
+            assert_eq!(message, 20);
+            assert_eq!(protocol_id, ProtocolId::ConsensusRpcBcs);
+        },
+        event => panic!("Unexpected network event: {:?}", event),
+    }
+    assert_eq!(
+        time_service.now_unix_time() - start_time,
+        Duration::from_millis(500)
+    );
+
+    // Verify there are no more messages to replay
+    assert!(network_replayer.replay_next_message().await.is_none());
+}
+
+/// Creates a temporary recording directory
+fn create_recording_dir() -> TempPath {
+    let recording_dir = TempPath::new();
+    recording_dir.create_as_dir().unwrap();
+    recording_dir
+}
+
+/// Creates the specified number of (inbound and outbound) recorded messages
+fn create_recorded_messages(num_messages: u64) -> Vec<RecordedMessage> {
+    let peer_id = PeerId::random();
+    (0..num_messages)
+        .map(|index| {
+            let direction = if index % 2 == 0 {
+                MessageDirection::Inbound
+            } else {
+                MessageDirection::Outbound
+            };
+            let message = NetworkMessage::RpcResponse(RpcResponse {
+                request_id: index as u32,
+                priority: 0,
+                raw_response: vec![index as u8; 10],
+            });
+            RecordedMessage {
+                timestamp_usecs: index,
+                network_id: NetworkId::Validator,
+                peer_id,
+                direction,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// Creates a recorded direct send message holding the given value
+fn create_direct_send_message(
+    timestamp_usecs: u64,
+    network_id: NetworkId,
+    peer_id: PeerId,
+    direction: MessageDirection,
+    protocol_id: ProtocolId,
+    value: u64,
+) -> RecordedMessage {
+    RecordedMessage {
+        timestamp_usecs,
+        network_id,
+        peer_id,
+        direction,
+        message: NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: bcs::to_bytes(&value).unwrap(),
+        }),
+    }
+}
+
+/// Creates a recorded inbound rpc request holding the given value
+fn create_rpc_request_message(
+    timestamp_usecs: u64,
+    network_id: NetworkId,
+    peer_id: PeerId,
+    protocol_id: ProtocolId,
+    value: u64,
+) -> RecordedMessage {
+    RecordedMessage {
+        timestamp_usecs,
+        network_id,
+        peer_id,
+        direction: MessageDirection::Inbound,
+        message: NetworkMessage::RpcRequest(RpcRequest {
+            protocol_id,
+            request_id: 0,
+            priority: 0,
+            raw_request: bcs::to_bytes(&value).unwrap(),
+        }),
+    }
+}
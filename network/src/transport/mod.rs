@@ -35,6 +35,7 @@ use futures::{
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, convert::TryFrom, fmt, io, pin::Pin, sync::Arc, time::Duration};
 
+pub mod quic;
 #[cfg(test)]
 mod test;
 
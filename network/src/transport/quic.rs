@@ -0,0 +1,1129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! AptosNet over QUIC.
+//!
+//! Connections are established with the base [`QuicTransport`] and then upgraded
+//! like any other AptosNet connection: we run a Noise IK handshake (over the QUIC
+//! control stream) to authenticate the remote peer with its x25519 network key,
+//! and negotiate the supported protocols with the `Handshake` protocol. Because the
+//! TLS session of the QUIC connection is not authenticated itself, both peers also
+//! exchange the TLS channel binding over the Noise session, which guarantees that
+//! the authenticated peer is the one on the other end of the QUIC connection.
+//!
+//! Once upgraded, the connection is exposed as a [`QuicSocket`], which avoids
+//! head-of-line blocking between application protocols: each protocol is sent on
+//! its own unidirectional QUIC stream, so a lost packet only stalls the messages
+//! of a single protocol (instead of the entire connection). Each unidirectional
+//! stream starts with a `StreamType` header, so the receiver knows which frames
+//! the stream carries without inspecting them.
+//!
+//! To allow mixed TCP/QUIC deployments, the [`TcpOrQuicTransport`] listens for both
+//! TCP and QUIC connections and dials peers using whichever transport protocol
+//! their `NetworkAddress` specifies.
+
+use crate::{
+    noise::{stream::NoiseStream, HandshakeAuthMode, NoiseUpgrader},
+    protocols::{
+        stream::{StreamFragment, StreamMessage},
+        wire::{
+            handshake::v1::{ProtocolId, ProtocolIdSet},
+            messaging::v1::{
+                DirectSendMsg, MultiplexMessage, NetworkMessage, RequestId, RpcRequest, RpcResponse,
+            },
+        },
+    },
+    transport::{
+        timeout_io, upgrade_inbound, upgrade_outbound, AptosNetTransport, Connection, TcpSocket,
+        UpgradeContext, SUPPORTED_MESSAGING_PROTOCOL, TRANSPORT_TIMEOUT,
+    },
+};
+use aptos_config::network_id::NetworkContext;
+use aptos_crypto::x25519;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_netcore::{
+    framing::{read_u16frame, write_u16frame},
+    transport::{
+        quic::{QuicConnection, QuicStream, QuicTransport, CHANNEL_BINDING_SIZE},
+        tcp::TcpTransport,
+        Transport,
+    },
+};
+use aptos_time_service::TimeService;
+use aptos_types::{
+    chain_id::ChainId,
+    network_address::{parse_dns_quic, parse_ip_quic, parse_ip_tcp, NetworkAddress, Protocol},
+    PeerId,
+};
+use bytes::{Bytes, BytesMut};
+use futures::{
+    channel::mpsc,
+    future::{self, BoxFuture, Future, FutureExt, TryFutureExt},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    ready,
+    stream::{self, BoxStream, FuturesUnordered, Stream, StreamExt, TryStreamExt},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    error::Error,
+    fmt, io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::task::JoinHandle;
+
+/// The size (in bytes) of the length prefix of each frame (see `network_message_frame_codec`)
+const FRAME_LENGTH_SIZE: usize = 4;
+/// The number of outbound frames that can be buffered for each stream
+const OUTBOUND_STREAM_BUFFER_SIZE: usize = 1024;
+/// The number of inbound frames that can be buffered before backpressure is applied
+const INBOUND_FRAME_BUFFER_SIZE: usize = 1024;
+/// The maximum number of inbound rpc requests for which the response stream is tracked
+const MAX_TRACKED_INBOUND_RPCS: usize = 4096;
+
+/// The maximum size (in bytes) of the serialized header of a stream
+const MAX_STREAM_HEADER_SIZE: usize = 16;
+
+/// The BCS encoded prefixes of the wire messages, derived from the message types
+static WIRE_PREFIXES: Lazy<WirePrefixes> = Lazy::new(WirePrefixes::new);
+
+/// The AptosNet transport over QUIC. See the module documentation for more details.
+pub struct AptosNetQuicTransport {
+    base_transport: QuicTransport,
+    ctxt: Arc<UpgradeContext>,
+    time_service: TimeService,
+    identity_pubkey: x25519::PublicKey,
+    max_frame_size: usize,
+}
+
+impl AptosNetQuicTransport {
+    pub fn new(
+        base_transport: QuicTransport,
+        network_context: NetworkContext,
+        time_service: TimeService,
+        identity_key: x25519::PrivateKey,
+        auth_mode: HandshakeAuthMode,
+        handshake_version: u8,
+        chain_id: ChainId,
+        application_protocols: ProtocolIdSet,
+        max_frame_size: usize,
+    ) -> Self {
+        // build supported protocols
+        let mut supported_protocols = BTreeMap::new();
+        supported_protocols.insert(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+
+        let identity_pubkey = identity_key.public_key();
+
+        let upgrade_context = UpgradeContext::new(
+            NoiseUpgrader::new(network_context, identity_key, auth_mode),
+            handshake_version,
+            supported_protocols,
+            chain_id,
+            network_context.network_id(),
+        );
+
+        Self {
+            base_transport,
+            ctxt: Arc::new(upgrade_context),
+            time_service,
+            identity_pubkey,
+            max_frame_size,
+        }
+    }
+
+    fn parse_dial_addr(
+        addr: &NetworkAddress,
+    ) -> io::Result<(NetworkAddress, x25519::PublicKey, u8)> {
+        use aptos_types::network_address::Protocol::*;
+
+        let protos = addr.as_slice();
+
+        // parse out the base transport protocols (which are dialed by the base transport)
+        let base_transport_suffix = parse_ip_quic(protos)
+            .map(|x| x.1)
+            .or_else(|| parse_dns_quic(protos).map(|x| x.1))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unexpected dialing network address: '{}', expected: \
+                         ip+quic or dns+quic",
+                        addr
+                    ),
+                )
+            })?;
+
+        // parse out the aptosnet protocols (noise ik and handshake)
+        match base_transport_suffix {
+            [NoiseIK(pubkey), Handshake(version)] => {
+                let base_addr = NetworkAddress::try_from(protos[..2].to_vec())
+                    .expect("base_transport_protos is always non-empty");
+                Ok((base_addr, *pubkey, *version))
+            },
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unexpected dialing network address: '{}', expected: \
+                     '/../noise-ik/<pubkey>/handshake/<version>'",
+                    addr
+                ),
+            )),
+        }
+    }
+
+    /// Dial a peer at `addr`. If the `addr` is not supported or formatted correctly,
+    /// return `Err`. Otherwise, return a `Future` that resolves to `Err` if there
+    /// was some issue dialing the peer and `Ok` with a fully upgraded connection
+    /// to that peer if our dial was successful.
+    ///
+    /// ### Dialing `NetworkAddress` format
+    ///
+    /// `/ip4/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>` or
+    /// `/ip6/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>` or
+    /// `/dns/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>` or
+    /// `/dns4/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>` or
+    /// `/dns6/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>`
+    pub fn dial(
+        &self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+    ) -> io::Result<impl Future<Output = io::Result<Connection<QuicSocket>>> + Send + 'static> {
+        let (base_addr, pubkey, handshake_version) = Self::parse_dial_addr(&addr)?;
+
+        // Check that the parsed handshake version from the dial addr is supported.
+        if self.ctxt.handshake_version != handshake_version {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Attempting to dial remote with unsupported handshake version: {}, expected: {}",
+                    handshake_version, self.ctxt.handshake_version,
+                ),
+            ));
+        }
+
+        // try to connect
+        let fut_connection = self.base_transport.dial(peer_id, base_addr)?;
+
+        // outbound dial upgrade task
+        let upgrade_fut = upgrade_outbound_quic(
+            self.ctxt.clone(),
+            fut_connection,
+            addr,
+            peer_id,
+            pubkey,
+            self.max_frame_size,
+        );
+        let upgrade_fut = timeout_io(self.time_service.clone(), TRANSPORT_TIMEOUT, upgrade_fut);
+        Ok(upgrade_fut)
+    }
+
+    /// Listen on address `addr`. If the `addr` is not supported or formatted correctly,
+    /// return `Err`. Otherwise, return a `Stream` of fully upgraded inbound connections
+    /// and the dialer's observed network address.
+    ///
+    /// ### Listening `NetworkAddress` format
+    ///
+    /// `/ip4/<ipaddr>/quic/<port>` or
+    /// `/ip6/<ipaddr>/quic/<port>`
+    pub fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> io::Result<(
+        impl Stream<
+                Item = io::Result<(
+                    impl Future<Output = io::Result<Connection<QuicSocket>>> + Send + 'static,
+                    NetworkAddress,
+                )>,
+            > + Send
+            + 'static,
+        NetworkAddress,
+    )> {
+        let (listener, listen_addr) = self.base_transport.listen_on(addr)?;
+        let listen_addr =
+            listen_addr.append_prod_protos(self.identity_pubkey, self.ctxt.handshake_version);
+
+        // need to move a ctxt into stream task
+        let ctxt = self.ctxt.clone();
+        let time_service = self.time_service.clone();
+        let max_frame_size = self.max_frame_size;
+        // stream of inbound upgrade tasks
+        let inbounds = listener.map_ok(move |(fut_connection, addr)| {
+            // inbound upgrade task
+            let fut_upgrade =
+                upgrade_inbound_quic(ctxt.clone(), fut_connection, addr.clone(), max_frame_size);
+            let fut_upgrade = timeout_io(time_service.clone(), TRANSPORT_TIMEOUT, fut_upgrade);
+            (fut_upgrade, addr)
+        });
+
+        Ok((inbounds, listen_addr))
+    }
+}
+
+impl Transport for AptosNetQuicTransport {
+    type Error = io::Error;
+    type Inbound = BoxFuture<'static, io::Result<Self::Output>>;
+    type Listener = BoxStream<'static, io::Result<(Self::Inbound, NetworkAddress)>>;
+    type Outbound = BoxFuture<'static, io::Result<Self::Output>>;
+    type Output = Connection<QuicSocket>;
+
+    fn dial(&self, peer_id: PeerId, addr: NetworkAddress) -> io::Result<Self::Outbound> {
+        self.dial(peer_id, addr)
+            .map(|upgrade_fut| upgrade_fut.boxed())
+    }
+
+    fn listen_on(&self, addr: NetworkAddress) -> io::Result<(Self::Listener, NetworkAddress)> {
+        let (listener, listen_addr) = self.listen_on(addr)?;
+        let listener = listener
+            .map_ok(|(upgrade_fut, addr)| (upgrade_fut.boxed(), addr))
+            .boxed();
+        Ok((listener, listen_addr))
+    }
+}
+
+/// Upgrade an inbound QUIC connection (see [`upgrade_inbound`]) and verify the
+/// channel binding of the connection.
+async fn upgrade_inbound_quic(
+    ctxt: Arc<UpgradeContext>,
+    fut_connection: impl Future<Output = io::Result<QuicConnection>>,
+    addr: NetworkAddress,
+    max_frame_size: usize,
+) -> io::Result<Connection<QuicSocket>> {
+    let quic_connection = fut_connection.await?;
+    let channel_binding = quic_connection.export_channel_binding()?;
+    let (quic_connection, control_stream) = quic_connection.into_parts();
+
+    let Connection { socket, metadata } =
+        upgrade_inbound(ctxt, future::ready(Ok(control_stream)), addr, false).await?;
+    let control_stream = exchange_channel_binding(socket, channel_binding).await?;
+
+    Ok(Connection {
+        socket: QuicSocket::new(quic_connection, control_stream, max_frame_size),
+        metadata,
+    })
+}
+
+/// Upgrade an outbound QUIC connection (see [`upgrade_outbound`]) and verify the
+/// channel binding of the connection.
+async fn upgrade_outbound_quic(
+    ctxt: Arc<UpgradeContext>,
+    fut_connection: impl Future<Output = io::Result<QuicConnection>>,
+    addr: NetworkAddress,
+    remote_peer_id: PeerId,
+    remote_pubkey: x25519::PublicKey,
+    max_frame_size: usize,
+) -> io::Result<Connection<QuicSocket>> {
+    let quic_connection = fut_connection.await?;
+    let channel_binding = quic_connection.export_channel_binding()?;
+    let (quic_connection, control_stream) = quic_connection.into_parts();
+
+    let Connection { socket, metadata } = upgrade_outbound(
+        ctxt,
+        future::ready(Ok(control_stream)),
+        addr,
+        remote_peer_id,
+        remote_pubkey,
+    )
+    .await?;
+    let control_stream = exchange_channel_binding(socket, channel_binding).await?;
+
+    Ok(Connection {
+        socket: QuicSocket::new(quic_connection, control_stream, max_frame_size),
+        metadata,
+    })
+}
+
+/// Exchanges the channel bindings over the Noise session and verifies that both
+/// peers observe the same TLS session. Otherwise, a man-in-the-middle could
+/// terminate the (unauthenticated) TLS session and relay the Noise handshake.
+///
+/// Once verified, the Noise session is no longer required (all streams are
+/// encrypted by QUIC), so the raw control stream is returned.
+async fn exchange_channel_binding(
+    mut socket: NoiseStream<QuicStream>,
+    channel_binding: [u8; CHANNEL_BINDING_SIZE],
+) -> io::Result<QuicStream> {
+    write_u16frame(&mut socket, &channel_binding).await?;
+    socket.flush().await?;
+
+    let mut remote_channel_binding = BytesMut::new();
+    read_u16frame(&mut socket, &mut remote_channel_binding).await?;
+    if remote_channel_binding.as_ref() != channel_binding.as_slice() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "The channel binding of the QUIC connection does not match the remote peer's!",
+        ));
+    }
+
+    Ok(socket.into_inner())
+}
+
+/// The QUIC stream on which an outbound frame is sent
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum FrameRoute {
+    /// The control stream (used for errors and any other frames without a protocol)
+    Control,
+    /// The stream dedicated to the application protocol
+    Protocol(ProtocolId),
+    /// The stream for all fragmented messages (as fragments must be delivered in order)
+    Fragmented,
+}
+
+impl FrameRoute {
+    /// Returns the header of the unidirectional stream of the route (the control
+    /// stream is the bidirectional stream opened by the dialer, so it has none).
+    fn stream_type(&self) -> Option<StreamType> {
+        match self {
+            FrameRoute::Control => None,
+            FrameRoute::Protocol(protocol_id) => Some(StreamType::Protocol(*protocol_id)),
+            FrameRoute::Fragmented => Some(StreamType::Fragmented),
+        }
+    }
+}
+
+/// The header sent at the start of each unidirectional stream
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+enum StreamType {
+    /// The stream carries the messages of a single application protocol
+    Protocol(ProtocolId),
+    /// The stream carries all fragmented messages
+    Fragmented,
+}
+
+/// The BCS encoded prefixes of the wire messages (see `MultiplexMessage`), used to
+/// route the outbound frames and to find the request ids of the inbound rpc
+/// requests without deserializing the (possibly large) payloads.
+struct WirePrefixes {
+    /// The prefixes of the rpc requests of each protocol (up to the request id)
+    rpc_requests: HashMap<ProtocolId, Vec<u8>>,
+    /// The prefixes of the direct send messages of each protocol
+    direct_sends: Vec<(Vec<u8>, ProtocolId)>,
+    /// The prefix of the rpc responses (up to the request id)
+    rpc_response: Vec<u8>,
+    /// The prefix of the fragmented messages
+    stream: Vec<u8>,
+}
+
+impl WirePrefixes {
+    fn new() -> Self {
+        // The empty payload is encoded as a single length byte
+        let rpc_requests = ProtocolId::all()
+            .iter()
+            .map(|protocol_id| {
+                let rpc_request = NetworkMessage::RpcRequest(RpcRequest {
+                    protocol_id: *protocol_id,
+                    request_id: 0,
+                    priority: 0,
+                    raw_request: vec![],
+                });
+                (*protocol_id, message_prefix(rpc_request, 4 + 1 + 1))
+            })
+            .collect();
+        let direct_sends = ProtocolId::all()
+            .iter()
+            .map(|protocol_id| {
+                let direct_send = NetworkMessage::DirectSendMsg(DirectSendMsg {
+                    protocol_id: *protocol_id,
+                    priority: 0,
+                    raw_msg: vec![],
+                });
+                (message_prefix(direct_send, 1 + 1), *protocol_id)
+            })
+            .collect();
+        let rpc_response = NetworkMessage::RpcResponse(RpcResponse {
+            request_id: 0,
+            priority: 0,
+            raw_response: vec![],
+        });
+        let fragment = MultiplexMessage::Stream(StreamMessage::Fragment(StreamFragment {
+            request_id: 0,
+            fragment_id: 0,
+            raw_data: vec![],
+        }));
+        let mut stream = bcs::to_bytes(&fragment).expect("Wire messages should serialize!");
+        stream.truncate(1);
+
+        Self {
+            rpc_requests,
+            direct_sends,
+            rpc_response: message_prefix(rpc_response, 4 + 1 + 1),
+            stream,
+        }
+    }
+
+    /// Returns the protocol of the message (if it is an rpc request or a direct send)
+    fn get_protocol(&self, message: &[u8]) -> Option<ProtocolId> {
+        self.rpc_requests
+            .iter()
+            .find(|(_, prefix)| message.starts_with(prefix))
+            .map(|(protocol_id, _)| *protocol_id)
+            .or_else(|| {
+                self.direct_sends
+                    .iter()
+                    .find(|(prefix, _)| message.starts_with(prefix))
+                    .map(|(_, protocol_id)| *protocol_id)
+            })
+    }
+
+    /// Returns the request id of the message if it is an rpc request of the protocol
+    fn get_rpc_request_id(&self, message: &[u8], protocol_id: ProtocolId) -> Option<RequestId> {
+        let prefix = self.rpc_requests.get(&protocol_id)?;
+        if !message.starts_with(prefix) {
+            return None;
+        }
+        read_request_id(&message[prefix.len()..])
+    }
+
+    /// Returns the request id of the message if it is an rpc response
+    fn get_rpc_response_id(&self, message: &[u8]) -> Option<RequestId> {
+        if !message.starts_with(&self.rpc_response) {
+            return None;
+        }
+        read_request_id(&message[self.rpc_response.len()..])
+    }
+
+    fn is_fragment(&self, message: &[u8]) -> bool {
+        message.starts_with(&self.stream)
+    }
+}
+
+/// Returns the BCS encoding of the wire message, without its last `num_trailing_bytes`
+fn message_prefix(message: NetworkMessage, num_trailing_bytes: usize) -> Vec<u8> {
+    let mut prefix = bcs::to_bytes(&MultiplexMessage::Message(message))
+        .expect("Wire messages should serialize!");
+    prefix.truncate(prefix.len() - num_trailing_bytes);
+    prefix
+}
+
+/// Reads the BCS encoded request id at the start of the bytes
+fn read_request_id(bytes: &[u8]) -> Option<RequestId> {
+    bytes
+        .get(..std::mem::size_of::<RequestId>())
+        .and_then(|bytes| bcs::from_bytes(bytes).ok())
+}
+
+/// The protocols of inbound rpc requests (so that the responses can be sent on
+/// the same stream as the requests of the protocol).
+#[derive(Debug)]
+struct InboundRpcRoutes(Mutex<BTreeMap<RequestId, ProtocolId>>);
+
+impl InboundRpcRoutes {
+    fn new() -> Self {
+        Self(Mutex::new(BTreeMap::new()))
+    }
+
+    fn insert(&self, request_id: RequestId, protocol_id: ProtocolId) {
+        let mut routes = self.0.lock();
+        routes.insert(request_id, protocol_id);
+
+        // Bound the memory used by requests that are never responded to
+        if routes.len() > MAX_TRACKED_INBOUND_RPCS {
+            if let Some(oldest_request_id) = routes.keys().next().copied() {
+                routes.remove(&oldest_request_id);
+            }
+        }
+    }
+
+    fn remove(&self, request_id: RequestId) -> Option<ProtocolId> {
+        self.0.lock().remove(&request_id)
+    }
+}
+
+/// A socket over an upgraded QUIC connection.
+///
+/// The socket expects the length-delimited [`MultiplexMessage`] frames written by
+/// the `Peer` actor, and routes each frame to a dedicated unidirectional stream
+/// based on its application protocol (rpc responses use the stream of the protocol
+/// of the request). Frames without a protocol are sent on the control stream.
+/// Inbound frames are read from all streams concurrently and interleaved at frame
+/// boundaries, so the socket can be read like any other AptosNet socket.
+///
+/// [`MultiplexMessage`]: crate::protocols::wire::messaging::v1::MultiplexMessage
+pub struct QuicSocket {
+    connection: quinn::Connection,
+    max_frame_size: usize,
+    /// The buffered bytes of the (partially written) outbound frame
+    write_buffer: BytesMut,
+    /// A complete outbound frame that is waiting for capacity on its stream
+    pending_frame: Option<(FrameRoute, Bytes)>,
+    /// The senders of the frames for each outbound stream
+    outbound_streams: HashMap<FrameRoute, mpsc::Sender<Bytes>>,
+    /// The tasks writing the frames to each outbound stream
+    outbound_stream_tasks: Vec<JoinHandle<()>>,
+    /// The complete frames read from all inbound streams
+    inbound_frames: mpsc::Receiver<Bytes>,
+    /// The remaining bytes of the inbound frame currently being read
+    read_buffer: Bytes,
+    inbound_rpc_routes: Arc<InboundRpcRoutes>,
+}
+
+impl QuicSocket {
+    fn new(
+        connection: quinn::Connection,
+        control_stream: QuicStream,
+        max_frame_size: usize,
+    ) -> Self {
+        let (control_send_stream, control_recv_stream) = control_stream.into_parts();
+        let inbound_rpc_routes = Arc::new(InboundRpcRoutes::new());
+
+        // Start reading the control stream and all streams opened by the remote peer
+        let (inbound_frames_tx, inbound_frames_rx) = mpsc::channel(INBOUND_FRAME_BUFFER_SIZE);
+        tokio::spawn(read_inbound_streams(
+            connection.clone(),
+            control_recv_stream,
+            inbound_frames_tx,
+            inbound_rpc_routes.clone(),
+            max_frame_size,
+        ));
+
+        let mut quic_socket = Self {
+            connection,
+            max_frame_size,
+            write_buffer: BytesMut::new(),
+            pending_frame: None,
+            outbound_streams: HashMap::new(),
+            outbound_stream_tasks: vec![],
+            inbound_frames: inbound_frames_rx,
+            read_buffer: Bytes::new(),
+            inbound_rpc_routes,
+        };
+        quic_socket.start_outbound_stream(FrameRoute::Control, Some(control_send_stream));
+        quic_socket
+    }
+
+    /// Returns the number of outbound streams (including the control stream)
+    pub fn num_outbound_streams(&self) -> usize {
+        self.outbound_streams.len()
+    }
+
+    /// Starts the task that writes the frames of the given route. If no send
+    /// stream is given, a new unidirectional stream is opened.
+    fn start_outbound_stream(&mut self, route: FrameRoute, send_stream: Option<quinn::SendStream>) {
+        let (outbound_frames_tx, outbound_frames_rx) = mpsc::channel(OUTBOUND_STREAM_BUFFER_SIZE);
+        let connection = self.connection.clone();
+        let outbound_stream_task = tokio::spawn(async move {
+            if let Err(error) =
+                write_frames(connection, route, send_stream, outbound_frames_rx).await
+            {
+                debug!(
+                    "Failed to write to the outbound QUIC stream! Route: {:?}, error: {}",
+                    route, error
+                );
+            }
+        });
+
+        self.outbound_streams.insert(route, outbound_frames_tx);
+        self.outbound_stream_tasks.push(outbound_stream_task);
+    }
+
+    /// Returns the number of bytes required to complete the length prefix or
+    /// (once the length prefix has been buffered) the outbound frame.
+    fn num_missing_frame_bytes(&self) -> io::Result<usize> {
+        let num_buffered_bytes = self.write_buffer.len();
+        if num_buffered_bytes < FRAME_LENGTH_SIZE {
+            return Ok(FRAME_LENGTH_SIZE - num_buffered_bytes);
+        }
+
+        let frame_length = get_frame_length(&self.write_buffer, self.max_frame_size)?;
+        Ok(FRAME_LENGTH_SIZE + frame_length - num_buffered_bytes)
+    }
+
+    /// Returns the route of the given outbound message
+    fn get_outbound_route(&self, message: &[u8]) -> FrameRoute {
+        let wire_prefixes = &*WIRE_PREFIXES;
+        if let Some(protocol_id) = wire_prefixes.get_protocol(message) {
+            FrameRoute::Protocol(protocol_id)
+        } else if let Some(request_id) = wire_prefixes.get_rpc_response_id(message) {
+            self.inbound_rpc_routes
+                .remove(request_id)
+                .map(FrameRoute::Protocol)
+                .unwrap_or(FrameRoute::Control)
+        } else if wire_prefixes.is_fragment(message) {
+            FrameRoute::Fragmented
+        } else {
+            FrameRoute::Control
+        }
+    }
+
+    /// Hands off the pending frame (if any) to the task of its outbound stream
+    fn poll_send_pending_frame(&mut self, context: &mut Context) -> Poll<io::Result<()>> {
+        let route = match &self.pending_frame {
+            Some((route, _)) => *route,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        // Lazily open the outbound stream
+        if !self.outbound_streams.contains_key(&route) {
+            self.start_outbound_stream(route, None);
+        }
+        let outbound_frames_tx = self
+            .outbound_streams
+            .get_mut(&route)
+            .expect("The outbound stream should exist!");
+
+        if ready!(outbound_frames_tx.poll_ready(context)).is_err() {
+            return Poll::Ready(Err(stream_closed_error(route)));
+        }
+        let (_, frame) = self
+            .pending_frame
+            .take()
+            .expect("The pending frame should exist!");
+        if outbound_frames_tx.start_send(frame).is_err() {
+            return Poll::Ready(Err(stream_closed_error(route)));
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for QuicSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "QuicSocket {{ remote_address: {}, num_outbound_streams: {} }}",
+            self.connection.remote_address(),
+            self.num_outbound_streams()
+        )
+    }
+}
+
+impl AsyncRead for QuicSocket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Fetch the next inbound frame once the previous one has been read
+        if this.read_buffer.is_empty() {
+            match ready!(this.inbound_frames.poll_next_unpin(context)) {
+                Some(frame) => this.read_buffer = frame,
+                // All inbound streams have been closed
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let num_bytes = cmp::min(buf.len(), this.read_buffer.len());
+        buf[..num_bytes].copy_from_slice(&this.read_buffer.split_to(num_bytes));
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl AsyncWrite for QuicSocket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Only accept more bytes once the previous frame has been handed off
+        ready!(this.poll_send_pending_frame(context))?;
+
+        // Buffer the bytes (up to the end of the length prefix or the frame)
+        let num_bytes = cmp::min(buf.len(), this.num_missing_frame_bytes()?);
+        this.write_buffer.extend_from_slice(&buf[..num_bytes]);
+
+        // Route the frame once it is complete
+        if this.write_buffer.len() >= FRAME_LENGTH_SIZE && this.num_missing_frame_bytes()? == 0 {
+            let frame = this.write_buffer.split().freeze();
+            let route = this.get_outbound_route(&frame[FRAME_LENGTH_SIZE..]);
+            this.pending_frame = Some((route, frame));
+
+            // Try to hand off the frame immediately (otherwise, it is handed
+            // off by the next write or flush).
+            if let Poll::Ready(Err(error)) = this.poll_send_pending_frame(context) {
+                return Poll::Ready(Err(error));
+            }
+        }
+
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        self.get_mut().poll_send_pending_frame(context)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_pending_frame(context))?;
+
+        // Finish all outbound streams and wait for the pending frames to be delivered
+        this.outbound_streams.clear();
+        this.outbound_stream_tasks
+            .retain_mut(|task| Pin::new(task).poll(context).is_pending());
+        if !this.outbound_stream_tasks.is_empty() {
+            return Poll::Pending;
+        }
+
+        this.connection.close(quinn::VarInt::from_u32(0), b"closed");
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for QuicSocket {
+    fn drop(&mut self) {
+        // Closing the connection terminates all stream tasks
+        self.connection
+            .close(quinn::VarInt::from_u32(0), b"socket dropped");
+    }
+}
+
+/// Reads the frames from the control stream and all unidirectional streams
+/// opened by the remote peer, until the connection is closed.
+async fn read_inbound_streams(
+    connection: quinn::Connection,
+    control_recv_stream: quinn::RecvStream,
+    inbound_frames_tx: mpsc::Sender<Bytes>,
+    inbound_rpc_routes: Arc<InboundRpcRoutes>,
+    max_frame_size: usize,
+) {
+    let mut stream_readers = FuturesUnordered::new();
+    stream_readers.push(
+        read_frames(
+            control_recv_stream,
+            None,
+            inbound_frames_tx.clone(),
+            inbound_rpc_routes.clone(),
+            max_frame_size,
+        )
+        .boxed(),
+    );
+
+    loop {
+        tokio::select! {
+            result = connection.accept_uni() => match result {
+                Ok(recv_stream) => stream_readers.push(
+                    read_stream(
+                        recv_stream,
+                        inbound_frames_tx.clone(),
+                        inbound_rpc_routes.clone(),
+                        max_frame_size,
+                    )
+                    .boxed(),
+                ),
+                Err(error) => {
+                    debug!(
+                        "Stopped accepting QUIC streams from {}! Error: {}",
+                        connection.remote_address(),
+                        error
+                    );
+                    break;
+                },
+            },
+            Some(result) = stream_readers.next(), if !stream_readers.is_empty() => {
+                if let Err(error) = result {
+                    debug!(
+                        "Failed to read from an inbound QUIC stream from {}! Error: {}",
+                        connection.remote_address(),
+                        error
+                    );
+                }
+            },
+        }
+    }
+
+    // Wait for the remaining streams to finish
+    while stream_readers.next().await.is_some() {}
+}
+
+/// Reads the header of a unidirectional stream, and then its frames
+async fn read_stream(
+    mut recv_stream: quinn::RecvStream,
+    inbound_frames_tx: mpsc::Sender<Bytes>,
+    inbound_rpc_routes: Arc<InboundRpcRoutes>,
+    max_frame_size: usize,
+) -> io::Result<()> {
+    let mut header_length = [0u8; 1];
+    recv_stream
+        .read_exact(&mut header_length)
+        .await
+        .map_err(to_io_error)?;
+    let header_length = header_length[0] as usize;
+    if header_length > MAX_STREAM_HEADER_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Stream header length {} exceeds the maximum header size {}",
+                header_length, MAX_STREAM_HEADER_SIZE
+            ),
+        ));
+    }
+    let mut header = vec![0u8; header_length];
+    recv_stream
+        .read_exact(&mut header)
+        .await
+        .map_err(to_io_error)?;
+    let protocol_id = match bcs::from_bytes(&header).map_err(to_io_error)? {
+        StreamType::Protocol(protocol_id) => Some(protocol_id),
+        StreamType::Fragmented => None,
+    };
+
+    read_frames(
+        recv_stream,
+        protocol_id,
+        inbound_frames_tx,
+        inbound_rpc_routes,
+        max_frame_size,
+    )
+    .await
+}
+
+/// Reads the length-delimited frames from the given stream (which carries the
+/// messages of the given protocol, if any).
+async fn read_frames(
+    mut recv_stream: quinn::RecvStream,
+    protocol_id: Option<ProtocolId>,
+    mut inbound_frames_tx: mpsc::Sender<Bytes>,
+    inbound_rpc_routes: Arc<InboundRpcRoutes>,
+    max_frame_size: usize,
+) -> io::Result<()> {
+    loop {
+        // Read the length prefix (a stream that finishes between frames ends cleanly)
+        let mut length_prefix = [0u8; FRAME_LENGTH_SIZE];
+        match recv_stream.read_exact(&mut length_prefix).await {
+            Ok(()) => {},
+            Err(quinn::ReadExactError::FinishedEarly) => return Ok(()),
+            Err(quinn::ReadExactError::ReadError(error)) => return Err(to_io_error(error)),
+        }
+        let frame_length = get_frame_length(&length_prefix, max_frame_size)?;
+
+        // Read the rest of the frame
+        let mut frame = BytesMut::with_capacity(FRAME_LENGTH_SIZE + frame_length);
+        frame.extend_from_slice(&length_prefix);
+        frame.resize(FRAME_LENGTH_SIZE + frame_length, 0);
+        recv_stream
+            .read_exact(&mut frame[FRAME_LENGTH_SIZE..])
+            .await
+            .map_err(to_io_error)?;
+
+        // Remember the protocol of rpc requests (so the response uses the same stream)
+        if let Some(protocol_id) = protocol_id {
+            if let Some(request_id) =
+                WIRE_PREFIXES.get_rpc_request_id(&frame[FRAME_LENGTH_SIZE..], protocol_id)
+            {
+                inbound_rpc_routes.insert(request_id, protocol_id);
+            }
+        }
+
+        // Hand off the frame to the socket (unless it has been dropped)
+        if inbound_frames_tx.send(frame.freeze()).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Writes the outbound frames to the given stream (or a newly opened
+/// unidirectional stream, after the header of the route), and finishes the
+/// stream once all frames are written.
+async fn write_frames(
+    connection: quinn::Connection,
+    route: FrameRoute,
+    send_stream: Option<quinn::SendStream>,
+    mut outbound_frames_rx: mpsc::Receiver<Bytes>,
+) -> io::Result<()> {
+    let mut send_stream = match send_stream {
+        Some(send_stream) => send_stream,
+        None => {
+            let stream_type = route
+                .stream_type()
+                .expect("The control stream is never opened lazily!");
+            let header = bcs::to_bytes(&stream_type).map_err(to_io_error)?;
+            let mut send_stream = connection.open_uni().await.map_err(to_io_error)?;
+            send_stream
+                .write_all(&[header.len() as u8])
+                .await
+                .map_err(to_io_error)?;
+            send_stream.write_all(&header).await.map_err(to_io_error)?;
+            send_stream
+        },
+    };
+
+    while let Some(frame) = outbound_frames_rx.next().await {
+        send_stream.write_all(&frame).await.map_err(to_io_error)?;
+    }
+    send_stream.finish().await.map_err(to_io_error)
+}
+
+/// Returns the length of the frame (as specified by its length prefix)
+fn get_frame_length(frame: &[u8], max_frame_size: usize) -> io::Result<usize> {
+    let mut length_prefix = [0u8; FRAME_LENGTH_SIZE];
+    length_prefix.copy_from_slice(&frame[..FRAME_LENGTH_SIZE]);
+    let frame_length = u32::from_be_bytes(length_prefix) as usize;
+    if frame_length > max_frame_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Frame length {} exceeds the maximum frame size {}",
+                frame_length, max_frame_size
+            ),
+        ));
+    }
+    Ok(frame_length)
+}
+
+fn stream_closed_error(route: FrameRoute) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        format!(
+            "The outbound QUIC stream has been closed! Route: {:?}",
+            route
+        ),
+    )
+}
+
+fn to_io_error<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+/// A socket for a connection established over either TCP or QUIC
+#[derive(Debug)]
+pub enum TcpOrQuicSocket {
+    Tcp(NoiseStream<TcpSocket>),
+    Quic(QuicSocket),
+}
+
+impl AsyncRead for TcpOrQuicSocket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TcpOrQuicSocket::Tcp(socket) => Pin::new(socket).poll_read(context, buf),
+            TcpOrQuicSocket::Quic(socket) => Pin::new(socket).poll_read(context, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TcpOrQuicSocket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TcpOrQuicSocket::Tcp(socket) => Pin::new(socket).poll_write(context, buf),
+            TcpOrQuicSocket::Quic(socket) => Pin::new(socket).poll_write(context, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpOrQuicSocket::Tcp(socket) => Pin::new(socket).poll_flush(context),
+            TcpOrQuicSocket::Quic(socket) => Pin::new(socket).poll_flush(context),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpOrQuicSocket::Tcp(socket) => Pin::new(socket).poll_close(context),
+            TcpOrQuicSocket::Quic(socket) => Pin::new(socket).poll_close(context),
+        }
+    }
+}
+
+/// A transport that supports both TCP and QUIC connections (to allow for mixed
+/// TCP/QUIC deployments).
+///
+/// Peers are dialed using the transport protocol of their `NetworkAddress` (i.e.,
+/// `/quic/<port>` addresses are dialed over QUIC and all others over TCP). When
+/// listening on `/ip4/<addr>/tcp/<port>`, the transport also listens for QUIC
+/// connections on `/ip4/<addr>/quic/<port>` (i.e., the same port, but over UDP).
+pub struct TcpOrQuicTransport {
+    tcp_transport: AptosNetTransport<TcpTransport>,
+    quic_transport: AptosNetQuicTransport,
+}
+
+impl TcpOrQuicTransport {
+    /// Creates a new transport from the given TCP transport (the QUIC transport
+    /// shares the same upgrade context, e.g., identity and authentication mode).
+    pub fn new(
+        tcp_transport: AptosNetTransport<TcpTransport>,
+        quic_base_transport: QuicTransport,
+        max_frame_size: usize,
+    ) -> Self {
+        let quic_transport = AptosNetQuicTransport {
+            base_transport: quic_base_transport,
+            ctxt: tcp_transport.ctxt.clone(),
+            time_service: tcp_transport.time_service.clone(),
+            identity_pubkey: tcp_transport.identity_pubkey,
+            max_frame_size,
+        };
+
+        Self {
+            tcp_transport,
+            quic_transport,
+        }
+    }
+}
+
+impl Transport for TcpOrQuicTransport {
+    type Error = io::Error;
+    type Inbound = BoxFuture<'static, io::Result<Self::Output>>;
+    type Listener = BoxStream<'static, io::Result<(Self::Inbound, NetworkAddress)>>;
+    type Outbound = BoxFuture<'static, io::Result<Self::Output>>;
+    type Output = Connection<TcpOrQuicSocket>;
+
+    fn dial(&self, peer_id: PeerId, addr: NetworkAddress) -> io::Result<Self::Outbound> {
+        let protos = addr.as_slice();
+        if parse_ip_quic(protos).is_some() || parse_dns_quic(protos).is_some() {
+            let upgrade_fut = self.quic_transport.dial(peer_id, addr)?;
+            Ok(upgrade_fut.map_ok(into_quic_connection).boxed())
+        } else {
+            let upgrade_fut = self.tcp_transport.dial(peer_id, addr)?;
+            Ok(upgrade_fut.map_ok(into_tcp_connection).boxed())
+        }
+    }
+
+    fn listen_on(&self, addr: NetworkAddress) -> io::Result<(Self::Listener, NetworkAddress)> {
+        let (tcp_listener, listen_addr) = self.tcp_transport.listen_on(addr)?;
+
+        // Listen for QUIC connections on the same address and port
+        let ((ipaddr, port), _) = parse_ip_tcp(listen_addr.as_slice()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unexpected TCP listen address: {}", listen_addr),
+            )
+        })?;
+        let quic_addr =
+            NetworkAddress::from_protocols(vec![Protocol::from(ipaddr), Protocol::Quic(port)])
+                .expect("An ip followed by a transport protocol is a valid network address");
+        let (quic_listener, quic_listen_addr) = self.quic_transport.listen_on(quic_addr)?;
+        info!(
+            "{} Listening for QUIC connections on {}",
+            self.quic_transport.ctxt.noise.network_context, quic_listen_addr
+        );
+
+        let tcp_listener = tcp_listener
+            .map_ok(|(upgrade_fut, addr)| (upgrade_fut.map_ok(into_tcp_connection).boxed(), addr));
+        let quic_listener = quic_listener
+            .map_ok(|(upgrade_fut, addr)| (upgrade_fut.map_ok(into_quic_connection).boxed(), addr));
+        Ok((
+            stream::select(tcp_listener, quic_listener).boxed(),
+            listen_addr,
+        ))
+    }
+}
+
+fn into_tcp_connection(
+    connection: Connection<NoiseStream<TcpSocket>>,
+) -> Connection<TcpOrQuicSocket> {
+    Connection {
+        socket: TcpOrQuicSocket::Tcp(connection.socket),
+        metadata: connection.metadata,
+    }
+}
+
+fn into_quic_connection(connection: Connection<QuicSocket>) -> Connection<TcpOrQuicSocket> {
+    Connection {
+        socket: TcpOrQuicSocket::Quic(connection.socket),
+        metadata: connection.metadata,
+    }
+}
@@ -3,16 +3,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    protocols::wire::handshake::v1::{MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+    protocols::wire::{
+        handshake::v1::{MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+        messaging::v1::{
+            DirectSendMsg, MultiplexMessage, MultiplexMessageSink, MultiplexMessageStream,
+            NetworkMessage, RpcRequest, RpcResponse,
+        },
+    },
     testutils,
-    transport::*,
+    transport::{
+        quic::{TcpOrQuicSocket, TcpOrQuicTransport},
+        *,
+    },
 };
-use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
+use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION, MAX_FRAME_SIZE};
 use aptos_crypto::{test_utils::TEST_SEED, traits::Uniform, x25519};
 use aptos_infallible::RwLock;
 use aptos_netcore::{
     framing::{read_u16frame, write_u16frame},
-    transport::{memory, ConnectionOrigin, Transport},
+    transport::{memory, quic::QuicTransport, ConnectionOrigin, Transport},
 };
 use aptos_time_service::MockTimeService;
 use aptos_types::{
@@ -21,7 +30,7 @@ use aptos_types::{
     PeerId,
 };
 use bytes::{Bytes, BytesMut};
-use futures::{future, io::AsyncWriteExt, stream::StreamExt};
+use futures::{future, io::AsyncWriteExt, sink::SinkExt, stream::StreamExt};
 use rand::{rngs::StdRng, SeedableRng};
 use std::{io, iter::FromIterator, sync::Arc};
 use tokio::runtime::Runtime;
@@ -581,3 +590,267 @@ fn test_tcp_transport_rejects_unauthed_dialer() {
         expect_ip4_tcp_noise_addr,
     );
 }
+
+////////////////////////
+// TcpOrQuicTransport //
+////////////////////////
+
+fn setup_tcp_or_quic(
+    auth: Auth,
+) -> (
+    Runtime,
+    (PeerId, TcpOrQuicTransport),
+    (PeerId, TcpOrQuicTransport),
+    Arc<RwLock<PeerSet>>,
+    ProtocolIdSet,
+) {
+    let (
+        rt,
+        _mock_time,
+        (listener_peer_id, listener_transport),
+        (dialer_peer_id, dialer_transport),
+        trusted_peers,
+        supported_protocols,
+    ) = setup(APTOS_TCP_TRANSPORT.clone(), auth);
+
+    let listener_transport = TcpOrQuicTransport::new(
+        listener_transport,
+        QuicTransport::new().unwrap(),
+        MAX_FRAME_SIZE,
+    );
+    let dialer_transport = TcpOrQuicTransport::new(
+        dialer_transport,
+        QuicTransport::new().unwrap(),
+        MAX_FRAME_SIZE,
+    );
+
+    (
+        rt,
+        (listener_peer_id, listener_transport),
+        (dialer_peer_id, dialer_transport),
+        trusted_peers,
+        supported_protocols,
+    )
+}
+
+/// Converts the given TCP address into the equivalent QUIC address (i.e., the
+/// address of the QUIC listener started by the `TcpOrQuicTransport`)
+fn to_quic_addr(addr: &NetworkAddress) -> NetworkAddress {
+    let protocols = addr
+        .as_slice()
+        .iter()
+        .map(|protocol| match protocol {
+            Tcp(port) => Quic(*port),
+            protocol => protocol.clone(),
+        })
+        .collect();
+    NetworkAddress::from_protocols(protocols).unwrap()
+}
+
+/// Check that the network address matches the format
+/// `"/ip4/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>"`
+fn expect_ip4_quic_noise_addr(addr: &NetworkAddress) {
+    assert!(
+        matches!(addr.as_slice(), [Ip4(_), Quic(_), NoiseIK(_), Handshake(_)]),
+        "addr: '{}'",
+        addr
+    );
+}
+
+/// Returns the number of outbound QUIC streams of the given socket
+fn num_outbound_quic_streams(socket: &TcpOrQuicSocket) -> usize {
+    match socket {
+        TcpOrQuicSocket::Quic(socket) => socket.num_outbound_streams(),
+        TcpOrQuicSocket::Tcp(_) => panic!("Expected a QUIC socket!"),
+    }
+}
+
+#[test]
+fn test_quic_transport_mutual_auth() {
+    let (
+        rt,
+        (listener_peer_id, listener_transport),
+        (dialer_peer_id, dialer_transport),
+        _trusted_peers,
+        supported_protocols,
+    ) = setup_tcp_or_quic(Auth::Mutual);
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+        .unwrap();
+    expect_ip4_tcp_noise_addr(&listener_addr);
+    let quic_listener_addr = to_quic_addr(&listener_addr);
+    let supported_protocols_clone = supported_protocols.clone();
+
+    let rpc_request = MultiplexMessage::Message(NetworkMessage::RpcRequest(RpcRequest {
+        protocol_id: ProtocolId::ConsensusRpcBcs,
+        request_id: 7,
+        priority: 0,
+        raw_request: b"request".to_vec(),
+    }));
+    let direct_send = MultiplexMessage::Message(NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::DiscoveryDirectSend,
+        priority: 0,
+        raw_msg: b"direct send".to_vec(),
+    }));
+    let rpc_response = MultiplexMessage::Message(NetworkMessage::RpcResponse(RpcResponse {
+        request_id: 7,
+        priority: 0,
+        raw_response: b"response".to_vec(),
+    }));
+    let expected_messages = vec![rpc_request.clone(), direct_send.clone()];
+    let expected_response = rpc_response.clone();
+
+    // accept the dialer's QUIC connection, check the connection metadata and
+    // verify that messages are exchanged over the per-protocol streams.
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        let mut conn = inbound.await.unwrap();
+
+        // check connection metadata
+        assert_eq!(conn.metadata.remote_peer_id, dialer_peer_id);
+        expect_ip4_quic_noise_addr(&conn.metadata.addr);
+        assert_eq!(conn.metadata.origin, ConnectionOrigin::Inbound);
+        assert_eq!(
+            conn.metadata.application_protocols,
+            supported_protocols_clone,
+        );
+
+        // read the messages (these are sent on different streams, so the
+        // order is not guaranteed)
+        let mut message_stream = MultiplexMessageStream::new(&mut conn.socket, MAX_FRAME_SIZE);
+        let mut messages = vec![
+            message_stream.next().await.unwrap().unwrap(),
+            message_stream.next().await.unwrap().unwrap(),
+        ];
+        messages.sort_by_key(|message| bcs::to_bytes(message).unwrap());
+        let mut expected_messages = expected_messages;
+        expected_messages.sort_by_key(|message| bcs::to_bytes(message).unwrap());
+        assert_eq!(messages, expected_messages);
+
+        // respond to the rpc (on the stream of the rpc protocol)
+        let mut message_sink = MultiplexMessageSink::new(&mut conn.socket, MAX_FRAME_SIZE);
+        message_sink.send(&rpc_response).await.unwrap();
+        assert_eq!(num_outbound_quic_streams(&conn.socket), 2);
+
+        // wait for the dialer to close the connection
+        let mut message_stream = MultiplexMessageStream::new(&mut conn.socket, MAX_FRAME_SIZE);
+        assert!(message_stream.next().await.is_none());
+    };
+
+    // dial the listener over QUIC, send the messages and wait for the response
+    let dialer_task = async move {
+        let mut conn = dialer_transport
+            .dial(listener_peer_id, quic_listener_addr.clone())
+            .unwrap()
+            .await
+            .unwrap();
+
+        // check connection metadata
+        assert_eq!(conn.metadata.remote_peer_id, listener_peer_id);
+        assert_eq!(conn.metadata.addr, quic_listener_addr);
+        assert_eq!(conn.metadata.origin, ConnectionOrigin::Outbound);
+        assert_eq!(conn.metadata.application_protocols, supported_protocols);
+
+        // send the messages (one stream per protocol and the control stream)
+        let mut message_sink = MultiplexMessageSink::new(&mut conn.socket, MAX_FRAME_SIZE);
+        message_sink.send(&rpc_request).await.unwrap();
+        message_sink.send(&direct_send).await.unwrap();
+        assert_eq!(num_outbound_quic_streams(&conn.socket), 3);
+
+        // read the response
+        let mut message_stream = MultiplexMessageStream::new(&mut conn.socket, MAX_FRAME_SIZE);
+        assert_eq!(
+            message_stream.next().await.unwrap().unwrap(),
+            expected_response
+        );
+        conn.socket.close().await.unwrap();
+    };
+
+    rt.block_on(future::join(listener_task, dialer_task));
+}
+
+#[test]
+fn test_tcp_or_quic_transport_dials_tcp() {
+    let (
+        rt,
+        (listener_peer_id, listener_transport),
+        (dialer_peer_id, dialer_transport),
+        _trusted_peers,
+        _supported_protocols,
+    ) = setup_tcp_or_quic(Auth::Mutual);
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+        .unwrap();
+
+    // accept the dialer's TCP connection and verify the socket works
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        let mut conn = inbound.await.unwrap();
+        assert_eq!(conn.metadata.remote_peer_id, dialer_peer_id);
+        expect_ip4_tcp_noise_addr(&conn.metadata.addr);
+        assert!(matches!(conn.socket, TcpOrQuicSocket::Tcp(_)));
+
+        let msg = write_read_msg(&mut conn.socket, b"foobar").await;
+        assert_eq!(&msg, b"barbaz".as_ref());
+        conn.socket.close().await.unwrap();
+    };
+
+    // dial the listener's TCP address and verify the socket works
+    let dialer_task = async move {
+        let mut conn = dialer_transport
+            .dial(listener_peer_id, listener_addr.clone())
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(conn.metadata.addr, listener_addr);
+        assert!(matches!(conn.socket, TcpOrQuicSocket::Tcp(_)));
+
+        let msg = write_read_msg(&mut conn.socket, b"barbaz").await;
+        assert_eq!(&msg, b"foobar".as_ref());
+        conn.socket.close().await.unwrap();
+    };
+
+    rt.block_on(future::join(listener_task, dialer_task));
+}
+
+#[test]
+fn test_quic_transport_rejects_unauthed_dialer() {
+    let (
+        rt,
+        (listener_peer_id, listener_transport),
+        (dialer_peer_id, dialer_transport),
+        trusted_peers,
+        _supported_protocols,
+    ) = setup_tcp_or_quic(Auth::Mutual);
+
+    // remove dialer from trusted_peers set
+    trusted_peers.write().remove(&dialer_peer_id).unwrap();
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+        .unwrap();
+    let quic_listener_addr = to_quic_addr(&listener_addr);
+
+    // the connection upgrade should fail because the dialer is not authenticated
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        inbound
+            .await
+            .expect_err("should fail because the dialer is not a trusted peer");
+    };
+
+    let dialer_task = async move {
+        dialer_transport
+            .dial(listener_peer_id, quic_listener_addr)
+            .unwrap()
+            .await
+            .expect_err("should fail because listener rejects our unauthed connection");
+    };
+
+    rt.block_on(future::join(listener_task, dialer_task));
+}
@@ -15,7 +15,15 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
+//!
+//! Optionally, the client and server can mutually authenticate and encrypt the stream with Noise
+//! (see the `noise` module), in which case the client only attempts to reconnect for the duration
+//! of the timeout, and the server rejects the clients that it doesn't trust.
+
+mod noise;
 
+pub use crate::noise::{NoiseClientConfig, NoiseServerConfig};
+use aptos_crypto::{noise::NoiseSession, x25519};
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
@@ -23,7 +31,8 @@ use serde::Serialize;
 use std::{
     io::{Read, Write},
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
-    thread, time,
+    thread,
+    time::{self, Instant},
 };
 use thiserror::Error;
 
@@ -56,6 +65,7 @@ enum LogEvent {
     ConnectionAttempt,
     ConnectionSuccessful,
     ConnectionFailed,
+    HandshakeFailed,
     DisconnectedPeerOnRead,
     DisconnectedPeerOnWrite,
     Shutdown,
@@ -139,14 +149,20 @@ pub enum Error {
     AlreadyShutdown,
     #[error("Found data that is too large to decode: {0}")]
     DataTooLarge(usize),
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
     #[error("Internal network error:")]
     NetworkError(#[from] std::io::Error),
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] aptos_crypto::noise::NoiseError),
     #[error("No active stream")]
     NoActiveStream,
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Peer with untrusted public key: {0}")]
+    UntrustedPeer(x25519::PublicKey),
 }
 
 pub struct NetworkClient {
@@ -155,6 +171,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseClientConfig>,
 }
 
 impl NetworkClient {
@@ -164,6 +181,20 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a client that authenticates and encrypts the stream with Noise
+    pub fn new_with_noise(
+        service: &'static str,
+        server: SocketAddr,
+        timeout_ms: u64,
+        noise: NoiseClientConfig,
+    ) -> Self {
+        Self {
+            noise: Some(noise),
+            ..Self::new(service, server, timeout_ms)
         }
     }
 
@@ -239,26 +270,47 @@ impl NetworkClient {
             .remote_peer(&self.server));
 
             let timeout = std::time::Duration::from_millis(self.timeout_ms);
+            let deadline = Instant::now() + timeout;
             let mut stream = TcpStream::connect_timeout(&self.server, timeout);
 
             let sleeptime = time::Duration::from_millis(100);
             while let Err(err) = stream {
                 self.increment_counter(Method::Connect, MethodResult::Failure);
+                let err: Error = err.into();
                 warn!(SecureNetLogSchema::new(
                     self.service,
                     NetworkMode::Client,
                     LogEvent::ConnectionFailed,
                 )
-                .error(&err.into())
+                .error(&err)
                 .remote_peer(&self.server));
 
+                // Authenticated clients give up once the timeout expires, so that their callers
+                // can time out their requests
+                if self.noise.is_some() && Instant::now() >= deadline {
+                    return Err(err);
+                }
                 thread::sleep(sleeptime);
                 stream = TcpStream::connect_timeout(&self.server, timeout);
             }
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise) = &self.noise {
+                if let Err(err) = noise.handshake(self.service, &mut stream) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Client,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -278,6 +330,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseServerConfig>,
 }
 
 impl NetworkServer {
@@ -288,6 +341,21 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a server that only accepts clients authenticated with Noise, and encrypts the
+    /// stream
+    pub fn new_with_noise(
+        service: &'static str,
+        listen: SocketAddr,
+        timeout_ms: u64,
+        noise: NoiseServerConfig,
+    ) -> Self {
+        Self {
+            noise: Some(noise),
+            ..Self::new(service, listen, timeout_ms)
         }
     }
 
@@ -391,6 +459,22 @@ impl NetworkServer {
                 },
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise) = &self.noise {
+                if let Err(err) = noise.handshake(self.service, &mut stream) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Server,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&stream_addr));
+                    return Err(err);
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -398,9 +482,7 @@ impl NetworkServer {
                 LogEvent::ConnectionSuccessful,
             )
             .remote_peer(&stream_addr));
-
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
@@ -412,6 +494,10 @@ struct NetworkStream {
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// The maximum size of a block that can be read from the stream
+    max_block_size: usize,
+    /// The Noise session used to encrypt the messages, if the stream is authenticated
+    session: Option<NoiseSession>,
 }
 
 impl NetworkStream {
@@ -426,12 +512,44 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            max_block_size: u32::max_value() as usize,
+            session: None,
         }
     }
 
     /// Blocking read until able to successfully read an entire message
     pub fn read(&mut self) -> Result<Vec<u8>, Error> {
-        let result = self.read_buffer();
+        if self.session.is_none() {
+            return self.read_block();
+        }
+
+        let mut message = self.read_encrypted_block()?;
+        let message_len = noise::message_len(&message)?;
+        while message.len() < message_len {
+            message.extend(self.read_encrypted_block()?);
+        }
+        if message.len() > message_len {
+            return Err(Error::InvalidMessage(format!(
+                "Expected {} bytes, received {}",
+                message_len,
+                message.len()
+            )));
+        }
+        Ok(noise::strip_length_prefix(message))
+    }
+
+    /// Blocking read until able to successfully read and decrypt an entire block
+    fn read_encrypted_block(&mut self) -> Result<Vec<u8>, Error> {
+        let mut block = self.read_block()?;
+        let session = self.session.as_mut().ok_or(Error::NoActiveStream)?;
+        let plaintext_len = session.read_message_in_place(&mut block)?.len();
+        block.truncate(plaintext_len);
+        Ok(block)
+    }
+
+    /// Blocking read until able to successfully read an entire block
+    fn read_block(&mut self) -> Result<Vec<u8>, Error> {
+        let result = self.read_buffer()?;
         if !result.is_empty() {
             return Ok(result);
         }
@@ -444,7 +562,7 @@ impl NetworkStream {
                 return Err(Error::RemoteStreamClosed);
             }
             self.buffer.extend(self.temp_buffer[..read].to_vec());
-            let result = self.read_buffer();
+            let result = self.read_buffer()?;
             if !result.is_empty() {
                 trace!("Found a message in the stream");
                 return Ok(result);
@@ -460,6 +578,25 @@ impl NetworkStream {
 
     /// Blocking write until able to successfully send an entire message
     pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let session = match self.session.as_mut() {
+            Some(session) => session,
+            None => return self.write_block(data),
+        };
+
+        let mut blocks = Vec::new();
+        for mut chunk in noise::split_message(data)? {
+            let authentication_tag = session.write_message_in_place(&mut chunk)?;
+            chunk.extend(authentication_tag);
+            blocks.push(chunk);
+        }
+        for block in blocks {
+            self.write_block(&block)?;
+        }
+        Ok(())
+    }
+
+    /// Blocking write until able to successfully send an entire block
+    fn write_block(&mut self, data: &[u8]) -> Result<(), Error> {
         let u32_max = u32::max_value() as usize;
         if u32_max <= data.len() {
             return Err(Error::DataTooLarge(data.len()));
@@ -479,23 +616,26 @@ impl NetworkStream {
     /// Data sent on a TCP socket may not necessarily be delivered at the exact time. So a read may
     /// only include a subset of what was sent. This wraps around the TCP read buffer to ensure
     /// that only full messages are received.
-    fn read_buffer(&mut self) -> Vec<u8> {
+    fn read_buffer(&mut self) -> Result<Vec<u8>, Error> {
         if self.buffer.len() < 4 {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         let mut u32_bytes = [0; 4];
         u32_bytes.copy_from_slice(&self.buffer[..4]);
         let data_size = u32::from_le_bytes(u32_bytes) as usize;
+        if data_size > self.max_block_size {
+            return Err(Error::DataTooLarge(data_size));
+        }
 
         let remaining_data = &self.buffer[4..];
         if remaining_data.len() < data_size {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         let returnable_data = remaining_data[..data_size].to_vec();
         self.buffer = remaining_data[data_size..].to_vec();
-        returnable_data
+        Ok(returnable_data)
     }
 
     /// Writing to a TCP socket will take in as much data as the underlying buffer has space for.
@@ -519,7 +659,11 @@ impl NetworkStream {
 mod test {
     use super::*;
     use aptos_config::utils;
-    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use aptos_crypto::Uniform;
+    use std::{
+        collections::HashSet,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+    };
 
     /// Read, Write, Connect timeout in milliseconds.
     const TIMEOUT: u64 = 5_000;
@@ -657,4 +801,92 @@ mod test {
         let result2 = server2.read().unwrap();
         assert_eq!(data2, result2);
     }
+
+    /// Creates a server and a client authenticated with Noise, where the server trusts the client
+    /// iff `trusted` is set, and the client knows the public key of the server iff
+    /// `known_server` is set.
+    fn noise_server_and_client(
+        trusted: bool,
+        known_server: bool,
+    ) -> (NetworkServer, NetworkClient) {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+
+        let client_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let mut trusted_peers = HashSet::new();
+        if trusted {
+            trusted_peers.insert(client_key.public_key());
+        }
+        let server_noise = NoiseServerConfig::new(
+            x25519::PrivateKey::generate(&mut rand::rngs::OsRng),
+            trusted_peers,
+        );
+        let server_public_key = if known_server {
+            server_noise.public_key()
+        } else {
+            x25519::PrivateKey::generate(&mut rand::rngs::OsRng).public_key()
+        };
+        let client_noise = NoiseClientConfig::new(client_key, server_public_key);
+
+        let server = NetworkServer::new_with_noise("test", server_addr, TIMEOUT, server_noise);
+        let client = NetworkClient::new_with_noise("test", server_addr, TIMEOUT, client_noise);
+        (server, client)
+    }
+
+    #[test]
+    fn test_noise_ping() {
+        let (mut server, mut client) = noise_server_and_client(true, true);
+
+        // The handshake requires the server to respond, so it runs on its own thread
+        let server_thread = thread::spawn(move || {
+            for _ in 0..2 {
+                let request = server.read().unwrap();
+                server.write(&request).unwrap();
+            }
+        });
+
+        let data = vec![0, 1, 2, 3];
+        client.write(&data).unwrap();
+        assert_eq!(data, client.read().unwrap());
+
+        // Messages larger than a Noise message are split into several encrypted blocks
+        let data: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        client.write(&data).unwrap();
+        assert_eq!(data, client.read().unwrap());
+
+        server_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_noise_untrusted_client() {
+        let (mut server, mut client) = noise_server_and_client(false, true);
+
+        let server_thread = thread::spawn(move || server.read());
+        client.write(&[0, 1, 2, 3]).unwrap_err();
+        assert!(matches!(
+            server_thread.join().unwrap(),
+            Err(Error::UntrustedPeer(_))
+        ));
+    }
+
+    #[test]
+    fn test_noise_unknown_server() {
+        let (mut server, mut client) = noise_server_and_client(true, false);
+
+        let server_thread = thread::spawn(move || server.read());
+        client.write(&[0, 1, 2, 3]).unwrap_err();
+        assert!(matches!(
+            server_thread.join().unwrap(),
+            Err(Error::NoiseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_noise_client_connect_timeout() {
+        let (server, mut client) = noise_server_and_client(true, true);
+        drop(server);
+
+        // Authenticated clients stop reconnecting once the timeout expires
+        client.write(&[0, 1, 2, 3]).unwrap_err();
+    }
 }
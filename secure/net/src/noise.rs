@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mutual authentication and encryption of the network streams, based on the Noise IK handshake
+//! implemented in `aptos_crypto::noise`. The client knows the static public key of the server ahead
+//! of time, and the server only accepts clients with a trusted static public key. The service name
+//! is used as the prologue of the handshake, so that a session can't be used for another service.
+//!
+//! After the handshake, each message is prefixed by its length and split into chunks that fit
+//! within a single Noise message. Each chunk is encrypted and sent as a block of the stream.
+
+use crate::{Error, NetworkStream};
+use aptos_crypto::{
+    noise::{self, NoiseConfig},
+    x25519,
+};
+use rand::rngs::OsRng;
+use std::{collections::HashSet, convert::TryFrom, sync::Arc};
+
+/// The size of the length prefix of the messages
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// The maximum size of a chunk of a message, so that it fits within a Noise message once encrypted
+const MAX_CHUNK_SIZE: usize = noise::decrypted_len(noise::MAX_SIZE_NOISE_MSG);
+
+/// The Noise configuration of a client: its static key and the static public key of the server
+#[derive(Clone)]
+pub struct NoiseClientConfig {
+    noise_config: Arc<NoiseConfig>,
+    server_public_key: x25519::PublicKey,
+}
+
+impl NoiseClientConfig {
+    pub fn new(private_key: x25519::PrivateKey, server_public_key: x25519::PublicKey) -> Self {
+        Self {
+            noise_config: Arc::new(NoiseConfig::new(private_key)),
+            server_public_key,
+        }
+    }
+
+    pub fn public_key(&self) -> x25519::PublicKey {
+        self.noise_config.public_key()
+    }
+
+    /// Returns the configuration authenticating with the same static key to another server
+    pub fn with_server_public_key(&self, server_public_key: x25519::PublicKey) -> Self {
+        Self {
+            noise_config: self.noise_config.clone(),
+            server_public_key,
+        }
+    }
+
+    /// Initiates the handshake with the server, which authenticates both ends of the stream
+    pub(crate) fn handshake(
+        &self,
+        service: &'static str,
+        stream: &mut NetworkStream,
+    ) -> Result<(), Error> {
+        stream.max_block_size = noise::MAX_SIZE_NOISE_MSG;
+
+        let mut init_message = vec![0; noise::handshake_init_msg_len(0)];
+        let handshake_state = self.noise_config.initiate_connection(
+            &mut OsRng,
+            service.as_bytes(),
+            self.server_public_key,
+            None,
+            &mut init_message,
+        )?;
+        stream.write_block(&init_message)?;
+
+        let response = stream.read_block()?;
+        let (_, session) = self
+            .noise_config
+            .finalize_connection(handshake_state, &response)?;
+        stream.session = Some(session);
+        Ok(())
+    }
+}
+
+/// The Noise configuration of a server: its static key and the static public keys of the clients
+/// that are allowed to connect
+#[derive(Clone)]
+pub struct NoiseServerConfig {
+    noise_config: Arc<NoiseConfig>,
+    trusted_peers: Arc<HashSet<x25519::PublicKey>>,
+}
+
+impl NoiseServerConfig {
+    pub fn new(private_key: x25519::PrivateKey, trusted_peers: HashSet<x25519::PublicKey>) -> Self {
+        Self {
+            noise_config: Arc::new(NoiseConfig::new(private_key)),
+            trusted_peers: Arc::new(trusted_peers),
+        }
+    }
+
+    pub fn public_key(&self) -> x25519::PublicKey {
+        self.noise_config.public_key()
+    }
+
+    /// Responds to the handshake of a client, which is rejected unless its static public key is
+    /// trusted
+    pub(crate) fn handshake(
+        &self,
+        service: &'static str,
+        stream: &mut NetworkStream,
+    ) -> Result<(), Error> {
+        stream.max_block_size = noise::MAX_SIZE_NOISE_MSG;
+
+        let init_message = stream.read_block()?;
+        let (remote_public_key, handshake_state, _) = self
+            .noise_config
+            .parse_client_init_message(service.as_bytes(), &init_message)?;
+        if !self.trusted_peers.contains(&remote_public_key) {
+            return Err(Error::UntrustedPeer(remote_public_key));
+        }
+
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session = self.noise_config.respond_to_client(
+            &mut OsRng,
+            handshake_state,
+            None,
+            &mut response,
+        )?;
+        stream.write_block(&response)?;
+        stream.session = Some(session);
+        Ok(())
+    }
+}
+
+/// Prefixes the message by its length and splits it into chunks that fit within a Noise message
+pub(crate) fn split_message(data: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let data_len = u32::try_from(data.len()).map_err(|_| Error::DataTooLarge(data.len()))?;
+    let mut message = Vec::with_capacity(LENGTH_PREFIX_SIZE + data.len());
+    message.extend_from_slice(&data_len.to_le_bytes());
+    message.extend_from_slice(data);
+    Ok(message
+        .chunks(MAX_CHUNK_SIZE)
+        .map(|chunk| chunk.to_vec())
+        .collect())
+}
+
+/// Returns the length of the message, including its length prefix, from its first chunk
+pub(crate) fn message_len(first_chunk: &[u8]) -> Result<usize, Error> {
+    if first_chunk.len() < LENGTH_PREFIX_SIZE {
+        return Err(Error::InvalidMessage(format!(
+            "Chunk of {} bytes is too short to contain the message length",
+            first_chunk.len()
+        )));
+    }
+    let mut u32_bytes = [0; LENGTH_PREFIX_SIZE];
+    u32_bytes.copy_from_slice(&first_chunk[..LENGTH_PREFIX_SIZE]);
+    Ok(LENGTH_PREFIX_SIZE + u32::from_le_bytes(u32_bytes) as usize)
+}
+
+/// Removes the length prefix of a fully received message
+pub(crate) fn strip_length_prefix(mut message: Vec<u8>) -> Vec<u8> {
+    message.split_off(LENGTH_PREFIX_SIZE)
+}
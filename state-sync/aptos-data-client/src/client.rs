@@ -30,17 +30,21 @@ use aptos_storage_service_types::{
     requests::{
         DataRequest, EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
         NewTransactionsOrOutputsWithProofRequest, NewTransactionsWithProofRequest,
-        StateValuesWithProofRequest, StorageServiceRequest, TransactionOutputsWithProofRequest,
-        TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
+        StateValuesByKeysWithProofRequest, StateValuesWithProofRequest, StorageServiceRequest,
+        TransactionOutputsWithProofRequest, TransactionsOrOutputsWithProofRequest,
+        TransactionsWithProofRequest,
+    },
+    responses::{
+        StateValuesByKeysWithProof, StorageServerSummary, StorageServiceResponse,
+        TransactionOrOutputListWithProof,
     },
-    responses::{StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProof},
     Epoch, StorageServiceMessage,
 };
 use aptos_time_service::TimeService;
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{state_key::StateKey, state_value::StateValueChunkWithProof},
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use async_trait::async_trait;
@@ -614,6 +618,21 @@ impl AptosDataClientInterface for AptosDataClient {
             .await
     }
 
+    async fn get_state_values_by_keys_with_proof(
+        &self,
+        version: u64,
+        state_keys: Vec<StateKey>,
+        request_timeout_ms: u64,
+    ) -> crate::error::Result<Response<StateValuesByKeysWithProof>> {
+        let data_request =
+            DataRequest::GetStateValuesByKeysWithProof(StateValuesByKeysWithProofRequest {
+                version,
+                state_keys,
+            });
+        self.create_and_send_storage_request(request_timeout_ms, data_request)
+            .await
+    }
+
     async fn get_transaction_outputs_with_proof(
         &self,
         proof_version: Version,
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{error, error::Error, global_summary::GlobalDataSummary};
-use aptos_storage_service_types::{responses::TransactionOrOutputListWithProof, Epoch};
+use aptos_storage_service_types::{
+    responses::{StateValuesByKeysWithProof, TransactionOrOutputListWithProof},
+    Epoch,
+};
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{state_key::StateKey, state_value::StateValueChunkWithProof},
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use async_trait::async_trait;
@@ -87,6 +90,18 @@ pub trait AptosDataClientInterface {
         request_timeout_ms: u64,
     ) -> error::Result<Response<StateValueChunkWithProof>>;
 
+    /// Fetches the state values (and sparse merkle proofs) of the given
+    /// state keys at the specified version. The values are returned in the
+    /// order of the keys. In some cases, values for only a prefix of the keys
+    /// may be returned (e.g., to tolerate network limits). If the data cannot
+    /// be fetched, an error is returned.
+    async fn get_state_values_by_keys_with_proof(
+        &self,
+        version: u64,
+        state_keys: Vec<StateKey>,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<StateValuesByKeysWithProof>>;
+
     /// Fetches a transaction output list with proof, with transaction
     /// outputs from start to end versions (inclusive). The proof is relative
     /// to the specified `proof_version`. In some cases, fewer outputs may be
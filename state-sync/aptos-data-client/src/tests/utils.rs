@@ -29,6 +29,7 @@ pub fn create_storage_summary(version: Version) -> StorageServerSummary {
         protocol_metadata: ProtocolMetadata {
             max_epoch_chunk_size: 1000,
             max_state_chunk_size: 1000,
+            max_state_values_by_keys_chunk_size: 1000,
             max_transaction_chunk_size: 1000,
             max_transaction_output_chunk_size: 1000,
         },
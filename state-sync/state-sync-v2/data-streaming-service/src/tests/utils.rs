@@ -20,7 +20,7 @@ use aptos_storage_service_types::{
         StateValuesWithProofRequest, TransactionOutputsWithProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
     },
-    responses::{CompleteDataRange, TransactionOrOutputListWithProof},
+    responses::{CompleteDataRange, StateValuesByKeysWithProof, TransactionOrOutputListWithProof},
     Epoch,
 };
 use aptos_types::{
@@ -281,6 +281,15 @@ impl AptosDataClientInterface for MockAptosDataClient {
         Ok(create_data_client_response(state_value_chunk_with_proof))
     }
 
+    async fn get_state_values_by_keys_with_proof(
+        &self,
+        _version: Version,
+        _state_keys: Vec<StateKey>,
+        _request_timeout_ms: u64,
+    ) -> Result<Response<StateValuesByKeysWithProof>, aptos_data_client::error::Error> {
+        unimplemented!("State values by keys are not requested by the data streaming service!")
+    }
+
     async fn get_epoch_ending_ledger_infos(
         &self,
         start_epoch: Epoch,
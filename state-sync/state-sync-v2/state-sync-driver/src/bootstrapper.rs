@@ -279,6 +279,66 @@ impl StateValueSyncer {
     }
 }
 
+/// The component that restores the database from a trusted backup (when
+/// bootstrapping with `BootstrappingMode::RestoreFromBackup`).
+pub trait BackupRestorer {
+    /// Returns the next version expected by the database
+    fn get_next_version(&self) -> anyhow::Result<Version>;
+
+    /// Restores the database from the backup, resuming any interrupted restore
+    fn restore(&self) -> anyhow::Result<()>;
+}
+
+/// Restores the database from a trusted backup. This must be done before the
+/// database is opened (and before genesis is committed), after which the
+/// bootstrapper syncs the missing outputs on top of the restored version.
+/// The progress of the restore is persisted in the metadata storage, so that
+/// an interrupted restore is resumed when the node restarts.
+pub fn restore_from_backup<MetadataStorage: MetadataStorageInterface>(
+    metadata_storage: &MetadataStorage,
+    backup_restorer: &dyn BackupRestorer,
+) -> Result<(), Error> {
+    match metadata_storage.previous_backup_restore()? {
+        Some(restore_progress) if restore_progress.restore_completed => {
+            info!(LogSchema::new(LogEntry::Bootstrapper)
+                .message("The database has already been restored from a backup!"));
+            return Ok(());
+        },
+        Some(_) => {
+            info!(LogSchema::new(LogEntry::Bootstrapper)
+                .message("Resuming the interrupted restore from a backup."));
+        },
+        None => {
+            // Only restore into an empty database
+            let next_version = backup_restorer.get_next_version().map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to get the next version of the database! Error: {:?}",
+                    error
+                ))
+            })?;
+            if next_version > GENESIS_TRANSACTION_VERSION {
+                return Err(Error::UnexpectedError(format!(
+                    "Restoring from a backup is unsupported for nodes with existing state! \
+                    The next version of the database is {}. Either select a different \
+                    bootstrapping mode, or delete your storage and restart your node.",
+                    next_version
+                )));
+            }
+            metadata_storage.update_backup_restore_progress(false)?;
+        },
+    }
+
+    // Restore the database and mark the restore as complete
+    backup_restorer.restore().map_err(|error| {
+        Error::UnexpectedError(format!(
+            "Failed to restore from the backup! The restore will resume when the node \
+            restarts. Error: {:?}",
+            error
+        ))
+    })?;
+    metadata_storage.update_backup_restore_progress(true)
+}
+
 /// A simple component that manages the bootstrapping of the node
 pub struct Bootstrapper<MetadataStorage, StorageSyncer, StreamingClient> {
     // The currently active data stream (provided by the data streaming service)
@@ -451,6 +511,11 @@ impl<
         // Reset the chunk executor to flush any invalid state currently held in-memory
         self.storage_synchronizer.reset_chunk_executor()?;
 
+        // Never sync on top of an incomplete restore from a backup
+        if self.get_bootstrapping_mode() == BootstrappingMode::RestoreFromBackup {
+            self.verify_backup_restore_complete()?;
+        }
+
         // Always fetch the new epoch ending ledger infos first
         if self.should_fetch_epoch_ending_ledger_infos() {
             return self
@@ -485,7 +550,8 @@ impl<
                 .await
             },
             _ => {
-                // We're either transaction or output syncing
+                // We're either transaction or output syncing (e.g., to catch
+                // up after restoring from a backup).
                 self.fetch_missing_transaction_data(
                     highest_synced_version,
                     highest_known_ledger_info,
@@ -495,6 +561,18 @@ impl<
         }
     }
 
+    /// Verifies that the database was fully restored from the backup
+    fn verify_backup_restore_complete(&self) -> Result<(), Error> {
+        match self.metadata_storage.previous_backup_restore()? {
+            Some(restore_progress) if restore_progress.restore_completed => Ok(()),
+            restore_progress => Err(Error::UnexpectedError(format!(
+                "The database was not fully restored from the backup! Restart the node \
+                to resume the restore. Restore progress: {:?}",
+                restore_progress
+            ))),
+        }
+    }
+
     /// Fetches all missing state snapshot data in order to bootstrap the node
     async fn fetch_missing_state_snapshot_data(
         &mut self,
@@ -705,7 +783,8 @@ impl<
                 Error::UnexpectedError("No higher epoch ending version known!".into())
             })?;
         let data_stream = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 self.streaming_client
                     .get_all_transaction_outputs(
                         next_version,
@@ -1131,7 +1210,8 @@ impl<
 
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs = match bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     utils::apply_transaction_outputs(
                         self.storage_synchronizer.clone(),
@@ -1330,7 +1410,8 @@ impl<
     ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         // Calculate the payload end version
         let num_versions = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     transaction_outputs_with_proof
                         .transactions_and_outputs
@@ -20,7 +20,7 @@ use crate::{
     utils,
     utils::{OutputFallbackHandler, PENDING_DATA_LOG_FREQ_SECS},
 };
-use aptos_config::config::{RoleType, StateSyncDriverConfig};
+use aptos_config::config::{ConsensusObserverConfig, RoleType, StateSyncDriverConfig};
 use aptos_consensus_notifications::{
     ConsensusCommitNotification, ConsensusNotification, ConsensusSyncNotification,
 };
@@ -52,6 +52,9 @@ pub struct DriverConfiguration {
     // The config file of the driver
     pub config: StateSyncDriverConfig,
 
+    // The config of the consensus observer (if the node is a fullnode)
+    pub consensus_observer_config: ConsensusObserverConfig,
+
     // The role of the node
     pub role: RoleType,
 
@@ -60,9 +63,15 @@ pub struct DriverConfiguration {
 }
 
 impl DriverConfiguration {
-    pub fn new(config: StateSyncDriverConfig, role: RoleType, waypoint: Waypoint) -> Self {
+    pub fn new(
+        config: StateSyncDriverConfig,
+        consensus_observer_config: ConsensusObserverConfig,
+        role: RoleType,
+        waypoint: Waypoint,
+    ) -> Self {
         Self {
             config,
+            consensus_observer_config,
             role,
             waypoint,
         }
@@ -104,6 +113,9 @@ pub struct StateSyncDriver<
     // The event subscription service to notify listeners of on-chain events
     event_subscription_service: Arc<Mutex<EventSubscriptionService>>,
 
+    // The timestamp of the last commit or completed sync request from consensus
+    last_consensus_progress_time: Option<Instant>,
+
     // The handler for notifications to mempool
     mempool_notification_handler: MempoolNotificationHandler<MempoolNotifier>,
 
@@ -172,6 +184,7 @@ impl<
             driver_configuration,
             error_notification_listener,
             event_subscription_service,
+            last_consensus_progress_time: None,
             mempool_notification_handler,
             start_time: None,
             storage,
@@ -213,9 +226,10 @@ impl<
 
     /// Handles a notification sent by consensus
     async fn handle_consensus_notification(&mut self, notification: ConsensusNotification) {
-        // Verify the notification: full nodes shouldn't receive notifications
-        // and consensus should only send notifications after bootstrapping!
-        let result = if self.driver_configuration.role == RoleType::FullNode {
+        // Verify the notification: full nodes shouldn't receive notifications (unless
+        // they run the consensus observer) and consensus should only send notifications
+        // after bootstrapping!
+        let result = if !self.is_consensus_or_observer_enabled() {
             Err(Error::FullNodeConsensusNotification(format!(
                 "Received consensus notification: {:?}",
                 notification
@@ -284,6 +298,7 @@ impl<
             ))
         );
         self.update_consensus_commit_metrics(&consensus_commit_notification);
+        self.last_consensus_progress_time = Some(self.time_service.now());
 
         // TODO(joshlind): can we get consensus to forward the events?
 
@@ -504,6 +519,7 @@ impl<
         if !self.active_sync_request() {
             self.continuous_syncer.reset_active_stream(None).await?;
             self.storage_synchronizer.finish_chunk_executor(); // Consensus is now in control
+            self.last_consensus_progress_time = Some(self.time_service.now());
         }
         Ok(())
     }
@@ -518,9 +534,40 @@ impl<
         self.driver_configuration.role == RoleType::Validator
     }
 
-    /// Returns true iff consensus is currently executing
+    /// Returns true iff this node runs consensus or the consensus observer
+    fn is_consensus_or_observer_enabled(&self) -> bool {
+        self.is_validator()
+            || self
+                .driver_configuration
+                .consensus_observer_config
+                .observer_enabled
+    }
+
+    /// Returns true iff consensus is currently executing. For consensus observers,
+    /// this is only the case while the observer keeps making progress. Otherwise,
+    /// state sync takes over until the observer issues a new sync request.
     fn check_if_consensus_executing(&self) -> bool {
-        self.is_validator() && self.bootstrapper.is_bootstrapped() && !self.active_sync_request()
+        if !self.is_consensus_or_observer_enabled()
+            || !self.bootstrapper.is_bootstrapped()
+            || self.active_sync_request()
+        {
+            return false;
+        }
+        if self.is_validator() {
+            return true;
+        }
+
+        // Check if the observer has made progress recently
+        let fallback_duration = Duration::from_millis(
+            self.driver_configuration
+                .consensus_observer_config
+                .observer_fallback_duration_ms,
+        );
+        self.last_consensus_progress_time
+            .map(|progress_time| self.time_service.now().duration_since(progress_time))
+            .map_or(false, |time_since_progress| {
+                time_since_progress < fallback_duration
+            })
     }
 
     /// Checks if the connection deadline has passed. If so, validators with
@@ -17,5 +17,7 @@ mod notification_handlers;
 mod storage_synchronizer;
 mod utils;
 
+pub use bootstrapper::{restore_from_backup, BackupRestorer};
+
 #[cfg(test)]
 mod tests;
@@ -47,6 +47,13 @@ pub trait MetadataStorageInterface {
         last_persisted_state_value_index: u64,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
+
+    /// Returns the progress of any restore from a backup that has previously
+    /// started. If no restore started, None is returned.
+    fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error>;
+
+    /// Updates the progress of the restore from a backup
+    fn update_backup_restore_progress(&self, restore_completed: bool) -> Result<(), Error>;
 }
 
 /// The name of the state sync db file
@@ -96,24 +103,33 @@ impl PersistentMetadataStorage {
     /// Returns the existing snapshot sync progress. Returns None if no progress is found.
     fn get_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>, Error> {
         let metadata_key = MetadataKey::StateSnapshotSync;
-        let maybe_metadata_value =
-            self.database
-                .get::<MetadataSchema>(&metadata_key)
-                .map_err(|error| {
-                    Error::StorageError(format!(
-                        "Failed to read metadata value for key: {:?}. Error: {:?}",
-                        metadata_key, error
-                    ))
-                })?;
-        match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+        match self.get_metadata_value(&metadata_key)? {
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
 
+    /// Reads the value of the metadata key. Returns None if no value is found.
+    fn get_metadata_value(
+        &self,
+        metadata_key: &MetadataKey,
+    ) -> Result<Option<MetadataValue>, Error> {
+        self.database
+            .get::<MetadataSchema>(metadata_key)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to read metadata value for key: {:?}. Error: {:?}",
+                    metadata_key, error
+                ))
+            })
+    }
+
     /// Returns the snapshot sync progress recorded for the specified version.
     /// Returns an error if no progress was found.
     fn get_snapshot_progress_at_target(
@@ -224,6 +240,25 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         // Insert the new key/value pair
         self.commit_key_value(metadata_key, metadata_value)
     }
+
+    fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error> {
+        let metadata_key = MetadataKey::BackupRestore;
+        match self.get_metadata_value(&metadata_key)? {
+            Some(MetadataValue::BackupRestore(restore_progress)) => Ok(Some(restore_progress)),
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn update_backup_restore_progress(&self, restore_completed: bool) -> Result<(), Error> {
+        let metadata_key = MetadataKey::BackupRestore;
+        let metadata_value =
+            MetadataValue::BackupRestore(BackupRestoreProgress { restore_completed });
+        self.commit_key_value(metadata_key, metadata_value)
+    }
 }
 
 /// A simple struct for recording the progress of a state snapshot sync
@@ -234,6 +269,12 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// A simple struct for recording the progress of a restore from a backup
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BackupRestoreProgress {
+    pub restore_completed: bool,
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -252,6 +293,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataKey {
         StateSnapshotSync, // A state snapshot sync that was started
+        BackupRestore,     // A restore from a backup that was started
     }
 
     /// A metadata value that can be inserted into the database
@@ -259,6 +301,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        BackupRestore(BackupRestoreProgress),     // A backup restore progress marker
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
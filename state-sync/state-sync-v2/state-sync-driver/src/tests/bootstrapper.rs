@@ -2,9 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    bootstrapper::{Bootstrapper, GENESIS_TRANSACTION_VERSION},
+    bootstrapper::{
+        restore_from_backup, BackupRestorer, Bootstrapper, GENESIS_TRANSACTION_VERSION,
+    },
     driver::DriverConfiguration,
     error::Error,
+    metadata_storage::{
+        BackupRestoreProgress, MetadataStorageInterface, PersistentMetadataStorage,
+    },
     tests::{
         mocks::{
             create_mock_db_reader, create_mock_streaming_client, create_ready_storage_synchronizer,
@@ -25,6 +30,7 @@ use aptos_data_streaming_service::{
     data_notification::{DataNotification, DataPayload, NotificationId},
     streaming_client::{NotificationAndFeedback, NotificationFeedback},
 };
+use aptos_temppath::TempPath;
 use aptos_time_service::TimeService;
 use aptos_types::{
     transaction::{TransactionOutputListWithProof, Version},
@@ -33,7 +39,7 @@ use aptos_types::{
 use claims::{assert_matches, assert_none, assert_ok};
 use futures::{channel::oneshot, FutureExt, SinkExt};
 use mockall::{predicate::eq, Sequence};
-use std::{sync::Arc, time::Duration};
+use std::{cell::Cell, sync::Arc, time::Duration};
 
 #[tokio::test]
 async fn test_bootstrap_genesis_waypoint() {
@@ -423,6 +429,148 @@ async fn test_data_stream_transaction_outputs() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_data_stream_restore_from_backup() {
+    // Create test data
+    let restored_version = 20;
+    let highest_version = 45;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a genesis waypoint and backup restoring
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock streaming client (outputs are fetched after the restored version)
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(restored_version + 1),
+            eq(highest_version),
+            eq(highest_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+
+    // Create the mock metadata storage (with a completed restore)
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_backup_restore()
+        .returning(|| {
+            Ok(Some(BackupRestoreProgress {
+                restore_completed: true,
+            }))
+        });
+
+    // Create the bootstrapper (with the database already restored from a backup)
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        restored_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress to initialize the transaction output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_restore_from_backup() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify that a failed restore is recorded as incomplete
+    let backup_restorer = TestBackupRestorer::new(0, true);
+    let error = restore_from_backup(&metadata_storage, &backup_restorer).unwrap_err();
+    assert_matches!(error, Error::UnexpectedError(_));
+    assert_eq!(backup_restorer.num_restores.get(), 1);
+    assert_eq!(
+        metadata_storage.previous_backup_restore().unwrap(),
+        Some(BackupRestoreProgress {
+            restore_completed: false
+        })
+    );
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify that the interrupted restore is resumed (even though the database isn't empty)
+    let backup_restorer = TestBackupRestorer::new(100, false);
+    restore_from_backup(&metadata_storage, &backup_restorer).unwrap();
+    assert_eq!(backup_restorer.num_restores.get(), 1);
+    assert_eq!(
+        metadata_storage.previous_backup_restore().unwrap(),
+        Some(BackupRestoreProgress {
+            restore_completed: true
+        })
+    );
+
+    // Verify that a completed restore isn't run again
+    let backup_restorer = TestBackupRestorer::new(100, false);
+    restore_from_backup(&metadata_storage, &backup_restorer).unwrap();
+    assert_eq!(backup_restorer.num_restores.get(), 0);
+}
+
+#[test]
+fn test_restore_from_backup_existing_state() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify that a database with existing state is never restored
+    let backup_restorer = TestBackupRestorer::new(1, false);
+    let error = restore_from_backup(&metadata_storage, &backup_restorer).unwrap_err();
+    assert_matches!(error, Error::UnexpectedError(_));
+    assert_eq!(backup_restorer.num_restores.get(), 0);
+    assert_none!(metadata_storage.previous_backup_restore().unwrap());
+}
+
+#[tokio::test]
+async fn test_data_stream_incomplete_restore_from_backup() {
+    // Create a driver configuration with a genesis waypoint and backup restoring
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock metadata storage (with an interrupted restore)
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_backup_restore()
+        .returning(|| {
+            Ok(Some(BackupRestoreProgress {
+                restore_completed: false,
+            }))
+        });
+
+    // Create the bootstrapper (no data stream should be created)
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        create_mock_streaming_client(),
+        metadata_storage,
+        20,
+        true,
+    );
+
+    // Verify that the bootstrapper refuses to make progress
+    let global_data_summary = create_global_summary(1);
+    let error = drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap_err();
+    assert_matches!(error, Error::UnexpectedError(_));
+}
+
 #[tokio::test]
 async fn test_data_stream_transactions_or_outputs() {
     // Create test data
@@ -1317,3 +1465,34 @@ async fn handle_storage_synchronizer_error(
         .await
         .unwrap();
 }
+
+/// A backup restorer that counts the restores (and fails them if required)
+struct TestBackupRestorer {
+    next_version: Version,
+    fail_restore: bool,
+    num_restores: Cell<u64>,
+}
+
+impl TestBackupRestorer {
+    fn new(next_version: Version, fail_restore: bool) -> Self {
+        Self {
+            next_version,
+            fail_restore,
+            num_restores: Cell::new(0),
+        }
+    }
+}
+
+impl BackupRestorer for TestBackupRestorer {
+    fn get_next_version(&self) -> anyhow::Result<Version> {
+        Ok(self.next_version)
+    }
+
+    fn restore(&self) -> anyhow::Result<()> {
+        self.num_restores.set(self.num_restores.get() + 1);
+        if self.fail_restore {
+            anyhow::bail!("Failed to download the backup!");
+        }
+        Ok(())
+    }
+}
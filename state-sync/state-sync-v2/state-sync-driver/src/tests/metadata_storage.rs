@@ -4,7 +4,8 @@
 use crate::{
     metadata_storage::{
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        BackupRestoreProgress, MetadataStorageInterface, PersistentMetadataStorage,
+        StateSnapshotProgress,
     },
     tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
@@ -99,6 +100,52 @@ fn test_metadata_schema_encode_decode() {
             snapshot_sync_completed: false,
         }),
     );
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::BackupRestore,
+        &MetadataValue::BackupRestore(BackupRestoreProgress {
+            restore_completed: true,
+        }),
+    );
+}
+
+#[test]
+fn test_snapshot_sync_and_backup_restore() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify the storage is empty
+    assert_none!(metadata_storage.previous_backup_restore().unwrap());
+
+    // Record the progress of a restore and a snapshot sync
+    metadata_storage
+        .update_backup_restore_progress(false)
+        .unwrap();
+    let target_ledger_info = create_ledger_info_at_version(100);
+    metadata_storage
+        .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
+        .unwrap();
+
+    // Complete the restore
+    metadata_storage
+        .update_backup_restore_progress(true)
+        .unwrap();
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Create another storage and verify that both progress markers are kept
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    assert_eq!(
+        Some(BackupRestoreProgress {
+            restore_completed: true
+        }),
+        metadata_storage.previous_backup_restore().unwrap()
+    );
+    assert_eq!(
+        Some(target_ledger_info),
+        metadata_storage.previous_snapshot_sync_target().unwrap()
+    );
 }
 
 #[test]
@@ -3,7 +3,7 @@
 
 use crate::{
     error::Error,
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{BackupRestoreProgress, MetadataStorageInterface},
     storage_synchronizer::StorageSynchronizerInterface,
     tests::utils::{
         create_empty_epoch_state, create_epoch_ending_ledger_info, create_transaction_info,
@@ -328,6 +328,10 @@ mock! {
             last_persisted_state_value_index: u64,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
+
+        fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error>;
+
+        fn update_backup_restore_progress(&self, restore_completed: bool) -> Result<(), Error>;
     }
 
     impl Clone for MetadataStorage {
@@ -283,6 +283,7 @@ fn refresh_cached_storage_summary<T: StorageReaderInterface>(
         max_epoch_chunk_size: storage_config.max_epoch_chunk_size,
         max_transaction_chunk_size: storage_config.max_transaction_chunk_size,
         max_state_chunk_size: storage_config.max_state_chunk_size,
+        max_state_values_by_keys_chunk_size: storage_config.max_state_values_by_keys_chunk_size,
         max_transaction_output_chunk_size: storage_config.max_transaction_output_chunk_size,
     };
 
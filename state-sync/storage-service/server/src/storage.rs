@@ -6,11 +6,12 @@ use aptos_config::config::StorageServiceConfig;
 use aptos_logger::debug;
 use aptos_storage_interface::DbReader;
 use aptos_storage_service_types::responses::{
-    CompleteDataRange, DataResponse, DataSummary, TransactionOrOutputListWithProof,
+    CompleteDataRange, DataResponse, DataSummary, StateValueWithProof, StateValuesByKeysWithProof,
+    TransactionOrOutputListWithProof,
 };
 use aptos_types::{
     epoch_change::EpochChangeProof,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{state_key::StateKey, state_value::StateValueChunkWithProof},
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use serde::Serialize;
@@ -88,6 +89,16 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns the state values (and sparse merkle proofs) of the given
+    /// `state_keys` at the specified version. The values are returned in
+    /// the order of the keys. In some cases, values for only a prefix of
+    /// the keys may be returned (e.g., due to network or chunk limits).
+    fn get_state_values_by_keys_with_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesByKeysWithProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -494,6 +505,68 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_state_values_by_keys_with_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesByKeysWithProof, Error> {
+        // Calculate the number of state values to fetch
+        if state_keys.is_empty() {
+            return Err(Error::InvalidRequest(
+                "At least one state key must be requested!".into(),
+            ));
+        }
+        let max_num_state_values = self.config.max_state_values_by_keys_chunk_size as usize;
+        let num_state_values_to_fetch = min(state_keys.len(), max_num_state_values);
+
+        // Fetch each state value and proof
+        let mut state_values_with_proof = vec![];
+        for state_key in &state_keys[..num_state_values_to_fetch] {
+            let (state_value, proof) = self
+                .storage
+                .get_state_value_with_proof_by_version(state_key, version)
+                .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+            state_values_with_proof.push(StateValueWithProof::new(
+                state_key.clone(),
+                state_value,
+                proof,
+            ));
+        }
+        let mut state_values_by_keys_with_proof =
+            StateValuesByKeysWithProof::new(version, state_values_with_proof);
+
+        // Attempt to divide up the response if it overflows the message size
+        loop {
+            let num_state_values = state_values_by_keys_with_proof
+                .state_values_with_proof
+                .len();
+            if num_state_values == 1 {
+                return Ok(state_values_by_keys_with_proof); // We cannot return less than a single item
+            }
+
+            let (overflow_frame, num_bytes) = check_overflow_network_frame(
+                &state_values_by_keys_with_proof,
+                self.config.max_network_chunk_bytes,
+            )?;
+            if !overflow_frame {
+                return Ok(state_values_by_keys_with_proof);
+            } else {
+                increment_network_frame_overflow(
+                    DataResponse::StateValuesByKeysWithProof(
+                        state_values_by_keys_with_proof.clone(),
+                    )
+                    .get_label(),
+                );
+                let new_num_state_values = num_state_values / 2;
+                debug!("The request for {:?} state values by keys was too large (num bytes: {:?}). Retrying with {:?}.",
+                    num_state_values, num_bytes, new_num_state_values);
+                state_values_by_keys_with_proof
+                    .state_values_with_proof
+                    .truncate(new_num_state_values); // Try again with half the amount of data
+            }
+        }
+    }
 }
 
 /// Calculate `(start..=end).len()`. Returns an error if `end < start` or
@@ -0,0 +1,240 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{mock, mock::MockClient, utils};
+use aptos_config::config::StorageServiceConfig;
+use aptos_storage_service_types::{
+    requests::{DataRequest, StateValuesByKeysWithProofRequest},
+    responses::{
+        DataResponse, StateValueWithProof, StateValuesByKeysWithProof, StorageServiceResponse,
+    },
+    StorageServiceError,
+};
+use aptos_types::{
+    proof::SparseMerkleProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use claims::assert_matches;
+use mockall::predicate::eq;
+
+#[tokio::test]
+async fn test_get_state_values_by_keys_with_proof() {
+    // Test small and large batch requests
+    let max_batch_size = StorageServiceConfig::default().max_state_values_by_keys_chunk_size;
+    for num_state_keys in [1, 10, max_batch_size] {
+        // Create test data
+        let version = 101;
+        let state_keys = create_state_keys(num_state_keys);
+        let state_values_with_proof = create_state_values_with_proof(&state_keys);
+
+        // Create the mock db reader
+        let mut db_reader = mock::create_mock_db_reader();
+        for state_value_with_proof in state_values_with_proof.clone() {
+            expect_get_state_value_with_proof_by_version(
+                &mut db_reader,
+                version,
+                state_value_with_proof,
+            );
+        }
+
+        // Create the storage client and server
+        let (mut mock_client, mut service, _, _) = MockClient::new(Some(db_reader), None);
+        utils::update_storage_server_summary(&mut service, version, 10);
+        tokio::spawn(service.start());
+
+        // Process a request to fetch the state values by keys
+        let response =
+            get_state_values_by_keys_with_proof(&mut mock_client, version, state_keys, false)
+                .await
+                .unwrap();
+
+        // Verify the response is correct
+        assert_matches!(response, StorageServiceResponse::RawResponse(_));
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            DataResponse::StateValuesByKeysWithProof(StateValuesByKeysWithProof::new(
+                version,
+                state_values_with_proof
+            ))
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_keys_with_proof_batch_limit() {
+    // Create test data
+    let max_batch_size = 5;
+    let version = 101;
+    let state_keys = create_state_keys(max_batch_size + 1);
+
+    // Create a storage config with the specified batch limit
+    let storage_config = StorageServiceConfig {
+        max_state_values_by_keys_chunk_size: max_batch_size,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _) = MockClient::new(None, Some(storage_config));
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request that exceeds the batch limit
+    let response =
+        get_state_values_by_keys_with_proof(&mut mock_client, version, state_keys, false)
+            .await
+            .unwrap_err();
+
+    // Verify the request is not serviceable
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_keys_with_proof_network_limit() {
+    // Test different byte limits
+    for network_limit_bytes in [1, 512, 1024, 10 * 1024] {
+        for use_compression in [true, false] {
+            // Create test data
+            let version = 101;
+            let state_keys = create_state_keys(100);
+            let state_values_with_proof = create_state_values_with_proof(&state_keys);
+
+            // Create the mock db reader
+            let mut db_reader = mock::create_mock_db_reader();
+            for state_value_with_proof in state_values_with_proof {
+                expect_get_state_value_with_proof_by_version(
+                    &mut db_reader,
+                    version,
+                    state_value_with_proof,
+                );
+            }
+
+            // Create a storage config with the specified max network byte limit
+            let storage_config = StorageServiceConfig {
+                max_network_chunk_bytes: network_limit_bytes,
+                ..Default::default()
+            };
+
+            // Create the storage client and server
+            let (mut mock_client, mut service, _, _) =
+                MockClient::new(Some(db_reader), Some(storage_config));
+            utils::update_storage_server_summary(&mut service, version, 10);
+            tokio::spawn(service.start());
+
+            // Process a request to fetch the state values by keys
+            let response = get_state_values_by_keys_with_proof(
+                &mut mock_client,
+                version,
+                state_keys.clone(),
+                use_compression,
+            )
+            .await
+            .unwrap();
+
+            // Verify the response adheres to the network limits
+            match response.get_data_response().unwrap() {
+                DataResponse::StateValuesByKeysWithProof(state_values_by_keys_with_proof) => {
+                    let num_response_bytes = bcs::to_bytes(&response).unwrap().len() as u64;
+                    let returned_keys: Vec<_> = state_values_by_keys_with_proof
+                        .state_values_with_proof
+                        .iter()
+                        .map(|state_value_with_proof| state_value_with_proof.state_key.clone())
+                        .collect();
+                    if num_response_bytes > network_limit_bytes {
+                        assert_eq!(returned_keys.len(), 1); // Data cannot be reduced more than a single item
+                    }
+
+                    // Verify the returned keys are a prefix of the requested keys
+                    assert!(!returned_keys.is_empty());
+                    assert_eq!(returned_keys, state_keys[..returned_keys.len()].to_vec());
+                },
+                _ => panic!(
+                    "Expected state values by keys with proof but got: {:?}",
+                    response
+                ),
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_keys_with_proof_not_serviceable() {
+    // Create test data
+    let version = 101;
+
+    // Create the storage client and server (that cannot service the request)
+    let (mut mock_client, mut service, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version - 1, 10);
+    tokio::spawn(service.start());
+
+    // Process requests that cannot be serviced (no keys, and a future version)
+    for state_keys in [vec![], create_state_keys(10)] {
+        let response =
+            get_state_values_by_keys_with_proof(&mut mock_client, version, state_keys, false)
+                .await
+                .unwrap_err();
+
+        // Verify the request is not serviceable
+        assert_matches!(response, StorageServiceError::InvalidRequest(_));
+    }
+}
+
+/// Creates the specified number of unique state keys
+fn create_state_keys(num_state_keys: u64) -> Vec<StateKey> {
+    (0..num_state_keys)
+        .map(|index| StateKey::raw(index.to_le_bytes().to_vec()))
+        .collect()
+}
+
+/// Creates a state value (and empty proof) for each of the given
+/// state keys. Every other key is treated as non-existent.
+fn create_state_values_with_proof(state_keys: &[StateKey]) -> Vec<StateValueWithProof> {
+    state_keys
+        .iter()
+        .enumerate()
+        .map(|(index, state_key)| {
+            let state_value = if index % 2 == 0 {
+                Some(StateValue::new_legacy(vec![index as u8; 100]))
+            } else {
+                None
+            };
+            StateValueWithProof::new(
+                state_key.clone(),
+                state_value,
+                SparseMerkleProof::new(None, vec![]),
+            )
+        })
+        .collect()
+}
+
+/// Sets an expectation on the given mock db for a call to fetch a state value with proof
+fn expect_get_state_value_with_proof_by_version(
+    mock_db: &mut mock::MockDatabaseReader,
+    version: u64,
+    state_value_with_proof: StateValueWithProof,
+) {
+    let StateValueWithProof {
+        state_key,
+        state_value,
+        proof,
+    } = state_value_with_proof;
+    mock_db
+        .expect_get_state_value_with_proof_by_version()
+        .times(0..=1)
+        .with(eq(state_key), eq(version))
+        .returning(move |_, _| Ok((state_value.clone(), proof.clone())));
+}
+
+/// Sends a state values by keys with proof request and processes the response
+async fn get_state_values_by_keys_with_proof(
+    mock_client: &mut MockClient,
+    version: u64,
+    state_keys: Vec<StateKey>,
+    use_compression: bool,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request =
+        DataRequest::GetStateValuesByKeysWithProof(StateValuesByKeysWithProofRequest {
+            version,
+            state_keys,
+        });
+    utils::send_storage_request(mock_client, use_compression, data_request).await
+}
@@ -74,6 +74,8 @@ async fn test_get_storage_server_summary() {
         protocol_metadata: ProtocolMetadata {
             max_epoch_chunk_size: default_storage_config.max_epoch_chunk_size,
             max_state_chunk_size: default_storage_config.max_state_chunk_size,
+            max_state_values_by_keys_chunk_size: default_storage_config
+                .max_state_values_by_keys_chunk_size,
             max_transaction_chunk_size: default_storage_config.max_transaction_chunk_size,
             max_transaction_output_chunk_size: default_storage_config
                 .max_transaction_output_chunk_size,
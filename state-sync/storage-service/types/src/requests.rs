@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
 use serde::{Deserialize, Serialize};
 
 /// A storage service request.
@@ -44,6 +44,7 @@ pub enum DataRequest {
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
     GetNewTransactionsOrOutputsWithProof(NewTransactionsOrOutputsWithProofRequest), // Optimistically fetches new transactions or outputs
     GetTransactionsOrOutputsWithProof(TransactionsOrOutputsWithProofRequest), // Fetches a list of transactions or outputs with a proof
+    GetStateValuesByKeysWithProof(StateValuesByKeysWithProofRequest), // Fetches the states of specific keys with proofs
 }
 
 impl DataRequest {
@@ -63,6 +64,7 @@ impl DataRequest {
                 "get_new_transactions_or_outputs_with_proof"
             },
             Self::GetTransactionsOrOutputsWithProof(_) => "get_transactions_or_outputs_with_proof",
+            Self::GetStateValuesByKeysWithProof(_) => "get_state_values_by_keys_with_proof",
         }
     }
 
@@ -114,6 +116,15 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching the state values (and the
+/// corresponding sparse merkle proofs) of a set of state keys at a
+/// specified version.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValuesByKeysWithProofRequest {
+    pub version: u64,              // The version to fetch the state values at
+    pub state_keys: Vec<StateKey>, // The state keys to fetch the values (and proofs) for
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
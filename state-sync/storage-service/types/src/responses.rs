@@ -5,19 +5,24 @@ use crate::{
     requests::DataRequest::{
         GetEpochEndingLedgerInfos, GetNewTransactionOutputsWithProof,
         GetNewTransactionsOrOutputsWithProof, GetNewTransactionsWithProof,
-        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesWithProof,
-        GetStorageServerSummary, GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-        GetTransactionsWithProof,
+        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesByKeysWithProof,
+        GetStateValuesWithProof, GetStorageServerSummary, GetTransactionOutputsWithProof,
+        GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
     },
     responses::Error::DegenerateRangeError,
     Epoch, StorageServiceRequest, COMPRESSION_SUFFIX_LABEL,
 };
 use aptos_compression::{metrics::CompressionClient, CompressedData, CompressionError};
 use aptos_config::config::{StorageServiceConfig, MAX_APPLICATION_MESSAGE_SIZE};
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use num_traits::{PrimInt, Zero};
@@ -134,6 +139,7 @@ pub enum DataResponse {
     TransactionsWithProof(TransactionListWithProof),
     NewTransactionsOrOutputsWithProof((TransactionOrOutputListWithProof, LedgerInfoWithSignatures)),
     TransactionsOrOutputsWithProof(TransactionOrOutputListWithProof),
+    StateValuesByKeysWithProof(StateValuesByKeysWithProof),
 }
 
 impl DataResponse {
@@ -151,6 +157,7 @@ impl DataResponse {
             Self::TransactionsWithProof(_) => "transactions_with_proof",
             Self::NewTransactionsOrOutputsWithProof(_) => "new_transactions_or_outputs_with_proof",
             Self::TransactionsOrOutputsWithProof(_) => "transactions_or_outputs_with_proof",
+            Self::StateValuesByKeysWithProof(_) => "state_values_by_keys_with_proof",
         }
     }
 }
@@ -188,6 +195,21 @@ impl TryFrom<StorageServiceResponse> for StateValueChunkWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for StateValuesByKeysWithProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::StateValuesByKeysWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected state_values_by_keys_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     type Error = crate::responses::Error;
 
@@ -342,6 +364,73 @@ impl TryFrom<StorageServiceResponse> for TransactionOrOutputListWithProof {
     }
 }
 
+/// A list of state values (and the corresponding sparse merkle proofs) for
+/// a set of state keys at a single version. The list may contain fewer
+/// entries than requested (e.g., due to network or chunk limits), but the
+/// entries are always returned in the order of the requested keys.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValuesByKeysWithProof {
+    pub version: Version, // The version the state values and proofs are relative to
+    pub state_values_with_proof: Vec<StateValueWithProof>,
+}
+
+impl StateValuesByKeysWithProof {
+    pub fn new(version: Version, state_values_with_proof: Vec<StateValueWithProof>) -> Self {
+        Self {
+            version,
+            state_values_with_proof,
+        }
+    }
+
+    /// Verifies every state value against the given state root hash
+    /// (i.e., the state checkpoint hash at the response version).
+    pub fn verify(&self, expected_root_hash: HashValue) -> crate::Result<(), Error> {
+        for state_value_with_proof in &self.state_values_with_proof {
+            state_value_with_proof.verify(expected_root_hash)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single state value (or the absence of one) for a state key, along with
+/// the sparse merkle proof that authenticates it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValueWithProof {
+    pub state_key: StateKey,
+    pub state_value: Option<StateValue>, // None iff the key does not exist at the version
+    pub proof: SparseMerkleProof,
+}
+
+impl StateValueWithProof {
+    pub fn new(
+        state_key: StateKey,
+        state_value: Option<StateValue>,
+        proof: SparseMerkleProof,
+    ) -> Self {
+        Self {
+            state_key,
+            state_value,
+            proof,
+        }
+    }
+
+    /// Verifies the state value (or its absence) against the given state root hash
+    pub fn verify(&self, expected_root_hash: HashValue) -> crate::Result<(), Error> {
+        self.proof
+            .verify(
+                expected_root_hash,
+                CryptoHash::hash(&self.state_key),
+                self.state_value.as_ref(),
+            )
+            .map_err(|error| {
+                Error::UnexpectedResponseError(format!(
+                    "Failed to verify the state value proof for key {:?}: {}",
+                    self.state_key, error
+                ))
+            })
+    }
+}
+
 /// The protocol version run by this server. Clients request this first to
 /// identify what API calls and data requests the server supports.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -372,14 +461,22 @@ pub struct ProtocolMetadata {
     pub max_state_chunk_size: u64, // The max number of states the server can return in a single chunk
     pub max_transaction_chunk_size: u64, // The max number of transactions the server can return in a single chunk
     pub max_transaction_output_chunk_size: u64, // The max number of transaction outputs the server can return in a single chunk
+    pub max_state_values_by_keys_chunk_size: u64, // The max number of state keys the server can serve in a single request
 }
 
 impl ProtocolMetadata {
-    /// We deem all requests serviceable, even if the requested chunk
+    /// We deem all range requests serviceable, even if the requested chunk
     /// sizes are larger than the maximum sizes that can be served (the
-    /// response will simply be truncated on the server side).
-    pub fn can_service(&self, _request: &StorageServiceRequest) -> bool {
-        true // TODO: figure out if should eventually remove this
+    /// response will simply be truncated on the server side). However,
+    /// requests for specific state keys must fit within the advertised
+    /// batch limit, as the client explicitly chose each key.
+    pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        match &request.data_request {
+            GetStateValuesByKeysWithProof(request) => {
+                (request.state_keys.len() as u64) <= self.max_state_values_by_keys_chunk_size
+            },
+            _ => true, // TODO: figure out if should eventually remove this
+        }
     }
 }
 
@@ -391,6 +488,7 @@ impl Default for ProtocolMetadata {
             max_transaction_chunk_size: config.max_transaction_chunk_size,
             max_transaction_output_chunk_size: config.max_transaction_output_chunk_size,
             max_state_chunk_size: config.max_state_chunk_size,
+            max_state_values_by_keys_chunk_size: config.max_state_values_by_keys_chunk_size,
         }
     }
 }
@@ -459,6 +557,22 @@ impl DataSummary {
 
                 can_serve_states && can_create_proof
             },
+            GetStateValuesByKeysWithProof(request) => {
+                let proof_version = request.version;
+
+                let can_serve_states = self
+                    .states
+                    .map(|range| range.contains(request.version))
+                    .unwrap_or(false);
+
+                let can_create_proof = self
+                    .synced_ledger_info
+                    .as_ref()
+                    .map(|li| li.ledger_info().version() >= proof_version)
+                    .unwrap_or(false);
+
+                !request.state_keys.is_empty() && can_serve_states && can_create_proof
+            },
             GetTransactionOutputsWithProof(request) => {
                 let desired_range =
                     match CompleteDataRange::new(request.start_version, request.end_version) {
@@ -3,11 +3,13 @@
 
 use crate::{
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, StateValuesWithProofRequest,
-        TransactionOutputsWithProofRequest, TransactionsOrOutputsWithProofRequest,
-        TransactionsWithProofRequest,
+        DataRequest, EpochEndingLedgerInfoRequest, StateValuesByKeysWithProofRequest,
+        StateValuesWithProofRequest, TransactionOutputsWithProofRequest,
+        TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
+    },
+    responses::{
+        CompleteDataRange, DataResponse, DataSummary, ProtocolMetadata, StorageServerSummary,
     },
-    responses::{CompleteDataRange, DataSummary, ProtocolMetadata},
     Epoch, StorageServiceRequest,
 };
 use aptos_crypto::hash::HashValue;
@@ -15,6 +17,7 @@ use aptos_types::{
     aggregate_signature::AggregateSignature,
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    state_store::state_key::StateKey,
     transaction::Version,
 };
 use claims::{assert_err, assert_ok};
@@ -209,6 +212,30 @@ fn test_data_summary_can_service_state_chunk_request() {
     }
 }
 
+#[test]
+fn test_data_summary_can_service_state_values_by_keys_request() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        states: Some(create_range(100, 300)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // in range and can provide proof => can service
+        assert!(summary.can_service(&state_values_by_keys_request(100, 10, compression)));
+        assert!(summary.can_service(&state_values_by_keys_request(250, 10, compression)));
+
+        // in range, but cannot provide proof => cannot service
+        assert!(!summary.can_service(&state_values_by_keys_request(251, 10, compression)));
+
+        // can provide proof, but out of range ==> cannot service
+        assert!(!summary.can_service(&state_values_by_keys_request(99, 10, compression)));
+
+        // no state keys were requested ==> cannot service
+        assert!(!summary.can_service(&state_values_by_keys_request(200, 0, compression)));
+    }
+}
+
 #[test]
 fn test_protocol_metadata_can_service() {
     let metadata = ProtocolMetadata {
@@ -216,6 +243,7 @@ fn test_protocol_metadata_can_service() {
         max_epoch_chunk_size: 100,
         max_transaction_output_chunk_size: 100,
         max_state_chunk_size: 100,
+        max_state_values_by_keys_chunk_size: 100,
     };
 
     for compression in [true, false] {
@@ -230,9 +258,47 @@ fn test_protocol_metadata_can_service() {
         assert!(metadata.can_service(&epochs_request(100, 10000, compression)));
         assert!(metadata.can_service(&outputs_request(200, 100, 9999989, compression)));
         assert!(metadata.can_service(&state_values_request(200, 100, 200, compression)));
+
+        // Requests for specific state keys must fit within the max
+        assert!(metadata.can_service(&state_values_by_keys_request(200, 1, compression)));
+        assert!(metadata.can_service(&state_values_by_keys_request(200, 100, compression)));
+        assert!(!metadata.can_service(&state_values_by_keys_request(200, 101, compression)));
     }
 }
 
+#[test]
+fn test_new_messages_are_backward_compatible() {
+    // The variants existing before the state values by keys were added keep their BCS index
+    assert_eq!(
+        bcs::to_bytes(&DataRequest::GetStorageServerSummary).unwrap(),
+        vec![6]
+    );
+    assert_eq!(
+        bcs::to_bytes(&DataResponse::NumberOfStatesAtVersion(0)).unwrap()[0],
+        3
+    );
+    assert_eq!(
+        bcs::to_bytes(&DataResponse::StorageServerSummary(
+            StorageServerSummary::default()
+        ))
+        .unwrap()[0],
+        6
+    );
+
+    // The old protocol metadata fields are encoded first, in the same order
+    let protocol_metadata = ProtocolMetadata {
+        max_epoch_chunk_size: 1,
+        max_state_chunk_size: 2,
+        max_transaction_chunk_size: 3,
+        max_transaction_output_chunk_size: 4,
+        max_state_values_by_keys_chunk_size: 5,
+    };
+    let old_fields = bcs::to_bytes(&(1u64, 2u64, 3u64, 4u64)).unwrap();
+    assert!(bcs::to_bytes(&protocol_metadata)
+        .unwrap()
+        .starts_with(&old_fields));
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(1000))]
 
@@ -326,6 +392,22 @@ fn state_values_request(
     StorageServiceRequest::new(data_request, use_compression)
 }
 
+fn state_values_by_keys_request(
+    version: Version,
+    num_state_keys: u64,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let state_keys = (0..num_state_keys)
+        .map(|index| StateKey::raw(index.to_le_bytes().to_vec()))
+        .collect();
+    let data_request =
+        DataRequest::GetStateValuesByKeysWithProof(StateValuesByKeysWithProofRequest {
+            version,
+            state_keys,
+        });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
 fn states_request(version: Version, use_compression: bool) -> StorageServiceRequest {
     state_values_request(version, 0, 1000, use_compression)
 }
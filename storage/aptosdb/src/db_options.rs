@@ -19,6 +19,7 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
         EVENT_BY_KEY_CF_NAME,
         EVENT_BY_VERSION_CF_NAME,
         EVENT_CF_NAME,
+        EXECUTION_FINGERPRINT_CF_NAME,
         LEDGER_INFO_CF_NAME,
         STALE_STATE_VALUE_INDEX_CF_NAME,
         STATE_VALUE_CF_NAME,
@@ -66,6 +67,7 @@ pub(super) fn transaction_info_db_column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         DB_METADATA_CF_NAME,
+        EXECUTION_FINGERPRINT_CF_NAME,
         TRANSACTION_INFO_CF_NAME,
     ]
 }
@@ -9,8 +9,8 @@ use crate::{
     errors::AptosDbError,
     ledger_db::LedgerDb,
     schema::{
-        epoch_by_version::EpochByVersionSchema, ledger_info::LedgerInfoSchema,
-        transaction_accumulator::TransactionAccumulatorSchema,
+        epoch_by_version::EpochByVersionSchema, execution_fingerprint::ExecutionFingerprintSchema,
+        ledger_info::LedgerInfoSchema, transaction_accumulator::TransactionAccumulatorSchema,
         transaction_info::TransactionInfoSchema,
     },
     utils::iterators::{EpochEndingLedgerInfoIter, ExpectContinuousVersions},
@@ -29,7 +29,7 @@ use aptos_types::{
         definition::LeafCount, position::Position, AccumulatorConsistencyProof,
         TransactionAccumulatorProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof,
     },
-    transaction::{TransactionInfo, Version},
+    transaction::{ExecutionFingerprint, TransactionInfo, Version},
 };
 use arc_swap::ArcSwap;
 use itertools::Itertools;
@@ -193,6 +193,26 @@ impl LedgerStore {
             .ok_or_else(|| AptosDbError::NotFound(String::from("Genesis TransactionInfo.")).into())
     }
 
+    /// Get the execution fingerprints recorded for the transactions in
+    /// [`start_version`, `end_version`), whose local execution didn't match the transaction
+    /// infos they were verified against.
+    pub fn get_recorded_execution_fingerprints(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        let mut iter = self
+            .ledger_db
+            .transaction_info_db()
+            .iter::<ExecutionFingerprintSchema>(ReadOptions::default())?;
+        iter.seek(&start_version)?;
+        iter.take_while(|res| {
+            res.as_ref()
+                .map_or(true, |(version, _)| *version < end_version)
+        })
+        .collect()
+    }
+
     /// Gets an iterator that yields `num_transaction_infos` transaction infos starting from
     /// `start_version`.
     pub(crate) fn get_transaction_info_iter(
@@ -310,6 +330,16 @@ impl LedgerStore {
         Ok(root_hash)
     }
 
+    /// Write the execution fingerprint of the transaction at `version` to `batch`.
+    pub fn put_execution_fingerprint(
+        &self,
+        version: Version,
+        execution_fingerprint: &ExecutionFingerprint,
+        batch: &SchemaBatch,
+    ) -> Result<()> {
+        batch.put::<ExecutionFingerprintSchema>(&version, execution_fingerprint)
+    }
+
     /// Write `ledger_info_with_sigs` to `batch`.
     pub fn put_ledger_info(
         &self,
@@ -107,9 +107,9 @@ use aptos_types::{
         ShardedStateUpdates,
     },
     transaction::{
-        AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof,
-        TransactionOutput, TransactionOutputListWithProof, TransactionToCommit,
-        TransactionWithProof, Version,
+        AccountTransactionsWithProof, ExecutionFingerprint, Transaction, TransactionInfo,
+        TransactionListWithProof, TransactionOutput, TransactionOutputListWithProof,
+        TransactionToCommit, TransactionWithProof, Version,
     },
     write_set::WriteSet,
 };
@@ -1439,6 +1439,21 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_execution_fingerprints(
+        &self,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        gauged_api("get_execution_fingerprints", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+
+            self.ledger_store.get_recorded_execution_fingerprints(
+                start_version,
+                start_version.saturating_add(limit),
+            )
+        })
+    }
+
     fn get_events(
         &self,
         event_key: &EventKey,
@@ -1944,6 +1959,24 @@ impl DbWriter for AptosDB {
     }
 
     /// Same as save_transactions, but only for a whole block.
+    fn save_execution_fingerprints(
+        &self,
+        first_version: Version,
+        execution_fingerprints: &[ExecutionFingerprint],
+    ) -> Result<()> {
+        gauged_api("save_execution_fingerprints", || {
+            let batch = SchemaBatch::new();
+            for (version, execution_fingerprint) in (first_version..).zip(execution_fingerprints) {
+                self.ledger_store.put_execution_fingerprint(
+                    version,
+                    execution_fingerprint,
+                    &batch,
+                )?;
+            }
+            self.ledger_db.transaction_info_db().write_schemas(batch)
+        })
+    }
+
     fn save_transaction_block(
         &self,
         txns_to_commit: &[Arc<TransactionToCommit>],
@@ -0,0 +1,56 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the fingerprints of the local execution of
+//! the transactions which didn't match the transaction infos they were verified against, recorded
+//! even though they are never committed.
+//!
+//! Serialized execution fingerprint bytes identified by version.
+//! ```text
+//! |<--key-->|<-------value------->|
+//! | version | fingerprint bytes   |
+//! ```
+//!
+//! `Version` is serialized in big endian so that records in RocksDB will be in order of it's
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, EXECUTION_FINGERPRINT_CF_NAME};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::{ExecutionFingerprint, Version};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::mem::size_of;
+
+define_schema!(
+    ExecutionFingerprintSchema,
+    Version,
+    ExecutionFingerprint,
+    EXECUTION_FINGERPRINT_CF_NAME
+);
+
+impl KeyCodec<ExecutionFingerprintSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<ExecutionFingerprintSchema> for ExecutionFingerprint {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,19 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::transaction::{ExecutionFingerprint, Version};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        execution_fingerprint in any::<ExecutionFingerprint>(),
+    ) {
+        assert_encode_decode::<ExecutionFingerprintSchema>(&version, &execution_fingerprint);
+    }
+}
+
+test_no_panic_decoding!(ExecutionFingerprintSchema);
@@ -12,6 +12,7 @@ pub(crate) mod event;
 pub(crate) mod event_accumulator;
 pub(crate) mod event_by_key;
 pub(crate) mod event_by_version;
+pub(crate) mod execution_fingerprint;
 pub(crate) mod jellyfish_merkle_node;
 pub(crate) mod ledger_info;
 pub(crate) mod stale_node_index;
@@ -35,6 +36,7 @@ pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
 pub const EVENT_BY_VERSION_CF_NAME: ColumnFamilyName = "event_by_version";
 pub const EVENT_CF_NAME: ColumnFamilyName = "event";
+pub const EXECUTION_FINGERPRINT_CF_NAME: ColumnFamilyName = "execution_fingerprint";
 pub const JELLYFISH_MERKLE_NODE_CF_NAME: ColumnFamilyName = "jellyfish_merkle_node";
 pub const LEDGER_INFO_CF_NAME: ColumnFamilyName = "ledger_info";
 pub const STALE_NODE_INDEX_CF_NAME: ColumnFamilyName = "stale_node_index";
@@ -81,6 +83,9 @@ pub mod fuzzing {
             assert_no_panic_decoding::<super::event_accumulator::EventAccumulatorSchema>(data);
             assert_no_panic_decoding::<super::event_by_key::EventByKeySchema>(data);
             assert_no_panic_decoding::<super::event_by_version::EventByVersionSchema>(data);
+            assert_no_panic_decoding::<super::execution_fingerprint::ExecutionFingerprintSchema>(
+                data,
+            );
             assert_no_panic_decoding::<super::jellyfish_merkle_node::JellyfishMerkleNodeSchema>(
                 data,
             );
@@ -8,7 +8,8 @@ use crate::{
     errors::AptosDbError,
     ledger_db::LedgerDb,
     schema::{
-        transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        execution_fingerprint::ExecutionFingerprintSchema, transaction::TransactionSchema,
+        transaction_by_account::TransactionByAccountSchema,
         transaction_by_hash::TransactionByHashSchema, write_set::WriteSetSchema,
     },
     transaction_accumulator::TransactionAccumulatorSchema,
@@ -262,7 +263,8 @@ impl TransactionStore {
         Ok(())
     }
 
-    /// Prune the transaction schema store between a range of version in [begin, end)
+    /// Prune the transaction info schema store, and the execution fingerprints stored alongside,
+    /// between a range of version in [begin, end)
     pub fn prune_transaction_info_schema(
         &self,
         begin: Version,
@@ -271,6 +273,7 @@ impl TransactionStore {
     ) -> Result<()> {
         for version in begin..end {
             db_batch.delete::<TransactionInfoSchema>(&version)?;
+            db_batch.delete::<ExecutionFingerprintSchema>(&version)?;
         }
         Ok(())
     }
@@ -0,0 +1,124 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_consensus::{
+    inspect_consensus_db, inspect_quorum_store_db, plan_prune_to_committed, prune_to_committed,
+    read_safety_data,
+};
+use aptos_db::AptosDB;
+use aptos_storage_interface::DbReader;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Inspect the ConsensusDB and the QuorumstoreDB, and prune them to the committed block
+#[derive(Parser)]
+pub enum Command {
+    Inspect(InspectCmd),
+    PruneToCommitted(PruneToCommittedCmd),
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Command::Inspect(cmd) => cmd.run(),
+            Command::PruneToCommitted(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Parser)]
+#[clap(
+    about = "Print the block tree and the quorum certs of the ConsensusDB, the batches of the \
+    QuorumstoreDB and the safety data, without modifying them."
+)]
+pub struct InspectCmd {
+    #[clap(long, parse(from_os_str), help = "The storage dir of the node.")]
+    db_dir: PathBuf,
+    #[clap(
+        long,
+        parse(from_os_str),
+        help = "The on-disk secure storage of the safety rules, to print the safety data."
+    )]
+    secure_storage_path: Option<PathBuf>,
+    #[clap(long, help = "Don't print the batches of the QuorumstoreDB.")]
+    skip_batches: bool,
+}
+
+impl InspectCmd {
+    pub fn run(self) -> Result<()> {
+        let report = inspect_consensus_db(&self.db_dir)?;
+        println!("ConsensusDB: {}", serde_json::to_string_pretty(&report)?);
+
+        if !self.skip_batches {
+            let batches = inspect_quorum_store_db(&self.db_dir)?;
+            println!("QuorumstoreDB: {}", serde_json::to_string_pretty(&batches)?);
+        }
+
+        if let Some(secure_storage_path) = self.secure_storage_path {
+            let safety_data = read_safety_data(secure_storage_path)?;
+            println!(
+                "SafetyData: {}",
+                serde_json::to_string_pretty(&safety_data)?
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+#[clap(
+    about = "Delete the blocks and quorum certs of the ConsensusDB that aren't required to restart \
+    from the latest committed block, and the expired batches of the QuorumstoreDB. The node must \
+    be stopped. The safety data isn't modified."
+)]
+pub struct PruneToCommittedCmd {
+    #[clap(long, parse(from_os_str), help = "The storage dir of the node.")]
+    db_dir: PathBuf,
+    #[clap(long, help = "Print the data to delete, without deleting it.")]
+    dry_run: bool,
+}
+
+impl PruneToCommittedCmd {
+    pub fn run(self) -> Result<()> {
+        let committed_ledger_info = {
+            let aptos_db = AptosDB::open(
+                &self.db_dir,
+                true,                        /* read_only */
+                NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+                RocksdbConfigs::default(),
+                false,
+                BUFFERED_STATE_TARGET_ITEMS,
+                DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            )?;
+            aptos_db.get_latest_ledger_info()?.ledger_info().clone()
+        };
+        println!("Latest committed LedgerInfo: {}", committed_ledger_info);
+
+        let plan = plan_prune_to_committed(&self.db_dir, &committed_ledger_info)?;
+        println!("Prune plan: {}", serde_json::to_string_pretty(&plan)?);
+        if !plan.root_found {
+            println!(
+                "The committed block can't be restored from the ConsensusDB, all the blocks will \
+                be pruned and consensus will recover from the ledger."
+            );
+        }
+
+        if self.dry_run {
+            println!("Dry run, nothing was deleted.");
+        } else {
+            prune_to_committed(&self.db_dir, &plan)?;
+            println!(
+                "Deleted {} blocks, {} quorum certs and {} batches.",
+                plan.blocks_to_prune.len(),
+                plan.quorum_certs_to_prune.len(),
+                plan.batches_to_prune.len()
+            );
+        }
+        Ok(())
+    }
+}
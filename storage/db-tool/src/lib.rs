@@ -5,6 +5,7 @@ extern crate core;
 
 mod backup;
 mod backup_maintenance;
+mod consensus_db;
 mod debugger;
 mod replay_verify;
 pub mod restore;
@@ -27,6 +28,8 @@ pub enum DBTool {
     Debug(debugger::Command),
     #[clap(subcommand)]
     BackupMaintenance(backup_maintenance::Command),
+    #[clap(subcommand)]
+    ConsensusDb(consensus_db::Command),
 }
 
 impl DBTool {
@@ -37,6 +40,7 @@ impl DBTool {
             DBTool::ReplayVerify(cmd) => cmd.run().await,
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
             DBTool::Debug(cmd) => cmd.run(),
+            DBTool::ConsensusDb(cmd) => cmd.run(),
         }
     }
 }
@@ -30,8 +30,9 @@ use aptos_types::{
         ShardedStateUpdates,
     },
     transaction::{
-        AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof,
-        TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
+        AccountTransactionsWithProof, ExecutionFingerprint, Transaction, TransactionInfo,
+        TransactionListWithProof, TransactionOutputListWithProof, TransactionToCommit,
+        TransactionWithProof, Version,
     },
     write_set::WriteSet,
 };
@@ -186,6 +187,18 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the execution fingerprints recorded for the transactions in
+    /// [`start_version`, `start_version` + `limit`), when the local execution didn't match the
+    /// transaction infos it was verified against. The versions without a recorded fingerprint are
+    /// skipped, and the recorded ones can be beyond the latest ledger version.
+    fn get_execution_fingerprints(
+        &self,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionFingerprint)>> {
+        unimplemented!()
+    }
+
     /// Returns events by given event key
     fn get_events(
         &self,
@@ -634,6 +647,17 @@ pub trait DbWriter: Send + Sync {
         unimplemented!()
     }
 
+    /// Records the fingerprints of the local execution of the transactions from `first_version`,
+    /// which didn't match the transaction infos they were verified against and so are never
+    /// committed.
+    fn save_execution_fingerprints(
+        &self,
+        first_version: Version,
+        execution_fingerprints: &[ExecutionFingerprint],
+    ) -> Result<()> {
+        unimplemented!()
+    }
+
     /// Persist transactions for block.
     /// See [`AptosDB::save_transaction_block`].
     ///
@@ -185,7 +185,7 @@ async fn test_onchain_config_change() {
         .with_init_genesis_config(Arc::new(|genesis_config| {
             let inner = match genesis_config.consensus_config.clone() {
                 OnChainConsensusConfig::V1(inner) => inner,
-                OnChainConsensusConfig::V2(inner) => inner,
+                OnChainConsensusConfig::V2(inner) | OnChainConsensusConfig::DagV1(inner) => inner,
             };
 
             let leader_reputation_type =
@@ -242,7 +242,7 @@ async fn test_onchain_config_change() {
 
     let inner = match current_consensus_config {
         OnChainConsensusConfig::V1(inner) => inner,
-        OnChainConsensusConfig::V2(inner) => inner,
+        OnChainConsensusConfig::V2(inner) | OnChainConsensusConfig::DagV1(inner) => inner,
     };
     let leader_reputation_type =
         if let ProposerElectionType::LeaderReputation(leader_reputation_type) =
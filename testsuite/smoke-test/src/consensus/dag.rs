@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{smoke_test_environment::SwarmBuilder, txn_emitter::generate_traffic};
+use aptos_forge::{Swarm, SwarmExt, TransactionType};
+use aptos_types::on_chain_config::{ConsensusConfigV1, OnChainConsensusConfig};
+use std::{sync::Arc, time::Duration};
+
+const MAX_WAIT_SECS: u64 = 60;
+
+#[tokio::test]
+async fn test_dag_consensus_commits_transactions() {
+    let mut swarm = SwarmBuilder::new_local(4)
+        .with_aptos()
+        .with_init_genesis_config(Arc::new(|genesis_config| {
+            genesis_config.consensus_config =
+                OnChainConsensusConfig::DagV1(ConsensusConfigV1::default())
+        }))
+        .build()
+        .await;
+    let validator_peer_ids = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+
+    let txn_stat = generate_traffic(
+        &mut swarm,
+        &validator_peer_ids,
+        Duration::from_secs(10),
+        1,
+        vec![vec![(
+            TransactionType::CoinTransfer {
+                invalid_transaction_ratio: 0,
+                sender_use_account_pool: false,
+            },
+            1,
+        )]],
+    )
+    .await
+    .unwrap();
+    println!("{:?}", txn_stat.rate());
+    // assert some much smaller number than expected, so it doesn't fail under contention
+    assert!(txn_stat.submitted > 30);
+    assert!(txn_stat.committed > 30);
+
+    swarm
+        .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_WAIT_SECS))
+        .await
+        .unwrap();
+}
@@ -19,7 +19,7 @@ use move_binary_format::{
 use move_core_types::{state::VMState, vm_status::StatusCode};
 use std::time::Instant;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VerifierConfig {
     pub max_loop_depth: Option<usize>,
     pub max_function_parameters: Option<usize>,
@@ -5,7 +5,8 @@
 use crate::compiler::compile_modules_in_file;
 use move_binary_format::{
     file_format::{
-        empty_module, AddressIdentifierIndex, IdentifierIndex, ModuleHandle, TableIndex,
+        empty_module, AddressIdentifierIndex, CompiledScript, IdentifierIndex, ModuleHandle,
+        TableIndex,
     },
     CompiledModule,
 };
@@ -15,10 +16,18 @@ use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::ModuleId,
 };
-use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM, verified_code_cache::VerifiedCodeCache};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas::UnmeteredGasMeter;
-use std::{path::PathBuf, sync::Arc, thread};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
 
 const WORKING_ACCOUNT: AccountAddress = AccountAddress::TWO;
 
@@ -81,6 +90,23 @@ impl Adapter {
         }
     }
 
+    fn fresh_with_verified_code_cache(self, cache: Arc<dyn VerifiedCodeCache>) -> Self {
+        let config = VMConfig {
+            verifier: VerifierConfig {
+                max_dependency_depth: Some(100),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Self {
+            store: self.store,
+            vm: Arc::new(
+                MoveVM::new_with_config_and_verified_code_cache(vec![], config, cache).unwrap(),
+            ),
+            functions: self.functions,
+        }
+    }
+
     fn publish_modules(&mut self, modules: Vec<CompiledModule>) {
         let mut session = self.vm.new_session(&self.store);
 
@@ -196,6 +222,56 @@ fn load_concurrent_many() {
     adapter.call_functions_async(30);
 }
 
+#[derive(Default)]
+struct TestVerifiedCodeCache {
+    modules: Mutex<HashMap<[u8; 32], Arc<CompiledModule>>>,
+    module_hits: AtomicUsize,
+}
+
+impl VerifiedCodeCache for TestVerifiedCodeCache {
+    fn get_module(&self, hash: &[u8; 32]) -> Option<Arc<CompiledModule>> {
+        let module = self.modules.lock().unwrap().get(hash).cloned();
+        if module.is_some() {
+            self.module_hits.fetch_add(1, Ordering::Relaxed);
+        }
+        module
+    }
+
+    fn insert_module(&self, hash: [u8; 32], module: Arc<CompiledModule>) {
+        self.modules.lock().unwrap().insert(hash, module);
+    }
+
+    fn get_script(&self, _hash: &[u8; 32]) -> Option<Arc<CompiledScript>> {
+        None
+    }
+
+    fn insert_script(&self, _hash: [u8; 32], _script: Arc<CompiledScript>) {}
+}
+
+#[test]
+fn load_with_verified_code_cache() {
+    let data_store = InMemoryStorage::new();
+    let mut adapter = Adapter::new(data_store);
+    let modules = get_modules();
+    adapter.publish_modules(modules);
+
+    let cache = Arc::new(TestVerifiedCodeCache::default());
+    let adapter = adapter.fresh_with_verified_code_cache(cache.clone());
+    adapter.call_functions();
+    let num_loaded_modules = cache.modules.lock().unwrap().len();
+    assert!(num_loaded_modules > 0);
+    assert_eq!(cache.module_hits.load(Ordering::Relaxed), 0);
+
+    // A new VM sharing the cache doesn't verify the modules again.
+    let adapter = adapter.fresh_with_verified_code_cache(cache.clone());
+    adapter.call_functions();
+    assert_eq!(cache.modules.lock().unwrap().len(), num_loaded_modules);
+    assert_eq!(
+        cache.module_hits.load(Ordering::Relaxed),
+        num_loaded_modules
+    );
+}
+
 #[test]
 fn deep_dependency_list_err_0() {
     let data_store = InMemoryStorage::new();
@@ -22,6 +22,7 @@ pub mod session;
 #[macro_use]
 mod tracing;
 pub mod config;
+pub mod verified_code_cache;
 
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
@@ -8,6 +8,7 @@ use crate::{
     logging::expect_no_verification_errors,
     native_functions::{NativeFunction, NativeFunctions, UnboxedNativeFunction},
     session::LoadedFunctionInstantiation,
+    verified_code_cache::VerifiedCodeCache,
 };
 use move_binary_format::{
     access::{ModuleAccess, ScriptAccess},
@@ -608,10 +609,18 @@ pub(crate) struct Loader {
     module_cache_hits: RwLock<BTreeSet<ModuleId>>,
 
     vm_config: VMConfig,
+
+    // The code deserialized and verified by this loader and by the other loaders sharing the
+    // cache. Unlike the caches above, it is never flushed, as it is keyed by the hash of the code.
+    verified_code_cache: Option<Arc<dyn VerifiedCodeCache>>,
 }
 
 impl Loader {
-    pub(crate) fn new(natives: NativeFunctions, vm_config: VMConfig) -> Self {
+    pub(crate) fn new(
+        natives: NativeFunctions,
+        vm_config: VMConfig,
+        verified_code_cache: Option<Arc<dyn VerifiedCodeCache>>,
+    ) -> Self {
         Self {
             scripts: RwLock::new(ScriptCache::new()),
             module_cache: RwLock::new(ModuleCache::new()),
@@ -620,6 +629,7 @@ impl Loader {
             invalidated: RwLock::new(false),
             module_cache_hits: RwLock::new(BTreeSet::new()),
             vm_config,
+            verified_code_cache,
         }
     }
 
@@ -704,7 +714,8 @@ impl Loader {
         let (main, parameters, return_) = match scripts.get(&hash_value) {
             Some(cached) => cached,
             None => {
-                let ver_script = self.deserialize_and_verify_script(script_blob, data_store)?;
+                let ver_script =
+                    self.deserialize_and_verify_script(script_blob, &hash_value, data_store)?;
                 let script = Script::new(ver_script, &hash_value, &self.module_cache.read())?;
                 scripts.insert(hash_value, script)
             },
@@ -745,7 +756,32 @@ impl Loader {
     fn deserialize_and_verify_script(
         &self,
         script: &[u8],
+        hash_value: &ScriptHash,
         data_store: &TransactionDataCache,
+    ) -> VMResult<CompiledScript> {
+        let cached_script = self
+            .verified_code_cache
+            .as_ref()
+            .and_then(|cache| cache.get_script(hash_value));
+        let script = match cached_script {
+            Some(script) => script.as_ref().clone(),
+            None => self.deserialize_and_verify_script_without_dependencies(script, hash_value)?,
+        };
+
+        // verify dependencies
+        let loaded_deps = script
+            .immediate_dependencies()
+            .into_iter()
+            .map(|module_id| self.load_module(&module_id, data_store))
+            .collect::<VMResult<_>>()?;
+        self.verify_script_dependencies(&script, loaded_deps)?;
+        Ok(script)
+    }
+
+    fn deserialize_and_verify_script_without_dependencies(
+        &self,
+        script: &[u8],
+        hash_value: &ScriptHash,
     ) -> VMResult<CompiledScript> {
         let script = match CompiledScript::deserialize_with_max_version(
             script,
@@ -760,19 +796,11 @@ impl Loader {
             },
         };
 
-        match self.verify_script(&script) {
-            Ok(_) => {
-                // verify dependencies
-                let loaded_deps = script
-                    .immediate_dependencies()
-                    .into_iter()
-                    .map(|module_id| self.load_module(&module_id, data_store))
-                    .collect::<VMResult<_>>()?;
-                self.verify_script_dependencies(&script, loaded_deps)?;
-                Ok(script)
-            },
-            Err(err) => Err(err),
+        self.verify_script(&script)?;
+        if let Some(cache) = &self.verified_code_cache {
+            cache.insert_script(*hash_value, Arc::new(script.clone()));
         }
+        Ok(script)
     }
 
     // Script verification steps.
@@ -1246,6 +1274,28 @@ impl Loader {
             },
         };
 
+        let hash_value = self.verified_code_cache.as_ref().map(|_| {
+            let mut sha3_256 = Sha3_256::new();
+            sha3_256.update(&bytes);
+            let hash_value: [u8; 32] = sha3_256.finalize().into();
+            hash_value
+        });
+        if let Some(module) = self
+            .verified_code_cache
+            .as_ref()
+            .zip(hash_value.as_ref())
+            .and_then(|(cache, hash_value)| cache.get_module(hash_value))
+        {
+            if self.vm_config.paranoid_type_checks && &module.self_id() != id {
+                return Err(
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                        .with_message("Module self id mismatch with storage".to_string())
+                        .finish(Location::Module(id.clone())),
+                );
+            }
+            return Ok(module.as_ref().clone());
+        }
+
         // for bytes obtained from the data store, they should always deserialize and verify.
         // It is an invariant violation if they don't.
         let module = CompiledModule::deserialize_with_max_version(
@@ -1275,6 +1325,9 @@ impl Loader {
             .map_err(expect_no_verification_errors)?;
         self.check_natives(&module)
             .map_err(expect_no_verification_errors)?;
+        if let Some((cache, hash_value)) = self.verified_code_cache.as_ref().zip(hash_value) {
+            cache.insert_module(hash_value, Arc::new(module.clone()));
+        }
         Ok(module)
     }
 
@@ -5,6 +5,7 @@
 use crate::{
     config::VMConfig, data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
     native_functions::NativeFunction, runtime::VMRuntime, session::Session,
+    verified_code_cache::VerifiedCodeCache,
 };
 use move_binary_format::{
     errors::{Location, VMResult},
@@ -32,7 +33,20 @@ impl MoveVM {
         vm_config: VMConfig,
     ) -> VMResult<Self> {
         Ok(Self {
-            runtime: VMRuntime::new(natives, vm_config)
+            runtime: VMRuntime::new(natives, vm_config, None)
+                .map_err(|err| err.finish(Location::Undefined))?,
+        })
+    }
+
+    /// Creates a VM as in `new_with_config`, which reuses the code verified by the other VMs
+    /// sharing the cache, and shares the code it verifies with them.
+    pub fn new_with_config_and_verified_code_cache(
+        natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+        vm_config: VMConfig,
+        verified_code_cache: Arc<dyn VerifiedCodeCache>,
+    ) -> VMResult<Self> {
+        Ok(Self {
+            runtime: VMRuntime::new(natives, vm_config, Some(verified_code_cache))
                 .map_err(|err| err.finish(Location::Undefined))?,
         })
     }
@@ -10,6 +10,7 @@ use crate::{
     native_extensions::NativeContextExtensions,
     native_functions::{NativeFunction, NativeFunctions},
     session::{LoadedFunctionInstantiation, SerializedReturnValues},
+    verified_code_cache::VerifiedCodeCache,
 };
 use move_binary_format::{
     access::ModuleAccess,
@@ -42,9 +43,14 @@ impl VMRuntime {
     pub(crate) fn new(
         natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
         vm_config: VMConfig,
+        verified_code_cache: Option<Arc<dyn VerifiedCodeCache>>,
     ) -> PartialVMResult<Self> {
         Ok(VMRuntime {
-            loader: Loader::new(NativeFunctions::new(natives)?, vm_config),
+            loader: Loader::new(
+                NativeFunctions::new(natives)?,
+                vm_config,
+                verified_code_cache,
+            ),
         })
     }
 
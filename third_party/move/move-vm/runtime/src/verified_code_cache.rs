@@ -0,0 +1,23 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::file_format::{CompiledModule, CompiledScript};
+use std::sync::Arc;
+
+/// A cache of the modules and scripts which have been deserialized and checked by the bytecode
+/// verifier, keyed by the SHA3-256 hash of their bytes. It outlives the loader of a VM, so it can
+/// be shared by several VMs (e.g. across blocks) to avoid verifying the same code again.
+///
+/// The code is only checked on its own before being cached: linking it against its dependencies
+/// still happens in each VM. As the outcome of the checks only depends on the bytes and on the
+/// `VMConfig`, a cache must only be shared by VMs with the same config, but it never has to be
+/// invalidated when code is published, since new code has a new hash.
+pub trait VerifiedCodeCache: Send + Sync {
+    fn get_module(&self, hash: &[u8; 32]) -> Option<Arc<CompiledModule>>;
+
+    fn insert_module(&self, hash: [u8; 32], module: Arc<CompiledModule>);
+
+    fn get_script(&self, hash: &[u8; 32]) -> Option<Arc<CompiledScript>>;
+
+    fn insert_script(&self, hash: [u8; 32], script: Arc<CompiledScript>);
+}
@@ -31,7 +31,7 @@ pub enum FeatureFlag {
 }
 
 /// Representation of features on chain as a bitset.
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 pub struct Features {
     #[serde(with = "serde_bytes")]
     pub features: Vec<u8>,
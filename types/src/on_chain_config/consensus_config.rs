@@ -13,6 +13,8 @@ use std::collections::HashMap;
 pub enum OnChainConsensusConfig {
     V1(ConsensusConfigV1),
     V2(ConsensusConfigV1),
+    // DAG based consensus, the nodes carry the transactions directly, reusing the same config struct
+    DagV1(ConsensusConfigV1),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -20,9 +22,9 @@ impl OnChainConsensusConfig {
     /// The number of recent rounds that don't count into reputations.
     pub fn leader_reputation_exclude_round(&self) -> u64 {
         match &self {
-            OnChainConsensusConfig::V1(config) | OnChainConsensusConfig::V2(config) => {
-                config.exclude_round
-            },
+            OnChainConsensusConfig::V1(config)
+            | OnChainConsensusConfig::V2(config)
+            | OnChainConsensusConfig::DagV1(config) => config.exclude_round,
         }
     }
 
@@ -35,27 +37,32 @@ impl OnChainConsensusConfig {
     // to this max size.
     pub fn max_failed_authors_to_store(&self) -> usize {
         match &self {
-            OnChainConsensusConfig::V1(config) | OnChainConsensusConfig::V2(config) => {
-                config.max_failed_authors_to_store
-            },
+            OnChainConsensusConfig::V1(config)
+            | OnChainConsensusConfig::V2(config)
+            | OnChainConsensusConfig::DagV1(config) => config.max_failed_authors_to_store,
         }
     }
 
     // Type and configuration used for proposer election.
     pub fn proposer_election_type(&self) -> &ProposerElectionType {
         match &self {
-            OnChainConsensusConfig::V1(config) | OnChainConsensusConfig::V2(config) => {
-                &config.proposer_election_type
-            },
+            OnChainConsensusConfig::V1(config)
+            | OnChainConsensusConfig::V2(config)
+            | OnChainConsensusConfig::DagV1(config) => &config.proposer_election_type,
         }
     }
 
     pub fn quorum_store_enabled(&self) -> bool {
         match &self {
-            OnChainConsensusConfig::V1(_config) => false,
+            OnChainConsensusConfig::V1(_config) | OnChainConsensusConfig::DagV1(_config) => false,
             OnChainConsensusConfig::V2(_config) => true,
         }
     }
+
+    /// Whether blocks are ordered by the DAG based protocol instead of the round manager.
+    pub fn dag_enabled(&self) -> bool {
+        matches!(self, OnChainConsensusConfig::DagV1(_))
+    }
 }
 
 /// This is used when on-chain config is not initialized.
@@ -11,6 +11,7 @@ pub enum OnChainExecutionConfig {
     V1(ExecutionConfigV1),
     V2(ExecutionConfigV2),
     V3(ExecutionConfigV3),
+    V4(ExecutionConfigV4),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -21,6 +22,7 @@ impl OnChainExecutionConfig {
             OnChainExecutionConfig::V1(config) => config.transaction_shuffler_type.clone(),
             OnChainExecutionConfig::V2(config) => config.transaction_shuffler_type.clone(),
             OnChainExecutionConfig::V3(config) => config.transaction_shuffler_type.clone(),
+            OnChainExecutionConfig::V4(config) => config.transaction_shuffler_type.clone(),
         }
     }
 
@@ -30,6 +32,7 @@ impl OnChainExecutionConfig {
             OnChainExecutionConfig::V1(_config) => None,
             OnChainExecutionConfig::V2(config) => config.block_gas_limit,
             OnChainExecutionConfig::V3(config) => config.block_gas_limit,
+            OnChainExecutionConfig::V4(config) => config.block_gas_limit,
         }
     }
 
@@ -39,6 +42,17 @@ impl OnChainExecutionConfig {
             OnChainExecutionConfig::V1(_config) => TransactionDeduperType::NoDedup,
             OnChainExecutionConfig::V2(_config) => TransactionDeduperType::NoDedup,
             OnChainExecutionConfig::V3(config) => config.transaction_deduper_type.clone(),
+            OnChainExecutionConfig::V4(config) => config.transaction_deduper_type.clone(),
+        }
+    }
+
+    /// The type of the block partitioner being used.
+    pub fn block_partitioner_type(&self) -> BlockPartitionerType {
+        match &self {
+            OnChainExecutionConfig::V1(_config) => BlockPartitionerType::NoPartitioning,
+            OnChainExecutionConfig::V2(_config) => BlockPartitionerType::NoPartitioning,
+            OnChainExecutionConfig::V3(_config) => BlockPartitionerType::NoPartitioning,
+            OnChainExecutionConfig::V4(config) => config.block_partitioner_type.clone(),
         }
     }
 }
@@ -113,11 +127,31 @@ impl Default for ExecutionConfigV3 {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ExecutionConfigV4 {
+    pub transaction_shuffler_type: TransactionShufflerType,
+    pub block_gas_limit: Option<u64>,
+    pub transaction_deduper_type: TransactionDeduperType,
+    pub block_partitioner_type: BlockPartitionerType,
+}
+
+impl Default for ExecutionConfigV4 {
+    fn default() -> Self {
+        Self {
+            transaction_shuffler_type: TransactionShufflerType::NoShuffling,
+            block_gas_limit: None,
+            transaction_deduper_type: TransactionDeduperType::NoDedup,
+            block_partitioner_type: BlockPartitionerType::NoPartitioning,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")] // cannot use tag = "type" as nested enums cannot work, and bcs doesn't support it
 pub enum TransactionShufflerType {
     NoShuffling,
     SenderAwareV1(u32),
+    ConflictAwareV1(u32),
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -127,6 +161,20 @@ pub enum TransactionDeduperType {
     TxnHashAndAuthenticatorV1,
 }
 
+/// How the blocks are partitioned into sub-blocks for sharded execution. The partitioner reorders
+/// the transactions of the blocks, so it's part of the on-chain config for all the validators to
+/// commit the same order. Blocks aren't partitioned while a block gas limit is set, as it would
+/// apply to each sub-block separately.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")] // cannot use tag = "type" as nested enums cannot work, and bcs doesn't support it
+pub enum BlockPartitionerType {
+    NoPartitioning,
+    ShardedV1 {
+        num_shards: u32,
+        num_partitioning_rounds: u32,
+    },
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -191,6 +239,45 @@ mod test {
             TransactionShufflerType::SenderAwareV1(32)
         ));
         assert!(matches!(result.block_gas_limit(), None));
+
+        // V3 test with conflict aware shuffling
+        let config = OnChainExecutionConfig::V3(ExecutionConfigV3 {
+            transaction_shuffler_type: TransactionShufflerType::ConflictAwareV1(16),
+            block_gas_limit: None,
+            transaction_deduper_type: TransactionDeduperType::NoDedup,
+        });
+
+        let s = bcs::to_bytes(&config).unwrap();
+        let result = bcs::from_bytes::<OnChainExecutionConfig>(&s).unwrap();
+        assert!(matches!(
+            result.transaction_shuffler_type(),
+            TransactionShufflerType::ConflictAwareV1(16)
+        ));
+
+        // V4 test with sharded block partitioning
+        let config = OnChainExecutionConfig::V4(ExecutionConfigV4 {
+            transaction_shuffler_type: TransactionShufflerType::NoShuffling,
+            block_gas_limit: None,
+            transaction_deduper_type: TransactionDeduperType::NoDedup,
+            block_partitioner_type: BlockPartitionerType::ShardedV1 {
+                num_shards: 4,
+                num_partitioning_rounds: 2,
+            },
+        });
+
+        let s = bcs::to_bytes(&config).unwrap();
+        let result = bcs::from_bytes::<OnChainExecutionConfig>(&s).unwrap();
+        assert_eq!(
+            result.block_partitioner_type(),
+            BlockPartitionerType::ShardedV1 {
+                num_shards: 4,
+                num_partitioning_rounds: 2,
+            }
+        );
+        assert_eq!(
+            OnChainExecutionConfig::default().block_partitioner_type(),
+            BlockPartitionerType::NoPartitioning
+        );
     }
 
     #[test]
@@ -6,6 +6,7 @@ use crate::{
     account_config::{AccountResource, CoinStoreResource},
     state_store::{state_key::StateKey, table::TableHandle},
     transaction::{SignedTransaction, Transaction, TransactionPayload},
+    utility_coin::APTOS_COIN_TYPE,
 };
 use aptos_crypto::{hash::CryptoHash, HashValue};
 pub use move_core_types::abi::{
@@ -88,6 +89,28 @@ impl AnalyzedTransaction {
         self.predictable_transaction
     }
 
+    /// Returns true if the read and write hints of the transaction are known, i.e., if it's one
+    /// of the user transactions analyzed by `From<Transaction>`. The other user transactions
+    /// can't be converted, and the other transactions have no hints.
+    pub fn has_known_hints(transaction: &Transaction) -> bool {
+        let func = match transaction {
+            Transaction::UserTransaction(signed_txn) => match signed_txn.payload() {
+                TransactionPayload::EntryFunction(func) => func,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        if *func.module().address() != AccountAddress::ONE {
+            return false;
+        }
+        match (func.module().name().as_str(), func.function().as_str()) {
+            // The hints of the coin transfers assume the utility coin
+            ("coin", "transfer") => func.ty_args() == [APTOS_COIN_TYPE.clone()].as_slice(),
+            ("aptos_account", "transfer") | ("aptos_account", "create_account") => true,
+            _ => false,
+        }
+    }
+
     pub fn sender(&self) -> Option<AccountAddress> {
         match &self.transaction {
             Transaction::UserTransaction(signed_txn) => Some(signed_txn.sender()),
@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    contract_event::ContractEvent,
+    proof::accumulator::InMemoryAccumulator,
+    transaction::{ExecutionStatus, TransactionInfo},
+    write_set::WriteSet,
+};
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher},
+    HashValue,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use serde::{Deserialize, Serialize};
+
+/// The outcome of the execution of a transaction: the hashes of its write set and events, the gas
+/// it used and its status, hashed together into its fingerprint.
+///
+/// Unlike the `TransactionInfo`, it doesn't depend on the state checkpoints or on the transaction
+/// accumulator, so the fingerprints of two replicas, or of a replica and a replay of its
+/// transactions, can be compared one transaction at a time to find the first one they diverged on.
+#[derive(Clone, CryptoHasher, BCSCryptoHash, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct ExecutionFingerprint {
+    write_set_hash: HashValue,
+    event_root_hash: HashValue,
+    gas_used: u64,
+    status: ExecutionStatus,
+}
+
+impl ExecutionFingerprint {
+    /// Creates the fingerprint from the hashes already computed for the `TransactionInfo`.
+    pub fn new(
+        write_set_hash: HashValue,
+        event_root_hash: HashValue,
+        gas_used: u64,
+        status: ExecutionStatus,
+    ) -> Self {
+        Self {
+            write_set_hash,
+            event_root_hash,
+            gas_used,
+            status,
+        }
+    }
+
+    /// The fingerprint of the execution a `TransactionInfo` was computed from.
+    pub fn from_txn_info(txn_info: &TransactionInfo) -> Self {
+        Self::new(
+            txn_info.state_change_hash(),
+            txn_info.event_root_hash(),
+            txn_info.gas_used(),
+            txn_info.status().clone(),
+        )
+    }
+
+    /// Computes the fingerprint of an output, e.g. one produced by replaying the transaction.
+    pub fn from_output(
+        write_set: &WriteSet,
+        events: &[ContractEvent],
+        gas_used: u64,
+        status: ExecutionStatus,
+    ) -> Self {
+        let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+        Self::new(
+            CryptoHash::hash(write_set),
+            InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash(),
+            gas_used,
+            status,
+        )
+    }
+
+    pub fn write_set_hash(&self) -> HashValue {
+        self.write_set_hash
+    }
+
+    pub fn event_root_hash(&self) -> HashValue {
+        self.event_root_hash
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    pub fn status(&self) -> &ExecutionStatus {
+        &self.status
+    }
+
+    /// The hash of all the parts of the fingerprint.
+    pub fn fingerprint(&self) -> HashValue {
+        self.hash()
+    }
+
+    /// The names of the parts which differ from the other fingerprint.
+    pub fn diff(&self, other: &Self) -> Vec<&'static str> {
+        let mut diff = vec![];
+        if self.write_set_hash != other.write_set_hash {
+            diff.push("write_set");
+        }
+        if self.event_root_hash != other.event_root_hash {
+            diff.push("events");
+        }
+        if self.gas_used != other.gas_used {
+            diff.push("gas_used");
+        }
+        if self.status != other.status {
+            diff.push("status");
+        }
+        diff
+    }
+}
@@ -38,12 +38,14 @@ use std::{
 pub mod analyzed_transaction;
 pub mod authenticator;
 mod change_set;
+mod execution_fingerprint;
 mod module;
 mod multisig;
 mod script;
 mod transaction_argument;
 
 pub use change_set::ChangeSet;
+pub use execution_fingerprint::ExecutionFingerprint;
 pub use module::{Module, ModuleBundle};
 use move_core_types::vm_status::AbortLocation;
 pub use multisig::{ExecutionError, Multisig, MultisigTransactionPayload};
@@ -983,6 +985,19 @@ impl TransactionOutput {
         (write_set, events, gas_used, status)
     }
 
+    /// The fingerprint of the execution, if the transaction is kept.
+    pub fn execution_fingerprint(&self) -> Option<ExecutionFingerprint> {
+        match &self.status {
+            TransactionStatus::Keep(status) => Some(ExecutionFingerprint::from_output(
+                &self.write_set,
+                &self.events,
+                self.gas_used,
+                status.clone(),
+            )),
+            _ => None,
+        }
+    }
+
     pub fn ensure_match_transaction_info(
         &self,
         version: Version,